@@ -6,6 +6,7 @@
 
 use std::fmt::Debug;
 
+use openmls_rust_crypto::ed448;
 use openmls_traits::{
     key_store::{MlsEntity, MlsEntityId, OpenMlsKeyStore},
     signatures::Signer,
@@ -54,7 +55,21 @@ impl Signer for SignatureKeyPair {
                 let signature = k.sign(payload);
                 Ok(signature.to_bytes().into())
             }
-            _ => Err(Error::SigningError),
+            SignatureScheme::ECDSA_SECP384R1_SHA384 => {
+                let k = p384::ecdsa::SigningKey::from_slice(&self.private)
+                    .map_err(|_| Error::SigningError)?;
+                let signature: p384::ecdsa::Signature = k.sign(payload);
+                Ok(signature.to_der().to_bytes().into())
+            }
+            SignatureScheme::ECDSA_SECP521R1_SHA512 => {
+                let k = p521::ecdsa::SigningKey::from_slice(&self.private)
+                    .map_err(|_| Error::SigningError)?;
+                let signature: p521::ecdsa::Signature = k.sign(payload);
+                Ok(signature.to_der().to_bytes().into())
+            }
+            SignatureScheme::ED448 => {
+                ed448::sign(&self.private, payload).map_err(|_| Error::SigningError)
+            }
         }
     }
 
@@ -91,7 +106,20 @@ impl SignatureKeyPair {
                 let pk = sk.verifying_key().to_bytes().into();
                 (sk.to_bytes().into(), pk)
             }
-            _ => return Err(CryptoError::UnsupportedSignatureScheme),
+            SignatureScheme::ECDSA_SECP384R1_SHA384 => {
+                let k = p384::ecdsa::SigningKey::random(&mut OsRng);
+                let pk = k.verifying_key().to_encoded_point(false).as_bytes().into();
+                (k.to_bytes().as_slice().into(), pk)
+            }
+            SignatureScheme::ECDSA_SECP521R1_SHA512 => {
+                let k = p521::ecdsa::SigningKey::random(&mut OsRng);
+                let pk = p521::ecdsa::VerifyingKey::from(&k)
+                    .to_encoded_point(false)
+                    .as_bytes()
+                    .into();
+                (k.to_bytes().as_slice().into(), pk)
+            }
+            SignatureScheme::ED448 => ed448::generate_key_pair(&mut OsRng),
         };
 
         Ok(Self {
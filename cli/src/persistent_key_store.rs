@@ -1,4 +1,4 @@
-use openmls_traits::key_store::{MlsEntity, OpenMlsKeyStore};
+use openmls_traits::key_store::{MlsEntity, MlsEntityId, OpenMlsKeyStore};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
@@ -10,14 +10,27 @@ use std::{
 
 use super::file_helpers;
 
+/// Values are stored with the [`MlsEntityId`] of their entity. Values that
+/// were loaded from a key store file without entity IDs have none and are
+/// never listed.
 #[derive(Debug, Default)]
 pub struct PersistentKeyStore {
-    values: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    values: RwLock<HashMap<Vec<u8>, (Option<MlsEntityId>, Vec<u8>)>>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct SerializableKeyStore {
-    values: HashMap<String, String>,
+    values: HashMap<String, SerializableValue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum SerializableValue {
+    /// A value with the entity ID it was stored for.
+    Entity(MlsEntityId, String),
+    /// A value from a key store file that was saved before entity IDs were
+    /// stored.
+    Legacy(String),
 }
 
 impl OpenMlsKeyStore for PersistentKeyStore {
@@ -35,7 +48,7 @@ impl OpenMlsKeyStore for PersistentKeyStore {
         // lock on `credential_bundles`. It only holds the lock very briefly and
         // should not panic during that period.
         let mut values = self.values.write().unwrap();
-        values.insert(k.to_vec(), value);
+        values.insert(k.to_vec(), (Some(V::ID), value));
         Ok(())
     }
 
@@ -48,7 +61,7 @@ impl OpenMlsKeyStore for PersistentKeyStore {
         // `init_key_package_bundles` (this one and `generate_key_package_bundle`) only
         // hold the lock very briefly and should not panic during that period.
         let values = self.values.read().unwrap();
-        if let Some(value) = values.get(k) {
+        if let Some((_, value)) = values.get(k) {
             serde_json::from_slice(value).ok()
         } else {
             None
@@ -64,6 +77,19 @@ impl OpenMlsKeyStore for PersistentKeyStore {
         values.remove(k);
         Ok(())
     }
+
+    /// Return the IDs of all values that are stored for the entity with the
+    /// given [`MlsEntityId`].
+    fn list_keys(&self, entity_id: MlsEntityId) -> Result<Option<Vec<Vec<u8>>>, Self::Error> {
+        let values = self.values.read().unwrap();
+        Ok(Some(
+            values
+                .iter()
+                .filter(|(_, (id, _))| *id == Some(entity_id))
+                .map(|(k, _)| k.clone())
+                .collect(),
+        ))
+    }
 }
 
 impl PersistentKeyStore {
@@ -75,10 +101,12 @@ impl PersistentKeyStore {
         let writer = BufWriter::new(output_file);
 
         let mut ser_ks = SerializableKeyStore::default();
-        for (key, value) in &*self.values.read().unwrap() {
-            ser_ks
-                .values
-                .insert(base64::encode(key), base64::encode(value));
+        for (key, (entity_id, value)) in &*self.values.read().unwrap() {
+            let value = match entity_id {
+                Some(entity_id) => SerializableValue::Entity(*entity_id, base64::encode(value)),
+                None => SerializableValue::Legacy(base64::encode(value)),
+            };
+            ser_ks.values.insert(base64::encode(key), value);
         }
 
         match serde_json::to_writer_pretty(writer, &ser_ks) {
@@ -103,10 +131,17 @@ impl PersistentKeyStore {
         // Read the JSON contents of the file as an instance of `SerializableKeyStore`.
         match serde_json::from_reader::<BufReader<&File>, SerializableKeyStore>(reader) {
             Ok(ser_ks) => {
-                let mut ks_map = self.values.write().unwrap();
+                let mut values = HashMap::with_capacity(ser_ks.values.len());
                 for (key, value) in ser_ks.values {
-                    ks_map.insert(base64::decode(key).unwrap(), base64::decode(value).unwrap());
+                    let (entity_id, value) = match value {
+                        SerializableValue::Entity(entity_id, value) => (Some(entity_id), value),
+                        SerializableValue::Legacy(value) => (None, value),
+                    };
+                    let key = base64::decode(key).map_err(|e| e.to_string())?;
+                    let value = base64::decode(value).map_err(|e| e.to_string())?;
+                    values.insert(key, (entity_id, value));
                 }
+                self.values.write().unwrap().extend(values);
                 Ok(())
             }
             Err(e) => Err(e.to_string()),
@@ -105,12 +105,22 @@ impl User {
                         Ok(_) => {
                             let groups = user.groups.get_mut();
                             for group_name in &user.group_list {
-                                let mlsgroup = MlsGroup::load(
+                                let mlsgroup = match MlsGroup::load(
                                     &GroupId::from_slice(group_name.as_bytes()),
                                     user.crypto.key_store(),
-                                );
+                                ) {
+                                    Ok(Some(mlsgroup)) => mlsgroup,
+                                    Ok(None) => {
+                                        log::error!("Group {group_name} is not in the key store");
+                                        return Err(format!("Unknown group {group_name}"));
+                                    }
+                                    Err(e) => {
+                                        log::error!("Error loading group {group_name}: {e:?}");
+                                        return Err(e.to_string());
+                                    }
+                                };
                                 let grp = Group {
-                                    mls_group: RefCell::new(mlsgroup.unwrap()),
+                                    mls_group: RefCell::new(mlsgroup),
                                     group_name: group_name.clone(),
                                     conversation: Conversation::default(),
                                 };
@@ -383,16 +393,17 @@ impl User {
             };
             let mut mls_group = group.mls_group.borrow_mut();
 
-            processed_message = match mls_group.process_message(&self.crypto, message) {
-                Ok(msg) => msg,
-                Err(e) => {
-                    log::error!(
-                        "Error processing unverified message: {:?} -  Dropping message.",
-                        e
-                    );
-                    return Err("error".to_string());
-                }
-            };
+            processed_message =
+                match mls_group.process_message(&self.crypto, message, &AcceptAllCredentials) {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        log::error!(
+                            "Error processing unverified message: {:?} -  Dropping message.",
+                            e
+                        );
+                        return Err("error".to_string());
+                    }
+                };
 
             let processed_message_credential: Credential = processed_message.credential().clone();
 
@@ -578,6 +589,7 @@ impl User {
                 &self.crypto,
                 &self.identity.borrow().signer,
                 &[joiner_key_package.into()],
+                &AcceptAllCredentials,
             )
             .map_err(|e| format!("Failed to add member to group - {e}"))?;
 
@@ -673,8 +685,14 @@ impl User {
         let group_config = MlsGroupConfig::builder()
             .use_ratchet_tree_extension(true)
             .build();
-        let mut mls_group = MlsGroup::new_from_welcome(&self.crypto, &group_config, welcome, None)
-            .expect("Failed to create MlsGroup");
+        let mut mls_group = MlsGroup::new_from_welcome(
+            &self.crypto,
+            &group_config,
+            welcome,
+            None,
+            &AcceptAllCredentials,
+        )
+        .expect("Failed to create MlsGroup");
 
         let group_id = mls_group.group_id().to_vec();
         // XXX: Use Welcome's encrypted_group_info field to store group_name.
@@ -280,7 +280,12 @@ async fn test_group() {
     // With the key package we can invite Client2 (create proposal and merge it
     // locally.)
     let (_out_messages, welcome_msg, _group_info) = group
-        .add_members(crypto, &signer_1, &[client2_key_package.into()])
+        .add_members(
+            crypto,
+            &signer_1,
+            &[client2_key_package.into()],
+            &AcceptAllCredentials,
+        )
         .expect("Could not add member to group.");
     group
         .merge_pending_commit(crypto)
@@ -322,6 +327,7 @@ async fn test_group() {
             .into_welcome()
             .expect("Unexpected message type."),
         Some(group.export_ratchet_tree().into()), // delivered out of band
+        &AcceptAllCredentials,
     )
     .expect("Error creating group from Welcome");
 
@@ -376,7 +382,7 @@ async fn test_group() {
 
     // Decrypt the message on Client1
     let processed_message = group
-        .process_message(crypto, protocol_message)
+        .process_message(crypto, protocol_message, &AcceptAllCredentials)
         .expect("Could not process unverified message.");
     if let ProcessedMessageContent::ApplicationMessage(application_message) =
         processed_message.into_content()
@@ -0,0 +1,772 @@
+//! # OpenMLS Encrypted Key Store
+//!
+//! An [`OpenMlsKeyStore`] that encrypts every value before it is written to
+//! another [`OpenMlsKeyStore`], e.g. a persistent one.
+//!
+//! Values are serialized with `serde_json` and encrypted with an AEAD under a
+//! [`StorageKey`] provided by the application, using an [`OpenMlsCrypto`]
+//! implementation. The [`MlsEntityId`] and the key of a value, as well as the
+//! id of the storage key, are bound to the ciphertext as additional data, such
+//! that encrypted values can't be swapped in the wrapped key store.
+//!
+//! The storage key is rotated with [`EncryptedKeyStore::rotate_storage_key()`],
+//! which re-encrypts all values under the new storage key. Values are
+//! decrypted with the storage key whose id is stored with them, so the
+//! previous storage keys are kept and can be added with
+//! [`EncryptedKeyStore::add_storage_key()`], e.g. after a rotation was
+//! interrupted.
+
+use std::{
+    fmt::Debug,
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use openmls_traits::{
+    crypto::OpenMlsCrypto,
+    key_store::{with_transaction, MlsEntity, MlsEntityId, OpenMlsKeyStore},
+    random::OpenMlsRand,
+    types::{AeadType, CryptoError},
+};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+/// The label that prefixes the additional data of all encrypted values.
+const AAD_LABEL: &[u8] = b"OpenMLS encrypted key store";
+
+/// All entity ids whose values are re-encrypted when the storage key is
+/// rotated.
+const ENTITY_IDS: [MlsEntityId; 6] = [
+    MlsEntityId::SignatureKeyPair,
+    MlsEntityId::HpkePrivateKey,
+    MlsEntityId::KeyPackage,
+    MlsEntityId::PskBundle,
+    MlsEntityId::EncryptionKeyPair,
+    MlsEntityId::GroupState,
+];
+
+/// Returns the tag of the entity with the given id in the additional data of
+/// its values.
+const fn tag(entity_id: MlsEntityId) -> u8 {
+    match entity_id {
+        MlsEntityId::SignatureKeyPair => 1,
+        MlsEntityId::HpkePrivateKey => 2,
+        MlsEntityId::KeyPackage => 3,
+        MlsEntityId::PskBundle => 4,
+        MlsEntityId::EncryptionKeyPair => 5,
+        MlsEntityId::GroupState => 6,
+    }
+}
+
+/// Returns the id of the entity with the given tag.
+const fn entity_id(tag: u8) -> MlsEntityId {
+    match tag {
+        1 => MlsEntityId::SignatureKeyPair,
+        2 => MlsEntityId::HpkePrivateKey,
+        3 => MlsEntityId::KeyPackage,
+        4 => MlsEntityId::PskBundle,
+        5 => MlsEntityId::EncryptionKeyPair,
+        6 => MlsEntityId::GroupState,
+        _ => panic!("Unknown entity tag"),
+    }
+}
+
+/// Evaluates `$body` with the constant `$tag` set to the tag of `$entity_id`.
+///
+/// The wrapped key store only distinguishes entities by the type of the
+/// values, so the encrypted values of each entity are of a different type.
+macro_rules! with_tag {
+    ($entity_id:expr, $tag:ident => $body:expr) => {
+        with_tag!(
+            @arms $entity_id, $tag, $body,
+            SignatureKeyPair, HpkePrivateKey, KeyPackage, PskBundle, EncryptionKeyPair, GroupState
+        )
+    };
+    (@arms $entity_id:expr, $tag:ident, $body:expr, $($entity:ident),*) => {
+        match $entity_id {
+            $(MlsEntityId::$entity => {
+                const $tag: u8 = tag(MlsEntityId::$entity);
+                $body
+            })*
+        }
+    };
+}
+
+/// The additional data of the value with key `k` of the entity with the given
+/// tag, encrypted under the storage key with id `key_id`.
+fn aad(tag: u8, key_id: u32, k: &[u8]) -> Vec<u8> {
+    [AAD_LABEL, &[tag], &key_id.to_be_bytes(), k].concat()
+}
+
+/// A key with which the values of an [`EncryptedKeyStore`] are encrypted.
+///
+/// The id of the storage key is stored with every value. It must be unique
+/// among the storage keys that are used for a key store. The key is zeroized
+/// when the storage key is dropped.
+pub struct StorageKey {
+    id: u32,
+    key: Vec<u8>,
+}
+
+impl StorageKey {
+    /// Create a storage key with the given id from the bytes of the key.
+    pub fn new(id: u32, key: Vec<u8>) -> Self {
+        Self { id, key }
+    }
+
+    /// Returns the id of this storage key.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+/// The key itself is not printed.
+impl Debug for StorageKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StorageKey").field("id", &self.id).finish()
+    }
+}
+
+impl Drop for StorageKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+/// The storage key that values are encrypted with and the previous storage
+/// keys that values may still be encrypted with.
+#[derive(Debug)]
+struct StorageKeys {
+    current: StorageKey,
+    previous: Vec<StorageKey>,
+}
+
+impl StorageKeys {
+    /// Returns the storage key with the given id.
+    fn get(&self, id: u32) -> Option<&StorageKey> {
+        std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|storage_key| storage_key.id == id)
+    }
+}
+
+/// An encrypted value as it is stored in the wrapped key store, for the entity
+/// with the tag `TAG`.
+#[derive(Serialize, Deserialize)]
+struct EncryptedValue<const TAG: u8> {
+    key_id: u32,
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+impl<const TAG: u8> MlsEntity for EncryptedValue<TAG> {
+    const ID: MlsEntityId = entity_id(TAG);
+}
+
+/// A key store that encrypts all values before writing them to the wrapped
+/// key store.
+#[derive(Debug)]
+pub struct EncryptedKeyStore<KeyStore, Crypto> {
+    key_store: KeyStore,
+    crypto: Crypto,
+    aead_type: AeadType,
+    storage_keys: RwLock<StorageKeys>,
+}
+
+impl<KeyStore, Crypto> EncryptedKeyStore<KeyStore, Crypto>
+where
+    KeyStore: OpenMlsKeyStore,
+    Crypto: OpenMlsCrypto + OpenMlsRand + Send + Sync,
+{
+    /// Create a key store that encrypts the values written to `key_store`
+    /// with the AEAD `aead_type` under the `storage_key`. The `crypto` is used
+    /// to encrypt and decrypt the values and to generate the nonces.
+    ///
+    /// Returns an error if the length of the storage key doesn't match the
+    /// AEAD.
+    pub fn new(
+        key_store: KeyStore,
+        crypto: Crypto,
+        aead_type: AeadType,
+        storage_key: StorageKey,
+    ) -> Result<Self, EncryptedKeyStoreError<KeyStore::Error>> {
+        if storage_key.key.len() != aead_type.key_size() {
+            return Err(EncryptedKeyStoreError::InvalidStorageKeyLength);
+        }
+
+        Ok(Self {
+            key_store,
+            crypto,
+            aead_type,
+            storage_keys: RwLock::new(StorageKeys {
+                current: storage_key,
+                previous: Vec::new(),
+            }),
+        })
+    }
+
+    /// Returns the wrapped key store.
+    pub fn key_store(&self) -> &KeyStore {
+        &self.key_store
+    }
+
+    /// Returns the id of the storage key that the values are encrypted with.
+    pub fn storage_key_id(&self) -> u32 {
+        self.storage_keys().current.id
+    }
+
+    /// Add a previous `storage_key` that values may still be encrypted with,
+    /// e.g. because a rotation of the storage key was interrupted.
+    ///
+    /// Returns an error if the length of the storage key doesn't match the
+    /// AEAD or a storage key with the same id was already added.
+    pub fn add_storage_key(
+        &self,
+        storage_key: StorageKey,
+    ) -> Result<(), EncryptedKeyStoreError<KeyStore::Error>> {
+        if storage_key.key.len() != self.aead_type.key_size() {
+            return Err(EncryptedKeyStoreError::InvalidStorageKeyLength);
+        }
+
+        let mut storage_keys = self.storage_keys_mut();
+        if storage_keys.get(storage_key.id).is_some() {
+            return Err(EncryptedKeyStoreError::DuplicateStorageKeyId);
+        }
+        storage_keys.previous.push(storage_key);
+        Ok(())
+    }
+
+    /// Remove the previous storage key with the given `id`. Values that are
+    /// still encrypted with it can't be read anymore.
+    ///
+    /// Returns an error if the storage key is the one that values are
+    /// encrypted with.
+    pub fn remove_storage_key(
+        &self,
+        id: u32,
+    ) -> Result<(), EncryptedKeyStoreError<KeyStore::Error>> {
+        let mut storage_keys = self.storage_keys_mut();
+        if storage_keys.current.id == id {
+            return Err(EncryptedKeyStoreError::CurrentStorageKey);
+        }
+        storage_keys
+            .previous
+            .retain(|storage_key| storage_key.id != id);
+        Ok(())
+    }
+
+    /// Re-encrypt all values under the `storage_key` and use it for all
+    /// values that are written from then on. The previous storage key is
+    /// kept.
+    ///
+    /// The values are re-encrypted in a transaction of the wrapped key store.
+    /// If re-encrypting a value fails, the transaction is rolled back and the
+    /// previous storage key is used for the values that are written from then
+    /// on. The rotation is only atomic if the wrapped key store discards the
+    /// values that were written in a transaction that is rolled back, like
+    /// the SQLite and the memory key store do. With a key store that doesn't
+    /// support transactions, some values stay encrypted under the
+    /// `storage_key` after a failed rotation. It is kept as a previous storage
+    /// key, so that these values can still be read, and the rotation can be
+    /// resumed by rotating to the same storage key again.
+    ///
+    /// Returns an error if the length of the storage key doesn't match the
+    /// AEAD, its id is the id of the current storage key, the wrapped key
+    /// store doesn't support listing values, a value can't be decrypted with
+    /// any of the storage keys or accessing the wrapped key store fails.
+    pub fn rotate_storage_key(
+        &self,
+        storage_key: StorageKey,
+    ) -> Result<(), EncryptedKeyStoreError<KeyStore::Error>> {
+        if storage_key.key.len() != self.aead_type.key_size() {
+            return Err(EncryptedKeyStoreError::InvalidStorageKeyLength);
+        }
+
+        // No value is written while the values are re-encrypted.
+        let mut storage_keys = self.storage_keys_mut();
+        if storage_keys.current.id == storage_key.id {
+            return Err(EncryptedKeyStoreError::DuplicateStorageKeyId);
+        }
+        // A previous storage key with the same id is the one of an
+        // interrupted rotation, whose values are decrypted with the new one.
+        storage_keys
+            .previous
+            .retain(|previous| previous.id != storage_key.id);
+
+        let result = with_transaction(
+            &self.key_store,
+            || -> Result<(), EncryptedKeyStoreError<KeyStore::Error>> {
+                for entity_id in ENTITY_IDS {
+                    with_tag!(entity_id, TAG => {
+                        self.reencrypt::<TAG>(&storage_keys, &storage_key)
+                    })?;
+                }
+                Ok(())
+            },
+        )
+        .map_err(EncryptedKeyStoreError::KeyStoreError)
+        .and_then(|result| result);
+
+        if result.is_ok() {
+            let previous = std::mem::replace(&mut storage_keys.current, storage_key);
+            storage_keys.previous.push(previous);
+        } else {
+            storage_keys.previous.push(storage_key);
+        }
+        result
+    }
+
+    /// Re-encrypt the values of the entity with tag `TAG` that are encrypted
+    /// under one of the storage keys `from` to the storage key `to`.
+    fn reencrypt<const TAG: u8>(
+        &self,
+        from: &StorageKeys,
+        to: &StorageKey,
+    ) -> Result<(), EncryptedKeyStoreError<KeyStore::Error>> {
+        let keys = self
+            .key_store
+            .list_keys(entity_id(TAG))
+            .map_err(EncryptedKeyStoreError::KeyStoreError)?
+            .ok_or(EncryptedKeyStoreError::ListingUnsupported)?;
+
+        for k in keys {
+            let value = self
+                .key_store
+                .read::<EncryptedValue<TAG>>(&k)
+                .ok_or(EncryptedKeyStoreError::DecryptionError)?;
+            if value.key_id == to.id {
+                continue;
+            }
+            let plaintext = self.decrypt(from, &k, &value)?;
+            let value = self.encrypt::<TAG>(to, &k, &plaintext)?;
+            self.key_store
+                .store(&k, &value)
+                .map_err(EncryptedKeyStoreError::KeyStoreError)?;
+        }
+
+        Ok(())
+    }
+
+    /// Encrypt the `plaintext` of the value with key `k` of the entity with
+    /// tag `TAG` under the `storage_key`.
+    fn encrypt<const TAG: u8>(
+        &self,
+        storage_key: &StorageKey,
+        k: &[u8],
+        plaintext: &[u8],
+    ) -> Result<EncryptedValue<TAG>, EncryptedKeyStoreError<KeyStore::Error>> {
+        let nonce = self
+            .crypto
+            .random_vec(self.aead_type.nonce_size())
+            .map_err(|_| EncryptedKeyStoreError::RandomnessError)?;
+        let ciphertext = self
+            .crypto
+            .aead_encrypt(
+                self.aead_type,
+                &storage_key.key,
+                plaintext,
+                &nonce,
+                &aad(TAG, storage_key.id, k),
+            )
+            .map_err(EncryptedKeyStoreError::CryptoError)?;
+
+        Ok(EncryptedValue {
+            key_id: storage_key.id,
+            nonce,
+            ciphertext,
+        })
+    }
+
+    /// Decrypt the `value` with key `k` of the entity with tag `TAG` with the
+    /// one of the `storage_keys` that it was encrypted under.
+    fn decrypt<const TAG: u8>(
+        &self,
+        storage_keys: &StorageKeys,
+        k: &[u8],
+        value: &EncryptedValue<TAG>,
+    ) -> Result<Vec<u8>, EncryptedKeyStoreError<KeyStore::Error>> {
+        let storage_key = storage_keys
+            .get(value.key_id)
+            .ok_or(EncryptedKeyStoreError::DecryptionError)?;
+
+        self.crypto
+            .aead_decrypt(
+                self.aead_type,
+                &storage_key.key,
+                &value.ciphertext,
+                &value.nonce,
+                &aad(TAG, value.key_id, k),
+            )
+            .map_err(|_| EncryptedKeyStoreError::DecryptionError)
+    }
+
+    fn storage_keys(&self) -> RwLockReadGuard<'_, StorageKeys> {
+        // The current storage key is only replaced after all values were
+        // re-encrypted, and the storage keys that values may be encrypted
+        // with are never dropped by a rotation, so the storage keys are
+        // consistent with the stored values even after a panic.
+        self.storage_keys
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn storage_keys_mut(&self) -> RwLockWriteGuard<'_, StorageKeys> {
+        self.storage_keys
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<KeyStore, Crypto> OpenMlsKeyStore for EncryptedKeyStore<KeyStore, Crypto>
+where
+    KeyStore: OpenMlsKeyStore,
+    Crypto: OpenMlsCrypto + OpenMlsRand + Send + Sync,
+{
+    /// The error type returned by the [`OpenMlsKeyStore`].
+    type Error = EncryptedKeyStoreError<KeyStore::Error>;
+
+    /// Encrypt a value `v` that implements the [`MlsEntity`] trait and store
+    /// it for ID `k` in the wrapped key store.
+    ///
+    /// Returns an error if encrypting or storing fails.
+    fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+        let plaintext =
+            serde_json::to_vec(v).map_err(|_| EncryptedKeyStoreError::SerializationError)?;
+        let storage_keys = self.storage_keys();
+
+        with_tag!(V::ID, TAG => {
+            let value = self.encrypt::<TAG>(&storage_keys.current, k, &plaintext)?;
+            self.key_store
+                .store(k, &value)
+                .map_err(EncryptedKeyStoreError::KeyStoreError)
+        })
+    }
+
+    /// Read and decrypt a value stored for ID `k` that implements the
+    /// [`MlsEntity`] trait for deserialization.
+    ///
+    /// Returns [`None`] if no value is stored for `k` or reading or decrypting
+    /// fails.
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> Option<V> {
+        let storage_keys = self.storage_keys();
+
+        let plaintext = with_tag!(V::ID, TAG => {
+            let value = self.key_store.read::<EncryptedValue<TAG>>(k)?;
+            self.decrypt(&storage_keys, k, &value).ok()?
+        });
+        serde_json::from_slice(&plaintext).ok()
+    }
+
+    /// Delete a value stored for ID `k`.
+    ///
+    /// Returns an error if deleting fails.
+    fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+        with_tag!(V::ID, TAG => self.key_store.delete::<EncryptedValue<TAG>>(k))
+            .map_err(EncryptedKeyStoreError::KeyStoreError)
+    }
+
+    /// Return the IDs of all values that are stored for the entity with the
+    /// given [`MlsEntityId`], or [`None`] if the wrapped key store doesn't
+    /// support listing values.
+    ///
+    /// Returns an error if listing the IDs fails.
+    fn list_keys(&self, entity_id: MlsEntityId) -> Result<Option<Vec<Vec<u8>>>, Self::Error> {
+        self.key_store
+            .list_keys(entity_id)
+            .map_err(EncryptedKeyStoreError::KeyStoreError)
+    }
+
+    /// Begin a transaction of the wrapped key store.
+    fn begin_transaction(&self) -> Result<(), Self::Error> {
+        self.key_store
+            .begin_transaction()
+            .map_err(EncryptedKeyStoreError::KeyStoreError)
+    }
+
+    /// Commit the innermost transaction of the wrapped key store.
+    fn commit_transaction(&self) -> Result<(), Self::Error> {
+        self.key_store
+            .commit_transaction()
+            .map_err(EncryptedKeyStoreError::KeyStoreError)
+    }
+
+    /// Roll back the innermost transaction of the wrapped key store.
+    fn rollback_transaction(&self) -> Result<(), Self::Error> {
+        self.key_store
+            .rollback_transaction()
+            .map_err(EncryptedKeyStoreError::KeyStoreError)
+    }
+}
+
+/// Errors thrown by the key store.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum EncryptedKeyStoreError<KeyStoreError> {
+    #[error("Error serializing value.")]
+    SerializationError,
+    #[error("The length of the storage key doesn't match the AEAD.")]
+    InvalidStorageKeyLength,
+    #[error("A storage key with the same id is already used.")]
+    DuplicateStorageKeyId,
+    #[error("The storage key that values are encrypted with can't be removed.")]
+    CurrentStorageKey,
+    #[error("Error generating a nonce.")]
+    RandomnessError,
+    #[error("Error encrypting value: {0}")]
+    CryptoError(CryptoError),
+    #[error("A stored value can't be decrypted with any of the storage keys.")]
+    DecryptionError,
+    #[error("The wrapped key store doesn't support listing values.")]
+    ListingUnsupported,
+    #[error("Error accessing the wrapped key store: {0}")]
+    KeyStoreError(KeyStoreError),
+}
+
+#[cfg(test)]
+mod test {
+    use openmls_memory_keystore::MemoryKeyStore;
+    use openmls_rust_crypto::RustCrypto;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct KeyPackage(Vec<u8>);
+
+    impl MlsEntity for KeyPackage {
+        const ID: MlsEntityId = MlsEntityId::KeyPackage;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct PskBundle(Vec<u8>);
+
+    impl MlsEntity for PskBundle {
+        const ID: MlsEntityId = MlsEntityId::PskBundle;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct GroupState(String);
+
+    impl MlsEntity for GroupState {
+        const ID: MlsEntityId = MlsEntityId::GroupState;
+    }
+
+    const AEAD_TYPE: AeadType = AeadType::Aes256Gcm;
+
+    fn storage_key(id: u32) -> StorageKey {
+        StorageKey::new(id, vec![id as u8; AEAD_TYPE.key_size()])
+    }
+
+    fn key_store(storage_key: StorageKey) -> EncryptedKeyStore<MemoryKeyStore, RustCrypto> {
+        EncryptedKeyStore::new(
+            MemoryKeyStore::default(),
+            RustCrypto::default(),
+            AEAD_TYPE,
+            storage_key,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn entity_tags() {
+        for entity_id in ENTITY_IDS {
+            assert_eq!(self::entity_id(tag(entity_id)), entity_id);
+        }
+    }
+
+    #[test]
+    fn store_read_delete() {
+        let key_store = key_store(storage_key(1));
+        assert_eq!(key_store.read::<KeyPackage>(b"id"), None);
+
+        key_store.store(b"id", &KeyPackage(vec![1, 2, 3])).unwrap();
+        key_store
+            .store(b"group", &GroupState("group".to_string()))
+            .unwrap();
+        assert_eq!(
+            key_store.read::<KeyPackage>(b"id"),
+            Some(KeyPackage(vec![1, 2, 3]))
+        );
+        assert_eq!(
+            key_store.list::<GroupState>().unwrap().unwrap(),
+            vec![(b"group".to_vec(), GroupState("group".to_string()))]
+        );
+
+        // The wrapped key store only holds the encrypted value.
+        let value = key_store
+            .key_store()
+            .read::<EncryptedValue<{ tag(MlsEntityId::GroupState) }>>(b"group")
+            .unwrap();
+        assert_eq!(value.key_id, 1);
+        assert!(!value
+            .ciphertext
+            .windows(b"group".len())
+            .any(|window| window == b"group"));
+
+        key_store.delete::<KeyPackage>(b"id").unwrap();
+        assert_eq!(key_store.read::<KeyPackage>(b"id"), None);
+
+        // Storage keys of the wrong length are rejected.
+        assert_eq!(
+            EncryptedKeyStore::new(
+                MemoryKeyStore::default(),
+                RustCrypto::default(),
+                AEAD_TYPE,
+                StorageKey::new(1, vec![0; 16]),
+            )
+            .unwrap_err(),
+            EncryptedKeyStoreError::InvalidStorageKeyLength
+        );
+    }
+
+    #[test]
+    fn swapped_values() {
+        let key_store = key_store(storage_key(1));
+        key_store.store(b"a", &KeyPackage(vec![1])).unwrap();
+        assert_eq!(key_store.read(b"a"), Some(KeyPackage(vec![1])));
+
+        // A value that is moved to another key can't be decrypted.
+        let value = key_store
+            .key_store()
+            .read::<EncryptedValue<{ tag(MlsEntityId::KeyPackage) }>>(b"a")
+            .unwrap();
+        key_store.key_store().store(b"b", &value).unwrap();
+        assert_eq!(key_store.read::<KeyPackage>(b"b"), None);
+
+        // A value that is moved to another entity can't be decrypted, even
+        // though it could be deserialized.
+        let value = EncryptedValue::<{ tag(MlsEntityId::PskBundle) }> {
+            key_id: value.key_id,
+            nonce: value.nonce,
+            ciphertext: value.ciphertext,
+        };
+        key_store.key_store().store(b"a", &value).unwrap();
+        assert_eq!(key_store.read::<PskBundle>(b"a"), None);
+    }
+
+    #[test]
+    fn rotation() {
+        let key_store = key_store(storage_key(1));
+        key_store.store(b"a", &KeyPackage(vec![1])).unwrap();
+        key_store
+            .store(b"b", &GroupState("group".to_string()))
+            .unwrap();
+
+        key_store.rotate_storage_key(storage_key(2)).unwrap();
+        assert_eq!(key_store.storage_key_id(), 2);
+        assert_eq!(key_store.read(b"a"), Some(KeyPackage(vec![1])));
+        assert_eq!(key_store.read(b"b"), Some(GroupState("group".to_string())));
+
+        // The values are no longer encrypted under the previous storage key.
+        let value = key_store
+            .key_store()
+            .read::<EncryptedValue<{ tag(MlsEntityId::KeyPackage) }>>(b"a")
+            .unwrap();
+        assert_eq!(value.key_id, 2);
+        key_store.remove_storage_key(1).unwrap();
+        assert_eq!(key_store.read(b"a"), Some(KeyPackage(vec![1])));
+        assert_eq!(
+            key_store.remove_storage_key(2),
+            Err(EncryptedKeyStoreError::CurrentStorageKey)
+        );
+        assert_eq!(
+            key_store.rotate_storage_key(storage_key(2)),
+            Err(EncryptedKeyStoreError::DuplicateStorageKeyId)
+        );
+
+        // A value that can't be decrypted aborts the rotation.
+        key_store
+            .key_store()
+            .store(
+                b"c",
+                &EncryptedValue::<{ tag(MlsEntityId::KeyPackage) }> {
+                    key_id: 2,
+                    nonce: vec![0; AEAD_TYPE.nonce_size()],
+                    ciphertext: vec![0; AEAD_TYPE.tag_size()],
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            key_store.rotate_storage_key(storage_key(3)),
+            Err(EncryptedKeyStoreError::DecryptionError)
+        );
+        assert_eq!(key_store.storage_key_id(), 2);
+        assert_eq!(key_store.read(b"a"), Some(KeyPackage(vec![1])));
+        assert_eq!(key_store.read(b"b"), Some(GroupState("group".to_string())));
+    }
+
+    /// A key store whose transactions do nothing.
+    #[derive(Default)]
+    struct NoTransactions(MemoryKeyStore);
+
+    impl OpenMlsKeyStore for NoTransactions {
+        type Error = <MemoryKeyStore as OpenMlsKeyStore>::Error;
+
+        fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+            self.0.store(k, v)
+        }
+
+        fn read<V: MlsEntity>(&self, k: &[u8]) -> Option<V> {
+            self.0.read(k)
+        }
+
+        fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+            self.0.delete::<V>(k)
+        }
+
+        fn list_keys(&self, entity_id: MlsEntityId) -> Result<Option<Vec<Vec<u8>>>, Self::Error> {
+            self.0.list_keys(entity_id)
+        }
+    }
+
+    #[test]
+    fn rotation_without_transactions() {
+        let key_store = EncryptedKeyStore::new(
+            NoTransactions::default(),
+            RustCrypto::default(),
+            AEAD_TYPE,
+            storage_key(1),
+        )
+        .unwrap();
+        key_store.store(b"a", &KeyPackage(vec![1])).unwrap();
+        key_store
+            .key_store()
+            .store(
+                b"b",
+                &EncryptedValue::<{ tag(MlsEntityId::GroupState) }> {
+                    key_id: 1,
+                    nonce: vec![0; AEAD_TYPE.nonce_size()],
+                    ciphertext: vec![0; AEAD_TYPE.tag_size()],
+                },
+            )
+            .unwrap();
+
+        // The key package was re-encrypted before the rotation failed and
+        // isn't restored, but it can still be decrypted.
+        assert_eq!(
+            key_store.rotate_storage_key(storage_key(2)),
+            Err(EncryptedKeyStoreError::DecryptionError)
+        );
+        assert_eq!(key_store.storage_key_id(), 1);
+        let value = key_store
+            .key_store()
+            .read::<EncryptedValue<{ tag(MlsEntityId::KeyPackage) }>>(b"a")
+            .unwrap();
+        assert_eq!(value.key_id, 2);
+        assert_eq!(key_store.read(b"a"), Some(KeyPackage(vec![1])));
+
+        // Without the storage key, the value can't be decrypted until it is
+        // added again.
+        key_store.remove_storage_key(2).unwrap();
+        assert_eq!(key_store.read::<KeyPackage>(b"a"), None);
+        key_store.add_storage_key(storage_key(2)).unwrap();
+        assert_eq!(key_store.read(b"a"), Some(KeyPackage(vec![1])));
+        assert_eq!(
+            key_store.add_storage_key(storage_key(1)),
+            Err(EncryptedKeyStoreError::DuplicateStorageKeyId)
+        );
+
+        // The rotation is resumed with the same storage key.
+        key_store.delete::<GroupState>(b"b").unwrap();
+        key_store.rotate_storage_key(storage_key(2)).unwrap();
+        assert_eq!(key_store.storage_key_id(), 2);
+        assert_eq!(key_store.read(b"a"), Some(KeyPackage(vec![1])));
+    }
+}
@@ -0,0 +1,27 @@
+//! # OpenMLS External Signer
+//!
+//! [`Signer`]s for signature keys that never leave the device or service that
+//! holds them, e.g. a hardware security module.
+//!
+//! OpenMLS only ever signs through the [`Signer`] trait and never reads
+//! signature keys from the key store, so key packages, leaf nodes, commits,
+//! proposals, messages and group infos can all be signed with a key that
+//! can't be exported. The public key of the [`Signer`] is passed to OpenMLS in
+//! the `CredentialWithKey`.
+//!
+//! * [`Pkcs11Signer`] signs with a private key object on a PKCS#11 token, e.g.
+//!   a smart card, an HSM or SoftHSM. It requires the `pkcs11` feature, which
+//!   is enabled by default.
+//! * [`ChannelSigner`] sends every payload as a [`SigningRequest`] over a
+//!   channel and waits for the signature, such that any signing oracle, e.g.
+//!   a remote signing service, can be used.
+//!
+//! [`Signer`]: openmls_traits::signatures::Signer
+
+mod oracle;
+#[cfg(feature = "pkcs11")]
+mod pkcs11;
+
+pub use oracle::{ChannelSigner, SigningRequest};
+#[cfg(feature = "pkcs11")]
+pub use pkcs11::{Pkcs11Signer, Pkcs11SignerError};
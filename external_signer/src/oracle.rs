@@ -0,0 +1,262 @@
+//! A [`Signer`] that forwards the payloads to a signing oracle over a channel.
+
+use std::sync::{
+    mpsc::{channel, Receiver, Sender},
+    Mutex, PoisonError,
+};
+
+use openmls_traits::{
+    signatures::Signer,
+    types::{Error, SignatureScheme},
+};
+
+/// A payload that the signing oracle is asked to sign.
+///
+/// The oracle responds with [`SigningRequest::respond()`]. A request that is
+/// dropped without a response fails the signing operation.
+#[derive(Debug)]
+pub struct SigningRequest {
+    payload: Vec<u8>,
+    response: Sender<Result<Vec<u8>, Error>>,
+}
+
+impl SigningRequest {
+    /// Returns the payload to sign.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Respond with the `signature` of the payload or an error.
+    pub fn respond(self, signature: Result<Vec<u8>, Error>) {
+        // The signer is no longer waiting if the operation was aborted.
+        let _ = self.response.send(signature);
+    }
+
+    /// Sign the payload with `signer` and respond with the signature.
+    pub fn respond_with(self, signer: &impl Signer) {
+        let signature = signer.sign(&self.payload);
+        self.respond(signature)
+    }
+}
+
+/// A [`Signer`] that sends every payload as a [`SigningRequest`] over a
+/// channel and blocks until the signing oracle at the other end responds.
+///
+/// The oracle, e.g. a thread that talks to a remote signing service, receives
+/// the requests from the [`Receiver`] returned by [`ChannelSigner::new()`].
+#[derive(Debug)]
+pub struct ChannelSigner {
+    requests: Mutex<Sender<SigningRequest>>,
+    signature_scheme: SignatureScheme,
+}
+
+impl ChannelSigner {
+    /// Create a signer for signatures with the `signature_scheme` and the
+    /// receiver of its signing requests.
+    pub fn new(signature_scheme: SignatureScheme) -> (Self, Receiver<SigningRequest>) {
+        let (requests, receiver) = channel();
+        let signer = Self {
+            requests: Mutex::new(requests),
+            signature_scheme,
+        };
+        (signer, receiver)
+    }
+}
+
+impl Signer for ChannelSigner {
+    /// Send the `payload` to the signing oracle and wait for the signature.
+    ///
+    /// Returns [`Error::SigningError`] if the receiver of the requests was
+    /// dropped or the request is dropped without a response.
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        let (response, signature) = channel();
+        let request = SigningRequest {
+            payload: payload.to_vec(),
+            response,
+        };
+        self.requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .send(request)
+            .map_err(|_| Error::SigningError)?;
+
+        signature.recv().map_err(|_| Error::SigningError)?
+    }
+
+    fn signature_scheme(&self) -> SignatureScheme {
+        self.signature_scheme
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use openmls::prelude::*;
+    use openmls_basic_credential::SignatureKeyPair;
+    use openmls_rust_crypto::OpenMlsRustCrypto;
+
+    use super::*;
+
+    const CIPHERSUITE: Ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+
+    /// Spawn a signing oracle that holds a fresh signature key pair and
+    /// returns the signer for it and its credential.
+    fn oracle(identity: &str) -> (ChannelSigner, CredentialWithKey) {
+        let (signer, requests) = ChannelSigner::new(CIPHERSUITE.signature_algorithm());
+        let key_pair = SignatureKeyPair::new(CIPHERSUITE.signature_algorithm()).unwrap();
+        let credential_with_key = CredentialWithKey {
+            credential: Credential::new(identity.into(), CredentialType::Basic).unwrap(),
+            signature_key: key_pair.to_public_vec().into(),
+        };
+
+        // The key pair only exists in the oracle thread, which ends once the
+        // signer is dropped.
+        thread::spawn(move || {
+            for request in requests {
+                request.respond_with(&key_pair);
+            }
+        });
+
+        (signer, credential_with_key)
+    }
+
+    #[test]
+    fn unavailable_oracle() {
+        let (signer, requests) = ChannelSigner::new(SignatureScheme::ED25519);
+        let responder = thread::spawn(move || {
+            // A request that is dropped fails.
+            drop(requests.recv().unwrap());
+        });
+        assert_eq!(signer.sign(b"payload"), Err(Error::SigningError));
+        responder.join().unwrap();
+
+        // The receiver of the requests is dropped.
+        assert_eq!(signer.sign(b"payload"), Err(Error::SigningError));
+    }
+
+    #[test]
+    fn group_operations() {
+        let provider = OpenMlsRustCrypto::default();
+        let mls_group_config = MlsGroupConfig::builder()
+            .use_ratchet_tree_extension(true)
+            .crypto_config(CryptoConfig::with_default_version(CIPHERSUITE))
+            .build();
+
+        let (alice_signer, alice_credential_with_key) = oracle("Alice");
+        let (bob_signer, bob_credential_with_key) = oracle("Bob");
+        let (charlie_signer, charlie_credential_with_key) = oracle("Charlie");
+
+        // Signers can also be chosen at runtime.
+        let bob_signer: Box<dyn Signer> = Box::new(bob_signer);
+
+        // === Alice creates a group and adds Bob ===
+        let mut alice_group = MlsGroup::new(
+            &provider,
+            &alice_signer,
+            &mls_group_config,
+            alice_credential_with_key,
+        )
+        .unwrap();
+
+        let bob_key_package = KeyPackage::builder()
+            .build(
+                CryptoConfig::with_default_version(CIPHERSUITE),
+                &provider,
+                &bob_signer,
+                bob_credential_with_key,
+            )
+            .unwrap();
+        let (_, welcome, _) = alice_group
+            .add_members(
+                &provider,
+                &alice_signer,
+                &[bob_key_package],
+                &AcceptAllCredentials,
+            )
+            .unwrap();
+        alice_group.merge_pending_commit(&provider).unwrap();
+
+        let mut bob_group = MlsGroup::new_from_welcome(
+            &provider,
+            &mls_group_config,
+            welcome.into_welcome().unwrap(),
+            None,
+            &AcceptAllCredentials,
+        )
+        .unwrap();
+
+        // === Bob updates his leaf and sends a message ===
+        let (commit, _, _) = bob_group.self_update(&provider, &bob_signer).unwrap();
+        bob_group.merge_pending_commit(&provider).unwrap();
+        let processed_message = alice_group
+            .process_message(
+                &provider,
+                commit.into_protocol_message().unwrap(),
+                &AcceptAllCredentials,
+            )
+            .unwrap();
+        let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+            processed_message.into_content()
+        else {
+            panic!("Expected a StagedCommit.");
+        };
+        alice_group
+            .merge_staged_commit(&provider, *staged_commit)
+            .unwrap();
+
+        let message = bob_group
+            .create_message(&provider, &bob_signer, b"Hello Alice")
+            .unwrap();
+        let processed_message = alice_group
+            .process_message(
+                &provider,
+                message.into_protocol_message().unwrap(),
+                &AcceptAllCredentials,
+            )
+            .unwrap();
+        assert!(matches!(
+            processed_message.into_content(),
+            ProcessedMessageContent::ApplicationMessage(_)
+        ));
+
+        // === Charlie joins with an external commit ===
+        let group_info = alice_group
+            .export_group_info(provider.crypto(), &alice_signer, true)
+            .unwrap();
+        let (mut charlie_group, commit, _) = MlsGroup::join_by_external_commit(
+            &provider,
+            &charlie_signer,
+            None,
+            group_info.into_verifiable_group_info().unwrap(),
+            &mls_group_config,
+            &[],
+            charlie_credential_with_key,
+            &AcceptAllCredentials,
+        )
+        .unwrap();
+        charlie_group.merge_pending_commit(&provider).unwrap();
+
+        let processed_message = alice_group
+            .process_message(
+                &provider,
+                commit.into_protocol_message().unwrap(),
+                &AcceptAllCredentials,
+            )
+            .unwrap();
+        let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+            processed_message.into_content()
+        else {
+            panic!("Expected a StagedCommit.");
+        };
+        alice_group
+            .merge_staged_commit(&provider, *staged_commit)
+            .unwrap();
+
+        assert_eq!(alice_group.members().count(), 3);
+        assert_eq!(
+            alice_group.epoch_authenticator().as_slice(),
+            charlie_group.epoch_authenticator().as_slice()
+        );
+    }
+}
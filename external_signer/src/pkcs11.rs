@@ -0,0 +1,315 @@
+//! A [`Signer`] for private keys on PKCS#11 tokens.
+
+use std::{
+    fmt::Debug,
+    sync::{Mutex, PoisonError},
+};
+
+use cryptoki::{
+    mechanism::Mechanism,
+    object::{Attribute, AttributeType, ObjectClass, ObjectHandle},
+    session::Session,
+};
+use openmls_traits::{
+    signatures::Signer,
+    types::{Error, SignatureScheme},
+};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+/// A [`Signer`] that signs with a private key object on a PKCS#11 token.
+///
+/// The private key never leaves the token. The signer finds the private key
+/// and the public key objects with the same label in a session that the
+/// application opened and logged into, e.g.
+///
+/// ```no_run
+/// use cryptoki::{
+///     context::{CInitializeArgs, Pkcs11},
+///     session::UserType,
+///     types::AuthPin,
+/// };
+/// use openmls_external_signer::Pkcs11Signer;
+/// use openmls_traits::types::SignatureScheme;
+///
+/// let pkcs11 = Pkcs11::new("/usr/lib/softhsm/libsofthsm2.so").unwrap();
+/// pkcs11.initialize(CInitializeArgs::OsThreads).unwrap();
+/// let slot = pkcs11.get_slots_with_token().unwrap()[0];
+/// let session = pkcs11.open_rw_session(slot).unwrap();
+/// session
+///     .login(UserType::User, Some(&AuthPin::new("1234".into())))
+///     .unwrap();
+///
+/// let signer = Pkcs11Signer::new(session, "alice", SignatureScheme::ED25519).unwrap();
+/// ```
+///
+/// ECDSA payloads are hashed before they are sent to the token, which signs
+/// them with `CKM_ECDSA`. EdDSA payloads are signed with `CKM_EDDSA`.
+pub struct Pkcs11Signer {
+    session: Mutex<Session>,
+    private_key: ObjectHandle,
+    public: Vec<u8>,
+    signature_scheme: SignatureScheme,
+}
+
+impl Pkcs11Signer {
+    /// Create a signer for the key pair with the given `label` on the token of
+    /// the `session`, which signs with the `signature_scheme`.
+    ///
+    /// Returns an error if the signature scheme is not supported or the key
+    /// pair can't be found.
+    pub fn new(
+        session: Session,
+        label: &str,
+        signature_scheme: SignatureScheme,
+    ) -> Result<Self, Pkcs11SignerError> {
+        match signature_scheme {
+            SignatureScheme::ECDSA_SECP256R1_SHA256
+            | SignatureScheme::ECDSA_SECP384R1_SHA384
+            | SignatureScheme::ECDSA_SECP521R1_SHA512
+            | SignatureScheme::ED25519
+            | SignatureScheme::ED448 => (),
+            _ => return Err(Pkcs11SignerError::UnsupportedSignatureScheme),
+        }
+
+        let private_key = find_object(&session, ObjectClass::PRIVATE_KEY, label)?;
+        let public_key = find_object(&session, ObjectClass::PUBLIC_KEY, label)?;
+        let public = match session
+            .get_attributes(public_key, &[AttributeType::EcPoint])?
+            .pop()
+        {
+            Some(Attribute::EcPoint(ec_point)) => decode_ec_point(ec_point)?,
+            _ => return Err(Pkcs11SignerError::InvalidPublicKey),
+        };
+
+        Ok(Self {
+            session: Mutex::new(session),
+            private_key,
+            public,
+            signature_scheme,
+        })
+    }
+
+    /// Returns the public key of this signer, i.e. the uncompressed point of
+    /// ECDSA keys and the encoded point of EdDSA keys.
+    ///
+    /// This is the signature key of the `CredentialWithKey` that is used with
+    /// this signer.
+    pub fn public(&self) -> &[u8] {
+        &self.public
+    }
+}
+
+/// The session is not printed.
+impl Debug for Pkcs11Signer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pkcs11Signer")
+            .field("public", &self.public)
+            .field("signature_scheme", &self.signature_scheme)
+            .finish()
+    }
+}
+
+impl Signer for Pkcs11Signer {
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        let (mechanism, data) = match self.signature_scheme {
+            SignatureScheme::ECDSA_SECP256R1_SHA256 => {
+                (Mechanism::Ecdsa, Sha256::digest(payload).to_vec())
+            }
+            SignatureScheme::ECDSA_SECP384R1_SHA384 => {
+                (Mechanism::Ecdsa, Sha384::digest(payload).to_vec())
+            }
+            SignatureScheme::ECDSA_SECP521R1_SHA512 => {
+                (Mechanism::Ecdsa, Sha512::digest(payload).to_vec())
+            }
+            SignatureScheme::ED25519 | SignatureScheme::ED448 => {
+                (Mechanism::Eddsa, payload.to_vec())
+            }
+            _ => return Err(Error::SigningError),
+        };
+
+        let signature = self
+            .session
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .sign(&mechanism, self.private_key, &data)
+            .map_err(|_| Error::SigningError)?;
+
+        match mechanism {
+            // The token returns `r || s`, while MLS uses DER encoded ECDSA
+            // signatures.
+            Mechanism::Ecdsa => encode_ecdsa_signature(&signature),
+            _ => Ok(signature),
+        }
+    }
+
+    fn signature_scheme(&self) -> SignatureScheme {
+        self.signature_scheme
+    }
+}
+
+/// Find the only object of the `class` with the `label`.
+fn find_object(
+    session: &Session,
+    class: ObjectClass,
+    label: &str,
+) -> Result<ObjectHandle, Pkcs11SignerError> {
+    let objects = session.find_objects(&[
+        Attribute::Class(class),
+        Attribute::Label(label.as_bytes().to_vec()),
+    ])?;
+    match objects.as_slice() {
+        [object] => Ok(*object),
+        [] => Err(Pkcs11SignerError::KeyNotFound),
+        _ => Err(Pkcs11SignerError::AmbiguousLabel),
+    }
+}
+
+/// Decode the `CKA_EC_POINT` of a public key, which is a DER encoded octet
+/// string.
+fn decode_ec_point(ec_point: Vec<u8>) -> Result<Vec<u8>, Pkcs11SignerError> {
+    let (tag, rest) = ec_point
+        .split_first()
+        .ok_or(Pkcs11SignerError::InvalidPublicKey)?;
+    if *tag != 0x04 {
+        return Err(Pkcs11SignerError::InvalidPublicKey);
+    }
+    let (length, point) = match rest {
+        [length @ 0..=0x7F, point @ ..] => (*length as usize, point),
+        [0x81, length, point @ ..] => (*length as usize, point),
+        _ => return Err(Pkcs11SignerError::InvalidPublicKey),
+    };
+    if length != point.len() {
+        return Err(Pkcs11SignerError::InvalidPublicKey);
+    }
+    Ok(point.to_vec())
+}
+
+/// DER encode the ECDSA signature `r || s` as specified in RFC 3279.
+fn encode_ecdsa_signature(signature: &[u8]) -> Result<Vec<u8>, Error> {
+    if signature.is_empty() || !signature.len().is_multiple_of(2) {
+        return Err(Error::SigningError);
+    }
+    let (r, s) = signature.split_at(signature.len() / 2);
+
+    let mut integers = Vec::with_capacity(signature.len() + 6);
+    for integer in [r, s] {
+        // Integers are minimally encoded and positive.
+        let first = integer
+            .iter()
+            .position(|byte| *byte != 0)
+            .unwrap_or(integer.len() - 1);
+        let integer = &integer[first..];
+        let padding = integer[0] >= 0x80;
+        integers.push(0x02);
+        push_der_length(&mut integers, integer.len() + usize::from(padding));
+        if padding {
+            integers.push(0x00);
+        }
+        integers.extend_from_slice(integer);
+    }
+
+    let mut der = vec![0x30];
+    push_der_length(&mut der, integers.len());
+    der.extend_from_slice(&integers);
+    Ok(der)
+}
+
+/// Append the DER encoding of `length`, which is less than 256.
+fn push_der_length(der: &mut Vec<u8>, length: usize) {
+    if length >= 0x80 {
+        der.push(0x81);
+    }
+    der.push(length as u8);
+}
+
+/// Errors thrown when creating a [`Pkcs11Signer`].
+#[derive(thiserror::Error, Debug)]
+pub enum Pkcs11SignerError {
+    #[error("The signature scheme is not supported.")]
+    UnsupportedSignatureScheme,
+    #[error("No key pair with the label was found.")]
+    KeyNotFound,
+    #[error("More than one key pair with the label was found.")]
+    AmbiguousLabel,
+    #[error("The public key is not a valid EC point.")]
+    InvalidPublicKey,
+    #[error("Error accessing the token: {0}")]
+    Pkcs11Error(#[from] cryptoki::error::Error),
+}
+
+#[cfg(test)]
+mod test {
+    use cryptoki::{
+        context::{CInitializeArgs, Pkcs11},
+        session::UserType,
+        types::AuthPin,
+    };
+    use openmls_rust_crypto::RustCrypto;
+    use openmls_traits::crypto::OpenMlsCrypto;
+
+    use super::*;
+
+    #[test]
+    fn ecdsa_signature_encoding() {
+        // Leading zeros are removed and integers with the high bit set are
+        // padded.
+        let mut signature = vec![0u8; 64];
+        signature[1] = 0x01;
+        signature[32] = 0x80;
+        let der = encode_ecdsa_signature(&signature).unwrap();
+        assert_eq!(&der[..4], &[0x30, 0x44, 0x02, 0x1F]);
+        assert_eq!(&der[35..38], &[0x02, 0x21, 0x00]);
+        assert_eq!(der.len(), 0x46);
+
+        // P-521 signatures need the long form of the length.
+        let der = encode_ecdsa_signature(&[0xFFu8; 132]).unwrap();
+        assert_eq!(&der[..3], &[0x30, 0x81, 0x8A]);
+
+        assert_eq!(encode_ecdsa_signature(&[1, 2, 3]), Err(Error::SigningError));
+    }
+
+    #[test]
+    fn ec_point_decoding() {
+        let point = vec![0x04; 65];
+        let ec_point = [[0x04, 0x41].as_slice(), &point].concat();
+        assert_eq!(decode_ec_point(ec_point).unwrap(), point);
+
+        let point = vec![0x04; 133];
+        let ec_point = [[0x04, 0x81, 0x85].as_slice(), &point].concat();
+        assert_eq!(decode_ec_point(ec_point).unwrap(), point);
+
+        assert!(matches!(
+            decode_ec_point(vec![0x04, 0x02, 0x00]),
+            Err(Pkcs11SignerError::InvalidPublicKey)
+        ));
+    }
+
+    /// Sign with the key pairs on a SoftHSM token, see the README for how to
+    /// set up the token.
+    #[test]
+    #[ignore = "requires a SoftHSM token"]
+    fn softhsm() {
+        let module = std::env::var("PKCS11_SOFTHSM2_MODULE")
+            .unwrap_or_else(|_| "/usr/lib/softhsm/libsofthsm2.so".to_string());
+        let pkcs11 = Pkcs11::new(module).unwrap();
+        pkcs11.initialize(CInitializeArgs::OsThreads).unwrap();
+        let slot = pkcs11.get_slots_with_token().unwrap()[0];
+
+        for (label, signature_scheme) in [
+            ("openmls-p256", SignatureScheme::ECDSA_SECP256R1_SHA256),
+            ("openmls-p384", SignatureScheme::ECDSA_SECP384R1_SHA384),
+            ("openmls-ed25519", SignatureScheme::ED25519),
+        ] {
+            let session = pkcs11.open_rw_session(slot).unwrap();
+            session
+                .login(UserType::User, Some(&AuthPin::new("1234".into())))
+                .unwrap();
+            let signer = Pkcs11Signer::new(session, label, signature_scheme).unwrap();
+
+            let signature = signer.sign(b"payload").unwrap();
+            RustCrypto::default()
+                .verify_signature(signature_scheme, b"payload", signer.public(), &signature)
+                .unwrap();
+        }
+    }
+}
@@ -14,13 +14,15 @@ use mls_client::{
 use mls_interop_proto::mls_client;
 use openmls::{
     ciphersuite::HpkePrivateKey,
-    credentials::{Credential, CredentialType, CredentialWithKey},
+    credentials::{AcceptAllCredentials, Credential, CredentialType, CredentialWithKey},
+    extensions::{Extension, Extensions},
     framing::{MlsMessageIn, MlsMessageInBody, MlsMessageOut, ProcessedMessageContent},
     group::{
         GroupEpoch, GroupId, MlsGroup, MlsGroupConfig, WireFormatPolicy,
         PURE_CIPHERTEXT_WIRE_FORMAT_POLICY, PURE_PLAINTEXT_WIRE_FORMAT_POLICY,
     },
     key_packages::KeyPackage,
+    messages::proposals::ReInitProposal,
     prelude::{config::CryptoConfig, Capabilities, ExtensionType, SenderRatchetConfiguration},
     schedule::{psk::ResumptionPskUsage, ExternalPsk, PreSharedKeyId, Psk},
     treesync::{
@@ -37,7 +39,7 @@ use openmls_traits::{
     types::{Ciphersuite, HpkeKeyPair},
     OpenMlsProvider,
 };
-use tls_codec::{Deserialize, Serialize};
+use tls_codec::{Deserialize, Serialize, VLBytes};
 use tonic::{async_trait, transport::Server, Code, Request, Response, Status};
 use tracing::{debug, error, info, instrument, trace, Span};
 use tracing_subscriber::EnvFilter;
@@ -72,14 +74,24 @@ type PendingState = (
     OpenMlsRustCrypto,
 );
 
+/// The state of a member of a reinitialized group until it creates or joins
+/// the successor group. The signature keys belong to the key package for the
+/// successor group.
+pub struct PendingReInit {
+    state_id: u32,
+    signature_keys: SignatureKeyPair,
+}
+
 /// This is the main state struct of the interop client. It keeps track of the
 /// individual MLS clients, as well as pending key packages that it was told to
 /// create. It also contains a transaction id map, that maps the `u32`
-/// transaction ids to key package hashes.
+/// transaction ids to key package hashes, and the pending reinitializations,
+/// indexed by their `u32` reinit ids.
 pub struct MlsClientImpl {
     groups: Mutex<Vec<InteropGroup>>,
     pending_state: Mutex<HashMap<Vec<u8>, PendingState>>,
     transaction_id_map: Mutex<HashMap<u32, Vec<u8>>>, // Indirection, linking to pending key packages
+    reinits: Mutex<Vec<Option<PendingReInit>>>,
 }
 
 impl MlsClientImpl {
@@ -89,8 +101,131 @@ impl MlsClientImpl {
             groups: Mutex::new(Vec::new()),
             pending_state: Mutex::new(HashMap::new()),
             transaction_id_map: Mutex::new(HashMap::new()),
+            reinits: Mutex::new(Vec::new()),
         }
     }
+
+    /// Removes the pending state of the key package created for `identity`
+    /// and stores its keys, so OpenMLS can find them when joining a group.
+    fn take_pending_state(
+        &self,
+        identity: &[u8],
+    ) -> Result<(SignatureKeyPair, OpenMlsRustCrypto), Status> {
+        let mut pending_key_packages = self.pending_state.lock().unwrap();
+        let (
+            my_key_package,
+            private_key,
+            encryption_keypair,
+            _my_credential,
+            my_signature_keys,
+            crypto_provider,
+        ) = pending_key_packages
+            .remove(identity)
+            .ok_or(Status::aborted(format!(
+                "failed to find key package for identity {:x?}",
+                identity
+            )))?;
+
+        // Store keys so OpenMLS can find them.
+        crypto_provider
+            .key_store()
+            .store(my_key_package.hpke_init_key().as_slice(), &private_key)
+            .map_err(|_| Status::aborted("failed to interact with the key store"))?;
+
+        // Store the key package in the key store with the hash reference as id
+        // for retrieval when parsing welcome messages.
+        crypto_provider
+            .key_store()
+            .store(
+                my_key_package
+                    .hash_ref(crypto_provider.crypto())
+                    .map_err(into_status)?
+                    .as_slice(),
+                &my_key_package,
+            )
+            .map_err(into_status)?;
+
+        // Store the encryption key pair in the key store.
+        write_keys_from_key_store(&crypto_provider, encryption_keypair);
+
+        // Store the private part of the init_key into the key store.
+        // The key is the public key.
+        crypto_provider
+            .key_store()
+            .store::<HpkePrivateKey>(my_key_package.hpke_init_key().as_slice(), &private_key)
+            .map_err(into_status)?;
+
+        Ok((my_signature_keys, crypto_provider))
+    }
+
+    /// Creates the key package for the successor of the reinitialized group
+    /// with the given `state_id` and remembers it as a pending
+    /// reinitialization.
+    fn pending_reinit(
+        &self,
+        state_id: u32,
+        epoch_authenticator: Vec<u8>,
+    ) -> Result<HandleReInitCommitResponse, Status> {
+        let (key_package, signature_keys) = {
+            let groups = self.groups.lock().unwrap();
+            let interop_group = groups
+                .get(state_id as usize)
+                .ok_or_else(|| Status::new(Code::InvalidArgument, "unknown state_id"))?;
+            let reinit_proposal =
+                interop_group
+                    .group
+                    .reinit_proposal()
+                    .ok_or(Status::failed_precondition(
+                        "the group was not reinitialized",
+                    ))?;
+
+            let credential = Credential::new(
+                interop_group.group.own_identity().unwrap().to_vec(),
+                CredentialType::Basic,
+            )
+            .unwrap();
+            let signature_keys =
+                SignatureKeyPair::new(reinit_proposal.ciphersuite().signature_algorithm())
+                    .map_err(|_| Status::internal("failed to create signature keys"))?;
+            let key_package = new_key_package(
+                &interop_group.crypto_provider,
+                CryptoConfig {
+                    ciphersuite: reinit_proposal.ciphersuite(),
+                    version: reinit_proposal.version(),
+                },
+                &signature_keys,
+                credential,
+            )?;
+
+            (key_package, signature_keys)
+        };
+
+        let mut reinits = self.reinits.lock().unwrap();
+        let reinit_id = reinits.len() as u32;
+        reinits.push(Some(PendingReInit {
+            state_id,
+            signature_keys,
+        }));
+
+        let key_package: MlsMessageOut = key_package.into();
+        Ok(HandleReInitCommitResponse {
+            reinit_id,
+            key_package: key_package
+                .tls_serialize_detached()
+                .map_err(|_| Status::internal("failed to serialize key package"))?,
+            epoch_authenticator,
+        })
+    }
+
+    /// Removes the pending reinitialization with the given `reinit_id`.
+    fn take_pending_reinit(&self, reinit_id: u32) -> Result<PendingReInit, Status> {
+        self.reinits
+            .lock()
+            .unwrap()
+            .get_mut(reinit_id as usize)
+            .and_then(Option::take)
+            .ok_or_else(|| Status::new(Code::InvalidArgument, "unknown reinit_id"))
+    }
 }
 
 fn into_status<E: Display>(e: E) -> Status {
@@ -123,6 +258,71 @@ fn to_ciphersuite(cs: u32) -> Result<&'static Ciphersuite, Status> {
     }
 }
 
+/// Creates a key package with the capabilities of this client.
+fn new_key_package(
+    crypto_provider: &OpenMlsRustCrypto,
+    crypto_config: CryptoConfig,
+    signature_keys: &SignatureKeyPair,
+    credential: Credential,
+) -> Result<KeyPackage, Status> {
+    KeyPackage::builder()
+        .leaf_node_capabilities(Capabilities::new(
+            Some(&[ProtocolVersion::Mls10, ProtocolVersion::Mls10Draft11]),
+            Some(&[
+                Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
+                Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256,
+                Ciphersuite::MLS_128_DHKEMX25519_CHACHA20POLY1305_SHA256_Ed25519,
+            ]),
+            Some(&EXTENSION_TYPES),
+            None,
+            Some(&CREDENTIAL_TYPES),
+        ))
+        .build(
+            crypto_config,
+            crypto_provider,
+            signature_keys,
+            CredentialWithKey {
+                credential,
+                signature_key: signature_keys.public().into(),
+            },
+        )
+        .map_err(into_status)
+}
+
+/// Converts the extensions of a request into OpenMLS [`Extensions`].
+fn to_extensions(extensions: &[mls_client::Extension]) -> Result<Extensions, Status> {
+    let extensions = extensions
+        .iter()
+        .map(|extension| {
+            let extension_type = u16::try_from(extension.extension_type)
+                .map_err(|_| Status::invalid_argument("invalid extension type"))?;
+            let mut bytes = extension_type.tls_serialize_detached().unwrap();
+            bytes.extend(
+                VLBytes::new(extension.extension_data.clone())
+                    .tls_serialize_detached()
+                    .map_err(|_| Status::invalid_argument("extension data is too long"))?,
+            );
+            Extension::tls_deserialize_exact(bytes.as_slice())
+                .map_err(|_| Status::invalid_argument("invalid extension"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Extensions::from_vec(extensions).map_err(into_status)
+}
+
+/// Deserializes the key packages of a request.
+fn to_key_packages(key_packages: &[Vec<u8>]) -> Result<Vec<KeyPackage>, Status> {
+    key_packages
+        .iter()
+        .map(|key_package| {
+            MlsMessageIn::tls_deserialize_exact(key_package.as_slice())
+                .map_err(|_| Status::invalid_argument("failed to deserialize key package"))?
+                .into_keypackage()
+                .ok_or(Status::invalid_argument("message was not a key package"))
+        })
+        .collect()
+}
+
 fn _into_bytes(obj: impl serde::Serialize) -> Vec<u8> {
     serde_json::to_string_pretty(&obj)
         .expect("Error serializing test vectors")
@@ -291,31 +491,15 @@ impl MlsClient for MlsClientImpl {
         let credential = Credential::new(identity, CredentialType::Basic).unwrap();
         let signature_keys = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
 
-        let key_package = KeyPackage::builder()
-            .leaf_node_capabilities(Capabilities::new(
-                Some(&[ProtocolVersion::Mls10, ProtocolVersion::Mls10Draft11]),
-                Some(&[
-                    Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
-                    Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256,
-                    Ciphersuite::MLS_128_DHKEMX25519_CHACHA20POLY1305_SHA256_Ed25519,
-                ]),
-                Some(&EXTENSION_TYPES),
-                None,
-                Some(&CREDENTIAL_TYPES),
-            ))
-            .build(
-                CryptoConfig {
-                    ciphersuite,
-                    version: ProtocolVersion::default(),
-                },
-                &crypto_provider,
-                &signature_keys,
-                CredentialWithKey {
-                    credential: credential.clone(),
-                    signature_key: signature_keys.public().into(),
-                },
-            )
-            .unwrap();
+        let key_package = new_key_package(
+            &crypto_provider,
+            CryptoConfig {
+                ciphersuite,
+                version: ProtocolVersion::default(),
+            },
+            &signature_keys,
+            credential.clone(),
+        )?;
         let private_key = crypto_provider
             .key_store()
             .read::<HpkePrivateKey>(key_package.hpke_init_key().as_slice())
@@ -383,49 +567,7 @@ impl MlsClient for MlsClientImpl {
             .wire_format_policy(wire_format_policy)
             .build();
 
-        let mut pending_key_packages = self.pending_state.lock().unwrap();
-        let (
-            my_key_package,
-            private_key,
-            encryption_keypair,
-            _my_credential,
-            my_signature_keys,
-            crypto_provider,
-        ) = pending_key_packages
-            .remove(&request.identity)
-            .ok_or(Status::aborted(format!(
-                "failed to find key package for identity {:x?}",
-                request.identity
-            )))?;
-
-        // Store keys so OpenMLS can find them.
-        crypto_provider
-            .key_store()
-            .store(my_key_package.hpke_init_key().as_slice(), &private_key)
-            .map_err(|_| Status::aborted("failed to interact with the key store"))?;
-
-        // Store the key package in the key store with the hash reference as id
-        // for retrieval when parsing welcome messages.
-        crypto_provider
-            .key_store()
-            .store(
-                my_key_package
-                    .hash_ref(crypto_provider.crypto())
-                    .map_err(into_status)?
-                    .as_slice(),
-                &my_key_package,
-            )
-            .map_err(into_status)?;
-
-        // Store the encryption key pair in the key store.
-        write_keys_from_key_store(&crypto_provider, encryption_keypair);
-
-        // Store the private part of the init_key into the key store.
-        // The key is the public key.
-        crypto_provider
-            .key_store()
-            .store::<HpkePrivateKey>(my_key_package.hpke_init_key().as_slice(), &private_key)
-            .map_err(into_status)?;
+        let (my_signature_keys, crypto_provider) = self.take_pending_state(&request.identity)?;
 
         let welcome_msg = MlsMessageIn::tls_deserialize(&mut request.welcome.as_slice())
             .map_err(|_| Status::aborted("failed to deserialize MlsMessage with a Welcome"))?;
@@ -436,9 +578,14 @@ impl MlsClient for MlsClientImpl {
 
         let ratchet_tree = ratchet_tree_from_config(request.ratchet_tree.clone());
 
-        let group =
-            MlsGroup::new_from_welcome(&crypto_provider, &mls_group_config, welcome, ratchet_tree)
-                .map_err(into_status)?;
+        let group = MlsGroup::new_from_welcome(
+            &crypto_provider,
+            &mls_group_config,
+            welcome,
+            ratchet_tree,
+            &AcceptAllCredentials,
+        )
+        .map_err(into_status)?;
 
         let interop_group = InteropGroup {
             wire_format_policy,
@@ -538,6 +685,7 @@ impl MlsClient for MlsClientImpl {
                 &mls_group_config,
                 b"",
                 credential_with_key,
+                &AcceptAllCredentials,
             )
             .unwrap();
 
@@ -695,7 +843,11 @@ impl MlsClient for MlsClientImpl {
         debug!("Processing message.");
         let processed_message = interop_group
             .group
-            .process_message(&interop_group.crypto_provider, message)
+            .process_message(
+                &interop_group.crypto_provider,
+                message,
+                &AcceptAllCredentials,
+            )
             .map_err(into_status)?;
         debug!("Processed.");
         trace!(?processed_message);
@@ -958,10 +1110,40 @@ impl MlsClient for MlsClientImpl {
         let request = request.get_ref();
         info!(?request, "Request");
 
-        let response = Status::unimplemented("Re-init is not implemented");
+        let mut groups = self.groups.lock().unwrap();
+        let interop_group = groups
+            .get_mut(request.state_id as usize)
+            .ok_or_else(|| Status::new(Code::InvalidArgument, "unknown state_id"))?;
+        trace!("   in epoch {:?}", interop_group.group.epoch());
+        trace!(
+            "   actor {:x?}",
+            String::from_utf8_lossy(interop_group.group.own_identity().unwrap())
+        );
+
+        let reinit_proposal = ReInitProposal::new(
+            GroupId::from_slice(&request.group_id),
+            ProtocolVersion::default(),
+            *to_ciphersuite(request.cipher_suite)?,
+            to_extensions(&request.extensions)?,
+        );
+        let (proposal, _) = interop_group
+            .group
+            .propose_reinit(
+                &interop_group.crypto_provider,
+                &interop_group.signature_keys,
+                reinit_proposal,
+            )
+            .map_err(into_status)?;
+
+        // Store the proposal for potential future use.
+        interop_group.messages_out.push(proposal.clone().into());
+
+        let proposal = proposal.to_bytes().unwrap();
+
+        let response = ProposalResponse { proposal };
 
         info!(?response, "Response");
-        Err(response)
+        Ok(Response::new(response))
     }
 
     #[instrument(skip_all, fields(actor))]
@@ -994,7 +1176,11 @@ impl MlsClient for MlsClientImpl {
             }
             trace!("Processing proposal ...");
             let processed_message = group
-                .process_message(&interop_group.crypto_provider, message)
+                .process_message(
+                    &interop_group.crypto_provider,
+                    message,
+                    &AcceptAllCredentials,
+                )
                 .map_err(into_status)?;
             trace!("... done");
 
@@ -1173,7 +1359,11 @@ impl MlsClient for MlsClientImpl {
             }
             trace!("   processing proposal ...");
             let processed_message = group
-                .process_message(&interop_group.crypto_provider, message)
+                .process_message(
+                    &interop_group.crypto_provider,
+                    message,
+                    &AcceptAllCredentials,
+                )
                 .map_err(into_status)?;
             trace!("       done");
             match processed_message.into_content() {
@@ -1197,7 +1387,11 @@ impl MlsClient for MlsClientImpl {
 
         debug!("Processing message.");
         let processed_message = group
-            .process_message(&interop_group.crypto_provider, message)
+            .process_message(
+                &interop_group.crypto_provider,
+                message,
+                &AcceptAllCredentials,
+            )
             .map_err(into_status)?;
         debug!("Processed.");
         trace!(?processed_message);
@@ -1409,53 +1603,393 @@ impl MlsClient for MlsClientImpl {
         ))
     }
 
+    #[instrument(skip_all)]
     async fn re_init_commit(
         &self,
-        _: Request<CommitRequest>,
+        request: Request<CommitRequest>,
     ) -> Result<Response<CommitResponse>, Status> {
-        todo!()
+        // The commit covers a ReInit proposal by reference.
+        self.commit(request).await
     }
 
+    #[instrument(skip_all)]
     async fn handle_pending_re_init_commit(
         &self,
-        _: Request<HandlePendingCommitRequest>,
+        request: Request<HandlePendingCommitRequest>,
     ) -> Result<Response<HandleReInitCommitResponse>, Status> {
-        todo!()
+        let state_id = request.get_ref().state_id;
+        let epoch_authenticator = self
+            .handle_pending_commit(request)
+            .await?
+            .into_inner()
+            .epoch_authenticator;
+
+        let response = self.pending_reinit(state_id, epoch_authenticator)?;
+
+        info!(?response, "Response");
+        Ok(Response::new(response))
     }
 
+    #[instrument(skip_all)]
     async fn handle_re_init_commit(
         &self,
-        _: Request<HandleCommitRequest>,
+        request: Request<HandleCommitRequest>,
     ) -> Result<Response<HandleReInitCommitResponse>, Status> {
-        todo!()
+        let state_id = request.get_ref().state_id;
+        let epoch_authenticator = self
+            .handle_commit(request)
+            .await?
+            .into_inner()
+            .epoch_authenticator;
+
+        let response = self.pending_reinit(state_id, epoch_authenticator)?;
+
+        info!(?response, "Response");
+        Ok(Response::new(response))
     }
 
+    #[instrument(skip_all, fields(actor))]
     async fn re_init_welcome(
         &self,
-        _: Request<ReInitWelcomeRequest>,
+        request: Request<ReInitWelcomeRequest>,
     ) -> Result<Response<CreateSubgroupResponse>, Status> {
-        todo!()
+        let request = request.get_ref();
+        info!(?request, "Request");
+
+        let reinit = self.take_pending_reinit(request.reinit_id)?;
+
+        let mut groups = self.groups.lock().unwrap();
+        let interop_group = groups
+            .get_mut(reinit.state_id as usize)
+            .ok_or_else(|| Status::new(Code::InvalidArgument, "unknown state_id"))?;
+        let own_identity = interop_group.group.own_identity().unwrap().to_vec();
+
+        Span::current().record("actor", bytes_to_string(&own_identity));
+
+        // The key package of this client is part of the key packages of all
+        // members, but it creates the successor group instead.
+        let key_packages = to_key_packages(&request.key_package)?
+            .into_iter()
+            .filter(|key_package| key_package.leaf_node().credential().identity() != own_identity)
+            .collect::<Vec<_>>();
+
+        // Note: We just use some values here that make live testing work.
+        //       There is nothing special about the used numbers and they
+        //       can be increased (or decreased) depending on the available scenarios.
+        let mls_group_config = MlsGroupConfig::builder()
+            .max_past_epochs(32)
+            .number_of_resumption_psks(32)
+            .sender_ratchet_configuration(SenderRatchetConfiguration::default())
+            .use_ratchet_tree_extension(!request.external_tree)
+            .wire_format_policy(interop_group.wire_format_policy)
+            .build();
+        let credential_with_key = CredentialWithKey {
+            credential: Credential::new(own_identity, CredentialType::Basic).unwrap(),
+            signature_key: reinit.signature_keys.public().into(),
+        };
+
+        let (group, welcome, _group_info) = interop_group
+            .group
+            .reinitialize(
+                &interop_group.crypto_provider,
+                &reinit.signature_keys,
+                &mls_group_config,
+                credential_with_key,
+                &key_packages,
+            )
+            .map_err(into_status)?;
+        debug!(epoch=?group.epoch(), "Successor group created.");
+
+        let welcome = welcome
+            .tls_serialize_detached()
+            .map_err(|_| Status::aborted("failed to serialize welcome"))?;
+        let ratchet_tree = if request.external_tree {
+            group
+                .export_ratchet_tree()
+                .tls_serialize_detached()
+                .map_err(|_| Status::aborted("failed to serialize ratchet tree"))?
+        } else {
+            vec![]
+        };
+        let epoch_authenticator = group.epoch_authenticator().as_slice().to_vec();
+
+        // The successor group uses the key store of the reinitialized group,
+        // which can't be used anymore.
+        let interop_group = InteropGroup {
+            wire_format_policy: interop_group.wire_format_policy,
+            group,
+            signature_keys: reinit.signature_keys,
+            messages_out: Vec::new(),
+            crypto_provider: std::mem::take(&mut interop_group.crypto_provider),
+        };
+
+        let state_id = groups.len() as u32;
+        groups.push(interop_group);
+
+        let response = CreateSubgroupResponse {
+            state_id,
+            welcome,
+            ratchet_tree,
+            epoch_authenticator,
+        };
+
+        info!(?response, "Response");
+        Ok(Response::new(response))
     }
 
+    #[instrument(skip_all, fields(actor))]
     async fn handle_re_init_welcome(
         &self,
-        _: Request<HandleReInitWelcomeRequest>,
+        request: Request<HandleReInitWelcomeRequest>,
     ) -> Result<Response<JoinGroupResponse>, Status> {
-        todo!()
+        let request = request.get_ref();
+        info!(?request, "Request");
+
+        let reinit = self.take_pending_reinit(request.reinit_id)?;
+
+        let mut groups = self.groups.lock().unwrap();
+        let interop_group = groups
+            .get_mut(reinit.state_id as usize)
+            .ok_or_else(|| Status::new(Code::InvalidArgument, "unknown state_id"))?;
+
+        Span::current().record(
+            "actor",
+            bytes_to_string(interop_group.group.own_identity().unwrap()),
+        );
+
+        // Note: We just use some values here that make live testing work.
+        //       There is nothing special about the used numbers and they
+        //       can be increased (or decreased) depending on the available scenarios.
+        let mls_group_config = MlsGroupConfig::builder()
+            .max_past_epochs(32)
+            .number_of_resumption_psks(32)
+            .sender_ratchet_configuration(SenderRatchetConfiguration::default())
+            .use_ratchet_tree_extension(true)
+            .wire_format_policy(interop_group.wire_format_policy)
+            .build();
+
+        let welcome = MlsMessageIn::tls_deserialize(&mut request.welcome.as_slice())
+            .map_err(|_| Status::aborted("failed to deserialize MlsMessage with a Welcome"))?
+            .into_welcome()
+            .ok_or(Status::invalid_argument(
+                "unable to get Welcome from MlsMessage",
+            ))?;
+        let ratchet_tree = ratchet_tree_from_config(request.ratchet_tree.clone());
+
+        // The key package for the successor group and the resumption PSK of
+        // the reinitialized group are in the key store of the reinitialized
+        // group.
+        let group = MlsGroup::new_from_welcome(
+            &interop_group.crypto_provider,
+            &mls_group_config,
+            welcome,
+            ratchet_tree,
+            &AcceptAllCredentials,
+        )
+        .map_err(into_status)?;
+        trace!(epoch=?group.epoch(), "Joined successor group.");
+
+        let epoch_authenticator = group.epoch_authenticator().as_slice().to_vec();
+
+        let interop_group = InteropGroup {
+            wire_format_policy: interop_group.wire_format_policy,
+            group,
+            signature_keys: reinit.signature_keys,
+            messages_out: Vec::new(),
+            crypto_provider: std::mem::take(&mut interop_group.crypto_provider),
+        };
+
+        let state_id = groups.len() as u32;
+        groups.push(interop_group);
+
+        let response = JoinGroupResponse {
+            state_id,
+            epoch_authenticator,
+        };
+
+        info!(?response, "Response");
+        Ok(Response::new(response))
     }
 
+    #[instrument(skip_all, fields(actor))]
     async fn create_branch(
         &self,
-        _: Request<CreateBranchRequest>,
+        request: Request<CreateBranchRequest>,
     ) -> Result<Response<CreateSubgroupResponse>, Status> {
-        todo!()
+        let request = request.get_ref();
+        info!(?request, "Request");
+
+        let mut groups = self.groups.lock().unwrap();
+        let interop_group = groups
+            .get_mut(request.state_id as usize)
+            .ok_or_else(|| Status::new(Code::InvalidArgument, "unknown state_id"))?;
+        let group = &interop_group.group;
+        let own_identity = group.own_identity().unwrap().to_vec();
+
+        Span::current().record("actor", bytes_to_string(&own_identity));
+        trace!(epoch=?group.epoch(), "Current group state.");
+
+        // Note: We just use some values here that make live testing work.
+        //       There is nothing special about the used numbers and they
+        //       can be increased (or decreased) depending on the available scenarios.
+        let mut mls_group_config = MlsGroupConfig::builder()
+            .max_past_epochs(32)
+            .number_of_resumption_psks(32)
+            .sender_ratchet_configuration(SenderRatchetConfiguration::default())
+            .use_ratchet_tree_extension(!request.external_tree)
+            .wire_format_policy(interop_group.wire_format_policy);
+        for extension in to_extensions(&request.extensions)?.iter() {
+            mls_group_config = match extension {
+                Extension::RequiredCapabilities(required_capabilities) => {
+                    mls_group_config.required_capabilities(required_capabilities.clone())
+                }
+                Extension::ExternalSenders(external_senders) => {
+                    mls_group_config.external_senders(external_senders.clone())
+                }
+                _ => {
+                    return Err(Status::unimplemented(
+                        "unsupported group context extension in branch",
+                    ))
+                }
+            };
+        }
+        let mls_group_config = mls_group_config.build();
+
+        // The branch is a group of its own with its own keys.
+        let crypto_provider = OpenMlsRustCrypto::default();
+        let signature_keys = SignatureKeyPair::new(group.ciphersuite().signature_algorithm())
+            .map_err(|_| Status::internal("failed to create signature keys"))?;
+        signature_keys
+            .store(crypto_provider.key_store())
+            .map_err(into_status)?;
+        let credential_with_key = CredentialWithKey {
+            credential: Credential::new(own_identity, CredentialType::Basic).unwrap(),
+            signature_key: signature_keys.public().into(),
+        };
+
+        let (branch, welcome, _group_info) = group
+            .branch(
+                &crypto_provider,
+                &signature_keys,
+                &mls_group_config,
+                GroupId::from_slice(&request.group_id),
+                credential_with_key,
+                &to_key_packages(&request.key_packages)?,
+            )
+            .map_err(into_status)?;
+        debug!(epoch=?branch.epoch(), "Branch created.");
+
+        let welcome = welcome
+            .tls_serialize_detached()
+            .map_err(|_| Status::aborted("failed to serialize welcome"))?;
+        let ratchet_tree = if request.external_tree {
+            branch
+                .export_ratchet_tree()
+                .tls_serialize_detached()
+                .map_err(|_| Status::aborted("failed to serialize ratchet tree"))?
+        } else {
+            vec![]
+        };
+        let epoch_authenticator = branch.epoch_authenticator().as_slice().to_vec();
+
+        let interop_group = InteropGroup {
+            wire_format_policy: interop_group.wire_format_policy,
+            group: branch,
+            signature_keys,
+            messages_out: Vec::new(),
+            crypto_provider,
+        };
+
+        let state_id = groups.len() as u32;
+        groups.push(interop_group);
+
+        let response = CreateSubgroupResponse {
+            state_id,
+            welcome,
+            ratchet_tree,
+            epoch_authenticator,
+        };
+
+        info!(?response, "Response");
+        Ok(Response::new(response))
     }
 
+    #[instrument(skip_all, fields(actor))]
     async fn handle_branch(
         &self,
-        _: Request<HandleBranchRequest>,
+        request: Request<HandleBranchRequest>,
     ) -> Result<Response<HandleBranchResponse>, Status> {
-        todo!()
+        let request = request.get_ref();
+        info!(?request, "Request");
+
+        let identity = self
+            .transaction_id_map
+            .lock()
+            .unwrap()
+            .get(&request.transaction_id)
+            .cloned()
+            .ok_or_else(|| Status::new(Code::InvalidArgument, "unknown transaction_id"))?;
+        let (signature_keys, crypto_provider) = self.take_pending_state(&identity)?;
+
+        let mut groups = self.groups.lock().unwrap();
+        let interop_group = groups
+            .get(request.state_id as usize)
+            .ok_or_else(|| Status::new(Code::InvalidArgument, "unknown state_id"))?;
+
+        Span::current().record("actor", bytes_to_string(&identity));
+        trace!(epoch=?interop_group.group.epoch(), "Current group state.");
+
+        // Note: We just use some values here that make live testing work.
+        //       There is nothing special about the used numbers and they
+        //       can be increased (or decreased) depending on the available scenarios.
+        let mls_group_config = MlsGroupConfig::builder()
+            .max_past_epochs(32)
+            .number_of_resumption_psks(32)
+            .sender_ratchet_configuration(SenderRatchetConfiguration::default())
+            .use_ratchet_tree_extension(true)
+            .wire_format_policy(interop_group.wire_format_policy)
+            .build();
+
+        let welcome = MlsMessageIn::tls_deserialize(&mut request.welcome.as_slice())
+            .map_err(|_| Status::aborted("failed to deserialize MlsMessage with a Welcome"))?
+            .into_welcome()
+            .ok_or(Status::invalid_argument(
+                "unable to get Welcome from MlsMessage",
+            ))?;
+        let ratchet_tree = ratchet_tree_from_config(request.ratchet_tree.clone());
+
+        let branch = interop_group
+            .group
+            .join_branch(
+                &crypto_provider,
+                &mls_group_config,
+                welcome,
+                ratchet_tree,
+                &AcceptAllCredentials,
+            )
+            .map_err(into_status)?;
+        trace!(epoch=?branch.epoch(), "Joined branch.");
+
+        let epoch_authenticator = branch.epoch_authenticator().as_slice().to_vec();
+
+        let interop_group = InteropGroup {
+            wire_format_policy: interop_group.wire_format_policy,
+            group: branch,
+            signature_keys,
+            messages_out: Vec::new(),
+            crypto_provider,
+        };
+
+        let state_id = groups.len() as u32;
+        groups.push(interop_group);
+
+        let response = HandleBranchResponse {
+            state_id,
+            epoch_authenticator,
+        };
+
+        info!(?response, "Response");
+        Ok(Response::new(response))
     }
 
     async fn new_member_add_proposal(
@@ -1,9 +1,102 @@
-use openmls_traits::key_store::{MlsEntity, OpenMlsKeyStore};
-use std::{collections::HashMap, sync::RwLock};
+use openmls_traits::key_store::{MlsEntity, MlsEntityId, OpenMlsKeyStore};
+use std::{
+    collections::HashMap,
+    sync::{Condvar, Mutex, MutexGuard, RwLock},
+    thread::{self, ThreadId},
+};
 
+/// A serialized value and the id of its entity.
+type Value = (MlsEntityId, Vec<u8>);
+
+/// A journal of a transaction that holds the values that the changed keys had
+/// before the transaction began.
+type Journal = HashMap<Vec<u8>, Option<Value>>;
+
+/// The open transactions of a [`MemoryKeyStore`] and the thread that opened
+/// them.
+#[derive(Debug, Default)]
+struct Transactions {
+    thread: Option<ThreadId>,
+    // The journals of the open transactions, the innermost one last.
+    journals: Vec<Journal>,
+}
+
+impl Transactions {
+    /// Returns `true` if another thread has open transactions.
+    fn of_other_thread(&self) -> bool {
+        matches!(self.thread, Some(thread) if thread != thread::current().id())
+    }
+
+    /// Remove the innermost transaction of the current thread and return its
+    /// journal.
+    ///
+    /// Returns an error if the current thread has no open transaction.
+    fn pop(&mut self) -> Result<Journal, MemoryKeyStoreError> {
+        if self.of_other_thread() {
+            return Err(MemoryKeyStoreError::NoTransaction);
+        }
+        let journal = self
+            .journals
+            .pop()
+            .ok_or(MemoryKeyStoreError::NoTransaction)?;
+        if self.journals.is_empty() {
+            self.thread = None;
+        }
+        Ok(journal)
+    }
+}
+
+/// A key store that holds all values in memory.
+///
+/// Transactions are not isolated from each other, so there can only be open
+/// transactions of one thread at a time. While a thread has open
+/// transactions, writes and transactions of other threads wait until the
+/// outermost transaction was committed or rolled back. Reads of other threads
+/// don't wait and see the uncommitted values.
 #[derive(Debug, Default)]
 pub struct MemoryKeyStore {
-    values: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    values: RwLock<HashMap<Vec<u8>, Value>>,
+    transactions: Mutex<Transactions>,
+    // Notified when the outermost transaction of a thread ends.
+    transactions_ended: Condvar,
+}
+
+impl MemoryKeyStore {
+    /// Lock the transactions once no other thread has open transactions.
+    fn lock_transactions(&self) -> MutexGuard<'_, Transactions> {
+        let transactions = self.transactions.lock().unwrap();
+        self.transactions_ended
+            .wait_while(transactions, |transactions| transactions.of_other_thread())
+            .unwrap()
+    }
+
+    /// Write `value` for `k`, or delete the value if it is `None`. The
+    /// previous value is recorded in the journal of the innermost
+    /// transaction, unless it was changed before in that transaction.
+    fn write(&self, k: &[u8], value: Option<Value>) {
+        let mut transactions = self.lock_transactions();
+        // We unwrap here, because the functions claiming a write lock on
+        // `values` only hold it very briefly and should not panic during that
+        // period.
+        let mut values = self.values.write().unwrap();
+        if let Some(journal) = transactions.journals.last_mut() {
+            journal
+                .entry(k.to_vec())
+                .or_insert_with(|| values.get(k).cloned());
+        }
+        match value {
+            Some(value) => values.insert(k.to_vec(), value),
+            None => values.remove(k),
+        };
+    }
+
+    /// Wake up the threads that wait for the transactions of the current
+    /// thread if it has no open transactions left.
+    fn notify_if_ended(&self, transactions: &Transactions) {
+        if transactions.thread.is_none() {
+            self.transactions_ended.notify_all();
+        }
+    }
 }
 
 impl OpenMlsKeyStore for MemoryKeyStore {
@@ -16,11 +109,7 @@ impl OpenMlsKeyStore for MemoryKeyStore {
     /// Returns an error if storing fails.
     fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
         let value = serde_json::to_vec(v).map_err(|_| MemoryKeyStoreError::SerializationError)?;
-        // We unwrap here, because this is the only function claiming a write
-        // lock on `credential_bundles`. It only holds the lock very briefly and
-        // should not panic during that period.
-        let mut values = self.values.write().unwrap();
-        values.insert(k.to_vec(), value);
+        self.write(k, Some((V::ID, value)));
         Ok(())
     }
 
@@ -33,7 +122,7 @@ impl OpenMlsKeyStore for MemoryKeyStore {
         // `init_key_package_bundles` (this one and `generate_key_package_bundle`) only
         // hold the lock very briefly and should not panic during that period.
         let values = self.values.read().unwrap();
-        if let Some(value) = values.get(k) {
+        if let Some((_, value)) = values.get(k) {
             serde_json::from_slice(value).ok()
         } else {
             None
@@ -44,9 +133,64 @@ impl OpenMlsKeyStore for MemoryKeyStore {
     ///
     /// Returns an error if storing fails.
     fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
-        // We just delete both ...
+        self.write(k, None);
+        Ok(())
+    }
+
+    /// Return the IDs of all values that are stored for the entity with the
+    /// given [`MlsEntityId`].
+    fn list_keys(&self, entity_id: MlsEntityId) -> Result<Option<Vec<Vec<u8>>>, Self::Error> {
+        let values = self.values.read().unwrap();
+        Ok(Some(
+            values
+                .iter()
+                .filter(|(_, (id, _))| *id == entity_id)
+                .map(|(k, _)| k.clone())
+                .collect(),
+        ))
+    }
+
+    /// Begin a transaction. Transactions can be nested.
+    ///
+    /// Waits until no other thread has open transactions.
+    fn begin_transaction(&self) -> Result<(), Self::Error> {
+        let mut transactions = self.lock_transactions();
+        transactions.thread = Some(thread::current().id());
+        transactions.journals.push(Journal::new());
+        Ok(())
+    }
+
+    /// Commit the innermost transaction. Its changes become part of the
+    /// enclosing transaction, if there is one.
+    ///
+    /// Returns an error if the current thread has no open transaction.
+    fn commit_transaction(&self) -> Result<(), Self::Error> {
+        let mut transactions = self.transactions.lock().unwrap();
+        let journal = transactions.pop()?;
+        if let Some(enclosing_journal) = transactions.journals.last_mut() {
+            for (k, value) in journal {
+                enclosing_journal.entry(k).or_insert(value);
+            }
+        }
+        self.notify_if_ended(&transactions);
+        Ok(())
+    }
+
+    /// Roll back the innermost transaction.
+    ///
+    /// Returns an error if the current thread has no open transaction.
+    fn rollback_transaction(&self) -> Result<(), Self::Error> {
+        let mut transactions = self.transactions.lock().unwrap();
+        let journal = transactions.pop()?;
         let mut values = self.values.write().unwrap();
-        values.remove(k);
+        for (k, value) in journal {
+            if let Some(value) = value {
+                values.insert(k, value);
+            } else {
+                values.remove(&k);
+            }
+        }
+        self.notify_if_ended(&transactions);
         Ok(())
     }
 }
@@ -60,4 +204,140 @@ pub enum MemoryKeyStoreError {
     UnsupportedMethod,
     #[error("Error serializing value.")]
     SerializationError,
+    #[error("There is no transaction to commit or roll back.")]
+    NoTransaction,
+}
+
+#[cfg(test)]
+mod test {
+    use std::{sync::mpsc, thread, time::Duration};
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct KeyPackage(Vec<u8>);
+
+    impl MlsEntity for KeyPackage {
+        const ID: MlsEntityId = MlsEntityId::KeyPackage;
+    }
+
+    #[test]
+    fn commit() {
+        let key_store = MemoryKeyStore::default();
+        key_store.store(b"id", &KeyPackage(vec![1])).unwrap();
+
+        key_store.begin_transaction().unwrap();
+        key_store.store(b"id", &KeyPackage(vec![2])).unwrap();
+        key_store.store(b"new", &KeyPackage(vec![3])).unwrap();
+        // Values written in a transaction can be read before it is committed.
+        assert_eq!(key_store.read(b"id"), Some(KeyPackage(vec![2])));
+        key_store.commit_transaction().unwrap();
+
+        assert_eq!(key_store.read(b"id"), Some(KeyPackage(vec![2])));
+        assert_eq!(key_store.read(b"new"), Some(KeyPackage(vec![3])));
+
+        // There is no transaction left.
+        assert_eq!(
+            key_store.commit_transaction(),
+            Err(MemoryKeyStoreError::NoTransaction)
+        );
+    }
+
+    #[test]
+    fn rollback() {
+        let key_store = MemoryKeyStore::default();
+        key_store.store(b"id", &KeyPackage(vec![1])).unwrap();
+        key_store.store(b"deleted", &KeyPackage(vec![2])).unwrap();
+
+        key_store.begin_transaction().unwrap();
+        key_store.store(b"id", &KeyPackage(vec![3])).unwrap();
+        key_store.store(b"id", &KeyPackage(vec![4])).unwrap();
+        key_store.store(b"new", &KeyPackage(vec![5])).unwrap();
+        key_store.delete::<KeyPackage>(b"deleted").unwrap();
+        key_store.rollback_transaction().unwrap();
+
+        assert_eq!(key_store.read(b"id"), Some(KeyPackage(vec![1])));
+        assert_eq!(key_store.read::<KeyPackage>(b"new"), None);
+        assert_eq!(key_store.read(b"deleted"), Some(KeyPackage(vec![2])));
+        assert_eq!(
+            key_store
+                .list_keys(MlsEntityId::KeyPackage)
+                .map(|keys| keys.map(|keys| keys.len())),
+            Ok(Some(2))
+        );
+
+        // There is no transaction left.
+        assert_eq!(
+            key_store.rollback_transaction(),
+            Err(MemoryKeyStoreError::NoTransaction)
+        );
+    }
+
+    #[test]
+    fn nested_transactions() {
+        let key_store = MemoryKeyStore::default();
+        key_store.store(b"id", &KeyPackage(vec![1])).unwrap();
+
+        // A committed nested transaction is rolled back with the enclosing
+        // transaction.
+        key_store.begin_transaction().unwrap();
+        key_store.delete::<KeyPackage>(b"id").unwrap();
+        key_store.begin_transaction().unwrap();
+        key_store.store(b"id", &KeyPackage(vec![2])).unwrap();
+        key_store.store(b"new", &KeyPackage(vec![3])).unwrap();
+        key_store.commit_transaction().unwrap();
+        key_store.rollback_transaction().unwrap();
+        assert_eq!(key_store.read(b"id"), Some(KeyPackage(vec![1])));
+        assert_eq!(key_store.read::<KeyPackage>(b"new"), None);
+
+        // A rolled back nested transaction doesn't affect the enclosing
+        // transaction.
+        key_store.begin_transaction().unwrap();
+        key_store.store(b"id", &KeyPackage(vec![2])).unwrap();
+        key_store.begin_transaction().unwrap();
+        key_store.store(b"id", &KeyPackage(vec![3])).unwrap();
+        key_store.store(b"new", &KeyPackage(vec![4])).unwrap();
+        key_store.rollback_transaction().unwrap();
+        key_store.commit_transaction().unwrap();
+        assert_eq!(key_store.read(b"id"), Some(KeyPackage(vec![2])));
+        assert_eq!(key_store.read::<KeyPackage>(b"new"), None);
+    }
+
+    #[test]
+    fn transactions_of_other_threads() {
+        let key_store = MemoryKeyStore::default();
+        key_store.begin_transaction().unwrap();
+        key_store.store(b"id", &KeyPackage(vec![1])).unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            let other_thread = scope.spawn(|| {
+                // Another thread can't end the transaction.
+                assert_eq!(
+                    key_store.commit_transaction(),
+                    Err(MemoryKeyStoreError::NoTransaction)
+                );
+                // Its writes wait until the transaction ended.
+                key_store.store(b"other", &KeyPackage(vec![2])).unwrap();
+                sender.send(()).unwrap();
+
+                key_store.begin_transaction().unwrap();
+                key_store.store(b"id", &KeyPackage(vec![3])).unwrap();
+                key_store.commit_transaction().unwrap();
+            });
+
+            assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+            assert_eq!(key_store.read::<KeyPackage>(b"other"), None);
+
+            // The write of the other thread is not part of the transaction.
+            key_store.rollback_transaction().unwrap();
+            receiver.recv().unwrap();
+            other_thread.join().unwrap();
+        });
+
+        assert_eq!(key_store.read(b"id"), Some(KeyPackage(vec![3])));
+        assert_eq!(key_store.read(b"other"), Some(KeyPackage(vec![2])));
+    }
 }
@@ -20,7 +20,7 @@ use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, fmt::Debug};
 use thiserror::Error;
 
-use crate::error::LibraryError;
+use crate::{error::LibraryError, persistence::Persist};
 
 use super::{
     sorted_iter::sorted_iter,
@@ -70,6 +70,31 @@ impl<L: Clone + Debug + Default, P: Clone + Debug + Default> StagedAbDiff<L, P>
     pub(super) fn tree_size(&self) -> TreeSize {
         self.size
     }
+
+    /// Return an iterator over the leaves that are changed by the diff.
+    pub(crate) fn leaves(&self) -> impl Iterator<Item = (LeafNodeIndex, &L)> {
+        self.leaf_diff.iter().map(|(index, leaf)| (*index, leaf))
+    }
+}
+
+impl<L, P> Persist for StagedAbDiff<L, P>
+where
+    L: Clone + Debug + Default + Persist,
+    P: Clone + Debug + Default + Persist,
+{
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.leaf_diff.persist(writer)?;
+        self.parent_diff.persist(writer)?;
+        self.size.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(StagedAbDiff {
+            leaf_diff: BTreeMap::restore(bytes)?,
+            parent_diff: BTreeMap::restore(bytes)?,
+            size: TreeSize::restore(bytes)?,
+        })
+    }
 }
 
 /// The [`AbDiff`] represents a set of differences (i.e. a "Diff") for an
@@ -102,6 +127,20 @@ impl<'a, L: Clone + Debug + Default, P: Clone + Debug + Default> From<&'a ABinar
 }
 
 impl<'a, L: Clone + Debug + Default, P: Clone + Debug + Default> AbDiff<'a, L, P> {
+    /// Create an [`AbDiff`] that contains the changes of the given
+    /// [`StagedAbDiff`] to the given tree, i.e., a view of the tree as it is
+    /// after merging the staged diff.
+    pub(super) fn from_staged(tree: &'a ABinaryTree<L, P>, diff: &StagedAbDiff<L, P>) -> Self {
+        AbDiff {
+            original_tree: tree,
+            leaf_diff: diff.leaf_diff.clone(),
+            parent_diff: diff.parent_diff.clone(),
+            size: diff.size,
+            default_leaf: L::default(),
+            default_parent: P::default(),
+        }
+    }
+
     // Functions handling interactions with leaves.
     ///////////////////////////////////////////////
 
@@ -16,6 +16,7 @@ use super::{
     diff::{AbDiff, StagedAbDiff},
     treemath::{common_direct_path, LeafNodeIndex, ParentNodeIndex, TreeSize, MAX_TREE_SIZE},
 };
+use crate::persistence::Persist;
 
 #[derive(Clone, Debug)]
 pub(crate) enum TreeNode<L, P>
@@ -149,6 +150,12 @@ impl<L: Clone + Debug + Default, P: Clone + Debug + Default> ABinaryTree<L, P> {
         self.into()
     }
 
+    /// Creates and returns an [`AbDiff`] with the changes of the given
+    /// [`StagedAbDiff`] without merging them into the tree.
+    pub(crate) fn staged_diff(&self, diff: &StagedAbDiff<L, P>) -> AbDiff<'_, L, P> {
+        AbDiff::from_staged(self, diff)
+    }
+
     /// Merges the changes applied to the [`StagedAbDiff`] into the tree.
     /// Depending on the changes made to the diff, this can either increase or
     /// decrease the size of the tree, although not beyond the minimum size of
@@ -224,6 +231,38 @@ impl<L: Clone + Debug + Default, P: Clone + Debug + Default> ABinaryTree<L, P> {
     }
 }
 
+impl<L, P> Persist for ABinaryTree<L, P>
+where
+    L: Clone + Debug + Default + Persist,
+    P: Clone + Debug + Default + Persist,
+{
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.leaf_nodes.persist(writer)?;
+        self.parent_nodes.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        let leaf_nodes = Vec::restore(bytes)?;
+        let parent_nodes = Vec::restore(bytes)?;
+
+        // Perform the same checks as `new()`.
+        if leaf_nodes.len() != parent_nodes.len() + 1
+            || leaf_nodes.len() + parent_nodes.len() > MAX_TREE_SIZE as usize
+        {
+            return Err(tls_codec::Error::DecodingError(
+                "Invalid number of nodes in the tree".into(),
+            ));
+        }
+
+        Ok(ABinaryTree {
+            leaf_nodes,
+            parent_nodes,
+            default_leaf: L::default(),
+            default_parent: P::default(),
+        })
+    }
+}
+
 /// Binary Tree error
 #[derive(Error, Debug, PartialEq, Clone)]
 pub(crate) enum ABinaryTreeError {
@@ -3,6 +3,8 @@ use std::cmp::Ordering;
 use serde::{Deserialize, Serialize};
 use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize};
 
+use crate::persistence::Persist;
+
 pub(crate) const MAX_TREE_SIZE: u32 = 1 << 30;
 pub(crate) const MIN_TREE_SIZE: u32 = 1;
 
@@ -235,6 +237,33 @@ impl TreeSize {
     }
 }
 
+impl Persist for ParentNodeIndex {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.0.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        u32::restore(bytes).map(Self)
+    }
+}
+
+impl Persist for TreeSize {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.0.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        let size = u32::restore(bytes)?;
+        // The size of a full, left-balanced binary tree is 2^k - 1.
+        if size > MAX_TREE_SIZE || !(size + 1).is_power_of_two() {
+            return Err(tls_codec::Error::DecodingError(format!(
+                "{size} is not a valid tree size"
+            )));
+        }
+        Ok(Self(size))
+    }
+}
+
 #[test]
 fn tree_size() {
     assert_eq!(TreeSize::new(1).u32(), 1);
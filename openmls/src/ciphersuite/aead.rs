@@ -1,6 +1,11 @@
-use tls_codec::SecretVLBytes;
+use std::io::Read;
+
+use tls_codec::{
+    Deserialize as TlsDeserializeTrait, SecretVLBytes, Serialize as TlsSerializeTrait,
+};
 
 use super::*;
+use crate::persistence::Persist;
 
 /// The default NONCE size in bytes.
 pub(crate) const NONCE_BYTES: usize = 12;
@@ -135,6 +140,41 @@ impl AeadNonce {
     }
 }
 
+impl Persist for AeadKey {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        (self.aead_mode as u16).persist(writer)?;
+        self.value.tls_serialize(writer).map(|_| ())
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        let aead_mode = match u16::restore(bytes)? {
+            0x0001 => AeadType::Aes128Gcm,
+            0x0002 => AeadType::Aes256Gcm,
+            0x0003 => AeadType::ChaCha20Poly1305,
+            value => {
+                return Err(tls_codec::Error::DecodingError(format!(
+                    "{value} is not a valid AEAD type"
+                )))
+            }
+        };
+        let value = SecretVLBytes::tls_deserialize(bytes)?;
+        Ok(Self { aead_mode, value })
+    }
+}
+
+impl Persist for AeadNonce {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        writer.extend_from_slice(&self.0);
+        Ok(())
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        let mut nonce = [0u8; NONCE_BYTES];
+        bytes.read_exact(&mut nonce)?;
+        Ok(Self(nonce))
+    }
+}
+
 #[cfg(test)]
 pub(crate) fn aead_key_gen(
     alg: openmls_traits::types::AeadType,
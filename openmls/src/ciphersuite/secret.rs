@@ -1,8 +1,11 @@
 use std::fmt::{Debug, Formatter};
 
-use tls_codec::SecretVLBytes;
+use tls_codec::{
+    Deserialize as TlsDeserializeTrait, SecretVLBytes, Serialize as TlsSerializeTrait,
+};
 
 use super::{kdf_label::KdfLabel, *};
+use crate::persistence::Persist;
 
 /// A struct to contain secrets. This is to provide better visibility into where
 /// and how secrets are used and to avoid passing secrets in their raw
@@ -255,3 +258,21 @@ impl From<&[u8]> for Secret {
         }
     }
 }
+
+/// In contrast to the TLS encoding of a [`Secret`], the persisted encoding
+/// includes the ciphersuite and the protocol version of the secret.
+impl Persist for Secret {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.ciphersuite.persist(writer)?;
+        self.mls_version.persist(writer)?;
+        self.value.tls_serialize(writer).map(|_| ())
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            ciphersuite: Ciphersuite::restore(bytes)?,
+            mls_version: ProtocolVersion::restore(bytes)?,
+            value: SecretVLBytes::tls_deserialize(bytes)?,
+        })
+    }
+}
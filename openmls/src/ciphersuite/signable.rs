@@ -28,7 +28,12 @@
 //! Similarly, only the [`Verifiable`] struct should implement the
 //! [`tls_codec::Deserialize`] trait.
 
-use openmls_traits::{crypto::OpenMlsCrypto, signatures::Signer};
+use openmls_traits::{
+    crypto::OpenMlsCrypto,
+    signatures::Signer,
+    types::{SignatureScheme, SignatureVerification},
+};
+use rayon::prelude::*;
 use thiserror::Error;
 use tls_codec::Serialize;
 
@@ -169,22 +174,76 @@ pub trait Verifiable: Sized {
     }
 }
 
-fn verify(
+/// The number of signatures that are verified in one batch by
+/// [`verify_batch()`]. Batches are verified in parallel.
+const BATCH_SIZE: usize = 64;
+
+/// Verify the signatures of all `verifiables` against their public keys.
+///
+/// The payloads are serialized in parallel and the signatures are verified in
+/// batches with [`OpenMlsCrypto::batch_verify_signatures()`], which is
+/// considerably faster than verifying them one at a time for large trees.
+///
+/// Returns `Ok(())` if all signatures are valid and
+/// `SignatureError::VerificationError` otherwise.
+pub(crate) fn verify_batch<V: Verifiable + Sync>(
     crypto: &impl OpenMlsCrypto,
-    verifiable: &impl Verifiable,
-    pk: &OpenMlsSignaturePublicKey,
+    verifiables: &[(V, OpenMlsSignaturePublicKey)],
 ) -> Result<(), SignatureError> {
+    let payloads = verifiables
+        .par_iter()
+        .map(|(verifiable, _)| sign_content(verifiable))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut signature_schemes: Vec<SignatureScheme> = Vec::new();
+    for (_, pk) in verifiables {
+        if !signature_schemes.contains(&pk.signature_scheme()) {
+            signature_schemes.push(pk.signature_scheme());
+        }
+    }
+
+    for signature_scheme in signature_schemes {
+        let signatures = verifiables
+            .iter()
+            .zip(payloads.iter())
+            .filter(|((_, pk), _)| pk.signature_scheme() == signature_scheme)
+            .map(|((verifiable, pk), payload)| SignatureVerification {
+                data: payload,
+                pk: pk.as_slice(),
+                signature: verifiable.signature().value(),
+            })
+            .collect::<Vec<_>>();
+        signatures
+            .par_chunks(BATCH_SIZE)
+            .try_for_each(|batch| crypto.batch_verify_signatures(signature_scheme, batch))
+            .map_err(|_| SignatureError::VerificationError)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the serialized `SignContent` of the `verifiable`, i.e., the payload
+/// that is signed.
+fn sign_content(verifiable: &impl Verifiable) -> Result<Vec<u8>, SignatureError> {
     let payload = verifiable
         .unsigned_payload()
         .map_err(|_| SignatureError::VerificationError)?;
     let sign_content = SignContent::new(verifiable.label(), payload.into());
-    let payload = match sign_content.tls_serialize_detached() {
-        Ok(p) => p,
+    match sign_content.tls_serialize_detached() {
+        Ok(p) => Ok(p),
         Err(e) => {
             log::error!("Serializing SignContent failed, {:?}", e);
-            return Err(SignatureError::VerificationError);
+            Err(SignatureError::VerificationError)
         }
-    };
+    }
+}
+
+fn verify(
+    crypto: &impl OpenMlsCrypto,
+    verifiable: &impl Verifiable,
+    pk: &OpenMlsSignaturePublicKey,
+) -> Result<(), SignatureError> {
+    let payload = sign_content(verifiable)?;
     crypto
         .verify_signature(
             pk.signature_scheme(),
@@ -292,17 +292,19 @@ pub fn run_test_vector(
         let my_signature = parsed.clone().sign(&parsed.key).unwrap();
 
         // verify signature
-        parsed
-            .clone()
-            .verify::<()>(
-                provider.crypto(),
-                &OpenMlsSignaturePublicKey::new(
-                    public.clone().into(),
-                    ciphersuite.signature_algorithm(),
+        if vector_signatures_verifiable(ciphersuite) {
+            parsed
+                .clone()
+                .verify::<()>(
+                    provider.crypto(),
+                    &OpenMlsSignaturePublicKey::new(
+                        public.clone().into(),
+                        ciphersuite.signature_algorithm(),
+                    )
+                    .unwrap(),
                 )
-                .unwrap(),
-            )
-            .expect("Signature verification failed");
+                .expect("Signature verification failed");
+        }
 
         // verify own signature
         parsed.signature = my_signature.0;
@@ -1,6 +1,6 @@
 //! Unit tests for the ciphersuites.
 use openmls_rust_crypto::OpenMlsRustCrypto;
-use openmls_traits::types::HpkeCiphertext;
+use openmls_traits::types::{HpkeCiphertext, SignatureVerification};
 
 use crate::{ciphersuite::*, test_utils::*};
 
@@ -73,3 +73,39 @@ fn test_hpke_seal_open(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider
         CryptoError::HpkeDecryptionError
     );
 }
+
+// Batch verification accepts valid signatures and rejects a batch with one
+// invalid signature.
+#[apply(ciphersuites_and_providers)]
+fn test_batch_verify_signatures(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let alg = ciphersuite.signature_algorithm();
+    let payloads = (0u8..5).map(|i| vec![i; 32]).collect::<Vec<_>>();
+    let signatures = payloads
+        .iter()
+        .map(|payload| {
+            let (private, public) = provider.crypto().signature_key_gen(alg).unwrap();
+            let signature = provider.crypto().sign(alg, payload, &private).unwrap();
+            (public, signature)
+        })
+        .collect::<Vec<_>>();
+    let batch = payloads
+        .iter()
+        .zip(signatures.iter())
+        .map(|(payload, (pk, signature))| SignatureVerification {
+            data: payload,
+            pk,
+            signature,
+        })
+        .collect::<Vec<_>>();
+    provider
+        .crypto()
+        .batch_verify_signatures(alg, &batch)
+        .expect("Valid signatures were rejected.");
+
+    let mut broken_batch = batch.clone();
+    broken_batch[3].data = &payloads[2];
+    provider
+        .crypto()
+        .batch_verify_signatures(alg, &broken_batch)
+        .expect_err("An invalid signature was accepted.");
+}
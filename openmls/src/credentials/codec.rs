@@ -8,7 +8,9 @@ impl tls_codec::Size for Credential {
         self.credential_type.tls_serialized_len()
             + match &self.credential {
                 MlsCredentialType::Basic(c) => c.tls_serialized_len(),
-                MlsCredentialType::X509(_) => unimplemented!(),
+                MlsCredentialType::X509(certificate_chain) => {
+                    certificate_chain.tls_serialized_len()
+                }
             }
     }
 }
@@ -20,10 +22,10 @@ impl tls_codec::Serialize for Credential {
                 let written = CredentialType::Basic.tls_serialize(writer)?;
                 basic_credential.tls_serialize(writer).map(|l| l + written)
             }
-            // TODO #134: implement encoding for X509 certificates
-            MlsCredentialType::X509(_) => Err(tls_codec::Error::EncodingError(
-                "X509 certificates are not yet implemented.".to_string(),
-            )),
+            MlsCredentialType::X509(certificate_chain) => {
+                let written = CredentialType::X509.tls_serialize(writer)?;
+                certificate_chain.tls_serialize(writer).map(|l| l + written)
+            }
         }
     }
 }
@@ -37,6 +39,11 @@ impl tls_codec::Deserialize for Credential {
             CredentialType::Basic => Ok(Credential::from(MlsCredentialType::Basic(
                 BasicCredential::tls_deserialize(bytes)?,
             ))),
+            CredentialType::X509 => {
+                Ok(Credential::from(MlsCredentialType::X509(
+                    Vec::<Certificate>::tls_deserialize(bytes)?,
+                )))
+            }
             _ => Err(tls_codec::Error::DecodingError(format!(
                 "{credential_type:?} can not be deserialized."
             ))),
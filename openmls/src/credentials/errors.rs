@@ -1,6 +1,6 @@
 //! Credential errors
 //!
-//! This module exposes [`CredentialError`].
+//! This module exposes [`CredentialError`] and [`X509Error`].
 
 use crate::error::LibraryError;
 use thiserror::Error;
@@ -17,4 +17,36 @@ pub enum CredentialError {
     /// Verifying the signature with this credential failed.
     #[error("Invalid signature.")]
     InvalidSignature,
+    /// See [`X509Error`] for more details.
+    #[error(transparent)]
+    X509(#[from] X509Error),
+}
+
+/// An error that occurs when handling X.509 certificates.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum X509Error {
+    /// The certificate chain is empty.
+    #[error("The certificate chain is empty.")]
+    EmptyChain,
+    /// A certificate could not be decoded.
+    #[error("A certificate could not be decoded.")]
+    MalformedCertificate,
+    /// The requested identity is not contained in the end-entity certificate.
+    #[error("The requested identity is not contained in the end-entity certificate.")]
+    MissingIdentity,
+    /// The signature key doesn't match the public key of the end-entity certificate.
+    #[error("The signature key doesn't match the public key of the end-entity certificate.")]
+    SignatureKeyMismatch,
+    /// The signature algorithm of a certificate is not supported.
+    #[error("The signature algorithm of a certificate is not supported.")]
+    UnsupportedSignatureAlgorithm,
+    /// A certificate is not valid at the current time.
+    #[error("A certificate is not valid at the current time.")]
+    InvalidValidityPeriod,
+    /// A certificate was not issued by the next certificate in the chain.
+    #[error("A certificate was not issued by the next certificate in the chain.")]
+    InvalidIssuer,
+    /// The certificate chain doesn't lead to a trust anchor.
+    #[error("The certificate chain doesn't lead to a trust anchor.")]
+    UntrustedChain,
 }
@@ -18,9 +18,14 @@
 //!
 //! When receiving a credential update from another member, applications must
 //! query the Authentication Service to ensure that the new credential is valid.
+//! OpenMLS does so through the [`CredentialValidator`] that is passed when
+//! joining a group, adding members and processing messages.
 //!
-//! There are multiple [`CredentialType`]s, although OpenMLS currently only
-//! supports the [`BasicCredential`].
+//! There are multiple [`CredentialType`]s, of which OpenMLS supports the
+//! [`BasicCredential`] and X.509 certificate chains. The signature key of a
+//! leaf with an X.509 credential must be the public key of its end-entity
+//! certificate. The certificate chain itself can be validated against the
+//! trust anchors of the application with a [`CertificateChainValidator`].
 
 use std::io::{Read, Write};
 
@@ -31,12 +36,14 @@ use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize, VLBytes};
 mod codec;
 #[cfg(test)]
 mod tests;
+mod x509;
 use errors::*;
 
 use crate::ciphersuite::SignaturePublicKey;
 
 // Public
 pub mod errors;
+pub use x509::{CertificateChainValidator, TrustAnchors, X509IdentitySource};
 
 /// CredentialType.
 ///
@@ -128,17 +135,34 @@ impl From<CredentialType> for u16 {
 
 /// X.509 Certificate.
 ///
-/// This struct contains an X.509 certificate chain.  Note that X.509
-/// certificates are not yet supported by OpenMLS.
+/// This struct contains a single DER encoded X.509 certificate. An X.509
+/// [`Credential`] contains a chain of them, starting with the end-entity
+/// certificate.
 ///
 /// ```c
 /// struct {
 ///     opaque cert_data<V>;
 /// } Certificate;
 /// ```
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[derive(
+    Debug, PartialEq, Eq, Clone, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
+)]
 pub struct Certificate {
-    cert_data: Vec<u8>,
+    cert_data: VLBytes,
+}
+
+impl Certificate {
+    /// Creates a new [`Certificate`] from a DER encoded X.509 certificate.
+    pub fn new(cert_data: Vec<u8>) -> Self {
+        Self {
+            cert_data: cert_data.into(),
+        }
+    }
+
+    /// Returns the DER encoded certificate.
+    pub fn cert_data(&self) -> &[u8] {
+        self.cert_data.as_slice()
+    }
 }
 
 /// MlsCredentialType.
@@ -148,8 +172,8 @@ pub struct Certificate {
 pub enum MlsCredentialType {
     /// A [`BasicCredential`]
     Basic(BasicCredential),
-    /// An X.509 [`Certificate`]
-    X509(Certificate),
+    /// An X.509 [`Certificate`] chain, starting with the end-entity certificate.
+    X509(Vec<Certificate>),
 }
 
 /// Credential.
@@ -204,12 +228,43 @@ impl Credential {
         Ok(credential)
     }
 
+    /// Creates and returns a new X.509 [`Credential`] from a certificate
+    /// chain, which starts with the end-entity certificate and is followed by
+    /// the certificate of its issuer and so on.
+    ///
+    /// Returns an error if the chain is empty or a certificate can't be
+    /// decoded. Note that the chain itself is not validated. See
+    /// [`Credential::validate_certificate_chain()`].
+    pub fn new_x509(certificate_chain: Vec<Certificate>) -> Result<Self, CredentialError> {
+        if certificate_chain.is_empty() {
+            return Err(X509Error::EmptyChain.into());
+        }
+        for certificate in certificate_chain.iter() {
+            certificate.parse()?;
+        }
+        Ok(Credential::from(MlsCredentialType::X509(certificate_chain)))
+    }
+
     /// Returns the identity of a given credential.
+    ///
+    /// For X.509 credentials, this is the DER encoded end-entity certificate.
+    /// Use [`Credential::x509_identity()`] to extract an identity from it.
     pub fn identity(&self) -> &[u8] {
         match &self.credential {
             MlsCredentialType::Basic(basic_credential) => basic_credential.identity.as_slice(),
-            // TODO: implement getter for identity for X509 certificates. See issue #134.
-            MlsCredentialType::X509(_) => panic!("X509 certificates are not yet implemented."),
+            MlsCredentialType::X509(certificate_chain) => certificate_chain
+                .first()
+                .map(|certificate| certificate.cert_data())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the certificate chain of an X.509 credential and `None` for
+    /// all other credentials.
+    pub fn certificate_chain(&self) -> Option<&[Certificate]> {
+        match &self.credential {
+            MlsCredentialType::X509(certificate_chain) => Some(certificate_chain),
+            _ => None,
         }
     }
 }
@@ -262,6 +317,52 @@ impl CredentialWithKey {
     }
 }
 
+/// A hook into the Authentication Service (AS) of the application.
+///
+/// The validator is invoked for every credential that is new to a group or
+/// that changes, i.e. when joining a group, when adding members and when
+/// processing proposals and commits. If it rejects a credential, the
+/// respective operation fails with a `CredentialRejected` error.
+///
+/// The trait is implemented for closures, so that simple policies don't need
+/// their own type.
+pub trait CredentialValidator {
+    /// Returns `true` if the Authentication Service accepts the `credential`
+    /// as belonging to the owner of the `signature_key`.
+    fn validate_credential(
+        &self,
+        credential: &Credential,
+        signature_key: &SignaturePublicKey,
+    ) -> bool;
+}
+
+impl<F: Fn(&Credential, &SignaturePublicKey) -> bool> CredentialValidator for F {
+    fn validate_credential(
+        &self,
+        credential: &Credential,
+        signature_key: &SignaturePublicKey,
+    ) -> bool {
+        self(credential, signature_key)
+    }
+}
+
+/// A [`CredentialValidator`] that accepts all credentials.
+///
+/// This is only appropriate if the credentials are authenticated in a
+/// different way, e.g. by the application after processing a message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AcceptAllCredentials;
+
+impl CredentialValidator for AcceptAllCredentials {
+    fn validate_credential(
+        &self,
+        _credential: &Credential,
+        _signature_key: &SignaturePublicKey,
+    ) -> bool {
+        true
+    }
+}
+
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils {
     use openmls_basic_credential::SignatureKeyPair;
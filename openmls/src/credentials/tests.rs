@@ -48,3 +48,141 @@ fn that_unknown_credential_types_are_de_serialized_correctly() {
         assert_eq!(test, got_serialized);
     }
 }
+
+/// Returns the end-entity certificate of Alice and the certificate of the CA
+/// that issued it.
+fn x509_test_certificates() -> (Certificate, Certificate) {
+    (
+        Certificate::new(include_bytes!("../../test_vectors/x509/alice.der").to_vec()),
+        Certificate::new(include_bytes!("../../test_vectors/x509/ca.der").to_vec()),
+    )
+}
+
+#[test]
+fn x509_credential_codec() {
+    let (alice_certificate, ca_certificate) = x509_test_certificates();
+    let credential = Credential::new_x509(vec![alice_certificate.clone(), ca_certificate])
+        .expect("Error creating X.509 credential.");
+    assert_eq!(credential.credential_type(), CredentialType::X509);
+    assert_eq!(credential.identity(), alice_certificate.cert_data());
+
+    let serialized = credential.tls_serialize_detached().unwrap();
+    let deserialized = Credential::tls_deserialize_exact(serialized).unwrap();
+    assert_eq!(credential, deserialized);
+
+    assert_eq!(
+        Credential::new_x509(vec![]).unwrap_err(),
+        CredentialError::X509(X509Error::EmptyChain)
+    );
+    assert_eq!(
+        Credential::new_x509(vec![Certificate::new(vec![1, 2, 3])]).unwrap_err(),
+        CredentialError::X509(X509Error::MalformedCertificate)
+    );
+}
+
+#[test]
+fn x509_identity() {
+    let (alice_certificate, _) = x509_test_certificates();
+    let credential = Credential::new_x509(vec![alice_certificate]).unwrap();
+
+    assert_eq!(
+        credential
+            .x509_identity(X509IdentitySource::SubjectAltNameUri)
+            .unwrap(),
+        b"im:alice@example.com"
+    );
+    assert_eq!(
+        credential
+            .x509_identity(X509IdentitySource::SubjectAltNameDns)
+            .unwrap(),
+        b"alice.example.com"
+    );
+    assert_eq!(
+        credential
+            .x509_identity(X509IdentitySource::SubjectAltNameEmail)
+            .unwrap(),
+        b"alice@example.com"
+    );
+    assert_eq!(
+        credential
+            .x509_identity(X509IdentitySource::Subject)
+            .unwrap(),
+        b"CN=Alice,O=OpenMLS"
+    );
+
+    let basic_credential = Credential::new(b"Alice".to_vec(), CredentialType::Basic).unwrap();
+    assert_eq!(
+        basic_credential
+            .x509_identity(X509IdentitySource::Subject)
+            .unwrap_err(),
+        CredentialError::UnsupportedCredentialType
+    );
+}
+
+#[test]
+fn x509_signature_key() {
+    let (alice_certificate, ca_certificate) = x509_test_certificates();
+    let credential = Credential::new_x509(vec![alice_certificate, ca_certificate]).unwrap();
+
+    let alice_public_key =
+        hex::decode("8f8daead0e999e96f083cab213aef2d901bebf15864d0cd023ea601e4248778e").unwrap();
+    assert!(credential.validate_signature_key(&alice_public_key).is_ok());
+    assert_eq!(
+        credential.validate_signature_key(&[0u8; 32]).unwrap_err(),
+        CredentialError::X509(X509Error::SignatureKeyMismatch)
+    );
+}
+
+#[test]
+fn x509_certificate_chain_validation() {
+    let crypto = openmls_rust_crypto::RustCrypto::default();
+    let time = openmls_traits::time::SystemClock::new();
+    let (alice_certificate, ca_certificate) = x509_test_certificates();
+    let trust_anchors = TrustAnchors::new(&[ca_certificate.clone()]).unwrap();
+
+    // The end-entity certificate is issued by the trust anchor.
+    let credential = Credential::new_x509(vec![alice_certificate.clone()]).unwrap();
+    assert!(credential
+        .validate_certificate_chain(&crypto, &time, &trust_anchors)
+        .is_ok());
+
+    // The trust anchor is part of the chain.
+    let credential =
+        Credential::new_x509(vec![alice_certificate.clone(), ca_certificate.clone()]).unwrap();
+    assert!(credential
+        .validate_certificate_chain(&crypto, &time, &trust_anchors)
+        .is_ok());
+
+    // The certificates aren't valid yet at the Unix epoch.
+    let provider = crate::test_utils::OpenMlsRustCrypto::default();
+    let epoch = crate::test_utils::FixedTimeProvider {
+        provider: &provider,
+        now: 0,
+        clock_skew_tolerance: 0,
+    };
+    assert_eq!(
+        credential
+            .validate_certificate_chain(&crypto, &epoch, &trust_anchors)
+            .unwrap_err(),
+        CredentialError::X509(X509Error::InvalidValidityPeriod)
+    );
+
+    // The chain is in the wrong order.
+    let credential = Credential::new_x509(vec![ca_certificate, alice_certificate]).unwrap();
+    assert_eq!(
+        credential
+            .validate_certificate_chain(&crypto, &time, &trust_anchors)
+            .unwrap_err(),
+        CredentialError::X509(X509Error::InvalidIssuer)
+    );
+
+    // Without trust anchors, no chain is valid.
+    let (alice_certificate, _) = x509_test_certificates();
+    let credential = Credential::new_x509(vec![alice_certificate]).unwrap();
+    assert_eq!(
+        credential
+            .validate_certificate_chain(&crypto, &time, &TrustAnchors::new(&[]).unwrap())
+            .unwrap_err(),
+        CredentialError::X509(X509Error::UntrustedChain)
+    );
+}
@@ -0,0 +1,302 @@
+//! X.509 credentials
+//!
+//! This module contains the decoding of the certificates in X.509
+//! [`Credential`]s, the extraction of identities from them, the binding of
+//! signature keys to end-entity certificates and the validation of certificate
+//! chains.
+
+use openmls_traits::{crypto::OpenMlsCrypto, time::OpenMlsTime, types::SignatureScheme};
+use x509_cert::{
+    der::{asn1::ObjectIdentifier, Decode, Encode},
+    ext::pkix::{name::GeneralName, BasicConstraints, SubjectAltName},
+    Certificate as X509Certificate,
+};
+
+use super::{errors::*, Certificate, Credential, MlsCredentialType};
+
+/// The certificate signature algorithms that can be verified with the
+/// signature schemes of MLS.
+const SIGNATURE_ALGORITHMS: [(ObjectIdentifier, SignatureScheme); 5] = [
+    // id-Ed25519
+    (
+        ObjectIdentifier::new_unwrap("1.3.101.112"),
+        SignatureScheme::ED25519,
+    ),
+    // id-Ed448
+    (
+        ObjectIdentifier::new_unwrap("1.3.101.113"),
+        SignatureScheme::ED448,
+    ),
+    // ecdsa-with-SHA256
+    (
+        ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.2"),
+        SignatureScheme::ECDSA_SECP256R1_SHA256,
+    ),
+    // ecdsa-with-SHA384
+    (
+        ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.3"),
+        SignatureScheme::ECDSA_SECP384R1_SHA384,
+    ),
+    // ecdsa-with-SHA512
+    (
+        ObjectIdentifier::new_unwrap("1.2.840.10045.4.3.4"),
+        SignatureScheme::ECDSA_SECP521R1_SHA512,
+    ),
+];
+
+/// The part of the end-entity certificate of an X.509 [`Credential`] that
+/// is used as the identity of the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum X509IdentitySource {
+    /// The first URI in the subject alternative name extension.
+    SubjectAltNameUri,
+    /// The first DNS name in the subject alternative name extension.
+    SubjectAltNameDns,
+    /// The first email address in the subject alternative name extension.
+    SubjectAltNameEmail,
+    /// The string representation of the subject as defined in RFC 4514.
+    Subject,
+}
+
+/// A hook to validate the certificate chains of X.509 [`Credential`]s, e.g.
+/// against the trust anchors of an enterprise PKI.
+///
+/// OpenMLS provides [`TrustAnchors`] as an implementation that works offline
+/// with a fixed set of trust anchors.
+pub trait CertificateChainValidator {
+    /// Validates the given `certificate_chain` at the current time of the
+    /// `time` source. The chain starts with the end-entity certificate, which
+    /// is followed by the certificate of its issuer and so on.
+    fn validate_chain(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
+        certificate_chain: &[Certificate],
+    ) -> Result<(), X509Error>;
+}
+
+/// A [`CertificateChainValidator`] that validates certificate chains against a
+/// fixed set of trust anchors.
+///
+/// A certificate chain is valid if
+/// * all certificates in it are valid at the current time of the time source,
+///   taking its clock skew tolerance into account,
+/// * each certificate was issued and signed by the next one, which must be a
+///   CA certificate, and
+/// * the last certificate is either a trust anchor or was issued and signed by
+///   a trust anchor that is valid at the current time.
+///
+/// Note that revocation is not checked.
+#[derive(Debug, Clone)]
+pub struct TrustAnchors {
+    anchors: Vec<X509Certificate>,
+}
+
+impl TrustAnchors {
+    /// Creates a new set of trust anchors from the given certificates.
+    ///
+    /// Returns an error if a certificate can't be decoded.
+    pub fn new(anchors: &[Certificate]) -> Result<Self, X509Error> {
+        let anchors = anchors
+            .iter()
+            .map(Certificate::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { anchors })
+    }
+}
+
+impl CertificateChainValidator for TrustAnchors {
+    fn validate_chain(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
+        certificate_chain: &[Certificate],
+    ) -> Result<(), X509Error> {
+        let certificate_chain = certificate_chain
+            .iter()
+            .map(Certificate::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        let last_certificate = certificate_chain.last().ok_or(X509Error::EmptyChain)?;
+
+        for certificate in certificate_chain.iter() {
+            check_validity_period(certificate, time)?;
+        }
+        for certificates in certificate_chain.windows(2) {
+            check_issuer(crypto, &certificates[0], &certificates[1])?;
+        }
+
+        if self.anchors.iter().any(|anchor| {
+            anchor == last_certificate
+                || (check_validity_period(anchor, time).is_ok()
+                    && check_issuer(crypto, last_certificate, anchor).is_ok())
+        }) {
+            Ok(())
+        } else {
+            Err(X509Error::UntrustedChain)
+        }
+    }
+}
+
+impl Certificate {
+    /// Decodes the DER encoded certificate.
+    pub(super) fn parse(&self) -> Result<X509Certificate, X509Error> {
+        X509Certificate::from_der(self.cert_data()).map_err(|_| X509Error::MalformedCertificate)
+    }
+}
+
+impl Credential {
+    /// Returns the identity of an X.509 credential, taken from its end-entity
+    /// certificate as specified by `source`.
+    ///
+    /// Returns an error if this is not an X.509 credential or if the
+    /// end-entity certificate doesn't contain the requested identity.
+    pub fn x509_identity(&self, source: X509IdentitySource) -> Result<Vec<u8>, CredentialError> {
+        let certificate = self.end_entity_certificate()?;
+        let tbs_certificate = &certificate.tbs_certificate;
+
+        if source == X509IdentitySource::Subject {
+            return Ok(tbs_certificate.subject.to_string().into_bytes());
+        }
+
+        let (_, subject_alt_name) = tbs_certificate
+            .get::<SubjectAltName>()
+            .map_err(|_| X509Error::MalformedCertificate)?
+            .ok_or(X509Error::MissingIdentity)?;
+        subject_alt_name
+            .0
+            .iter()
+            .find_map(|name| match (source, name) {
+                (
+                    X509IdentitySource::SubjectAltNameUri,
+                    GeneralName::UniformResourceIdentifier(name),
+                )
+                | (X509IdentitySource::SubjectAltNameDns, GeneralName::DnsName(name))
+                | (X509IdentitySource::SubjectAltNameEmail, GeneralName::Rfc822Name(name)) => {
+                    Some(name.as_str().as_bytes().to_vec())
+                }
+                _ => None,
+            })
+            .ok_or_else(|| X509Error::MissingIdentity.into())
+    }
+
+    /// Validates the certificate chain of an X.509 credential with the given
+    /// [`CertificateChainValidator`] at the current time of the `time` source,
+    /// e.g. [`OpenMlsProvider::time()`](openmls_traits::OpenMlsProvider::time()).
+    ///
+    /// Returns an error if this is not an X.509 credential or if the
+    /// validator rejects the certificate chain.
+    pub fn validate_certificate_chain(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
+        validator: &impl CertificateChainValidator,
+    ) -> Result<(), CredentialError> {
+        match &self.credential {
+            MlsCredentialType::X509(certificate_chain) => {
+                Ok(validator.validate_chain(crypto, time, certificate_chain)?)
+            }
+            _ => Err(CredentialError::UnsupportedCredentialType),
+        }
+    }
+
+    /// Checks that the given signature key belongs to this credential. For
+    /// X.509 credentials, it has to be the public key of the end-entity
+    /// certificate. Other credentials are not bound to a signature key.
+    pub(crate) fn validate_signature_key(
+        &self,
+        signature_key: &[u8],
+    ) -> Result<(), CredentialError> {
+        match &self.credential {
+            MlsCredentialType::X509(_) => {
+                let certificate = self.end_entity_certificate()?;
+                let public_key = certificate
+                    .tbs_certificate
+                    .subject_public_key_info
+                    .subject_public_key
+                    .as_bytes()
+                    .ok_or(X509Error::MalformedCertificate)?;
+                if public_key != signature_key {
+                    return Err(X509Error::SignatureKeyMismatch.into());
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn end_entity_certificate(&self) -> Result<X509Certificate, CredentialError> {
+        match &self.credential {
+            MlsCredentialType::X509(certificate_chain) => Ok(certificate_chain
+                .first()
+                .ok_or(X509Error::EmptyChain)?
+                .parse()?),
+            _ => Err(CredentialError::UnsupportedCredentialType),
+        }
+    }
+}
+
+/// Checks that the `certificate` is valid at the current time of the `time`
+/// source, taking its clock skew tolerance into account.
+fn check_validity_period(
+    certificate: &X509Certificate,
+    time: &dyn OpenMlsTime,
+) -> Result<(), X509Error> {
+    let validity = &certificate.tbs_certificate.validity;
+    let now = time.now();
+    let tolerance = time.clock_skew_tolerance();
+    if validity.not_before.to_unix_duration().as_secs() <= now.saturating_add(tolerance)
+        && now.saturating_sub(tolerance) <= validity.not_after.to_unix_duration().as_secs()
+    {
+        Ok(())
+    } else {
+        Err(X509Error::InvalidValidityPeriod)
+    }
+}
+
+/// Checks that the `certificate` was issued and signed by `issuer`, which must
+/// be a CA certificate.
+fn check_issuer(
+    crypto: &impl OpenMlsCrypto,
+    certificate: &X509Certificate,
+    issuer: &X509Certificate,
+) -> Result<(), X509Error> {
+    if certificate.tbs_certificate.issuer != issuer.tbs_certificate.subject {
+        return Err(X509Error::InvalidIssuer);
+    }
+    let is_ca = matches!(
+        issuer.tbs_certificate.get::<BasicConstraints>(),
+        Ok(Some((_, basic_constraints))) if basic_constraints.ca
+    );
+    if !is_ca {
+        return Err(X509Error::InvalidIssuer);
+    }
+
+    let signature_scheme = SIGNATURE_ALGORITHMS
+        .iter()
+        .find(|(oid, _)| oid == &certificate.signature_algorithm.oid)
+        .map(|(_, signature_scheme)| *signature_scheme)
+        .ok_or(X509Error::UnsupportedSignatureAlgorithm)?;
+    let tbs_certificate = certificate
+        .tbs_certificate
+        .to_der()
+        .map_err(|_| X509Error::MalformedCertificate)?;
+    let signature = certificate
+        .signature
+        .as_bytes()
+        .ok_or(X509Error::MalformedCertificate)?;
+    let issuer_public_key = issuer
+        .tbs_certificate
+        .subject_public_key_info
+        .subject_public_key
+        .as_bytes()
+        .ok_or(X509Error::MalformedCertificate)?;
+
+    crypto
+        .verify_signature(
+            signature_scheme,
+            &tbs_certificate,
+            issuer_public_key,
+            signature,
+        )
+        .map_err(|_| X509Error::InvalidIssuer)
+}
@@ -0,0 +1,756 @@
+//! # Deferred execution with asynchronous providers
+//!
+//! The operations of OpenMLS are synchronous. To run an operation with an
+//! [`AsyncOpenMlsProvider`] and an [`AsyncSigner`], it is run with a
+//! [`DeferredProvider`] and a [`DeferredSigner`] that only answer with values
+//! that are already available. Values that have to be read from the key store
+//! and signatures that were not created yet are recorded while the operation
+//! runs. They are then read from the asynchronous key store or created by the
+//! asynchronous signer before the operation is run again, until it completes
+//! without missing values.
+//!
+//! For later runs to read the same values and sign the same payloads, the
+//! randomness, the current time and the results of randomized cryptographic
+//! operations of earlier runs are replayed. Credentials are passed to the
+//! [`CredentialValidator`] only once and its results are replayed as well.
+//! The values that the operation writes to the key
+//! store are kept until it completed and, if it succeeded, are then written to the
+//! asynchronous key store in a single transaction.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use openmls_traits::{
+    crypto::OpenMlsCrypto,
+    key_store::{AsyncOpenMlsKeyStore, MlsEntity, OpenMlsKeyStore},
+    random::OpenMlsRand,
+    signatures::{AsyncSigner, Signer},
+    time::OpenMlsTime,
+    types::{
+        AeadType, Ciphersuite, CryptoError, Error, ExporterSecret, HashType, HpkeCiphertext,
+        HpkeConfig, HpkeKeyPair, KemOutput, SignatureScheme, SignatureVerification,
+    },
+    AsyncOpenMlsProvider, OpenMlsProvider,
+};
+use tls_codec::{SecretVLBytes, Serialize as TlsSerializeTrait};
+
+use crate::{
+    ciphersuite::SignaturePublicKey,
+    credentials::{Credential, CredentialValidator},
+    error::LibraryError,
+    group::errors::AsyncOperationError,
+};
+
+/// The maximum number of times an operation is run before it is aborted.
+///
+/// Every run but the last one completes at least one missing value. Signed
+/// values that contain other signatures, e.g. a commit with an update path,
+/// need one run per signature.
+const MAX_RUNS: usize = 16;
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+type PendingSignature<'a> = BoxFuture<'a, Result<Vec<u8>, Error>>;
+type PendingRead<'a> = BoxFuture<'a, Option<serde_json::Result<Vec<u8>>>>;
+
+/// The error type of the asynchronous key store of a provider.
+type AsyncKeyStoreError<Provider> =
+    <<Provider as AsyncOpenMlsProvider>::KeyStoreProvider as AsyncOpenMlsKeyStore>::Error;
+
+/// An [`OpenMlsProvider`] that defers the calls to the key store of an
+/// [`AsyncOpenMlsProvider`]. See the module documentation for more details.
+pub(crate) struct DeferredProvider<'a, Provider: AsyncOpenMlsProvider> {
+    crypto: ReplayCrypto<'a, Provider::CryptoProvider>,
+    rand: ReplayRand<'a, Provider>,
+    key_store: DeferredKeyStore<'a, Provider::KeyStoreProvider>,
+    time: ReplayTime,
+    signatures: Mutex<Signatures<'a>>,
+    credentials: Mutex<HashMap<Vec<u8>, bool>>,
+}
+
+impl<'a, Provider: AsyncOpenMlsProvider> DeferredProvider<'a, Provider> {
+    pub(crate) fn new(provider: &'a Provider) -> Self {
+        Self {
+            crypto: ReplayCrypto {
+                crypto: provider.crypto(),
+                memos: Mutex::default(),
+            },
+            rand: ReplayRand {
+                provider,
+                values: Mutex::default(),
+            },
+            key_store: DeferredKeyStore {
+                key_store: provider.key_store(),
+                state: Mutex::default(),
+            },
+            time: ReplayTime {
+                now: provider.time().now(),
+                clock_skew_tolerance: provider.time().clock_skew_tolerance(),
+            },
+            signatures: Mutex::default(),
+            credentials: Mutex::default(),
+        }
+    }
+
+    /// Returns a [`Signer`] that defers the signatures of `signer` to the runs
+    /// of this provider.
+    pub(crate) fn signer<'p, S: AsyncSigner>(&'p self, signer: &'a S) -> DeferredSigner<'p, 'a, S> {
+        DeferredSigner {
+            signer,
+            signatures: &self.signatures,
+        }
+    }
+
+    /// Returns a [`CredentialValidator`] that passes each credential to
+    /// `validator` only once in all runs of this provider.
+    pub(crate) fn credential_validator<'p, V: CredentialValidator>(
+        &'p self,
+        validator: &'p V,
+    ) -> ReplayCredentialValidator<'p, V> {
+        ReplayCredentialValidator {
+            validator,
+            results: &self.credentials,
+        }
+    }
+
+    /// Run `operation` with this provider until it completes without missing
+    /// values and, if it succeeds, write the values it stored to the
+    /// asynchronous key store.
+    ///
+    /// The values stored by a failed operation are discarded, just like a
+    /// synchronous operation rolls back the key store transaction it runs in.
+    pub(crate) async fn run<T, E>(
+        &self,
+        mut operation: impl FnMut() -> Result<T, E>,
+    ) -> Result<T, AsyncOperationError<E, AsyncKeyStoreError<Provider>>> {
+        for _ in 0..MAX_RUNS {
+            if let Some(result) = self.run_once(&mut operation) {
+                if let Some(error) = self.key_store.take_failure() {
+                    return Err(error.into());
+                }
+                let value = result.map_err(AsyncOperationError::OperationError)?;
+                self.key_store
+                    .flush()
+                    .await
+                    .map_err(AsyncOperationError::KeyStoreError)?;
+                return Ok(value);
+            }
+            self.complete_missing().await?;
+        }
+
+        Err(
+            LibraryError::custom("The operation didn't complete with the asynchronous provider")
+                .into(),
+        )
+    }
+
+    /// Run `operation` once and return its result if no values were missing.
+    fn run_once<T, E>(&self, operation: &mut impl FnMut() -> Result<T, E>) -> Option<Result<T, E>> {
+        self.rand.start_run();
+        self.key_store.start_run();
+
+        let result = operation();
+
+        let complete = lock(&self.key_store.state).missing.is_empty()
+            && lock(&self.signatures).missing.is_empty();
+        complete.then_some(result)
+    }
+
+    /// Read the missing values from the asynchronous key store and create the
+    /// missing signatures.
+    async fn complete_missing(&self) -> Result<(), LibraryError> {
+        self.key_store.fetch_missing().await?;
+
+        let missing = mem::take(&mut lock(&self.signatures).missing);
+        for (payload, signature) in missing {
+            let signature = signature.await;
+            lock(&self.signatures).signatures.insert(payload, signature);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, Provider: AsyncOpenMlsProvider> OpenMlsProvider for DeferredProvider<'a, Provider> {
+    type CryptoProvider = ReplayCrypto<'a, Provider::CryptoProvider>;
+    type RandProvider = ReplayRand<'a, Provider>;
+    type KeyStoreProvider = DeferredKeyStore<'a, Provider::KeyStoreProvider>;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        &self.crypto
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        &self.rand
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        &self.key_store
+    }
+
+    fn time(&self) -> &dyn OpenMlsTime {
+        &self.time
+    }
+}
+
+// === Signatures ===
+
+#[derive(Default)]
+struct Signatures<'a> {
+    signatures: HashMap<Vec<u8>, Result<Vec<u8>, Error>>,
+    // The payloads whose signatures were missing in the current run.
+    missing: Vec<(Vec<u8>, PendingSignature<'a>)>,
+}
+
+/// A [`Signer`] that answers with the signatures of an [`AsyncSigner`] that
+/// were created for earlier runs of a [`DeferredProvider`].
+pub(crate) struct DeferredSigner<'p, 'a, S: AsyncSigner> {
+    signer: &'a S,
+    signatures: &'p Mutex<Signatures<'a>>,
+}
+
+impl<'p, 'a, S: AsyncSigner> Signer for DeferredSigner<'p, 'a, S> {
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut signatures = lock(self.signatures);
+        if let Some(signature) = signatures.signatures.get(payload) {
+            return signature.clone();
+        }
+
+        if !signatures
+            .missing
+            .iter()
+            .any(|(missing, _)| missing == payload)
+        {
+            let signer = self.signer;
+            let owned_payload = payload.to_vec();
+            let signature: PendingSignature<'a> =
+                Box::pin(async move { AsyncSigner::sign(signer, &owned_payload).await });
+            signatures.missing.push((payload.to_vec(), signature));
+        }
+
+        Err(Error::SigningError)
+    }
+
+    fn signature_scheme(&self) -> SignatureScheme {
+        AsyncSigner::signature_scheme(self.signer)
+    }
+}
+
+// === Credentials ===
+
+/// A [`CredentialValidator`] that returns the results of earlier runs of a
+/// [`DeferredProvider`] for credentials that were already validated.
+pub(crate) struct ReplayCredentialValidator<'p, V: CredentialValidator> {
+    validator: &'p V,
+    results: &'p Mutex<HashMap<Vec<u8>, bool>>,
+}
+
+impl<'p, V: CredentialValidator> CredentialValidator for ReplayCredentialValidator<'p, V> {
+    fn validate_credential(
+        &self,
+        credential: &Credential,
+        signature_key: &SignaturePublicKey,
+    ) -> bool {
+        let mut key = Vec::new();
+        if credential.tls_serialize(&mut key).is_err() {
+            return self
+                .validator
+                .validate_credential(credential, signature_key);
+        }
+        key.extend_from_slice(signature_key.as_slice());
+
+        if let Some(result) = lock(self.results).get(&key) {
+            return *result;
+        }
+        let result = self
+            .validator
+            .validate_credential(credential, signature_key);
+        lock(self.results).insert(key, result);
+
+        result
+    }
+}
+
+// === Key store ===
+
+struct KeyStoreState<'a, KeyStoreError> {
+    // The values read from the asynchronous key store, or `None` if no value
+    // is stored for a key.
+    fetched: HashMap<Vec<u8>, Option<Vec<u8>>>,
+    // The keys whose values were missing in the current run.
+    missing: Vec<(Vec<u8>, PendingRead<'a>)>,
+    // The values stored or deleted in the current run.
+    writes: Vec<Write<'a, KeyStoreError>>,
+    // The number of writes when each of the open transactions began.
+    transactions: Vec<usize>,
+    failure: Option<LibraryError>,
+}
+
+impl<'a, KeyStoreError> Default for KeyStoreState<'a, KeyStoreError> {
+    fn default() -> Self {
+        Self {
+            fetched: HashMap::new(),
+            missing: Vec::new(),
+            writes: Vec::new(),
+            transactions: Vec::new(),
+            failure: None,
+        }
+    }
+}
+
+struct Write<'a, KeyStoreError> {
+    key: Vec<u8>,
+    // The serialized value, or `None` if the value is deleted.
+    value: Option<Vec<u8>>,
+    write: BoxFuture<'a, Result<(), KeyStoreError>>,
+}
+
+/// An [`OpenMlsKeyStore`] that answers with the values read from an
+/// [`AsyncOpenMlsKeyStore`] for earlier runs of a [`DeferredProvider`] and
+/// keeps the values that are written until the operation completed.
+///
+/// The values are serialized as JSON while they are kept.
+pub(crate) struct DeferredKeyStore<'a, KeyStore: AsyncOpenMlsKeyStore> {
+    key_store: &'a KeyStore,
+    state: Mutex<KeyStoreState<'a, KeyStore::Error>>,
+}
+
+impl<'a, KeyStore: AsyncOpenMlsKeyStore> DeferredKeyStore<'a, KeyStore> {
+    fn start_run(&self) {
+        let mut state = lock(&self.state);
+        state.missing.clear();
+        state.writes.clear();
+        state.transactions.clear();
+    }
+
+    fn take_failure(&self) -> Option<LibraryError> {
+        lock(&self.state).failure.take()
+    }
+
+    async fn fetch_missing(&self) -> Result<(), LibraryError> {
+        let missing = mem::take(&mut lock(&self.state).missing);
+        for (key, value) in missing {
+            let value = value
+                .await
+                .transpose()
+                .map_err(|_| LibraryError::custom("Failed to serialize a key store value"))?;
+            lock(&self.state).fetched.insert(key, value);
+        }
+
+        Ok(())
+    }
+
+    /// Write the values of the last run to the asynchronous key store in a
+    /// transaction.
+    async fn flush(&self) -> Result<(), KeyStore::Error> {
+        let writes = mem::take(&mut lock(&self.state).writes);
+        if writes.is_empty() {
+            return Ok(());
+        }
+
+        self.key_store.begin_transaction().await?;
+        for Write { write, .. } in writes {
+            if let Err(error) = write.await {
+                self.key_store.rollback_transaction().await?;
+                return Err(error);
+            }
+        }
+        self.key_store.commit_transaction().await
+    }
+
+    fn fail(&self, message: &'static str) {
+        lock(&self.state).failure = Some(LibraryError::custom(message));
+    }
+}
+
+impl<'a, KeyStore: AsyncOpenMlsKeyStore> OpenMlsKeyStore for DeferredKeyStore<'a, KeyStore> {
+    type Error = KeyStore::Error;
+
+    fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+        // The value is copied through its serialization, such that it can be
+        // written to the asynchronous key store later.
+        let copy = serde_json::to_vec(v).and_then(|serialized| {
+            serde_json::from_slice::<V>(&serialized).map(|value| (serialized, value))
+        });
+        let Ok((serialized, value)) = copy else {
+            self.fail("Failed to serialize a key store value");
+            return Ok(());
+        };
+
+        let key_store = self.key_store;
+        let key = k.to_vec();
+        lock(&self.state).writes.push(Write {
+            key: k.to_vec(),
+            value: Some(serialized),
+            write: Box::pin(
+                async move { AsyncOpenMlsKeyStore::store(key_store, &key, &value).await },
+            ),
+        });
+
+        Ok(())
+    }
+
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> Option<V> {
+        let mut state = lock(&self.state);
+        let value = state
+            .writes
+            .iter()
+            .rev()
+            .find(|write| write.key == k)
+            .map(|write| write.value.clone())
+            .or_else(|| state.fetched.get(k).cloned());
+
+        match value {
+            Some(value) => value.and_then(|value| serde_json::from_slice(&value).ok()),
+            None => {
+                if !state.missing.iter().any(|(key, _)| key == k) {
+                    let key_store = self.key_store;
+                    let key = k.to_vec();
+                    let value: PendingRead<'a> = Box::pin(async move {
+                        AsyncOpenMlsKeyStore::read::<V>(key_store, &key)
+                            .await
+                            .map(|value| serde_json::to_vec(&value))
+                    });
+                    state.missing.push((k.to_vec(), value));
+                }
+                None
+            }
+        }
+    }
+
+    fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+        let key_store = self.key_store;
+        let key = k.to_vec();
+        lock(&self.state).writes.push(Write {
+            key: k.to_vec(),
+            value: None,
+            write: Box::pin(
+                async move { AsyncOpenMlsKeyStore::delete::<V>(key_store, &key).await },
+            ),
+        });
+
+        Ok(())
+    }
+
+    fn begin_transaction(&self) -> Result<(), Self::Error> {
+        let mut state = lock(&self.state);
+        let writes = state.writes.len();
+        state.transactions.push(writes);
+
+        Ok(())
+    }
+
+    fn commit_transaction(&self) -> Result<(), Self::Error> {
+        lock(&self.state).transactions.pop();
+
+        Ok(())
+    }
+
+    fn rollback_transaction(&self) -> Result<(), Self::Error> {
+        let mut state = lock(&self.state);
+        if let Some(writes) = state.transactions.pop() {
+            state.writes.truncate(writes);
+        }
+
+        Ok(())
+    }
+}
+
+// === Randomness ===
+
+/// An [`OpenMlsRand`] that replays the randomness of earlier runs of a
+/// [`DeferredProvider`].
+pub(crate) struct ReplayRand<'a, Provider: AsyncOpenMlsProvider> {
+    provider: &'a Provider,
+    // The random values drawn in all runs and the number of values drawn in
+    // the current run.
+    values: Mutex<(Vec<Vec<u8>>, usize)>,
+}
+
+impl<'a, Provider: AsyncOpenMlsProvider> ReplayRand<'a, Provider> {
+    fn start_run(&self) {
+        lock(&self.values).1 = 0;
+    }
+
+    /// Returns the next random value of `len` bytes of earlier runs, or draws
+    /// a new one with `draw` if the current run diverged from them.
+    fn replay(
+        &self,
+        len: usize,
+        draw: impl FnOnce() -> Result<Vec<u8>, <Provider::RandProvider as OpenMlsRand>::Error>,
+    ) -> Result<Vec<u8>, <Provider::RandProvider as OpenMlsRand>::Error> {
+        let mut values = lock(&self.values);
+        let (values, drawn) = &mut *values;
+        let value = match values.get(*drawn) {
+            Some(value) if value.len() == len => value.clone(),
+            _ => {
+                let value = draw()?;
+                values.truncate(*drawn);
+                values.push(value.clone());
+                value
+            }
+        };
+        *drawn += 1;
+
+        Ok(value)
+    }
+}
+
+impl<'a, Provider: AsyncOpenMlsProvider> OpenMlsRand for ReplayRand<'a, Provider> {
+    type Error = <Provider::RandProvider as OpenMlsRand>::Error;
+
+    fn random_array<const N: usize>(&self) -> Result<[u8; N], Self::Error> {
+        let value = self.replay(N, || {
+            self.provider
+                .rand()
+                .random_array::<N>()
+                .map(|array| array.to_vec())
+        })?;
+        let mut array = [0u8; N];
+        array.copy_from_slice(&value);
+
+        Ok(array)
+    }
+
+    fn random_vec(&self, len: usize) -> Result<Vec<u8>, Self::Error> {
+        self.replay(len, || self.provider.rand().random_vec(len))
+    }
+}
+
+// === Time ===
+
+/// An [`OpenMlsTime`] that returns the time at which a [`DeferredProvider`]
+/// was created in all of its runs.
+pub(crate) struct ReplayTime {
+    now: u64,
+    clock_skew_tolerance: u64,
+}
+
+impl OpenMlsTime for ReplayTime {
+    fn now(&self) -> u64 {
+        self.now
+    }
+
+    fn clock_skew_tolerance(&self) -> u64 {
+        self.clock_skew_tolerance
+    }
+}
+
+// === Crypto ===
+
+#[derive(Default)]
+struct Memos {
+    hpke_seal: HashMap<Vec<u8>, HpkeCiphertext>,
+    hpke_setup_sender_and_export:
+        HashMap<Vec<u8>, Result<(KemOutput, ExporterSecret), CryptoError>>,
+    sign: HashMap<Vec<u8>, Result<Vec<u8>, CryptoError>>,
+}
+
+/// An [`OpenMlsCrypto`] that returns the results of randomized operations of
+/// earlier runs of a [`DeferredProvider`] for the same inputs.
+pub(crate) struct ReplayCrypto<'a, Crypto: OpenMlsCrypto> {
+    crypto: &'a Crypto,
+    memos: Mutex<Memos>,
+}
+
+impl<'a, Crypto: OpenMlsCrypto> ReplayCrypto<'a, Crypto> {
+    fn memoize<T: Clone>(
+        &self,
+        memo: impl Fn(&mut Memos) -> &mut HashMap<Vec<u8>, T>,
+        inputs: &[&[u8]],
+        compute: impl FnOnce() -> T,
+    ) -> T {
+        let mut key = Vec::new();
+        for input in inputs {
+            key.extend_from_slice(&(input.len() as u64).to_be_bytes());
+            key.extend_from_slice(input);
+        }
+
+        if let Some(result) = memo(&mut lock(&self.memos)).get(&key) {
+            return result.clone();
+        }
+        let result = compute();
+        memo(&mut lock(&self.memos)).insert(key, result.clone());
+
+        result
+    }
+}
+
+fn hpke_config_bytes(config: &HpkeConfig) -> [u8; 6] {
+    let mut bytes = [0u8; 6];
+    bytes[..2].copy_from_slice(&(config.0 as u16).to_be_bytes());
+    bytes[2..4].copy_from_slice(&(config.1 as u16).to_be_bytes());
+    bytes[4..].copy_from_slice(&(config.2 as u16).to_be_bytes());
+    bytes
+}
+
+impl<'a, Crypto: OpenMlsCrypto> OpenMlsCrypto for ReplayCrypto<'a, Crypto> {
+    fn supports(&self, ciphersuite: Ciphersuite) -> Result<(), CryptoError> {
+        self.crypto.supports(ciphersuite)
+    }
+
+    fn supported_ciphersuites(&self) -> Vec<Ciphersuite> {
+        self.crypto.supported_ciphersuites()
+    }
+
+    fn hkdf_extract(
+        &self,
+        hash_type: HashType,
+        salt: &[u8],
+        ikm: &[u8],
+    ) -> Result<SecretVLBytes, CryptoError> {
+        self.crypto.hkdf_extract(hash_type, salt, ikm)
+    }
+
+    fn hkdf_expand(
+        &self,
+        hash_type: HashType,
+        prk: &[u8],
+        info: &[u8],
+        okm_len: usize,
+    ) -> Result<SecretVLBytes, CryptoError> {
+        self.crypto.hkdf_expand(hash_type, prk, info, okm_len)
+    }
+
+    fn hash(&self, hash_type: HashType, data: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        self.crypto.hash(hash_type, data)
+    }
+
+    fn aead_encrypt(
+        &self,
+        alg: AeadType,
+        key: &[u8],
+        data: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        self.crypto.aead_encrypt(alg, key, data, nonce, aad)
+    }
+
+    fn aead_decrypt(
+        &self,
+        alg: AeadType,
+        key: &[u8],
+        ct_tag: &[u8],
+        nonce: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        self.crypto.aead_decrypt(alg, key, ct_tag, nonce, aad)
+    }
+
+    fn signature_key_gen(&self, alg: SignatureScheme) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+        self.crypto.signature_key_gen(alg)
+    }
+
+    fn verify_signature(
+        &self,
+        alg: SignatureScheme,
+        data: &[u8],
+        pk: &[u8],
+        signature: &[u8],
+    ) -> Result<(), CryptoError> {
+        self.crypto.verify_signature(alg, data, pk, signature)
+    }
+
+    fn batch_verify_signatures(
+        &self,
+        alg: SignatureScheme,
+        signatures: &[SignatureVerification<'_>],
+    ) -> Result<(), CryptoError> {
+        self.crypto.batch_verify_signatures(alg, signatures)
+    }
+
+    fn sign(&self, alg: SignatureScheme, data: &[u8], key: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        self.memoize(
+            |memos| &mut memos.sign,
+            &[&(alg as u16).to_be_bytes(), data, key],
+            || self.crypto.sign(alg, data, key),
+        )
+    }
+
+    fn hpke_seal(
+        &self,
+        config: HpkeConfig,
+        pk_r: &[u8],
+        info: &[u8],
+        aad: &[u8],
+        ptxt: &[u8],
+    ) -> HpkeCiphertext {
+        self.memoize(
+            |memos| &mut memos.hpke_seal,
+            &[&hpke_config_bytes(&config), pk_r, info, aad, ptxt],
+            || self.crypto.hpke_seal(config, pk_r, info, aad, ptxt),
+        )
+    }
+
+    fn hpke_open(
+        &self,
+        config: HpkeConfig,
+        input: &HpkeCiphertext,
+        sk_r: &[u8],
+        info: &[u8],
+        aad: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        self.crypto.hpke_open(config, input, sk_r, info, aad)
+    }
+
+    fn hpke_setup_sender_and_export(
+        &self,
+        config: HpkeConfig,
+        pk_r: &[u8],
+        info: &[u8],
+        exporter_context: &[u8],
+        exporter_length: usize,
+    ) -> Result<(KemOutput, ExporterSecret), CryptoError> {
+        self.memoize(
+            |memos| &mut memos.hpke_setup_sender_and_export,
+            &[
+                &hpke_config_bytes(&config),
+                pk_r,
+                info,
+                exporter_context,
+                &(exporter_length as u64).to_be_bytes(),
+            ],
+            || {
+                self.crypto.hpke_setup_sender_and_export(
+                    config,
+                    pk_r,
+                    info,
+                    exporter_context,
+                    exporter_length,
+                )
+            },
+        )
+    }
+
+    fn hpke_setup_receiver_and_export(
+        &self,
+        config: HpkeConfig,
+        enc: &[u8],
+        sk_r: &[u8],
+        info: &[u8],
+        exporter_context: &[u8],
+        exporter_length: usize,
+    ) -> Result<ExporterSecret, CryptoError> {
+        self.crypto.hpke_setup_receiver_and_export(
+            config,
+            enc,
+            sk_r,
+            info,
+            exporter_context,
+            exporter_length,
+        )
+    }
+
+    fn derive_hpke_keypair(&self, config: HpkeConfig, ikm: &[u8]) -> HpkeKeyPair {
+        self.crypto.derive_hpke_keypair(config, ikm)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,90 @@
+use tls_codec::{Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait};
+
+use super::{Extension, ExtensionError, ExtensionType, Extensions, UnknownExtension};
+
+/// # Custom Extension
+///
+/// Applications can define their own extensions by implementing this trait for
+/// a type with a TLS encoding. On the wire, a custom extension is an
+/// [`Extension::Unknown`] with the extension type [`Self::EXTENSION_TYPE`] and
+/// the TLS encoding of the type as extension data.
+///
+/// Custom extensions can be used anywhere [`Extensions`] are used, i.e., in
+/// key packages, leaf nodes and the group context. Typed values can be read
+/// with [`Extensions::custom()`] and added with [`Extensions::add_custom()`].
+/// Custom extensions that are required by a group, e.g. in the
+/// [`RequiredCapabilitiesExtension`](super::RequiredCapabilitiesExtension) of
+/// a new group or of a group that is joined, must be listed in the extensions
+/// of the [`Capabilities`](crate::treesync::node::leaf_node::Capabilities) of
+/// the own leaf node, e.g. with
+/// [`MlsGroupConfigBuilder::leaf_node_capabilities()`](crate::group::MlsGroupConfigBuilder::leaf_node_capabilities())
+/// or in the key package that is used to join the group.
+///
+/// The extension type must not be one defined by the MLS specification.
+/// Applications should use an extension type in the range `0xff00 - 0xffff`,
+/// which is reserved for private use.
+pub trait CustomExtension: TlsSerializeTrait + TlsDeserializeTrait + Sized {
+    /// The extension type of this extension.
+    const EXTENSION_TYPE: u16;
+
+    /// Returns the [`ExtensionType`] of this extension.
+    fn extension_type() -> ExtensionType {
+        ExtensionType::from(Self::EXTENSION_TYPE)
+    }
+}
+
+impl Extension {
+    /// Create an [`Extension`] from a [`CustomExtension`].
+    /// Returns an [`ExtensionError::ReservedExtensionType`] if the extension
+    /// type of `T` is defined by the MLS specification.
+    pub fn custom<T: CustomExtension>(extension: &T) -> Result<Self, ExtensionError> {
+        match T::extension_type() {
+            ExtensionType::Unknown(extension_type) => Ok(Self::Unknown(
+                extension_type,
+                UnknownExtension(extension.tls_serialize_detached()?),
+            )),
+            _ => Err(ExtensionError::ReservedExtensionType),
+        }
+    }
+
+    /// Decode this extension as the [`CustomExtension`] `T`.
+    /// Returns an [`ExtensionError::InvalidExtensionType`] if called on an
+    /// [`Extension`] with another extension type and an
+    /// [`ExtensionError::CustomExtensionCodec`] if the extension data is not a
+    /// valid encoding of `T`.
+    pub fn as_custom<T: CustomExtension>(&self) -> Result<T, ExtensionError> {
+        match self {
+            Self::Unknown(extension_type, UnknownExtension(data))
+                if *extension_type == T::EXTENSION_TYPE =>
+            {
+                Ok(T::tls_deserialize_exact(data)?)
+            }
+            _ => Err(ExtensionError::InvalidExtensionType(
+                "This is not the requested custom extension".into(),
+            )),
+        }
+    }
+}
+
+impl Extensions {
+    /// Get the [`CustomExtension`] `T` if there is any.
+    ///
+    /// Returns an error if the extension data is not a valid encoding of `T`.
+    pub fn custom<T: CustomExtension>(&self) -> Result<Option<T>, ExtensionError> {
+        self.iter()
+            .find(|ext| ext.extension_type() == T::extension_type())
+            .map(Extension::as_custom)
+            .transpose()
+    }
+
+    /// Add a [`CustomExtension`] to the extension list.
+    ///
+    /// Returns an error when there already is an extension with the same
+    /// extension type or when the extension type of `T` is defined by the MLS
+    /// specification.
+    pub fn add_custom<T: CustomExtension>(&mut self, extension: &T) -> Result<(), ExtensionError> {
+        self.add(Extension::custom(extension)?)?;
+
+        Ok(())
+    }
+}
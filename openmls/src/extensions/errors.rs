@@ -22,6 +22,9 @@ pub enum ExtensionError {
     /// Unsupported extension type in required capabilities.
     #[error("Unsupported extension type in required capabilities.")]
     UnsupportedExtensionType,
+    /// The extension type of a custom extension is defined by the MLS specification.
+    #[error("The extension type of a custom extension is defined by the MLS specification.")]
+    ReservedExtensionType,
     /// See [`LibraryError`] for more details.
     #[error(transparent)]
     LibraryError(#[from] LibraryError),
@@ -43,6 +46,9 @@ pub enum ExtensionError {
     /// See [`InvalidExtensionError`] for more details.
     #[error(transparent)]
     InvalidExtension(#[from] InvalidExtensionError),
+    /// See [`tls_codec::Error`] for more details.
+    #[error(transparent)]
+    CustomExtensionCodec(#[from] tls_codec::Error),
 }
 
 /// Capabilities extension error
@@ -0,0 +1,47 @@
+use std::io::{Read, Write};
+
+use tls_codec::{Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, Size};
+
+use super::{Deserialize, Serialize};
+
+/// # Last Resort
+///
+/// The last resort extension marks a [`KeyPackage`](crate::key_packages::KeyPackage)
+/// that the delivery service hands out when a client has no other key package
+/// left. Last resort key packages may be used to join several groups and
+/// their private keys are therefore kept in the key store after joining a
+/// group.
+///
+/// The extension has no content.
+///
+/// ```c
+/// // draft-ietf-mls-extensions-03
+/// struct {} LastResort;
+/// ```
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct LastResortExtension {}
+
+impl LastResortExtension {
+    /// Create a new `last_resort` extension.
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Size for LastResortExtension {
+    fn tls_serialized_len(&self) -> usize {
+        0
+    }
+}
+
+impl TlsSerializeTrait for LastResortExtension {
+    fn tls_serialize<W: Write>(&self, _writer: &mut W) -> Result<usize, tls_codec::Error> {
+        Ok(0)
+    }
+}
+
+impl TlsDeserializeTrait for LastResortExtension {
+    fn tls_deserialize<R: Read>(_bytes: &mut R) -> Result<Self, tls_codec::Error> {
+        Ok(Self {})
+    }
+}
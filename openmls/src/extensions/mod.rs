@@ -18,6 +18,10 @@
 //! - [`RatchetTreeExtension`] (GroupInfo extension)
 //! - [`RequiredCapabilitiesExtension`] (GroupContext extension)
 //! - [`ExternalPubExtension`] (GroupInfo extension)
+//! - [`LastResortExtension`] (KeyPackage extension)
+//!
+//! Applications can define their own extensions by implementing
+//! [`CustomExtension`].
 
 use std::{
     fmt::Debug,
@@ -29,8 +33,10 @@ use serde::{Deserialize, Serialize};
 // Private
 mod application_id_extension;
 mod codec;
+mod custom_extension;
 mod external_pub_extension;
 mod external_sender_extension;
+mod last_resort_extension;
 mod ratchet_tree_extension;
 mod required_capabilities;
 use errors::*;
@@ -40,10 +46,12 @@ pub mod errors;
 
 // Public re-exports
 pub use application_id_extension::ApplicationIdExtension;
+pub use custom_extension::CustomExtension;
 pub use external_pub_extension::ExternalPubExtension;
 pub use external_sender_extension::{
     ExternalSender, ExternalSendersExtension, SenderExtensionIndex,
 };
+pub use last_resort_extension::LastResortExtension;
 pub use ratchet_tree_extension::RatchetTreeExtension;
 pub use required_capabilities::RequiredCapabilitiesExtension;
 
@@ -62,6 +70,7 @@ mod test_extensions;
 /// | 0x0003           | required_capabilities    | GC         | Y           | RFC XXXX  |
 /// | 0x0004           | external_pub             | GI         | Y           | RFC XXXX  |
 /// | 0x0005           | external_senders         | GC         | Y           | RFC XXXX  |
+/// | 0x000A           | last_resort              | KP         | Y           | draft-ietf-mls-extensions |
 /// | 0xff00  - 0xffff | Reserved for Private Use | N/A        | N/A         | RFC XXXX  |
 ///
 /// Note: OpenMLS does not provide a `Reserved` variant in [ExtensionType].
@@ -87,6 +96,10 @@ pub enum ExtensionType {
     /// of senders that are permitted to send external proposals to the group.
     ExternalSenders,
 
+    /// KeyPackage extension that marks a key package as a last resort key
+    /// package, which may be used more than once.
+    LastResort,
+
     /// A currently unknown extension type.
     Unknown(u16),
 }
@@ -125,6 +138,7 @@ impl From<u16> for ExtensionType {
             3 => ExtensionType::RequiredCapabilities,
             4 => ExtensionType::ExternalPub,
             5 => ExtensionType::ExternalSenders,
+            10 => ExtensionType::LastResort,
             unknown => ExtensionType::Unknown(unknown),
         }
     }
@@ -138,6 +152,7 @@ impl From<ExtensionType> for u16 {
             ExtensionType::RequiredCapabilities => 3,
             ExtensionType::ExternalPub => 4,
             ExtensionType::ExternalSenders => 5,
+            ExtensionType::LastResort => 10,
             ExtensionType::Unknown(unknown) => unknown,
         }
     }
@@ -153,6 +168,7 @@ impl ExtensionType {
                 | ExtensionType::RequiredCapabilities
                 | ExtensionType::ExternalPub
                 | ExtensionType::ExternalSenders
+                | ExtensionType::LastResort
         )
     }
 }
@@ -188,7 +204,10 @@ pub enum Extension {
     /// A [`ExternalPubExtension`]
     ExternalSenders(ExternalSendersExtension),
 
-    /// A currently unknown extension.
+    /// A [`LastResortExtension`]
+    LastResort(LastResortExtension),
+
+    /// A currently unknown extension or a [`CustomExtension`].
     Unknown(u16, UnknownExtension),
 }
 
@@ -361,6 +380,15 @@ impl Extensions {
                 _ => None,
             })
     }
+
+    /// Get a reference to the [`LastResortExtension`] if there is any.
+    pub fn last_resort(&self) -> Option<&LastResortExtension> {
+        self.find_by_type(ExtensionType::LastResort)
+            .and_then(|e| match e {
+                Extension::LastResort(e) => Some(e),
+                _ => None,
+            })
+    }
 }
 
 impl Extension {
@@ -428,6 +456,18 @@ impl Extension {
         }
     }
 
+    /// Get a reference to this extension as [`LastResortExtension`].
+    /// Returns an [`ExtensionError::InvalidExtensionType`] error if called on an
+    /// [`Extension`] that's not a [`LastResortExtension`].
+    pub fn as_last_resort_extension(&self) -> Result<&LastResortExtension, ExtensionError> {
+        match self {
+            Self::LastResort(e) => Ok(e),
+            _ => Err(ExtensionError::InvalidExtensionType(
+                "This is not a LastResortExtension".into(),
+            )),
+        }
+    }
+
     /// Returns the [`ExtensionType`]
     #[inline]
     pub const fn extension_type(&self) -> ExtensionType {
@@ -437,6 +477,7 @@ impl Extension {
             Extension::RequiredCapabilities(_) => ExtensionType::RequiredCapabilities,
             Extension::ExternalPub(_) => ExtensionType::ExternalPub,
             Extension::ExternalSenders(_) => ExtensionType::ExternalSenders,
+            Extension::LastResort(_) => ExtensionType::LastResort,
             Extension::Unknown(kind, _) => ExtensionType::Unknown(*kind),
         }
     }
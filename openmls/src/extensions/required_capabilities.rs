@@ -75,15 +75,19 @@ impl RequiredCapabilitiesExtension {
         self.credential_types.as_slice()
     }
 
-    /// Check if all extension and proposal types are supported.
+    /// Check if all extension and proposal types are supported by OpenMLS.
+    /// Unknown extension and proposal types are supported as custom
+    /// extensions and custom proposals. Whether a member supports them is
+    /// determined by the [`Capabilities`](crate::treesync::node::leaf_node::Capabilities)
+    /// of its leaf node.
     pub(crate) fn check_support(&self) -> Result<(), ExtensionError> {
         for extension in self.extension_types() {
-            if !extension.is_supported() {
+            if !extension.is_supported() && !matches!(extension, ExtensionType::Unknown(_)) {
                 return Err(ExtensionError::UnsupportedExtensionType);
             }
         }
         for proposal in self.proposal_types() {
-            if !proposal.is_supported() {
+            if !proposal.is_supported() && !matches!(proposal, ProposalType::Unknown(_)) {
                 return Err(ExtensionError::UnsupportedProposalType);
             }
         }
@@ -3,17 +3,18 @@
 //! Proper testing is done through the public APIs.
 
 use openmls_rust_crypto::OpenMlsRustCrypto;
-use tls_codec::{Deserialize, Serialize};
+use tls_codec::{Deserialize, Serialize, TlsDeserialize, TlsSerialize, TlsSize, VLBytes};
 
 use super::*;
 use crate::{
     credentials::*,
     framing::*,
     group::{errors::*, *},
-    key_packages::*,
+    key_packages::{errors::KeyPackageExtensionSupportError, *},
     messages::proposals::ProposalType,
     schedule::psk::store::ResumptionPskStore,
     test_utils::*,
+    treesync::node::leaf_node::Capabilities,
 };
 
 #[test]
@@ -243,3 +244,197 @@ fn required_capabilities() {
     assert_eq!(ext, ext_decoded);
     assert_eq!(extension_bytes, encoded);
 }
+
+/// A custom extension carrying an application-defined group name.
+#[derive(Debug, PartialEq, Eq, Clone, TlsSerialize, TlsDeserialize, TlsSize)]
+struct GroupNameExtension {
+    version: u8,
+    name: VLBytes,
+}
+
+impl CustomExtension for GroupNameExtension {
+    const EXTENSION_TYPE: u16 = 0xff00;
+}
+
+/// A custom extension that (wrongly) uses the extension type of the
+/// application id extension.
+#[derive(Debug, PartialEq, Eq, Clone, TlsSerialize, TlsDeserialize, TlsSize)]
+struct ReservedTypeExtension {
+    value: u8,
+}
+
+impl CustomExtension for ReservedTypeExtension {
+    const EXTENSION_TYPE: u16 = 0x0001;
+}
+
+#[test]
+fn custom_extension() {
+    let group_name = GroupNameExtension {
+        version: 1,
+        name: b"ab".to_vec().into(),
+    };
+    let extension_bytes = vec![0xffu8, 0x00, 4, 1, 2, b'a', b'b'];
+
+    // Encoding creates the expected bytes.
+    let ext = Extension::custom(&group_name).expect("error creating custom extension");
+    assert_eq!(ext.extension_type(), ExtensionType::Unknown(0xff00));
+    assert_eq!(
+        extension_bytes,
+        ext.tls_serialize_detached()
+            .expect("error encoding custom extension")
+    );
+
+    // Decoding yields an unknown extension that can be read as the typed value.
+    let ext_decoded = Extension::tls_deserialize(&mut extension_bytes.as_slice())
+        .expect("error decoding custom extension");
+    assert_eq!(ext, ext_decoded);
+    assert_eq!(
+        ext_decoded
+            .as_custom::<GroupNameExtension>()
+            .expect("error reading custom extension"),
+        group_name
+    );
+
+    // Typed access through the extension list.
+    let mut extensions = Extensions::empty();
+    assert_eq!(extensions.custom::<GroupNameExtension>(), Ok(None));
+    extensions
+        .add_custom(&group_name)
+        .expect("error adding custom extension");
+    assert_eq!(
+        extensions.custom::<GroupNameExtension>(),
+        Ok(Some(group_name.clone()))
+    );
+    assert_eq!(
+        extensions.add_custom(&group_name),
+        Err(ExtensionError::InvalidExtension(
+            InvalidExtensionError::Duplicate
+        ))
+    );
+
+    // Extension types defined by the specification can't be used.
+    assert_eq!(
+        Extension::custom(&ReservedTypeExtension { value: 1 }),
+        Err(ExtensionError::ReservedExtensionType)
+    );
+
+    // Extension data that doesn't decode as the typed value is rejected.
+    let invalid = Extension::Unknown(0xff00, UnknownExtension(vec![1, 2, b'a', b'b', 0]));
+    assert!(matches!(
+        invalid.as_custom::<GroupNameExtension>(),
+        Err(ExtensionError::CustomExtensionCodec(_))
+    ));
+    assert!(matches!(
+        Extension::RequiredCapabilities(RequiredCapabilitiesExtension::default())
+            .as_custom::<GroupNameExtension>(),
+        Err(ExtensionError::InvalidExtensionType(_))
+    ));
+
+    // OpenMLS supports unknown extension types as custom extensions. Whether
+    // members support them is determined by their capabilities.
+    let required_capabilities =
+        RequiredCapabilitiesExtension::new(&[GroupNameExtension::extension_type()], &[], &[]);
+    assert!(required_capabilities.check_support().is_ok());
+}
+
+#[apply(ciphersuites_and_providers)]
+fn custom_extension_support(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (credential_with_key, signer) = test_utils::new_credential(
+        provider,
+        b"Alice",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let group_name = GroupNameExtension {
+        version: 1,
+        name: b"Test Group".to_vec().into(),
+    };
+    let mut leaf_node_extensions = Extensions::empty();
+    leaf_node_extensions
+        .add_custom(&group_name)
+        .expect("error adding custom extension");
+
+    // A key package that lists the custom extension in its capabilities.
+    let key_package = KeyPackage::builder()
+        .leaf_node_capabilities(Capabilities::new(
+            None,
+            Some(&[ciphersuite]),
+            Some(&[GroupNameExtension::extension_type()]),
+            None,
+            None,
+        ))
+        .leaf_node_extensions(leaf_node_extensions)
+        .build(
+            config::CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &signer,
+            credential_with_key.clone(),
+        )
+        .expect("error creating key package");
+
+    assert_eq!(
+        key_package
+            .leaf_node()
+            .extensions()
+            .custom::<GroupNameExtension>(),
+        Ok(Some(group_name))
+    );
+    assert!(key_package
+        .check_extension_support(&[GroupNameExtension::extension_type()])
+        .is_ok());
+
+    // A key package without the capability doesn't support the extension.
+    let key_package = KeyPackage::builder()
+        .build(
+            config::CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &signer,
+            credential_with_key.clone(),
+        )
+        .expect("error creating key package");
+
+    assert_eq!(
+        key_package.check_extension_support(&[GroupNameExtension::extension_type()]),
+        Err(KeyPackageExtensionSupportError::UnsupportedExtension)
+    );
+
+    // A group can only require the extension if it is in the capabilities of
+    // the own leaf node.
+    let required_capabilities =
+        RequiredCapabilitiesExtension::new(&[GroupNameExtension::extension_type()], &[], &[]);
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(config::CryptoConfig::with_default_version(ciphersuite))
+        .required_capabilities(required_capabilities.clone())
+        .leaf_node_capabilities(Capabilities::new(
+            None,
+            Some(&[ciphersuite]),
+            None,
+            None,
+            None,
+        ))
+        .build();
+    assert_eq!(
+        MlsGroup::new(
+            provider,
+            &signer,
+            &mls_group_config,
+            credential_with_key.clone()
+        )
+        .expect_err("created a group that requires an unsupported extension"),
+        NewGroupError::UnsupportedExtensionType
+    );
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .crypto_config(config::CryptoConfig::with_default_version(ciphersuite))
+        .required_capabilities(required_capabilities)
+        .leaf_node_capabilities(Capabilities::new(
+            None,
+            Some(&[ciphersuite]),
+            Some(&[GroupNameExtension::extension_type()]),
+            None,
+            None,
+        ))
+        .build();
+    MlsGroup::new(provider, &signer, &mls_group_config, credential_with_key)
+        .expect("error creating a group that requires a custom extension");
+}
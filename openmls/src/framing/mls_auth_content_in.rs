@@ -9,7 +9,7 @@
 
 use std::io::Read;
 
-use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+use openmls_traits::{crypto::OpenMlsCrypto, time::OpenMlsTime, types::Ciphersuite};
 use tls_codec::Serialize as TlsSerializeTrait;
 
 use super::{mls_auth_content::*, mls_content_in::*, *};
@@ -54,6 +54,7 @@ impl AuthenticatedContentIn {
         self,
         ciphersuite: Ciphersuite,
         crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
         sender_context: Option<SenderContext>,
         protocol_version: ProtocolVersion,
     ) -> Result<AuthenticatedContent, ValidationError> {
@@ -62,6 +63,7 @@ impl AuthenticatedContentIn {
             content: self.content.validate(
                 ciphersuite,
                 crypto,
+                time,
                 sender_context,
                 protocol_version,
             )?,
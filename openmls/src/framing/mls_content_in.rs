@@ -18,7 +18,7 @@ use super::{
     ContentType, Sender, WireFormat,
 };
 
-use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+use openmls_traits::{crypto::OpenMlsCrypto, time::OpenMlsTime, types::Ciphersuite};
 use serde::{Deserialize, Serialize};
 use tls_codec::{
     Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, Size, TlsDeserialize,
@@ -53,6 +53,7 @@ impl FramedContentIn {
         self,
         ciphersuite: Ciphersuite,
         crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
         sender_context: Option<SenderContext>,
         protocol_version: ProtocolVersion,
     ) -> Result<FramedContent, ValidationError> {
@@ -61,9 +62,13 @@ impl FramedContentIn {
             epoch: self.epoch,
             sender: self.sender,
             authenticated_data: self.authenticated_data,
-            body: self
-                .body
-                .validate(ciphersuite, crypto, sender_context, protocol_version)?,
+            body: self.body.validate(
+                ciphersuite,
+                crypto,
+                time,
+                sender_context,
+                protocol_version,
+            )?,
         })
     }
 }
@@ -133,20 +138,28 @@ impl FramedContentBodyIn {
         self,
         ciphersuite: Ciphersuite,
         crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
         sender_context: Option<SenderContext>,
         protocol_version: ProtocolVersion,
     ) -> Result<FramedContentBody, ValidationError> {
         Ok(match self {
             FramedContentBodyIn::Application(bytes) => FramedContentBody::Application(bytes),
-            FramedContentBodyIn::Proposal(proposal_in) => FramedContentBody::Proposal(
-                proposal_in.validate(crypto, ciphersuite, sender_context, protocol_version)?,
-            ),
+            FramedContentBodyIn::Proposal(proposal_in) => {
+                FramedContentBody::Proposal(proposal_in.validate(
+                    crypto,
+                    time,
+                    ciphersuite,
+                    sender_context,
+                    protocol_version,
+                )?)
+            }
             FramedContentBodyIn::Commit(commit_in) => {
                 let sender_context = sender_context
                     .ok_or(LibraryError::custom("Forgot the commit sender context"))?;
                 FramedContentBody::Commit(commit_in.validate(
                     ciphersuite,
                     crypto,
+                    time,
                     sender_context,
                     protocol_version,
                 )?)
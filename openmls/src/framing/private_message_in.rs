@@ -169,6 +169,12 @@ impl PrivateMessageIn {
         // Prepare the nonce by xoring with the reuse guard.
         let prepared_nonce = ratchet_nonce.xor_with_reuse_guard(&sender_data.reuse_guard);
         let private_message_content = self.decrypt(crypto, ratchet_key, &prepared_nonce)?;
+        // Only messages that were decrypted successfully count as received,
+        // e.g. for AppAck proposals.
+        message_secrets
+            .secret_tree_mut()
+            .mark_received(sender_index, secret_type, sender_data.generation)
+            .map_err(|_| LibraryError::custom("The decryption ratchet was just used."))?;
 
         // Extract sender. The sender type is always of type Member for PrivateMessage.
         let sender = Sender::from_sender_data(sender_data);
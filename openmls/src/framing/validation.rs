@@ -23,7 +23,7 @@
 //! ```
 // TODO #106/#151: Update the above diagram
 
-use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+use openmls_traits::{crypto::OpenMlsCrypto, time::OpenMlsTime, types::Ciphersuite};
 
 use crate::{
     binary_tree::LeafNodeIndex,
@@ -271,14 +271,20 @@ impl UnverifiedMessage {
         self,
         ciphersuite: Ciphersuite,
         crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
         protocol_version: ProtocolVersion,
     ) -> Result<(AuthenticatedContent, Credential), ProcessMessageError> {
         let content: AuthenticatedContentIn = self
             .verifiable_content
             .verify(crypto, &self.sender_pk)
             .map_err(|_| ProcessMessageError::InvalidSignature)?;
-        let content =
-            content.validate(ciphersuite, crypto, self.sender_context, protocol_version)?;
+        let content = content.validate(
+            ciphersuite,
+            crypto,
+            time,
+            self.sender_context,
+            protocol_version,
+        )?;
         Ok((content, self.credential))
     }
 
@@ -6,7 +6,7 @@
 use openmls_traits::types::Ciphersuite;
 use serde::{Deserialize, Serialize};
 
-use crate::versions::ProtocolVersion;
+use crate::{persistence::Persist, versions::ProtocolVersion};
 
 /// A config struct for commonly used values when performing cryptographic
 /// operations.
@@ -38,3 +38,17 @@ impl Default for CryptoConfig {
         }
     }
 }
+
+impl Persist for CryptoConfig {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.ciphersuite.persist(writer)?;
+        self.version.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            ciphersuite: Ciphersuite::restore(bytes)?,
+            version: ProtocolVersion::restore(bytes)?,
+        })
+    }
+}
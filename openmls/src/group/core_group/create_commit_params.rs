@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     credentials::CredentialWithKey, framing::FramingParameters, group::ProposalStore,
-    messages::proposals::Proposal,
+    messages::proposals::Proposal, schedule::PreSharedKeyId,
 };
 
 #[cfg(doc)]
@@ -24,6 +24,7 @@ pub(crate) struct CreateCommitParams<'a> {
     force_self_update: bool,                        // Optional
     commit_type: CommitType,                        // Optional (default is `Member`)
     credential_with_key: Option<CredentialWithKey>, // Mandatory for external commits
+    injected_psks: Vec<PreSharedKeyId>,             // Optional
 }
 
 pub(crate) struct TempBuilderCCPM0 {}
@@ -58,6 +59,7 @@ impl<'a> TempBuilderCCPM1<'a> {
                 force_self_update: true,
                 commit_type: CommitType::Member,
                 credential_with_key: None,
+                injected_psks: vec![],
             },
         }
     }
@@ -81,6 +83,13 @@ impl<'a> CreateCommitParamsBuilder<'a> {
         self.ccp.credential_with_key = Some(credential_with_key);
         self
     }
+    /// Inject PSKs into the key schedule that are not covered by a
+    /// PreSharedKey proposal, e.g. the resumption PSK linking a group to the
+    /// group it was reinitialized from.
+    pub(crate) fn injected_psks(mut self, injected_psks: Vec<PreSharedKeyId>) -> Self {
+        self.ccp.injected_psks = injected_psks;
+        self
+    }
     pub(crate) fn build(self) -> CreateCommitParams<'a> {
         self.ccp
     }
@@ -108,4 +117,7 @@ impl<'a> CreateCommitParams<'a> {
     pub(crate) fn take_credential_with_key(&mut self) -> Option<CredentialWithKey> {
         self.credential_with_key.take()
     }
+    pub(crate) fn injected_psks(&self) -> &[PreSharedKeyId] {
+        &self.injected_psks
+    }
 }
@@ -5,6 +5,7 @@ use serde::{self, Deserialize, Serialize};
 use tls_codec::{Deserialize as TlsDeserialize, Serialize as TlsSerialize};
 
 use crate::{
+    credentials::AcceptAllCredentials,
     framing::{MlsMessageIn, MlsMessageInBody, MlsMessageOut, ProcessedMessageContent},
     group::{config::CryptoConfig, *},
     key_packages::*,
@@ -289,6 +290,7 @@ impl PassiveClient {
             &self.group_config,
             mls_message_welcome.into_welcome().unwrap(),
             ratchet_tree,
+            &AcceptAllCredentials,
         )
         .unwrap();
 
@@ -301,7 +303,11 @@ impl PassiveClient {
             .group
             .as_mut()
             .unwrap()
-            .process_message(&self.provider, message.into_protocol_message().unwrap())
+            .process_message(
+                &self.provider,
+                message.into_protocol_message().unwrap(),
+                &AcceptAllCredentials,
+            )
             .unwrap();
 
         match processed_message.into_content() {
@@ -366,6 +372,7 @@ pub fn generate_test_vector(cipher_suite: Ciphersuite) -> PassiveClientWelcomeTe
             &creator_provider,
             &creator.signature_keypair,
             &[passive.key_package.clone()],
+            &AcceptAllCredentials,
         )
         .unwrap();
 
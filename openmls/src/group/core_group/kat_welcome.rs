@@ -158,6 +158,10 @@ pub fn run_test_vector(test_vector: WelcomeTestVector) -> Result<(), &'static st
         println!("Unsupported ciphersuite.");
         return Ok(());
     }
+    if !vector_signatures_verifiable(cipher_suite) {
+        println!("The signatures of the test vector can't be verified.");
+        return Ok(());
+    }
 
     // ---------------------------------------------------------------------------------------------
 
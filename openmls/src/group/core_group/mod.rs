@@ -5,12 +5,10 @@
 //! This means that some functions that are not expected to fail and throw an
 //! error, will still return a `Result` since they may throw a `LibraryError`.
 
-// Private
-mod new_from_welcome;
-
 // Crate
 pub(crate) mod create_commit_params;
 pub(crate) mod new_from_external_init;
+pub(crate) mod new_from_welcome;
 pub(crate) mod past_secrets;
 pub(crate) mod process;
 pub(crate) mod proposals;
@@ -46,8 +44,8 @@ use self::{
 use super::{
     builder::TempBuilderPG1,
     errors::{
-        CoreGroupBuildError, CreateAddProposalError, CreateCommitError, ExporterError,
-        ValidationError,
+        CoreGroupBuildError, CreateAddProposalError, CreateCommitError, CreateCustomProposalError,
+        CreateGroupContextExtProposalError, ExporterError, ValidationError,
     },
     group_context::*,
     public_group::{diff::compute_path::PathComputationResult, PublicGroup},
@@ -66,18 +64,20 @@ use crate::{
         proposals::*,
         *,
     },
+    persistence::{restore_exact, Persist},
     schedule::{
         message_secrets::*,
         psk::{load_psks, store::ResumptionPskStore, PskSecret},
         *,
     },
     tree::{secret_tree::SecretTreeError, sender_ratchet::SenderRatchetConfiguration},
-    treesync::{node::encryption_keys::EncryptionKeyPair, *},
+    treesync::{
+        node::{encryption_keys::EncryptionKeyPair, leaf_node::Capabilities},
+        *,
+    },
     versions::ProtocolVersion,
 };
 
-#[cfg(test)]
-use super::errors::CreateGroupContextExtProposalError;
 #[cfg(test)]
 use crate::treesync::node::leaf_node::TreePosition;
 use openmls_traits::crypto::OpenMlsCrypto;
@@ -122,6 +122,24 @@ impl Member {
     }
 }
 
+impl Persist for Member {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.index.persist(writer)?;
+        self.credential.persist(writer)?;
+        self.encryption_key.persist(writer)?;
+        self.signature_key.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            index: LeafNodeIndex::restore(bytes)?,
+            credential: Credential::restore(bytes)?,
+            encryption_key: Vec::restore(bytes)?,
+            signature_key: Vec::restore(bytes)?,
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[cfg_attr(test, derive(PartialEq, Clone))]
 pub(crate) struct CoreGroup {
@@ -210,6 +228,11 @@ impl CoreGroupBuilder {
         self.public_group_builder = self.public_group_builder.with_lifetime(lifetime);
         self
     }
+    /// Set the [`Capabilities`] of the own leaf in the group.
+    pub(crate) fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.public_group_builder = self.public_group_builder.with_capabilities(capabilities);
+        self
+    }
 
     /// Build the [`CoreGroup`].
     /// Any values that haven't been set in the builder are set to their default
@@ -418,33 +441,43 @@ impl CoreGroup {
         )
     }
 
+    // 12.1.5. ReInit
+    // struct {
+    //     opaque group_id<V>;
+    //     ProtocolVersion version;
+    //     CipherSuite cipher_suite;
+    //     Extension extensions<V>;
+    // } ReInit;
+    pub(crate) fn create_reinit_proposal(
+        &self,
+        framing_parameters: FramingParameters,
+        reinit_proposal: ReInitProposal,
+        signer: &impl Signer,
+    ) -> Result<AuthenticatedContent, LibraryError> {
+        let proposal = Proposal::ReInit(reinit_proposal);
+        AuthenticatedContent::member_proposal(
+            framing_parameters,
+            self.own_leaf_index(),
+            proposal,
+            self.context(),
+            signer,
+        )
+    }
+
     /// Create a `GroupContextExtensions` proposal.
-    #[cfg(test)]
     pub(crate) fn create_group_context_ext_proposal(
         &self,
         framing_parameters: FramingParameters,
         extensions: Extensions,
         signer: &impl Signer,
     ) -> Result<AuthenticatedContent, CreateGroupContextExtProposalError> {
-        // Ensure that the group supports all the extensions that are wanted.
-
-        let required_extension = extensions
-            .iter()
-            .find(|extension| extension.extension_type() == ExtensionType::RequiredCapabilities);
-        if let Some(required_extension) = required_extension {
-            let required_capabilities = required_extension.as_required_capabilities_extension()?;
-            // Ensure we support all the capabilities.
+        // Ensure that OpenMLS supports the required capabilities ...
+        if let Some(required_capabilities) = extensions.required_capabilities() {
             required_capabilities.check_support()?;
-            // TODO #566/#1361: This needs to be re-enabled once we support GCEs
-            /* self.own_leaf_node()?
-            .capabilities()
-            .supports_required_capabilities(required_capabilities)?; */
-
-            // Ensure that all other leaf nodes support all the required
-            // extensions as well.
-            self.public_group()
-                .check_extension_support(required_capabilities.extension_types())?;
         }
+        // ... and that all members of the group support the new extensions.
+        self.public_group().check_extension_support(&extensions)?;
+
         let proposal = GroupContextExtensionProposal::new(extensions);
         let proposal = Proposal::GroupContextExtensions(proposal);
         AuthenticatedContent::member_proposal(
@@ -457,6 +490,56 @@ impl CoreGroup {
         .map_err(|e| e.into())
     }
 
+    /// Create an `AppAck` proposal covering all application messages received
+    /// from other members in the current epoch.
+    pub(crate) fn create_app_ack_proposal(
+        &self,
+        framing_parameters: FramingParameters,
+        signer: &impl Signer,
+    ) -> Result<AuthenticatedContent, LibraryError> {
+        let received_ranges = self
+            .message_secrets()
+            .secret_tree()
+            .received_application_generations()
+            .map(|(sender, first_generation, last_generation)| {
+                MessageRange::new(sender, first_generation, last_generation)
+            })
+            .collect();
+        let proposal = Proposal::AppAck(AppAckProposal::new(received_ranges));
+        AuthenticatedContent::member_proposal(
+            framing_parameters,
+            self.own_leaf_index(),
+            proposal,
+            self.context(),
+            signer,
+        )
+    }
+
+    /// Create a custom proposal. The proposal type must not be defined by MLS
+    /// and all members of the group must support it.
+    pub(crate) fn create_custom_proposal(
+        &self,
+        framing_parameters: FramingParameters,
+        custom_proposal: CustomProposal,
+        signer: &impl Signer,
+    ) -> Result<AuthenticatedContent, CreateCustomProposalError> {
+        if !custom_proposal.has_custom_type() {
+            return Err(CreateCustomProposalError::ReservedProposalType);
+        }
+        self.public_group()
+            .check_proposal_support(ProposalType::Unknown(custom_proposal.proposal_type()))?;
+
+        let proposal = Proposal::Custom(custom_proposal);
+        AuthenticatedContent::member_proposal(
+            framing_parameters,
+            self.own_leaf_index(),
+            proposal,
+            self.context(),
+            signer,
+        )
+        .map_err(|e| e.into())
+    }
+
     // Create application message
     pub(crate) fn create_application_message(
         &mut self,
@@ -788,20 +871,26 @@ impl CoreGroup {
             .unwrap_or_default()
     }
 
-    /// Delete the [`EncryptionKeyPair`]s from the previous [`GroupEpoch`] from
-    /// the `provider`'s key store.
+    /// Returns the key under which the [`EncryptionKeyPair`]s of this group and
+    /// its current [`GroupEpoch`] are stored in the key store.
+    pub(crate) fn epoch_keypairs_key(&self) -> Vec<u8> {
+        EpochKeypairId::new(
+            self.group_id(),
+            self.context().epoch().as_u64(),
+            self.own_leaf_index(),
+        )
+        .0
+    }
+
+    /// Delete the [`EncryptionKeyPair`]s of this group and its current
+    /// [`GroupEpoch`] from the `provider`'s key store.
     ///
     /// Returns an error if access to the key store fails.
-    pub(super) fn delete_previous_epoch_keypairs<KeyStore: OpenMlsKeyStore>(
+    pub(super) fn delete_epoch_keypairs<KeyStore: OpenMlsKeyStore>(
         &self,
         store: &KeyStore,
     ) -> Result<(), KeyStore::Error> {
-        let k = EpochKeypairId::new(
-            self.group_id(),
-            self.context().epoch().as_u64() - 1,
-            self.own_leaf_index(),
-        );
-        store.delete::<Vec<EncryptionKeyPair>>(&k.0)
+        store.delete::<Vec<EncryptionKeyPair>>(&self.epoch_keypairs_key())
     }
 
     pub(crate) fn create_commit<KeyStore: OpenMlsKeyStore>(
@@ -871,6 +960,12 @@ impl CoreGroup {
             // ValSem112
             self.public_group
                 .validate_update_proposals(&proposal_queue, *sender_index)?;
+            self.public_group
+                .validate_reinit_proposals(&proposal_queue)?;
+            self.public_group
+                .validate_group_context_extensions_proposals(&proposal_queue)?;
+            self.public_group
+                .validate_custom_proposals(&proposal_queue)?;
         }
 
         // Make a copy of the public group to apply proposals safely
@@ -938,12 +1033,21 @@ impl CoreGroup {
         )
         .map_err(LibraryError::unexpected_crypto_error)?;
 
+        // Besides the PSKs from the proposals, PSKs can be injected directly
+        // into the key schedule.
+        let presharedkeys: Vec<PreSharedKeyId> = apply_proposals_values
+            .presharedkeys
+            .iter()
+            .chain(params.injected_psks())
+            .cloned()
+            .collect();
+
         // Prepare the PskSecret
         let psk_secret = {
             let psks = load_psks(
                 provider.key_store(),
                 &self.resumption_psk_store,
-                &apply_proposals_values.presharedkeys,
+                &presharedkeys,
             )?;
 
             PskSecret::new(provider.crypto(), ciphersuite, psks)?
@@ -1047,7 +1151,7 @@ impl CoreGroup {
                 &joiner_secret,
                 apply_proposals_values.invitation_list,
                 path_computation_result.plain_path.as_deref(),
-                &apply_proposals_values.presharedkeys,
+                &presharedkeys,
                 &encrypted_group_info,
                 provider.crypto(),
                 self.own_leaf_index(),
@@ -1095,6 +1199,110 @@ impl CoreGroup {
     }
 }
 
+// Persistence
+impl CoreGroup {
+    /// Persist the state of this group without the tree and the message
+    /// secrets. The tree is persisted with [`PublicGroup::persist_tree()`] and the
+    /// message secrets of each epoch in [`Self::message_secrets_epochs()`]
+    /// with [`Self::persist_message_secrets()`].
+    pub(crate) fn persist_without_tree_and_secrets(
+        &self,
+        writer: &mut Vec<u8>,
+    ) -> Result<(), tls_codec::Error> {
+        self.public_group.persist_without_tree(writer)?;
+        self.group_epoch_secrets.persist(writer)?;
+        self.own_leaf_index.persist(writer)?;
+        self.use_ratchet_tree_extension.persist(writer)?;
+        self.message_secrets_store.persist_without_secrets(writer)?;
+        self.resumption_psk_store.persist(writer)
+    }
+
+    /// Restore a group that was persisted with
+    /// [`Self::persist_without_tree_and_secrets()`] with the given tree. The
+    /// message secrets of each epoch are restored with `restore_epoch`.
+    pub(crate) fn restore_with_tree_and_secrets<E: From<tls_codec::Error>>(
+        bytes: &mut &[u8],
+        treesync: TreeSync,
+        restore_epoch: impl FnMut(GroupEpoch) -> Result<(MessageSecrets, Vec<Member>), E>,
+    ) -> Result<Self, E> {
+        let public_group = PublicGroup::restore_with_tree(bytes, treesync)?;
+        let group_epoch_secrets = GroupEpochSecrets::restore(bytes)?;
+        let own_leaf_index = LeafNodeIndex::restore(bytes)?;
+        let use_ratchet_tree_extension = bool::restore(bytes)?;
+        let message_secrets_store = MessageSecretsStore::restore_with_secrets(
+            bytes,
+            public_group.group_context().epoch(),
+            restore_epoch,
+        )?;
+        let resumption_psk_store = ResumptionPskStore::restore(bytes)?;
+
+        Ok(Self {
+            public_group,
+            group_epoch_secrets,
+            own_leaf_index,
+            use_ratchet_tree_extension,
+            message_secrets_store,
+            resumption_psk_store,
+        })
+    }
+
+    /// Returns the epochs for which the group holds message secrets, i.e.,
+    /// the past epochs and the current epoch.
+    pub(crate) fn message_secrets_epochs(&self) -> impl Iterator<Item = GroupEpoch> + '_ {
+        self.message_secrets_store
+            .past_epochs()
+            .chain(std::iter::once(self.context().epoch()))
+    }
+
+    /// Persist the message secrets of `epoch` together with the members of
+    /// that epoch.
+    pub(crate) fn persist_message_secrets(
+        &self,
+        epoch: GroupEpoch,
+        writer: &mut Vec<u8>,
+    ) -> Result<(), tls_codec::Error> {
+        self.message_secrets_store
+            .persist_epoch(epoch, self.context().epoch(), writer)
+    }
+
+    /// Replace the message secrets of `epoch` with ones that were persisted
+    /// with [`Self::persist_message_secrets()`].
+    pub(crate) fn restore_message_secrets(
+        &mut self,
+        epoch: GroupEpoch,
+        bytes: &[u8],
+    ) -> Result<(), LibraryError> {
+        let (message_secrets, _) =
+            restore_exact(bytes, <(MessageSecrets, Vec<Member>)>::restore)
+                .map_err(|_| LibraryError::custom("Failed to restore the message secrets"))?;
+        let current_epoch = self.context().epoch();
+        self.message_secrets_store
+            .replace_epoch(epoch, current_epoch, message_secrets)
+    }
+}
+
+impl Persist for CoreGroup {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.public_group.persist(writer)?;
+        self.group_epoch_secrets.persist(writer)?;
+        self.own_leaf_index.persist(writer)?;
+        self.use_ratchet_tree_extension.persist(writer)?;
+        self.message_secrets_store.persist(writer)?;
+        self.resumption_psk_store.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            public_group: PublicGroup::restore(bytes)?,
+            group_epoch_secrets: GroupEpochSecrets::restore(bytes)?,
+            own_leaf_index: LeafNodeIndex::restore(bytes)?,
+            use_ratchet_tree_extension: bool::restore(bytes)?,
+            message_secrets_store: MessageSecretsStore::restore(bytes)?,
+            resumption_psk_store: ResumptionPskStore::restore(bytes)?,
+        })
+    }
+}
+
 /// Composite key for key material of a client within an epoch
 pub struct EpochKeypairId(Vec<u8>);
 
@@ -1,8 +1,10 @@
 use crate::{
     binary_tree::array_representation::LeafNodeIndex,
+    credentials::CredentialValidator,
     group::{
         core_group::create_commit_params::{CommitType, CreateCommitParams},
         errors::ExternalCommitError,
+        mls_group::validate_member_credentials,
     },
     messages::proposals::{ExternalInitProposal, Proposal},
 };
@@ -24,12 +26,17 @@ impl CoreGroup {
     ///
     /// Note: If there is a group member in the group with the same identity as us,
     /// this will create a remove proposal.
+    ///
+    /// The credentials of the members of the group are checked with the
+    /// `credential_validator` before anything is created or signed.
     pub(crate) fn join_by_external_commit(
         provider: &impl OpenMlsProvider,
         signer: &impl Signer,
         mut params: CreateCommitParams,
         ratchet_tree: Option<RatchetTreeIn>,
         verifiable_group_info: VerifiableGroupInfo,
+        lifetime_policy: LifetimePolicy,
+        credential_validator: &impl CredentialValidator,
     ) -> Result<ExternalCommitResult, ExternalCommitError> {
         // Build the ratchet tree
 
@@ -48,13 +55,33 @@ impl CoreGroup {
 
         let (public_group, group_info) = PublicGroup::from_external(
             provider.crypto(),
+            provider.time(),
             ratchet_tree,
             verifiable_group_info,
             // Existing proposals are discarded when joining by external commit.
             ProposalStore::new(),
+            lifetime_policy,
         )?;
         let group_context = public_group.group_context();
 
+        // If there is a group member in the group with the same identity as us,
+        // commit a remove proposal.
+        let params_credential_with_key = params
+            .take_credential_with_key()
+            .ok_or(ExternalCommitError::MissingCredential)?;
+        let remove_proposal = public_group
+            .members()
+            .find(|member| {
+                member.signature_key == params_credential_with_key.signature_key.as_slice()
+            })
+            .map(|us| Proposal::Remove(RemoveProposal { removed: us.index }));
+
+        let own_leaf_index = public_group.leftmost_free_index(remove_proposal.iter().map(Some))?;
+
+        if !validate_member_credentials(&public_group, own_leaf_index, credential_validator) {
+            return Err(ExternalCommitError::CredentialRejected);
+        }
+
         // Obtain external_pub from GroupInfo extensions.
         let external_pub = group_info
             .extensions()
@@ -88,21 +115,9 @@ impl CoreGroup {
 
         let external_init_proposal = Proposal::ExternalInit(ExternalInitProposal::from(kem_output));
 
-        let mut inline_proposals = vec![external_init_proposal];
-
-        // If there is a group member in the group with the same identity as us,
-        // commit a remove proposal.
-        let params_credential_with_key = params
-            .take_credential_with_key()
-            .ok_or(ExternalCommitError::MissingCredential)?;
-        if let Some(us) = public_group.members().find(|member| {
-            member.signature_key == params_credential_with_key.signature_key.as_slice()
-        }) {
-            let remove_proposal = Proposal::Remove(RemoveProposal { removed: us.index });
-            inline_proposals.push(remove_proposal);
-        };
-
-        let own_leaf_index = public_group.leftmost_free_index(inline_proposals.iter().map(Some))?;
+        let inline_proposals = std::iter::once(external_init_proposal)
+            .chain(remove_proposal)
+            .collect();
 
         let group = CoreGroup {
             public_group,
@@ -4,7 +4,10 @@ use openmls_traits::key_store::OpenMlsKeyStore;
 use crate::{
     ciphersuite::hash_ref::HashReference,
     group::{core_group::*, errors::WelcomeError},
-    schedule::psk::store::ResumptionPskStore,
+    schedule::{
+        errors::PskError,
+        psk::{store::ResumptionPskStore, PreSharedKeyId, PskBundle, ResumptionPskUsage},
+    },
     treesync::{
         errors::{DerivePathError, PublicTreeError},
         node::encryption_keys::EncryptionKeyPair,
@@ -12,17 +15,47 @@ use crate::{
 };
 
 impl CoreGroup {
-    // Join a group from a welcome message
-    pub fn new_from_welcome<KeyStore: OpenMlsKeyStore>(
+    // Join a group from a welcome message with the default lifetime policy
+    #[cfg(test)]
+    pub(crate) fn new_from_welcome<KeyStore: OpenMlsKeyStore>(
         welcome: Welcome,
         ratchet_tree: Option<RatchetTreeIn>,
         key_package_bundle: KeyPackageBundle,
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
-        mut resumption_psk_store: ResumptionPskStore,
+        resumption_psk_store: ResumptionPskStore,
     ) -> Result<Self, WelcomeError<KeyStore::Error>> {
-        log::debug!("CoreGroup::new_from_welcome_internal");
+        Self::stage_welcome(
+            welcome,
+            ratchet_tree,
+            key_package_bundle,
+            provider,
+            resumption_psk_store,
+            LifetimePolicy::default(),
+            None,
+        )?
+        .into_core_group(provider)
+    }
 
-        // Read the encryption key pair from the key store and delete it there.
+    // Process a welcome message up to the point where the group would be
+    // joined, i.e., decrypt the group secrets and the group info, validate the
+    // ratchet tree and verify the confirmation tag. Apart from the temporary
+    // branch PSK, nothing is written to the key store. The lifetimes of the
+    // leaf nodes in the tree are checked with the `lifetime_policy`. If
+    // `branch_parent` is set, the welcome message must belong to a group that
+    // was branched off of it.
+    pub(crate) fn stage_welcome<KeyStore: OpenMlsKeyStore>(
+        welcome: Welcome,
+        ratchet_tree: Option<RatchetTreeIn>,
+        key_package_bundle: KeyPackageBundle,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mut resumption_psk_store: ResumptionPskStore,
+        lifetime_policy: LifetimePolicy,
+        branch_parent: Option<&CoreGroup>,
+    ) -> Result<StagedCoreWelcome, WelcomeError<KeyStore::Error>> {
+        log::debug!("CoreGroup::stage_welcome");
+
+        // Read the encryption key pair from the key store. It is deleted there
+        // when the group is joined.
         // TODO #1207: Key store access happens as early as possible so it can
         // be pulled up later more easily.
         let leaf_keypair = EncryptionKeyPair::read_from_key_store(
@@ -30,9 +63,6 @@ impl CoreGroup {
             key_package_bundle.key_package.leaf_node().encryption_key(),
         )
         .ok_or(WelcomeError::NoMatchingEncryptionKey)?;
-        leaf_keypair
-            .delete_from_key_store(provider.key_store())
-            .map_err(|_| WelcomeError::NoMatchingEncryptionKey)?;
 
         let ciphersuite = welcome.ciphersuite();
 
@@ -61,15 +91,109 @@ impl CoreGroup {
             provider.crypto(),
         )?;
 
+        // Resumption PSKs with usage reinit or branch link the new group to
+        // another group. There can be at most one of them.
+        let linking_psks = group_secrets
+            .psks
+            .iter()
+            .filter_map(|psk_id| match psk_id.psk() {
+                Psk::Resumption(resumption)
+                    if resumption.usage() != ResumptionPskUsage::Application =>
+                {
+                    Some(resumption)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if linking_psks.len() > 1 {
+            return Err(WelcomeError::MultipleResumptionPsks);
+        }
+        let is_linked_group = !linking_psks.is_empty();
+
+        // The resumption PSK of a reinitialized group is stored together with
+        // the ReInit proposal, which the successor group must match. It is
+        // deleted from the key store when the group is joined.
+        let reinit_psk_keystore_id = linking_psks
+            .first()
+            .filter(|resumption| resumption.usage() == ResumptionPskUsage::Reinit)
+            .map(|resumption| {
+                PreSharedKeyId::resumption(
+                    ResumptionPskUsage::Reinit,
+                    resumption.psk_group_id().clone(),
+                    resumption.psk_epoch(),
+                    vec![],
+                )
+                .keystore_id()
+            })
+            .transpose()?;
+        let reinit_proposal = match &reinit_psk_keystore_id {
+            Some(keystore_id) => {
+                let psk_bundle = provider
+                    .key_store()
+                    .read::<PskBundle>(keystore_id)
+                    .ok_or(WelcomeError::Psk(PskError::KeyNotFound))?;
+                let reinit_proposal = psk_bundle
+                    .reinit_proposal()
+                    .ok_or(WelcomeError::ReInitParametersMismatch)?;
+                Some(reinit_proposal.clone())
+            }
+            None => None,
+        };
+
+        // When joining a branch, the branch PSK is taken from the resumption
+        // PSK store of the parent group and temporarily put into the key store.
+        let branch_psk_id = if let Some(parent_group) = branch_parent {
+            let branch_psk = linking_psks
+                .first()
+                .filter(|resumption| resumption.usage() == ResumptionPskUsage::Branch)
+                .ok_or(WelcomeError::MissingBranchPsk)?;
+            if branch_psk.psk_group_id() != parent_group.group_id() {
+                return Err(WelcomeError::BranchPskMismatch);
+            }
+            // The resumption PSK of the current epoch is only in the store if
+            // the parent group keeps resumption PSKs.
+            let resumption_psk = if branch_psk.psk_epoch() == parent_group.context().epoch() {
+                Some(parent_group.group_epoch_secrets().resumption_psk())
+            } else {
+                parent_group
+                    .resumption_psk_store
+                    .get(branch_psk.psk_epoch())
+            }
+            .ok_or(WelcomeError::BranchPskMismatch)?;
+            let psk_id = PreSharedKeyId::resumption(
+                ResumptionPskUsage::Branch,
+                branch_psk.psk_group_id().clone(),
+                branch_psk.psk_epoch(),
+                vec![],
+            );
+            provider
+                .key_store()
+                .store(
+                    &psk_id.keystore_id()?,
+                    &PskBundle::from_resumption_psk(resumption_psk, ciphersuite),
+                )
+                .map_err(WelcomeError::KeyStoreError)?;
+            Some(psk_id)
+        } else {
+            None
+        };
+
         // Prepare the PskSecret
         let psk_secret = {
             let psks = load_psks(
                 provider.key_store(),
                 &resumption_psk_store,
                 &group_secrets.psks,
-            )?;
+            );
+
+            if let Some(psk_id) = branch_psk_id {
+                provider
+                    .key_store()
+                    .delete::<PskBundle>(&psk_id.keystore_id()?)
+                    .map_err(WelcomeError::KeyStoreError)?;
+            }
 
-            PskSecret::new(provider.crypto(), ciphersuite, psks)?
+            PskSecret::new(provider.crypto(), ciphersuite, psks?)?
         };
 
         // Create key schedule
@@ -130,15 +254,64 @@ impl CoreGroup {
 
         let welcome_sender_index = verifiable_group_info.signer();
 
-        // Since there is currently only the external pub extension, there is no
-        // group info extension of interest here.
-        let (public_group, _group_info_extensions) = PublicGroup::from_external(
+        let (public_group, group_info) = PublicGroup::from_external(
             provider.crypto(),
+            provider.time(),
             ratchet_tree,
             verifiable_group_info,
             ProposalStore::new(),
+            lifetime_policy,
         )?;
 
+        // A group that is linked to another group through a resumption PSK
+        // must be joined in its first epoch.
+        if is_linked_group && public_group.group_context().epoch() != GroupEpoch::from(1) {
+            return Err(WelcomeError::InvalidEpochForResumptionPsk);
+        }
+
+        // The successor of a reinitialized group must have the group ID,
+        // version and ciphersuite of the ReInit proposal, as well as the
+        // required capabilities and external senders in its extensions.
+        if let Some(reinit_proposal) = reinit_proposal {
+            let extensions = public_group.group_context().extensions();
+            if public_group.group_id() != reinit_proposal.group_id()
+                || public_group.version() != reinit_proposal.version()
+                || public_group.ciphersuite() != reinit_proposal.ciphersuite()
+                || reinit_proposal
+                    .extensions()
+                    .required_capabilities()
+                    .is_some_and(|required_capabilities| {
+                        extensions.required_capabilities() != Some(required_capabilities)
+                    })
+                || reinit_proposal
+                    .extensions()
+                    .external_senders()
+                    .is_some_and(|external_senders| {
+                        extensions.external_senders() != Some(external_senders)
+                    })
+            {
+                return Err(WelcomeError::ReInitParametersMismatch);
+            }
+        }
+
+        // A branch must have the same version and ciphersuite as its parent
+        // group and its members must be a subset of the parent's members.
+        if let Some(parent_group) = branch_parent {
+            if public_group.version() != parent_group.version()
+                || public_group.ciphersuite() != parent_group.ciphersuite()
+            {
+                return Err(WelcomeError::BranchParametersMismatch);
+            }
+            if !public_group.members().all(|member| {
+                parent_group
+                    .public_group()
+                    .members()
+                    .any(|parent_member| parent_member.credential == member.credential)
+            }) {
+                return Err(WelcomeError::BranchMembersMismatch);
+            }
+        }
+
         // Find our own leaf in the tree.
         let own_leaf_index = public_group
             .members()
@@ -229,19 +402,21 @@ impl CoreGroup {
         let resumption_psk = group_epoch_secrets.resumption_psk();
         resumption_psk_store.add(public_group.group_context().epoch(), resumption_psk.clone());
 
-        let group = CoreGroup {
-            public_group,
-            group_epoch_secrets,
-            own_leaf_index,
-            use_ratchet_tree_extension: enable_ratchet_tree_extension,
-            message_secrets_store,
-            resumption_psk_store,
-        };
-        group
-            .store_epoch_keypairs(provider.key_store(), group_keypairs.as_slice())
-            .map_err(WelcomeError::KeyStoreError)?;
-
-        Ok(group)
+        Ok(StagedCoreWelcome {
+            group: CoreGroup {
+                public_group,
+                group_epoch_secrets,
+                own_leaf_index,
+                use_ratchet_tree_extension: enable_ratchet_tree_extension,
+                message_secrets_store,
+                resumption_psk_store,
+            },
+            group_keypairs,
+            key_package_bundle,
+            welcome_sender_index,
+            group_info,
+            reinit_psk_keystore_id,
+        })
     }
 
     // Helper functions
@@ -258,3 +433,68 @@ impl CoreGroup {
         None
     }
 }
+
+/// A group that was staged from a welcome message, but not joined yet.
+#[derive(Debug)]
+pub(crate) struct StagedCoreWelcome {
+    group: CoreGroup,
+    // The leaf encryption key pair and the key pairs derived from the path
+    // secret.
+    group_keypairs: Vec<EncryptionKeyPair>,
+    key_package_bundle: KeyPackageBundle,
+    welcome_sender_index: LeafNodeIndex,
+    group_info: GroupInfo,
+    // The key under which the resumption PSK of the reinitialized
+    // predecessor is stored, if the group is the successor of one.
+    reinit_psk_keystore_id: Option<Vec<u8>>,
+}
+
+impl StagedCoreWelcome {
+    pub(crate) fn public_group(&self) -> &PublicGroup {
+        self.group.public_group()
+    }
+
+    pub(crate) fn own_leaf_index(&self) -> LeafNodeIndex {
+        self.group.own_leaf_index()
+    }
+
+    pub(crate) fn welcome_sender_index(&self) -> LeafNodeIndex {
+        self.welcome_sender_index
+    }
+
+    pub(crate) fn group_info(&self) -> &GroupInfo {
+        &self.group_info
+    }
+
+    pub(crate) fn key_package(&self) -> &KeyPackage {
+        self.key_package_bundle.key_package()
+    }
+
+    // Join the staged group: Delete the leaf encryption key pair from the key
+    // store, unless it belongs to a last resort key package that can be used
+    // again, delete the resumption PSK of a reinitialized predecessor and
+    // store the encryption key pairs of the epoch.
+    pub(crate) fn into_core_group<KeyStore: OpenMlsKeyStore>(
+        self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+    ) -> Result<CoreGroup, WelcomeError<KeyStore::Error>> {
+        if !self.key_package_bundle.key_package.last_resort() {
+            if let Some(leaf_keypair) = self.group_keypairs.first() {
+                leaf_keypair
+                    .delete_from_key_store(provider.key_store())
+                    .map_err(|_| WelcomeError::NoMatchingEncryptionKey)?;
+            }
+        }
+        if let Some(keystore_id) = self.reinit_psk_keystore_id {
+            provider
+                .key_store()
+                .delete::<PskBundle>(&keystore_id)
+                .map_err(WelcomeError::KeyStoreError)?;
+        }
+        self.group
+            .store_epoch_keypairs(provider.key_store(), self.group_keypairs.as_slice())
+            .map_err(WelcomeError::KeyStoreError)?;
+
+        Ok(self.group)
+    }
+}
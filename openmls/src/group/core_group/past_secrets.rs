@@ -1,6 +1,6 @@
 use std::collections::VecDeque;
 
-use crate::schedule::message_secrets::MessageSecrets;
+use crate::{persistence::persist_sequence, schedule::message_secrets::MessageSecrets};
 
 use super::*;
 
@@ -172,3 +172,135 @@ impl MessageSecretsStore {
         &self.message_secrets
     }
 }
+
+impl Persist for EpochTree {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.epoch.persist(writer)?;
+        self.message_secrets.persist(writer)?;
+        self.leaves.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            epoch: u64::restore(bytes)?,
+            message_secrets: MessageSecrets::restore(bytes)?,
+            leaves: Vec::restore(bytes)?,
+        })
+    }
+}
+
+impl Persist for MessageSecretsStore {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.max_epochs.persist(writer)?;
+        self.past_epoch_trees.persist(writer)?;
+        self.message_secrets.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            max_epochs: usize::restore(bytes)?,
+            past_epoch_trees: VecDeque::restore(bytes)?,
+            message_secrets: MessageSecrets::restore(bytes)?,
+        })
+    }
+}
+
+impl MessageSecretsStore {
+    /// Returns the epochs of the past message secrets in the store.
+    pub(crate) fn past_epochs(&self) -> impl Iterator<Item = GroupEpoch> + '_ {
+        self.past_epoch_trees
+            .iter()
+            .map(|epoch_tree| GroupEpoch(epoch_tree.epoch))
+    }
+
+    /// Persist the store without the message secrets, which are persisted
+    /// per epoch with [`Self::persist_epoch()`].
+    pub(crate) fn persist_without_secrets(
+        &self,
+        writer: &mut Vec<u8>,
+    ) -> Result<(), tls_codec::Error> {
+        self.max_epochs.persist(writer)?;
+        persist_sequence(
+            self.past_epoch_trees
+                .iter()
+                .map(|epoch_tree| &epoch_tree.epoch),
+            writer,
+        )
+    }
+
+    /// Persist the message secrets and the leaves of `epoch`, where
+    /// `current_epoch` is the epoch of the group. The leaves of the current
+    /// epoch are persisted as an empty list.
+    pub(crate) fn persist_epoch(
+        &self,
+        epoch: GroupEpoch,
+        current_epoch: GroupEpoch,
+        writer: &mut Vec<u8>,
+    ) -> Result<(), tls_codec::Error> {
+        let (message_secrets, leaves) = if epoch == current_epoch {
+            (&self.message_secrets, [].as_slice())
+        } else {
+            self.past_epoch_trees
+                .iter()
+                .find(|epoch_tree| epoch_tree.epoch == epoch.as_u64())
+                .map(|epoch_tree| (&epoch_tree.message_secrets, epoch_tree.leaves.as_slice()))
+                .ok_or_else(|| {
+                    tls_codec::Error::EncodingError(format!(
+                        "No message secrets for epoch {}",
+                        epoch.as_u64()
+                    ))
+                })?
+        };
+        message_secrets.persist(writer)?;
+        persist_sequence(leaves.iter(), writer)
+    }
+
+    /// Replace the message secrets of `epoch`, where `current_epoch` is the
+    /// epoch of the group. The leaves of the epoch are kept.
+    pub(crate) fn replace_epoch(
+        &mut self,
+        epoch: GroupEpoch,
+        current_epoch: GroupEpoch,
+        message_secrets: MessageSecrets,
+    ) -> Result<(), LibraryError> {
+        let secrets = if epoch == current_epoch {
+            &mut self.message_secrets
+        } else {
+            self.secrets_for_epoch_mut(epoch)
+                .ok_or_else(|| LibraryError::custom("No message secrets for the epoch"))?
+        };
+        *secrets = message_secrets;
+
+        Ok(())
+    }
+
+    /// Restore a store that was persisted with
+    /// [`Self::persist_without_secrets()`]. The message secrets of each epoch
+    /// are restored with `restore_epoch`.
+    pub(crate) fn restore_with_secrets<E: From<tls_codec::Error>>(
+        bytes: &mut &[u8],
+        current_epoch: GroupEpoch,
+        mut restore_epoch: impl FnMut(GroupEpoch) -> Result<(MessageSecrets, Vec<Member>), E>,
+    ) -> Result<Self, E> {
+        let max_epochs = usize::restore(bytes)?;
+        let past_epochs: Vec<u64> = Vec::restore(bytes)?;
+        let past_epoch_trees = past_epochs
+            .into_iter()
+            .map(|epoch| {
+                let (message_secrets, leaves) = restore_epoch(GroupEpoch(epoch))?;
+                Ok(EpochTree {
+                    epoch,
+                    message_secrets,
+                    leaves,
+                })
+            })
+            .collect::<Result<_, E>>()?;
+        let (message_secrets, _) = restore_epoch(current_epoch)?;
+
+        Ok(Self {
+            max_epochs,
+            past_epoch_trees,
+            message_secrets,
+        })
+    }
+}
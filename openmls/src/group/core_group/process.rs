@@ -49,8 +49,12 @@ impl CoreGroup {
         // Checks the following semantic validation:
         //  - ValSem010
         //  - ValSem246 (as part of ValSem010)
-        let (content, credential) =
-            unverified_message.verify(self.ciphersuite(), provider.crypto(), self.version())?;
+        let (content, credential) = unverified_message.verify(
+            self.ciphersuite(),
+            provider.crypto(),
+            provider.time(),
+            self.version(),
+        )?;
 
         match content.sender() {
             Sender::Member(_) | Sender::NewMemberCommit | Sender::NewMemberProposal => {
@@ -103,14 +107,17 @@ impl CoreGroup {
                     FramedContentBody::Application(_) => {
                         Err(ProcessMessageError::UnauthorizedExternalApplicationMessage)
                     }
-                    FramedContentBody::Proposal(Proposal::Remove(_)) => {
-                        let content = ProcessedMessageContent::ProposalMessage(Box::new(
-                            QueuedProposal::from_authenticated_content_by_ref(
-                                self.ciphersuite(),
-                                provider.crypto(),
-                                content,
-                            )?,
-                        ));
+                    FramedContentBody::Proposal(_) => {
+                        let proposal = QueuedProposal::from_authenticated_content_by_ref(
+                            self.ciphersuite(),
+                            provider.crypto(),
+                            content,
+                        )?;
+                        // External senders can only send Add, Remove,
+                        // PreSharedKey, ReInit and GroupContextExtensions
+                        // proposals.
+                        self.public_group.validate_external_proposal(&proposal)?;
+                        let content = ProcessedMessageContent::ProposalMessage(Box::new(proposal));
                         Ok(ProcessedMessage::new(
                             self.group_id().clone(),
                             self.context().epoch(),
@@ -120,11 +127,10 @@ impl CoreGroup {
                             credential,
                         ))
                     }
-                    // TODO #151/#106
-                    FramedContentBody::Proposal(_) => {
-                        Err(ProcessMessageError::UnsupportedProposalType)
-                    }
-                    FramedContentBody::Commit(_) => unimplemented!(),
+                    // External senders can't commit.
+                    FramedContentBody::Commit(_) => Err(ProcessMessageError::InvalidCommit(
+                        StageCommitError::SenderTypeExternal,
+                    )),
                 }
             }
         }
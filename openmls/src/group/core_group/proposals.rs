@@ -13,9 +13,10 @@ use crate::{
     },
     group::errors::*,
     messages::proposals::{
-        AddProposal, PreSharedKeyProposal, Proposal, ProposalOrRef, ProposalOrRefType,
-        ProposalType, RemoveProposal, UpdateProposal,
+        AddProposal, AppAckProposal, CustomProposal, PreSharedKeyProposal, Proposal, ProposalOrRef,
+        ProposalOrRefType, ProposalType, ReInitProposal, RemoveProposal, UpdateProposal,
     },
+    persistence::{persist_sequence, Persist},
     utils::vector_converter,
 };
 
@@ -65,6 +66,18 @@ impl ProposalStore {
     }
 }
 
+impl Persist for ProposalStore {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.queued_proposals.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            queued_proposals: Vec::restore(bytes)?,
+        })
+    }
+}
+
 /// Alternative representation of a Proposal, where the sender is extracted from
 /// the encapsulating PublicMessage and the ProposalRef is attached.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -164,6 +177,24 @@ impl QueuedProposal {
     }
 }
 
+impl Persist for QueuedProposal {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.proposal.persist(writer)?;
+        self.proposal_reference.persist(writer)?;
+        self.sender.persist(writer)?;
+        self.proposal_or_ref_type.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            proposal: Proposal::restore(bytes)?,
+            proposal_reference: ProposalRef::restore(bytes)?,
+            sender: Sender::restore(bytes)?,
+            proposal_or_ref_type: ProposalOrRefType::restore(bytes)?,
+        })
+    }
+}
+
 /// Helper struct to collect proposals such that they are unique and can be read
 /// out in the order in that they were added.
 struct OrderedProposalRefs {
@@ -390,6 +421,53 @@ impl ProposalQueue {
         })
     }
 
+    /// Returns the ReInit proposal in the queue, if there is one
+    pub(crate) fn reinit_proposal(&self) -> Option<QueuedReInitProposal> {
+        self.queued_proposals().find_map(|queued_proposal| {
+            if let Proposal::ReInit(reinit_proposal) = queued_proposal.proposal() {
+                let sender = queued_proposal.sender();
+                Some(QueuedReInitProposal {
+                    reinit_proposal,
+                    sender,
+                })
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns an iterator over all AppAck proposals in the queue
+    /// in the order of the the Commit message
+    pub(crate) fn app_ack_proposals(&self) -> impl Iterator<Item = QueuedAppAckProposal> {
+        self.queued_proposals().filter_map(|queued_proposal| {
+            if let Proposal::AppAck(app_ack_proposal) = queued_proposal.proposal() {
+                let sender = queued_proposal.sender();
+                Some(QueuedAppAckProposal {
+                    app_ack_proposal,
+                    sender,
+                })
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns an iterator over all custom proposals in the queue
+    /// in the order of the the Commit message
+    pub(crate) fn custom_proposals(&self) -> impl Iterator<Item = QueuedCustomProposal> {
+        self.queued_proposals().filter_map(|queued_proposal| {
+            if let Proposal::Custom(custom_proposal) = queued_proposal.proposal() {
+                let sender = queued_proposal.sender();
+                Some(QueuedCustomProposal {
+                    custom_proposal,
+                    sender,
+                })
+            } else {
+                None
+            }
+        })
+    }
+
     /// Filters received proposals
     ///
     /// 11.2 Commit
@@ -408,6 +486,8 @@ impl ProposalQueue {
     /// - Check for invalid indexes and drop proposal
     /// - Check for presence of Removes and delete Updates
     /// - Only keep the last Update
+    /// - Only keep the last GroupContextExtensions
+    /// - Only keep the first ReInit and drop all other proposals if there is one
     ///
     /// Return a [`ProposalQueue`] and a bool that indicates whether Updates for the
     /// own node were included
@@ -432,6 +512,8 @@ impl ProposalQueue {
         let mut proposal_pool: HashMap<ProposalRef, QueuedProposal> = HashMap::new();
         let mut contains_own_updates = false;
         let mut contains_external_init = false;
+        let mut reinit_proposal: Option<ProposalRef> = None;
+        let mut group_context_extensions_proposal: Option<ProposalRef> = None;
 
         // Aggregate both proposal types to a common iterator
         // We checked earlier that only proposals can end up here
@@ -493,8 +575,11 @@ impl ProposalQueue {
                     proposal_pool.insert(queued_proposal.proposal_reference(), queued_proposal);
                 }
                 Proposal::ReInit(_) => {
-                    // TODO #751: Only keep one ReInit
-                    proposal_pool.insert(queued_proposal.proposal_reference(), queued_proposal);
+                    // Only use the first ReInit proposal we find.
+                    if reinit_proposal.is_none() {
+                        reinit_proposal = Some(queued_proposal.proposal_reference());
+                        proposal_pool.insert(queued_proposal.proposal_reference(), queued_proposal);
+                    }
                 }
                 Proposal::ExternalInit(_) => {
                     // Only use the first external init proposal we find.
@@ -505,10 +590,14 @@ impl ProposalQueue {
                     }
                 }
                 Proposal::GroupContextExtensions(_) => {
-                    // TODO: Validate proposal?
+                    // Only use the last GroupContextExtensions proposal we find.
+                    group_context_extensions_proposal = Some(queued_proposal.proposal_reference());
+                    proposal_pool.insert(queued_proposal.proposal_reference(), queued_proposal);
+                }
+                Proposal::AppAck(_) | Proposal::Custom(_) => {
+                    valid_proposals.add(queued_proposal.proposal_reference());
                     proposal_pool.insert(queued_proposal.proposal_reference(), queued_proposal);
                 }
-                Proposal::AppAck(_) => unimplemented!("See #291"),
             }
         }
         // Check for presence of Removes and delete Updates
@@ -525,6 +614,20 @@ impl ProposalQueue {
                 valid_proposals.add(last_update.proposal_reference());
             }
         }
+        if let Some(group_context_extensions_proposal) = group_context_extensions_proposal {
+            valid_proposals.add(group_context_extensions_proposal);
+        }
+        // A ReInit proposal can't be combined with any other proposal, so it
+        // supersedes everything else.
+        if let Some(reinit_proposal) = reinit_proposal {
+            let mut proposal_queue = ProposalQueue::default();
+            proposal_queue.add(
+                proposal_pool
+                    .remove(&reinit_proposal)
+                    .ok_or(ProposalQueueError::ProposalNotFound)?,
+            );
+            return Ok((proposal_queue, false));
+        }
         // Only retain `adds` and `valid_proposals`
         let mut proposal_queue = ProposalQueue::default();
         for proposal_reference in adds.iter().chain(valid_proposals.iter()) {
@@ -568,6 +671,22 @@ impl ProposalQueue {
     }
 }
 
+/// The queue is persisted as the sequence of its proposals in the order of the
+/// queue, from which both the order and the map are restored.
+impl Persist for ProposalQueue {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        persist_sequence(self.queued_proposals(), writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        let mut proposal_queue = Self::default();
+        for queued_proposal in Vec::<QueuedProposal>::restore(bytes)? {
+            proposal_queue.add(queued_proposal);
+        }
+        Ok(proposal_queue)
+    }
+}
+
 /// A queued Add proposal
 #[derive(PartialEq, Debug)]
 pub struct QueuedAddProposal<'a> {
@@ -643,3 +762,60 @@ impl<'a> QueuedPskProposal<'a> {
         self.sender
     }
 }
+
+/// A queued ReInit proposal
+#[derive(PartialEq, Eq, Debug)]
+pub struct QueuedReInitProposal<'a> {
+    reinit_proposal: &'a ReInitProposal,
+    sender: &'a Sender,
+}
+
+impl<'a> QueuedReInitProposal<'a> {
+    /// Returns a reference to the proposal
+    pub fn reinit_proposal(&self) -> &ReInitProposal {
+        self.reinit_proposal
+    }
+
+    /// Returns a reference to the sender
+    pub fn sender(&self) -> &Sender {
+        self.sender
+    }
+}
+
+/// A queued AppAck proposal
+#[derive(PartialEq, Debug)]
+pub struct QueuedAppAckProposal<'a> {
+    app_ack_proposal: &'a AppAckProposal,
+    sender: &'a Sender,
+}
+
+impl<'a> QueuedAppAckProposal<'a> {
+    /// Returns a reference to the proposal
+    pub fn app_ack_proposal(&self) -> &AppAckProposal {
+        self.app_ack_proposal
+    }
+
+    /// Returns a reference to the sender
+    pub fn sender(&self) -> &Sender {
+        self.sender
+    }
+}
+
+/// A queued custom proposal
+#[derive(PartialEq, Eq, Debug)]
+pub struct QueuedCustomProposal<'a> {
+    custom_proposal: &'a CustomProposal,
+    sender: &'a Sender,
+}
+
+impl<'a> QueuedCustomProposal<'a> {
+    /// Returns a reference to the proposal
+    pub fn custom_proposal(&self) -> &CustomProposal {
+        self.custom_proposal
+    }
+
+    /// Returns a reference to the sender
+    pub fn sender(&self) -> &Sender {
+        self.sender
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    credentials::AcceptAllCredentials,
     framing::{
         test_framing::setup_alice_bob_group, FramedContentBody, FramingParameters, WireFormat,
     },
@@ -8,6 +9,7 @@ use crate::{
         test_core_group::{setup_alice_group, setup_client},
         CreateCommitParams,
     },
+    key_packages::LifetimePolicy,
     messages::proposals::{ProposalOrRef, ProposalType},
     test_utils::*,
 };
@@ -50,6 +52,8 @@ fn test_external_init(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider)
         params,
         None,
         verifiable_group_info,
+        LifetimePolicy::default(),
+        &AcceptAllCredentials,
     )
     .expect("Error initializing group externally.");
 
@@ -128,6 +132,8 @@ fn test_external_init(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider)
         params,
         Some(ratchet_tree.into()),
         verifiable_group_info,
+        LifetimePolicy::default(),
+        &AcceptAllCredentials,
     )
     .expect("Error initializing group externally.");
 
@@ -215,6 +221,8 @@ fn test_external_init_single_member_group(
         params,
         Some(ratchet_tree.into()),
         verifiable_group_info,
+        LifetimePolicy::default(),
+        &AcceptAllCredentials,
     )
     .expect("Error initializing group externally.");
 
@@ -278,7 +286,9 @@ fn test_external_init_broken_signature(ciphersuite: Ciphersuite, provider: &impl
             &charlie_signer,
             params,
             None,
-            verifiable_group_info
+            verifiable_group_info,
+            LifetimePolicy::default(),
+            &AcceptAllCredentials,
         )
         .expect_err("Signature was corrupted. This should have failed.")
     );
@@ -22,7 +22,7 @@ use crate::{
     messages::proposals::{AddProposal, Proposal, ProposalOrRef, ProposalType},
     schedule::psk::store::ResumptionPskStore,
     test_utils::*,
-    treesync::errors::LeafNodeValidationError,
+    treesync::{errors::LeafNodeValidationError, node::leaf_node::Capabilities},
     versions::ProtocolVersion,
 };
 
@@ -45,7 +45,7 @@ fn proposal_queue_functions(ciphersuite: Ciphersuite, provider: &impl OpenMlsPro
     let alice_update_key_package = alice_update_key_package_bundle.key_package();
     let kpi = KeyPackageIn::from(alice_update_key_package.clone());
     assert!(kpi
-        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .validate(provider.crypto(), provider.time(), ProtocolVersion::Mls10)
         .is_ok());
 
     let group_context = GroupContext::new(
@@ -189,7 +189,7 @@ fn proposal_queue_order(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
     let alice_update_key_package = alice_update_key_package_bundle.key_package();
     let kpi = KeyPackageIn::from(alice_update_key_package.clone());
     assert!(kpi
-        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .validate(provider.crypto(), provider.time(), ProtocolVersion::Mls10)
         .is_ok());
 
     let group_context = GroupContext::new(
@@ -288,12 +288,15 @@ fn test_required_unsupported_proposals(ciphersuite: Ciphersuite, provider: &impl
 
     // Set required capabilities
     let extensions = &[];
-    let proposals = &[ProposalType::GroupContextExtensions, ProposalType::AppAck];
+    let proposals = &[
+        ProposalType::GroupContextExtensions,
+        ProposalType::Unknown(0xff00),
+    ];
     let credentials = &[CredentialType::Basic];
     let required_capabilities =
         RequiredCapabilitiesExtension::new(extensions, proposals, credentials);
 
-    // This must fail because we don't actually support AppAck proposals
+    // This must fail because Alice doesn't support the unknown proposal type
     let e = CoreGroup::builder(
         GroupId::random(provider.rand()),
         CryptoConfig::with_default_version(ciphersuite),
@@ -301,15 +304,53 @@ fn test_required_unsupported_proposals(ciphersuite: Ciphersuite, provider: &impl
     )
     .with_required_capabilities(required_capabilities)
     .build(provider, &alice_signer)
-    .expect_err(
-        "CoreGroup creation must fail because AppAck proposals aren't supported in OpenMLS yet.",
-    );
+    .expect_err("CoreGroup creation must fail because Alice doesn't support the unknown proposal.");
     assert_eq!(
         e,
         CoreGroupBuildError::PublicGroupBuildError(PublicGroupBuildError::UnsupportedProposalType)
     )
 }
 
+#[apply(ciphersuites_and_providers)]
+fn test_required_custom_proposals(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential, _, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+
+    // Set required capabilities
+    let extensions = &[];
+    let proposals = &[
+        ProposalType::GroupContextExtensions,
+        ProposalType::Unknown(0xff00),
+    ];
+    let credentials = &[CredentialType::Basic];
+    let required_capabilities =
+        RequiredCapabilitiesExtension::new(extensions, proposals, credentials);
+
+    // Unknown proposal types can be required as custom proposals if the
+    // creator of the group supports them.
+    let alice_group = CoreGroup::builder(
+        GroupId::random(provider.rand()),
+        CryptoConfig::with_default_version(ciphersuite),
+        alice_credential,
+    )
+    .with_required_capabilities(required_capabilities)
+    .with_capabilities(Capabilities::new(
+        None,
+        Some(&[ciphersuite]),
+        None,
+        Some(proposals),
+        None,
+    ))
+    .build(provider, &alice_signer)
+    .expect("Error creating CoreGroup.");
+    assert!(alice_group
+        .own_leaf_node()
+        .expect("An unexpected error occurred.")
+        .capabilities()
+        .proposals()
+        .contains(&ProposalType::Unknown(0xff00)));
+}
+
 #[apply(ciphersuites_and_providers)]
 fn test_required_extension_key_package_mismatch(
     ciphersuite: Ciphersuite,
@@ -11,8 +11,8 @@ use crate::{
     error::LibraryError,
     extensions::errors::{ExtensionError, InvalidExtensionError},
     framing::errors::{MessageDecryptionError, SenderError},
+    key_packages::errors::KeyPackageNewError,
     key_packages::errors::KeyPackageVerifyError,
-    key_packages::errors::{KeyPackageExtensionSupportError, KeyPackageNewError},
     messages::{group_info::GroupInfoError, GroupSecretsError},
     schedule::errors::PskError,
     treesync::errors::*,
@@ -88,6 +88,41 @@ pub enum WelcomeError<KeyStoreError> {
     /// This error indicates the leaf node is invalid. See [`LeafNodeValidationError`] for more details.
     #[error(transparent)]
     LeafNodeValidation(#[from] LeafNodeValidationError),
+    /// The Welcome message contains more than one resumption PSK with usage reinit or branch.
+    #[error(
+        "The Welcome message contains more than one resumption PSK with usage reinit or branch."
+    )]
+    MultipleResumptionPsks,
+    /// A resumption PSK with usage reinit or branch was used for a group that is not in epoch 1.
+    #[error(
+        "A resumption PSK with usage reinit or branch was used for a group that is not in epoch 1."
+    )]
+    InvalidEpochForResumptionPsk,
+    /// The Welcome message does not contain a resumption PSK with usage branch.
+    #[error("The Welcome message does not contain a resumption PSK with usage branch.")]
+    MissingBranchPsk,
+    /// The branch PSK doesn't refer to an available epoch of the parent group.
+    #[error("The branch PSK doesn't refer to an available epoch of the parent group.")]
+    BranchPskMismatch,
+    /// The version or ciphersuite of the branched group don't match the ones of the parent group.
+    #[error(
+        "The version or ciphersuite of the branched group don't match the ones of the parent group."
+    )]
+    BranchParametersMismatch,
+    /// The members of the branched group are not a subset of the members of the parent group.
+    #[error(
+        "The members of the branched group are not a subset of the members of the parent group."
+    )]
+    BranchMembersMismatch,
+    /// The group ID, version, ciphersuite or extensions of the successor group don't match the ReInit proposal.
+    #[error(
+        "The group ID, version, ciphersuite or extensions of the successor group don't match the ReInit proposal."
+    )]
+    ReInitParametersMismatch,
+    /// A credential was rejected by the
+    /// [`CredentialValidator`](crate::credentials::CredentialValidator).
+    #[error("A credential was rejected by the credential validator.")]
+    CredentialRejected,
 }
 
 /// External Commit error
@@ -121,6 +156,10 @@ pub enum ExternalCommitError {
     /// Credential is missing from external commit.
     #[error("Credential is missing from external commit.")]
     MissingCredential,
+    /// A credential was rejected by the
+    /// [`CredentialValidator`](crate::credentials::CredentialValidator).
+    #[error("A credential was rejected by the credential validator.")]
+    CredentialRejected,
 }
 
 /// Stage Commit error
@@ -361,6 +400,24 @@ pub enum ProposalValidationError {
     /// See [`PskError`] for more details.
     #[error(transparent)]
     Psk(#[from] PskError),
+    /// A ReInit proposal was committed together with other proposals.
+    #[error("A ReInit proposal was committed together with other proposals.")]
+    ReInitWithOtherProposals,
+    /// The ReInit proposal's protocol version is lower than the one of the group.
+    #[error("The ReInit proposal's protocol version is lower than the one of the group.")]
+    InvalidReInitVersion,
+    /// Not all members of the group support the proposed group context extensions.
+    #[error("Not all members of the group support the proposed group context extensions.")]
+    UnsupportedGroupContextExtensions,
+    /// The commit covers more than one GroupContextExtensions proposal.
+    #[error("The commit covers more than one GroupContextExtensions proposal.")]
+    MultipleGroupContextExtensionsProposals,
+    /// A custom proposal uses a proposal type that is defined by MLS.
+    #[error("A custom proposal uses a proposal type that is defined by MLS.")]
+    ReservedProposalType,
+    /// Not all members of the group support the type of a custom proposal.
+    #[error("Not all members of the group support the type of a custom proposal.")]
+    UnsupportedCustomProposal,
 }
 
 /// External Commit validaton error
@@ -408,6 +465,20 @@ pub enum CreateAddProposalError {
     LeafNodeValidation(#[from] LeafNodeValidationError),
 }
 
+/// Create custom proposal error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum CreateCustomProposalError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// The proposal type is defined by MLS and can't be used for a custom proposal.
+    #[error("The proposal type is defined by MLS and can't be used for a custom proposal.")]
+    ReservedProposalType,
+    /// See [`LeafNodeValidationError`] for more details.
+    #[error(transparent)]
+    LeafNodeValidation(#[from] LeafNodeValidationError),
+}
+
 // === Crate errors ===
 
 /// Exporter error
@@ -500,16 +571,10 @@ pub(crate) enum CoreGroupParseMessageError {
 
 /// Create group context ext proposal error
 #[derive(Error, Debug, PartialEq, Clone)]
-pub(crate) enum CreateGroupContextExtProposalError {
+pub enum CreateGroupContextExtProposalError {
     /// See [`LibraryError`] for more details.
     #[error(transparent)]
     LibraryError(#[from] LibraryError),
-    /// See [`KeyPackageExtensionSupportError`] for more details.
-    #[error(transparent)]
-    KeyPackageExtensionSupport(#[from] KeyPackageExtensionSupportError),
-    /// See [`TreeSyncError`] for more details.
-    #[error(transparent)]
-    TreeSyncError(#[from] TreeSyncError),
     /// See [`ExtensionError`] for more details.
     #[error(transparent)]
     Extension(#[from] ExtensionError),
@@ -528,3 +593,29 @@ pub enum MergeCommitError<KeyStoreError> {
     #[error("Error accessing the key store.")]
     KeyStoreError(KeyStoreError),
 }
+
+/// Error loading a persisted group state.
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum PersistenceError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// The state was persisted in a format version that is not supported by
+    /// this version of OpenMLS.
+    #[error("The state format version {0} is not supported.")]
+    UnsupportedFormatVersion(u16),
+    /// The persisted state is malformed.
+    #[error(transparent)]
+    MalformedState(#[from] tls_codec::Error),
+}
+
+/// Error saving a group to the key store.
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum SaveGroupError<KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// Error accessing the key store.
+    #[error("Error accessing the key store.")]
+    KeyStoreError(KeyStoreError),
+}
@@ -96,6 +96,12 @@ impl GroupContext {
         self.tree_hash = new_tree_hash.into()
     }
 
+    /// Replace the extensions with the ones of a GroupContextExtensions
+    /// proposal.
+    pub(crate) fn set_extensions(&mut self, extensions: Extensions) {
+        self.extensions = extensions
+    }
+
     /// Update the confirmed transcript hash using the given
     /// `interim_transcript_hash`, as well as the `commit_content`.
     pub(crate) fn update_confirmed_transcript_hash(
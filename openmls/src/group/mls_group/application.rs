@@ -41,7 +41,7 @@ impl MlsGroup {
             .map_err(|_| LibraryError::custom("Malformed plaintext"))?;
 
         // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        self.flag_state_change(GroupStateComponent::MessageSecrets(self.epoch()));
 
         Ok(MlsMessageOut::from_private_message(
             ciphertext,
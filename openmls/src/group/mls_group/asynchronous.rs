@@ -0,0 +1,280 @@
+//! Operations of an [`MlsGroup`] with asynchronous providers
+//!
+//! This module contains the asynchronous variants of the operations of an
+//! [`MlsGroup`] that access the key store or sign. They take an
+//! [`AsyncOpenMlsProvider`] and an [`AsyncSigner`] instead of their
+//! synchronous counterparts.
+//!
+//! Each operation runs its synchronous counterpart, possibly several times,
+//! until all values it reads from the key store and all signatures it needs
+//! are available (see `crate::deferred`). Before the first run, the parts of
+//! the group that the operation can change are copied, e.g. only the message
+//! secrets of the message's epoch when processing a private message. The
+//! group is reset to that copy before it is run again. The values the
+//! operation writes are written to the key store in a single transaction once
+//! it succeeded. If an operation fails, the group and the key store are left
+//! unchanged.
+
+use openmls_traits::{
+    key_store::AsyncOpenMlsKeyStore, signatures::AsyncSigner, AsyncOpenMlsProvider,
+};
+
+use super::{
+    errors::{
+        AddMembersError, AsyncOperationError, ProcessMessageError, RemoveMembersError,
+        SelfUpdateError,
+    },
+    *,
+};
+use crate::{
+    credentials::CredentialWithKey,
+    deferred::DeferredProvider,
+    group::errors::{MergeCommitError, WelcomeError},
+    messages::group_info::GroupInfo,
+    persistence::restore_exact,
+    treesync::RatchetTreeIn,
+};
+
+impl MlsGroup {
+    /// Asynchronous variant of [`MlsGroup::new()`].
+    pub async fn new_async<KeyStore: AsyncOpenMlsKeyStore>(
+        provider: &impl AsyncOpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl AsyncSigner,
+        mls_group_config: &MlsGroupConfig,
+        credential_with_key: CredentialWithKey,
+    ) -> Result<Self, AsyncOperationError<NewGroupError<KeyStore::Error>, KeyStore::Error>> {
+        let provider = DeferredProvider::new(provider);
+        let signer = provider.signer(signer);
+        provider
+            .run(|| {
+                MlsGroup::new(
+                    &provider,
+                    &signer,
+                    mls_group_config,
+                    credential_with_key.clone(),
+                )
+            })
+            .await
+    }
+
+    /// Asynchronous variant of [`MlsGroup::new_from_welcome()`].
+    pub async fn new_from_welcome_async<KeyStore: AsyncOpenMlsKeyStore>(
+        provider: &impl AsyncOpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        ratchet_tree: Option<RatchetTreeIn>,
+        credential_validator: &impl CredentialValidator,
+    ) -> Result<Self, AsyncOperationError<WelcomeError<KeyStore::Error>, KeyStore::Error>> {
+        let provider = DeferredProvider::new(provider);
+        let credential_validator = provider.credential_validator(credential_validator);
+        provider
+            .run(|| {
+                MlsGroup::new_from_welcome(
+                    &provider,
+                    mls_group_config,
+                    welcome.clone(),
+                    ratchet_tree.clone(),
+                    &credential_validator,
+                )
+            })
+            .await
+    }
+
+    /// Asynchronous variant of [`MlsGroup::add_members()`].
+    #[allow(clippy::type_complexity)]
+    pub async fn add_members_async<KeyStore: AsyncOpenMlsKeyStore>(
+        &mut self,
+        provider: &impl AsyncOpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl AsyncSigner,
+        key_packages: &[KeyPackage],
+        credential_validator: &impl CredentialValidator,
+    ) -> Result<
+        (MlsMessageOut, MlsMessageOut, Option<GroupInfo>),
+        AsyncOperationError<AddMembersError<KeyStore::Error>, KeyStore::Error>,
+    > {
+        let provider = DeferredProvider::new(provider);
+        let signer = provider.signer(signer);
+        let credential_validator = provider.credential_validator(credential_validator);
+        let snapshot = self.snapshot()?;
+        self.run_deferred(&provider, snapshot, |group| {
+            group.add_members(&provider, &signer, key_packages, &credential_validator)
+        })
+        .await
+    }
+
+    /// Asynchronous variant of [`MlsGroup::remove_members()`].
+    #[allow(clippy::type_complexity)]
+    pub async fn remove_members_async<KeyStore: AsyncOpenMlsKeyStore>(
+        &mut self,
+        provider: &impl AsyncOpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl AsyncSigner,
+        members: &[LeafNodeIndex],
+    ) -> Result<
+        (MlsMessageOut, Option<MlsMessageOut>, Option<GroupInfo>),
+        AsyncOperationError<RemoveMembersError<KeyStore::Error>, KeyStore::Error>,
+    > {
+        let provider = DeferredProvider::new(provider);
+        let signer = provider.signer(signer);
+        let snapshot = self.snapshot()?;
+        self.run_deferred(&provider, snapshot, |group| {
+            group.remove_members(&provider, &signer, members)
+        })
+        .await
+    }
+
+    /// Asynchronous variant of [`MlsGroup::self_update()`].
+    #[allow(clippy::type_complexity)]
+    pub async fn self_update_async<KeyStore: AsyncOpenMlsKeyStore>(
+        &mut self,
+        provider: &impl AsyncOpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl AsyncSigner,
+    ) -> Result<
+        (MlsMessageOut, Option<MlsMessageOut>, Option<GroupInfo>),
+        AsyncOperationError<SelfUpdateError<KeyStore::Error>, KeyStore::Error>,
+    > {
+        let provider = DeferredProvider::new(provider);
+        let signer = provider.signer(signer);
+        let snapshot = self.snapshot()?;
+        self.run_deferred(&provider, snapshot, |group| {
+            group.self_update(&provider, &signer)
+        })
+        .await
+    }
+
+    /// Asynchronous variant of [`MlsGroup::merge_pending_commit()`].
+    pub async fn merge_pending_commit_async<KeyStore: AsyncOpenMlsKeyStore>(
+        &mut self,
+        provider: &impl AsyncOpenMlsProvider<KeyStoreProvider = KeyStore>,
+    ) -> Result<(), AsyncOperationError<MergePendingCommitError<KeyStore::Error>, KeyStore::Error>>
+    {
+        let provider = DeferredProvider::new(provider);
+        let snapshot = self.snapshot()?;
+        self.run_deferred(&provider, snapshot, |group| {
+            group.merge_pending_commit(&provider)
+        })
+        .await
+    }
+
+    /// Asynchronous variant of [`MlsGroup::merge_staged_commit()`].
+    pub async fn merge_staged_commit_async<KeyStore: AsyncOpenMlsKeyStore>(
+        &mut self,
+        provider: &impl AsyncOpenMlsProvider<KeyStoreProvider = KeyStore>,
+        staged_commit: StagedCommit,
+    ) -> Result<(), AsyncOperationError<MergeCommitError<KeyStore::Error>, KeyStore::Error>> {
+        // Every run merges a copy of the staged commit.
+        let mut staged_commit_state = Vec::new();
+        staged_commit
+            .persist(&mut staged_commit_state)
+            .map_err(LibraryError::missing_bound_check)?;
+
+        let provider = DeferredProvider::new(provider);
+        let snapshot = self.snapshot()?;
+        self.run_deferred(&provider, snapshot, |group| {
+            let staged_commit = restore_exact(&staged_commit_state, StagedCommit::restore)
+                .map_err(|_| LibraryError::custom("Failed to copy the staged commit"))?;
+            group.merge_staged_commit(&provider, staged_commit)
+        })
+        .await
+    }
+
+    /// Asynchronous variant of [`MlsGroup::process_message()`].
+    pub async fn process_message_async<KeyStore: AsyncOpenMlsKeyStore>(
+        &mut self,
+        provider: &impl AsyncOpenMlsProvider<KeyStoreProvider = KeyStore>,
+        message: impl Into<ProtocolMessage>,
+        credential_validator: &impl CredentialValidator,
+    ) -> Result<ProcessedMessage, AsyncOperationError<ProcessMessageError, KeyStore::Error>> {
+        let message = message.into();
+        let provider = DeferredProvider::new(provider);
+        let credential_validator = provider.credential_validator(credential_validator);
+        // Processing a message only changes the message secrets of its epoch,
+        // and only if it is decrypted.
+        let snapshot = match message.wire_format() {
+            WireFormat::PrivateMessage => self.snapshot_message_secrets(message.epoch())?,
+            _ => Snapshot::Nothing,
+        };
+        self.run_deferred(&provider, snapshot, |group| {
+            group.process_message(&provider, message.clone(), &credential_validator)
+        })
+        .await
+    }
+
+    /// Copy the whole group.
+    fn snapshot(&self) -> Result<Snapshot, LibraryError> {
+        let mut state = Vec::new();
+        self.persist(&mut state)
+            .map_err(LibraryError::missing_bound_check)?;
+        Ok(Snapshot::Group(state))
+    }
+
+    /// Copy the message secrets of `epoch`, if the group has any.
+    fn snapshot_message_secrets(&self, epoch: GroupEpoch) -> Result<Snapshot, LibraryError> {
+        if !self.group.message_secrets_epochs().any(|e| e == epoch) {
+            return Ok(Snapshot::Nothing);
+        }
+        let mut state = Vec::new();
+        self.group
+            .persist_message_secrets(epoch, &mut state)
+            .map_err(LibraryError::missing_bound_check)?;
+        Ok(Snapshot::MessageSecrets(epoch, state))
+    }
+
+    /// Reset the parts of this group that are copied in `snapshot`.
+    fn reset(
+        &mut self,
+        snapshot: &Snapshot,
+        state_changes: &StateChanges,
+    ) -> Result<(), LibraryError> {
+        match snapshot {
+            Snapshot::Group(state) => {
+                *self = restore_exact(state, MlsGroup::restore)
+                    .map_err(|_| LibraryError::custom("Failed to restore the group"))?;
+            }
+            Snapshot::MessageSecrets(epoch, state) => {
+                self.group.restore_message_secrets(*epoch, state)?;
+            }
+            Snapshot::Nothing => (),
+        }
+        self.state_changes = state_changes.clone();
+        Ok(())
+    }
+
+    /// Run `operation` with the `provider` on this group. The group is reset
+    /// to `snapshot` before the operation is run again and if it fails.
+    async fn run_deferred<Provider: AsyncOpenMlsProvider, T, E: From<LibraryError>>(
+        &mut self,
+        provider: &DeferredProvider<'_, Provider>,
+        snapshot: Snapshot,
+        mut operation: impl FnMut(&mut MlsGroup) -> Result<T, E>,
+    ) -> Result<
+        T,
+        AsyncOperationError<E, <Provider::KeyStoreProvider as AsyncOpenMlsKeyStore>::Error>,
+    > {
+        let state_changes = self.state_changes.clone();
+        let mut changed = false;
+        let result = provider
+            .run(|| {
+                if changed {
+                    self.reset(&snapshot, &state_changes)?;
+                }
+                changed = true;
+                operation(self)
+            })
+            .await;
+
+        if result.is_err() {
+            self.reset(&snapshot, &state_changes)?;
+        }
+        result
+    }
+}
+
+/// The parts of a group that an asynchronous operation can change.
+enum Snapshot {
+    /// The persisted state of the whole group.
+    Group(Vec<u8>),
+    /// The persisted message secrets of an epoch.
+    MessageSecrets(GroupEpoch, Vec<u8>),
+    /// The operation doesn't change the group.
+    Nothing,
+}
@@ -0,0 +1,179 @@
+//! MLS group branching
+//!
+//! This module contains the functions to branch off a new group from a subset
+//! of the members of an existing group and to join such a branch.
+
+use core_group::create_commit_params::CreateCommitParams;
+use openmls_traits::signatures::Signer;
+
+use super::{errors::BranchError, *};
+use crate::{
+    credentials::CredentialWithKey,
+    group::{config::CryptoConfig, errors::WelcomeError},
+    messages::group_info::GroupInfo,
+    schedule::psk::{PreSharedKeyId, Psk, PskBundle, ResumptionPsk, ResumptionPskUsage},
+    treesync::RatchetTreeIn,
+};
+
+impl MlsGroup {
+    /// Branches off a new group with the given `group_id` from the current
+    /// epoch of this group.
+    ///
+    /// The new group has the same protocol version and ciphersuite as this
+    /// group, regardless of the `crypto_config` of the `mls_group_config`.
+    /// The members of the new group are added by providing a `KeyPackage` for
+    /// each of them and all of them must be members of this group. The Welcome
+    /// message links the new group to the current epoch of this group through
+    /// a resumption PSK with usage `branch`. The new members can join the
+    /// branch with [`MlsGroup::join_branch()`].
+    ///
+    /// If successful, it returns a triple of the new [`MlsGroup`], the
+    /// [`MlsMessageOut`] containing the [Welcome] and an optional [GroupInfo]
+    /// that will be [Some] if the group has the `use_ratchet_tree_extension`
+    /// flag set.
+    #[allow(clippy::type_complexity)]
+    pub fn branch<KeyStore: OpenMlsKeyStore>(
+        &self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        mls_group_config: &MlsGroupConfig,
+        group_id: GroupId,
+        credential_with_key: CredentialWithKey,
+        key_packages: &[KeyPackage],
+    ) -> Result<(MlsGroup, MlsMessageOut, Option<GroupInfo>), BranchError<KeyStore::Error>> {
+        self.is_operational()?;
+
+        if key_packages.is_empty() {
+            return Err(BranchError::EmptyInput(EmptyInputError::AddMembers));
+        }
+
+        // The members of the branch must be a subset of the members of this
+        // group.
+        for key_package in key_packages {
+            if !self
+                .members()
+                .any(|member| &member.credential == key_package.leaf_node().credential())
+            {
+                return Err(BranchError::UnknownMember);
+            }
+        }
+
+        let mut mls_group_config = mls_group_config.clone();
+        mls_group_config.crypto_config = CryptoConfig {
+            ciphersuite: self.ciphersuite(),
+            version: self.group.version(),
+        };
+
+        let mut branch = MlsGroup::new_with_group_id(
+            provider,
+            signer,
+            &mls_group_config,
+            group_id,
+            credential_with_key,
+        )?;
+
+        // The resumption PSK of the current epoch is put into the key store
+        // for the duration of the commit, so that it can be injected into its
+        // key schedule.
+        let psk_keystore_id = PreSharedKeyId::resumption(
+            ResumptionPskUsage::Branch,
+            self.group_id().clone(),
+            self.epoch(),
+            vec![],
+        )
+        .keystore_id()?;
+        provider
+            .key_store()
+            .store(
+                &psk_keystore_id,
+                &PskBundle::from_resumption_psk(
+                    self.group.group_epoch_secrets().resumption_psk(),
+                    self.ciphersuite(),
+                ),
+            )
+            .map_err(BranchError::KeyStoreError)?;
+
+        let psk_id = PreSharedKeyId::new(
+            self.ciphersuite(),
+            provider.rand(),
+            Psk::Resumption(ResumptionPsk::new(
+                ResumptionPskUsage::Branch,
+                self.group_id().clone(),
+                self.epoch(),
+            )),
+        )
+        .map_err(LibraryError::unexpected_crypto_error)?;
+
+        // Create inline add proposals from key packages
+        let inline_proposals = key_packages
+            .iter()
+            .map(|key_package| {
+                Proposal::Add(AddProposal {
+                    key_package: key_package.clone(),
+                })
+            })
+            .collect::<Vec<Proposal>>();
+
+        let params = CreateCommitParams::builder()
+            .framing_parameters(branch.framing_parameters())
+            .proposal_store(&branch.proposal_store)
+            .inline_proposals(inline_proposals)
+            .injected_psks(vec![psk_id])
+            .build();
+        let create_commit_result = branch.group.create_commit(params, provider, signer);
+
+        provider
+            .key_store()
+            .delete::<PskBundle>(&psk_keystore_id)
+            .map_err(BranchError::KeyStoreError)?;
+
+        let create_commit_result = create_commit_result?;
+        let welcome = match create_commit_result.welcome_option {
+            Some(welcome) => welcome,
+            None => {
+                return Err(LibraryError::custom("No secrets to generate commit message.").into())
+            }
+        };
+
+        // Nobody else is in the branch yet, so the commit can be merged right
+        // away.
+        branch.merge_staged_commit(provider, create_commit_result.staged_commit)?;
+
+        let welcome = MlsMessageOut::from_welcome(welcome, branch.group.version());
+
+        Ok((branch, welcome, create_commit_result.group_info))
+    }
+
+    /// Joins a group that was branched off of this group from a [`Welcome`]
+    /// message.
+    ///
+    /// In addition to the checks of [`MlsGroup::new_from_welcome()`], this
+    /// verifies that the Welcome contains a resumption PSK with usage `branch`
+    /// that refers to an epoch of this group that is still available, that
+    /// the version and ciphersuite of the branch match the ones of this group
+    /// and that all members of the branch are members of this group.
+    ///
+    /// Like with [`MlsGroup::new_from_welcome()`], the credentials of all
+    /// members of the branch are checked with the given
+    /// [`CredentialValidator`].
+    pub fn join_branch<KeyStore: OpenMlsKeyStore>(
+        &self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        ratchet_tree: Option<RatchetTreeIn>,
+        credential_validator: &impl CredentialValidator,
+    ) -> Result<MlsGroup, WelcomeError<KeyStore::Error>> {
+        with_transaction(provider.key_store(), || {
+            Self::new_from_welcome_internal(
+                provider,
+                mls_group_config,
+                welcome,
+                ratchet_tree,
+                Some(&self.group),
+                credential_validator,
+            )
+        })
+        .map_err(WelcomeError::KeyStoreError)?
+    }
+}
@@ -0,0 +1,302 @@
+//! Persistence of an [`MlsGroup`] in independent components.
+//!
+//! When an [`MlsGroup`] is saved to a key store, its state is split into the
+//! components defined by [`GroupStateComponent`], which are stored under
+//! separate keys. The group tracks which components changed since it was last
+//! saved in its [`StateChanges`], such that [`MlsGroup::save()`] only writes
+//! those components again.
+//!
+//! Each component is encoded like a persisted state (see
+//! [`crate::persistence`]) and wrapped in the versioned envelope.
+
+use std::collections::BTreeSet;
+
+use openmls_traits::key_store::{MlsEntity, MlsEntityId};
+use serde::{Deserialize, Serialize};
+use tls_codec::Serialize as TlsSerializeTrait;
+
+use super::*;
+use crate::{
+    persistence::{restore_exact, unchanged, unwrap_state, wrap_state},
+    schedule::message_secrets::MessageSecrets,
+    treesync::TreeSync,
+};
+
+/// The label that prefixes the key store keys of all components.
+const COMPONENT_KEY_LABEL: &[u8] = b"MLS group state component";
+
+/// A part of the state of an [`MlsGroup`] that is persisted independently.
+///
+/// The components are ordered such that [`GroupStateComponent::Group`], which
+/// is needed to load all other components, is written last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum GroupStateComponent {
+    /// The ratchet tree of the group.
+    Tree,
+    /// The pending proposals of the group.
+    ProposalStore,
+    /// The own leaf nodes of pending update proposals.
+    OwnLeafNodes,
+    /// The message secrets of an epoch, including the current one.
+    MessageSecrets(GroupEpoch),
+    /// The remaining state of the group, e.g. the configuration, the key
+    /// schedule and a pending commit.
+    Group,
+}
+
+impl GroupStateComponent {
+    /// Returns the tag of the component in its key.
+    fn tag(&self) -> u8 {
+        match self {
+            GroupStateComponent::Group => 1,
+            GroupStateComponent::Tree => 2,
+            GroupStateComponent::ProposalStore => 3,
+            GroupStateComponent::OwnLeafNodes => 4,
+            GroupStateComponent::MessageSecrets(_) => 5,
+        }
+    }
+
+    /// Returns the key under which this component of the group with the
+    /// given id is stored.
+    fn key(&self, group_id: &GroupId) -> Result<Vec<u8>, LibraryError> {
+        let mut key = COMPONENT_KEY_LABEL.to_vec();
+        group_id
+            .tls_serialize(&mut key)
+            .map_err(LibraryError::missing_bound_check)?;
+        key.push(self.tag());
+        if let GroupStateComponent::MessageSecrets(epoch) = self {
+            key.extend_from_slice(&epoch.as_u64().to_be_bytes());
+        }
+        Ok(key)
+    }
+
+    /// Returns the migrations of this component. See
+    /// [`PersistedState::MIGRATIONS`] for more details.
+    pub(crate) fn migrations(&self) -> &'static [Migration] {
+        match self {
+            GroupStateComponent::Group => {
+                &[migrate_config_v1, migrate_config_v2, migrate_config_v3]
+            }
+            GroupStateComponent::Tree
+            | GroupStateComponent::ProposalStore
+            | GroupStateComponent::OwnLeafNodes
+            | GroupStateComponent::MessageSecrets(_) => &[unchanged, unchanged, unchanged],
+        }
+    }
+}
+
+/// The encoded state of a component as it is stored in the key store.
+#[derive(Serialize, Deserialize)]
+struct StoredComponent(Vec<u8>);
+
+impl MlsEntity for StoredComponent {
+    const ID: MlsEntityId = MlsEntityId::GroupState;
+}
+
+/// Tracks the components of an [`MlsGroup`] that changed since the group was
+/// last saved.
+#[derive(Debug, Clone)]
+pub(crate) struct StateChanges {
+    // The components that changed, or `None` if all components have to be
+    // written, e.g. because the group was never saved.
+    changed: Option<BTreeSet<GroupStateComponent>>,
+    // The epochs of the message secrets that are stored in the key store.
+    stored_epochs: BTreeSet<GroupEpoch>,
+}
+
+impl StateChanges {
+    /// Returns the changes of a group that has to be saved entirely.
+    pub(crate) fn all() -> Self {
+        Self {
+            changed: None,
+            stored_epochs: BTreeSet::new(),
+        }
+    }
+
+    /// Returns the changes of a group that was just saved with the message
+    /// secrets of the given epochs.
+    fn saved(stored_epochs: BTreeSet<GroupEpoch>) -> Self {
+        Self {
+            changed: Some(BTreeSet::new()),
+            stored_epochs,
+        }
+    }
+
+    /// Marks `component` as changed.
+    pub(crate) fn flag(&mut self, component: GroupStateComponent) {
+        if let Some(changed) = &mut self.changed {
+            changed.insert(component);
+        }
+    }
+
+    /// Returns [`InnerState::Changed`] if any component changed.
+    pub(crate) fn inner_state(&self) -> InnerState {
+        match &self.changed {
+            Some(changed) if changed.is_empty() => InnerState::Persisted,
+            _ => InnerState::Changed,
+        }
+    }
+}
+
+impl MlsGroup {
+    /// Write the components that changed since the group was last saved to
+    /// the key store and delete the message secrets of epochs that were
+    /// dropped from the group.
+    pub(super) fn save_components<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        store: &KeyStore,
+    ) -> Result<(), SaveGroupError<KeyStore::Error>> {
+        let group_id = self.group_id().clone();
+        let epochs: BTreeSet<GroupEpoch> = self.group.message_secrets_epochs().collect();
+
+        let components: BTreeSet<GroupStateComponent> = match &self.state_changes.changed {
+            Some(changed) => changed
+                .iter()
+                .copied()
+                .filter(|component| match component {
+                    GroupStateComponent::MessageSecrets(epoch) => epochs.contains(epoch),
+                    _ => true,
+                })
+                .chain(
+                    epochs
+                        .difference(&self.state_changes.stored_epochs)
+                        .map(|epoch| GroupStateComponent::MessageSecrets(*epoch)),
+                )
+                .collect(),
+            None => [
+                GroupStateComponent::Tree,
+                GroupStateComponent::ProposalStore,
+                GroupStateComponent::OwnLeafNodes,
+                GroupStateComponent::Group,
+            ]
+            .into_iter()
+            .chain(
+                epochs
+                    .iter()
+                    .map(|epoch| GroupStateComponent::MessageSecrets(*epoch)),
+            )
+            .collect(),
+        };
+
+        for component in components {
+            store
+                .store(
+                    &component.key(&group_id)?,
+                    &StoredComponent(self.component_state(component)?),
+                )
+                .map_err(SaveGroupError::KeyStoreError)?;
+        }
+
+        for epoch in self.state_changes.stored_epochs.difference(&epochs) {
+            store
+                .delete::<StoredComponent>(
+                    &GroupStateComponent::MessageSecrets(*epoch).key(&group_id)?,
+                )
+                .map_err(SaveGroupError::KeyStoreError)?;
+        }
+
+        // Previous versions of OpenMLS stored the group as a whole under its
+        // group id. That state is outdated once the components are written.
+        if self.state_changes.changed.is_none() {
+            store
+                .delete::<MlsGroup>(group_id.as_slice())
+                .map_err(SaveGroupError::KeyStoreError)?;
+        }
+
+        self.state_changes = StateChanges::saved(epochs);
+        Ok(())
+    }
+
+    /// Load the group with the given id from its components in the key store.
+    ///
+    /// Returns `Ok(None)` if the group wasn't stored as components and an
+    /// error if one of its components is missing or can't be decoded.
+    pub(super) fn load_components(
+        group_id: &GroupId,
+        store: &impl OpenMlsKeyStore,
+    ) -> Result<Option<MlsGroup>, PersistenceError> {
+        if store
+            .read::<StoredComponent>(&GroupStateComponent::Group.key(group_id)?)
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let read = |component: GroupStateComponent| -> Result<Vec<u8>, PersistenceError> {
+            let StoredComponent(bytes) = store
+                .read::<StoredComponent>(&component.key(group_id)?)
+                .ok_or_else(missing_component)?;
+            unwrap_state(&bytes, component.migrations())
+        };
+
+        let load = || -> Result<MlsGroup, PersistenceError> {
+            let treesync = restore_exact(&read(GroupStateComponent::Tree)?, TreeSync::restore)?;
+            let proposal_store = restore_exact(
+                &read(GroupStateComponent::ProposalStore)?,
+                ProposalStore::restore,
+            )?;
+            let own_leaf_nodes =
+                restore_exact(&read(GroupStateComponent::OwnLeafNodes)?, Vec::restore)?;
+
+            let mut mls_group = restore_exact(
+                &read(GroupStateComponent::Group)?,
+                |bytes| -> Result<MlsGroup, PersistenceError> {
+                    Ok(MlsGroup {
+                        mls_group_config: MlsGroupConfig::restore(bytes)?,
+                        group: CoreGroup::restore_with_tree_and_secrets(
+                            bytes,
+                            treesync,
+                            |epoch| -> Result<_, PersistenceError> {
+                                restore_exact(
+                                    &read(GroupStateComponent::MessageSecrets(epoch))?,
+                                    <(MessageSecrets, Vec<Member>)>::restore,
+                                )
+                                .map_err(PersistenceError::from)
+                            },
+                        )?,
+                        proposal_store,
+                        own_leaf_nodes,
+                        aad: Vec::restore(bytes)?,
+                        group_state: MlsGroupState::restore(bytes)?,
+                        state_changes: StateChanges::all(),
+                    })
+                },
+            )?;
+            mls_group.state_changes =
+                StateChanges::saved(mls_group.group.message_secrets_epochs().collect());
+
+            Ok(mls_group)
+        };
+
+        load().map(Some)
+    }
+
+    /// Encode the state of `component` and wrap it in the versioned envelope.
+    fn component_state(&self, component: GroupStateComponent) -> Result<Vec<u8>, LibraryError> {
+        let mut state = Vec::new();
+        match component {
+            GroupStateComponent::Tree => self.group.public_group().persist_tree(&mut state),
+            GroupStateComponent::ProposalStore => self.proposal_store.persist(&mut state),
+            GroupStateComponent::OwnLeafNodes => self.own_leaf_nodes.persist(&mut state),
+            GroupStateComponent::MessageSecrets(epoch) => {
+                self.group.persist_message_secrets(epoch, &mut state)
+            }
+            GroupStateComponent::Group => self.persist_group_component(&mut state),
+        }
+        .map_err(LibraryError::missing_bound_check)?;
+
+        wrap_state(&state)
+    }
+
+    /// Persist the state of the group that is not part of another component.
+    fn persist_group_component(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.mls_group_config.persist(writer)?;
+        self.group.persist_without_tree_and_secrets(writer)?;
+        self.aad.persist(writer)?;
+        self.group_state.persist(writer)
+    }
+}
+
+fn missing_component() -> tls_codec::Error {
+    tls_codec::Error::DecodingError("Missing component of the group state".into())
+}
@@ -29,8 +29,10 @@
 
 use super::*;
 use crate::{
-    group::config::CryptoConfig, key_packages::Lifetime,
+    group::config::CryptoConfig,
+    key_packages::{Lifetime, LifetimePolicy},
     tree::sender_ratchet::SenderRatchetConfiguration,
+    treesync::node::leaf_node::Capabilities,
 };
 use serde::{Deserialize, Serialize};
 
@@ -56,10 +58,19 @@ pub struct MlsGroupConfig {
     pub(crate) external_senders: ExternalSendersExtension,
     /// Sender ratchet configuration
     pub(crate) sender_ratchet_configuration: SenderRatchetConfiguration,
-    /// Lifetime of the own leaf node
-    pub(crate) lifetime: Lifetime,
+    /// Lifetime (in seconds) of the own leaf node, starting when the group is
+    /// created. The default lifetime is used if it isn't set.
+    #[serde(deserialize_with = "deserialize_lifetime")]
+    pub(crate) lifetime: Option<u64>,
     /// Ciphersuite and protocol version
     pub(crate) crypto_config: CryptoConfig,
+    /// Policy for the lifetimes of leaf nodes that enter the group
+    #[serde(default)]
+    pub(crate) lifetime_policy: LifetimePolicy,
+    /// Capabilities of the own leaf node when the group is created. The
+    /// default capabilities are used if they aren't set.
+    #[serde(default)]
+    pub(crate) leaf_node_capabilities: Option<Capabilities>,
 }
 
 impl MlsGroupConfig {
@@ -98,14 +109,26 @@ impl MlsGroupConfig {
         &self.sender_ratchet_configuration
     }
 
+    /// Returns the [`MlsGroupConfig`] required capabilities extension
+    pub fn required_capabilities(&self) -> &RequiredCapabilitiesExtension {
+        &self.required_capabilities
+    }
+
+    /// Returns the [`MlsGroupConfig`] capabilities of the own leaf node, or
+    /// `None` if the default capabilities are used.
+    pub fn leaf_node_capabilities(&self) -> Option<&Capabilities> {
+        self.leaf_node_capabilities.as_ref()
+    }
+
     /// Returns the [`MlsGroupConfig`] external senders extension
     pub fn external_senders(&self) -> &ExternalSendersExtension {
         &self.external_senders
     }
 
-    /// Returns the [`MlsGroupConfig`] lifetime configuration.
-    pub fn lifetime(&self) -> &Lifetime {
-        &self.lifetime
+    /// Returns the [`MlsGroupConfig`] lifetime (in seconds) of the own leaf
+    /// node, or `None` if the default lifetime is used.
+    pub fn lifetime(&self) -> Option<u64> {
+        self.lifetime
     }
 
     /// Returns the [`CryptoConfig`].
@@ -113,6 +136,11 @@ impl MlsGroupConfig {
         &self.crypto_config
     }
 
+    /// Returns the [`MlsGroupConfig`] lifetime policy.
+    pub fn lifetime_policy(&self) -> &LifetimePolicy {
+        &self.lifetime_policy
+    }
+
     #[cfg(any(feature = "test-utils", test))]
     pub fn test_default(ciphersuite: Ciphersuite) -> Self {
         Self::builder()
@@ -125,6 +153,153 @@ impl MlsGroupConfig {
     }
 }
 
+impl Persist for MlsGroupConfig {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.persist_v3(writer)?;
+        self.leaf_node_capabilities.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        // The leaf node capabilities were appended in format version 4.
+        let mut config = Self::restore_v3(bytes)?;
+        config.leaf_node_capabilities = Option::<Capabilities>::restore(bytes)?;
+        Ok(config)
+    }
+}
+
+impl MlsGroupConfig {
+    /// Encode the configuration in format version 3, i.e., without leaf node
+    /// capabilities.
+    fn persist_v3(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.wire_format_policy.persist(writer)?;
+        self.padding_size.persist(writer)?;
+        self.max_past_epochs.persist(writer)?;
+        self.number_of_resumption_psks.persist(writer)?;
+        self.use_ratchet_tree_extension.persist(writer)?;
+        self.required_capabilities.persist(writer)?;
+        self.external_senders.persist(writer)?;
+        self.sender_ratchet_configuration.persist(writer)?;
+        self.lifetime.persist(writer)?;
+        self.crypto_config.persist(writer)?;
+        self.lifetime_policy.persist(writer)
+    }
+
+    /// Decode a configuration that was persisted in format version 1, i.e.,
+    /// with an absolute lifetime and without a lifetime policy. The default
+    /// lifetime policy is used.
+    fn restore_v1(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Self::restore_with(bytes, restore_absolute_lifetime, false)
+    }
+
+    /// Decode a configuration that was persisted in format version 2, i.e.,
+    /// with an absolute lifetime.
+    fn restore_v2(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Self::restore_with(bytes, restore_absolute_lifetime, true)
+    }
+
+    /// Decode a configuration that was persisted in format version 3, i.e.,
+    /// without leaf node capabilities.
+    fn restore_v3(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Self::restore_with(bytes, Option::<u64>::restore, true)
+    }
+
+    /// Decode a configuration without leaf node capabilities whose lifetime
+    /// is decoded with `restore_lifetime`. The lifetime policy is only
+    /// decoded if `has_lifetime_policy` is set, otherwise the default is
+    /// used.
+    fn restore_with(
+        bytes: &mut &[u8],
+        restore_lifetime: fn(&mut &[u8]) -> Result<Option<u64>, tls_codec::Error>,
+        has_lifetime_policy: bool,
+    ) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            wire_format_policy: WireFormatPolicy::restore(bytes)?,
+            padding_size: usize::restore(bytes)?,
+            max_past_epochs: usize::restore(bytes)?,
+            number_of_resumption_psks: usize::restore(bytes)?,
+            use_ratchet_tree_extension: bool::restore(bytes)?,
+            required_capabilities: RequiredCapabilitiesExtension::restore(bytes)?,
+            external_senders: ExternalSendersExtension::restore(bytes)?,
+            sender_ratchet_configuration: SenderRatchetConfiguration::restore(bytes)?,
+            lifetime: restore_lifetime(bytes)?,
+            crypto_config: CryptoConfig::restore(bytes)?,
+            lifetime_policy: if has_lifetime_policy {
+                LifetimePolicy::restore(bytes)?
+            } else {
+                LifetimePolicy::default()
+            },
+            leaf_node_capabilities: None,
+        })
+    }
+}
+
+/// Decode the absolute [`Lifetime`] of a configuration that was persisted
+/// before format version 3 as the duration it was created with.
+fn restore_absolute_lifetime(bytes: &mut &[u8]) -> Result<Option<u64>, tls_codec::Error> {
+    Ok(Some(Lifetime::restore(bytes)?.duration()))
+}
+
+/// The lifetime of a configuration was an absolute [`Lifetime`] before it
+/// became a duration.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SerializedLifetime {
+    Duration(Option<u64>),
+    Absolute(Lifetime),
+}
+
+/// Deserialize the lifetime of a configuration, which may be an absolute
+/// [`Lifetime`] if it was serialized by an older version of OpenMLS.
+fn deserialize_lifetime<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match SerializedLifetime::deserialize(deserializer)? {
+        SerializedLifetime::Duration(duration) => duration,
+        SerializedLifetime::Absolute(lifetime) => Some(lifetime.duration()),
+    })
+}
+
+/// Migrates a state that starts with an [`MlsGroupConfig`] from format
+/// version 1 to 2, i.e., adds the default [`LifetimePolicy`] to the
+/// configuration.
+pub(crate) fn migrate_config_v1(state: Vec<u8>) -> Result<Vec<u8>, tls_codec::Error> {
+    let mut bytes = state.as_slice();
+    MlsGroupConfig::restore_v1(&mut bytes)?;
+    let config_length = state.len() - bytes.len();
+    let mut migrated = Vec::with_capacity(state.len() + 9);
+    migrated.extend_from_slice(&state[..config_length]);
+    LifetimePolicy::default().persist(&mut migrated)?;
+    migrated.extend_from_slice(bytes);
+    Ok(migrated)
+}
+
+/// Migrates a state that starts with an [`MlsGroupConfig`] from format
+/// version 2 to 3, i.e., replaces the absolute lifetime of the configuration
+/// with the duration it was created with.
+pub(crate) fn migrate_config_v2(state: Vec<u8>) -> Result<Vec<u8>, tls_codec::Error> {
+    let mut bytes = state.as_slice();
+    let config = MlsGroupConfig::restore_v2(&mut bytes)?;
+    let mut migrated = Vec::with_capacity(state.len());
+    config.persist_v3(&mut migrated)?;
+    migrated.extend_from_slice(bytes);
+    Ok(migrated)
+}
+
+/// Migrates a state that starts with an [`MlsGroupConfig`] from format
+/// version 3 to 4, i.e., adds the leaf node capabilities to the
+/// configuration, which aren't set.
+pub(crate) fn migrate_config_v3(state: Vec<u8>) -> Result<Vec<u8>, tls_codec::Error> {
+    let mut bytes = state.as_slice();
+    MlsGroupConfig::restore_v3(&mut bytes)?;
+    let config_length = state.len() - bytes.len();
+    let mut migrated = Vec::with_capacity(state.len() + 1);
+    migrated.extend_from_slice(&state[..config_length]);
+    None::<Capabilities>.persist(&mut migrated)?;
+    migrated.extend_from_slice(bytes);
+    Ok(migrated)
+}
+
 /// Builder for an [`MlsGroupConfig`].
 #[derive(Default)]
 pub struct MlsGroupConfigBuilder {
@@ -188,9 +363,10 @@ impl MlsGroupConfigBuilder {
         self
     }
 
-    /// Sets the `lifetime` property of the MlsGroupConfig.
-    pub fn lifetime(mut self, lifetime: Lifetime) -> Self {
-        self.config.lifetime = lifetime;
+    /// Sets the `lifetime` property of the MlsGroupConfig, i.e., the lifetime
+    /// (in seconds) of the own leaf node, starting when the group is created.
+    pub fn lifetime(mut self, lifetime: u64) -> Self {
+        self.config.lifetime = Some(lifetime);
         self
     }
 
@@ -200,6 +376,33 @@ impl MlsGroupConfigBuilder {
         self
     }
 
+    /// Sets the `lifetime_policy` property of the MlsGroupConfig.
+    /// See [`LifetimePolicy`] for more information.
+    pub fn lifetime_policy(mut self, lifetime_policy: LifetimePolicy) -> Self {
+        self.config.lifetime_policy = lifetime_policy;
+        self
+    }
+
+    /// Sets the `required_capabilities` property of the MlsGroupConfig.
+    /// All members of the group must support the required extension and
+    /// proposal types, e.g. the proposal types of custom proposals.
+    pub fn required_capabilities(
+        mut self,
+        required_capabilities: RequiredCapabilitiesExtension,
+    ) -> Self {
+        self.config.required_capabilities = required_capabilities;
+        self
+    }
+
+    /// Sets the `leaf_node_capabilities` property of the MlsGroupConfig, i.e.,
+    /// the [`Capabilities`] of the own leaf node when the group is created.
+    /// They must include the required extension and proposal types, e.g. the
+    /// proposal types of custom proposals.
+    pub fn leaf_node_capabilities(mut self, leaf_node_capabilities: Capabilities) -> Self {
+        self.config.leaf_node_capabilities = Some(leaf_node_capabilities);
+        self
+    }
+
     /// Sets the `external_senders` property of the MlsGroupConfig.
     pub fn external_senders(mut self, external_senders: ExternalSendersExtension) -> Self {
         self.config.external_senders = external_senders;
@@ -283,6 +486,47 @@ impl Default for WireFormatPolicy {
     }
 }
 
+/// The policies are encoded as `uint8` values in the order in which their
+/// variants are declared, starting at 1.
+impl Persist for WireFormatPolicy {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        let outgoing: u8 = match self.outgoing {
+            OutgoingWireFormatPolicy::AlwaysCiphertext => 1,
+            OutgoingWireFormatPolicy::AlwaysPlaintext => 2,
+        };
+        let incoming: u8 = match self.incoming {
+            IncomingWireFormatPolicy::AlwaysCiphertext => 1,
+            IncomingWireFormatPolicy::AlwaysPlaintext => 2,
+            IncomingWireFormatPolicy::Mixed => 3,
+        };
+        outgoing.persist(writer)?;
+        incoming.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        let outgoing = match u8::restore(bytes)? {
+            1 => OutgoingWireFormatPolicy::AlwaysCiphertext,
+            2 => OutgoingWireFormatPolicy::AlwaysPlaintext,
+            value => {
+                return Err(tls_codec::Error::DecodingError(format!(
+                    "{value} is not a valid outgoing wire format policy"
+                )))
+            }
+        };
+        let incoming = match u8::restore(bytes)? {
+            1 => IncomingWireFormatPolicy::AlwaysCiphertext,
+            2 => IncomingWireFormatPolicy::AlwaysPlaintext,
+            3 => IncomingWireFormatPolicy::Mixed,
+            value => {
+                return Err(tls_codec::Error::DecodingError(format!(
+                    "{value} is not a valid incoming wire format policy"
+                )))
+            }
+        };
+        Ok(Self { outgoing, incoming })
+    }
+}
+
 impl From<OutgoingWireFormatPolicy> for WireFormat {
     fn from(outgoing: OutgoingWireFormatPolicy) -> Self {
         match outgoing {
@@ -2,15 +2,14 @@ use openmls_traits::signatures::Signer;
 
 use super::*;
 use crate::{
-    ciphersuite::HpkePrivateKey,
-    credentials::CredentialWithKey,
+    credentials::{CredentialValidator, CredentialWithKey},
     group::{
         core_group::create_commit_params::CreateCommitParams,
         errors::{CoreGroupBuildError, ExternalCommitError, WelcomeError},
         public_group::errors::PublicGroupBuildError,
     },
+    key_packages::Lifetime,
     messages::group_info::{GroupInfo, VerifiableGroupInfo},
-    schedule::psk::store::ResumptionPskStore,
     treesync::RatchetTreeIn,
 };
 
@@ -49,7 +48,7 @@ impl MlsGroup {
             add_ratchet_tree_extension: mls_group_config.use_ratchet_tree_extension,
         };
 
-        let mut group = CoreGroup::builder(
+        let mut group_builder = CoreGroup::builder(
             group_id,
             mls_group_config.crypto_config,
             credential_with_key,
@@ -58,9 +57,15 @@ impl MlsGroup {
         .with_required_capabilities(mls_group_config.required_capabilities.clone())
         .with_external_senders(mls_group_config.external_senders.clone())
         .with_max_past_epoch_secrets(mls_group_config.max_past_epochs)
-        .with_lifetime(*mls_group_config.lifetime())
-        .build(provider, signer)
-        .map_err(|e| match e {
+        .with_lifetime(match mls_group_config.lifetime {
+            Some(lifetime) => Lifetime::new_with_time(provider.time(), lifetime),
+            None => Lifetime::default_with_time(provider.time()),
+        });
+        if let Some(capabilities) = &mls_group_config.leaf_node_capabilities {
+            group_builder = group_builder.with_capabilities(capabilities.clone());
+        }
+
+        let mut group = group_builder.build(provider, signer).map_err(|e| match e {
             CoreGroupBuildError::LibraryError(e) => e.into(),
             // We don't support PSKs yet
             CoreGroupBuildError::Psk(e) => {
@@ -93,7 +98,7 @@ impl MlsGroup {
             own_leaf_nodes: vec![],
             aad: vec![],
             group_state: MlsGroupState::Operational,
-            state_changed: InnerState::Changed,
+            state_changes: StateChanges::all(),
         };
 
         Ok(mls_group)
@@ -102,64 +107,59 @@ impl MlsGroup {
     /// Creates a new group from a [`Welcome`] message. Returns an error
     /// ([`WelcomeError::NoMatchingKeyPackage`]) if no [`KeyPackage`]
     /// can be found.
+    ///
+    /// The credentials of all members of the group are checked with the given
+    /// [`CredentialValidator`].
+    ///
+    /// If the group is the successor of a reinitialized group, its group ID,
+    /// version, ciphersuite and extensions are checked against the ReInit
+    /// proposal of the reinitialized group
+    /// ([`WelcomeError::ReInitParametersMismatch`]).
+    ///
+    /// To inspect the group before joining it, use
+    /// [`StagedWelcome::new_from_welcome()`] instead.
     // TODO: #1326 This should take an MlsMessage rather than a Welcome message.
     pub fn new_from_welcome<KeyStore: OpenMlsKeyStore>(
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
         mls_group_config: &MlsGroupConfig,
         welcome: Welcome,
         ratchet_tree: Option<RatchetTreeIn>,
+        credential_validator: &impl CredentialValidator,
     ) -> Result<Self, WelcomeError<KeyStore::Error>> {
-        let resumption_psk_store =
-            ResumptionPskStore::new(mls_group_config.number_of_resumption_psks);
-        let (key_package, _) = welcome
-            .secrets()
-            .iter()
-            .find_map(|egs| {
-                let hash_ref = egs.new_member().as_slice().to_vec();
-                provider
-                    .key_store()
-                    .read(&hash_ref)
-                    .map(|kp: KeyPackage| (kp, hash_ref))
-            })
-            .ok_or(WelcomeError::NoMatchingKeyPackage)?;
-
-        // TODO #751
-        let private_key = provider
-            .key_store()
-            .read::<HpkePrivateKey>(key_package.hpke_init_key().as_slice())
-            .ok_or(WelcomeError::NoMatchingKeyPackage)?;
-        let key_package_bundle = KeyPackageBundle {
-            key_package,
-            private_key,
-        };
-
-        // Delete the [`KeyPackage`] and the corresponding private key from the
-        // key store
-        key_package_bundle
-            .key_package
-            .delete(provider)
-            .map_err(WelcomeError::KeyStoreError)?;
+        // The key package is only deleted from the key store if the group is
+        // joined.
+        with_transaction(provider.key_store(), || {
+            StagedWelcome::new_from_welcome(
+                provider,
+                mls_group_config,
+                welcome,
+                ratchet_tree,
+                credential_validator,
+            )?
+            .into_group_internal(provider)
+        })
+        .map_err(WelcomeError::KeyStoreError)?
+    }
 
-        let mut group = CoreGroup::new_from_welcome(
+    // Creates a new group from a [`Welcome`] message. If `branch_parent` is
+    // set, the group must have been branched off of it.
+    pub(super) fn new_from_welcome_internal<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        ratchet_tree: Option<RatchetTreeIn>,
+        branch_parent: Option<&CoreGroup>,
+        credential_validator: &impl CredentialValidator,
+    ) -> Result<Self, WelcomeError<KeyStore::Error>> {
+        StagedWelcome::new_internal(
+            provider,
+            mls_group_config,
             welcome,
             ratchet_tree,
-            key_package_bundle,
-            provider,
-            resumption_psk_store,
-        )?;
-        group.set_max_past_epochs(mls_group_config.max_past_epochs);
-
-        let mls_group = MlsGroup {
-            mls_group_config: mls_group_config.clone(),
-            group,
-            proposal_store: ProposalStore::new(),
-            own_leaf_nodes: vec![],
-            aad: vec![],
-            group_state: MlsGroupState::Operational,
-            state_changed: InnerState::Changed,
-        };
-
-        Ok(mls_group)
+            branch_parent,
+            credential_validator,
+        )?
+        .into_group_internal(provider)
     }
 
     /// Join an existing group through an External Commit.
@@ -176,6 +176,10 @@ impl MlsGroup {
     ///
     /// Note: If there is a group member in the group with the same identity as us,
     /// this will create a remove proposal.
+    ///
+    /// The credentials of all members of the group are checked with the given
+    /// [`CredentialValidator`].
+    #[allow(clippy::too_many_arguments)]
     pub fn join_by_external_commit(
         provider: &impl OpenMlsProvider,
         signer: &impl Signer,
@@ -184,6 +188,7 @@ impl MlsGroup {
         mls_group_config: &MlsGroupConfig,
         aad: &[u8],
         credential_with_key: CredentialWithKey,
+        credential_validator: &impl CredentialValidator,
     ) -> Result<(Self, MlsMessageOut, Option<GroupInfo>), ExternalCommitError> {
         // Prepare the commit parameters
         let framing_parameters = FramingParameters::new(aad, WireFormat::PublicMessage);
@@ -200,6 +205,8 @@ impl MlsGroup {
             params,
             ratchet_tree,
             verifiable_group_info,
+            mls_group_config.lifetime_policy,
+            credential_validator,
         )?;
         group.set_max_past_epochs(mls_group_config.max_past_epochs);
 
@@ -212,7 +219,7 @@ impl MlsGroup {
             group_state: MlsGroupState::PendingCommit(Box::new(PendingCommitState::External(
                 create_commit_result.staged_commit,
             ))),
-            state_changed: InnerState::Changed,
+            state_changes: StateChanges::all(),
         };
 
         let public_message: PublicMessage = create_commit_result.commit.into();
@@ -9,10 +9,11 @@ use thiserror::Error;
 
 use crate::{
     error::LibraryError,
-    extensions::errors::InvalidExtensionError,
+    extensions::errors::{ExtensionError, InvalidExtensionError},
     group::errors::{
-        CreateAddProposalError, CreateCommitError, MergeCommitError, StageCommitError,
-        ValidationError,
+        CreateAddProposalError, CreateCommitError, CreateCustomProposalError,
+        CreateGroupContextExtProposalError, MergeCommitError, ProposalValidationError,
+        StageCommitError, ValidationError,
     },
     schedule::errors::PskError,
     treesync::errors::{LeafNodeValidationError, PublicTreeError},
@@ -61,6 +62,9 @@ pub enum MlsGroupStateError {
     /// Tried to use a group after being evicted from it.
     #[error("Tried to use a group after being evicted from it.")]
     UseAfterEviction,
+    /// Tried to use a group after it has been reinitialized.
+    #[error("Tried to use a group after it has been reinitialized.")]
+    UseAfterReInit,
     /// Can't create message because a pending proposal exists.
     #[error("Can't create message because a pending proposal exists.")]
     PendingProposal,
@@ -86,6 +90,12 @@ pub enum MergePendingCommitError<KeyStoreError> {
     MergeCommitError(#[from] MergeCommitError<KeyStoreError>),
 }
 
+impl<KeyStoreError> From<LibraryError> for MergePendingCommitError<KeyStoreError> {
+    fn from(error: LibraryError) -> Self {
+        MergeCommitError::LibraryError(error).into()
+    }
+}
+
 /// Process message error
 #[derive(Error, Debug, PartialEq, Clone)]
 pub enum ProcessMessageError {
@@ -113,6 +123,17 @@ pub enum ProcessMessageError {
     /// The proposal is invalid for the Sender of type [External](crate::prelude::Sender::External)
     #[error("The proposal is invalid for the Sender of type External")]
     UnsupportedProposalType,
+    /// See [`ProposalValidationError`] for more details.
+    #[error(transparent)]
+    InvalidProposal(#[from] ProposalValidationError),
+    /// A credential was rejected by the
+    /// [`CredentialValidator`](crate::credentials::CredentialValidator).
+    #[error("A credential was rejected by the credential validator.")]
+    CredentialRejected,
+    /// The lifetime of a leaf node was rejected by the
+    /// [`LifetimePolicy`](crate::key_packages::LifetimePolicy).
+    #[error("The lifetime of a leaf node was rejected by the lifetime policy.")]
+    LifetimeRejected,
 }
 
 /// Create message error
@@ -141,6 +162,14 @@ pub enum AddMembersError<KeyStoreError> {
     /// See [`MlsGroupStateError`] for more details.
     #[error(transparent)]
     GroupStateError(#[from] MlsGroupStateError),
+    /// A credential was rejected by the
+    /// [`CredentialValidator`](crate::credentials::CredentialValidator).
+    #[error("A credential was rejected by the credential validator.")]
+    CredentialRejected,
+    /// The lifetime of a leaf node was rejected by the
+    /// [`LifetimePolicy`](crate::key_packages::LifetimePolicy).
+    #[error("The lifetime of a leaf node was rejected by the lifetime policy.")]
+    LifetimeRejected,
 }
 
 /// Propose add members error
@@ -222,6 +251,23 @@ pub enum SelfUpdateError<KeyStoreError> {
     KeyStoreError,
 }
 
+/// Update group context extensions error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum UpdateGroupContextExtensionsError<KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`CreateCommitError`] for more details.
+    #[error(transparent)]
+    CreateCommitError(#[from] CreateCommitError<KeyStoreError>),
+    /// See [`MlsGroupStateError`] for more details.
+    #[error(transparent)]
+    GroupStateError(#[from] MlsGroupStateError),
+    /// See [`ExtensionError`] for more details.
+    #[error(transparent)]
+    Extension(#[from] ExtensionError),
+}
+
 /// Propose self update error
 #[derive(Error, Debug, PartialEq, Clone)]
 pub enum ProposeSelfUpdateError<KeyStoreError> {
@@ -254,6 +300,64 @@ pub enum CommitToPendingProposalsError<KeyStoreError> {
     GroupStateError(#[from] MlsGroupStateError),
 }
 
+/// Reinitialization error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum ReInitError<KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// The group has not been reinitialized.
+    #[error("The group has not been reinitialized.")]
+    NotReinitialized,
+    /// See [`EmptyInputError`] for more details.
+    #[error(transparent)]
+    EmptyInput(#[from] EmptyInputError),
+    /// A KeyPackage doesn't belong to a member of the reinitialized group.
+    #[error("A KeyPackage doesn't belong to a member of the reinitialized group.")]
+    UnknownMember,
+    /// See [`NewGroupError`] for more details.
+    #[error(transparent)]
+    NewGroupError(#[from] NewGroupError<KeyStoreError>),
+    /// See [`CreateCommitError`] for more details.
+    #[error(transparent)]
+    CreateCommitError(#[from] CreateCommitError<KeyStoreError>),
+    /// See [`MergeCommitError`] for more details.
+    #[error(transparent)]
+    MergeCommitError(#[from] MergeCommitError<KeyStoreError>),
+    /// Error accessing the key store.
+    #[error("Error accessing the key store.")]
+    KeyStoreError(KeyStoreError),
+}
+
+/// Branch error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum BranchError<KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// See [`MlsGroupStateError`] for more details.
+    #[error(transparent)]
+    GroupStateError(#[from] MlsGroupStateError),
+    /// See [`EmptyInputError`] for more details.
+    #[error(transparent)]
+    EmptyInput(#[from] EmptyInputError),
+    /// A KeyPackage doesn't belong to a member of the parent group.
+    #[error("A KeyPackage doesn't belong to a member of the parent group.")]
+    UnknownMember,
+    /// Error accessing the key store.
+    #[error("Error accessing the key store.")]
+    KeyStoreError(KeyStoreError),
+    /// See [`NewGroupError`] for more details.
+    #[error(transparent)]
+    NewGroupError(#[from] NewGroupError<KeyStoreError>),
+    /// See [`CreateCommitError`] for more details.
+    #[error(transparent)]
+    CreateCommitError(#[from] CreateCommitError<KeyStoreError>),
+    /// See [`MergeCommitError`] for more details.
+    #[error(transparent)]
+    MergeCommitError(#[from] MergeCommitError<KeyStoreError>),
+}
+
 /// Errors that can happen when exporting a group info object.
 #[derive(Error, Debug, PartialEq, Clone)]
 pub enum ExportGroupInfoError {
@@ -293,6 +397,14 @@ pub enum ProposePskError {
     LibraryError(#[from] LibraryError),
 }
 
+/// Create external proposal error
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum CreateExternalProposalError {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+}
+
 /// Export secret error
 #[derive(Error, Debug, PartialEq, Clone)]
 pub enum ProposalError<KeyStoreError> {
@@ -311,6 +423,12 @@ pub enum ProposalError<KeyStoreError> {
     /// See [`ProposeRemoveMemberError`] for more details.
     #[error(transparent)]
     ProposeRemoveMemberError(#[from] ProposeRemoveMemberError),
+    /// See [`CreateGroupContextExtProposalError`] for more details.
+    #[error(transparent)]
+    CreateGroupContextExtProposalError(#[from] CreateGroupContextExtProposalError),
+    /// See [`CreateCustomProposalError`] for more details.
+    #[error(transparent)]
+    CreateCustomProposalError(#[from] CreateCustomProposalError),
     /// See [`MlsGroupStateError`] for more details.
     #[error(transparent)]
     GroupStateError(#[from] MlsGroupStateError),
@@ -318,3 +436,19 @@ pub enum ProposalError<KeyStoreError> {
     #[error(transparent)]
     ValidationError(#[from] ValidationError),
 }
+
+/// Error of an operation of an [`MlsGroup`](super::MlsGroup) with an
+/// asynchronous provider.
+#[cfg(feature = "async")]
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum AsyncOperationError<OperationError, KeyStoreError> {
+    /// See [`LibraryError`] for more details.
+    #[error(transparent)]
+    LibraryError(#[from] LibraryError),
+    /// The operation failed.
+    #[error(transparent)]
+    OperationError(OperationError),
+    /// Error writing the values of the operation to the key store.
+    #[error("Error writing the values of the operation to the key store.")]
+    KeyStoreError(KeyStoreError),
+}
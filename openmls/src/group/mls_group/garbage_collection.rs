@@ -0,0 +1,98 @@
+//! Garbage collection of the values that OpenMLS stored in the key store.
+
+use std::collections::HashSet;
+
+use openmls_traits::{
+    key_store::{with_transaction, MlsEntityId, OpenMlsKeyStore},
+    types::HpkePrivateKey,
+    OpenMlsProvider,
+};
+
+use super::*;
+use crate::treesync::node::encryption_keys::EncryptionKeyPair;
+
+impl MlsGroup {
+    /// Remove the values from the key store of the `provider` that are no
+    /// longer needed by any of the given `groups`, i.e.,
+    ///
+    /// - the key packages whose [`Lifetime`](crate::key_packages::Lifetime)
+    ///   expired,
+    /// - the private init keys that don't belong to a stored key package,
+    /// - the encryption keys that don't belong to a stored key package, the
+    ///   current epoch of one of the `groups` or a pending update of one of the
+    ///   `groups`.
+    ///
+    /// The `groups` must contain all groups that are still in use. The keys of
+    /// all other groups are removed.
+    ///
+    /// All values are removed in a single transaction of the key store. If the
+    /// key store doesn't support listing values (see
+    /// [`OpenMlsKeyStore::list_keys()`]), no value is removed.
+    ///
+    /// Returns the expired key packages that were removed, or an error if
+    /// accessing the key store fails.
+    pub fn collect_garbage<'a, KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        groups: impl IntoIterator<Item = &'a MlsGroup>,
+    ) -> Result<Vec<KeyPackage>, KeyStore::Error> {
+        let key_store = provider.key_store();
+
+        with_transaction(key_store, || -> Result<_, KeyStore::Error> {
+            let mut expired_key_packages = Vec::new();
+            let mut init_keys = HashSet::new();
+            let mut encryption_keys = HashSet::new();
+
+            // Without the key packages, it isn't known which keys are still
+            // used by a key package.
+            let Some(key_packages) = key_store.list::<KeyPackage>()? else {
+                return Ok(expired_key_packages);
+            };
+            for (key, key_package) in key_packages {
+                let leaf_node = key_package.leaf_node();
+                if leaf_node
+                    .life_time()
+                    .map(|lifetime| lifetime.is_expired(provider.time()))
+                    .unwrap_or_default()
+                {
+                    key_store.delete::<KeyPackage>(&key)?;
+                    expired_key_packages.push(key_package);
+                } else {
+                    init_keys.insert(key_package.hpke_init_key().as_slice().to_vec());
+                    encryption_keys.insert(leaf_node.encryption_key().to_bytes_with_prefix());
+                }
+            }
+
+            for group in groups {
+                encryption_keys.insert(group.group.epoch_keypairs_key());
+                encryption_keys.extend(
+                    group
+                        .own_leaf_nodes
+                        .iter()
+                        .map(|leaf_node| leaf_node.encryption_key().to_bytes_with_prefix()),
+                );
+            }
+
+            for key in key_store
+                .list_keys(MlsEntityId::HpkePrivateKey)?
+                .unwrap_or_default()
+            {
+                if !init_keys.contains(&key) {
+                    key_store.delete::<HpkePrivateKey>(&key)?;
+                }
+            }
+
+            // The encryption keys of the epochs of a group are stored as a
+            // list under the same entity id as single encryption keys.
+            for key in key_store
+                .list_keys(MlsEntityId::EncryptionKeyPair)?
+                .unwrap_or_default()
+            {
+                if !encryption_keys.contains(&key) {
+                    key_store.delete::<EncryptionKeyPair>(&key)?;
+                }
+            }
+
+            Ok(expired_key_packages)
+        })?
+    }
+}
@@ -26,6 +26,11 @@ impl MlsGroup {
     /// contains the commit, the second one the [Welcome] and the third an optional [GroupInfo] that
     /// will be [Some] if the group has the `use_ratchet_tree_extension` flag set.
     ///
+    /// The credentials of the new members are checked with the given
+    /// [`CredentialValidator`] and the lifetimes of their key packages with
+    /// the [`LifetimePolicy`](crate::key_packages::LifetimePolicy) of the
+    /// group.
+    ///
     /// Returns an error if there is a pending commit.
     // FIXME: #1217
     #[allow(clippy::type_complexity)]
@@ -34,6 +39,7 @@ impl MlsGroup {
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
         signer: &impl Signer,
         key_packages: &[KeyPackage],
+        credential_validator: &impl CredentialValidator,
     ) -> Result<(MlsMessageOut, MlsMessageOut, Option<GroupInfo>), AddMembersError<KeyStore::Error>>
     {
         self.is_operational()?;
@@ -42,6 +48,23 @@ impl MlsGroup {
             return Err(AddMembersError::EmptyInput(EmptyInputError::AddMembers));
         }
 
+        if !self.validate_leaf_node_lifetimes(
+            provider,
+            key_packages
+                .iter()
+                .map(|key_package| key_package.leaf_node()),
+        ) {
+            return Err(AddMembersError::LifetimeRejected);
+        }
+        if !self.validate_leaf_node_credentials(
+            key_packages
+                .iter()
+                .map(|key_package| (None, key_package.leaf_node())),
+            credential_validator,
+        ) {
+            return Err(AddMembersError::CredentialRejected);
+        }
+
         // Create inline add proposals from key packages
         let inline_proposals = key_packages
             .iter()
@@ -79,7 +102,7 @@ impl MlsGroup {
         )));
 
         // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        self.flag_state_change(GroupStateComponent::Group);
 
         Ok((
             mls_messages,
@@ -151,7 +174,7 @@ impl MlsGroup {
         )));
 
         // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        self.flag_state_change(GroupStateComponent::Group);
 
         Ok((
             mls_message,
@@ -188,6 +211,9 @@ impl MlsGroup {
                 remove_proposal.clone(),
             )?);
 
+        // Since the state of the group might be changed, arm the state flag
+        self.flag_state_change(GroupStateComponent::ProposalStore);
+
         Ok(self.content_to_mls_message(remove_proposal, provider)?)
     }
 
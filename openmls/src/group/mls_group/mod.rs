@@ -6,23 +6,36 @@ use super::proposals::{ProposalStore, QueuedProposal};
 use crate::{
     binary_tree::array_representation::LeafNodeIndex,
     ciphersuite::hash_ref::ProposalRef,
-    credentials::Credential,
+    credentials::{Credential, CredentialValidator},
     error::LibraryError,
+    extensions::Extensions,
     framing::{mls_auth_content::AuthenticatedContent, *},
     group::*,
     key_packages::{KeyPackage, KeyPackageBundle},
     messages::{proposals::*, Welcome},
+    persistence::{Migration, Persist, PersistedState},
     schedule::ResumptionPskSecret,
     treesync::{node::leaf_node::LeafNode, RatchetTree},
 };
-use openmls_traits::{key_store::OpenMlsKeyStore, types::Ciphersuite, OpenMlsProvider};
+use openmls_traits::{
+    key_store::{with_transaction, OpenMlsKeyStore},
+    types::Ciphersuite,
+    OpenMlsProvider,
+};
 
 // Private
 mod application;
+#[cfg(feature = "async")]
+mod asynchronous;
+mod branch;
+mod components;
 mod creation;
 mod exporting;
+mod garbage_collection;
+mod reinit;
 mod updates;
 
+use components::*;
 use config::*;
 use errors::*;
 
@@ -33,6 +46,7 @@ pub(crate) mod membership;
 pub(crate) mod processing;
 pub(crate) mod proposal;
 pub(crate) mod ser;
+pub(crate) mod staged_welcome;
 
 // Tests
 #[cfg(test)]
@@ -87,6 +101,12 @@ impl From<PendingCommitState> for StagedCommit {
 /// wants to re-join the group, it can either be added by a group member or it
 /// can join via external commit.
 ///
+/// * [`MlsGroupState::Reinitialized`]: A group enters this state when it merges
+/// a commit that covers a ReInit proposal. This is a terminal state that the
+/// group can not exit from. The group can only be used to create its successor
+/// via [`MlsGroup::reinitialize()`] or to join it via
+/// [`MlsGroup::new_from_welcome()`].
+///
 /// * [`MlsGroupState::PendingCommit`]: This state is split into two possible
 /// sub-states, one for each Commit type:
 /// [`PendingCommitState::Member`] and [`PendingCommitState::Member`]:
@@ -120,6 +140,62 @@ pub enum MlsGroupState {
     Operational,
     /// The group is inactive because the member has been removed.
     Inactive,
+    /// The group has been reinitialized with the parameters of the given ReInit proposal.
+    Reinitialized(Box<ReInitProposal>),
+}
+
+impl Persist for PendingCommitState {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        match self {
+            PendingCommitState::Member(staged_commit) => {
+                1u8.persist(writer)?;
+                staged_commit.persist(writer)
+            }
+            PendingCommitState::External(staged_commit) => {
+                2u8.persist(writer)?;
+                staged_commit.persist(writer)
+            }
+        }
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        match u8::restore(bytes)? {
+            1 => StagedCommit::restore(bytes).map(PendingCommitState::Member),
+            2 => StagedCommit::restore(bytes).map(PendingCommitState::External),
+            tag => Err(tls_codec::Error::DecodingError(format!(
+                "{tag} is not a valid pending commit state"
+            ))),
+        }
+    }
+}
+
+impl Persist for MlsGroupState {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        match self {
+            MlsGroupState::PendingCommit(pending_commit_state) => {
+                1u8.persist(writer)?;
+                pending_commit_state.persist(writer)
+            }
+            MlsGroupState::Operational => 2u8.persist(writer),
+            MlsGroupState::Inactive => 3u8.persist(writer),
+            MlsGroupState::Reinitialized(reinit_proposal) => {
+                4u8.persist(writer)?;
+                reinit_proposal.persist(writer)
+            }
+        }
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        match u8::restore(bytes)? {
+            1 => Box::restore(bytes).map(MlsGroupState::PendingCommit),
+            2 => Ok(MlsGroupState::Operational),
+            3 => Ok(MlsGroupState::Inactive),
+            4 => Box::restore(bytes).map(MlsGroupState::Reinitialized),
+            tag => Err(tls_codec::Error::DecodingError(format!(
+                "{tag} is not a valid group state"
+            ))),
+        }
+    }
 }
 
 /// A `MlsGroup` represents an MLS group with a high-level API. The API exposes
@@ -166,10 +242,10 @@ pub struct MlsGroup {
     // A variable that indicates the state of the group. See [`MlsGroupState`]
     // for more information.
     group_state: MlsGroupState,
-    // A flag that indicates if the group state has changed and needs to be persisted again. The value
-    // is set to `InnerState::Changed` whenever an the internal group state is change and is set to
-    // `InnerState::Persisted` once the state has been persisted.
-    state_changed: InnerState,
+    // Tracks the components of the group state that have changed and need to be persisted again.
+    // Components are flagged whenever they are changed and the flags are reset once the group
+    // has been saved. See [`StateChanges`] for more information.
+    state_changes: StateChanges,
 }
 
 impl MlsGroup {
@@ -185,7 +261,7 @@ impl MlsGroup {
         self.mls_group_config = mls_group_config.clone();
 
         // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        self.flag_state_change(GroupStateComponent::Group);
     }
 
     /// Returns the AAD used in the framing.
@@ -198,7 +274,7 @@ impl MlsGroup {
         self.aad = aad.to_vec();
 
         // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        self.flag_state_change(GroupStateComponent::Group);
     }
 
     // === Advanced functions ===
@@ -214,6 +290,15 @@ impl MlsGroup {
         !matches!(self.group_state, MlsGroupState::Inactive)
     }
 
+    /// Returns the ReInit proposal the group was reinitialized with, or `None`
+    /// if the group hasn't been reinitialized.
+    pub fn reinit_proposal(&self) -> Option<&ReInitProposal> {
+        match self.group_state {
+            MlsGroupState::Reinitialized(ref reinit_proposal) => Some(reinit_proposal),
+            _ => None,
+        }
+    }
+
     /// Returns own credential. If the group is inactive, it returns a
     /// `UseAfterEviction` error.
     pub fn credential(&self) -> Result<&Credential, MlsGroupStateError> {
@@ -267,6 +352,7 @@ impl MlsGroup {
             }
             MlsGroupState::Operational => None,
             MlsGroupState::Inactive => None,
+            MlsGroupState::Reinitialized(_) => None,
         }
     }
 
@@ -285,35 +371,76 @@ impl MlsGroup {
         match self.group_state {
             MlsGroupState::PendingCommit(ref pending_commit_state) => {
                 if let PendingCommitState::Member(_) = **pending_commit_state {
-                    self.group_state = MlsGroupState::Operational
+                    self.group_state = MlsGroupState::Operational;
+                    self.flag_state_change(GroupStateComponent::Group);
                 }
             }
-            MlsGroupState::Operational | MlsGroupState::Inactive => (),
+            MlsGroupState::Operational
+            | MlsGroupState::Inactive
+            | MlsGroupState::Reinitialized(_) => (),
         }
     }
 
     // === Load & save ===
 
     /// Loads the state from persisted state.
-    pub fn load(group_id: &GroupId, store: &impl OpenMlsKeyStore) -> Option<MlsGroup> {
-        store.read(group_id.as_slice())
+    ///
+    /// Groups that were saved as a whole by previous versions of OpenMLS are
+    /// loaded as well. They are stored as components the next time they are
+    /// saved.
+    ///
+    /// Returns `Ok(None)` if no group with the given id is stored and a
+    /// [`PersistenceError`] if the stored state can't be loaded, e.g. because
+    /// one of its components is missing or malformed.
+    pub fn load(
+        group_id: &GroupId,
+        store: &impl OpenMlsKeyStore,
+    ) -> Result<Option<MlsGroup>, PersistenceError> {
+        match Self::load_components(group_id, store)? {
+            Some(mls_group) => Ok(Some(mls_group)),
+            None => Ok(store.read(group_id.as_slice())),
+        }
     }
 
     /// Persists the state.
+    ///
+    /// The state is stored in independent components, i.e., the tree, the
+    /// message secrets of each epoch, the pending proposals, the own leaf
+    /// nodes and the remaining group state. Only the components that changed
+    /// since the group was last saved or loaded are written, in a single
+    /// transaction of the key store.
     pub fn save<KeyStore: OpenMlsKeyStore>(
         &mut self,
         store: &KeyStore,
-    ) -> Result<(), KeyStore::Error> {
-        store.store(self.group_id().as_slice(), &*self)?;
+    ) -> Result<(), SaveGroupError<KeyStore::Error>> {
+        with_transaction(store, || self.save_components(store))
+            .map_err(SaveGroupError::KeyStoreError)?
+    }
 
-        self.state_changed = InnerState::Persisted;
-        Ok(())
+    /// Encode the state of this group in the current
+    /// [`STATE_FORMAT_VERSION`](crate::group::STATE_FORMAT_VERSION).
+    ///
+    /// In contrast to the serde serialization of internal structs, the
+    /// encoding of a released format version never changes. States persisted
+    /// by previous versions of OpenMLS can therefore always be loaded with
+    /// [`Self::from_state_bytes()`].
+    pub fn to_state_bytes(&self) -> Result<Vec<u8>, LibraryError> {
+        self.to_persisted_state()
+    }
+
+    /// Load a group from a state that was encoded with
+    /// [`Self::to_state_bytes()`] by this or a previous version of OpenMLS.
+    ///
+    /// Returns an error if the state format version is not supported or the
+    /// state is malformed.
+    pub fn from_state_bytes(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        Self::from_persisted_state(bytes)
     }
 
     /// Returns `true` if the internal state has changed and needs to be persisted and
     /// `false` otherwise. Calling [`Self::save()`] resets the value to `false`.
     pub fn state_changed(&self) -> InnerState {
-        self.state_changed
+        self.state_changes.inner_state()
     }
 
     // === Extensions ===
@@ -357,15 +484,16 @@ impl MlsGroup {
                     )
                     // We can be sure the encryption will work because the plaintext was created by us
                     .map_err(|_| LibraryError::custom("Malformed plaintext"))?;
+                self.flag_state_change(GroupStateComponent::MessageSecrets(self.epoch()));
                 MlsMessageOut::from_private_message(ciphertext, self.group.version())
             }
         };
         Ok(msg)
     }
 
-    /// Arm the state changed flag function
-    fn flag_state_change(&mut self) {
-        self.state_changed = InnerState::Changed;
+    /// Arm the state changed flag of the given component
+    fn flag_state_change(&mut self, component: GroupStateComponent) {
+        self.state_changes.flag(component);
     }
 
     /// Group framing parameters
@@ -382,9 +510,109 @@ impl MlsGroup {
         match self.group_state {
             MlsGroupState::PendingCommit(_) => Err(MlsGroupStateError::PendingCommit),
             MlsGroupState::Inactive => Err(MlsGroupStateError::UseAfterEviction),
+            MlsGroupState::Reinitialized(_) => Err(MlsGroupStateError::UseAfterReInit),
             MlsGroupState::Operational => Ok(()),
         }
     }
+
+    /// Checks the lifetimes of the leaf nodes that are added to the group or
+    /// that replace a leaf node with the
+    /// [`LifetimePolicy`](crate::key_packages::LifetimePolicy) of the group.
+    fn validate_leaf_node_lifetimes<'a>(
+        &self,
+        provider: &impl OpenMlsProvider,
+        leaf_nodes: impl IntoIterator<Item = &'a LeafNode>,
+    ) -> bool {
+        let lifetime_policy = self.mls_group_config.lifetime_policy;
+        leaf_nodes
+            .into_iter()
+            .all(|leaf_node| lifetime_policy.accepts(leaf_node, provider.time()))
+    }
+
+    /// Checks the leaf nodes that are added to the group or that replace the
+    /// leaf node at the given index with the given [`CredentialValidator`].
+    /// Leaf nodes that don't change the credential or the signature key of
+    /// the member are skipped.
+    fn validate_leaf_node_credentials<'a>(
+        &self,
+        leaf_nodes: impl IntoIterator<Item = (Option<LeafNodeIndex>, &'a LeafNode)>,
+        credential_validator: &impl CredentialValidator,
+    ) -> bool {
+        leaf_nodes.into_iter().all(|(leaf_index, leaf_node)| {
+            let current_leaf_node =
+                leaf_index.and_then(|leaf_index| self.group.public_group().leaf(leaf_index));
+            let changed = match current_leaf_node {
+                Some(current_leaf_node) => {
+                    current_leaf_node.credential() != leaf_node.credential()
+                        || current_leaf_node.signature_key() != leaf_node.signature_key()
+                }
+                None => true,
+            };
+            !changed
+                || credential_validator
+                    .validate_credential(leaf_node.credential(), leaf_node.signature_key())
+        })
+    }
+
+    /// Checks the external senders in the given group context `extensions`
+    /// with the given [`CredentialValidator`]. External senders that are
+    /// already external senders of the group are skipped.
+    fn validate_external_sender_credentials(
+        &self,
+        extensions: &Extensions,
+        credential_validator: &impl CredentialValidator,
+    ) -> bool {
+        let current_external_senders = self
+            .group
+            .public_group()
+            .group_context()
+            .extensions()
+            .external_senders();
+        extensions
+            .external_senders()
+            .into_iter()
+            .flatten()
+            .filter(|external_sender| {
+                !current_external_senders.is_some_and(|current| current.contains(external_sender))
+            })
+            .all(|external_sender| {
+                credential_validator.validate_credential(
+                    external_sender.credential(),
+                    external_sender.signature_key(),
+                )
+            })
+    }
+}
+
+/// Checks the credentials of all members of the `public_group` except for the
+/// one at `own_leaf_index` and the credentials of its external senders with
+/// the given [`CredentialValidator`].
+pub(crate) fn validate_member_credentials(
+    public_group: &PublicGroup,
+    own_leaf_index: LeafNodeIndex,
+    credential_validator: &impl CredentialValidator,
+) -> bool {
+    let members_valid = public_group
+        .members()
+        .filter(|member| member.index != own_leaf_index)
+        .all(|member| {
+            credential_validator
+                .validate_credential(&member.credential, &member.signature_key.as_slice().into())
+        });
+    let external_senders_valid = public_group
+        .group_context()
+        .extensions()
+        .external_senders()
+        .into_iter()
+        .flatten()
+        .all(|external_sender| {
+            credential_validator.validate_credential(
+                external_sender.credential(),
+                external_sender.signature_key(),
+            )
+        });
+
+    members_valid && external_senders_valid
 }
 
 // Methods used in tests
@@ -423,7 +651,11 @@ impl MlsGroup {
     ) -> Result<(), MlsGroupStateError> {
         self.proposal_store
             .remove(proposal_ref)
-            .ok_or(MlsGroupStateError::PendingProposalNotFound)
+            .ok_or(MlsGroupStateError::PendingProposalNotFound)?;
+
+        // Since the state of the group might be changed, arm the state flag
+        self.flag_state_change(GroupStateComponent::ProposalStore);
+        Ok(())
     }
 }
 
@@ -437,3 +669,32 @@ pub enum InnerState {
     /// The inner group state hasn't changed and doesn't need to be persisted.
     Persisted,
 }
+
+impl Persist for MlsGroup {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.mls_group_config.persist(writer)?;
+        self.group.persist(writer)?;
+        self.proposal_store.persist(writer)?;
+        self.own_leaf_nodes.persist(writer)?;
+        self.aad.persist(writer)?;
+        self.group_state.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            mls_group_config: MlsGroupConfig::restore(bytes)?,
+            group: CoreGroup::restore(bytes)?,
+            proposal_store: ProposalStore::restore(bytes)?,
+            own_leaf_nodes: Vec::restore(bytes)?,
+            aad: Vec::restore(bytes)?,
+            group_state: MlsGroupState::restore(bytes)?,
+            // The state isn't stored as components yet.
+            state_changes: StateChanges::all(),
+        })
+    }
+}
+
+impl PersistedState for MlsGroup {
+    const MIGRATIONS: &'static [Migration] =
+        &[migrate_config_v1, migrate_config_v2, migrate_config_v3];
+}
@@ -6,7 +6,9 @@ use core_group::staged_commit::StagedCommit;
 use openmls_traits::signatures::Signer;
 
 use crate::{
-    group::core_group::create_commit_params::CreateCommitParams, messages::group_info::GroupInfo,
+    group::core_group::create_commit_params::CreateCommitParams,
+    messages::group_info::GroupInfo,
+    schedule::psk::{PreSharedKeyId, PskBundle, ResumptionPskUsage},
 };
 
 use crate::group::errors::MergeCommitError;
@@ -20,6 +22,12 @@ impl MlsGroup {
     /// and semantic validation of the message. It returns a [ProcessedMessage]
     /// enum.
     ///
+    /// Credentials that are added to the group or that change through a
+    /// proposal or a commit, including the credentials of new external
+    /// senders, are checked with the given [`CredentialValidator`]. The lifetimes of the leaf nodes of added key
+    /// packages are checked with the
+    /// [`LifetimePolicy`](crate::key_packages::LifetimePolicy) of the group.
+    ///
     /// # Errors:
     /// Returns an [`ProcessMessageError`] when the validation checks fail
     /// with the exact reason of the failure.
@@ -27,6 +35,7 @@ impl MlsGroup {
         &mut self,
         provider: &impl OpenMlsProvider,
         message: impl Into<ProtocolMessage>,
+        credential_validator: &impl CredentialValidator,
     ) -> Result<ProcessedMessage, ProcessMessageError> {
         // Make sure we are still a member of the group
         if !self.is_active() {
@@ -34,6 +43,12 @@ impl MlsGroup {
                 MlsGroupStateError::UseAfterEviction,
             ));
         }
+        // The group can't advance after it has been reinitialized
+        if self.reinit_proposal().is_some() {
+            return Err(ProcessMessageError::GroupStateError(
+                MlsGroupStateError::UseAfterReInit,
+            ));
+        }
         let message = message.into();
 
         // Check that handshake messages are compatible with the incoming wire format policy
@@ -48,19 +63,71 @@ impl MlsGroup {
             return Err(ProcessMessageError::IncompatibleWireFormat);
         }
 
-        // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        // Decrypting a message advances the secret tree of the message's epoch
+        if message.wire_format() == WireFormat::PrivateMessage {
+            self.flag_state_change(GroupStateComponent::MessageSecrets(message.epoch()));
+        }
 
         // Parse the message
         let sender_ratchet_configuration =
             self.configuration().sender_ratchet_configuration().clone();
-        self.group.process_message(
+        let processed_message = self.group.process_message(
             provider,
             message,
             &sender_ratchet_configuration,
             &self.proposal_store,
             &self.own_leaf_nodes,
-        )
+        )?;
+
+        // Check the credentials that are new to the group
+        let leaf_nodes = match processed_message.content() {
+            ProcessedMessageContent::ApplicationMessage(_) => vec![],
+            ProcessedMessageContent::ProposalMessage(queued_proposal)
+            | ProcessedMessageContent::ExternalJoinProposalMessage(queued_proposal) => {
+                match (queued_proposal.proposal(), queued_proposal.sender()) {
+                    (Proposal::Add(add_proposal), _) => {
+                        vec![(None, add_proposal.key_package().leaf_node())]
+                    }
+                    (Proposal::Update(update_proposal), Sender::Member(leaf_index)) => {
+                        vec![(Some(*leaf_index), update_proposal.leaf_node())]
+                    }
+                    _ => vec![],
+                }
+            }
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => staged_commit
+                .changed_leaves()
+                .map(|(leaf_index, leaf_node)| (Some(leaf_index), leaf_node))
+                .collect(),
+        };
+        if !self.validate_leaf_node_lifetimes(provider, leaf_nodes.iter().map(|(_, leaf)| *leaf)) {
+            return Err(ProcessMessageError::LifetimeRejected);
+        }
+        if !self.validate_leaf_node_credentials(leaf_nodes, credential_validator) {
+            return Err(ProcessMessageError::CredentialRejected);
+        }
+
+        // Check the external senders that are new to the group
+        let group_context_extensions = match processed_message.content() {
+            ProcessedMessageContent::ProposalMessage(queued_proposal) => {
+                match queued_proposal.proposal() {
+                    Proposal::GroupContextExtensions(extensions_proposal) => {
+                        Some(extensions_proposal.extensions())
+                    }
+                    _ => None,
+                }
+            }
+            ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+                staged_commit.group_context_extensions()
+            }
+            _ => None,
+        };
+        if let Some(extensions) = group_context_extensions {
+            if !self.validate_external_sender_credentials(extensions, credential_validator) {
+                return Err(ProcessMessageError::CredentialRejected);
+            }
+        }
+
+        Ok(processed_message)
     }
 
     /// Stores a standalone proposal in the internal [ProposalStore]
@@ -69,7 +136,7 @@ impl MlsGroup {
         self.proposal_store.add(proposal);
 
         // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        self.flag_state_change(GroupStateComponent::ProposalStore);
     }
 
     /// Creates a Commit message that covers the pending proposals that are
@@ -110,7 +177,7 @@ impl MlsGroup {
         )));
 
         // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        self.flag_state_change(GroupStateComponent::Group);
 
         Ok((
             mls_message,
@@ -123,23 +190,76 @@ impl MlsGroup {
 
     /// Merge a [StagedCommit] into the group after inspection. As this advances
     /// the epoch of the group, it also clears any pending commits.
+    ///
+    /// The key material of the new epoch is stored and the one of the previous
+    /// epoch is deleted in a single transaction of the key store.
     pub fn merge_staged_commit<KeyStore: OpenMlsKeyStore>(
         &mut self,
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
         staged_commit: StagedCommit,
     ) -> Result<(), MergeCommitError<KeyStore::Error>> {
-        // Check if we were removed from the group
-        if staged_commit.self_removed() {
-            self.group_state = MlsGroupState::Inactive;
+        with_transaction(provider.key_store(), || {
+            self.merge_staged_commit_internal(provider, staged_commit)
+        })
+        .map_err(MergeCommitError::KeyStoreError)?
+    }
+
+    fn merge_staged_commit_internal<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        staged_commit: StagedCommit,
+    ) -> Result<(), MergeCommitError<KeyStore::Error>> {
+        // Check if the group is reinitialized by this commit
+        let reinit_proposal = staged_commit
+            .reinit_proposal()
+            .map(|queued_proposal| queued_proposal.reinit_proposal().clone());
+
+        if let (Some(reinit_proposal), Some(resumption_psk)) =
+            (&reinit_proposal, staged_commit.resumption_psk())
+        {
+            // Store the resumption psk of the new epoch together with the
+            // ReInit proposal, so that it can be injected into the successor
+            // group and the successor group can be checked against the
+            // proposal when joining it. This happens before the group state
+            // changes, so that a failed write leaves the group unchanged.
+            let psk_id = PreSharedKeyId::resumption(
+                ResumptionPskUsage::Reinit,
+                self.group_id().clone(),
+                staged_commit.group_context().epoch(),
+                vec![],
+            );
+            let psk_bundle = PskBundle::from_reinit(resumption_psk, reinit_proposal.clone());
+            provider
+                .key_store()
+                .store(&psk_id.keystore_id()?, &psk_bundle)
+                .map_err(MergeCommitError::KeyStoreError)?;
         }
 
-        // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        let self_removed = staged_commit.self_removed();
+        let past_epoch = self.epoch();
 
         // Merge staged commit
         self.group
             .merge_staged_commit(provider, staged_commit, &mut self.proposal_store)?;
 
+        // Check if we were removed from the group
+        if self_removed {
+            self.group_state = MlsGroupState::Inactive;
+        }
+
+        // Since the state of the group has changed, arm the state flags of all
+        // components that change with the epoch
+        for component in [
+            GroupStateComponent::Group,
+            GroupStateComponent::Tree,
+            GroupStateComponent::ProposalStore,
+            GroupStateComponent::OwnLeafNodes,
+            GroupStateComponent::MessageSecrets(past_epoch),
+            GroupStateComponent::MessageSecrets(self.epoch()),
+        ] {
+            self.flag_state_change(component);
+        }
+
         // Extract and store the resumption psk for the current epoch
         let resumption_psk = self.group.group_epoch_secrets().resumption_psk();
         self.group
@@ -152,6 +272,10 @@ impl MlsGroup {
         // Delete a potential pending commit
         self.clear_pending_commit();
 
+        if let Some(reinit_proposal) = reinit_proposal {
+            self.group_state = MlsGroupState::Reinitialized(Box::new(reinit_proposal));
+        }
+
         Ok(())
     }
 
@@ -170,6 +294,7 @@ impl MlsGroup {
                 Ok(())
             }
             MlsGroupState::Inactive => Err(MlsGroupStateError::UseAfterEviction)?,
+            MlsGroupState::Reinitialized(_) => Err(MlsGroupStateError::UseAfterReInit)?,
             MlsGroupState::Operational => Ok(()),
         }
     }
@@ -3,6 +3,7 @@ use openmls_traits::{
 };
 
 use super::{
+    components::GroupStateComponent,
     errors::{ProposalError, ProposeAddMemberError, ProposeRemoveMemberError},
     MlsGroup,
 };
@@ -14,7 +15,7 @@ use crate::{
     framing::MlsMessageOut,
     group::{errors::CreateAddProposalError, GroupId, QueuedProposal},
     key_packages::KeyPackage,
-    messages::proposals::ProposalOrRefType,
+    messages::proposals::{CustomProposal, ProposalOrRefType, ReInitProposal},
     prelude::LibraryError,
     schedule::PreSharedKeyId,
     treesync::LeafNode,
@@ -52,6 +53,9 @@ pub enum Propose {
 
     /// Propose adding new group context extensions.
     GroupContextExtensions(Extensions),
+
+    /// A custom proposal with an application defined proposal type.
+    Custom(CustomProposal),
 }
 
 macro_rules! impl_propose_fun {
@@ -85,7 +89,7 @@ macro_rules! impl_propose_fun {
             let mls_message = self.content_to_mls_message(proposal, provider)?;
 
             // Since the state of the group might be changed, arm the state flag
-            self.flag_state_change();
+            self.flag_state_change(GroupStateComponent::ProposalStore);
 
             Ok((mls_message, proposal_ref))
         }
@@ -121,6 +125,48 @@ impl MlsGroup {
         ProposalOrRefType::Proposal
     );
 
+    impl_propose_fun!(
+        propose_reinit,
+        ReInitProposal,
+        create_reinit_proposal,
+        ProposalOrRefType::Reference
+    );
+
+    impl_propose_fun!(
+        propose_reinit_by_value,
+        ReInitProposal,
+        create_reinit_proposal,
+        ProposalOrRefType::Proposal
+    );
+
+    impl_propose_fun!(
+        propose_group_context_extensions,
+        Extensions,
+        create_group_context_ext_proposal,
+        ProposalOrRefType::Reference
+    );
+
+    impl_propose_fun!(
+        propose_group_context_extensions_by_value,
+        Extensions,
+        create_group_context_ext_proposal,
+        ProposalOrRefType::Proposal
+    );
+
+    impl_propose_fun!(
+        propose_custom_proposal,
+        CustomProposal,
+        create_custom_proposal,
+        ProposalOrRefType::Reference
+    );
+
+    impl_propose_fun!(
+        propose_custom_proposal_by_value,
+        CustomProposal,
+        create_custom_proposal,
+        ProposalOrRefType::Proposal
+    );
+
     /// Generate a proposal
     pub fn propose<KeyStore: OpenMlsKeyStore>(
         &mut self,
@@ -181,19 +227,41 @@ impl MlsGroup {
                 )),
             },
             Propose::ReInit {
-                group_id: _,
-                version: _,
-                ciphersuite: _,
-                extensions: _,
-            } => Err(ProposalError::LibraryError(LibraryError::custom(
-                "Unsupported proposal type ReInit",
-            ))),
+                group_id,
+                version,
+                ciphersuite,
+                extensions,
+            } => {
+                let reinit_proposal =
+                    ReInitProposal::new(group_id, version, ciphersuite, extensions);
+                match ref_or_value {
+                    ProposalOrRefType::Proposal => {
+                        self.propose_reinit_by_value(provider, signer, reinit_proposal)
+                    }
+                    ProposalOrRefType::Reference => {
+                        self.propose_reinit(provider, signer, reinit_proposal)
+                    }
+                }
+            }
             Propose::ExternalInit(_) => Err(ProposalError::LibraryError(LibraryError::custom(
                 "Unsupported proposal type ExternalInit",
             ))),
-            Propose::GroupContextExtensions(_) => Err(ProposalError::LibraryError(
-                LibraryError::custom("Unsupported proposal type GroupContextExtensions"),
-            )),
+            Propose::GroupContextExtensions(extensions) => match ref_or_value {
+                ProposalOrRefType::Proposal => {
+                    self.propose_group_context_extensions_by_value(provider, signer, extensions)
+                }
+                ProposalOrRefType::Reference => {
+                    self.propose_group_context_extensions(provider, signer, extensions)
+                }
+            },
+            Propose::Custom(custom_proposal) => match ref_or_value {
+                ProposalOrRefType::Proposal => {
+                    self.propose_custom_proposal_by_value(provider, signer, custom_proposal)
+                }
+                ProposalOrRefType::Reference => {
+                    self.propose_custom_proposal(provider, signer, custom_proposal)
+                }
+            },
         }
     }
 
@@ -229,7 +297,38 @@ impl MlsGroup {
         let mls_message = self.content_to_mls_message(add_proposal, provider)?;
 
         // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        self.flag_state_change(GroupStateComponent::ProposalStore);
+
+        Ok((mls_message, proposal_ref))
+    }
+
+    /// Creates an AppAck proposal that acknowledges all application messages
+    /// received from other members in the current epoch.
+    ///
+    /// Returns an error if there is a pending commit.
+    pub fn propose_app_ack<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+    ) -> Result<(MlsMessageOut, ProposalRef), ProposalError<KeyStore::Error>> {
+        self.is_operational()?;
+
+        let app_ack_proposal = self
+            .group
+            .create_app_ack_proposal(self.framing_parameters(), signer)?;
+
+        let proposal = QueuedProposal::from_authenticated_content_by_ref(
+            self.ciphersuite(),
+            provider.crypto(),
+            app_ack_proposal.clone(),
+        )?;
+        let proposal_ref = proposal.proposal_reference();
+        self.proposal_store.add(proposal);
+
+        let mls_message = self.content_to_mls_message(app_ack_proposal, provider)?;
+
+        // Since the state of the group might be changed, arm the state flag
+        self.flag_state_change(GroupStateComponent::ProposalStore);
 
         Ok((mls_message, proposal_ref))
     }
@@ -262,7 +361,7 @@ impl MlsGroup {
         let mls_message = self.content_to_mls_message(remove_proposal, provider)?;
 
         // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        self.flag_state_change(GroupStateComponent::ProposalStore);
 
         Ok((mls_message, proposal_ref))
     }
@@ -318,35 +417,4 @@ impl MlsGroup {
             ))
         }
     }
-
-    #[cfg(test)]
-    pub fn propose_group_context_extensions(
-        &mut self,
-        provider: &impl OpenMlsProvider,
-        extensions: Extensions,
-        signer: &impl Signer,
-    ) -> Result<(MlsMessageOut, ProposalRef), ProposalError<()>> {
-        self.is_operational()?;
-
-        let proposal = self
-            .group
-            .create_group_context_ext_proposal(self.framing_parameters(), extensions, signer)
-            .unwrap();
-
-        let queued_proposal = QueuedProposal::from_authenticated_content_by_ref(
-            self.ciphersuite(),
-            provider.crypto(),
-            proposal.clone(),
-        )?;
-
-        let proposal_ref = queued_proposal.proposal_reference();
-        self.proposal_store.add(queued_proposal);
-
-        let mls_message = self.content_to_mls_message(proposal, provider)?;
-
-        // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
-
-        Ok((mls_message, proposal_ref))
-    }
 }
@@ -0,0 +1,148 @@
+//! MLS group reinitialization
+//!
+//! This module contains the functions to create the successor of a group that
+//! was reinitialized through a ReInit proposal.
+
+use core_group::create_commit_params::CreateCommitParams;
+use openmls_traits::signatures::Signer;
+
+use super::{errors::ReInitError, *};
+use crate::{
+    credentials::CredentialWithKey,
+    group::config::CryptoConfig,
+    messages::group_info::GroupInfo,
+    schedule::psk::{PreSharedKeyId, Psk, PskBundle, ResumptionPsk, ResumptionPskUsage},
+};
+
+impl MlsGroup {
+    /// Creates the successor of a group that has been reinitialized, i.e. that
+    /// merged a commit covering a ReInit proposal.
+    ///
+    /// The successor group is created with the group ID, protocol version and
+    /// ciphersuite of the ReInit proposal. The required capabilities and
+    /// external senders of the `mls_group_config` are replaced by the ones in
+    /// the extensions of the ReInit proposal, if present.
+    ///
+    /// The members of the old group are added by providing a `KeyPackage` for
+    /// each of them. The Welcome message links the successor group to the
+    /// epoch of the old group that was reached with the ReInit commit through
+    /// a resumption PSK with usage `reinit`. Members of the old group that have
+    /// merged the ReInit commit can join the successor group with
+    /// [`MlsGroup::new_from_welcome()`]. The resumption PSK of the old group
+    /// is deleted from the key store once the successor group was created or
+    /// joined, so that it is only used once.
+    ///
+    /// If successful, it returns a triple of the successor [`MlsGroup`], the
+    /// [`MlsMessageOut`] containing the [Welcome] and an optional [GroupInfo]
+    /// that will be [Some] if the group has the `use_ratchet_tree_extension`
+    /// flag set.
+    ///
+    /// Returns an error if the group has not been reinitialized.
+    #[allow(clippy::type_complexity)]
+    pub fn reinitialize<KeyStore: OpenMlsKeyStore>(
+        &self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        mls_group_config: &MlsGroupConfig,
+        credential_with_key: CredentialWithKey,
+        key_packages: &[KeyPackage],
+    ) -> Result<(MlsGroup, MlsMessageOut, Option<GroupInfo>), ReInitError<KeyStore::Error>> {
+        let reinit_proposal = self
+            .reinit_proposal()
+            .ok_or(ReInitError::NotReinitialized)?;
+
+        if key_packages.is_empty() {
+            return Err(ReInitError::EmptyInput(EmptyInputError::AddMembers));
+        }
+
+        // The successor group must have the same members as the old group.
+        for key_package in key_packages {
+            if !self
+                .members()
+                .any(|member| &member.credential == key_package.leaf_node().credential())
+            {
+                return Err(ReInitError::UnknownMember);
+            }
+        }
+
+        // Apply the parameters of the ReInit proposal to the configuration.
+        let mut mls_group_config = mls_group_config.clone();
+        mls_group_config.crypto_config = CryptoConfig {
+            ciphersuite: reinit_proposal.ciphersuite(),
+            version: reinit_proposal.version(),
+        };
+        if let Some(required_capabilities) = reinit_proposal.extensions().required_capabilities() {
+            mls_group_config.required_capabilities = required_capabilities.clone();
+        }
+        if let Some(external_senders) = reinit_proposal.extensions().external_senders() {
+            mls_group_config.external_senders = external_senders.clone();
+        }
+
+        let mut successor = MlsGroup::new_with_group_id(
+            provider,
+            signer,
+            &mls_group_config,
+            reinit_proposal.group_id().clone(),
+            credential_with_key,
+        )?;
+
+        // The resumption PSK of the old group is injected into the key schedule
+        // of the first commit, which is the one the Welcome refers to.
+        let psk_keystore_id = PreSharedKeyId::resumption(
+            ResumptionPskUsage::Reinit,
+            self.group_id().clone(),
+            self.epoch(),
+            vec![],
+        )
+        .keystore_id()?;
+        let psk_id = PreSharedKeyId::new(
+            reinit_proposal.ciphersuite(),
+            provider.rand(),
+            Psk::Resumption(ResumptionPsk::new(
+                ResumptionPskUsage::Reinit,
+                self.group_id().clone(),
+                self.epoch(),
+            )),
+        )
+        .map_err(LibraryError::unexpected_crypto_error)?;
+
+        // Create inline add proposals from key packages
+        let inline_proposals = key_packages
+            .iter()
+            .map(|key_package| {
+                Proposal::Add(AddProposal {
+                    key_package: key_package.clone(),
+                })
+            })
+            .collect::<Vec<Proposal>>();
+
+        let params = CreateCommitParams::builder()
+            .framing_parameters(successor.framing_parameters())
+            .proposal_store(&successor.proposal_store)
+            .inline_proposals(inline_proposals)
+            .injected_psks(vec![psk_id])
+            .build();
+        let create_commit_result = successor.group.create_commit(params, provider, signer)?;
+
+        let welcome = match create_commit_result.welcome_option {
+            Some(welcome) => welcome,
+            None => {
+                return Err(LibraryError::custom("No secrets to generate commit message.").into())
+            }
+        };
+
+        // Nobody else is in the successor group yet, so the commit can be
+        // merged right away.
+        successor.merge_staged_commit(provider, create_commit_result.staged_commit)?;
+
+        // The resumption PSK of the old group isn't needed anymore.
+        provider
+            .key_store()
+            .delete::<PskBundle>(&psk_keystore_id)
+            .map_err(ReInitError::KeyStoreError)?;
+
+        let welcome = MlsMessageOut::from_welcome(welcome, successor.group.version());
+
+        Ok((successor, welcome, create_commit_result.group_info))
+    }
+}
@@ -1,19 +1,22 @@
-// TODO #245: Remove this once we have a proper serialization format
+//! Serde support for [`MlsGroup`].
+//!
+//! An [`MlsGroup`] is serialized as its versioned state (see
+//! [`MlsGroup::to_state_bytes()`]). Groups that were serialized by previous
+//! versions of OpenMLS as a [`SerializedMlsGroup`] can still be deserialized.
+
+// TODO #245: Remove the legacy format once stored groups have been migrated.
 #![allow(deprecated)]
 
 use super::*;
 use crate::schedule::psk::store::ResumptionPskStore;
 
 use openmls_traits::key_store::{MlsEntity, MlsEntityId};
-use serde::{
-    ser::{SerializeStruct, Serializer},
-    Deserialize, Serialize,
-};
+use serde::{de::Error as _, ser::Error as _, Deserialize, Serialize, Serializer};
 
 /// Helper struct that contains the serializable values of an `MlsGroup.
 #[deprecated(
     since = "0.4.1",
-    note = "It is temporarily exposed, it will be private again after #245"
+    note = "Use `MlsGroup::to_state_bytes()` and `MlsGroup::from_state_bytes()` instead"
 )]
 #[derive(Serialize, Deserialize)]
 pub struct SerializedMlsGroup {
@@ -36,7 +39,7 @@ impl Into<MlsGroup> for SerializedMlsGroup {
             own_leaf_nodes: self.own_leaf_nodes,
             aad: self.aad,
             group_state: self.group_state,
-            state_changed: InnerState::Persisted,
+            state_changes: StateChanges::all(),
         }
     }
 }
@@ -50,24 +53,30 @@ impl Serialize for MlsGroup {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("SerializedMlsGroup", 6)?;
-        state.serialize_field("mls_group_config", &self.mls_group_config)?;
-        state.serialize_field("group", &self.group)?;
-        state.serialize_field("proposal_store", &self.proposal_store)?;
-        state.serialize_field("own_leaf_nodes", &self.own_leaf_nodes)?;
-        state.serialize_field("aad", &self.aad)?;
-        state.serialize_field("resumption_psk_store", &self.group.resumption_psk_store)?;
-        state.serialize_field("group_state", &self.group_state)?;
-        state.end()
+        self.to_state_bytes()
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
     }
 }
 
+/// The serialized forms of an [`MlsGroup`] that can be deserialized.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MlsGroupSerde {
+    Versioned(Vec<u8>),
+    Legacy(Box<SerializedMlsGroup>),
+}
+
 impl<'de> Deserialize<'de> for MlsGroup {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let sgroup = SerializedMlsGroup::deserialize(deserializer)?;
-        Ok(sgroup.into())
+        match MlsGroupSerde::deserialize(deserializer)? {
+            MlsGroupSerde::Versioned(bytes) => {
+                MlsGroup::from_state_bytes(&bytes).map_err(D::Error::custom)
+            }
+            MlsGroupSerde::Legacy(sgroup) => Ok((*sgroup).into()),
+        }
     }
 }
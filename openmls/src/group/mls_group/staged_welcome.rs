@@ -0,0 +1,199 @@
+//! A group that was staged from a [`Welcome`] message, but not joined yet.
+
+use super::*;
+use crate::{
+    ciphersuite::HpkePrivateKey,
+    extensions::Extensions,
+    group::{core_group::new_from_welcome::StagedCoreWelcome, errors::WelcomeError},
+    schedule::psk::store::ResumptionPskStore,
+    treesync::RatchetTreeIn,
+};
+
+/// A group that was staged from a [`Welcome`] message, but not joined yet.
+///
+/// A [`StagedWelcome`] is created with [`StagedWelcome::new_from_welcome()`],
+/// which performs the same checks as [`MlsGroup::new_from_welcome()`] without
+/// changing the key store. It can be inspected, e.g. to show who invited the
+/// user to which group, and is then either joined with
+/// [`StagedWelcome::into_group()`] or discarded by dropping it. A discarded
+/// Welcome doesn't consume the key package it was encrypted to.
+#[derive(Debug)]
+pub struct StagedWelcome {
+    mls_group_config: MlsGroupConfig,
+    group: StagedCoreWelcome,
+}
+
+impl StagedWelcome {
+    /// Stages a group from a [`Welcome`] message, i.e., decrypts its group
+    /// secrets and group info with the matching [`KeyPackage`] from the key
+    /// store, validates the ratchet tree and checks the credentials of all
+    /// members of the group with the given [`CredentialValidator`].
+    ///
+    /// Returns the same errors as [`MlsGroup::new_from_welcome()`], e.g.
+    /// [`WelcomeError::NoMatchingKeyPackage`] if no [`KeyPackage`] can be
+    /// found.
+    pub fn new_from_welcome<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        ratchet_tree: Option<RatchetTreeIn>,
+        credential_validator: &impl CredentialValidator,
+    ) -> Result<Self, WelcomeError<KeyStore::Error>> {
+        Self::new_internal(
+            provider,
+            mls_group_config,
+            welcome,
+            ratchet_tree,
+            None,
+            credential_validator,
+        )
+    }
+
+    // Stages a group from a [`Welcome`] message. If `branch_parent` is set,
+    // the group must have been branched off of it.
+    pub(super) fn new_internal<KeyStore: OpenMlsKeyStore>(
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        mls_group_config: &MlsGroupConfig,
+        welcome: Welcome,
+        ratchet_tree: Option<RatchetTreeIn>,
+        branch_parent: Option<&CoreGroup>,
+        credential_validator: &impl CredentialValidator,
+    ) -> Result<Self, WelcomeError<KeyStore::Error>> {
+        let resumption_psk_store =
+            ResumptionPskStore::new(mls_group_config.number_of_resumption_psks);
+        let key_package = welcome
+            .secrets()
+            .iter()
+            .find_map(|egs| {
+                provider
+                    .key_store()
+                    .read::<KeyPackage>(egs.new_member().as_slice())
+            })
+            .ok_or(WelcomeError::NoMatchingKeyPackage)?;
+
+        // TODO #751
+        let private_key = provider
+            .key_store()
+            .read::<HpkePrivateKey>(key_package.hpke_init_key().as_slice())
+            .ok_or(WelcomeError::NoMatchingKeyPackage)?;
+        let key_package_bundle = KeyPackageBundle {
+            key_package,
+            private_key,
+        };
+
+        let group = CoreGroup::stage_welcome(
+            welcome,
+            ratchet_tree,
+            key_package_bundle,
+            provider,
+            resumption_psk_store,
+            mls_group_config.lifetime_policy,
+            branch_parent,
+        )?;
+
+        if !validate_member_credentials(
+            group.public_group(),
+            group.own_leaf_index(),
+            credential_validator,
+        ) {
+            return Err(WelcomeError::CredentialRejected);
+        }
+
+        Ok(Self {
+            mls_group_config: mls_group_config.clone(),
+            group,
+        })
+    }
+
+    /// Joins the staged group.
+    ///
+    /// The [`KeyPackage`] the [`Welcome`] was encrypted to and its private
+    /// keys are deleted from the key store, unless it is a last resort key
+    /// package. Returns [`WelcomeError::NoMatchingKeyPackage`] if the key
+    /// package was deleted since the group was staged, e.g. because another
+    /// Welcome for it was joined.
+    pub fn into_group<KeyStore: OpenMlsKeyStore>(
+        self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+    ) -> Result<MlsGroup, WelcomeError<KeyStore::Error>> {
+        with_transaction(provider.key_store(), || self.into_group_internal(provider))
+            .map_err(WelcomeError::KeyStoreError)?
+    }
+
+    pub(super) fn into_group_internal<KeyStore: OpenMlsKeyStore>(
+        self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+    ) -> Result<MlsGroup, WelcomeError<KeyStore::Error>> {
+        let key_package = self.group.key_package();
+        let hash_ref = key_package.hash_ref(provider.crypto())?;
+        if provider
+            .key_store()
+            .read::<KeyPackage>(hash_ref.as_slice())
+            .is_none()
+        {
+            return Err(WelcomeError::NoMatchingKeyPackage);
+        }
+
+        // Delete the [`KeyPackage`] and the corresponding private key from the
+        // key store, unless it is a last resort key package that can be used
+        // again.
+        if !key_package.last_resort() {
+            key_package
+                .delete(provider)
+                .map_err(WelcomeError::KeyStoreError)?;
+        }
+
+        let mut group = self.group.into_core_group(provider)?;
+        group.set_max_past_epochs(self.mls_group_config.max_past_epochs);
+
+        Ok(MlsGroup {
+            mls_group_config: self.mls_group_config,
+            group,
+            proposal_store: ProposalStore::new(),
+            own_leaf_nodes: vec![],
+            aad: vec![],
+            group_state: MlsGroupState::Operational,
+            state_changes: StateChanges::all(),
+        })
+    }
+
+    /// Returns the [`GroupContext`] of the staged group, e.g. its group ID,
+    /// epoch, ciphersuite and group context extensions.
+    pub fn group_context(&self) -> &GroupContext {
+        self.group.public_group().group_context()
+    }
+
+    /// Returns the extensions of the group info in the [`Welcome`].
+    pub fn group_info_extensions(&self) -> &Extensions {
+        self.group.group_info().extensions()
+    }
+
+    /// Returns the leaf index of the member that created the [`Welcome`].
+    pub fn welcome_sender_index(&self) -> LeafNodeIndex {
+        self.group.welcome_sender_index()
+    }
+
+    /// Returns the leaf node of the member that created the [`Welcome`].
+    pub fn welcome_sender(&self) -> Result<&LeafNode, LibraryError> {
+        self.group
+            .public_group()
+            .leaf(self.welcome_sender_index())
+            .ok_or_else(|| LibraryError::custom("The welcome sender is not in the tree."))
+    }
+
+    /// Returns an `Iterator` over the members of the staged group, including
+    /// the own leaf.
+    pub fn members(&self) -> impl Iterator<Item = Member> + '_ {
+        self.group.public_group().members()
+    }
+
+    /// Returns the own leaf index in the staged group.
+    pub fn own_leaf_index(&self) -> LeafNodeIndex {
+        self.group.own_leaf_index()
+    }
+
+    /// Returns the [`KeyPackage`] the [`Welcome`] was encrypted to.
+    pub fn key_package(&self) -> &KeyPackage {
+        self.group.key_package()
+    }
+}
@@ -1,17 +1,36 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use core_group::test_core_group::setup_client;
 use openmls_rust_crypto::OpenMlsRustCrypto;
-use openmls_traits::{key_store::OpenMlsKeyStore, OpenMlsProvider};
+use openmls_traits::{
+    crypto::OpenMlsCrypto,
+    key_store::{MlsEntity, MlsEntityId, OpenMlsKeyStore},
+    OpenMlsProvider,
+};
+use tls_codec::{Deserialize, Serialize};
 
 use crate::{
     binary_tree::LeafNodeIndex,
+    ciphersuite::SignaturePublicKey,
+    credentials::{AcceptAllCredentials, Credential},
+    extensions::{
+        Extension, ExtensionType, Extensions, ExternalSender, RequiredCapabilitiesExtension,
+        UnknownExtension,
+    },
     framing::*,
-    group::{config::CryptoConfig, errors::*, *},
+    group::{config::CryptoConfig, errors::*, public_group::errors::CreationFromExternalError, *},
     key_packages::*,
     messages::proposals::*,
+    schedule::psk::{PreSharedKeyId, PskBundle, ResumptionPskUsage},
     test_utils::test_framework::{
         errors::ClientError, ActionType::Commit, CodecUse, MlsGroupTestSetup,
     },
     test_utils::*,
+    treesync::{
+        errors::LeafNodeValidationError,
+        node::{encryption_keys::EncryptionKeyPair, leaf_node::Capabilities},
+    },
+    versions::ProtocolVersion,
 };
 
 #[apply(ciphersuites_and_providers)]
@@ -41,8 +60,9 @@ fn test_mls_group_persistence(ciphersuite: Ciphersuite, provider: &impl OpenMlsP
         .save(provider.key_store())
         .expect("Could not write group state to file");
 
-    let alice_group_deserialized =
-        MlsGroup::load(&group_id, provider.key_store()).expect("Could not deserialize MlsGroup");
+    let alice_group_deserialized = MlsGroup::load(&group_id, provider.key_store())
+        .expect("Could not deserialize MlsGroup")
+        .expect("MlsGroup not found");
 
     assert_eq!(
         (
@@ -56,6 +76,195 @@ fn test_mls_group_persistence(ciphersuite: Ciphersuite, provider: &impl OpenMlsP
     );
 }
 
+/// A key store that counts the values that are written to and deleted from
+/// the wrapped key store.
+struct CountingKeyStore<'a, KeyStore> {
+    key_store: &'a KeyStore,
+    writes: AtomicUsize,
+    deletes: AtomicUsize,
+}
+
+impl<'a, KeyStore: OpenMlsKeyStore> CountingKeyStore<'a, KeyStore> {
+    fn new(key_store: &'a KeyStore) -> Self {
+        Self {
+            key_store,
+            writes: AtomicUsize::new(0),
+            deletes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of writes and deletes and resets the counters.
+    fn take_counts(&self) -> (usize, usize) {
+        (
+            self.writes.swap(0, Ordering::SeqCst),
+            self.deletes.swap(0, Ordering::SeqCst),
+        )
+    }
+}
+
+impl<KeyStore: OpenMlsKeyStore> OpenMlsKeyStore for CountingKeyStore<'_, KeyStore> {
+    type Error = KeyStore::Error;
+
+    fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        self.key_store.store(k, v)
+    }
+
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> Option<V> {
+        self.key_store.read(k)
+    }
+
+    fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+        self.deletes.fetch_add(1, Ordering::SeqCst);
+        self.key_store.delete::<V>(k)
+    }
+
+    fn list_keys(&self, entity_id: MlsEntityId) -> Result<Option<Vec<Vec<u8>>>, Self::Error> {
+        self.key_store.list_keys(entity_id)
+    }
+
+    fn begin_transaction(&self) -> Result<(), Self::Error> {
+        self.key_store.begin_transaction()
+    }
+
+    fn commit_transaction(&self) -> Result<(), Self::Error> {
+        self.key_store.commit_transaction()
+    }
+
+    fn rollback_transaction(&self) -> Result<(), Self::Error> {
+        self.key_store.rollback_transaction()
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn test_mls_group_persistence_components(
+    ciphersuite: Ciphersuite,
+    provider: &impl OpenMlsProvider,
+) {
+    let group_id = GroupId::from_slice(b"Test Group");
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::builder()
+        .max_past_epochs(1)
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        group_id.clone(),
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let key_store = CountingKeyStore::new(provider.key_store());
+
+    // A new group is written entirely: the tree, the proposal store, the own
+    // leaf nodes, the message secrets of the current epoch and the remaining
+    // group state. The state of a previous version is deleted.
+    alice_group
+        .save(&key_store)
+        .expect("Error saving the group");
+    assert_eq!(key_store.take_counts(), (5, 1));
+    assert_eq!(alice_group.state_changed(), InnerState::Persisted);
+
+    // Nothing is written if nothing changed.
+    alice_group
+        .save(&key_store)
+        .expect("Error saving the group");
+    assert_eq!(key_store.take_counts(), (0, 0));
+
+    // Only the changed component is written.
+    alice_group.set_aad(b"Test AAD");
+    assert_eq!(alice_group.state_changed(), InnerState::Changed);
+    alice_group
+        .save(&key_store)
+        .expect("Error saving the group");
+    assert_eq!(key_store.take_counts(), (1, 0));
+
+    // A new epoch changes all components and the message secrets of the
+    // previous epoch become past message secrets.
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("Error creating a commit");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("Error merging the commit");
+    alice_group
+        .save(&key_store)
+        .expect("Error saving the group");
+    assert_eq!(key_store.take_counts(), (6, 0));
+
+    // Message secrets that are dropped from the group are deleted.
+    alice_group
+        .self_update(provider, &alice_signer)
+        .expect("Error creating a commit");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("Error merging the commit");
+    alice_group
+        .save(&key_store)
+        .expect("Error saving the group");
+    assert_eq!(key_store.take_counts(), (6, 1));
+
+    // The loaded group equals the saved group.
+    let alice_group_loaded = MlsGroup::load(&group_id, &key_store)
+        .expect("Could not load MlsGroup")
+        .expect("MlsGroup not found");
+    assert_eq!(alice_group_loaded.state_changed(), InnerState::Persisted);
+    assert_eq!(alice_group_loaded.aad(), b"Test AAD");
+    assert_eq!(alice_group_loaded.epoch(), alice_group.epoch());
+    assert_eq!(
+        alice_group_loaded.export_ratchet_tree(),
+        alice_group.export_ratchet_tree()
+    );
+    assert_eq!(
+        alice_group_loaded.export_secret(provider.crypto(), "test", &[], 32),
+        alice_group.export_secret(provider.crypto(), "test", &[], 32)
+    );
+    assert_eq!(
+        alice_group_loaded.group().message_secrets_store(),
+        alice_group.group().message_secrets_store()
+    );
+
+    // Groups that were stored as a whole can still be loaded and are stored
+    // as components when they are saved.
+    let legacy_group_id = GroupId::from_slice(b"Legacy Group");
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let bob_group = MlsGroup::new_with_group_id(
+        provider,
+        &bob_signer,
+        &mls_group_config,
+        legacy_group_id.clone(),
+        bob_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+    provider
+        .key_store()
+        .store(legacy_group_id.as_slice(), &bob_group)
+        .expect("Error storing the group");
+
+    let mut bob_group_loaded = MlsGroup::load(&legacy_group_id, &key_store)
+        .expect("Could not load MlsGroup")
+        .expect("MlsGroup not found");
+    assert_eq!(bob_group_loaded.state_changed(), InnerState::Changed);
+    bob_group_loaded
+        .save(&key_store)
+        .expect("Error saving the group");
+    assert_eq!(key_store.take_counts(), (5, 1));
+    assert!(provider
+        .key_store()
+        .read::<MlsGroup>(legacy_group_id.as_slice())
+        .is_none());
+    assert!(MlsGroup::load(&legacy_group_id, &key_store)
+        .expect("Could not load MlsGroup")
+        .is_some());
+}
+
 // This tests if the remover is correctly passed to the callback when one member
 // issues a RemoveProposal and another members issues the next Commit.
 #[apply(ciphersuites_and_providers)]
@@ -86,7 +295,12 @@ fn remover(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // === Alice adds Bob ===
     let (_queued_message, welcome, _group_info) = alice_group
-        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_kpb.key_package().clone()],
+            &AcceptAllCredentials,
+        )
         .expect("Could not add member to group.");
 
     alice_group
@@ -98,12 +312,18 @@ fn remover(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         &mls_group_config,
         welcome.into_welcome().expect("Unexpected message type."),
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("Error creating group from Welcome");
 
     // === Bob adds Charlie ===
     let (queued_messages, welcome, _group_info) = bob_group
-        .add_members(provider, &bob_signer, &[charlie_kpb.key_package().clone()])
+        .add_members(
+            provider,
+            &bob_signer,
+            &[charlie_kpb.key_package().clone()],
+            &AcceptAllCredentials,
+        )
         .unwrap();
 
     let alice_processed_message = alice_group
@@ -112,6 +332,7 @@ fn remover(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             queued_messages
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process messages.");
     if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
@@ -133,6 +354,7 @@ fn remover(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         &mls_group_config,
         welcome.into_welcome().expect("Unexpected message type."),
         Some(bob_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("Error creating group from Welcome");
 
@@ -148,6 +370,7 @@ fn remover(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             queued_messages
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process messages.");
 
@@ -367,7 +590,11 @@ fn test_pending_commit_logic(ciphersuite: Ciphersuite, provider: &impl OpenMlsPr
         .expect("error creating self-update proposal");
 
     let alice_processed_message = alice_group
-        .process_message(provider, proposal.into_protocol_message().unwrap())
+        .process_message(
+            provider,
+            proposal.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
         .expect("Could not process messages.");
     assert!(alice_group.pending_commit().is_none());
 
@@ -394,7 +621,12 @@ fn test_pending_commit_logic(ciphersuite: Ciphersuite, provider: &impl OpenMlsPr
     // If there is a pending commit, other commit- or proposal-creating actions
     // should fail.
     let error = alice_group
-        .add_members(provider, &alice_signer, &[bob_key_package.clone()])
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package.clone()],
+            &AcceptAllCredentials,
+        )
         .expect_err("no error committing while a commit is pending");
     assert_eq!(
         error,
@@ -467,6 +699,7 @@ fn test_pending_commit_logic(ciphersuite: Ciphersuite, provider: &impl OpenMlsPr
             .into_welcome()
             .expect("Unexpected message type."),
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("error creating group from welcome");
 
@@ -489,7 +722,11 @@ fn test_pending_commit_logic(ciphersuite: Ciphersuite, provider: &impl OpenMlsPr
         .expect("error creating self-update commit");
 
     let alice_processed_message = alice_group
-        .process_message(provider, msg.into_protocol_message().unwrap())
+        .process_message(
+            provider,
+            msg.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
         .expect("Could not process messages.");
     assert!(alice_group.pending_commit().is_some());
 
@@ -534,7 +771,12 @@ fn key_package_deletion(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
 
     // === Alice adds Bob ===
     let (_queued_message, welcome, _group_info) = alice_group
-        .add_members(provider, &alice_signer, &[bob_key_package.clone()])
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package.clone()],
+            &AcceptAllCredentials,
+        )
         .unwrap();
 
     alice_group.merge_pending_commit(provider).unwrap();
@@ -545,6 +787,7 @@ fn key_package_deletion(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
         &mls_group_config,
         welcome.into_welcome().expect("Unexpected message type."),
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("Error creating group from Welcome");
 
@@ -570,59 +813,607 @@ fn key_package_deletion(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
     );
 }
 
+// Test that a last resort key package and its private keys are kept when
+// creating a new group for a welcome message, such that it can be used to join
+// more groups.
 #[apply(ciphersuites_and_providers)]
-fn remove_prosposal_by_ref(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
-    let group_id = GroupId::from_slice(b"Test Group");
+fn last_resort_key_package(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let bob_key_package = KeyPackage::builder()
+        .mark_as_last_resort()
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &bob_signer,
+            bob_credential_with_key,
+        )
+        .unwrap();
+    assert!(bob_key_package.last_resort());
+    assert!(bob_key_package
+        .leaf_node()
+        .capabilities()
+        .extensions()
+        .contains(&ExtensionType::LastResort));
+
+    // The last resort extension survives encoding and validation.
+    let bob_key_package = KeyPackageIn::tls_deserialize(
+        &mut bob_key_package.tls_serialize_detached().unwrap().as_slice(),
+    )
+    .unwrap()
+    .validate(provider.crypto(), provider.time(), ProtocolVersion::Mls10)
+    .unwrap();
+    assert!(bob_key_package.last_resort());
+
+    let mls_group_config = MlsGroupConfig::test_default(ciphersuite);
+
+    // === Alice adds Bob to two groups with the same key package ===
+    for _ in 0..2 {
+        let mut alice_group = MlsGroup::new(
+            provider,
+            &alice_signer,
+            &mls_group_config,
+            alice_credential_with_key.clone(),
+        )
+        .unwrap();
+        let (_commit, welcome, _group_info) = alice_group
+            .add_members(
+                provider,
+                &alice_signer,
+                &[bob_key_package.clone()],
+                &AcceptAllCredentials,
+            )
+            .unwrap();
+        alice_group.merge_pending_commit(provider).unwrap();
+
+        let bob_group = MlsGroup::new_from_welcome(
+            provider,
+            &mls_group_config,
+            welcome.into_welcome().unwrap(),
+            Some(alice_group.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
+        )
+        .unwrap();
+        assert_eq!(
+            alice_group.epoch_authenticator().as_slice(),
+            bob_group.epoch_authenticator().as_slice()
+        );
+
+        // The key package and its private keys are still in the key store.
+        let key_store = provider.key_store();
+        assert!(key_store
+            .read::<KeyPackage>(
+                bob_key_package
+                    .hash_ref(provider.crypto())
+                    .unwrap()
+                    .as_slice()
+            )
+            .is_some());
+        assert!(key_store
+            .read::<HpkePrivateKey>(bob_key_package.hpke_init_key().as_slice())
+            .is_some());
+    }
+}
 
+#[apply(ciphersuites_and_providers)]
+fn lifetime_policy(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
         setup_client("Alice", ciphersuite, provider);
-    let (_bob_credential_with_key, bob_kpb, _bob_signer, _bob_pk) =
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_pk) =
         setup_client("Bob", ciphersuite, provider);
-    let bob_key_package = bob_kpb.key_package().clone();
-    let (_charlie_credential_with_key, charlie_kpb, _charlie_signer, _charlie_pk) =
-        setup_client("Charlie", ciphersuite, provider);
-    let charlie_key_package = charlie_kpb.key_package();
 
-    // Define the MlsGroup configuration
-    let mls_group_config = MlsGroupConfigBuilder::new()
+    let rejecting_config = MlsGroupConfig::builder()
         .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .lifetime_policy(LifetimePolicy::new(
+            LifetimeEnforcement::Reject,
+            60 * 60 * 24 * 28 * 3 + 60 * 60,
+        ))
         .build();
+    assert_eq!(
+        rejecting_config.lifetime_policy().enforcement(),
+        LifetimeEnforcement::Reject
+    );
+    let mls_group_config = MlsGroupConfig::test_default(ciphersuite);
+    assert_eq!(
+        mls_group_config.lifetime_policy(),
+        &LifetimePolicy::default()
+    );
 
-    // === Alice creates a group ===
-    let mut alice_group = MlsGroup::new_with_group_id(
+    // === Bob's key package has a lifetime of a year ===
+    let bob_key_package = KeyPackage::builder()
+        .key_package_lifetime(Lifetime::new(60 * 60 * 24 * 365))
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &bob_signer,
+            bob_credential_with_key.clone(),
+        )
+        .unwrap();
+
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &rejecting_config,
+        alice_credential_with_key.clone(),
+    )
+    .unwrap();
+    let err = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package.clone()],
+            &AcceptAllCredentials,
+        )
+        .unwrap_err();
+    assert_eq!(err, AddMembersError::LifetimeRejected);
+
+    // The default policy only warns about the range.
+    let mut alice_group = MlsGroup::new(
         provider,
         &alice_signer,
         &mls_group_config,
-        group_id,
         alice_credential_with_key,
     )
-    .expect("An unexpected error occurred.");
+    .unwrap();
+    alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package],
+            &AcceptAllCredentials,
+        )
+        .unwrap();
+    alice_group.clear_pending_commit();
 
-    // alice adds bob and bob processes the welcome
-    let (_, welcome, _) = alice_group
-        .add_members(provider, &alice_signer, &[bob_key_package])
+    // === Bob joins a year later, when all lifetimes in the tree expired ===
+    let bob_key_package = KeyPackage::builder()
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &bob_signer,
+            bob_credential_with_key,
+        )
+        .unwrap();
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package],
+            &AcceptAllCredentials,
+        )
+        .unwrap();
+    alice_group.merge_pending_commit(provider).unwrap();
+    let welcome = welcome.into_welcome().unwrap();
+    let ratchet_tree = alice_group.export_ratchet_tree();
+
+    let later = FixedTimeProvider::shifted(provider, 60 * 60 * 24 * 365);
+    let err = MlsGroup::new_from_welcome(
+        &later,
+        &rejecting_config,
+        welcome.clone(),
+        Some(ratchet_tree.clone().into()),
+        &AcceptAllCredentials,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        WelcomeError::PublicGroupError(CreationFromExternalError::LifetimeRejected)
+    );
+
+    // The key package was kept and Bob can join with the default policy.
+    let bob_group = MlsGroup::new_from_welcome(
+        &later,
+        &mls_group_config,
+        welcome,
+        Some(ratchet_tree.into()),
+        &AcceptAllCredentials,
+    )
+    .unwrap();
+    assert_eq!(
+        alice_group.epoch_authenticator().as_slice(),
+        bob_group.epoch_authenticator().as_slice()
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn staged_welcome(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let group_id = GroupId::from_slice(b"Test Group");
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let bob_key_package = KeyPackage::builder()
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &bob_signer,
+            bob_credential_with_key,
+        )
+        .unwrap();
+    let bob_key_package_ref = bob_key_package.hash_ref(provider.crypto()).unwrap();
+
+    let mls_group_config = MlsGroupConfig::test_default(ciphersuite);
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        group_id.clone(),
+        alice_credential_with_key.clone(),
+    )
+    .unwrap();
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package.clone()],
+            &AcceptAllCredentials,
+        )
+        .unwrap();
+    alice_group.merge_pending_commit(provider).unwrap();
+    let welcome = welcome.into_welcome().unwrap();
+    let ratchet_tree = alice_group.export_ratchet_tree();
+
+    // === Bob inspects the group before joining it ===
+    let stage = || {
+        StagedWelcome::new_from_welcome(
+            provider,
+            &mls_group_config,
+            welcome.clone(),
+            Some(ratchet_tree.clone().into()),
+            &AcceptAllCredentials,
+        )
+    };
+    let staged_welcome = stage().unwrap();
+    assert_eq!(staged_welcome.group_context().group_id(), &group_id);
+    assert_eq!(staged_welcome.group_context().epoch(), alice_group.epoch());
+    assert_eq!(staged_welcome.welcome_sender_index(), LeafNodeIndex::new(0));
+    assert_eq!(
+        staged_welcome.welcome_sender().unwrap().credential(),
+        &alice_credential_with_key.credential
+    );
+    assert_eq!(staged_welcome.own_leaf_index(), LeafNodeIndex::new(1));
+    assert_eq!(staged_welcome.key_package(), &bob_key_package);
+    assert!(staged_welcome
+        .members()
+        .eq(alice_group.members().collect::<Vec<_>>()));
+
+    // Discarding the staged welcome keeps the key package.
+    drop(staged_welcome);
+    assert!(provider
+        .key_store()
+        .read::<KeyPackage>(bob_key_package_ref.as_slice())
+        .is_some());
+
+    // === Bob joins the group ===
+    let staged_welcome = stage().unwrap();
+    let other_staged_welcome = stage().unwrap();
+    let bob_group = staged_welcome.into_group(provider).unwrap();
+    assert_eq!(
+        alice_group.epoch_authenticator().as_slice(),
+        bob_group.epoch_authenticator().as_slice()
+    );
+    assert!(provider
+        .key_store()
+        .read::<KeyPackage>(bob_key_package_ref.as_slice())
+        .is_none());
+
+    // The key package was consumed.
+    assert_eq!(
+        other_staged_welcome.into_group(provider).unwrap_err(),
+        WelcomeError::NoMatchingKeyPackage
+    );
+    assert_eq!(stage().unwrap_err(), WelcomeError::NoMatchingKeyPackage);
+}
+
+#[apply(ciphersuites_and_providers)]
+fn key_store_garbage_collection(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential_with_key, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    let mls_group_config = MlsGroupConfig::test_default(ciphersuite);
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key.clone(),
+    )
+    .unwrap();
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_kpb.key_package().clone()],
+            &AcceptAllCredentials,
+        )
         .unwrap();
     alice_group.merge_pending_commit(provider).unwrap();
+
     let mut bob_group = MlsGroup::new_from_welcome(
         provider,
         &mls_group_config,
         welcome.into_welcome().unwrap(),
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .unwrap();
-    // alice proposes to add charlie
-    let (_, reference) = alice_group
-        .propose_add_member(provider, &alice_signer, charlie_key_package)
-        .unwrap();
 
-    assert_eq!(alice_group.proposal_store.proposals().count(), 1);
-    // clearing the proposal by reference
+    // === Alice creates a key package that expires immediately and proposes an update ===
+    let expired_key_package = KeyPackage::builder()
+        .key_package_lifetime(Lifetime::new(0))
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &alice_signer,
+            alice_credential_with_key,
+        )
+        .unwrap();
     alice_group
-        .remove_pending_proposal(reference.clone())
+        .propose_self_update(provider, &alice_signer, None)
         .unwrap();
-    assert!(alice_group.proposal_store.is_empty());
 
-    // the proposal should not be stored anymore
+    // The key store holds the key packages of Alice with their private keys,
+    // the encryption keys of the current epoch of both groups and the
+    // encryption key of Alice's update.
+    let key_store = provider.key_store();
+    assert_eq!(key_store.list::<KeyPackage>().unwrap().unwrap().len(), 2);
+    assert_eq!(
+        key_store
+            .list_keys(MlsEntityId::HpkePrivateKey)
+            .unwrap()
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(
+        key_store
+            .list::<EncryptionKeyPair>()
+            .unwrap()
+            .unwrap()
+            .len(),
+        3
+    );
+    assert_eq!(
+        key_store
+            .list::<Vec<EncryptionKeyPair>>()
+            .unwrap()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    // === Only the expired key package and its keys are removed ===
+    let removed_key_packages =
+        MlsGroup::collect_garbage(provider, [&alice_group, &bob_group]).unwrap();
+    assert_eq!(removed_key_packages, vec![expired_key_package.clone()]);
+    assert!(key_store
+        .read::<KeyPackage>(
+            expired_key_package
+                .hash_ref(provider.crypto())
+                .unwrap()
+                .as_slice()
+        )
+        .is_none());
+    assert!(key_store
+        .read::<HpkePrivateKey>(expired_key_package.hpke_init_key().as_slice())
+        .is_none());
+    assert_eq!(key_store.list::<KeyPackage>().unwrap().unwrap().len(), 1);
+    assert_eq!(
+        key_store
+            .list_keys(MlsEntityId::HpkePrivateKey)
+            .unwrap()
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(
+        key_store
+            .list::<EncryptionKeyPair>()
+            .unwrap()
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(
+        key_store
+            .list::<Vec<EncryptionKeyPair>>()
+            .unwrap()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    // === Both groups still have their keys ===
+    let (commit, _welcome, _group_info) = bob_group.self_update(provider, &bob_signer).unwrap();
+    let processed_message = alice_group
+        .process_message(
+            provider,
+            commit.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
+        .unwrap();
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => alice_group
+            .merge_staged_commit(provider, *staged_commit)
+            .unwrap(),
+        _ => panic!("Expected a staged commit."),
+    }
+    bob_group.merge_pending_commit(provider).unwrap();
+    assert_eq!(
+        alice_group
+            .export_secret(provider.crypto(), "test", &[], 32)
+            .unwrap(),
+        bob_group
+            .export_secret(provider.crypto(), "test", &[], 32)
+            .unwrap()
+    );
+
+    // === The keys of Bob's group and of Alice's dropped update are removed ===
+    assert!(MlsGroup::collect_garbage(provider, [&alice_group])
+        .unwrap()
+        .is_empty());
+    assert_eq!(key_store.list::<KeyPackage>().unwrap().unwrap().len(), 1);
+    assert_eq!(
+        key_store
+            .list::<EncryptionKeyPair>()
+            .unwrap()
+            .unwrap()
+            .len(),
+        1
+    );
+    let epoch_keys = key_store.list::<Vec<EncryptionKeyPair>>().unwrap().unwrap();
+    assert_eq!(epoch_keys.len(), 1);
+    assert_eq!(epoch_keys[0].0, alice_group.group.epoch_keypairs_key());
+}
+
+/// A key store that doesn't support listing values.
+struct UnlistedKeyStore<'a, KeyStore>(&'a KeyStore);
+
+impl<KeyStore: OpenMlsKeyStore> OpenMlsKeyStore for UnlistedKeyStore<'_, KeyStore> {
+    type Error = KeyStore::Error;
+
+    fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+        self.0.store(k, v)
+    }
+
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> Option<V> {
+        self.0.read(k)
+    }
+
+    fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+        self.0.delete::<V>(k)
+    }
+}
+
+/// A provider whose key store doesn't support listing values.
+struct UnlistedProvider<'a, Provider: OpenMlsProvider> {
+    provider: &'a Provider,
+    key_store: UnlistedKeyStore<'a, Provider::KeyStoreProvider>,
+}
+
+impl<'a, Provider: OpenMlsProvider> OpenMlsProvider for UnlistedProvider<'a, Provider> {
+    type CryptoProvider = Provider::CryptoProvider;
+    type RandProvider = Provider::RandProvider;
+    type KeyStoreProvider = UnlistedKeyStore<'a, Provider::KeyStoreProvider>;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        self.provider.crypto()
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        self.provider.rand()
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        &self.key_store
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn key_store_garbage_collection_without_listing(
+    ciphersuite: Ciphersuite,
+    provider: &impl OpenMlsProvider,
+) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let expired_key_package = KeyPackage::builder()
+        .key_package_lifetime(Lifetime::new(0))
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &alice_signer,
+            alice_credential_with_key,
+        )
+        .unwrap();
+
+    // Nothing is removed if the key store can't list the values.
+    let unlisted_provider = UnlistedProvider {
+        provider,
+        key_store: UnlistedKeyStore(provider.key_store()),
+    };
+    assert!(MlsGroup::collect_garbage(&unlisted_provider, [])
+        .unwrap()
+        .is_empty());
+    let key_package_ref = expired_key_package.hash_ref(provider.crypto()).unwrap();
+    assert!(provider
+        .key_store()
+        .read::<KeyPackage>(key_package_ref.as_slice())
+        .is_some());
+    assert!(provider
+        .key_store()
+        .read::<HpkePrivateKey>(expired_key_package.hpke_init_key().as_slice())
+        .is_some());
+}
+
+#[apply(ciphersuites_and_providers)]
+fn remove_prosposal_by_ref(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let group_id = GroupId::from_slice(b"Test Group");
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential_with_key, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let bob_key_package = bob_kpb.key_package().clone();
+    let (_charlie_credential_with_key, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let charlie_key_package = charlie_kpb.key_package();
+
+    // Define the MlsGroup configuration
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    // === Alice creates a group ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        group_id,
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    // alice adds bob and bob processes the welcome
+    let (_, welcome, _) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package],
+            &AcceptAllCredentials,
+        )
+        .unwrap();
+    alice_group.merge_pending_commit(provider).unwrap();
+    let mut bob_group = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.into_welcome().unwrap(),
+        Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .unwrap();
+    // alice proposes to add charlie
+    let (_, reference) = alice_group
+        .propose_add_member(provider, &alice_signer, charlie_key_package)
+        .unwrap();
+
+    assert_eq!(alice_group.proposal_store.proposals().count(), 1);
+    // clearing the proposal by reference
+    alice_group
+        .remove_pending_proposal(reference.clone())
+        .unwrap();
+    assert!(alice_group.proposal_store.is_empty());
+
+    // the proposal should not be stored anymore
     let err = alice_group.remove_pending_proposal(reference).unwrap_err();
     assert_eq!(err, MlsGroupStateError::PendingProposalNotFound);
 
@@ -631,7 +1422,7 @@ fn remove_prosposal_by_ref(ciphersuite: Ciphersuite, provider: &impl OpenMlsProv
         .commit_to_pending_proposals(provider, &alice_signer)
         .unwrap();
     let msg = bob_group
-        .process_message(provider, MlsMessageIn::from(commit))
+        .process_message(provider, MlsMessageIn::from(commit), &AcceptAllCredentials)
         .unwrap();
     match msg.into_content() {
         ProcessedMessageContent::StagedCommitMessage(commit) => {
@@ -645,3 +1436,1541 @@ fn remove_prosposal_by_ref(ciphersuite: Ciphersuite, provider: &impl OpenMlsProv
         _ => unreachable!("Expected a StagedCommit."),
     }
 }
+
+#[apply(ciphersuites_and_providers)]
+fn reinit<Provider: OpenMlsProvider + Default>(ciphersuite: Ciphersuite, provider: &Provider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    // Alice and Bob use separate key stores, so that their resumption PSKs are
+    // stored separately.
+    let bob_provider = &Provider::default();
+    let (bob_credential_with_key, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, bob_provider);
+
+    // Define the MlsGroup configuration
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key.clone(),
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_, welcome, _) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_kpb.key_package().clone()],
+            &AcceptAllCredentials,
+        )
+        .expect("Could not add member to group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let mut bob_group = MlsGroup::new_from_welcome(
+        bob_provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("Unexpected message type."),
+        Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .expect("Error creating group from Welcome");
+
+    // === Bob proposes to reinitialize the group ===
+    let new_group_id = GroupId::from_slice(b"Reinitialized Test Group");
+    let (proposal, _) = bob_group
+        .propose_reinit(
+            bob_provider,
+            &bob_signer,
+            ReInitProposal::new(
+                new_group_id.clone(),
+                ProtocolVersion::default(),
+                ciphersuite,
+                Extensions::empty(),
+            ),
+        )
+        .expect("Could not create ReInit proposal.");
+
+    let processed_message = alice_group
+        .process_message(
+            provider,
+            proposal
+                .into_protocol_message()
+                .expect("Unexpected message type."),
+            &AcceptAllCredentials,
+        )
+        .expect("Could not process message.");
+    match processed_message.into_content() {
+        ProcessedMessageContent::ProposalMessage(proposal) => {
+            alice_group.store_pending_proposal(*proposal)
+        }
+        _ => unreachable!("Expected a proposal."),
+    }
+
+    // === Alice commits to the ReInit proposal ===
+    let (commit, welcome, _) = alice_group
+        .commit_to_pending_proposals(provider, &alice_signer)
+        .expect("Could not commit to pending proposals.");
+    assert!(welcome.is_none());
+    assert!(alice_group
+        .pending_commit()
+        .expect("Expected a pending commit.")
+        .reinit_proposal()
+        .is_some());
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let processed_message = bob_group
+        .process_message(
+            bob_provider,
+            commit
+                .into_protocol_message()
+                .expect("Unexpected message type."),
+            &AcceptAllCredentials,
+        )
+        .expect("Could not process message.");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+            let reinit_proposal = staged_commit
+                .reinit_proposal()
+                .expect("Expected a ReInit proposal.");
+            assert_eq!(reinit_proposal.reinit_proposal().group_id(), &new_group_id);
+            bob_group
+                .merge_staged_commit(bob_provider, *staged_commit)
+                .expect("Error merging commit.");
+        }
+        _ => unreachable!("Expected a StagedCommit."),
+    }
+
+    // === Both groups are reinitialized and can't be used anymore ===
+    assert!(alice_group.reinit_proposal().is_some());
+    assert!(bob_group.reinit_proposal().is_some());
+    assert!(matches!(
+        alice_group.self_update(provider, &alice_signer),
+        Err(SelfUpdateError::GroupStateError(
+            MlsGroupStateError::UseAfterReInit
+        ))
+    ));
+
+    // === Alice creates the successor group and Bob joins it ===
+    let bob_kpb = KeyPackageBundle::new(
+        bob_provider,
+        &bob_signer,
+        ciphersuite,
+        bob_credential_with_key,
+    );
+    let (alice_successor, welcome, _) = alice_group
+        .reinitialize(
+            provider,
+            &alice_signer,
+            &mls_group_config,
+            alice_credential_with_key,
+            &[bob_kpb.key_package().clone()],
+        )
+        .expect("Could not reinitialize group.");
+    assert_eq!(alice_successor.group_id(), &new_group_id);
+
+    // The resumption PSK of the old group is deleted once it was used.
+    let psk_keystore_id = PreSharedKeyId::resumption(
+        ResumptionPskUsage::Reinit,
+        alice_group.group_id().clone(),
+        alice_group.epoch(),
+        vec![],
+    )
+    .keystore_id()
+    .unwrap();
+    assert!(provider
+        .key_store()
+        .read::<PskBundle>(&psk_keystore_id)
+        .is_none());
+
+    // === Bob doesn't join a successor group that doesn't match the ReInit proposal ===
+    let welcome = welcome.into_welcome().expect("Unexpected message type.");
+    let resumption_psk = bob_group.group.group_epoch_secrets().resumption_psk();
+    let other_reinit_proposal = ReInitProposal::new(
+        GroupId::from_slice(b"Other Test Group"),
+        ProtocolVersion::default(),
+        ciphersuite,
+        Extensions::empty(),
+    );
+    bob_provider
+        .key_store()
+        .store(
+            &psk_keystore_id,
+            &PskBundle::from_reinit(resumption_psk, other_reinit_proposal),
+        )
+        .unwrap();
+    let error = MlsGroup::new_from_welcome(
+        bob_provider,
+        &mls_group_config,
+        welcome.clone(),
+        Some(alice_successor.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .expect_err("Joined a successor group that doesn't match the ReInit proposal.");
+    assert_eq!(error, WelcomeError::ReInitParametersMismatch);
+
+    bob_provider
+        .key_store()
+        .store(
+            &psk_keystore_id,
+            &PskBundle::from_reinit(resumption_psk, bob_group.reinit_proposal().unwrap().clone()),
+        )
+        .unwrap();
+    let bob_successor = MlsGroup::new_from_welcome(
+        bob_provider,
+        &mls_group_config,
+        welcome,
+        Some(alice_successor.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .expect("Error creating group from Welcome");
+    assert!(bob_provider
+        .key_store()
+        .read::<PskBundle>(&psk_keystore_id)
+        .is_none());
+
+    assert_eq!(
+        alice_successor.export_secret(provider.crypto(), "test", &[], 32),
+        bob_successor.export_secret(provider.crypto(), "test", &[], 32)
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn branch(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential_with_key, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    // Define the MlsGroup configuration
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key.clone(),
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_, welcome, _) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_kpb.key_package().clone()],
+            &AcceptAllCredentials,
+        )
+        .expect("Could not add member to group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let bob_group = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("Unexpected message type."),
+        Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .expect("Error creating group from Welcome");
+
+    // === Only members of the group can be added to a branch ===
+    let branch_group_id = GroupId::from_slice(b"Branched Test Group");
+    let error = alice_group
+        .branch(
+            provider,
+            &alice_signer,
+            &mls_group_config,
+            branch_group_id.clone(),
+            alice_credential_with_key.clone(),
+            &[charlie_kpb.key_package().clone()],
+        )
+        .expect_err("Branched off a group with a non-member.");
+    assert_eq!(error, BranchError::UnknownMember);
+
+    // === Alice branches off a new group with Bob ===
+    let bob_kpb =
+        KeyPackageBundle::new(provider, &bob_signer, ciphersuite, bob_credential_with_key);
+    let (alice_branch, welcome, _) = alice_group
+        .branch(
+            provider,
+            &alice_signer,
+            &mls_group_config,
+            branch_group_id.clone(),
+            alice_credential_with_key,
+            &[bob_kpb.key_package().clone()],
+        )
+        .expect("Could not branch off group.");
+    assert_eq!(alice_branch.group_id(), &branch_group_id);
+    assert_eq!(alice_branch.members().count(), 2);
+
+    // The parent group is unaffected by the branch.
+    assert!(alice_group.is_active());
+    assert_eq!(alice_group.epoch(), bob_group.epoch());
+
+    let bob_branch = bob_group
+        .join_branch(
+            provider,
+            &mls_group_config,
+            welcome.into_welcome().expect("Unexpected message type."),
+            Some(alice_branch.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
+        )
+        .expect("Error joining branch.");
+
+    assert_eq!(
+        alice_branch.export_secret(provider.crypto(), "test", &[], 32),
+        bob_branch.export_secret(provider.crypto(), "test", &[], 32)
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn join_branch_mismatch(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (charlie_credential_with_key, charlie_kpb, charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    // Define the MlsGroup configuration. The groups keep the resumption PSKs of
+    // the current and the previous epoch, so that a branch can be joined after
+    // the parent group moved on.
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .number_of_resumption_psks(2)
+        .build();
+
+    // === Alice creates a group with Bob and Charlie ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key.clone(),
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_, welcome, _) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+            &AcceptAllCredentials,
+        )
+        .expect("Could not add members to group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let mut bob_group = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("Unexpected message type."),
+        Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .expect("Error creating group from Welcome");
+
+    // === Alice creates another group with Bob ===
+    let bob_kpb = KeyPackageBundle::new(
+        provider,
+        &bob_signer,
+        ciphersuite,
+        bob_credential_with_key.clone(),
+    );
+    let mut alice_other_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Other Test Group"),
+        alice_credential_with_key.clone(),
+    )
+    .expect("An unexpected error occurred.");
+    let (_, other_welcome, _) = alice_other_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_kpb.key_package().clone()],
+            &AcceptAllCredentials,
+        )
+        .expect("Could not add member to group.");
+    alice_other_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    // A Welcome without a branch PSK can't be joined as a branch.
+    let error = bob_group
+        .join_branch(
+            provider,
+            &mls_group_config,
+            other_welcome
+                .clone()
+                .into_welcome()
+                .expect("Unexpected message type."),
+            Some(alice_other_group.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
+        )
+        .expect_err("Joined a group without a branch PSK as a branch.");
+    assert_eq!(error, WelcomeError::MissingBranchPsk);
+
+    let bob_other_group = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        other_welcome
+            .into_welcome()
+            .expect("Unexpected message type."),
+        Some(alice_other_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .expect("Error creating group from Welcome");
+
+    // === Alice branches off a new group with Bob and Charlie ===
+    let bob_kpb =
+        KeyPackageBundle::new(provider, &bob_signer, ciphersuite, bob_credential_with_key);
+    let charlie_kpb = KeyPackageBundle::new(
+        provider,
+        &charlie_signer,
+        ciphersuite,
+        charlie_credential_with_key,
+    );
+    let (alice_branch, welcome, _) = alice_group
+        .branch(
+            provider,
+            &alice_signer,
+            &mls_group_config,
+            GroupId::from_slice(b"Branched Test Group"),
+            alice_credential_with_key,
+            &[
+                bob_kpb.key_package().clone(),
+                charlie_kpb.key_package().clone(),
+            ],
+        )
+        .expect("Could not branch off group.");
+    let welcome = welcome.into_welcome().expect("Unexpected message type.");
+
+    // The branch PSK doesn't refer to the group the branch is joined from.
+    let error = bob_other_group
+        .join_branch(
+            provider,
+            &mls_group_config,
+            welcome.clone(),
+            Some(alice_branch.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
+        )
+        .expect_err("Joined a branch of another group.");
+    assert_eq!(error, WelcomeError::BranchPskMismatch);
+
+    // === Alice removes Charlie from the parent group ===
+    let (commit, _, _) = alice_group
+        .remove_members(provider, &alice_signer, &[LeafNodeIndex::new(2)])
+        .expect("Could not remove member from group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let processed_message = bob_group
+        .process_message(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("Unexpected message type."),
+            &AcceptAllCredentials,
+        )
+        .expect("Could not process message.");
+    match processed_message.into_content() {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("Error merging commit."),
+        _ => unreachable!("Expected a StagedCommit."),
+    }
+
+    // The branch PSK is still available, but Charlie is no longer a member of
+    // the parent group.
+    let error = bob_group
+        .join_branch(
+            provider,
+            &mls_group_config,
+            welcome,
+            Some(alice_branch.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
+        )
+        .expect_err("Joined a branch with members that are not in the parent group.");
+    assert_eq!(error, WelcomeError::BranchMembersMismatch);
+}
+
+#[apply(ciphersuites_and_providers)]
+fn credential_validation(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential_with_key, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (_charlie_credential_with_key, charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+
+    // The Authentication Service doesn't know Charlie.
+    let credential_validator = |credential: &Credential, _signature_key: &SignaturePublicKey| {
+        credential.identity() != b"Charlie"
+    };
+
+    // Define the MlsGroup configuration
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    // Alice can't add Charlie.
+    let error = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[charlie_kpb.key_package().clone()],
+            &credential_validator,
+        )
+        .expect_err("Added a member with a rejected credential.");
+    assert_eq!(error, AddMembersError::CredentialRejected);
+
+    let (_, welcome, _) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_kpb.key_package().clone()],
+            &credential_validator,
+        )
+        .expect("Could not add member to group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let mut bob_group = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("Unexpected message type."),
+        Some(alice_group.export_ratchet_tree().into()),
+        &credential_validator,
+    )
+    .expect("Error creating group from Welcome");
+
+    // === Bob rejects proposals and commits that add Charlie ===
+    let (proposal, _) = alice_group
+        .propose_add_member(provider, &alice_signer, charlie_kpb.key_package())
+        .expect("Could not create proposal.");
+    let error = bob_group
+        .process_message(
+            provider,
+            proposal.into_protocol_message().unwrap(),
+            &credential_validator,
+        )
+        .expect_err("Processed a proposal with a rejected credential.");
+    assert_eq!(error, ProcessMessageError::CredentialRejected);
+
+    alice_group.clear_pending_proposals();
+    let (commit, welcome, _) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[charlie_kpb.key_package().clone()],
+            &AcceptAllCredentials,
+        )
+        .expect("Could not add member to group.");
+    let error = bob_group
+        .process_message(
+            provider,
+            commit.into_protocol_message().unwrap(),
+            &credential_validator,
+        )
+        .expect_err("Processed a commit with a rejected credential.");
+    assert_eq!(error, ProcessMessageError::CredentialRejected);
+
+    // === Charlie doesn't accept Alice ===
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+    let welcome = welcome.into_welcome().expect("Unexpected message type.");
+    let error = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.clone(),
+        Some(alice_group.export_ratchet_tree().into()),
+        &|credential: &Credential, _signature_key: &SignaturePublicKey| {
+            credential.identity() != b"Alice"
+        },
+    )
+    .expect_err("Joined a group with a rejected credential.");
+    assert_eq!(error, WelcomeError::CredentialRejected);
+
+    // The key package of Charlie is kept in the key store, so Charlie can
+    // still join the group.
+    MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .expect("Error creating group from Welcome");
+
+    // === Dave doesn't accept Alice and doesn't sign an external commit ===
+    let (dave_credential_with_key, _dave_kpb, _dave_signer, _dave_pk) =
+        setup_client("Dave", ciphersuite, provider);
+    let verifiable_group_info = alice_group
+        .export_group_info(provider.crypto(), &alice_signer, true)
+        .expect("Error exporting group info")
+        .into_verifiable_group_info()
+        .expect("Unexpected message type.");
+    let error = MlsGroup::join_by_external_commit(
+        provider,
+        &UnusedSigner(ciphersuite.signature_algorithm()),
+        None,
+        verifiable_group_info,
+        &mls_group_config,
+        &[],
+        dave_credential_with_key,
+        &|credential: &Credential, _signature_key: &SignaturePublicKey| {
+            credential.identity() != b"Alice"
+        },
+    )
+    .expect_err("Joined a group with a rejected credential.");
+    assert_eq!(error, ExternalCommitError::CredentialRejected);
+}
+
+/// A signer that fails the test if anything is signed with it.
+struct UnusedSigner(openmls_traits::types::SignatureScheme);
+
+impl openmls_traits::signatures::Signer for UnusedSigner {
+    fn sign(&self, _payload: &[u8]) -> Result<Vec<u8>, openmls_traits::types::Error> {
+        panic!("Signed with an unused signer.")
+    }
+
+    fn signature_scheme(&self) -> openmls_traits::types::SignatureScheme {
+        self.0
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn external_sender_credential_validation(
+    ciphersuite: Ciphersuite,
+    provider: &impl OpenMlsProvider,
+) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential_with_key, bob_kpb, _bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (charlie_credential_with_key, _charlie_kpb, _charlie_signer, _charlie_pk) =
+        setup_client("Charlie", ciphersuite, provider);
+    let (_dave_credential_with_key, dave_kpb, _dave_signer, _dave_pk) =
+        setup_client("Dave", ciphersuite, provider);
+
+    // The Authentication Service doesn't know Charlie.
+    let credential_validator = |credential: &Credential, _signature_key: &SignaturePublicKey| {
+        credential.identity() != b"Charlie"
+    };
+
+    // Define the MlsGroup configuration
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_, welcome, _) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_kpb.key_package().clone()],
+            &credential_validator,
+        )
+        .expect("Could not add member to group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let mut bob_group = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("Unexpected message type."),
+        Some(alice_group.export_ratchet_tree().into()),
+        &credential_validator,
+    )
+    .expect("Error creating group from Welcome");
+
+    // === Bob rejects proposals and commits that make Charlie an external sender ===
+    let extensions = Extensions::single(Extension::ExternalSenders(vec![ExternalSender::new(
+        charlie_credential_with_key.signature_key,
+        charlie_credential_with_key.credential,
+    )]));
+    let (proposal, _) = alice_group
+        .propose_group_context_extensions(provider, &alice_signer, extensions.clone())
+        .expect("Could not create proposal.");
+    let error = bob_group
+        .process_message(
+            provider,
+            proposal.into_protocol_message().unwrap(),
+            &credential_validator,
+        )
+        .expect_err("Processed a proposal with a rejected external sender.");
+    assert_eq!(error, ProcessMessageError::CredentialRejected);
+
+    alice_group.clear_pending_proposals();
+    let (commit, _, _) = alice_group
+        .update_group_context_extensions(provider, &alice_signer, extensions)
+        .expect("Could not update group context extensions.");
+    let error = bob_group
+        .process_message(
+            provider,
+            commit.into_protocol_message().unwrap(),
+            &credential_validator,
+        )
+        .expect_err("Processed a commit with a rejected external sender.");
+    assert_eq!(error, ProcessMessageError::CredentialRejected);
+
+    // === Dave doesn't join a group with Charlie as external sender ===
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+    let (_, welcome, _) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[dave_kpb.key_package().clone()],
+            &credential_validator,
+        )
+        .expect("Could not add member to group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let error = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("Unexpected message type."),
+        Some(alice_group.export_ratchet_tree().into()),
+        &credential_validator,
+    )
+    .expect_err("Joined a group with a rejected external sender.");
+    assert_eq!(error, WelcomeError::CredentialRejected);
+}
+
+#[apply(ciphersuites_and_providers)]
+fn group_context_extensions(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential_with_key, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let (ds_credential_with_key, _ds_kpb, _ds_signer, _ds_pk) =
+        setup_client("Delivery Service", ciphersuite, provider);
+
+    // Define the MlsGroup configuration
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_, welcome, _) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_kpb.key_package().clone()],
+            &AcceptAllCredentials,
+        )
+        .expect("Could not add member to group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let mut bob_group = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("Unexpected message type."),
+        Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .expect("Error creating group from Welcome");
+
+    // === Alice adds the Delivery Service as external sender ===
+    let external_senders = Extension::ExternalSenders(vec![ExternalSender::new(
+        ds_credential_with_key.signature_key,
+        ds_credential_with_key.credential,
+    )]);
+    let extensions = Extensions::single(external_senders.clone());
+    let (commit, _, _) = alice_group
+        .update_group_context_extensions(provider, &alice_signer, extensions.clone())
+        .expect("Could not update group context extensions.");
+
+    let bob_processed_message = bob_group
+        .process_message(
+            provider,
+            commit.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
+        .expect("Could not process messages.");
+    if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        bob_processed_message.into_content()
+    {
+        assert_eq!(staged_commit.group_context_extensions(), Some(&extensions));
+        bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("Error merging commit.");
+    } else {
+        unreachable!("Expected a StagedCommit.");
+    }
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    assert_eq!(alice_group.group().group_context_extensions(), &extensions);
+    assert_eq!(bob_group.group().group_context_extensions(), &extensions);
+
+    // === Bob proposes to require the external senders extension ===
+    let required_capabilities = Extension::RequiredCapabilities(
+        RequiredCapabilitiesExtension::new(&[ExtensionType::ExternalSenders], &[], &[]),
+    );
+    let extensions = Extensions::from_vec(vec![external_senders, required_capabilities])
+        .expect("Invalid extensions.");
+    let (proposal, _) = bob_group
+        .propose_group_context_extensions(provider, &bob_signer, extensions.clone())
+        .expect("Could not create proposal.");
+
+    let alice_processed_message = alice_group
+        .process_message(
+            provider,
+            proposal.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
+        .expect("Could not process messages.");
+    if let ProcessedMessageContent::ProposalMessage(staged_proposal) =
+        alice_processed_message.into_content()
+    {
+        alice_group.store_pending_proposal(*staged_proposal);
+    } else {
+        unreachable!("Expected a QueuedProposal.");
+    }
+
+    let (commit, _, _) = alice_group
+        .commit_to_pending_proposals(provider, &alice_signer)
+        .expect("Could not commit to pending proposals.");
+
+    let bob_processed_message = bob_group
+        .process_message(
+            provider,
+            commit.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
+        .expect("Could not process messages.");
+    if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        bob_processed_message.into_content()
+    {
+        assert_eq!(staged_commit.group_context_extensions(), Some(&extensions));
+        bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("Error merging commit.");
+    } else {
+        unreachable!("Expected a StagedCommit.");
+    }
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    assert_eq!(alice_group.group().group_context_extensions(), &extensions);
+    assert_eq!(bob_group.group().group_context_extensions(), &extensions);
+
+    // === Extensions that not all members support are rejected ===
+    let unsupported_extensions =
+        Extensions::single(Extension::Unknown(0xff00, UnknownExtension(vec![1, 2, 3])));
+
+    let error = bob_group
+        .propose_group_context_extensions(provider, &bob_signer, unsupported_extensions.clone())
+        .expect_err("Proposed unsupported group context extensions.");
+    assert_eq!(
+        error,
+        ProposalError::CreateGroupContextExtProposalError(
+            CreateGroupContextExtProposalError::LeafNodeValidation(
+                LeafNodeValidationError::UnsupportedExtensions
+            )
+        )
+    );
+
+    let error = alice_group
+        .update_group_context_extensions(provider, &alice_signer, unsupported_extensions)
+        .expect_err("Committed unsupported group context extensions.");
+    assert_eq!(
+        error,
+        UpdateGroupContextExtensionsError::CreateCommitError(
+            CreateCommitError::ProposalValidationError(
+                ProposalValidationError::UnsupportedGroupContextExtensions
+            )
+        )
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn app_ack(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential_with_key, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    // Define the MlsGroup configuration
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let (_, welcome, _) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_kpb.key_package().clone()],
+            &AcceptAllCredentials,
+        )
+        .expect("Could not add member to group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let mut bob_group = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("Unexpected message type."),
+        Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .expect("Error creating group from Welcome");
+
+    // === Alice sends four messages, the second one is replaced with a forgery by the DS ===
+    let messages: Vec<MlsMessageOut> = (0..4u8)
+        .map(|i| {
+            alice_group
+                .create_message(provider, &alice_signer, &[i])
+                .expect("An unexpected error occurred.")
+        })
+        .collect();
+    for (i, message) in messages.into_iter().enumerate() {
+        if i == 1 {
+            // A message that can't be decrypted doesn't count as received.
+            let mut forged_message = match message.into_protocol_message().unwrap() {
+                ProtocolMessage::PrivateMessage(private_message) => private_message,
+                _ => unreachable!("Expected a PrivateMessage."),
+            };
+            forged_message.set_ciphertext(vec![1, 2, 3]);
+            bob_group
+                .process_message(provider, forged_message, &AcceptAllCredentials)
+                .expect_err("Processed a forged message.");
+            continue;
+        }
+        let processed_message = bob_group
+            .process_message(
+                provider,
+                message.into_protocol_message().unwrap(),
+                &AcceptAllCredentials,
+            )
+            .expect("Could not process messages.");
+        assert!(matches!(
+            processed_message.into_content(),
+            ProcessedMessageContent::ApplicationMessage(_)
+        ));
+    }
+
+    // === Bob acknowledges the messages he received ===
+    let (proposal, _) = bob_group
+        .propose_app_ack(provider, &bob_signer)
+        .expect("Could not create proposal.");
+
+    let alice_processed_message = alice_group
+        .process_message(
+            provider,
+            proposal.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
+        .expect("Could not process messages.");
+    let queued_proposal = match alice_processed_message.into_content() {
+        ProcessedMessageContent::ProposalMessage(queued_proposal) => queued_proposal,
+        _ => unreachable!("Expected a QueuedProposal."),
+    };
+    let alice_index = alice_group.own_leaf_index();
+    match queued_proposal.proposal() {
+        Proposal::AppAck(app_ack_proposal) => {
+            assert_eq!(
+                app_ack_proposal.received_ranges(),
+                &[
+                    MessageRange::new(alice_index, 0, 0),
+                    MessageRange::new(alice_index, 2, 3)
+                ]
+            );
+            assert!(app_ack_proposal.acknowledges(alice_index, 0));
+            assert!(!app_ack_proposal.acknowledges(alice_index, 1));
+            assert!(app_ack_proposal.acknowledges(alice_index, 3));
+        }
+        _ => unreachable!("Expected an AppAck proposal."),
+    }
+    alice_group.store_pending_proposal(*queued_proposal);
+
+    // === Alice commits to the AppAck proposal ===
+    let (commit, _, _) = alice_group
+        .commit_to_pending_proposals(provider, &alice_signer)
+        .expect("Could not commit to pending proposals.");
+
+    let bob_processed_message = bob_group
+        .process_message(
+            provider,
+            commit.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
+        .expect("Could not process messages.");
+    if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        bob_processed_message.into_content()
+    {
+        let app_ack_proposals: Vec<_> = staged_commit.app_ack_proposals().collect();
+        assert_eq!(app_ack_proposals.len(), 1);
+        assert_eq!(
+            app_ack_proposals[0].sender(),
+            &Sender::Member(bob_group.own_leaf_index())
+        );
+        assert!(!app_ack_proposals[0]
+            .app_ack_proposal()
+            .acknowledges(alice_index, 1));
+        bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("Error merging commit.");
+    } else {
+        unreachable!("Expected a StagedCommit.");
+    }
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    // === The new epoch starts without acknowledged messages ===
+    let (proposal, _) = bob_group
+        .propose_app_ack(provider, &bob_signer)
+        .expect("Could not create proposal.");
+    let alice_processed_message = alice_group
+        .process_message(
+            provider,
+            proposal.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
+        .expect("Could not process messages.");
+    match alice_processed_message.into_content() {
+        ProcessedMessageContent::ProposalMessage(queued_proposal) => {
+            assert_eq!(
+                queued_proposal.proposal(),
+                &Proposal::AppAck(AppAckProposal::new(Vec::new()))
+            );
+        }
+        _ => unreachable!("Expected a QueuedProposal."),
+    }
+}
+
+#[apply(ciphersuites_and_providers)]
+fn custom_proposals(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+
+    // The application uses a custom proposal to agree on the group name.
+    let group_name_proposal_type = 0xff00;
+    let proposal_types = &[ProposalType::Unknown(group_name_proposal_type)];
+    let capabilities =
+        Capabilities::new(None, Some(&[ciphersuite]), None, Some(proposal_types), None);
+
+    // Define the MlsGroup configuration
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .required_capabilities(RequiredCapabilitiesExtension::new(&[], proposal_types, &[]))
+        .leaf_node_capabilities(capabilities.clone())
+        .build();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = MlsGroup::new_with_group_id(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        GroupId::from_slice(b"Test Group"),
+        alice_credential_with_key,
+    )
+    .expect("An unexpected error occurred.");
+
+    let bob_key_package = KeyPackage::builder()
+        .leaf_node_capabilities(capabilities)
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            provider,
+            &bob_signer,
+            bob_credential_with_key,
+        )
+        .expect("An unexpected error occurred.");
+    let (_, welcome, _) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package],
+            &AcceptAllCredentials,
+        )
+        .expect("Could not add member to group.");
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    let mut bob_group = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.into_welcome().expect("Unexpected message type."),
+        Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .expect("Error creating group from Welcome");
+
+    // === Bob proposes a group name by reference ===
+    let custom_proposal = CustomProposal::new(group_name_proposal_type, b"Bob's group".to_vec());
+    let (proposal, _) = bob_group
+        .propose_custom_proposal(provider, &bob_signer, custom_proposal.clone())
+        .expect("Could not create proposal.");
+
+    let alice_processed_message = alice_group
+        .process_message(
+            provider,
+            proposal.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
+        .expect("Could not process messages.");
+    if let ProcessedMessageContent::ProposalMessage(queued_proposal) =
+        alice_processed_message.into_content()
+    {
+        assert_eq!(
+            queued_proposal.proposal(),
+            &Proposal::Custom(custom_proposal.clone())
+        );
+        alice_group.store_pending_proposal(*queued_proposal);
+    } else {
+        unreachable!("Expected a QueuedProposal.");
+    }
+
+    let (commit, _, _) = alice_group
+        .commit_to_pending_proposals(provider, &alice_signer)
+        .expect("Could not commit to pending proposals.");
+
+    let bob_processed_message = bob_group
+        .process_message(
+            provider,
+            commit.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
+        .expect("Could not process messages.");
+    if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        bob_processed_message.into_content()
+    {
+        let custom_proposals: Vec<_> = staged_commit.custom_proposals().collect();
+        assert_eq!(custom_proposals.len(), 1);
+        assert_eq!(custom_proposals[0].custom_proposal(), &custom_proposal);
+        assert_eq!(
+            custom_proposals[0].sender(),
+            &Sender::Member(bob_group.own_leaf_index())
+        );
+        bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("Error merging commit.");
+    } else {
+        unreachable!("Expected a StagedCommit.");
+    }
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    // === Alice commits to a new group name by value ===
+    let custom_proposal = CustomProposal::new(group_name_proposal_type, b"Alice's group".to_vec());
+    alice_group
+        .propose_custom_proposal_by_value(provider, &alice_signer, custom_proposal.clone())
+        .expect("Could not create proposal.");
+    let (commit, _, _) = alice_group
+        .commit_to_pending_proposals(provider, &alice_signer)
+        .expect("Could not commit to pending proposals.");
+
+    let bob_processed_message = bob_group
+        .process_message(
+            provider,
+            commit.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
+        .expect("Could not process messages.");
+    if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        bob_processed_message.into_content()
+    {
+        let custom_proposals: Vec<_> = staged_commit.custom_proposals().collect();
+        assert_eq!(custom_proposals.len(), 1);
+        assert_eq!(custom_proposals[0].custom_proposal(), &custom_proposal);
+        bob_group
+            .merge_staged_commit(provider, *staged_commit)
+            .expect("Error merging commit.");
+    } else {
+        unreachable!("Expected a StagedCommit.");
+    }
+    alice_group
+        .merge_pending_commit(provider)
+        .expect("error merging pending commit");
+
+    // === Proposal types that are defined by MLS or not supported are rejected ===
+    let error = alice_group
+        .propose_custom_proposal(provider, &alice_signer, CustomProposal::new(1, vec![]))
+        .expect_err("Created a custom proposal with a reserved proposal type.");
+    assert_eq!(
+        error,
+        ProposalError::CreateCustomProposalError(CreateCustomProposalError::ReservedProposalType)
+    );
+
+    let error = alice_group
+        .propose_custom_proposal(provider, &alice_signer, CustomProposal::new(0xff01, vec![]))
+        .expect_err("Created a custom proposal that isn't supported by all members.");
+    assert_eq!(
+        error,
+        ProposalError::CreateCustomProposalError(CreateCustomProposalError::LeafNodeValidation(
+            LeafNodeValidationError::UnsupportedProposals
+        ))
+    );
+}
+
+/// An [`AsyncSigner`](openmls_traits::signatures::AsyncSigner) whose
+/// signatures are only ready when they are polled the second time.
+#[cfg(feature = "async")]
+struct YieldingSigner<'a, S>(&'a S);
+
+#[cfg(feature = "async")]
+impl<S: openmls_traits::signatures::Signer + Sync> openmls_traits::signatures::AsyncSigner
+    for YieldingSigner<'_, S>
+{
+    fn sign(
+        &self,
+        payload: &[u8],
+    ) -> impl std::future::Future<Output = Result<Vec<u8>, openmls_traits::types::Error>> + Send
+    {
+        let signature = openmls_traits::signatures::Signer::sign(self.0, payload);
+        let mut polled = false;
+        std::future::poll_fn(move |context| {
+            if polled {
+                std::task::Poll::Ready(signature.clone())
+            } else {
+                polled = true;
+                context.waker().wake_by_ref();
+                std::task::Poll::Pending
+            }
+        })
+    }
+
+    fn signature_scheme(&self) -> openmls_traits::types::SignatureScheme {
+        openmls_traits::signatures::Signer::signature_scheme(self.0)
+    }
+}
+
+/// Poll `future` until it is ready.
+#[cfg(feature = "async")]
+fn block_on<F: std::future::Future + Send>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+    loop {
+        if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[apply(ciphersuites)]
+fn async_operations(ciphersuite: Ciphersuite) {
+    let provider = &OpenMlsRustCrypto::default();
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, provider);
+    let (_bob_credential_with_key, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, provider);
+    let alice_signer = YieldingSigner(&alice_signer);
+    let bob_signer = YieldingSigner(&bob_signer);
+
+    let mls_group_config = MlsGroupConfigBuilder::new()
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .use_ratchet_tree_extension(true)
+        .build();
+
+    // === Alice creates a group and adds Bob ===
+    let mut alice_group = block_on(MlsGroup::new_async(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    ))
+    .expect("Error creating group.");
+
+    // The commit is signed in a later run, but Bob's credential is only
+    // validated once.
+    let validations = AtomicUsize::new(0);
+    let counting_validator = |_: &Credential, _: &SignaturePublicKey| {
+        validations.fetch_add(1, Ordering::SeqCst);
+        true
+    };
+    let (_commit, welcome, _group_info) = block_on(alice_group.add_members_async(
+        provider,
+        &alice_signer,
+        &[bob_kpb.key_package().clone()],
+        &counting_validator,
+    ))
+    .expect("Could not add member to group.");
+    assert_eq!(validations.load(Ordering::SeqCst), 1);
+    block_on(alice_group.merge_pending_commit_async(provider))
+        .expect("error merging pending commit");
+
+    let welcome = welcome.into_welcome().expect("Unexpected message type.");
+    let mut bob_group = block_on(MlsGroup::new_from_welcome_async(
+        provider,
+        &mls_group_config,
+        welcome.clone(),
+        None,
+        &AcceptAllCredentials,
+    ))
+    .expect("Error creating group from Welcome");
+
+    // The key package was deleted from the key store when Bob joined.
+    let error = MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome,
+        None,
+        &AcceptAllCredentials,
+    )
+    .expect_err("Joined a group with a deleted key package.");
+    assert_eq!(error, WelcomeError::NoMatchingKeyPackage);
+
+    // === Bob updates and Alice processes the commit ===
+    let (commit, _welcome, _group_info) =
+        block_on(bob_group.self_update_async(provider, &bob_signer))
+            .expect("Could not update own key package.");
+
+    let processed_message = block_on(
+        alice_group.process_message_async(
+            provider,
+            commit
+                .into_protocol_message()
+                .expect("Unexpected message type."),
+            &AcceptAllCredentials,
+        ),
+    )
+    .expect("Could not process message.");
+    let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+        processed_message.into_content()
+    else {
+        unreachable!("Expected a StagedCommit.");
+    };
+    block_on(alice_group.merge_staged_commit_async(provider, *staged_commit))
+        .expect("Error merging commit.");
+    block_on(bob_group.merge_pending_commit_async(provider)).expect("error merging pending commit");
+
+    assert_eq!(alice_group.epoch(), bob_group.epoch());
+    assert_eq!(
+        alice_group.export_secret(provider.crypto(), "test", &[], 32),
+        bob_group.export_secret(provider.crypto(), "test", &[], 32)
+    );
+
+    // === A failed operation leaves the group unchanged ===
+    let message = bob_group
+        .create_message(provider, bob_signer.0, b"Hello")
+        .expect("Could not create message.");
+    let ProtocolMessage::PrivateMessage(private_message) = message
+        .into_protocol_message()
+        .expect("Unexpected message type.")
+    else {
+        unreachable!("Expected a PrivateMessage.");
+    };
+    let mut forged_message = private_message.clone();
+    forged_message.set_ciphertext(vec![1, 2, 3]);
+    block_on(alice_group.process_message_async(provider, forged_message, &AcceptAllCredentials))
+        .expect_err("Processed a forged message.");
+    // The secret of the message wasn't consumed by the failed attempt.
+    block_on(alice_group.process_message_async(provider, private_message, &AcceptAllCredentials))
+        .expect("Could not process message.");
+
+    let error = block_on(alice_group.remove_members_async(
+        provider,
+        &alice_signer,
+        &[LeafNodeIndex::new(7)],
+    ))
+    .expect_err("Removed a member that doesn't exist.");
+    assert!(matches!(error, AsyncOperationError::OperationError(_)));
+    assert!(alice_group.pending_commit().is_none());
+
+    // === Alice removes Bob ===
+    block_on(alice_group.remove_members_async(
+        provider,
+        &alice_signer,
+        &[bob_group.own_leaf_index()],
+    ))
+    .expect("Could not remove member from group.");
+    block_on(alice_group.merge_pending_commit_async(provider))
+        .expect("error merging pending commit");
+    assert_eq!(alice_group.members().count(), 1);
+}
+
+#[apply(providers)]
+fn ciphersuites_with_256_bit_security(provider: &impl OpenMlsProvider) {
+    for ciphersuite in [
+        Ciphersuite::MLS_256_DHKEMX448_AES256GCM_SHA512_Ed448,
+        Ciphersuite::MLS_256_DHKEMP521_AES256GCM_SHA512_P521,
+        Ciphersuite::MLS_256_DHKEMX448_CHACHA20POLY1305_SHA512_Ed448,
+        Ciphersuite::MLS_256_DHKEMP384_AES256GCM_SHA384_P384,
+    ] {
+        assert!(provider.crypto().supports(ciphersuite).is_ok());
+
+        let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+            setup_client("Alice", ciphersuite, provider);
+        let (bob_credential_with_key, _bob_kpb, bob_signer, _bob_pk) =
+            setup_client("Bob", ciphersuite, provider);
+
+        let mls_group_config = MlsGroupConfig::test_default(ciphersuite);
+
+        // === Alice creates a group and adds Bob ===
+        let mut alice_group = MlsGroup::new_with_group_id(
+            provider,
+            &alice_signer,
+            &mls_group_config,
+            GroupId::from_slice(b"Test Group"),
+            alice_credential_with_key,
+        )
+        .expect("An unexpected error occurred.");
+
+        // The ciphersuite isn't a default ciphersuite and has to be listed in
+        // the capabilities of Bob.
+        let bob_key_package = KeyPackage::builder()
+            .leaf_node_capabilities(Capabilities::new(
+                None,
+                Some(&[ciphersuite]),
+                None,
+                None,
+                None,
+            ))
+            .build(
+                CryptoConfig::with_default_version(ciphersuite),
+                provider,
+                &bob_signer,
+                bob_credential_with_key,
+            )
+            .expect("An unexpected error occurred.");
+        let (_, welcome, _) = alice_group
+            .add_members(
+                provider,
+                &alice_signer,
+                &[bob_key_package],
+                &AcceptAllCredentials,
+            )
+            .expect("Could not add member to group.");
+        alice_group
+            .merge_pending_commit(provider)
+            .expect("error merging pending commit");
+
+        let mut bob_group = MlsGroup::new_from_welcome(
+            provider,
+            &mls_group_config,
+            welcome.into_welcome().expect("Unexpected message type."),
+            Some(alice_group.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
+        )
+        .expect("Error creating group from Welcome");
+
+        // === Bob updates his leaf and sends a message ===
+        let (commit, _, _) = bob_group
+            .self_update(provider, &bob_signer)
+            .expect("Could not update own leaf.");
+        bob_group
+            .merge_pending_commit(provider)
+            .expect("error merging pending commit");
+
+        let processed_message = alice_group
+            .process_message(
+                provider,
+                commit.into_protocol_message().unwrap(),
+                &AcceptAllCredentials,
+            )
+            .expect("Could not process messages.");
+        if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
+            processed_message.into_content()
+        {
+            alice_group
+                .merge_staged_commit(provider, *staged_commit)
+                .expect("Error merging commit.");
+        } else {
+            unreachable!("Expected a StagedCommit.");
+        }
+
+        let message = bob_group
+            .create_message(provider, &bob_signer, b"Hello Alice")
+            .expect("Could not create message.");
+        let processed_message = alice_group
+            .process_message(
+                provider,
+                message.into_protocol_message().unwrap(),
+                &AcceptAllCredentials,
+            )
+            .expect("Could not process messages.");
+        if let ProcessedMessageContent::ApplicationMessage(application_message) =
+            processed_message.into_content()
+        {
+            assert_eq!(application_message.into_bytes(), b"Hello Alice");
+        } else {
+            unreachable!("Expected an ApplicationMessage.");
+        }
+
+        assert_eq!(
+            alice_group.epoch_authenticator(),
+            bob_group.epoch_authenticator()
+        );
+    }
+}
@@ -50,7 +50,73 @@ impl MlsGroup {
         )));
 
         // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        self.flag_state_change(GroupStateComponent::Group);
+
+        Ok((
+            mls_message,
+            create_commit_result
+                .welcome_option
+                .map(|w| MlsMessageOut::from_welcome(w, self.group.version())),
+            create_commit_result.group_info,
+        ))
+    }
+
+    /// Updates the extensions of the group context.
+    ///
+    /// Creates a commit that covers a GroupContextExtensions proposal with the
+    /// given `extensions`, which replace the current group context extensions,
+    /// as well as all pending proposals. All members of the group have to
+    /// support the new extensions.
+    ///
+    /// If successful, it returns a tuple of [`MlsMessageOut`] (containing the
+    /// commit), an optional [`MlsMessageOut`] (containing the [`Welcome`]) and the [GroupInfo].
+    /// The [Welcome] is [Some] when the queue of pending proposals contained
+    /// add proposals
+    /// The [GroupInfo] is [Some] if the group has the `use_ratchet_tree_extension` flag set.
+    ///
+    /// Returns an error if there is a pending commit.
+    #[allow(clippy::type_complexity)]
+    pub fn update_group_context_extensions<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+        extensions: Extensions,
+    ) -> Result<
+        (MlsMessageOut, Option<MlsMessageOut>, Option<GroupInfo>),
+        UpdateGroupContextExtensionsError<KeyStore::Error>,
+    > {
+        self.is_operational()?;
+
+        // Ensure that OpenMLS supports the required capabilities. Whether the
+        // members support the new extensions is validated with the commit.
+        if let Some(required_capabilities) = extensions.required_capabilities() {
+            required_capabilities.check_support()?;
+        }
+
+        let inline_proposals = vec![Proposal::GroupContextExtensions(
+            GroupContextExtensionProposal::new(extensions),
+        )];
+
+        let params = CreateCommitParams::builder()
+            .framing_parameters(self.framing_parameters())
+            .proposal_store(&self.proposal_store)
+            .inline_proposals(inline_proposals)
+            .build();
+        // Create Commit over all proposals.
+        let create_commit_result = self.group.create_commit(params, provider, signer)?;
+
+        // Convert PublicMessage messages to MLSMessage and encrypt them if required by
+        // the configuration
+        let mls_message = self.content_to_mls_message(create_commit_result.commit, provider)?;
+
+        // Set the current group state to [`MlsGroupState::PendingCommit`],
+        // storing the current [`StagedCommit`] from the commit results
+        self.group_state = MlsGroupState::PendingCommit(Box::new(PendingCommitState::Member(
+            create_commit_result.staged_commit,
+        )));
+
+        // Since the state of the group might be changed, arm the state flag
+        self.flag_state_change(GroupStateComponent::Group);
 
         Ok((
             mls_message,
@@ -112,6 +178,7 @@ impl MlsGroup {
         )?;
 
         self.own_leaf_nodes.push(own_leaf);
+        self.flag_state_change(GroupStateComponent::OwnLeafNodes);
 
         Ok(update_proposal)
     }
@@ -135,7 +202,7 @@ impl MlsGroup {
         let mls_message = self.content_to_mls_message(update_proposal, provider)?;
 
         // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        self.flag_state_change(GroupStateComponent::ProposalStore);
 
         Ok((mls_message, proposal_ref))
     }
@@ -159,7 +226,7 @@ impl MlsGroup {
         let mls_message = self.content_to_mls_message(update_proposal, provider)?;
 
         // Since the state of the group might be changed, arm the state flag
-        self.flag_state_change();
+        self.flag_state_change(GroupStateComponent::ProposalStore);
 
         Ok((mls_message, proposal_ref))
     }
@@ -26,6 +26,7 @@ pub(crate) mod mls_group;
 pub mod config;
 pub(crate) mod errors;
 
+pub use crate::persistence::STATE_FORMAT_VERSION;
 pub use core_group::proposals::*;
 pub use core_group::staged_commit::StagedCommit;
 pub use errors::*;
@@ -33,6 +34,7 @@ pub use group_context::*;
 pub use mls_group::config::*;
 pub use mls_group::membership::*;
 pub use mls_group::processing::*;
+pub use mls_group::staged_welcome::StagedWelcome;
 pub use mls_group::*;
 pub use public_group::*;
 
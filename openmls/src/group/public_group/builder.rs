@@ -5,12 +5,12 @@ use crate::{
     credentials::CredentialWithKey,
     error::LibraryError,
     extensions::{
-        errors::ExtensionError, Extension, Extensions, ExternalSendersExtension,
+        errors::ExtensionError, Extension, ExtensionType, Extensions, ExternalSendersExtension,
         RequiredCapabilitiesExtension,
     },
     group::{config::CryptoConfig, GroupContext, GroupId},
     key_packages::Lifetime,
-    messages::ConfirmationTag,
+    messages::{proposals::ProposalType, ConfirmationTag},
     schedule::CommitSecret,
     treesync::{
         node::{encryption_keys::EncryptionKeyPair, leaf_node::Capabilities},
@@ -26,6 +26,7 @@ pub(crate) struct TempBuilderPG1 {
     required_capabilities: Option<RequiredCapabilitiesExtension>,
     external_senders: Option<ExternalSendersExtension>,
     leaf_extensions: Option<Extensions>,
+    capabilities: Option<Capabilities>,
 }
 
 impl TempBuilderPG1 {
@@ -52,31 +53,53 @@ impl TempBuilderPG1 {
         self
     }
 
+    pub(crate) fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
     pub(crate) fn get_secrets(
         self,
         provider: &impl OpenMlsProvider,
         signer: &impl Signer,
     ) -> Result<(TempBuilderPG2, CommitSecret, EncryptionKeyPair), PublicGroupBuildError> {
-        let capabilities = self
-            .required_capabilities
-            .as_ref()
-            .map(|re| re.extension_types());
-        let (treesync, commit_secret, leaf_keypair) = TreeSync::new(
-            provider,
-            signer,
-            self.crypto_config,
-            self.credential_with_key,
-            self.lifetime.unwrap_or_default(),
+        let capabilities = self.capabilities.unwrap_or_else(|| {
             Capabilities::new(
                 Some(&[self.crypto_config.version]), // TODO: Allow more versions
                 Some(&[self.crypto_config.ciphersuite]), // TODO: allow more ciphersuites
-                capabilities,
+                self.required_capabilities
+                    .as_ref()
+                    .map(|re| re.extension_types()),
                 None,
                 None,
-            ),
+            )
+        });
+        let required_capabilities = self.required_capabilities.unwrap_or_default();
+        // The own leaf node must support the required custom extension and
+        // proposal types. The other types are supported by OpenMLS, which is
+        // checked below.
+        if required_capabilities.extension_types().iter().any(|e| {
+            matches!(e, ExtensionType::Unknown(_)) && !capabilities.extensions().contains(e)
+        }) {
+            return Err(PublicGroupBuildError::UnsupportedExtensionType);
+        }
+        if required_capabilities
+            .proposal_types()
+            .iter()
+            .any(|p| matches!(p, ProposalType::Unknown(_)) && !capabilities.proposals().contains(p))
+        {
+            return Err(PublicGroupBuildError::UnsupportedProposalType);
+        }
+        let (treesync, commit_secret, leaf_keypair) = TreeSync::new(
+            provider,
+            signer,
+            self.crypto_config,
+            self.credential_with_key,
+            self.lifetime
+                .unwrap_or_else(|| Lifetime::default_with_time(provider.time())),
+            capabilities,
             self.leaf_extensions.unwrap_or(Extensions::empty()),
         )?;
-        let required_capabilities = self.required_capabilities.unwrap_or_default();
         required_capabilities.check_support().map_err(|e| match e {
             ExtensionError::UnsupportedProposalType => {
                 PublicGroupBuildError::UnsupportedProposalType
@@ -172,6 +195,7 @@ impl PublicGroup {
             required_capabilities: None,
             external_senders: None,
             leaf_extensions: None,
+            capabilities: None,
         }
     }
 }
@@ -16,6 +16,7 @@ use crate::{
     framing::{mls_auth_content::AuthenticatedContent, public_message::InterimTranscriptHashInput},
     group::GroupContext,
     messages::{proposals::AddProposal, ConfirmationTag, EncryptedGroupSecrets},
+    persistence::Persist,
     schedule::{psk::PreSharedKeyId, CommitSecret, JoinerSecret},
     treesync::{
         diff::{StagedTreeSyncDiff, TreeSyncDiff},
@@ -246,4 +247,27 @@ impl StagedPublicGroupDiff {
     pub(crate) fn group_context(&self) -> &GroupContext {
         &self.group_context
     }
+
+    /// Get the leaves that are added or changed by this diff.
+    pub(crate) fn changed_leaves(&self) -> impl Iterator<Item = (LeafNodeIndex, &LeafNode)> {
+        self.staged_diff.changed_leaves()
+    }
+}
+
+impl Persist for StagedPublicGroupDiff {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.staged_diff.persist(writer)?;
+        self.group_context.persist(writer)?;
+        self.interim_transcript_hash.persist(writer)?;
+        self.confirmation_tag.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            staged_diff: StagedTreeSyncDiff::restore(bytes)?,
+            group_context: GroupContext::restore(bytes)?,
+            interim_transcript_hash: Vec::restore(bytes)?,
+            confirmation_tag: ConfirmationTag::restore(bytes)?,
+        })
+    }
 }
@@ -140,6 +140,17 @@ impl<'a> PublicGroupDiff<'a> {
             })
             .collect();
 
+        // Process the GroupContextExtensions proposal. The proposals were
+        // validated, so there is at most one.
+        if let Some(Proposal::GroupContextExtensions(extensions_proposal)) = proposal_queue
+            .filtered_by_type(ProposalType::GroupContextExtensions)
+            .next()
+            .map(|queued_proposal| queued_proposal.proposal())
+        {
+            self.group_context
+                .set_extensions(extensions_proposal.extensions().clone());
+        }
+
         let proposals_require_path = proposal_queue
             .queued_proposals()
             .any(|p| p.proposal().is_path_required());
@@ -26,6 +26,9 @@ pub enum CreationFromExternalError {
     /// We don't support the version of the group we are trying to join.
     #[error("We don't support the version of the group we are trying to join.")]
     UnsupportedMlsVersion,
+    /// The lifetime of a leaf node in the tree was rejected by the lifetime policy.
+    #[error("The lifetime of a leaf node in the tree was rejected by the lifetime policy.")]
+    LifetimeRejected,
 }
 
 /// Public group builder error.
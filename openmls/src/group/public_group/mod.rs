@@ -16,14 +16,17 @@ use crate::prelude::OpenMlsProvider;
 #[cfg(test)]
 use std::collections::HashSet;
 
-use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+use openmls_traits::{crypto::OpenMlsCrypto, time::OpenMlsTime, types::Ciphersuite};
 use serde::{Deserialize, Serialize};
 
 use self::{
     diff::{PublicGroupDiff, StagedPublicGroupDiff},
     errors::CreationFromExternalError,
 };
-use super::{GroupContext, GroupId, Member, ProposalStore, QueuedProposal, StagedCommit};
+use super::{
+    errors::PersistenceError, GroupContext, GroupId, Member, ProposalStore, QueuedProposal,
+    StagedCommit,
+};
 #[cfg(test)]
 use crate::treesync::{node::parent_node::PlainUpdatePathNode, treekem::UpdatePathNode};
 use crate::{
@@ -32,11 +35,13 @@ use crate::{
     error::LibraryError,
     extensions::RequiredCapabilitiesExtension,
     framing::InterimTranscriptHashInput,
+    key_packages::LifetimePolicy,
     messages::{
         group_info::{GroupInfo, VerifiableGroupInfo},
         proposals::{Proposal, ProposalOrRefType, ProposalType},
         ConfirmationTag, PathSecret,
     },
+    persistence::{unchanged, Migration, Persist, PersistedState},
     schedule::CommitSecret,
     treesync::{
         errors::{DerivePathError, TreeSyncFromNodesError},
@@ -105,11 +110,16 @@ impl PublicGroup {
     /// This function performs basic validation checks and returns an error if
     /// one of the checks fails. See [`CreationFromExternalError`] for more
     /// details.
+    ///
+    /// The lifetimes of the leaf nodes in the tree are checked with the given
+    /// [`LifetimePolicy`] at the current time of `time`.
     pub fn from_external(
         crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
         ratchet_tree: RatchetTreeIn,
         verifiable_group_info: VerifiableGroupInfo,
         proposal_store: ProposalStore,
+        lifetime_policy: LifetimePolicy,
     ) -> Result<(Self, GroupInfo), CreationFromExternalError> {
         let ciphersuite = verifiable_group_info.ciphersuite();
 
@@ -127,6 +137,13 @@ impl PublicGroup {
         // signature against.
         let treesync = TreeSync::from_ratchet_tree(crypto, ciphersuite, ratchet_tree)?;
 
+        if !treesync
+            .full_leaves()
+            .all(|leaf_node| lifetime_policy.accepts(leaf_node, time))
+        {
+            return Err(CreationFromExternalError::LifetimeRejected);
+        }
+
         let group_info: GroupInfo = {
             let signer_signature_key = treesync
                 .leaf(verifiable_group_info.signer())
@@ -284,6 +301,22 @@ impl PublicGroup {
     pub fn add_proposal(&mut self, proposal: QueuedProposal) {
         self.proposal_store.add(proposal)
     }
+
+    /// Encode the state of this [`PublicGroup`] in the current
+    /// [`STATE_FORMAT_VERSION`](crate::group::STATE_FORMAT_VERSION).
+    pub fn to_state_bytes(&self) -> Result<Vec<u8>, LibraryError> {
+        self.to_persisted_state()
+    }
+
+    /// Load a [`PublicGroup`] from a state that was encoded with
+    /// [`PublicGroup::to_state_bytes()`] by this or a previous version of
+    /// OpenMLS.
+    ///
+    /// Returns an error if the state format version is not supported or the
+    /// state is malformed.
+    pub fn from_state_bytes(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        Self::from_persisted_state(bytes)
+    }
 }
 
 // Getters
@@ -338,13 +371,70 @@ impl PublicGroup {
         &self.interim_transcript_hash
     }
 
-    /// Return a vector containing all [`EncryptionKey`]s for which the owner of
-    /// the given `leaf_index` should have private key material.
-    pub(crate) fn owned_encryption_keys(&self, leaf_index: LeafNodeIndex) -> Vec<EncryptionKey> {
-        self.treesync().owned_encryption_keys(leaf_index)
+    /// Returns the [`EncryptionKey`]s for which the owner of the given
+    /// `leaf_index` should have private key material after merging the given
+    /// [`StagedPublicGroupDiff`].
+    pub(crate) fn staged_owned_encryption_keys(
+        &self,
+        staged_diff: &StagedPublicGroupDiff,
+        leaf_index: LeafNodeIndex,
+    ) -> Vec<EncryptionKey> {
+        self.treesync()
+            .staged_owned_encryption_keys(&staged_diff.staged_diff, leaf_index)
+    }
+}
+
+// Persistence
+impl PublicGroup {
+    /// Persist the tree of this group.
+    pub(crate) fn persist_tree(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.treesync.persist(writer)
+    }
+
+    /// Persist the state of this group without the tree, which can be
+    /// persisted separately with [`Self::persist_tree()`].
+    pub(crate) fn persist_without_tree(
+        &self,
+        writer: &mut Vec<u8>,
+    ) -> Result<(), tls_codec::Error> {
+        self.proposal_store.persist(writer)?;
+        self.group_context.persist(writer)?;
+        self.interim_transcript_hash.persist(writer)?;
+        self.confirmation_tag.persist(writer)
+    }
+
+    /// Restore a group that was persisted with [`Self::persist_without_tree()`]
+    /// with the given tree.
+    pub(crate) fn restore_with_tree(
+        bytes: &mut &[u8],
+        treesync: TreeSync,
+    ) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            treesync,
+            proposal_store: ProposalStore::restore(bytes)?,
+            group_context: GroupContext::restore(bytes)?,
+            interim_transcript_hash: Vec::restore(bytes)?,
+            confirmation_tag: ConfirmationTag::restore(bytes)?,
+        })
     }
 }
 
+impl Persist for PublicGroup {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.persist_tree(writer)?;
+        self.persist_without_tree(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        let treesync = TreeSync::restore(bytes)?;
+        Self::restore_with_tree(bytes, treesync)
+    }
+}
+
+impl PersistedState for PublicGroup {
+    const MIGRATIONS: &'static [Migration] = &[unchanged, unchanged, unchanged];
+}
+
 // Test functions
 #[cfg(any(feature = "test-utils", test))]
 impl PublicGroup {
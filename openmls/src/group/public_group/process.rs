@@ -1,4 +1,4 @@
-use openmls_traits::crypto::OpenMlsCrypto;
+use openmls_traits::{crypto::OpenMlsCrypto, time::OpenMlsTime};
 use tls_codec::Serialize;
 
 use crate::{
@@ -11,11 +11,10 @@ use crate::{
     },
     group::{
         core_group::proposals::{ProposalStore, QueuedProposal},
-        errors::ValidationError,
+        errors::{StageCommitError, ValidationError},
         mls_group::errors::ProcessMessageError,
         past_secrets::MessageSecretsStore,
     },
-    messages::proposals::Proposal,
 };
 
 use super::PublicGroup;
@@ -57,7 +56,6 @@ impl PublicGroup {
         //  - ValSem245
         //  - Prepares ValSem246 by setting the right credential. The remainder
         //    of ValSem246 is validated as part of ValSem010.
-        // External senders are not supported yet #106/#151.
         let CredentialWithKey {
             credential,
             signature_key,
@@ -130,9 +128,13 @@ impl PublicGroup {
     ///  - ValSem244
     ///  - ValSem245
     ///  - ValSem246 (as part of ValSem010)
+    ///
+    /// The lifetimes of key packages in the message are validated at the
+    /// current time of `time`.
     pub fn process_message(
         &self,
         crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
         message: impl Into<ProtocolMessage>,
     ) -> Result<ProcessedMessage, ProcessMessageError> {
         let protocol_message = message.into();
@@ -160,7 +162,7 @@ impl PublicGroup {
         let unverified_message = self
             .parse_message(decrypted_message, None)
             .map_err(ProcessMessageError::from)?;
-        self.process_unverified_message(crypto, unverified_message, &self.proposal_store)
+        self.process_unverified_message(crypto, time, unverified_message, &self.proposal_store)
     }
 }
 
@@ -195,6 +197,7 @@ impl PublicGroup {
     pub(crate) fn process_unverified_message(
         &self,
         crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
         unverified_message: UnverifiedMessage,
         proposal_store: &ProposalStore,
     ) -> Result<ProcessedMessage, ProcessMessageError> {
@@ -202,7 +205,7 @@ impl PublicGroup {
         //  - ValSem010
         //  - ValSem246 (as part of ValSem010)
         let (content, credential) =
-            unverified_message.verify(self.ciphersuite(), crypto, self.version())?;
+            unverified_message.verify(self.ciphersuite(), crypto, time, self.version())?;
 
         match content.sender() {
             Sender::Member(_) | Sender::NewMemberCommit | Sender::NewMemberProposal => {
@@ -249,14 +252,17 @@ impl PublicGroup {
                     FramedContentBody::Application(_) => {
                         Err(ProcessMessageError::UnauthorizedExternalApplicationMessage)
                     }
-                    FramedContentBody::Proposal(Proposal::Remove(_)) => {
-                        let content = ProcessedMessageContent::ProposalMessage(Box::new(
-                            QueuedProposal::from_authenticated_content_by_ref(
-                                self.ciphersuite(),
-                                crypto,
-                                content,
-                            )?,
-                        ));
+                    FramedContentBody::Proposal(_) => {
+                        let proposal = QueuedProposal::from_authenticated_content_by_ref(
+                            self.ciphersuite(),
+                            crypto,
+                            content,
+                        )?;
+                        // External senders can only send Add, Remove,
+                        // PreSharedKey, ReInit and GroupContextExtensions
+                        // proposals.
+                        self.validate_external_proposal(&proposal)?;
+                        let content = ProcessedMessageContent::ProposalMessage(Box::new(proposal));
                         Ok(ProcessedMessage::new(
                             self.group_id().clone(),
                             self.group_context().epoch(),
@@ -266,11 +272,10 @@ impl PublicGroup {
                             credential,
                         ))
                     }
-                    // TODO #151/#106
-                    FramedContentBody::Proposal(_) => {
-                        Err(ProcessMessageError::UnsupportedProposalType)
-                    }
-                    FramedContentBody::Commit(_) => unimplemented!(),
+                    // External senders can't commit.
+                    FramedContentBody::Commit(_) => Err(ProcessMessageError::InvalidCommit(
+                        StageCommitError::SenderTypeExternal,
+                    )),
                 }
             }
         }
@@ -98,6 +98,9 @@ impl PublicGroup {
                 // ValSem111
                 // ValSem112
                 self.validate_update_proposals(&proposal_queue, *leaf_index)?;
+                self.validate_reinit_proposals(&proposal_queue)?;
+                self.validate_group_context_extensions_proposals(&proposal_queue)?;
+                self.validate_custom_proposals(&proposal_queue)?;
             }
             Sender::External(_) => {
                 // A commit cannot be issued by a pre-configured sender.
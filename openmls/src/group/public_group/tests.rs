@@ -5,6 +5,7 @@ use rstest_reuse::{self, *};
 
 use crate::{
     binary_tree::LeafNodeIndex,
+    credentials::AcceptAllCredentials,
     framing::{
         public_message_in::PublicMessageIn, MlsMessageOut, ProcessedMessage,
         ProcessedMessageContent, ProtocolMessage, Sender,
@@ -13,6 +14,7 @@ use crate::{
         config::CryptoConfig, test_core_group::setup_client, GroupId, MlsGroup,
         MlsGroupConfigBuilder, ProposalStore, StagedCommit, PURE_PLAINTEXT_WIRE_FORMAT_POLICY,
     },
+    key_packages::LifetimePolicy,
     messages::proposals::Proposal,
 };
 
@@ -55,15 +57,22 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let ratchet_tree = alice_group.export_ratchet_tree();
     let (mut public_group, _extensions) = PublicGroup::from_external(
         provider.crypto(),
+        provider.time(),
         ratchet_tree.into(),
         verifiable_group_info,
         ProposalStore::new(),
+        LifetimePolicy::default(),
     )
     .unwrap();
 
     // === Alice adds Bob ===
     let (message, welcome, _group_info) = alice_group
-        .add_members(provider, &alice_signer, &[bob_kpb.key_package().clone()])
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_kpb.key_package().clone()],
+            &AcceptAllCredentials,
+        )
         .expect("Could not add member to group.");
 
     alice_group
@@ -75,7 +84,7 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         ProtocolMessage::PublicMessage(public_message) => public_message,
     };
     let processed_message = public_group
-        .process_message(provider.crypto(), public_message)
+        .process_message(provider.crypto(), provider.time(), public_message)
         .unwrap();
 
     // Further inspection of the message can take place here ...
@@ -98,12 +107,18 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         &mls_group_config,
         welcome.into_welcome().expect("Unexpected message type."),
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("Error creating group from Welcome");
 
     // === Bob adds Charlie ===
     let (queued_messages, welcome, _group_info) = bob_group
-        .add_members(provider, &bob_signer, &[charlie_kpb.key_package().clone()])
+        .add_members(
+            provider,
+            &bob_signer,
+            &[charlie_kpb.key_package().clone()],
+            &AcceptAllCredentials,
+        )
         .unwrap();
 
     // Alice processes
@@ -114,6 +129,7 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                 .clone()
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process messages.");
     if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
@@ -128,7 +144,11 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // The public group processes
     let ppm = public_group
-        .process_message(provider.crypto(), into_public_message(queued_messages))
+        .process_message(
+            provider.crypto(),
+            provider.time(),
+            into_public_message(queued_messages),
+        )
         .unwrap();
     public_group.merge_commit(extract_staged_commit(ppm));
 
@@ -142,6 +162,7 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         &mls_group_config,
         welcome.into_welcome().expect("Unexpected message type."),
         Some(bob_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("Error creating group from Welcome");
 
@@ -158,12 +179,17 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                 .clone()
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process messages.");
 
     // The public group processes
     let ppm = public_group
-        .process_message(provider.crypto(), into_public_message(queued_messages))
+        .process_message(
+            provider.crypto(),
+            provider.time(),
+            into_public_message(queued_messages),
+        )
         .unwrap();
     // We have to add the proposal to the public group's proposal store.
     match ppm.into_content() {
@@ -210,6 +236,7 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let ppm = public_group
         .process_message(
             provider.crypto(),
+            provider.time(),
             into_public_message(queued_messages.clone()),
         )
         .unwrap();
@@ -240,6 +267,7 @@ fn public_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             queued_messages
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process messages.");
     if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
@@ -6,25 +6,25 @@ use std::collections::{BTreeSet, HashSet};
 use openmls_traits::types::VerifiableCiphersuite;
 
 use super::PublicGroup;
-#[cfg(test)]
-use crate::treesync::errors::LeafNodeValidationError;
 use crate::{
     binary_tree::array_representation::LeafNodeIndex,
+    extensions::Extensions,
     framing::{
         mls_auth_content_in::VerifiableAuthenticatedContentIn, ContentType, ProtocolMessage,
         Sender, WireFormat,
     },
     group::{
         errors::{ExternalCommitValidationError, ProposalValidationError, ValidationError},
+        mls_group::errors::ProcessMessageError,
         past_secrets::MessageSecretsStore,
-        Member, ProposalQueue,
+        Member, ProposalQueue, QueuedProposal,
     },
     messages::{
         proposals::{Proposal, ProposalOrRefType, ProposalType},
         Commit,
     },
     schedule::errors::PskError,
-    treesync::node::leaf_node::LeafNode,
+    treesync::{errors::LeafNodeValidationError, node::leaf_node::LeafNode},
 };
 
 impl PublicGroup {
@@ -108,6 +108,13 @@ impl PublicGroup {
             }
         }
 
+        // External senders can only send proposals
+        if matches!(sender, Sender::External(_))
+            && verifiable_content.content_type() == ContentType::Commit
+        {
+            return Err(ValidationError::InvalidSenderType);
+        }
+
         // ValSem009
         if verifiable_content.content_type() == ContentType::Commit
             && verifiable_content.confirmation_tag().is_none()
@@ -452,6 +459,158 @@ impl PublicGroup {
         Ok(())
     }
 
+    /// Validate ReInit proposals. This function checks that a ReInit proposal
+    /// is the only proposal covered by a commit and that it doesn't downgrade
+    /// the protocol version of the group.
+    pub(crate) fn validate_reinit_proposals(
+        &self,
+        proposal_queue: &ProposalQueue,
+    ) -> Result<(), ProposalValidationError> {
+        if let Some(reinit_proposal) = proposal_queue.reinit_proposal() {
+            if proposal_queue.queued_proposals().count() > 1 {
+                return Err(ProposalValidationError::ReInitWithOtherProposals);
+            }
+
+            if reinit_proposal.reinit_proposal().version() < self.version() {
+                return Err(ProposalValidationError::InvalidReInitVersion);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate GroupContextExtensions proposals. This function checks that
+    /// at most one GroupContextExtensions proposal is covered by a commit and
+    /// that all remaining and new members of the group support the proposed
+    /// extensions and the proposed required capabilities.
+    pub(crate) fn validate_group_context_extensions_proposals(
+        &self,
+        proposal_queue: &ProposalQueue,
+    ) -> Result<(), ProposalValidationError> {
+        let mut extensions_proposals =
+            proposal_queue
+                .queued_proposals()
+                .filter_map(|p| match p.proposal() {
+                    Proposal::GroupContextExtensions(extensions_proposal) => {
+                        Some(extensions_proposal.extensions())
+                    }
+                    _ => None,
+                });
+
+        let extensions = match extensions_proposals.next() {
+            Some(extensions) => extensions,
+            None => return Ok(()),
+        };
+        if extensions_proposals.next().is_some() {
+            return Err(ProposalValidationError::MultipleGroupContextExtensionsProposals);
+        }
+
+        // Members that are removed by the commit don't have to support the
+        // new extensions, but the ones that are added do.
+        if !self
+            .leaf_nodes_after_commit(proposal_queue)
+            .all(|leaf_node| leaf_node.supports_group_context_extensions(extensions))
+        {
+            return Err(ProposalValidationError::UnsupportedGroupContextExtensions);
+        }
+
+        Ok(())
+    }
+
+    /// Validate custom proposals. This function checks that the proposal type
+    /// of every custom proposal is not defined by MLS and that it is supported
+    /// by all members of the group after the commit.
+    pub(crate) fn validate_custom_proposals(
+        &self,
+        proposal_queue: &ProposalQueue,
+    ) -> Result<(), ProposalValidationError> {
+        for custom_proposal in proposal_queue.custom_proposals() {
+            let custom_proposal = custom_proposal.custom_proposal();
+            if !custom_proposal.has_custom_type() {
+                return Err(ProposalValidationError::ReservedProposalType);
+            }
+            let proposal_type = ProposalType::Unknown(custom_proposal.proposal_type());
+            if !self
+                .leaf_nodes_after_commit(proposal_queue)
+                .all(|leaf_node| {
+                    leaf_node
+                        .capabilities()
+                        .proposals()
+                        .contains(&proposal_type)
+                })
+            {
+                return Err(ProposalValidationError::UnsupportedCustomProposal);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the leaf nodes of the members of the group after the proposals
+    /// in the given queue were applied, i.e. without the members that are
+    /// removed and with the members that are added.
+    fn leaf_nodes_after_commit<'a>(
+        &'a self,
+        proposal_queue: &'a ProposalQueue,
+    ) -> impl Iterator<Item = &'a LeafNode> {
+        let removed = HashSet::<LeafNodeIndex>::from_iter(
+            proposal_queue
+                .remove_proposals()
+                .map(|remove_proposal| remove_proposal.remove_proposal().removed),
+        );
+        let remaining_leaf_nodes = self
+            .treesync()
+            .full_leave_members()
+            .filter(move |member| !removed.contains(&member.index))
+            .filter_map(move |member| self.treesync().leaf(member.index));
+        let added_leaf_nodes =
+            proposal_queue
+                .queued_proposals()
+                .filter_map(|p| match p.proposal() {
+                    Proposal::Add(add_proposal) => Some(add_proposal.key_package().leaf_node()),
+                    _ => None,
+                });
+
+        remaining_leaf_nodes.chain(added_leaf_nodes)
+    }
+
+    /// Validate a proposal sent by an external sender. External senders can
+    /// propose to add or remove members, to inject a PSK, to reinitialize the
+    /// group and to change the group context extensions. Except for Remove
+    /// proposals, the proposal is subject to the same checks as when it is
+    /// committed.
+    pub(crate) fn validate_external_proposal(
+        &self,
+        queued_proposal: &QueuedProposal,
+    ) -> Result<(), ProcessMessageError> {
+        let mut proposal_queue = ProposalQueue::default();
+        proposal_queue.add(queued_proposal.clone());
+
+        match queued_proposal.proposal() {
+            Proposal::Add(_) => {
+                // ValSem101, ValSem102, ValSem103
+                self.validate_key_uniqueness(&proposal_queue, None)?;
+                // ValSem105
+                self.validate_add_proposals(&proposal_queue)?;
+                // ValSem106
+                self.validate_capabilities(&proposal_queue)?;
+            }
+            // Remove proposals are validated when they are committed (ValSem108).
+            Proposal::Remove(_) => (),
+            // ValSem401, ValSem402
+            Proposal::PreSharedKey(_) => self.validate_pre_shared_key_proposals(&proposal_queue)?,
+            Proposal::ReInit(_) => self.validate_reinit_proposals(&proposal_queue)?,
+            Proposal::GroupContextExtensions(_) => {
+                self.validate_group_context_extensions_proposals(&proposal_queue)?
+            }
+            Proposal::Update(_)
+            | Proposal::ExternalInit(_)
+            | Proposal::AppAck(_)
+            | Proposal::Custom(_) => return Err(ProcessMessageError::UnsupportedProposalType),
+        }
+        Ok(())
+    }
+
     /// Validate constraints on an external commit. This function implements the following checks:
     ///  - ValSem240: External Commit, inline Proposals: There MUST be at least one ExternalInit proposal.
     ///  - ValSem241: External Commit, inline Proposals: There MUST be at most one ExternalInit proposal.
@@ -513,15 +672,35 @@ impl PublicGroup {
         Ok(())
     }
 
-    /// Returns a [`LeafNodeValidationError`] if an [`ExtensionType`]
-    /// in `extensions` is not supported by a leaf in this tree.
-    #[cfg(test)]
+    /// Returns a [`LeafNodeValidationError`] if a member of the group doesn't
+    /// support the given group context `extensions`.
     pub(crate) fn check_extension_support(
         &self,
-        extensions: &[crate::extensions::ExtensionType],
+        extensions: &Extensions,
+    ) -> Result<(), LeafNodeValidationError> {
+        if !self
+            .treesync()
+            .full_leaves()
+            .all(|leaf_node| leaf_node.supports_group_context_extensions(extensions))
+        {
+            return Err(LeafNodeValidationError::UnsupportedExtensions);
+        }
+        Ok(())
+    }
+
+    /// Returns a [`LeafNodeValidationError`] if a member of the group doesn't
+    /// support the given `proposal_type`.
+    pub(crate) fn check_proposal_support(
+        &self,
+        proposal_type: ProposalType,
     ) -> Result<(), LeafNodeValidationError> {
-        for leaf in self.treesync().full_leaves() {
-            leaf.check_extension_support(extensions)?;
+        if !self.treesync().full_leaves().all(|leaf_node| {
+            leaf_node
+                .capabilities()
+                .proposals()
+                .contains(&proposal_type)
+        }) {
+            return Err(LeafNodeValidationError::UnsupportedProposals);
         }
         Ok(())
     }
@@ -5,6 +5,7 @@ use rstest_reuse::{self, *};
 
 use crate::{
     binary_tree::LeafNodeIndex,
+    credentials::AcceptAllCredentials,
     framing::*,
     group::{config::CryptoConfig, *},
     messages::{
@@ -75,7 +76,12 @@ fn validation_test_setup(
     );
 
     let (_message, welcome, _group_info) = alice_group
-        .add_members(provider, &alice_signer_with_keys.signer, &[bob_key_package])
+        .add_members(
+            provider,
+            &alice_signer_with_keys.signer,
+            &[bob_key_package],
+            &AcceptAllCredentials,
+        )
         .expect("error adding Bob to group");
 
     alice_group
@@ -93,6 +99,7 @@ fn validation_test_setup(
         &mls_group_config,
         welcome.into_welcome().expect("Unexpected message type."),
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("error creating group from welcome");
 
@@ -148,7 +155,11 @@ fn external_add_proposal_should_succeed(ciphersuite: Ciphersuite, provider: &imp
         );
 
         let msg = alice_group
-            .process_message(provider, proposal.clone().into_protocol_message().unwrap())
+            .process_message(
+                provider,
+                proposal.clone().into_protocol_message().unwrap(),
+                &AcceptAllCredentials,
+            )
             .unwrap();
 
         match msg.into_content() {
@@ -164,7 +175,11 @@ fn external_add_proposal_should_succeed(ciphersuite: Ciphersuite, provider: &imp
         }
 
         let msg = bob_group
-            .process_message(provider, proposal.into_protocol_message().unwrap())
+            .process_message(
+                provider,
+                proposal.into_protocol_message().unwrap(),
+                &AcceptAllCredentials,
+            )
             .unwrap();
 
         match msg.into_content() {
@@ -183,7 +198,11 @@ fn external_add_proposal_should_succeed(ciphersuite: Ciphersuite, provider: &imp
 
         // Bob will also process the commit
         let msg = bob_group
-            .process_message(provider, commit.into_protocol_message().unwrap())
+            .process_message(
+                provider,
+                commit.into_protocol_message().unwrap(),
+                &AcceptAllCredentials,
+            )
             .unwrap();
         match msg.into_content() {
             ProcessedMessageContent::StagedCommitMessage(commit) => {
@@ -203,6 +222,7 @@ fn external_add_proposal_should_succeed(ciphersuite: Ciphersuite, provider: &imp
             &cfg,
             welcome.unwrap().into_welcome().unwrap(),
             Some(alice_group.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
         )
         .unwrap();
         assert_eq!(charlie_group.members().count(), 3);
@@ -249,7 +269,11 @@ fn external_add_proposal_should_be_signed_by_key_package_it_references(
     // fails because the message was not signed by the same credential as the one in the Add proposal
     assert!(matches!(
         alice_group
-            .process_message(provider, invalid_proposal.into_protocol_message().unwrap())
+            .process_message(
+                provider,
+                invalid_proposal.into_protocol_message().unwrap(),
+                &AcceptAllCredentials
+            )
             .unwrap_err(),
         ProcessMessageError::InvalidSignature
     ));
@@ -299,7 +323,11 @@ fn new_member_proposal_sender_should_be_reserved_for_join_proposals(
 
         // Finally check that the message can be processed without errors
         assert!(bob_group
-            .process_message(provider, join_proposal.into_protocol_message().unwrap())
+            .process_message(
+                provider,
+                join_proposal.into_protocol_message().unwrap(),
+                &AcceptAllCredentials
+            )
             .is_ok());
     } else {
         panic!()
@@ -314,7 +342,9 @@ fn new_member_proposal_sender_should_be_reserved_for_join_proposals(
     if let MlsMessageInBody::PublicMessage(mut plaintext) = remove_proposal.body {
         plaintext.set_sender(Sender::NewMemberProposal);
         assert!(matches!(
-            bob_group.process_message(provider, plaintext).unwrap_err(),
+            bob_group
+                .process_message(provider, plaintext, &AcceptAllCredentials)
+                .unwrap_err(),
             ProcessMessageError::ValidationError(ValidationError::NotAnExternalAddProposal)
         ));
     } else {
@@ -330,7 +360,9 @@ fn new_member_proposal_sender_should_be_reserved_for_join_proposals(
     if let MlsMessageInBody::PublicMessage(mut plaintext) = update_proposal.body {
         plaintext.set_sender(Sender::NewMemberProposal);
         assert!(matches!(
-            bob_group.process_message(provider, plaintext).unwrap_err(),
+            bob_group
+                .process_message(provider, plaintext, &AcceptAllCredentials)
+                .unwrap_err(),
             ProcessMessageError::ValidationError(ValidationError::NotAnExternalAddProposal)
         ));
     } else {
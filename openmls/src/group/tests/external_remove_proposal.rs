@@ -3,12 +3,16 @@ use rstest::*;
 use rstest_reuse::{self, *};
 
 use crate::{
+    credentials::AcceptAllCredentials,
     framing::*,
     group::{config::CryptoConfig, *},
-    messages::external_proposals::*,
+    messages::{external_proposals::*, proposals::ReInitProposal},
+    schedule::PreSharedKeyId,
+    versions::ProtocolVersion,
 };
 
 use openmls_traits::types::Ciphersuite;
+use tls_codec::Serialize;
 
 use super::utils::*;
 
@@ -73,7 +77,12 @@ fn validation_test_setup(
     );
 
     alice_group
-        .add_members(provider, &alice_signer_when_keys.signer, &[bob_key_package])
+        .add_members(
+            provider,
+            &alice_signer_when_keys.signer,
+            &[bob_key_package],
+            &AcceptAllCredentials,
+        )
         .expect("error adding Bob to group");
 
     alice_group
@@ -138,7 +147,11 @@ fn external_remove_proposal_should_remove_member(
 
     // Alice validates the message
     let processed_message = alice_group
-        .process_message(provider, bob_external_remove_proposal)
+        .process_message(
+            provider,
+            bob_external_remove_proposal,
+            &AcceptAllCredentials,
+        )
         .unwrap();
     // commit the proposal
     let ProcessedMessageContent::ProposalMessage(remove_proposal) =
@@ -164,7 +177,11 @@ fn external_remove_proposal_should_remove_member(
     .unwrap()
     .into();
     let processed_message = alice_group
-        .process_message(provider, invalid_bob_external_remove_proposal)
+        .process_message(
+            provider,
+            invalid_bob_external_remove_proposal,
+            &AcceptAllCredentials,
+        )
         .unwrap();
     // commit the proposal
     let ProcessedMessageContent::ProposalMessage(remove_proposal) =
@@ -232,7 +249,11 @@ fn external_remove_proposal_should_fail_when_invalid_external_senders_index(
 
     // Alice tries to validate the message and should fail as sender is invalid
     let error = alice_group
-        .process_message(provider, bob_external_remove_proposal)
+        .process_message(
+            provider,
+            bob_external_remove_proposal,
+            &AcceptAllCredentials,
+        )
         .unwrap_err();
     assert_eq!(
         error,
@@ -290,7 +311,11 @@ fn external_remove_proposal_should_fail_when_invalid_signature(
 
     // Alice tries to validate the message and should fail as sender is invalid
     let error = alice_group
-        .process_message(provider, bob_external_remove_proposal)
+        .process_message(
+            provider,
+            bob_external_remove_proposal,
+            &AcceptAllCredentials,
+        )
         .unwrap_err();
     assert_eq!(error, ProcessMessageError::InvalidSignature);
 }
@@ -332,10 +357,287 @@ fn external_remove_proposal_should_fail_when_no_external_senders(
 
     // Alice tries to validate the message and should fail as sender is invalid
     let error = alice_group
-        .process_message(provider, bob_external_remove_proposal)
+        .process_message(
+            provider,
+            bob_external_remove_proposal,
+            &AcceptAllCredentials,
+        )
         .unwrap_err();
     assert_eq!(
         error,
         ProcessMessageError::ValidationError(ValidationError::NoExternalSendersExtension)
     );
 }
+
+#[apply(ciphersuites_and_providers)]
+fn external_add_proposal_should_add_member(
+    ciphersuite: Ciphersuite,
+    provider: &impl OpenMlsProvider,
+) {
+    // delivery service credentials. DS will craft an external add proposal
+    let ds_credential_with_key = generate_credential_with_key(
+        "delivery-service".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+
+    let (mut alice_group, alice_credential) = validation_test_setup(
+        PURE_PLAINTEXT_WIRE_FORMAT_POLICY,
+        ciphersuite,
+        provider,
+        vec![ExternalSender::new(
+            ds_credential_with_key
+                .credential_with_key
+                .signature_key
+                .clone(),
+            ds_credential_with_key.credential_with_key.credential,
+        )],
+    );
+
+    let charlie_credential_with_key = generate_credential_with_key(
+        "Charlie".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+    let charlie_key_package = generate_key_package(
+        ciphersuite,
+        Extensions::empty(),
+        provider,
+        charlie_credential_with_key.clone(),
+    );
+
+    // The Delivery Service wants to add Charlie
+    let charlie_external_add_proposal: MlsMessageIn = ExternalProposal::new_add(
+        charlie_key_package,
+        alice_group.group_id().clone(),
+        alice_group.epoch(),
+        &ds_credential_with_key.signer,
+        SenderExtensionIndex::new(0),
+    )
+    .unwrap()
+    .into();
+
+    // Alice validates the message
+    let processed_message = alice_group
+        .process_message(
+            provider,
+            charlie_external_add_proposal,
+            &AcceptAllCredentials,
+        )
+        .unwrap();
+    // commit the proposal
+    let ProcessedMessageContent::ProposalMessage(add_proposal) = processed_message.into_content()
+    else {
+        panic!("Not an add proposal");
+    };
+    alice_group.store_pending_proposal(*add_proposal);
+    let (_commit, welcome, _group_info) = alice_group
+        .commit_to_pending_proposals(provider, &alice_credential.signer)
+        .unwrap();
+    alice_group.merge_pending_commit(provider).unwrap();
+    assert_eq!(alice_group.members().count(), 3);
+
+    // Charlie can join the group
+    let charlie_group = MlsGroup::new_from_welcome(
+        provider,
+        &MlsGroupConfig::default(),
+        welcome.unwrap().into_welcome().unwrap(),
+        Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .unwrap();
+    assert_eq!(charlie_group.members().count(), 3);
+
+    // Adding Charlie a second time should fail, since his signature key is
+    // already in the group.
+    let charlie_key_package = generate_key_package(
+        ciphersuite,
+        Extensions::empty(),
+        provider,
+        charlie_credential_with_key,
+    );
+    let charlie_external_add_proposal: MlsMessageIn = ExternalProposal::new_add(
+        charlie_key_package,
+        alice_group.group_id().clone(),
+        alice_group.epoch(),
+        &ds_credential_with_key.signer,
+        SenderExtensionIndex::new(0),
+    )
+    .unwrap()
+    .into();
+    let error = alice_group
+        .process_message(
+            provider,
+            charlie_external_add_proposal,
+            &AcceptAllCredentials,
+        )
+        .unwrap_err();
+    assert_eq!(
+        error,
+        ProcessMessageError::InvalidProposal(ProposalValidationError::DuplicateSignatureKey)
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn external_proposals_should_be_validated(
+    ciphersuite: Ciphersuite,
+    provider: &impl OpenMlsProvider,
+) {
+    // delivery service credentials. DS will craft the external proposals
+    let ds_credential_with_key = generate_credential_with_key(
+        "delivery-service".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+
+    let (mut alice_group, _alice_credential) = validation_test_setup(
+        PURE_PLAINTEXT_WIRE_FORMAT_POLICY,
+        ciphersuite,
+        provider,
+        vec![ExternalSender::new(
+            ds_credential_with_key
+                .credential_with_key
+                .signature_key
+                .clone(),
+            ds_credential_with_key.credential_with_key.credential,
+        )],
+    );
+
+    // A valid PSK proposal
+    let psk_proposal: MlsMessageIn = ExternalProposal::new_psk(
+        PreSharedKeyId::external(b"external psk".to_vec(), vec![0; ciphersuite.hash_length()]),
+        alice_group.group_id().clone(),
+        alice_group.epoch(),
+        &ds_credential_with_key.signer,
+        SenderExtensionIndex::new(0),
+    )
+    .unwrap()
+    .into();
+    let processed_message = alice_group
+        .process_message(provider, psk_proposal, &AcceptAllCredentials)
+        .unwrap();
+    assert!(matches!(
+        processed_message.into_content(),
+        ProcessedMessageContent::ProposalMessage(_)
+    ));
+
+    // A PSK proposal with a nonce of the wrong length (ValSem401)
+    let psk_proposal: MlsMessageIn = ExternalProposal::new_psk(
+        PreSharedKeyId::external(b"external psk".to_vec(), vec![0; 7]),
+        alice_group.group_id().clone(),
+        alice_group.epoch(),
+        &ds_credential_with_key.signer,
+        SenderExtensionIndex::new(0),
+    )
+    .unwrap()
+    .into();
+    let error = alice_group
+        .process_message(provider, psk_proposal, &AcceptAllCredentials)
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        ProcessMessageError::InvalidProposal(ProposalValidationError::Psk(_))
+    ));
+
+    // A valid ReInit proposal
+    let reinit_proposal: MlsMessageIn = ExternalProposal::new_reinit(
+        ReInitProposal::new(
+            GroupId::from_slice(b"Successor Group"),
+            ProtocolVersion::default(),
+            ciphersuite,
+            Extensions::empty(),
+        ),
+        alice_group.group_id().clone(),
+        alice_group.epoch(),
+        &ds_credential_with_key.signer,
+        SenderExtensionIndex::new(0),
+    )
+    .unwrap()
+    .into();
+    let processed_message = alice_group
+        .process_message(provider, reinit_proposal, &AcceptAllCredentials)
+        .unwrap();
+    assert!(matches!(
+        processed_message.into_content(),
+        ProcessedMessageContent::ProposalMessage(_)
+    ));
+
+    // A GroupContextExtensions proposal with an extension that is not
+    // supported by the members
+    let extensions_proposal: MlsMessageIn = ExternalProposal::new_group_context_extensions(
+        Extensions::single(Extension::Unknown(0xff00, UnknownExtension(vec![1, 2, 3]))),
+        alice_group.group_id().clone(),
+        alice_group.epoch(),
+        &ds_credential_with_key.signer,
+        SenderExtensionIndex::new(0),
+    )
+    .unwrap()
+    .into();
+    let error = alice_group
+        .process_message(provider, extensions_proposal, &AcceptAllCredentials)
+        .unwrap_err();
+    assert_eq!(
+        error,
+        ProcessMessageError::InvalidProposal(
+            ProposalValidationError::UnsupportedGroupContextExtensions
+        )
+    );
+}
+
+#[apply(ciphersuites_and_providers)]
+fn external_commit_from_external_sender_should_fail(
+    ciphersuite: Ciphersuite,
+    provider: &impl OpenMlsProvider,
+) {
+    // delivery service credentials. DS will try to send a commit
+    let ds_credential_with_key = generate_credential_with_key(
+        "delivery-service".into(),
+        ciphersuite.signature_algorithm(),
+        provider,
+    );
+
+    let (mut alice_group, alice_credential) = validation_test_setup(
+        PURE_PLAINTEXT_WIRE_FORMAT_POLICY,
+        ciphersuite,
+        provider,
+        vec![ExternalSender::new(
+            ds_credential_with_key
+                .credential_with_key
+                .signature_key
+                .clone(),
+            ds_credential_with_key.credential_with_key.credential,
+        )],
+    );
+
+    // Alice creates a commit, which is then re-signed by the Delivery Service
+    // as an external sender.
+    let (commit, _welcome, _group_info) = alice_group
+        .self_update(provider, &alice_credential.signer)
+        .unwrap();
+    let mut public_message = MlsMessageIn::from(commit).into_plaintext().unwrap();
+    let confirmation_tag = public_message.confirmation_tag().unwrap().clone();
+    public_message.set_sender(Sender::External(SenderExtensionIndex::new(0)));
+    let public_message = resign_external_commit(
+        &ds_credential_with_key.signer,
+        public_message,
+        confirmation_tag,
+        alice_group
+            .export_group_context()
+            .tls_serialize_detached()
+            .unwrap(),
+    );
+    alice_group.clear_pending_commit();
+
+    // Alice must reject the commit instead of panicking
+    let error = alice_group
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message),
+            &AcceptAllCredentials,
+        )
+        .unwrap_err();
+    assert_eq!(
+        error,
+        ProcessMessageError::ValidationError(ValidationError::InvalidSenderType)
+    );
+}
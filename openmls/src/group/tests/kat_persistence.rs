@@ -0,0 +1,360 @@
+//! # Known Answer Tests for the persisted group state
+//!
+//! The test vectors in [`TEST_VECTOR_PATH_READ`] contain states of an
+//! [`MlsGroup`] and a [`PublicGroup`] that were persisted with
+//! [`MlsGroup::to_state_bytes()`] and [`PublicGroup::to_state_bytes()`]. They
+//! guarantee that states persisted by previous versions of OpenMLS can still
+//! be loaded.
+//!
+//! The test vectors must never be replaced. When [`STATE_FORMAT_VERSION`] is
+//! increased, the vectors written by [`write_test_vectors()`] are appended to
+//! the existing ones.
+
+use openmls_rust_crypto::OpenMlsRustCrypto;
+use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite, OpenMlsProvider};
+use serde::{self, Deserialize, Serialize};
+
+use crate::{
+    credentials::AcceptAllCredentials,
+    framing::{MlsMessageOut, ProcessedMessageContent, ProtocolMessage},
+    group::{config::CryptoConfig, test_core_group::setup_client, *},
+    key_packages::{Lifetime, LifetimePolicy},
+    test_utils::{read, write},
+};
+
+const TEST_VECTOR_PATH_READ: &str = "test_vectors/persistence.json";
+const TEST_VECTOR_PATH_WRITE: &str = "test_vectors/persistence-new.json";
+
+const EXPORTER_LABEL: &str = "persistence";
+const EXPORTER_LENGTH: usize = 32;
+
+/// ```json
+/// {
+///   "cipher_suite": /* uint16 */,
+///   "format_version": /* uint16 */,
+///
+///   /* Persisted states */
+///   "mls_group": /* hex-encoded binary data */,
+///   "public_group": /* hex-encoded binary data */,
+///
+///   /* Values of the persisted group */
+///   "group_id": /* hex-encoded binary data */,
+///   "epoch": /* uint64 */,
+///   "tree_hash": /* hex-encoded binary data */,
+///   "exported_secret": /* hex-encoded binary data */,
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PersistenceTestVector {
+    pub cipher_suite: u16,
+    pub format_version: u16,
+
+    #[serde(with = "hex::serde")]
+    pub mls_group: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub public_group: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    pub group_id: Vec<u8>,
+    pub epoch: u64,
+    #[serde(with = "hex::serde")]
+    pub tree_hash: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    pub exported_secret: Vec<u8>,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[test]
+fn read_test_vectors_persistence() {
+    let tests: Vec<PersistenceTestVector> = read(TEST_VECTOR_PATH_READ);
+
+    for test_vector in tests {
+        run_test_vector(test_vector);
+    }
+}
+
+pub fn run_test_vector(test_vector: PersistenceTestVector) {
+    let provider = OpenMlsRustCrypto::default();
+
+    let ciphersuite = Ciphersuite::try_from(test_vector.cipher_suite).unwrap();
+    if provider.crypto().supports(ciphersuite).is_err() {
+        log::debug!("Skipping unsupported ciphersuite `{ciphersuite:?}`.");
+        return;
+    }
+
+    for state in [&test_vector.mls_group, &test_vector.public_group] {
+        assert_eq!(
+            u16::from_be_bytes([state[0], state[1]]),
+            test_vector.format_version
+        );
+    }
+
+    // Load the group ...
+    let mls_group = MlsGroup::from_state_bytes(&test_vector.mls_group).unwrap();
+    assert_eq!(mls_group.ciphersuite(), ciphersuite);
+    assert_eq!(mls_group.group_id().as_slice(), test_vector.group_id);
+    assert_eq!(mls_group.epoch().as_u64(), test_vector.epoch);
+    assert_eq!(mls_group.tree_hash(), test_vector.tree_hash);
+    assert_eq!(
+        mls_group
+            .export_secret(provider.crypto(), EXPORTER_LABEL, &[], EXPORTER_LENGTH)
+            .unwrap(),
+        test_vector.exported_secret
+    );
+    assert_eq!(mls_group.pending_proposals().count(), 1);
+    assert!(mls_group.pending_commit().is_some());
+    // The group isn't stored in a key store yet.
+    assert_eq!(mls_group.state_changed(), InnerState::Changed);
+    // Groups persisted before format version 2 use the default lifetime
+    // policy.
+    if test_vector.format_version < 2 {
+        assert_eq!(
+            mls_group.configuration().lifetime_policy(),
+            &LifetimePolicy::default()
+        );
+    }
+    // Groups persisted before format version 3 use the duration of their
+    // absolute lifetime, which was the default one.
+    if test_vector.format_version < 3 {
+        assert_eq!(
+            mls_group.configuration().lifetime(),
+            Some(Lifetime::default().duration())
+        );
+    }
+    // Groups persisted before format version 4 don't have leaf node
+    // capabilities.
+    if test_vector.format_version < 4 {
+        assert_eq!(mls_group.configuration().leaf_node_capabilities(), None);
+    }
+
+    // ... and the public group.
+    let public_group = PublicGroup::from_state_bytes(&test_vector.public_group).unwrap();
+    assert_eq!(public_group.group_id().as_slice(), test_vector.group_id);
+    assert_eq!(
+        public_group.group_context().epoch().as_u64(),
+        test_vector.epoch
+    );
+    assert_eq!(
+        public_group.group_context().tree_hash(),
+        test_vector.tree_hash
+    );
+    assert_eq!(public_group.members().count(), 2);
+
+    // The encoding of the current format version must not change.
+    if test_vector.format_version == STATE_FORMAT_VERSION {
+        assert_eq!(mls_group.to_state_bytes().unwrap(), test_vector.mls_group);
+        assert_eq!(
+            public_group.to_state_bytes().unwrap(),
+            test_vector.public_group
+        );
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[test]
+fn write_test_vectors() {
+    write(TEST_VECTOR_PATH_WRITE, generate_test_vectors());
+}
+
+fn generate_test_vectors() -> Vec<PersistenceTestVector> {
+    OpenMlsRustCrypto::default()
+        .crypto()
+        .supported_ciphersuites()
+        .into_iter()
+        .map(generate_test_vector)
+        .collect()
+}
+
+/// Generate the state of a group of Alice and Bob in which Alice has a pending
+/// proposal from Bob and a pending commit. Alice keeps the message secrets of
+/// past epochs and received an application message from Bob, such that the
+/// state contains sender ratchets in use.
+pub fn generate_test_vector(ciphersuite: Ciphersuite) -> PersistenceTestVector {
+    let provider = OpenMlsRustCrypto::default();
+
+    let (alice_credential_with_key, _alice_kpb, alice_signer, _alice_pk) =
+        setup_client("Alice", ciphersuite, &provider);
+    let (_bob_credential_with_key, bob_kpb, bob_signer, _bob_pk) =
+        setup_client("Bob", ciphersuite, &provider);
+
+    // Handshake messages are sent as public messages such that the public
+    // group can track the group.
+    let mls_group_config = MlsGroupConfig::builder()
+        .wire_format_policy(PURE_PLAINTEXT_WIRE_FORMAT_POLICY)
+        .max_past_epochs(2)
+        .crypto_config(CryptoConfig::with_default_version(ciphersuite))
+        .build();
+
+    // === Alice creates a group ===
+    let mut alice_group = MlsGroup::new(
+        &provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .unwrap();
+
+    let verifiable_group_info = alice_group
+        .export_group_info(provider.crypto(), &alice_signer, false)
+        .unwrap()
+        .into_verifiable_group_info()
+        .unwrap();
+    let (mut public_group, _extensions) = PublicGroup::from_external(
+        provider.crypto(),
+        provider.time(),
+        alice_group.export_ratchet_tree().into(),
+        verifiable_group_info,
+        ProposalStore::new(),
+        LifetimePolicy::default(),
+    )
+    .unwrap();
+
+    // === Alice adds Bob ===
+    let (commit, welcome, _group_info) = alice_group
+        .add_members(
+            &provider,
+            &alice_signer,
+            &[bob_kpb.key_package().clone()],
+            &AcceptAllCredentials,
+        )
+        .unwrap();
+    alice_group.merge_pending_commit(&provider).unwrap();
+    match public_group
+        .process_message(
+            provider.crypto(),
+            provider.time(),
+            into_protocol_message(commit),
+        )
+        .unwrap()
+        .into_content()
+    {
+        ProcessedMessageContent::StagedCommitMessage(staged_commit) => {
+            public_group.merge_commit(*staged_commit)
+        }
+        _ => panic!("Expected a staged commit."),
+    }
+
+    let mut bob_group = MlsGroup::new_from_welcome(
+        &provider,
+        &mls_group_config,
+        welcome.into_welcome().unwrap(),
+        Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .unwrap();
+
+    // === Bob sends an application message and proposes an update ===
+    let message = bob_group
+        .create_message(&provider, &bob_signer, b"Hello Alice")
+        .unwrap();
+    alice_group
+        .process_message(
+            &provider,
+            into_protocol_message(message),
+            &AcceptAllCredentials,
+        )
+        .unwrap();
+
+    let (proposal, _proposal_ref) = bob_group
+        .propose_self_update(&provider, &bob_signer, None)
+        .unwrap();
+    match alice_group
+        .process_message(
+            &provider,
+            into_protocol_message(proposal.clone()),
+            &AcceptAllCredentials,
+        )
+        .unwrap()
+        .into_content()
+    {
+        ProcessedMessageContent::ProposalMessage(queued_proposal) => {
+            alice_group.store_pending_proposal(*queued_proposal)
+        }
+        _ => panic!("Expected a proposal."),
+    }
+    match public_group
+        .process_message(
+            provider.crypto(),
+            provider.time(),
+            into_protocol_message(proposal),
+        )
+        .unwrap()
+        .into_content()
+    {
+        ProcessedMessageContent::ProposalMessage(queued_proposal) => {
+            public_group.add_proposal(*queued_proposal)
+        }
+        _ => panic!("Expected a proposal."),
+    }
+
+    // === Alice commits without merging the commit ===
+    alice_group.self_update(&provider, &alice_signer).unwrap();
+    assert!(alice_group.pending_commit().is_some());
+
+    PersistenceTestVector {
+        cipher_suite: ciphersuite.into(),
+        format_version: STATE_FORMAT_VERSION,
+        mls_group: alice_group.to_state_bytes().unwrap(),
+        public_group: public_group.to_state_bytes().unwrap(),
+        group_id: alice_group.group_id().as_slice().to_vec(),
+        epoch: alice_group.epoch().as_u64(),
+        tree_hash: alice_group.tree_hash().to_vec(),
+        exported_secret: alice_group
+            .export_secret(provider.crypto(), EXPORTER_LABEL, &[], EXPORTER_LENGTH)
+            .unwrap(),
+    }
+}
+
+fn into_protocol_message(message: MlsMessageOut) -> ProtocolMessage {
+    message
+        .into_protocol_message()
+        .expect("Unexpected message type.")
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[test]
+fn migrations() {
+    // There is one migration per format version after the first one.
+    assert_eq!(
+        <MlsGroup as crate::persistence::PersistedState>::MIGRATIONS.len() + 1,
+        STATE_FORMAT_VERSION as usize
+    );
+    assert_eq!(
+        <PublicGroup as crate::persistence::PersistedState>::MIGRATIONS.len() + 1,
+        STATE_FORMAT_VERSION as usize
+    );
+}
+
+#[test]
+fn unsupported_states() {
+    let ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+    let test_vector = generate_test_vector(ciphersuite);
+
+    // States of unknown format versions are rejected.
+    for format_version in [0, STATE_FORMAT_VERSION + 1] {
+        let mut state = test_vector.mls_group.clone();
+        state[..2].copy_from_slice(&format_version.to_be_bytes());
+        assert_eq!(
+            MlsGroup::from_state_bytes(&state).unwrap_err(),
+            PersistenceError::UnsupportedFormatVersion(format_version)
+        );
+    }
+
+    // Truncated states and trailing data are rejected.
+    let mut state = test_vector.public_group.clone();
+    state.truncate(2);
+    assert!(matches!(
+        PublicGroup::from_state_bytes(&state),
+        Err(PersistenceError::MalformedState(_))
+    ));
+
+    let mut state = test_vector.public_group;
+    state.push(0);
+    assert!(matches!(
+        PublicGroup::from_state_bytes(&state),
+        Err(PersistenceError::MalformedState(_))
+    ));
+}
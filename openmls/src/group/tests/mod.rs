@@ -7,6 +7,8 @@ mod external_remove_proposal;
 #[cfg(test)]
 pub mod kat_messages;
 #[cfg(test)]
+mod kat_persistence;
+#[cfg(test)]
 pub mod kat_transcript_hashes;
 #[cfg(test)]
 mod test_commit_validation;
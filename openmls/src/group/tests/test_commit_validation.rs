@@ -13,6 +13,7 @@ use super::utils::{
 use crate::{
     binary_tree::LeafNodeIndex,
     ciphersuite::signable::Signable,
+    credentials::AcceptAllCredentials,
     framing::*,
     group::{config::CryptoConfig, *},
     messages::proposals::*,
@@ -84,6 +85,7 @@ fn validation_test_setup(
             provider,
             &alice_credential.signer,
             &[bob_key_package, charlie_key_package],
+            &AcceptAllCredentials,
         )
         .expect("error adding Bob to group");
 
@@ -98,6 +100,7 @@ fn validation_test_setup(
         &mls_group_config,
         welcome.clone(),
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("error creating group from welcome");
 
@@ -106,6 +109,7 @@ fn validation_test_setup(
         &mls_group_config,
         welcome,
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("error creating group from welcome");
 
@@ -221,7 +225,7 @@ fn test_valsem200(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let message_in = ProtocolMessage::from(signed_plaintext);
 
     let err = bob_group
-        .process_message(provider, message_in)
+        .process_message(provider, message_in, &AcceptAllCredentials)
         .expect_err("Could process unverified message despite self remove.");
 
     assert_eq!(
@@ -231,7 +235,11 @@ fn test_valsem200(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, ProtocolMessage::from(original_plaintext))
+        .process_message(
+            provider,
+            ProtocolMessage::from(original_plaintext),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -311,9 +319,10 @@ fn test_valsem201(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         ))
     };
 
+    let app_ack_proposal = || queued(Proposal::AppAck(AppAckProposal::new(Vec::new())));
+
     // ExternalInit Proposal cannot be used alone and has to be in an external commit which
     // always contains a path anyway
-    // TODO: #916 when/if AppAck proposal are implemented (path not required)
     // TODO: #751 when ReInit proposal validation are implemented (path not required). Currently one
     // cannot distinguish when the commit has a single ReInit proposal from the commit without proposals
     // in [CoreGroup::apply_proposals()]
@@ -323,6 +332,7 @@ fn test_valsem201(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         (vec![update_proposal.clone()], true),
         (vec![remove_proposal()], true),
         (vec![gce_proposal()], true),
+        (vec![app_ack_proposal()], true),
         // !path_required + !path_required = !path_required
         (vec![add_proposal(), psk_proposal()], false),
         // path_required + !path_required = path_required
@@ -375,7 +385,8 @@ fn test_valsem201(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                 &alice_group,
                 &alice_credential.signer,
             );
-            let processed_msg = bob_group.process_message(provider, commit_wo_path);
+            let processed_msg =
+                bob_group.process_message(provider, commit_wo_path, &AcceptAllCredentials);
             assert_eq!(
                 processed_msg.unwrap_err(),
                 ProcessMessageError::InvalidCommit(StageCommitError::RequiredPathNotFound)
@@ -383,7 +394,8 @@ fn test_valsem201(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         }
 
         // Positive case
-        let process_message_result = bob_group.process_message(provider, commit);
+        let process_message_result =
+            bob_group.process_message(provider, commit, &AcceptAllCredentials);
         assert!(process_message_result.is_ok(), "{process_message_result:?}");
 
         // cleanup & restore for next iteration
@@ -473,7 +485,7 @@ fn test_valsem202(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let update_message_in = ProtocolMessage::from(plaintext);
 
     let err = bob_group
-        .process_message(provider, update_message_in)
+        .process_message(provider, update_message_in, &AcceptAllCredentials)
         .expect_err("Could process unverified message despite path length mismatch.");
 
     assert_eq!(
@@ -489,7 +501,7 @@ fn test_valsem202(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, original_update_plaintext)
+        .process_message(provider, original_update_plaintext, &AcceptAllCredentials)
         .expect("Unexpected error.");
 }
 
@@ -546,7 +558,7 @@ fn test_valsem203(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let update_message_in = ProtocolMessage::from(plaintext);
 
     let err = bob_group
-        .process_message(provider, update_message_in)
+        .process_message(provider, update_message_in, &AcceptAllCredentials)
         .expect_err("Could process unverified message despite scrambled ciphertexts.");
 
     assert_eq!(
@@ -562,7 +574,7 @@ fn test_valsem203(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, original_update_plaintext)
+        .process_message(provider, original_update_plaintext, &AcceptAllCredentials)
         .expect("Unexpected error.");
 }
 
@@ -605,7 +617,7 @@ fn test_valsem204(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Let Charlie process the commit, so we can pull the post-merge tree hash
     // from them.
     let message = charlie_group
-        .process_message(provider, original_plaintext.clone())
+        .process_message(provider, original_plaintext.clone(), &AcceptAllCredentials)
         .unwrap();
     match message.into_content() {
         ProcessedMessageContent::StagedCommitMessage(staged_commit) => charlie_group
@@ -665,7 +677,7 @@ fn test_valsem204(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let update_message_in = ProtocolMessage::from(plaintext);
 
     let err = bob_group
-        .process_message(provider, update_message_in)
+        .process_message(provider, update_message_in, &AcceptAllCredentials)
         .expect_err("Could process unverified message despite modified public key in path.");
 
     assert_eq!(
@@ -681,7 +693,7 @@ fn test_valsem204(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, original_update_plaintext)
+        .process_message(provider, original_update_plaintext, &AcceptAllCredentials)
         .expect("Unexpected error.");
 }
 
@@ -737,7 +749,7 @@ fn test_valsem205(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let update_message_in = ProtocolMessage::from(plaintext);
 
     let err = bob_group
-        .process_message(provider, update_message_in)
+        .process_message(provider, update_message_in, &AcceptAllCredentials)
         .expect_err("Could process unverified message despite confirmation tag mismatch.");
 
     assert_eq!(
@@ -747,7 +759,11 @@ fn test_valsem205(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, ProtocolMessage::from(original_plaintext))
+        .process_message(
+            provider,
+            ProtocolMessage::from(original_plaintext),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -773,7 +789,9 @@ fn test_partial_proposal_commit(ciphersuite: Ciphersuite, provider: &impl OpenMl
         .propose_remove_member(provider, &alice_credential.signer, charlie_index)
         .map(|(out, _)| MlsMessageIn::from(out))
         .unwrap();
-    let proposal_1 = bob_group.process_message(provider, proposal_1).unwrap();
+    let proposal_1 = bob_group
+        .process_message(provider, proposal_1, &AcceptAllCredentials)
+        .unwrap();
     match proposal_1.into_content() {
         ProcessedMessageContent::ProposalMessage(p) => bob_group.store_pending_proposal(*p),
         _ => unreachable!(),
@@ -784,7 +802,9 @@ fn test_partial_proposal_commit(ciphersuite: Ciphersuite, provider: &impl OpenMl
         .propose_self_update(provider, &alice_credential.signer, None)
         .map(|(out, _)| MlsMessageIn::from(out))
         .unwrap();
-    let proposal_2 = bob_group.process_message(provider, proposal_2).unwrap();
+    let proposal_2 = bob_group
+        .process_message(provider, proposal_2, &AcceptAllCredentials)
+        .unwrap();
     match proposal_2.into_content() {
         ProcessedMessageContent::ProposalMessage(p) => bob_group.store_pending_proposal(*p),
         _ => unreachable!(),
@@ -809,7 +829,11 @@ fn test_partial_proposal_commit(ciphersuite: Ciphersuite, provider: &impl OpenMl
 
     // Bob should be able to process the commit
     bob_group
-        .process_message(provider, commit.into_protocol_message().unwrap())
+        .process_message(
+            provider,
+            commit.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
         .expect("Commits with partial proposals are not supported");
     bob_group
         .merge_pending_commit(provider)
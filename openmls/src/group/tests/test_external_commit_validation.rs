@@ -11,6 +11,7 @@ use tls_codec::{Deserialize, Serialize};
 use self::utils::*;
 use crate::{
     ciphersuite::{hash_ref::ProposalRef, signable::Verifiable},
+    credentials::AcceptAllCredentials,
     framing::{
         mls_auth_content_in::AuthenticatedContentIn, ContentType, DecryptedMessage,
         FramedContentBody, MlsMessageIn, ProtocolMessage, Sender, WireFormat,
@@ -82,7 +83,11 @@ fn test_valsem240(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Have alice process the commit resulting from external init.
     // Negative case
     let err = alice_group
-        .process_message(provider, ProtocolMessage::from(public_message_commit_bad))
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message_commit_bad),
+            &AcceptAllCredentials,
+        )
         .expect_err("Could process message despite missing external init proposal.");
 
     assert_eq!(
@@ -94,7 +99,11 @@ fn test_valsem240(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     alice_group
-        .process_message(provider, ProtocolMessage::from(public_message_commit))
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message_commit),
+            &AcceptAllCredentials,
+        )
         .unwrap();
 }
 
@@ -145,7 +154,11 @@ fn test_valsem241(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Have alice process the commit resulting from external init.
     // Negative case
     let err = alice_group
-        .process_message(provider, ProtocolMessage::from(public_message_commit_bad))
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message_commit_bad),
+            &AcceptAllCredentials,
+        )
         .expect_err("Could process message despite second ext. init proposal in commit.");
 
     assert_eq!(
@@ -157,7 +170,11 @@ fn test_valsem241(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     alice_group
-        .process_message(provider, ProtocolMessage::from(public_message_commit))
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message_commit),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -184,7 +201,12 @@ fn test_valsem242(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     );
 
     alice_group
-        .add_members(provider, &alice_credential.signer, &[bob_key_package])
+        .add_members(
+            provider,
+            &alice_credential.signer,
+            &[bob_key_package],
+            &AcceptAllCredentials,
+        )
         .unwrap();
     alice_group.merge_pending_commit(provider).unwrap();
 
@@ -202,6 +224,7 @@ fn test_valsem242(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         alice_group.configuration(),
         &[],
         bob_credential.credential_with_key.clone(),
+        &AcceptAllCredentials,
     )
     .unwrap();
 
@@ -292,7 +315,7 @@ fn test_valsem242(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
         // Negative case
         let err = alice_group
-            .process_message(provider, public_message_commit_bad)
+            .process_message(provider, public_message_commit_bad, &AcceptAllCredentials)
             .unwrap_err();
 
         assert_eq!(
@@ -304,7 +327,11 @@ fn test_valsem242(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
         // Positive case
         alice_group
-            .process_message(provider, public_message_commit.clone())
+            .process_message(
+                provider,
+                public_message_commit.clone(),
+                &AcceptAllCredentials,
+            )
             .unwrap();
     }
 }
@@ -331,7 +358,12 @@ fn test_valsem243(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     );
 
     alice_group
-        .add_members(provider, &alice_credential.signer, &[bob_key_package])
+        .add_members(
+            provider,
+            &alice_credential.signer,
+            &[bob_key_package],
+            &AcceptAllCredentials,
+        )
         .unwrap();
 
     alice_group.merge_pending_commit(provider).unwrap();
@@ -355,6 +387,7 @@ fn test_valsem243(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         alice_group.configuration(),
         &[],
         bob_credential.credential_with_key,
+        &AcceptAllCredentials,
     )
     .unwrap();
 
@@ -429,7 +462,11 @@ fn test_valsem243(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Have alice process the commit resulting from external init.
     // Negative case
     let err = alice_group
-        .process_message(provider, ProtocolMessage::from(public_message_commit_bad))
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message_commit_bad),
+            &AcceptAllCredentials,
+        )
         .expect_err(
             "Could process message despite the remove proposal targeting the wrong group member.",
         );
@@ -450,12 +487,17 @@ fn test_valsem243(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         alice_group.configuration(),
         &[],
         alice_credential.credential_with_key,
+        &AcceptAllCredentials,
     );
     assert!(alice_new_group.is_ok());
 
     // Positive case
     alice_group
-        .process_message(provider, ProtocolMessage::from(public_message_commit))
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message_commit),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -520,7 +562,11 @@ fn test_valsem244(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Expect error because the message can't be processed due to the external
     // commit including an external init proposal by reference.
     let err = alice_group
-        .process_message(provider, ProtocolMessage::from(public_message_commit_bad))
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message_commit_bad),
+            &AcceptAllCredentials,
+        )
         .unwrap_err();
 
     assert_eq!(
@@ -532,7 +578,11 @@ fn test_valsem244(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     alice_group
-        .process_message(provider, ProtocolMessage::from(public_message_commit))
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message_commit),
+            &AcceptAllCredentials,
+        )
         .unwrap();
 }
 
@@ -578,7 +628,11 @@ fn test_valsem245(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Have alice process the commit resulting from external init.
     // Negative case
     let err = alice_group
-        .process_message(provider, ProtocolMessage::from(public_message_commit_bad))
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message_commit_bad),
+            &AcceptAllCredentials,
+        )
         .expect_err("Could process message despite missing path.");
 
     assert_eq!(
@@ -588,7 +642,11 @@ fn test_valsem245(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     alice_group
-        .process_message(provider, ProtocolMessage::from(public_message_commit))
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message_commit),
+            &AcceptAllCredentials,
+        )
         .unwrap();
 }
 
@@ -649,7 +707,11 @@ fn test_valsem246(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Have alice process the commit resulting from external init.
     // Negative case
     let err = alice_group
-        .process_message(provider, ProtocolMessage::from(public_message_commit_bad))
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message_commit_bad),
+            &AcceptAllCredentials,
+        )
         .expect_err("Could process message despite wrong signature.");
 
     // This shows that signature verification fails if the signature is not done
@@ -700,7 +762,11 @@ fn test_valsem246(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // This shows it again, since ValSem010 ensures that the signature is
     // correct (which it only is, if alice is using the credential in the path).
     alice_group
-        .process_message(provider, ProtocolMessage::from(public_message_commit))
+        .process_message(
+            provider,
+            ProtocolMessage::from(public_message_commit),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -732,6 +798,7 @@ fn test_pure_ciphertest(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
         alice_group.configuration(),
         &[],
         bob_credential.credential_with_key.clone(),
+        &AcceptAllCredentials,
     )
     .expect("Error initializing group externally.");
 
@@ -740,7 +807,7 @@ fn test_pure_ciphertest(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
 
     // Would fail if handshake message processing did not distinguish external messages
     assert!(alice_group
-        .process_message(provider, mls_message_in)
+        .process_message(provider, mls_message_in, &AcceptAllCredentials)
         .is_ok());
 }
 
@@ -749,6 +816,7 @@ mod utils {
     use tls_codec::{Deserialize, Serialize};
 
     use crate::{
+        credentials::AcceptAllCredentials,
         framing::{MlsMessageIn, PublicMessage, Sender},
         group::{
             config::CryptoConfig,
@@ -815,6 +883,7 @@ mod utils {
             alice_group.configuration(),
             &[],
             bob_credential.credential_with_key.clone(),
+            &AcceptAllCredentials,
         )
         .unwrap();
 
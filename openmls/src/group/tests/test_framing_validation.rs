@@ -10,6 +10,7 @@ use rstest_reuse::{self, *};
 
 use crate::{
     binary_tree::LeafNodeIndex,
+    credentials::AcceptAllCredentials,
     framing::*,
     group::{config::CryptoConfig, *},
     key_packages::*,
@@ -81,6 +82,7 @@ fn validation_test_setup(
             provider,
             &alice_credential.signer,
             &[bob_key_package.clone()],
+            &AcceptAllCredentials,
         )
         .expect("Could not add member.");
 
@@ -93,6 +95,7 @@ fn validation_test_setup(
         &mls_group_config,
         welcome.into_welcome().expect("Unexpected message type."),
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("error creating bob's group from welcome");
 
@@ -138,7 +141,7 @@ fn test_valsem002(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let message_in = ProtocolMessage::from(plaintext);
 
     let err = bob_group
-        .process_message(provider, message_in)
+        .process_message(provider, message_in, &AcceptAllCredentials)
         .expect_err("Could parse message despite wrong group ID.");
 
     assert_eq!(
@@ -148,7 +151,11 @@ fn test_valsem002(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, ProtocolMessage::from(original_message))
+        .process_message(
+            provider,
+            ProtocolMessage::from(original_message),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -175,7 +182,11 @@ fn test_valsem003(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         .expect("Could not merge commit.");
 
     let processed_message = bob_group
-        .process_message(provider, message.into_protocol_message().unwrap())
+        .process_message(
+            provider,
+            message.into_protocol_message().unwrap(),
+            &AcceptAllCredentials,
+        )
         .expect("Could not process message.");
 
     if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
@@ -206,7 +217,7 @@ fn test_valsem003(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Set the epoch too high
     plaintext.set_epoch(current_epoch.as_u64() + 1);
     let err = bob_group
-        .process_message(provider, plaintext.clone())
+        .process_message(provider, plaintext.clone(), &AcceptAllCredentials)
         .expect_err("Could parse message despite wrong epoch.");
     assert_eq!(
         err,
@@ -216,7 +227,7 @@ fn test_valsem003(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Set the epoch too low
     plaintext.set_epoch(current_epoch.as_u64() - 1);
     let err = bob_group
-        .process_message(provider, plaintext)
+        .process_message(provider, plaintext, &AcceptAllCredentials)
         .expect_err("Could parse message despite wrong epoch.");
     assert_eq!(
         err,
@@ -225,7 +236,7 @@ fn test_valsem003(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     let processed_msg = bob_group
-        .process_message(provider, original_message.clone())
+        .process_message(provider, original_message.clone(), &AcceptAllCredentials)
         .unwrap();
 
     if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
@@ -239,7 +250,8 @@ fn test_valsem003(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     }
 
     // Processing a commit twice should fail i.e. an epoch can only be used once in a commit message
-    let process_twice = bob_group.process_message(provider, original_message);
+    let process_twice =
+        bob_group.process_message(provider, original_message, &AcceptAllCredentials);
     assert_eq!(
         process_twice.unwrap_err(),
         ProcessMessageError::ValidationError(ValidationError::WrongEpoch)
@@ -288,7 +300,7 @@ fn test_valsem004(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let message_in = ProtocolMessage::from(plaintext);
 
     let err = bob_group
-        .process_message(provider, message_in)
+        .process_message(provider, message_in, &AcceptAllCredentials)
         .expect_err("Could parse message despite wrong sender.");
 
     assert_eq!(
@@ -298,7 +310,11 @@ fn test_valsem004(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, ProtocolMessage::from(original_message))
+        .process_message(
+            provider,
+            ProtocolMessage::from(original_message),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -343,7 +359,7 @@ fn test_valsem005(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let message_in = ProtocolMessage::from(plaintext);
 
     let err = bob_group
-        .process_message(provider, message_in)
+        .process_message(provider, message_in, &AcceptAllCredentials)
         .expect_err("Could parse message despite unencrypted application message.");
 
     assert_eq!(
@@ -353,7 +369,11 @@ fn test_valsem005(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, ProtocolMessage::from(original_message))
+        .process_message(
+            provider,
+            ProtocolMessage::from(original_message),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -389,7 +409,7 @@ fn test_valsem006(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let message_in = ProtocolMessage::from(ciphertext);
 
     let err = bob_group
-        .process_message(provider, message_in)
+        .process_message(provider, message_in, &AcceptAllCredentials)
         .expect_err("Could parse message despite garbled ciphertext.");
 
     assert_eq!(
@@ -401,7 +421,11 @@ fn test_valsem006(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, ProtocolMessage::from(original_message))
+        .process_message(
+            provider,
+            ProtocolMessage::from(original_message),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -437,7 +461,7 @@ fn test_valsem007(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let message_in = ProtocolMessage::from(plaintext);
 
     let err = bob_group
-        .process_message(provider, message_in)
+        .process_message(provider, message_in, &AcceptAllCredentials)
         .expect_err("Could parse message despite missing membership tag.");
 
     assert_eq!(
@@ -447,7 +471,11 @@ fn test_valsem007(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, ProtocolMessage::from(original_message))
+        .process_message(
+            provider,
+            ProtocolMessage::from(original_message),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -487,7 +515,7 @@ fn test_valsem008(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let message_in = ProtocolMessage::from(plaintext);
 
     let err = bob_group
-        .process_message(provider, message_in)
+        .process_message(provider, message_in, &AcceptAllCredentials)
         .expect_err("Could process message despite wrong membership tag.");
 
     assert_eq!(
@@ -497,7 +525,11 @@ fn test_valsem008(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, ProtocolMessage::from(original_message))
+        .process_message(
+            provider,
+            ProtocolMessage::from(original_message),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -542,7 +574,7 @@ fn test_valsem009(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let message_in = ProtocolMessage::from(plaintext);
 
     let err = bob_group
-        .process_message(provider, message_in)
+        .process_message(provider, message_in, &AcceptAllCredentials)
         .expect_err("Could parse message despite missing confirmation tag.");
 
     assert_eq!(
@@ -552,7 +584,11 @@ fn test_valsem009(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, ProtocolMessage::from(original_message))
+        .process_message(
+            provider,
+            ProtocolMessage::from(original_message),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -599,13 +635,17 @@ fn test_valsem010(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     let message_in = ProtocolMessage::from(plaintext);
 
     let err = bob_group
-        .process_message(provider, message_in)
+        .process_message(provider, message_in, &AcceptAllCredentials)
         .expect_err("Could process message despite wrong signature.");
 
     assert_eq!(err, ProcessMessageError::InvalidSignature);
 
     // Positive case
     bob_group
-        .process_message(provider, ProtocolMessage::from(original_message))
+        .process_message(
+            provider,
+            ProtocolMessage::from(original_message),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
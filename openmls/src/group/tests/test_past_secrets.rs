@@ -8,6 +8,7 @@ use rstest_reuse::{self, *};
 
 use super::utils::{generate_credential_with_key, generate_key_package};
 use crate::{
+    credentials::AcceptAllCredentials,
     framing::{MessageDecryptionError, ProcessedMessageContent},
     group::{config::CryptoConfig, *},
 };
@@ -61,6 +62,7 @@ fn test_past_secrets_in_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsP
                 provider,
                 &alice_credential_with_keys.signer,
                 &[bob_key_package],
+                &AcceptAllCredentials,
             )
             .expect("An unexpected error occurred.");
 
@@ -73,6 +75,7 @@ fn test_past_secrets_in_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsP
             &mls_group_config,
             welcome.into_welcome().expect("Unexpected message type."),
             Some(alice_group.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
         )
         .expect("Error creating group from Welcome");
 
@@ -103,7 +106,11 @@ fn test_past_secrets_in_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsP
 
         for update_commit in update_commits {
             let bob_processed_message = bob_group
-                .process_message(provider, update_commit.into_protocol_message().unwrap())
+                .process_message(
+                    provider,
+                    update_commit.into_protocol_message().unwrap(),
+                    &AcceptAllCredentials,
+                )
                 .expect("An unexpected error occurred.");
 
             if let ProcessedMessageContent::StagedCommitMessage(staged_commit) =
@@ -122,7 +129,7 @@ fn test_past_secrets_in_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsP
         // The first messages should fail
         for application_message in application_messages.iter().take(max_epochs / 2) {
             let err = bob_group
-                .process_message(provider, application_message.clone())
+                .process_message(provider, application_message.clone(), &AcceptAllCredentials)
                 .expect_err("An unexpected error occurred.");
             assert_eq!(
                 err,
@@ -135,7 +142,7 @@ fn test_past_secrets_in_group(ciphersuite: Ciphersuite, provider: &impl OpenMlsP
         // The last messages should not fail
         for application_message in application_messages.iter().skip(max_epochs / 2) {
             let bob_processed_message = bob_group
-                .process_message(provider, application_message.clone())
+                .process_message(provider, application_message.clone(), &AcceptAllCredentials)
                 .expect("An unexpected error occurred.");
 
             if let ProcessedMessageContent::ApplicationMessage(application_message) =
@@ -76,6 +76,7 @@ fn create_group_with_members<KeyStore: OpenMlsKeyStore>(
             provider,
             &alice_credential_with_key_and_signer.signer,
             member_key_packages,
+            &AcceptAllCredentials,
         )
         .map(|(msg, welcome, _group_info)| {
             (
@@ -149,6 +150,7 @@ fn validation_test_setup(
             provider,
             &alice_credential_with_key_and_signer.signer,
             &[bob_key_package],
+            &AcceptAllCredentials,
         )
         .unwrap();
 
@@ -165,6 +167,7 @@ fn validation_test_setup(
         &mls_group_config,
         welcome.into_welcome().unwrap(),
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .unwrap();
 
@@ -330,6 +333,7 @@ fn test_valsem101a(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             provider,
             &alice_credential_with_key_and_signer.signer,
             &[charlie_key_package],
+            &AcceptAllCredentials,
         )
         .expect("Error creating self-update")
         .tls_serialize_detached()
@@ -379,7 +383,7 @@ fn test_valsem101a(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Have bob process the resulting plaintext
     let err = bob_group
-        .process_message(provider, update_message_in)
+        .process_message(provider, update_message_in, &AcceptAllCredentials)
         .expect_err("Could process message despite modified public key in path.");
 
     assert_eq!(
@@ -395,7 +399,7 @@ fn test_valsem101a(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, original_update_plaintext)
+        .process_message(provider, original_update_plaintext, &AcceptAllCredentials)
         .expect("Unexpected error.");
 }
 
@@ -488,6 +492,7 @@ fn test_valsem102(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             provider,
             &alice_credential_with_key_and_signer.signer,
             &[charlie_key_package.clone()],
+            &AcceptAllCredentials,
         )
         .expect("Error creating self-update")
         .tls_serialize_detached()
@@ -531,7 +536,7 @@ fn test_valsem102(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Have bob process the resulting plaintext
     let err = bob_group
-        .process_message(provider, update_message_in)
+        .process_message(provider, update_message_in, &AcceptAllCredentials)
         .expect_err("Could process message despite modified encryption key in path.");
 
     assert_eq!(
@@ -547,7 +552,7 @@ fn test_valsem102(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, original_update_plaintext)
+        .process_message(provider, original_update_plaintext, &AcceptAllCredentials)
         .expect("Unexpected error.");
 }
 
@@ -626,6 +631,7 @@ fn test_valsem101b(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                         provider,
                         &alice_credential_with_key.signer,
                         &[bob_key_package, target_key_package],
+                        &AcceptAllCredentials,
                     )
                     .expect_err("was able to add user with same signature key as a group member!");
                 assert_eq!(
@@ -641,6 +647,7 @@ fn test_valsem101b(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                         provider,
                         &alice_credential_with_key.signer,
                         &[bob_key_package, target_key_package],
+                        &AcceptAllCredentials,
                     )
                     .expect("failed to add user with different signature keypair!");
             }
@@ -650,6 +657,7 @@ fn test_valsem101b(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                         provider,
                         &alice_credential_with_key.signer,
                         &[bob_key_package.clone()],
+                        &AcceptAllCredentials,
                     )
                     .unwrap();
                 alice_group.merge_pending_commit(provider).unwrap();
@@ -667,7 +675,7 @@ fn test_valsem101b(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                     .propose_remove_member(provider, &alice_credential_with_key.signer, bob_index)
                     .unwrap();
                 alice_group
-                    .add_members(provider, &alice_credential_with_key.signer, &[target_key_package])
+                    .add_members(provider, &alice_credential_with_key.signer, &[target_key_package], &AcceptAllCredentials)
                     .expect(
                     "failed to add a user with the same identity as someone in the group (with a remove proposal)!",
                 );
@@ -775,7 +783,7 @@ fn test_valsem101b(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             KeyUniqueness::NegativeSameKey => {
                 // Have bob process the resulting plaintext
                 let err = bob_group
-                    .process_message(provider, verifiable_plaintext)
+                    .process_message(provider, verifiable_plaintext, &AcceptAllCredentials)
                     .expect_err("Could process message despite modified public key in path.");
 
                 assert_eq!(
@@ -787,7 +795,7 @@ fn test_valsem101b(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             }
             KeyUniqueness::PositiveSameKeyWithRemove => {
                 bob_group
-                    .process_message(provider, verifiable_plaintext)
+                    .process_message(provider, verifiable_plaintext, &AcceptAllCredentials)
                     .expect(
                         "Could not process message despite having a remove proposal in the commit",
                     );
@@ -801,7 +809,7 @@ fn test_valsem101b(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
         // Positive case
         bob_group
-            .process_message(provider, original_update_plaintext)
+            .process_message(provider, original_update_plaintext, &AcceptAllCredentials)
             .expect("Unexpected error.");
     } */
 }
@@ -946,7 +954,7 @@ fn test_valsem103_valsem104(ciphersuite: Ciphersuite, provider: &impl OpenMlsPro
 
     // Have bob process the resulting plaintext
     let err = bob_group
-        .process_message(provider, update_message_in)
+        .process_message(provider, update_message_in, &AcceptAllCredentials)
         .expect_err("Could process message despite modified public key in path.");
 
     assert_eq!(
@@ -962,7 +970,7 @@ fn test_valsem103_valsem104(ciphersuite: Ciphersuite, provider: &impl OpenMlsPro
 
     // Positive case
     bob_group
-        .process_message(provider, original_update_plaintext)
+        .process_message(provider, original_update_plaintext, &AcceptAllCredentials)
         .expect("Unexpected error.");
 }
 
@@ -1032,7 +1040,7 @@ fn test_valsem105(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             generate_credential_with_key_and_key_package("Charlie".into(), ciphersuite, provider);
 
         let kpi = KeyPackageIn::from(charlie_key_package.clone());
-        kpi.validate(provider.crypto(), ProtocolVersion::Mls10)
+        kpi.validate(provider.crypto(), provider.time(), ProtocolVersion::Mls10)
             .unwrap();
 
         // Let's just pick a ciphersuite that's not the one we're testing right now.
@@ -1151,6 +1159,7 @@ fn test_valsem105(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                         provider,
                         &alice_credential_with_key_and_signer.signer,
                         &[test_kp_2.clone()],
+                        &AcceptAllCredentials,
                     );
 
                     match key_package_version {
@@ -1225,7 +1234,7 @@ fn test_valsem105(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
             // Have bob process the resulting plaintext
             let err = bob_group
-                .process_message(provider, update_message_in)
+                .process_message(provider, update_message_in, &AcceptAllCredentials)
                 .expect_err("Could process message despite injected add proposal.");
 
             match key_package_version {
@@ -1308,7 +1317,7 @@ fn test_valsem105(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
             // Positive case
             bob_group
-                .process_message(provider, original_update_plaintext)
+                .process_message(provider, original_update_plaintext, &AcceptAllCredentials)
                 .unwrap();
         }
 
@@ -1567,7 +1576,7 @@ fn test_valsem108(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Have bob process the resulting plaintext
     let err = bob_group
-        .process_message(provider, update_message_in)
+        .process_message(provider, update_message_in, &AcceptAllCredentials)
         .expect_err("Could process message despite modified public key in path.");
 
     assert_eq!(
@@ -1583,7 +1592,7 @@ fn test_valsem108(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, original_update_plaintext)
+        .process_message(provider, original_update_plaintext, &AcceptAllCredentials)
         .expect("Unexpected error.");
 }
 
@@ -1645,7 +1654,7 @@ fn test_valsem110(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Have Alice process this proposal.
     if let ProcessedMessageContent::ProposalMessage(proposal) = alice_group
-        .process_message(provider, update_proposal)
+        .process_message(provider, update_proposal, &AcceptAllCredentials)
         .expect("error processing proposal")
         .into_content()
     {
@@ -1720,7 +1729,7 @@ fn test_valsem110(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Have bob process the resulting plaintext
     let err = bob_group
-        .process_message(provider, update_message_in)
+        .process_message(provider, update_message_in, &AcceptAllCredentials)
         .expect_err("Could process message despite modified public key in path.");
 
     assert_eq!(
@@ -1814,7 +1823,7 @@ fn test_valsem111(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Have bob process the resulting plaintext
     let err = bob_group
-        .process_message(provider, update_message_in)
+        .process_message(provider, update_message_in, &AcceptAllCredentials)
         .expect_err("Could process message despite modified public key in path.");
 
     assert_eq!(
@@ -1874,7 +1883,7 @@ fn test_valsem111(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Have bob process the resulting plaintext
     let err = bob_group
-        .process_message(provider, update_message_in)
+        .process_message(provider, update_message_in, &AcceptAllCredentials)
         .expect_err("Could process message despite modified public key in path.");
 
     assert_eq!(
@@ -1890,7 +1899,7 @@ fn test_valsem111(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, original_update_plaintext)
+        .process_message(provider, original_update_plaintext, &AcceptAllCredentials)
         .expect("Unexpected error.");
 }
 
@@ -1940,7 +1949,7 @@ fn test_valsem112(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Have bob process the resulting plaintext
     let err = bob_group
-        .process_message(provider, update_message_in)
+        .process_message(provider, update_message_in, &AcceptAllCredentials)
         .expect_err("Could parse message despite modified public key in path.");
 
     assert_eq!(
@@ -1954,7 +1963,11 @@ fn test_valsem112(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Positive case
     bob_group
-        .process_message(provider, ProtocolMessage::from(original_plaintext))
+        .process_message(
+            provider,
+            ProtocolMessage::from(original_plaintext),
+            &AcceptAllCredentials,
+        )
         .expect("Unexpected error.");
 }
 
@@ -2082,7 +2095,11 @@ fn test_valsem401_valsem402(ciphersuite: Ciphersuite, provider: &impl OpenMlsPro
 
         for psk_proposal in proposals.into_iter() {
             let processed_message = bob_group
-                .process_message(&bob_provider, psk_proposal.into_protocol_message().unwrap())
+                .process_message(
+                    &bob_provider,
+                    psk_proposal.into_protocol_message().unwrap(),
+                    &AcceptAllCredentials,
+                )
                 .unwrap();
 
             match processed_message.into_content() {
@@ -2096,7 +2113,11 @@ fn test_valsem401_valsem402(ciphersuite: Ciphersuite, provider: &impl OpenMlsPro
         assert_eq!(
             expected_error,
             bob_group
-                .process_message(&bob_provider, commit.into_protocol_message().unwrap())
+                .process_message(
+                    &bob_provider,
+                    commit.into_protocol_message().unwrap(),
+                    &AcceptAllCredentials
+                )
                 .unwrap_err(),
         );
 
@@ -2,6 +2,7 @@
 
 use super::utils::{generate_credential_with_key, generate_key_package};
 use crate::{
+    credentials::AcceptAllCredentials,
     framing::*,
     group::{config::CryptoConfig, *},
     test_utils::*,
@@ -82,6 +83,7 @@ fn test_remove_operation_variants(ciphersuite: Ciphersuite, provider: &impl Open
                 &alice_provider,
                 &alice_credential_with_key_and_signer.signer,
                 &[bob_key_package, charlie_key_package],
+                &AcceptAllCredentials,
             )
             .expect("An unexpected error occurred.");
         alice_group
@@ -95,6 +97,7 @@ fn test_remove_operation_variants(ciphersuite: Ciphersuite, provider: &impl Open
             &mls_group_config,
             welcome.clone(),
             Some(alice_group.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
         )
         .expect("Error creating group from Welcome");
 
@@ -103,6 +106,7 @@ fn test_remove_operation_variants(ciphersuite: Ciphersuite, provider: &impl Open
             &mls_group_config,
             welcome,
             Some(alice_group.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
         )
         .expect("Error creating group from Welcome");
 
@@ -134,6 +138,7 @@ fn test_remove_operation_variants(ciphersuite: Ciphersuite, provider: &impl Open
                         .process_message(
                             &charlie_provider,
                             message.clone().into_protocol_message().unwrap(),
+                            &AcceptAllCredentials,
                         )
                         .expect("Could not process message.");
 
@@ -196,6 +201,7 @@ fn test_remove_operation_variants(ciphersuite: Ciphersuite, provider: &impl Open
             .process_message(
                 &bob_provider,
                 message.clone().into_protocol_message().unwrap(),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
 
@@ -247,7 +253,11 @@ fn test_remove_operation_variants(ciphersuite: Ciphersuite, provider: &impl Open
         // === Remove operation from Charlie's perspective ===
 
         let charlie_processed_message = charlie_group
-            .process_message(&charlie_provider, message.into_protocol_message().unwrap())
+            .process_message(
+                &charlie_provider,
+                message.into_protocol_message().unwrap(),
+                &AcceptAllCredentials,
+            )
             .expect("Could not process message.");
 
         match charlie_processed_message.into_content() {
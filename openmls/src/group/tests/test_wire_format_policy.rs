@@ -7,6 +7,7 @@ use rstest::*;
 use rstest_reuse::{self, *};
 
 use crate::{
+    credentials::AcceptAllCredentials,
     framing::*,
     group::{config::CryptoConfig, *},
 };
@@ -67,7 +68,12 @@ fn receive_message(
     );
 
     let (_message, welcome, _group_info) = alice_group
-        .add_members(provider, alice_signer, &[bob_key_package])
+        .add_members(
+            provider,
+            alice_signer,
+            &[bob_key_package],
+            &AcceptAllCredentials,
+        )
         .expect("Could not add member.");
 
     alice_group
@@ -84,6 +90,7 @@ fn receive_message(
         &mls_group_config,
         welcome.into_welcome().expect("Unexpected message type."),
         None,
+        &AcceptAllCredentials,
     )
     .expect("error creating bob's group from welcome");
 
@@ -106,7 +113,7 @@ fn test_wire_policy_positive(ciphersuite: Ciphersuite, provider: &impl OpenMlsPr
             &alice_credential_with_key_and_signer.signer,
         );
         alice_group
-            .process_message(provider, message)
+            .process_message(provider, message, &AcceptAllCredentials)
             .expect("An unexpected error occurred.");
     }
 }
@@ -135,7 +142,7 @@ fn test_wire_policy_negative(ciphersuite: Ciphersuite, provider: &impl OpenMlsPr
             &alice_credential_with_key_and_signer.signer,
         );
         let err = alice_group
-            .process_message(provider, message)
+            .process_message(provider, message, &AcceptAllCredentials)
             .expect_err("An unexpected error occurred.");
         assert_eq!(err, ProcessMessageError::IncompatibleWireFormat);
     }
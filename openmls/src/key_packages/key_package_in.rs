@@ -8,7 +8,7 @@ use crate::{
     treesync::node::leaf_node::{LeafNode, LeafNodeIn, VerifiableLeafNode},
     versions::ProtocolVersion,
 };
-use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+use openmls_traits::{crypto::OpenMlsCrypto, time::OpenMlsTime, types::Ciphersuite};
 use serde::{Deserialize, Serialize};
 use tls_codec::{Serialize as TlsSerializeTrait, TlsDeserialize, TlsSerialize, TlsSize};
 
@@ -104,7 +104,7 @@ impl KeyPackageIn {
     /// * verify that the signature on this key package is valid
     /// * verify that the signature on the leaf node is valid
     /// * verify that all extensions are supported by the leaf node
-    /// * make sure that the lifetime is valid
+    /// * make sure that the lifetime is valid at the current time of `time`
     /// * make sure that the init key and the encryption key are different
     /// * make sure that the protocol version is valid
     ///
@@ -113,6 +113,7 @@ impl KeyPackageIn {
     pub fn validate(
         self,
         crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
         protocol_version: ProtocolVersion,
     ) -> Result<KeyPackage, KeyPackageVerifyError> {
         // We first need to verify the LeafNode inside the KeyPackage
@@ -167,7 +168,7 @@ impl KeyPackageIn {
 
         // Ensure validity of the life time extension in the leaf node.
         if let Some(life_time) = key_package.payload.leaf_node.life_time() {
-            if !life_time.is_valid() {
+            if !life_time.is_valid(time) {
                 return Err(KeyPackageVerifyError::InvalidLifetime);
             }
         } else {
@@ -1,11 +1,12 @@
-use std::time::{SystemTime, UNIX_EPOCH};
-
+use openmls_traits::time::{OpenMlsTime, SystemClock};
 use serde::{Deserialize, Serialize};
 use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize};
 
+use crate::{persistence::Persist, treesync::LeafNode};
+
 /// This value is used as the default lifetime if no default  lifetime is configured.
 /// The value is in seconds and amounts to 3 * 28 Days, i.e. about 3 months.
-const DEFAULT_KEY_PACKAGE_LIFETIME_SECONDS: u64 = 60 * 60 * 24 * 28 * 3;
+pub(super) const DEFAULT_KEY_PACKAGE_LIFETIME_SECONDS: u64 = 60 * 60 * 24 * 28 * 3;
 
 /// This value is used as the default amount of time (in seconds) the lifetime
 /// of a `KeyPackage` is extended into the past to allow for skewed clocks. The
@@ -43,42 +44,79 @@ pub struct Lifetime {
 }
 
 impl Lifetime {
-    /// Create a new lifetime with lifetime `t` (in seconds).
+    /// Create a new lifetime with lifetime `t` (in seconds), starting at the
+    /// current system time.
     /// Note that the lifetime is extended 1h into the past to adapt to skewed
     /// clocks, i.e. `not_before` is set to now - 1h.
     pub fn new(t: u64) -> Self {
+        Self::new_with_time(&SystemClock::new(), t)
+    }
+
+    /// Create a new lifetime with lifetime `t` (in seconds), starting at the
+    /// current time of the `time` source.
+    /// Note that the lifetime is extended 1h into the past to adapt to skewed
+    /// clocks, i.e. `not_before` is set to now - 1h.
+    pub fn new_with_time(time: &dyn OpenMlsTime, t: u64) -> Self {
         let lifetime_margin: u64 = DEFAULT_KEY_PACKAGE_LIFETIME_MARGIN_SECONDS;
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("SystemTime before UNIX EPOCH!")
-            .as_secs();
-        let not_before = now - lifetime_margin;
-        let not_after = now + t;
+        let now = time.now();
+        let not_before = now.saturating_sub(lifetime_margin);
+        let not_after = now.saturating_add(t);
         Self {
             not_before,
             not_after,
         }
     }
 
-    /// Returns true if this lifetime is valid.
-    pub(crate) fn is_valid(&self) -> bool {
-        match SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|duration| duration.as_secs())
-        {
-            Ok(elapsed) => self.not_before < elapsed && elapsed < self.not_after,
-            Err(_) => {
-                log::error!("SystemTime before UNIX EPOCH.");
-                false
-            }
-        }
+    /// Create a new lifetime with the default lifetime, starting at the
+    /// current time of the `time` source.
+    pub(crate) fn default_with_time(time: &dyn OpenMlsTime) -> Self {
+        Self::new_with_time(time, DEFAULT_KEY_PACKAGE_LIFETIME_SECONDS)
+    }
+
+    /// Returns the `not_before` timestamp in seconds since the Unix epoch.
+    pub fn not_before(&self) -> u64 {
+        self.not_before
+    }
+
+    /// Returns the `not_after` timestamp in seconds since the Unix epoch.
+    pub fn not_after(&self) -> u64 {
+        self.not_after
+    }
+
+    /// Returns true if this lifetime is valid at the current time of the
+    /// `time` source, taking its clock skew tolerance into account.
+    pub(crate) fn is_valid(&self, time: &dyn OpenMlsTime) -> bool {
+        let now = time.now();
+        let tolerance = time.clock_skew_tolerance();
+        self.not_before < now.saturating_add(tolerance)
+            && now.saturating_sub(tolerance) < self.not_after
+    }
+
+    /// Returns true if this lifetime ended, i.e., `not_after` is not in the
+    /// future of the `time` source, taking its clock skew tolerance into
+    /// account.
+    pub(crate) fn is_expired(&self, time: &dyn OpenMlsTime) -> bool {
+        self.not_after <= time.now().saturating_sub(time.clock_skew_tolerance())
     }
 
     /// ValSem(openmls/annotations#32):
     /// Applications MUST define a maximum total lifetime that is acceptable for a LeafNode,
     /// and reject any LeafNode where the total lifetime is longer than this duration.
     pub fn has_acceptable_range(&self) -> bool {
-        self.not_after.saturating_sub(self.not_before) <= MAX_LEAF_NODE_LIFETIME_RANGE_SECONDS
+        self.range() <= MAX_LEAF_NODE_LIFETIME_RANGE_SECONDS
+    }
+
+    /// Returns the lifetime `t` (in seconds) this lifetime was created with by
+    /// [`Lifetime::new()`], i.e., its range without the margin for skewed
+    /// clocks.
+    pub(crate) fn duration(&self) -> u64 {
+        self.range()
+            .saturating_sub(DEFAULT_KEY_PACKAGE_LIFETIME_MARGIN_SECONDS)
+    }
+
+    /// Returns the total lifetime range in seconds.
+    fn range(&self) -> u64 {
+        self.not_after.saturating_sub(self.not_before)
     }
 }
 
@@ -88,22 +126,161 @@ impl Default for Lifetime {
     }
 }
 
+/// Defines what happens to a leaf node whose lifetime is rejected by a
+/// [`LifetimePolicy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LifetimeEnforcement {
+    /// The leaf node is rejected.
+    Reject,
+    /// The leaf node is accepted, but a warning is logged.
+    Warn,
+    /// Lifetimes are not checked.
+    Ignore,
+}
+
+/// The policy for the lifetimes of leaf nodes that were created for a key
+/// package and that enter a group, i.e., the leaf nodes of the ratchet tree
+/// when joining a group and of key packages that are added to the group.
+///
+/// A lifetime is rejected if it is not valid at the current time of the
+/// provider's [`OpenMlsTime`], or if its total range is longer than the
+/// maximum range.
+///
+/// The default policy logs a warning for rejected lifetimes and accepts
+/// ranges of up to about 3 months. Note that key packages in Add proposals
+/// must always have a valid lifetime, see [`KeyPackageIn::validate()`].
+///
+/// [`KeyPackageIn::validate()`]: crate::key_packages::KeyPackageIn::validate()
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LifetimePolicy {
+    enforcement: LifetimeEnforcement,
+    max_range: u64,
+}
+
+impl LifetimePolicy {
+    /// Create a new lifetime policy with the given `enforcement` and the
+    /// maximum total lifetime range `max_range` (in seconds).
+    pub fn new(enforcement: LifetimeEnforcement, max_range: u64) -> Self {
+        Self {
+            enforcement,
+            max_range,
+        }
+    }
+
+    /// Returns the [`LifetimeEnforcement`] of this policy.
+    pub fn enforcement(&self) -> LifetimeEnforcement {
+        self.enforcement
+    }
+
+    /// Returns the maximum total lifetime range (in seconds).
+    pub fn max_range(&self) -> u64 {
+        self.max_range
+    }
+
+    /// Returns false if the lifetime of `leaf_node` is rejected and the
+    /// policy rejects leaf nodes. Leaf nodes without a lifetime are accepted.
+    pub(crate) fn accepts(&self, leaf_node: &LeafNode, time: &dyn OpenMlsTime) -> bool {
+        let lifetime = match (self.enforcement, leaf_node.life_time()) {
+            (LifetimeEnforcement::Ignore, _) | (_, None) => return true,
+            (_, Some(lifetime)) => lifetime,
+        };
+
+        let rejection = if !lifetime.is_valid(time) {
+            "is not valid at the current time"
+        } else if lifetime.range() > self.max_range {
+            "exceeds the maximum range"
+        } else {
+            return true;
+        };
+
+        match self.enforcement {
+            LifetimeEnforcement::Reject => {
+                log::debug!("Rejecting leaf node: Its lifetime {rejection}.");
+                false
+            }
+            _ => {
+                log::warn!("The lifetime of a leaf node {rejection}.");
+                true
+            }
+        }
+    }
+}
+
+impl Default for LifetimePolicy {
+    fn default() -> Self {
+        Self::new(
+            LifetimeEnforcement::Warn,
+            MAX_LEAF_NODE_LIFETIME_RANGE_SECONDS,
+        )
+    }
+}
+
+/// The enforcement is encoded as a `uint8` value in the order in which the
+/// variants are declared, starting at 1.
+impl Persist for LifetimePolicy {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        let enforcement: u8 = match self.enforcement {
+            LifetimeEnforcement::Reject => 1,
+            LifetimeEnforcement::Warn => 2,
+            LifetimeEnforcement::Ignore => 3,
+        };
+        enforcement.persist(writer)?;
+        self.max_range.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        let enforcement = match u8::restore(bytes)? {
+            1 => LifetimeEnforcement::Reject,
+            2 => LifetimeEnforcement::Warn,
+            3 => LifetimeEnforcement::Ignore,
+            value => {
+                return Err(tls_codec::Error::DecodingError(format!(
+                    "{value} is not a valid lifetime enforcement"
+                )))
+            }
+        };
+        Ok(Self {
+            enforcement,
+            max_range: u64::restore(bytes)?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use openmls_traits::time::{OpenMlsTime, SystemClock};
     use tls_codec::{Deserialize, Serialize};
 
     use super::Lifetime;
 
+    /// A clock that is fixed at the given time.
+    struct FixedClock {
+        now: u64,
+        clock_skew_tolerance: u64,
+    }
+
+    impl OpenMlsTime for FixedClock {
+        fn now(&self) -> u64 {
+            self.now
+        }
+
+        fn clock_skew_tolerance(&self) -> u64 {
+            self.clock_skew_tolerance
+        }
+    }
+
     #[test]
     fn lifetime() {
+        let time = SystemClock::new();
+
         // A freshly created extensions must be valid.
         let ext = Lifetime::default();
-        assert!(ext.is_valid());
+        assert!(ext.is_valid(&time));
 
         // An extension without lifetime is invalid (waiting for 1 second).
         let ext = Lifetime::new(0);
         std::thread::sleep(std::time::Duration::from_secs(1));
-        assert!(!ext.is_valid());
+        assert!(!ext.is_valid(&time));
 
         // Test (de)serializing invalid extension
         let serialized = ext
@@ -111,6 +288,36 @@ mod tests {
             .expect("error encoding life time extension");
         let ext_deserialized = Lifetime::tls_deserialize(&mut serialized.as_slice())
             .expect("Error deserializing lifetime");
-        assert!(!ext_deserialized.is_valid());
+        assert!(!ext_deserialized.is_valid(&time));
+        assert!(ext_deserialized.is_expired(&time));
+        assert!(!Lifetime::default().is_expired(&time));
+    }
+
+    #[test]
+    fn lifetime_with_time() {
+        let mut time = FixedClock {
+            now: 1_000_000,
+            clock_skew_tolerance: 0,
+        };
+        let lifetime = Lifetime::new_with_time(&time, 100);
+        assert_eq!(lifetime.not_before(), 1_000_000 - 60 * 60);
+        assert_eq!(lifetime.not_after(), 1_000_100);
+        assert!(lifetime.is_valid(&time));
+
+        // The lifetime expired.
+        time.now = 1_000_100;
+        assert!(!lifetime.is_valid(&time));
+        assert!(lifetime.is_expired(&time));
+
+        // It is still accepted within the clock skew tolerance.
+        time.clock_skew_tolerance = 10;
+        assert!(lifetime.is_valid(&time));
+        assert!(!lifetime.is_expired(&time));
+
+        // The lifetime didn't start yet.
+        time.now = 1_000_000 - 60 * 60 - 10;
+        assert!(!lifetime.is_valid(&time));
+        time.now += 1;
+        assert!(lifetime.is_valid(&time));
     }
 }
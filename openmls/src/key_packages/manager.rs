@@ -0,0 +1,350 @@
+//! # Key package manager
+//!
+//! The [`KeyPackageManager`] maintains a pool of key packages for each
+//! configured ciphersuite. It builds new key packages when the pool runs low,
+//! replaces key packages whose [`Lifetime`](super::Lifetime) is about to end
+//! and keeps track of the key packages that were consumed by Welcome messages.
+//!
+//! The changes that have to be uploaded to the delivery service are collected
+//! in a [`KeyPackageDiff`].
+
+use std::collections::BTreeMap;
+
+use openmls_traits::{
+    key_store::OpenMlsKeyStore, signatures::Signer, types::Ciphersuite, OpenMlsProvider,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    errors::KeyPackageNewError, lifetime::DEFAULT_KEY_PACKAGE_LIFETIME_SECONDS, KeyPackage,
+    KeyPackageBuilder, Lifetime,
+};
+use crate::{
+    ciphersuite::hash_ref::KeyPackageRef, credentials::CredentialWithKey,
+    group::config::CryptoConfig, messages::Welcome,
+};
+
+/// The default amount of time (in seconds) before the end of its lifetime at
+/// which a key package is replaced. The value amounts to 7 days.
+const DEFAULT_REFRESH_MARGIN_SECONDS: u64 = 60 * 60 * 24 * 7;
+
+/// A key package in a pool of the [`KeyPackageManager`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManagedKeyPackage {
+    key_package_ref: KeyPackageRef,
+    key_package: KeyPackage,
+    published: bool,
+}
+
+impl ManagedKeyPackage {
+    /// Returns true if the lifetime of the key package ends before `deadline`.
+    fn ends_before(&self, deadline: u64) -> bool {
+        self.key_package
+            .leaf_node()
+            .life_time()
+            .map(|lifetime| lifetime.not_after() <= deadline)
+            .unwrap_or_default()
+    }
+}
+
+/// The pool of key packages of a ciphersuite.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyPackagePool {
+    crypto_config: CryptoConfig,
+    target: usize,
+    builder: KeyPackageBuilder,
+    key_packages: Vec<ManagedKeyPackage>,
+}
+
+/// The changes to the key packages of a [`KeyPackageManager`] that have to be
+/// applied at the delivery service.
+#[derive(Debug, Clone, Default)]
+pub struct KeyPackageDiff {
+    to_publish: Vec<(KeyPackageRef, KeyPackage)>,
+    to_revoke: Vec<KeyPackageRef>,
+}
+
+impl KeyPackageDiff {
+    /// Returns an iterator over the key packages that have to be published.
+    pub fn to_publish(&self) -> impl Iterator<Item = &KeyPackage> {
+        self.to_publish.iter().map(|(_, key_package)| key_package)
+    }
+
+    /// Returns the references of the key packages that have to be removed from
+    /// the delivery service.
+    pub fn to_revoke(&self) -> &[KeyPackageRef] {
+        &self.to_revoke
+    }
+
+    /// Returns true if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.to_publish.is_empty() && self.to_revoke.is_empty()
+    }
+}
+
+/// Maintains a pool of key packages for each configured ciphersuite.
+///
+/// The key packages are created with [`KeyPackageBuilder::build()`] and
+/// therefore stored in the key store of the provider. The manager is used as
+/// follows:
+///
+/// 1. [`KeyPackageManager::set_target()`] configures the number of key
+///    packages that should be available for a ciphersuite.
+/// 2. [`KeyPackageManager::replenish()`] replaces the key packages whose
+///    lifetime ends within the refresh margin and builds new key packages
+///    until the target of each pool is reached. It should be called
+///    regularly, e.g. on start-up and after joining groups.
+/// 3. [`KeyPackageManager::process_welcome()`] removes the key package a
+///    Welcome was encrypted to from its pool.
+/// 4. [`KeyPackageManager::diff()`] returns the key packages to publish and
+///    the references of the key packages to revoke at the delivery service.
+///    After the changes were uploaded, they are confirmed with
+///    [`KeyPackageManager::confirm()`].
+///
+/// Key packages that were never published are deleted from the key store
+/// right away when they are removed from a pool. Key packages that were
+/// published are kept, such that Welcome messages that are still in flight
+/// can be processed, until [`MlsGroup::collect_garbage()`] removes them after
+/// their lifetime ended.
+///
+/// The manager holds no secrets and can be stored with `serde`.
+///
+/// [`MlsGroup::collect_garbage()`]: crate::group::MlsGroup::collect_garbage()
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyPackageManager {
+    credential_with_key: CredentialWithKey,
+    key_package_lifetime: u64,
+    refresh_margin: u64,
+    pools: BTreeMap<Ciphersuite, KeyPackagePool>,
+    to_revoke: Vec<KeyPackageRef>,
+}
+
+impl KeyPackageManager {
+    /// Create a new key package manager without any pools. The key packages
+    /// are created for the given `credential_with_key`.
+    ///
+    /// Key packages have a lifetime of about 3 months and are replaced 7 days
+    /// before their lifetime ends.
+    pub fn new(credential_with_key: CredentialWithKey) -> Self {
+        Self {
+            credential_with_key,
+            key_package_lifetime: DEFAULT_KEY_PACKAGE_LIFETIME_SECONDS,
+            refresh_margin: DEFAULT_REFRESH_MARGIN_SECONDS,
+            pools: BTreeMap::new(),
+            to_revoke: Vec::new(),
+        }
+    }
+
+    /// Set the lifetime (in seconds) of new key packages.
+    pub fn with_key_package_lifetime(mut self, key_package_lifetime: u64) -> Self {
+        self.key_package_lifetime = key_package_lifetime;
+        self
+    }
+
+    /// Set the amount of time (in seconds) before the end of their lifetime
+    /// at which key packages are replaced. The margin should be considerably
+    /// shorter than the lifetime of the key packages, otherwise new key
+    /// packages are replaced right away.
+    pub fn with_refresh_margin(mut self, refresh_margin: u64) -> Self {
+        self.refresh_margin = refresh_margin;
+        self
+    }
+
+    /// Set the number of key packages that should be available for the
+    /// ciphersuite of `crypto_config`. A `target` of 0 revokes all key
+    /// packages of the ciphersuite with the next call to
+    /// [`KeyPackageManager::replenish()`].
+    pub fn set_target(&mut self, crypto_config: CryptoConfig, target: usize) {
+        self.set_target_with_builder(crypto_config, target, KeyPackageBuilder::new())
+    }
+
+    /// Set the number of key packages that should be available for the
+    /// ciphersuite of `crypto_config`, and the `builder` that new key packages
+    /// are built with, e.g. to set their extensions or capabilities. The
+    /// lifetime of the `builder` is replaced with the lifetime configured for
+    /// the manager.
+    pub fn set_target_with_builder(
+        &mut self,
+        crypto_config: CryptoConfig,
+        target: usize,
+        builder: KeyPackageBuilder,
+    ) {
+        let pool = self
+            .pools
+            .entry(crypto_config.ciphersuite)
+            .or_insert_with(|| KeyPackagePool {
+                crypto_config,
+                target,
+                builder: KeyPackageBuilder::new(),
+                key_packages: Vec::new(),
+            });
+        pool.crypto_config = crypto_config;
+        pool.target = target;
+        pool.builder = builder;
+    }
+
+    /// Returns the number of key packages that should be available for the
+    /// `ciphersuite`.
+    pub fn target(&self, ciphersuite: Ciphersuite) -> usize {
+        self.pools
+            .get(&ciphersuite)
+            .map(|pool| pool.target)
+            .unwrap_or_default()
+    }
+
+    /// Returns an iterator over the key packages in the pool of the
+    /// `ciphersuite`, including the ones that were not published yet.
+    pub fn key_packages(&self, ciphersuite: Ciphersuite) -> impl Iterator<Item = &KeyPackage> {
+        self.pools
+            .get(&ciphersuite)
+            .into_iter()
+            .flat_map(|pool| pool.key_packages.iter())
+            .map(|managed| &managed.key_package)
+    }
+
+    /// Replace the key packages whose lifetime ends within the refresh margin
+    /// at the current time of the provider's
+    /// [`OpenMlsTime`](openmls_traits::time::OpenMlsTime), remove the key
+    /// packages that exceed the target of their pool, and build new key
+    /// packages until the target of each pool is reached.
+    ///
+    /// Returns an error if building a key package or accessing the key store
+    /// fails. The key packages that were built before the error occurred are
+    /// kept in their pool.
+    pub fn replenish<KeyStore: OpenMlsKeyStore>(
+        &mut self,
+        provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
+        signer: &impl Signer,
+    ) -> Result<(), KeyPackageNewError<KeyStore::Error>> {
+        let deadline = provider.time().now().saturating_add(self.refresh_margin);
+
+        for pool in self.pools.values_mut() {
+            // Remove the key packages that end soon, and the ones that end
+            // first if there are more than needed.
+            pool.key_packages.sort_by_key(|managed| {
+                managed
+                    .key_package
+                    .leaf_node()
+                    .life_time()
+                    .map(|lifetime| std::cmp::Reverse(lifetime.not_after()))
+            });
+            let mut keep = pool.target;
+            let mut removed = Vec::new();
+            for managed in std::mem::take(&mut pool.key_packages) {
+                if keep > 0 && !managed.ends_before(deadline) {
+                    keep -= 1;
+                    pool.key_packages.push(managed);
+                } else {
+                    removed.push(managed);
+                }
+            }
+
+            // Published key packages are revoked before the others are
+            // deleted, such that none of them is lost if deleting fails.
+            let (published, unpublished): (Vec<_>, Vec<_>) =
+                removed.into_iter().partition(|managed| managed.published);
+            self.to_revoke
+                .extend(published.into_iter().map(|managed| managed.key_package_ref));
+            for managed in unpublished {
+                managed
+                    .key_package
+                    .delete(provider)
+                    .map_err(KeyPackageNewError::KeyStoreError)?;
+            }
+
+            while pool.key_packages.len() < pool.target {
+                let key_package = pool
+                    .builder
+                    .clone()
+                    .key_package_lifetime(Lifetime::new_with_time(
+                        provider.time(),
+                        self.key_package_lifetime,
+                    ))
+                    .build(
+                        pool.crypto_config,
+                        provider,
+                        signer,
+                        self.credential_with_key.clone(),
+                    )?;
+                pool.key_packages.push(ManagedKeyPackage {
+                    key_package_ref: key_package.hash_ref(provider.crypto())?,
+                    key_package,
+                    published: false,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remove the key package the `welcome` was encrypted to from its pool,
+    /// such that it is revoked and replaced.
+    ///
+    /// This should be called for every Welcome message that is received, e.g.
+    /// before joining the group with
+    /// [`MlsGroup::new_from_welcome()`](crate::group::MlsGroup::new_from_welcome()).
+    /// Last resort key packages are kept in their pool.
+    ///
+    /// Returns the reference of the consumed key package, or `None` if the
+    /// `welcome` wasn't encrypted to a key package of this manager.
+    pub fn process_welcome(&mut self, welcome: &Welcome) -> Option<KeyPackageRef> {
+        for secrets in welcome.secrets() {
+            let key_package_ref = secrets.new_member();
+            for pool in self.pools.values_mut() {
+                let position = match pool
+                    .key_packages
+                    .iter()
+                    .position(|managed| managed.key_package_ref == key_package_ref)
+                {
+                    Some(position) => position,
+                    None => continue,
+                };
+                if !pool.key_packages[position].key_package.last_resort() {
+                    let managed = pool.key_packages.remove(position);
+                    if managed.published {
+                        self.to_revoke.push(managed.key_package_ref);
+                    }
+                }
+                return Some(key_package_ref);
+            }
+        }
+        None
+    }
+
+    /// Returns the key packages that have to be published and the references
+    /// of the key packages that have to be revoked at the delivery service.
+    pub fn diff(&self) -> KeyPackageDiff {
+        KeyPackageDiff {
+            to_publish: self
+                .pools
+                .values()
+                .flat_map(|pool| pool.key_packages.iter())
+                .filter(|managed| !managed.published)
+                .map(|managed| (managed.key_package_ref.clone(), managed.key_package.clone()))
+                .collect(),
+            to_revoke: self.to_revoke.clone(),
+        }
+    }
+
+    /// Confirm that the changes of the `diff` were applied at the delivery
+    /// service, i.e., mark its key packages as published and forget about its
+    /// revoked key packages.
+    ///
+    /// Key packages that were removed from their pool since the `diff` was
+    /// created are revoked with the next diff.
+    pub fn confirm(&mut self, diff: &KeyPackageDiff) {
+        for (key_package_ref, _) in &diff.to_publish {
+            match self
+                .pools
+                .values_mut()
+                .flat_map(|pool| pool.key_packages.iter_mut())
+                .find(|managed| &managed.key_package_ref == key_package_ref)
+            {
+                Some(managed) => managed.published = true,
+                None => self.to_revoke.push(key_package_ref.clone()),
+            }
+        }
+        self.to_revoke
+            .retain(|key_package_ref| !diff.to_revoke.contains(key_package_ref));
+    }
+}
@@ -87,7 +87,7 @@
 //!     .expect("Could not deserialize KeyPackage");
 //!
 //! let key_package = key_package_in
-//!     .validate(provider.crypto(), ProtocolVersion::Mls10)
+//!     .validate(provider.crypto(), provider.time(), ProtocolVersion::Mls10)
 //!     .expect("Invalid KeyPackage");
 //! ```
 //!
@@ -103,9 +103,9 @@ use crate::{
     },
     credentials::*,
     error::LibraryError,
-    extensions::ExtensionType,
-    extensions::Extensions,
+    extensions::{Extension, ExtensionType, Extensions, LastResortExtension},
     group::config::CryptoConfig,
+    persistence::Persist,
     treesync::{
         node::{
             encryption_keys::EncryptionKeyPair,
@@ -117,7 +117,7 @@ use crate::{
 };
 use openmls_traits::{
     crypto::OpenMlsCrypto,
-    key_store::{MlsEntity, MlsEntityId, OpenMlsKeyStore},
+    key_store::{with_transaction, MlsEntity, MlsEntityId, OpenMlsKeyStore},
     signatures::Signer,
     types::Ciphersuite,
     OpenMlsProvider,
@@ -133,6 +133,7 @@ pub mod errors;
 pub mod key_package_in;
 
 mod lifetime;
+mod manager;
 
 // Tests
 #[cfg(test)]
@@ -140,7 +141,8 @@ pub(crate) mod test_key_packages;
 
 // Public types
 pub use key_package_in::KeyPackageIn;
-pub use lifetime::Lifetime;
+pub use lifetime::{Lifetime, LifetimeEnforcement, LifetimePolicy};
+pub use manager::{KeyPackageDiff, KeyPackageManager};
 
 /// The unsigned payload of a key package.
 /// Any modification must happen on this unsigned struct. Use `sign` to get a
@@ -209,6 +211,26 @@ impl MlsEntity for KeyPackage {
     const ID: MlsEntityId = MlsEntityId::KeyPackage;
 }
 
+/// Key packages in the group state have been verified before. They are
+/// therefore restored without verifying them again.
+impl Persist for KeyPackage {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.tls_serialize(writer).map(|_| ())
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        let payload = KeyPackageTbs {
+            protocol_version: ProtocolVersion::restore(bytes)?,
+            ciphersuite: Ciphersuite::restore(bytes)?,
+            init_key: HpkePublicKey::restore(bytes)?,
+            leaf_node: LeafNode::restore(bytes)?,
+            extensions: Extensions::restore(bytes)?,
+        };
+        let signature = Signature::restore(bytes)?;
+        Ok(Self { payload, signature })
+    }
+}
+
 /// Helper struct containing the results of building a new [`KeyPackage`].
 pub(crate) struct KeyPackageCreationResult {
     pub key_package: KeyPackage,
@@ -334,13 +356,17 @@ impl KeyPackage {
     }
 
     /// Check whether the this key package supports all the required extensions
-    /// in the provided list.
+    /// in the provided list. An extension is supported if it is contained in
+    /// the key package or listed in the capabilities of its leaf node. This
+    /// includes [`CustomExtension`](crate::extensions::CustomExtension)s.
     pub fn check_extension_support(
         &self,
         required_extensions: &[ExtensionType],
     ) -> Result<(), KeyPackageExtensionSupportError> {
         for required_extension in required_extensions.iter() {
-            if !self.extensions().contains(*required_extension) {
+            if !self.extensions().contains(*required_extension)
+                && !self.leaf_node().supports_extension(required_extension)
+            {
                 return Err(KeyPackageExtensionSupportError::UnsupportedExtension);
             }
         }
@@ -376,6 +402,15 @@ impl KeyPackage {
     pub fn hpke_init_key(&self) -> &HpkePublicKey {
         &self.payload.init_key
     }
+
+    /// Returns `true` if this is a last resort key package, i.e., it contains
+    /// a [`LastResortExtension`].
+    ///
+    /// Last resort key packages and their private keys are not deleted from
+    /// the key store when a group is joined with them.
+    pub fn last_resort(&self) -> bool {
+        self.extensions().last_resort().is_some()
+    }
 }
 
 /// Crate visible `KeyPackage` functions.
@@ -406,7 +441,7 @@ impl KeyPackage {
             provider,
             signer,
             credential_with_key,
-            Lifetime::default(),
+            Lifetime::default_with_time(provider.time()),
             extensions,
             leaf_node_capabilities,
             leaf_node_extensions,
@@ -463,7 +498,7 @@ impl KeyPackage {
         let leaf_node = LeafNode::create_new_with_key(
             encryption_key,
             credential_with_key,
-            LeafNodeSource::KeyPackage(Lifetime::default()),
+            LeafNodeSource::KeyPackage(Lifetime::default_with_time(provider.time())),
             leaf_node_capabilities,
             leaf_node_extensions,
             TreeInfoTbs::KeyPackage,
@@ -555,6 +590,8 @@ pub struct KeyPackageBuilder {
     key_package_extensions: Option<Extensions>,
     leaf_node_capabilities: Option<Capabilities>,
     leaf_node_extensions: Option<Extensions>,
+    #[serde(default)]
+    last_resort: bool,
 }
 
 impl KeyPackageBuilder {
@@ -565,6 +602,7 @@ impl KeyPackageBuilder {
             key_package_extensions: None,
             leaf_node_capabilities: None,
             leaf_node_extensions: None,
+            last_resort: false,
         }
     }
 
@@ -592,33 +630,60 @@ impl KeyPackageBuilder {
         self
     }
 
+    /// Mark the key package as a last resort key package.
+    ///
+    /// A [`LastResortExtension`] is added to the key package extensions and
+    /// its extension type to the leaf node capabilities. The key package, its
+    /// HPKE init key and its encryption key are kept in the key store when a
+    /// group is joined with it, so that it can be used to join more groups.
+    pub fn mark_as_last_resort(mut self) -> Self {
+        self.last_resort = true;
+        self
+    }
+
+    /// Returns the key package extensions and the leaf node capabilities,
+    /// including the last resort extension if the key package is marked as a
+    /// last resort key package.
+    fn extensions_and_capabilities(&mut self) -> (Extensions, Capabilities) {
+        let mut extensions = self.key_package_extensions.take().unwrap_or_default();
+        let mut capabilities = self.leaf_node_capabilities.take().unwrap_or_default();
+        if self.last_resort {
+            extensions.add_or_replace(Extension::LastResort(LastResortExtension::new()));
+            capabilities.add_extension(ExtensionType::LastResort);
+        }
+        (extensions, capabilities)
+    }
+
     pub(crate) fn build_without_key_storage<KeyStore: OpenMlsKeyStore>(
-        self,
+        mut self,
         config: CryptoConfig,
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
         signer: &impl Signer,
         credential_with_key: CredentialWithKey,
     ) -> Result<KeyPackageCreationResult, KeyPackageNewError<KeyStore::Error>> {
+        let (extensions, capabilities) = self.extensions_and_capabilities();
         KeyPackage::create(
             config,
             provider,
             signer,
             credential_with_key,
-            self.key_package_lifetime.unwrap_or_default(),
-            self.key_package_extensions.unwrap_or_default(),
-            self.leaf_node_capabilities.unwrap_or_default(),
+            self.key_package_lifetime
+                .unwrap_or_else(|| Lifetime::default_with_time(provider.time())),
+            extensions,
+            capabilities,
             self.leaf_node_extensions.unwrap_or_default(),
         )
     }
 
     /// Finalize and build the key package.
     pub fn build<KeyStore: OpenMlsKeyStore>(
-        self,
+        mut self,
         config: CryptoConfig,
         provider: &impl OpenMlsProvider<KeyStoreProvider = KeyStore>,
         signer: &impl Signer,
         credential_with_key: CredentialWithKey,
     ) -> Result<KeyPackage, KeyPackageNewError<KeyStore::Error>> {
+        let (extensions, capabilities) = self.extensions_and_capabilities();
         let KeyPackageCreationResult {
             key_package,
             encryption_keypair,
@@ -628,33 +693,45 @@ impl KeyPackageBuilder {
             provider,
             signer,
             credential_with_key,
-            self.key_package_lifetime.unwrap_or_default(),
-            self.key_package_extensions.unwrap_or_default(),
-            self.leaf_node_capabilities.unwrap_or_default(),
+            self.key_package_lifetime
+                .unwrap_or_else(|| Lifetime::default_with_time(provider.time())),
+            extensions,
+            capabilities,
             self.leaf_node_extensions.unwrap_or_default(),
         )?;
 
-        // Store the key package in the key store with the hash reference as id
-        // for retrieval when parsing welcome messages.
-        provider
-            .key_store()
-            .store(
-                key_package.hash_ref(provider.crypto())?.as_slice(),
-                &key_package,
-            )
-            .map_err(KeyPackageNewError::KeyStoreError)?;
-
-        // Store the encryption key pair in the key store.
-        encryption_keypair
-            .write_to_key_store(provider.key_store())
-            .map_err(KeyPackageNewError::KeyStoreError)?;
-
-        // Store the private part of the init_key into the key store.
-        // The key is the public key.
-        provider
-            .key_store()
-            .store::<HpkePrivateKey>(key_package.hpke_init_key().as_slice(), &init_private_key)
-            .map_err(KeyPackageNewError::KeyStoreError)?;
+        // The key package and its private keys are stored in a single
+        // transaction, such that no key package is stored without them.
+        with_transaction(
+            provider.key_store(),
+            || -> Result<(), KeyPackageNewError<KeyStore::Error>> {
+                // Store the key package in the key store with the hash reference as id
+                // for retrieval when parsing welcome messages.
+                provider
+                    .key_store()
+                    .store(
+                        key_package.hash_ref(provider.crypto())?.as_slice(),
+                        &key_package,
+                    )
+                    .map_err(KeyPackageNewError::KeyStoreError)?;
+
+                // Store the encryption key pair in the key store.
+                encryption_keypair
+                    .write_to_key_store(provider.key_store())
+                    .map_err(KeyPackageNewError::KeyStoreError)?;
+
+                // Store the private part of the init_key into the key store.
+                // The key is the public key.
+                provider
+                    .key_store()
+                    .store::<HpkePrivateKey>(
+                        key_package.hpke_init_key().as_slice(),
+                        &init_private_key,
+                    )
+                    .map_err(KeyPackageNewError::KeyStoreError)
+            },
+        )
+        .map_err(KeyPackageNewError::KeyStoreError)??;
 
         Ok(key_package)
     }
@@ -691,6 +768,7 @@ impl KeyPackageBundle {
         credential_with_key: CredentialWithKey,
     ) -> Self {
         let key_package = KeyPackage::builder()
+            .leaf_node_capabilities(Capabilities::with_ciphersuite(ciphersuite))
             .build(
                 CryptoConfig {
                     ciphersuite,
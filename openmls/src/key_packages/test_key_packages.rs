@@ -3,7 +3,12 @@ use openmls_basic_credential::SignatureKeyPair;
 use openmls_rust_crypto::OpenMlsRustCrypto;
 use tls_codec::Deserialize;
 
-use crate::{extensions::*, key_packages::*};
+use crate::{
+    credentials::{test_utils, AcceptAllCredentials},
+    extensions::*,
+    group::{MlsGroup, MlsGroupConfig},
+    key_packages::*,
+};
 
 /// Helper function to generate key packages
 pub(crate) fn key_package(
@@ -38,7 +43,7 @@ fn generate_key_package(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
 
     let kpi = KeyPackageIn::from(key_package);
     assert!(kpi
-        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .validate(provider.crypto(), provider.time(), ProtocolVersion::Mls10)
         .is_ok());
 }
 
@@ -85,7 +90,7 @@ fn application_id_extension(ciphersuite: Ciphersuite, provider: &impl OpenMlsPro
 
     let kpi = KeyPackageIn::from(key_package.clone());
     assert!(kpi
-        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .validate(provider.crypto(), provider.time(), ProtocolVersion::Mls10)
         .is_ok());
 
     // Check ID
@@ -119,7 +124,7 @@ fn key_package_validation(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvi
 
     let key_package_in = KeyPackageIn::tls_deserialize(&mut encoded.as_slice()).unwrap();
     let err = key_package_in
-        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .validate(provider.crypto(), provider.time(), ProtocolVersion::Mls10)
         .unwrap_err();
 
     // Expect an invalid protocol version error
@@ -138,9 +143,224 @@ fn key_package_validation(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvi
 
     let key_package_in = KeyPackageIn::tls_deserialize(&mut encoded.as_slice()).unwrap();
     let err = key_package_in
-        .validate(provider.crypto(), ProtocolVersion::Mls10)
+        .validate(provider.crypto(), provider.time(), ProtocolVersion::Mls10)
         .unwrap_err();
 
     // Expect an invalid init/encryption key error
     assert_eq!(err, KeyPackageVerifyError::InitKeyEqualsEncryptionKey);
 }
+
+/// Test that the lifetime of a key package is created and validated with the
+/// time source of the provider.
+#[apply(ciphersuites_and_providers)]
+fn key_package_lifetime_validation(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    // A key package created a year ago has expired.
+    let past = FixedTimeProvider::shifted(provider, -60 * 60 * 24 * 365);
+    let (key_package, _, _) = key_package(ciphersuite, &past);
+    let lifetime = *key_package.leaf_node().life_time().unwrap();
+    assert_eq!(lifetime.not_before(), past.now - 60 * 60);
+
+    let err = KeyPackageIn::from(key_package.clone())
+        .validate(provider.crypto(), provider.time(), ProtocolVersion::Mls10)
+        .unwrap_err();
+    assert_eq!(err, KeyPackageVerifyError::InvalidLifetime);
+
+    // It was valid at the time it was created.
+    assert!(KeyPackageIn::from(key_package.clone())
+        .validate(provider.crypto(), past.time(), ProtocolVersion::Mls10)
+        .is_ok());
+
+    // It is valid again, with a clock skew tolerance that covers the time
+    // since it expired.
+    let tolerant = FixedTimeProvider {
+        provider,
+        now: provider.time().now(),
+        clock_skew_tolerance: provider.time().now() - lifetime.not_after() + 1,
+    };
+    assert!(KeyPackageIn::from(key_package)
+        .validate(provider.crypto(), tolerant.time(), ProtocolVersion::Mls10)
+        .is_ok());
+}
+
+#[test]
+fn x509_key_package_validation() {
+    let provider = OpenMlsRustCrypto::default();
+    let ciphersuite = Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519;
+
+    let credential = Credential::new_x509(vec![
+        Certificate::new(include_bytes!("../../test_vectors/x509/alice.der").to_vec()),
+        Certificate::new(include_bytes!("../../test_vectors/x509/ca.der").to_vec()),
+    ])
+    .unwrap();
+    let alice_signer = SignatureKeyPair::from_raw(
+        ciphersuite.signature_algorithm(),
+        include_bytes!("../../test_vectors/x509/alice_key.raw").to_vec(),
+        hex::decode("8f8daead0e999e96f083cab213aef2d901bebf15864d0cd023ea601e4248778e").unwrap(),
+    );
+    let capabilities = Capabilities::new(
+        None,
+        None,
+        None,
+        None,
+        Some(&[CredentialType::Basic, CredentialType::X509]),
+    );
+
+    // A key package signed with the key of the end-entity certificate is valid.
+    let key_package = KeyPackage::builder()
+        .leaf_node_capabilities(capabilities.clone())
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            &provider,
+            &alice_signer,
+            CredentialWithKey {
+                credential: credential.clone(),
+                signature_key: alice_signer.to_public_vec().into(),
+            },
+        )
+        .expect("An unexpected error occurred.");
+    let key_package_in = KeyPackageIn::tls_deserialize(
+        &mut key_package.tls_serialize_detached().unwrap().as_slice(),
+    )
+    .unwrap();
+    let key_package_in = key_package_in
+        .validate(provider.crypto(), provider.time(), ProtocolVersion::Mls10)
+        .expect("Invalid key package.");
+    assert_eq!(key_package_in.leaf_node().credential(), &credential);
+
+    // A key package signed with another key is rejected.
+    let signer = SignatureKeyPair::new(ciphersuite.signature_algorithm()).unwrap();
+    let key_package = KeyPackage::builder()
+        .leaf_node_capabilities(capabilities)
+        .build(
+            CryptoConfig::with_default_version(ciphersuite),
+            &provider,
+            &signer,
+            CredentialWithKey {
+                credential,
+                signature_key: signer.to_public_vec().into(),
+            },
+        )
+        .expect("An unexpected error occurred.");
+    let err = KeyPackageIn::from(key_package)
+        .validate(provider.crypto(), provider.time(), ProtocolVersion::Mls10)
+        .unwrap_err();
+    assert_eq!(err, KeyPackageVerifyError::InvalidLeafNodeSignature);
+}
+
+/// Test that the key package manager keeps its pools filled, replaces key
+/// packages before they expire and revokes consumed key packages.
+#[apply(ciphersuites_and_providers)]
+fn key_package_manager(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
+    let (alice_credential_with_key, alice_signer) = test_utils::new_credential(
+        provider,
+        b"Alice",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let (bob_credential_with_key, bob_signer) = test_utils::new_credential(
+        provider,
+        b"Bob",
+        CredentialType::Basic,
+        ciphersuite.signature_algorithm(),
+    );
+    let crypto_config = CryptoConfig::with_default_version(ciphersuite);
+    let hash_ref = |key_package: &KeyPackage| key_package.hash_ref(provider.crypto()).unwrap();
+
+    // === Bob fills a pool of 3 key packages and publishes them ===
+    let mut manager = KeyPackageManager::new(bob_credential_with_key);
+    manager.set_target(crypto_config, 3);
+    assert_eq!(manager.target(ciphersuite), 3);
+    manager.replenish(provider, &bob_signer).unwrap();
+    assert_eq!(manager.key_packages(ciphersuite).count(), 3);
+
+    let diff = manager.diff();
+    assert_eq!(diff.to_publish().count(), 3);
+    assert!(diff.to_revoke().is_empty());
+    for key_package in diff.to_publish() {
+        assert!(provider
+            .key_store()
+            .read::<KeyPackage>(hash_ref(key_package).as_slice())
+            .is_some());
+    }
+    manager.confirm(&diff);
+    assert!(manager.diff().is_empty());
+
+    // === Alice adds Bob with one of his key packages ===
+    let bob_key_package = manager.key_packages(ciphersuite).next().unwrap().clone();
+    let mls_group_config = MlsGroupConfig::test_default(ciphersuite);
+    let mut alice_group = MlsGroup::new(
+        provider,
+        &alice_signer,
+        &mls_group_config,
+        alice_credential_with_key,
+    )
+    .unwrap();
+    let (_commit, welcome, _group_info) = alice_group
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package.clone()],
+            &AcceptAllCredentials,
+        )
+        .unwrap();
+    alice_group.merge_pending_commit(provider).unwrap();
+    let welcome = welcome.into_welcome().unwrap();
+
+    assert_eq!(
+        manager.process_welcome(&welcome),
+        Some(hash_ref(&bob_key_package))
+    );
+    assert_eq!(manager.key_packages(ciphersuite).count(), 2);
+    assert_eq!(manager.process_welcome(&welcome), None);
+    MlsGroup::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome,
+        Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
+    )
+    .unwrap();
+
+    // The consumed key package is revoked and replaced.
+    manager.replenish(provider, &bob_signer).unwrap();
+    let diff = manager.diff();
+    assert_eq!(diff.to_publish().count(), 1);
+    assert_eq!(diff.to_revoke(), &[hash_ref(&bob_key_package)]);
+    manager.confirm(&diff);
+    assert!(manager.diff().is_empty());
+
+    // === Shortly before the key packages expire, they are replaced ===
+    let published: Vec<_> = manager.key_packages(ciphersuite).map(hash_ref).collect();
+    let later = FixedTimeProvider::shifted(provider, 60 * 60 * 24 * 28 * 3 - 60 * 60 * 24);
+    manager.replenish(&later, &bob_signer).unwrap();
+    let diff = manager.diff();
+    assert_eq!(diff.to_publish().count(), 3);
+    assert_eq!(diff.to_revoke().len(), 3);
+    for key_package_ref in &published {
+        assert!(diff.to_revoke().contains(key_package_ref));
+        // Published key packages are kept until they expired.
+        assert!(provider
+            .key_store()
+            .read::<KeyPackage>(key_package_ref.as_slice())
+            .is_some());
+    }
+
+    // === Key packages that were never published are deleted right away ===
+    manager.set_target(crypto_config, 1);
+    manager.replenish(&later, &bob_signer).unwrap();
+    let remaining: Vec<_> = manager.key_packages(ciphersuite).map(hash_ref).collect();
+    assert_eq!(remaining.len(), 1);
+    for key_package in diff.to_publish() {
+        let key_package_ref = hash_ref(key_package);
+        assert_eq!(
+            provider
+                .key_store()
+                .read::<KeyPackage>(key_package_ref.as_slice())
+                .is_some(),
+            remaining.contains(&key_package_ref)
+        );
+    }
+    let diff = manager.diff();
+    assert_eq!(diff.to_publish().count(), 1);
+    assert_eq!(diff.to_revoke().len(), 3);
+}
@@ -97,7 +97,7 @@
 //! // The key package has to be retrieved from Maxim in some way. Most likely
 //! // via a server storing key packages for users.
 //! let (mls_message_out, welcome_out, group_info) = sasha_group
-//!     .add_members(provider, &sasha_signer, &[maxim_key_package])
+//!     .add_members(provider, &sasha_signer, &[maxim_key_package], &AcceptAllCredentials)
 //!     .expect("Could not add members.");
 //!
 //! // Sasha merges the pending commit that adds Maxim.
@@ -129,6 +129,9 @@
 //!     // The public tree is need and transferred out of band.
 //!     // It is also possible to use the [`RatchetTreeExtension`]
 //!     Some(sasha_group.export_ratchet_tree().into()),
+//!     // The credentials of the members should be checked by the
+//!     // Authentication Service of the application.
+//!     &AcceptAllCredentials,
 //!  )
 //!  .expect("Error joining group from Welcome");
 //! ```
@@ -180,6 +183,9 @@ pub mod versions;
 
 // Private
 mod binary_tree;
+#[cfg(feature = "async")]
+mod deferred;
+mod persistence;
 mod tree;
 
 /// Single place, re-exporting the most used public functions.
@@ -1,23 +1,26 @@
 //! External Proposals
 //!
-//! Contains the types and methods to build external proposal to add/remove a client from a MLS group
-//!
-//! `Add` (from external sender), `Remove` & `ReInit are not yet implemented`
+//! Contains the types and methods to build external proposals to add/remove a client, to inject a
+//! PSK, to reinitialize a MLS group or to change its group context extensions.
 
 use crate::{
     binary_tree::LeafNodeIndex,
-    extensions::SenderExtensionIndex,
+    extensions::{Extensions, SenderExtensionIndex},
     framing::{mls_auth_content::AuthenticatedContent, MlsMessageOut, PublicMessage},
     group::{
-        errors::ProposeRemoveMemberError, mls_group::errors::ProposeAddMemberError, GroupEpoch,
-        GroupId,
+        errors::{CreateExternalProposalError, ProposePskError, ProposeRemoveMemberError},
+        mls_group::errors::ProposeAddMemberError,
+        GroupEpoch, GroupId,
     },
     key_packages::KeyPackage,
     messages::{AddProposal, Proposal},
+    schedule::PreSharedKeyId,
 };
 use openmls_traits::signatures::Signer;
 
-use super::proposals::RemoveProposal;
+use super::proposals::{
+    GroupContextExtensionProposal, PreSharedKeyProposal, ReInitProposal, RemoveProposal,
+};
 
 /// External Add Proposal where sender is [NewMemberProposal](crate::prelude::Sender::NewMemberProposal). A client
 /// outside the group can request joining the group. This proposal should then be committed by a
@@ -26,7 +29,8 @@ use super::proposals::RemoveProposal;
 pub struct JoinProposal;
 
 /// External Proposal where sender is [External](crate::prelude::Sender::External). A party
-/// outside the group can request to add or remove a member to the group. This proposal should then
+/// outside the group can request to add or remove a member to the group, to inject a PSK, to
+/// reinitialize the group or to change its group context extensions. This proposal should then
 /// be committed by a group member. The sender must be pre configured within the group through the [crate::extensions::ExternalSendersExtension]
 pub struct ExternalProposal;
 
@@ -87,4 +91,122 @@ impl ExternalProposal {
         .map(MlsMessageOut::from)
         .map_err(ProposeRemoveMemberError::from)
     }
+
+    /// Creates an external Add proposal. For delivery services requesting to add a client.
+    /// This proposal will have to be committed later by a group member.
+    ///
+    /// # Arguments
+    /// * `key_package` - of the client to add
+    /// * `group_id` - unique group identifier of the group
+    /// * `epoch` - group's epoch
+    /// * `signer` - of the sender to sign the message
+    /// * `sender` - index of the sender of the proposal (in the [crate::extensions::ExternalSendersExtension] array
+    /// from the Group Context)
+    pub fn new_add(
+        key_package: KeyPackage,
+        group_id: GroupId,
+        epoch: GroupEpoch,
+        signer: &impl Signer,
+        sender_index: SenderExtensionIndex,
+    ) -> Result<MlsMessageOut, ProposeAddMemberError> {
+        AuthenticatedContent::new_external_proposal(
+            Proposal::Add(AddProposal { key_package }),
+            group_id,
+            epoch,
+            signer,
+            sender_index,
+        )
+        .map(PublicMessage::from)
+        .map(MlsMessageOut::from)
+        .map_err(ProposeAddMemberError::from)
+    }
+
+    /// Creates an external PreSharedKey proposal. For delivery services requesting to inject a
+    /// PSK into the key schedule of the group. This proposal will have to be committed later by a
+    /// group member.
+    ///
+    /// # Arguments
+    /// * `psk_id` - identifier of the PSK to inject
+    /// * `group_id` - unique group identifier of the group
+    /// * `epoch` - group's epoch
+    /// * `signer` - of the sender to sign the message
+    /// * `sender` - index of the sender of the proposal (in the [crate::extensions::ExternalSendersExtension] array
+    /// from the Group Context)
+    pub fn new_psk(
+        psk_id: PreSharedKeyId,
+        group_id: GroupId,
+        epoch: GroupEpoch,
+        signer: &impl Signer,
+        sender_index: SenderExtensionIndex,
+    ) -> Result<MlsMessageOut, ProposePskError> {
+        AuthenticatedContent::new_external_proposal(
+            Proposal::PreSharedKey(PreSharedKeyProposal::new(psk_id)),
+            group_id,
+            epoch,
+            signer,
+            sender_index,
+        )
+        .map(PublicMessage::from)
+        .map(MlsMessageOut::from)
+        .map_err(ProposePskError::from)
+    }
+
+    /// Creates an external ReInit proposal. For delivery services requesting to reinitialize the
+    /// group. This proposal will have to be committed later by a group member.
+    ///
+    /// # Arguments
+    /// * `reinit_proposal` - parameters of the successor group
+    /// * `group_id` - unique group identifier of the group
+    /// * `epoch` - group's epoch
+    /// * `signer` - of the sender to sign the message
+    /// * `sender` - index of the sender of the proposal (in the [crate::extensions::ExternalSendersExtension] array
+    /// from the Group Context)
+    pub fn new_reinit(
+        reinit_proposal: ReInitProposal,
+        group_id: GroupId,
+        epoch: GroupEpoch,
+        signer: &impl Signer,
+        sender_index: SenderExtensionIndex,
+    ) -> Result<MlsMessageOut, CreateExternalProposalError> {
+        AuthenticatedContent::new_external_proposal(
+            Proposal::ReInit(reinit_proposal),
+            group_id,
+            epoch,
+            signer,
+            sender_index,
+        )
+        .map(PublicMessage::from)
+        .map(MlsMessageOut::from)
+        .map_err(CreateExternalProposalError::from)
+    }
+
+    /// Creates an external GroupContextExtensions proposal. For delivery services requesting to
+    /// change the extensions of the group context. This proposal will have to be committed later
+    /// by a group member.
+    ///
+    /// # Arguments
+    /// * `extensions` - the new group context extensions
+    /// * `group_id` - unique group identifier of the group
+    /// * `epoch` - group's epoch
+    /// * `signer` - of the sender to sign the message
+    /// * `sender` - index of the sender of the proposal (in the [crate::extensions::ExternalSendersExtension] array
+    /// from the Group Context)
+    pub fn new_group_context_extensions(
+        extensions: Extensions,
+        group_id: GroupId,
+        epoch: GroupEpoch,
+        signer: &impl Signer,
+        sender_index: SenderExtensionIndex,
+    ) -> Result<MlsMessageOut, CreateExternalProposalError> {
+        AuthenticatedContent::new_external_proposal(
+            Proposal::GroupContextExtensions(GroupContextExtensionProposal::new(extensions)),
+            group_id,
+            epoch,
+            signer,
+            sender_index,
+        )
+        .map(PublicMessage::from)
+        .map(MlsMessageOut::from)
+        .map_err(CreateExternalProposalError::from)
+    }
 }
@@ -5,8 +5,10 @@
 
 use openmls_traits::{
     crypto::OpenMlsCrypto,
+    time::OpenMlsTime,
     types::{Ciphersuite, HpkeCiphertext, HpkeKeyPair},
 };
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tls_codec::{Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, *};
@@ -193,13 +195,16 @@ impl CommitIn {
         self,
         ciphersuite: Ciphersuite,
         crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
         sender_context: SenderContext,
         protocol_version: ProtocolVersion,
     ) -> Result<Commit, ValidationError> {
+        // The proposals, e.g., the key packages of Add proposals, are
+        // verified in parallel.
         let proposals = self
             .proposals
-            .into_iter()
-            .map(|p| p.validate(crypto, ciphersuite, protocol_version))
+            .into_par_iter()
+            .map(|p| p.validate(crypto, time, ciphersuite, protocol_version))
             .collect::<Result<Vec<_>, _>>()?;
 
         let path = if let Some(path) = self.path {
@@ -10,11 +10,14 @@ use std::io::{Read, Write};
 use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tls_codec::{Serialize as TlsSerializeTrait, TlsDeserialize, TlsSerialize, TlsSize, VLBytes};
+use tls_codec::{
+    Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, Size, TlsDeserialize,
+    TlsSerialize, TlsSize, VLByteSlice, VLBytes,
+};
 
 use crate::{
     binary_tree::array_representation::LeafNodeIndex,
-    ciphersuite::hash_ref::{make_proposal_ref, KeyPackageRef, ProposalRef},
+    ciphersuite::hash_ref::{make_proposal_ref, ProposalRef},
     error::LibraryError,
     extensions::Extensions,
     framing::{
@@ -22,6 +25,7 @@ use crate::{
     },
     group::GroupId,
     key_packages::*,
+    persistence::Persist,
     prelude::LeafNode,
     schedule::psk::*,
     versions::ProtocolVersion,
@@ -121,14 +125,22 @@ impl ProposalType {
                 | ProposalType::Reinit
                 | ProposalType::ExternalInit
                 | ProposalType::GroupContextExtensions
+                | ProposalType::AppAck
         )
     }
 
     /// Returns `true` if the proposal type requires a path and `false`
+    /// otherwise. Unknown proposal types, i.e. custom proposals, always
+    /// require a path.
     pub fn is_path_required(&self) -> bool {
         matches!(
             self,
-            Self::Update | Self::Remove | Self::ExternalInit | Self::GroupContextExtensions
+            Self::Update
+                | Self::Remove
+                | Self::ExternalInit
+                | Self::GroupContextExtensions
+                | Self::AppAck
+                | Self::Unknown(_)
         )
     }
 }
@@ -181,33 +193,63 @@ impl From<ProposalType> for u16 {
 ///         case reinit:                   ReInit;
 ///         case external_init:            ExternalInit;
 ///         case group_context_extensions: GroupContextExtensions;
+///         case app_ack:                  AppAck;
+///         // All other proposal types
+///         default:                       opaque payload<V>;
 ///     };
 /// } Proposal;
 /// ```
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, TlsSize, TlsSerialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[allow(missing_docs)]
-#[repr(u16)]
 pub enum Proposal {
-    #[tls_codec(discriminant = 1)]
     Add(AddProposal),
-    #[tls_codec(discriminant = 2)]
     Update(UpdateProposal),
-    #[tls_codec(discriminant = 3)]
     Remove(RemoveProposal),
-    #[tls_codec(discriminant = 4)]
     PreSharedKey(PreSharedKeyProposal),
-    #[tls_codec(discriminant = 5)]
     ReInit(ReInitProposal),
-    #[tls_codec(discriminant = 6)]
     ExternalInit(ExternalInitProposal),
-    #[tls_codec(discriminant = 7)]
     GroupContextExtensions(GroupContextExtensionProposal),
     // # Extensions
     // TODO(#916): `AppAck` is not in draft-ietf-mls-protocol-17 but
     //             was moved to `draft-ietf-mls-extensions-00`.
-    #[tls_codec(discriminant = 8)]
     AppAck(AppAckProposal),
+    Custom(CustomProposal),
+}
+
+impl Size for Proposal {
+    fn tls_serialized_len(&self) -> usize {
+        let proposal_len = match self {
+            Proposal::Add(p) => p.tls_serialized_len(),
+            Proposal::Update(p) => p.tls_serialized_len(),
+            Proposal::Remove(p) => p.tls_serialized_len(),
+            Proposal::PreSharedKey(p) => p.tls_serialized_len(),
+            Proposal::ReInit(p) => p.tls_serialized_len(),
+            Proposal::ExternalInit(p) => p.tls_serialized_len(),
+            Proposal::GroupContextExtensions(p) => p.tls_serialized_len(),
+            Proposal::AppAck(p) => p.tls_serialized_len(),
+            Proposal::Custom(p) => VLByteSlice(p.payload()).tls_serialized_len(),
+        };
+        self.proposal_type().tls_serialized_len() + proposal_len
+    }
+}
+
+impl TlsSerializeTrait for Proposal {
+    fn tls_serialize<W: Write>(&self, writer: &mut W) -> Result<usize, tls_codec::Error> {
+        let written = self.proposal_type().tls_serialize(writer)?;
+        match self {
+            Proposal::Add(p) => p.tls_serialize(writer),
+            Proposal::Update(p) => p.tls_serialize(writer),
+            Proposal::Remove(p) => p.tls_serialize(writer),
+            Proposal::PreSharedKey(p) => p.tls_serialize(writer),
+            Proposal::ReInit(p) => p.tls_serialize(writer),
+            Proposal::ExternalInit(p) => p.tls_serialize(writer),
+            Proposal::GroupContextExtensions(p) => p.tls_serialize(writer),
+            Proposal::AppAck(p) => p.tls_serialize(writer),
+            Proposal::Custom(p) => VLByteSlice(p.payload()).tls_serialize(writer),
+        }
+        .map(|l| l + written)
+    }
 }
 
 impl Proposal {
@@ -222,6 +264,7 @@ impl Proposal {
             Proposal::ExternalInit(_) => ProposalType::ExternalInit,
             Proposal::GroupContextExtensions(_) => ProposalType::GroupContextExtensions,
             Proposal::AppAck(_) => ProposalType::AppAck,
+            Proposal::Custom(custom) => ProposalType::Unknown(custom.proposal_type()),
         }
     }
 
@@ -361,6 +404,43 @@ pub struct ReInitProposal {
     pub(crate) extensions: Extensions,
 }
 
+impl ReInitProposal {
+    /// Create a new ReInit proposal.
+    pub fn new(
+        group_id: GroupId,
+        version: ProtocolVersion,
+        ciphersuite: Ciphersuite,
+        extensions: Extensions,
+    ) -> Self {
+        Self {
+            group_id,
+            version,
+            ciphersuite,
+            extensions,
+        }
+    }
+
+    /// Returns the group ID of the new group.
+    pub fn group_id(&self) -> &GroupId {
+        &self.group_id
+    }
+
+    /// Returns the protocol version of the new group.
+    pub fn version(&self) -> ProtocolVersion {
+        self.version
+    }
+
+    /// Returns the ciphersuite of the new group.
+    pub fn ciphersuite(&self) -> Ciphersuite {
+        self.ciphersuite
+    }
+
+    /// Returns the group context extensions of the new group.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+}
+
 /// ExternalInit Proposal.
 ///
 /// An ExternalInit proposal is used by new members that want to join a group by using an external
@@ -394,11 +474,19 @@ impl From<Vec<u8>> for ExternalInitProposal {
     }
 }
 
-// TODO: #291 Implement AppAck
-
 /// AppAck Proposal.
 ///
-/// This is not yet supported.
+/// An AppAck proposal is used to acknowledge the application messages a
+/// member received in the current epoch. Comparing it with the messages that
+/// were sent allows detecting messages that were dropped or withheld by the
+/// Delivery Service.
+///
+/// ```c
+/// // draft-ietf-mls-extensions-00
+/// struct {
+///     MessageRange received_ranges<V>;
+/// } AppAck;
+/// ```
 #[derive(
     Debug, PartialEq, Clone, Serialize, Deserialize, TlsDeserialize, TlsSerialize, TlsSize,
 )]
@@ -406,6 +494,26 @@ pub struct AppAckProposal {
     received_ranges: Vec<MessageRange>,
 }
 
+impl AppAckProposal {
+    /// Create a new [`AppAckProposal`] from the given message ranges.
+    pub(crate) fn new(received_ranges: Vec<MessageRange>) -> Self {
+        Self { received_ranges }
+    }
+
+    /// Returns the ranges of application messages that were acknowledged.
+    pub fn received_ranges(&self) -> &[MessageRange] {
+        self.received_ranges.as_slice()
+    }
+
+    /// Returns `true` if the application message with the given generation
+    /// from the given sender was acknowledged and `false` otherwise.
+    pub fn acknowledges(&self, sender: LeafNodeIndex, generation: u32) -> bool {
+        self.received_ranges
+            .iter()
+            .any(|range| range.sender() == sender && range.contains(generation))
+    }
+}
+
 /// GroupContextExtensions Proposal.
 ///
 /// A GroupContextExtensions proposal is used to update the list of extensions in the GroupContext
@@ -426,10 +534,62 @@ pub struct GroupContextExtensionProposal {
 
 impl GroupContextExtensionProposal {
     /// Create a new [`GroupContextExtensionProposal`].
-    #[cfg(test)]
     pub(crate) fn new(extensions: Extensions) -> Self {
         Self { extensions }
     }
+
+    /// Returns the new [`Extensions`] of the group context.
+    pub(crate) fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+}
+
+/// Custom Proposal.
+///
+/// A custom proposal carries an opaque payload for a proposal type that is
+/// defined by the application, e.g. to agree on the name or the admins of a
+/// group. Its proposal type must not be one of the types defined by MLS and
+/// all members of the group must list it in the `proposals` of their
+/// [`Capabilities`](crate::treesync::node::leaf_node::Capabilities).
+///
+/// ```c
+/// struct {
+///     opaque payload<V>;
+/// } CustomProposal;
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct CustomProposal {
+    proposal_type: u16,
+    payload: Vec<u8>,
+}
+
+impl CustomProposal {
+    /// Create a new [`CustomProposal`] of the given proposal type.
+    pub fn new(proposal_type: u16, payload: Vec<u8>) -> Self {
+        Self {
+            proposal_type,
+            payload,
+        }
+    }
+
+    /// Returns the proposal type of the custom proposal.
+    pub fn proposal_type(&self) -> u16 {
+        self.proposal_type
+    }
+
+    /// Returns the payload of the custom proposal.
+    pub fn payload(&self) -> &[u8] {
+        self.payload.as_slice()
+    }
+
+    /// Returns `true` if the proposal type is not one of the proposal types
+    /// defined by MLS and `false` otherwise.
+    pub(crate) fn has_custom_type(&self) -> bool {
+        matches!(
+            ProposalType::from(self.proposal_type),
+            ProposalType::Unknown(_)
+        )
+    }
 }
 
 // Crate-only types
@@ -528,10 +688,14 @@ impl ProposalRef {
     }
 }
 
-/// TODO: #291 Implement AppAck
+/// A range of application messages of one sender in an [`AppAckProposal`].
+///
+/// The sender is identified by its leaf index and the generations are
+/// inclusive.
+///
 /// ```text
 /// struct {
-///     KeyPackageRef sender;
+///     uint32 sender;
 ///     uint32 first_generation;
 ///     uint32 last_generation;
 /// } MessageRange;
@@ -539,12 +703,79 @@ impl ProposalRef {
 #[derive(
     Debug, PartialEq, Clone, Serialize, Deserialize, TlsDeserialize, TlsSerialize, TlsSize,
 )]
-pub(crate) struct MessageRange {
-    sender: KeyPackageRef,
+pub struct MessageRange {
+    sender: LeafNodeIndex,
     first_generation: u32,
     last_generation: u32,
 }
 
+impl MessageRange {
+    /// Create a new [`MessageRange`].
+    pub(crate) fn new(sender: LeafNodeIndex, first_generation: u32, last_generation: u32) -> Self {
+        Self {
+            sender,
+            first_generation,
+            last_generation,
+        }
+    }
+
+    /// Returns the leaf index of the sender of the messages.
+    pub fn sender(&self) -> LeafNodeIndex {
+        self.sender
+    }
+
+    /// Returns the generation of the first message in the range.
+    pub fn first_generation(&self) -> u32 {
+        self.first_generation
+    }
+
+    /// Returns the generation of the last message in the range.
+    pub fn last_generation(&self) -> u32 {
+        self.last_generation
+    }
+
+    /// Returns `true` if the given generation is part of the range.
+    pub fn contains(&self, generation: u32) -> bool {
+        (self.first_generation..=self.last_generation).contains(&generation)
+    }
+}
+
+/// Proposals in the group state have been validated before. They are therefore
+/// restored without validating them again.
+impl Persist for Proposal {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.tls_serialize(writer).map(|_| ())
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        let proposal = match ProposalType::tls_deserialize(bytes)? {
+            ProposalType::Add => Proposal::Add(AddProposal {
+                key_package: KeyPackage::restore(bytes)?,
+            }),
+            ProposalType::Update => Proposal::Update(UpdateProposal {
+                leaf_node: LeafNode::restore(bytes)?,
+            }),
+            ProposalType::Remove => Proposal::Remove(RemoveProposal::tls_deserialize(bytes)?),
+            ProposalType::PreSharedKey => {
+                Proposal::PreSharedKey(PreSharedKeyProposal::tls_deserialize(bytes)?)
+            }
+            ProposalType::Reinit => Proposal::ReInit(ReInitProposal::tls_deserialize(bytes)?),
+            ProposalType::ExternalInit => {
+                Proposal::ExternalInit(ExternalInitProposal::tls_deserialize(bytes)?)
+            }
+            ProposalType::GroupContextExtensions => Proposal::GroupContextExtensions(
+                GroupContextExtensionProposal::tls_deserialize(bytes)?,
+            ),
+            ProposalType::AppAck => Proposal::AppAck(AppAckProposal::tls_deserialize(bytes)?),
+            ProposalType::Unknown(proposal_type) => Proposal::Custom(CustomProposal::new(
+                proposal_type,
+                VLBytes::tls_deserialize(bytes)?.as_slice().to_vec(),
+            )),
+        };
+        Ok(proposal)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use tls_codec::{Deserialize, Serialize};
@@ -15,14 +15,19 @@ use crate::{
     versions::ProtocolVersion,
 };
 
-use openmls_traits::{crypto::OpenMlsCrypto, types::Ciphersuite};
+use std::io::{Read, Write};
+
+use openmls_traits::{crypto::OpenMlsCrypto, time::OpenMlsTime, types::Ciphersuite};
 use serde::{Deserialize, Serialize};
-use tls_codec::{TlsDeserialize, TlsSerialize, TlsSize};
+use tls_codec::{
+    Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, Size, TlsDeserialize,
+    TlsSerialize, TlsSize, VLByteSlice, VLBytes,
+};
 
 use super::proposals::{
-    AddProposal, AppAckProposal, ExternalInitProposal, GroupContextExtensionProposal,
-    PreSharedKeyProposal, Proposal, ProposalOrRef, ProposalType, ReInitProposal, RemoveProposal,
-    UpdateProposal,
+    AddProposal, AppAckProposal, CustomProposal, ExternalInitProposal,
+    GroupContextExtensionProposal, PreSharedKeyProposal, Proposal, ProposalOrRef, ProposalType,
+    ReInitProposal, RemoveProposal, UpdateProposal,
 };
 
 /// Proposal.
@@ -41,35 +46,92 @@ use super::proposals::{
 ///         case reinit:                   ReInit;
 ///         case external_init:            ExternalInit;
 ///         case group_context_extensions: GroupContextExtensions;
+///         case app_ack:                  AppAck;
+///         // All other proposal types
+///         default:                       opaque payload<V>;
 ///     };
 /// } Proposal;
 /// ```
 #[allow(clippy::large_enum_variant)]
-#[derive(
-    Debug, PartialEq, Clone, Serialize, Deserialize, TlsSize, TlsSerialize, TlsDeserialize,
-)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[allow(missing_docs)]
-#[repr(u16)]
 pub enum ProposalIn {
-    #[tls_codec(discriminant = 1)]
     Add(AddProposalIn),
-    #[tls_codec(discriminant = 2)]
     Update(UpdateProposalIn),
-    #[tls_codec(discriminant = 3)]
     Remove(RemoveProposal),
-    #[tls_codec(discriminant = 4)]
     PreSharedKey(PreSharedKeyProposal),
-    #[tls_codec(discriminant = 5)]
     ReInit(ReInitProposal),
-    #[tls_codec(discriminant = 6)]
     ExternalInit(ExternalInitProposal),
-    #[tls_codec(discriminant = 7)]
     GroupContextExtensions(GroupContextExtensionProposal),
     // # Extensions
     // TODO(#916): `AppAck` is not in draft-ietf-mls-protocol-17 but
     //             was moved to `draft-ietf-mls-extensions-00`.
-    #[tls_codec(discriminant = 8)]
     AppAck(AppAckProposal),
+    Custom(CustomProposal),
+}
+
+impl Size for ProposalIn {
+    fn tls_serialized_len(&self) -> usize {
+        let proposal_len = match self {
+            ProposalIn::Add(p) => p.tls_serialized_len(),
+            ProposalIn::Update(p) => p.tls_serialized_len(),
+            ProposalIn::Remove(p) => p.tls_serialized_len(),
+            ProposalIn::PreSharedKey(p) => p.tls_serialized_len(),
+            ProposalIn::ReInit(p) => p.tls_serialized_len(),
+            ProposalIn::ExternalInit(p) => p.tls_serialized_len(),
+            ProposalIn::GroupContextExtensions(p) => p.tls_serialized_len(),
+            ProposalIn::AppAck(p) => p.tls_serialized_len(),
+            ProposalIn::Custom(p) => VLByteSlice(p.payload()).tls_serialized_len(),
+        };
+        self.proposal_type().tls_serialized_len() + proposal_len
+    }
+}
+
+impl TlsSerializeTrait for ProposalIn {
+    fn tls_serialize<W: Write>(&self, writer: &mut W) -> Result<usize, tls_codec::Error> {
+        let written = self.proposal_type().tls_serialize(writer)?;
+        match self {
+            ProposalIn::Add(p) => p.tls_serialize(writer),
+            ProposalIn::Update(p) => p.tls_serialize(writer),
+            ProposalIn::Remove(p) => p.tls_serialize(writer),
+            ProposalIn::PreSharedKey(p) => p.tls_serialize(writer),
+            ProposalIn::ReInit(p) => p.tls_serialize(writer),
+            ProposalIn::ExternalInit(p) => p.tls_serialize(writer),
+            ProposalIn::GroupContextExtensions(p) => p.tls_serialize(writer),
+            ProposalIn::AppAck(p) => p.tls_serialize(writer),
+            ProposalIn::Custom(p) => VLByteSlice(p.payload()).tls_serialize(writer),
+        }
+        .map(|l| l + written)
+    }
+}
+
+impl TlsDeserializeTrait for ProposalIn {
+    fn tls_deserialize<R: Read>(bytes: &mut R) -> Result<Self, tls_codec::Error> {
+        let proposal_type = ProposalType::tls_deserialize(bytes)?;
+        Ok(match proposal_type {
+            ProposalType::Add => ProposalIn::Add(AddProposalIn::tls_deserialize(bytes)?),
+            ProposalType::Update => ProposalIn::Update(UpdateProposalIn::tls_deserialize(bytes)?),
+            ProposalType::Remove => ProposalIn::Remove(RemoveProposal::tls_deserialize(bytes)?),
+            ProposalType::PreSharedKey => {
+                ProposalIn::PreSharedKey(PreSharedKeyProposal::tls_deserialize(bytes)?)
+            }
+            ProposalType::Reinit => ProposalIn::ReInit(ReInitProposal::tls_deserialize(bytes)?),
+            ProposalType::ExternalInit => {
+                ProposalIn::ExternalInit(ExternalInitProposal::tls_deserialize(bytes)?)
+            }
+            ProposalType::GroupContextExtensions => ProposalIn::GroupContextExtensions(
+                GroupContextExtensionProposal::tls_deserialize(bytes)?,
+            ),
+            ProposalType::AppAck => ProposalIn::AppAck(AppAckProposal::tls_deserialize(bytes)?),
+            ProposalType::Unknown(proposal_type) => {
+                let payload = VLBytes::tls_deserialize(bytes)?;
+                ProposalIn::Custom(CustomProposal::new(
+                    proposal_type,
+                    payload.as_slice().to_vec(),
+                ))
+            }
+        })
+    }
 }
 
 impl ProposalIn {
@@ -84,6 +146,7 @@ impl ProposalIn {
             ProposalIn::ExternalInit(_) => ProposalType::ExternalInit,
             ProposalIn::GroupContextExtensions(_) => ProposalType::GroupContextExtensions,
             ProposalIn::AppAck(_) => ProposalType::AppAck,
+            ProposalIn::Custom(custom) => ProposalType::Unknown(custom.proposal_type()),
         }
     }
 
@@ -96,13 +159,14 @@ impl ProposalIn {
     pub(crate) fn validate(
         self,
         crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
         ciphersuite: Ciphersuite,
         sender_context: Option<SenderContext>,
         protocol_version: ProtocolVersion,
     ) -> Result<Proposal, ValidationError> {
         Ok(match self {
             ProposalIn::Add(add) => {
-                Proposal::Add(add.validate(crypto, protocol_version, ciphersuite)?)
+                Proposal::Add(add.validate(crypto, time, protocol_version, ciphersuite)?)
             }
             ProposalIn::Update(update) => {
                 let sender_context =
@@ -117,6 +181,7 @@ impl ProposalIn {
                 Proposal::GroupContextExtensions(group_context_extension)
             }
             ProposalIn::AppAck(app_ack) => Proposal::AppAck(app_ack),
+            ProposalIn::Custom(custom) => Proposal::Custom(custom),
         })
     }
 }
@@ -147,10 +212,11 @@ impl AddProposalIn {
     pub(crate) fn validate(
         self,
         crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
         protocol_version: ProtocolVersion,
         ciphersuite: Ciphersuite,
     ) -> Result<AddProposal, ValidationError> {
-        let key_package = self.key_package.validate(crypto, protocol_version)?;
+        let key_package = self.key_package.validate(crypto, time, protocol_version)?;
         // Verify that the ciphersuite is valid
         if key_package.ciphersuite() != ciphersuite {
             return Err(ValidationError::InvalidAddProposalCiphersuite);
@@ -230,12 +296,13 @@ impl ProposalOrRefIn {
     pub(crate) fn validate(
         self,
         crypto: &impl OpenMlsCrypto,
+        time: &dyn OpenMlsTime,
         ciphersuite: Ciphersuite,
         protocol_version: ProtocolVersion,
     ) -> Result<ProposalOrRef, ValidationError> {
         Ok(match self {
             ProposalOrRefIn::Proposal(proposal_in) => ProposalOrRef::Proposal(
-                proposal_in.validate(crypto, ciphersuite, None, protocol_version)?,
+                proposal_in.validate(crypto, time, ciphersuite, None, protocol_version)?,
             ),
             ProposalOrRefIn::Reference(reference) => ProposalOrRef::Reference(reference),
         })
@@ -294,6 +361,7 @@ impl From<ProposalIn> for crate::messages::proposals::Proposal {
                 Self::GroupContextExtensions(group_context_extension)
             }
             ProposalIn::AppAck(app_ack) => Self::AppAck(app_ack),
+            ProposalIn::Custom(custom) => Self::Custom(custom),
         }
     }
 }
@@ -311,6 +379,7 @@ impl From<crate::messages::proposals::Proposal> for ProposalIn {
                 Self::GroupContextExtensions(group_context_extension)
             }
             Proposal::AppAck(app_ack) => Self::AppAck(app_ack),
+            Proposal::Custom(custom) => Self::Custom(custom),
         }
     }
 }
@@ -4,7 +4,11 @@ use tls_codec::{Deserialize, Serialize};
 use crate::{
     extensions::Extensions,
     group::GroupId,
-    messages::{PreSharedKeyProposal, ProtocolVersion, ReInitProposal},
+    messages::{
+        proposals::{CustomProposal, Proposal},
+        proposals_in::ProposalIn,
+        PreSharedKeyProposal, ProtocolVersion, ReInitProposal,
+    },
     schedule::psk::{ExternalPsk, PreSharedKeyId, Psk, ResumptionPsk, ResumptionPskUsage},
     test_utils::*,
 };
@@ -94,3 +98,16 @@ fn test_reinit_proposal_codec(ciphersuite: Ciphersuite, provider: &impl OpenMlsP
         .expect("An unexpected error occurred.");
     assert_eq!(decoded, orig);
 }
+
+/// Test the encoding for custom proposals
+#[test]
+fn test_custom_proposal_codec() {
+    let orig = Proposal::Custom(CustomProposal::new(0xff00, vec![1, 2, 3]));
+    let encoded = orig
+        .tls_serialize_detached()
+        .expect("An unexpected error occurred.");
+    assert_eq!(encoded, vec![0xff, 0x00, 0x03, 1, 2, 3]);
+    let decoded = ProposalIn::tls_deserialize(&mut encoded.as_slice())
+        .expect("An unexpected error occurred.");
+    assert_eq!(decoded, ProposalIn::from(orig));
+}
@@ -12,6 +12,7 @@ use crate::{
     ciphersuite::{
         hash_ref::KeyPackageRef, hpke, signable::Signable, AeadKey, AeadNonce, Mac, Secret,
     },
+    credentials::AcceptAllCredentials,
     extensions::Extensions,
     group::{
         config::CryptoConfig, errors::WelcomeError, GroupContext, GroupId, MlsGroup,
@@ -71,7 +72,12 @@ fn test_welcome_context_mismatch(ciphersuite: Ciphersuite, provider: &impl OpenM
     .expect("An unexpected error occurred.");
 
     let (_queued_message, welcome, _group_info) = alice_group
-        .add_members(provider, &alice_signer, &[bob_kp.clone()])
+        .add_members(
+            provider,
+            &alice_signer,
+            &[bob_kp.clone()],
+            &AcceptAllCredentials,
+        )
         .expect("Could not add member to group.");
 
     alice_group
@@ -166,6 +172,7 @@ fn test_welcome_context_mismatch(ciphersuite: Ciphersuite, provider: &impl OpenM
         &mls_group_config,
         welcome,
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect_err("Created a group from an invalid Welcome.");
 
@@ -199,6 +206,7 @@ fn test_welcome_context_mismatch(ciphersuite: Ciphersuite, provider: &impl OpenM
         &mls_group_config,
         original_welcome,
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("Error creating group from a valid Welcome.");
 }
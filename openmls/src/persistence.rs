@@ -0,0 +1,329 @@
+//! # Persistence of group state
+//!
+//! This module defines the binary format in which OpenMLS persists the state
+//! of groups, e.g. of an [`MlsGroup`](crate::group::MlsGroup) or a
+//! [`PublicGroup`](crate::group::PublicGroup).
+//!
+//! The state is encoded with the TLS presentation language and wrapped in an
+//! envelope that carries the format version:
+//!
+//! ```text
+//! struct {
+//!     uint16 format_version;
+//!     opaque state<V>;
+//! } PersistedState;
+//! ```
+//!
+//! In contrast to the encoding of MLS messages, the encoding of the state is
+//! not defined by the MLS specification and may change between releases of
+//! OpenMLS. Each such change increases [`STATE_FORMAT_VERSION`] and comes with
+//! a migration that converts states of the previous format version, such that
+//! states persisted by older releases can always be loaded.
+//!
+//! When an [`MlsGroup`](crate::group::MlsGroup) is saved to a key store, its
+//! state is split into components that are persisted independently, such that
+//! only the components that changed are written again. Each component is
+//! wrapped in the same envelope and has its own migrations.
+//!
+//! Types that are part of the persisted state implement the crate-internal
+//! [`Persist`] trait. For types that are sent over the wire, this is simply
+//! their TLS encoding. The other types define their encoding where they are
+//! defined, since they usually can't be constructed from the outside.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use tls_codec::{
+    Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, VLByteSlice, VLBytes,
+};
+
+use crate::{error::LibraryError, group::errors::PersistenceError};
+
+/// The version of the format in which the group state is persisted.
+///
+/// - Version 1 is the initial format.
+/// - Version 2 adds the lifetime policy to the configuration of an
+///   [`MlsGroup`](crate::group::MlsGroup).
+/// - Version 3 replaces the absolute lifetime in the configuration of an
+///   [`MlsGroup`](crate::group::MlsGroup) with a duration.
+/// - Version 4 adds the capabilities of the own leaf node to the
+///   configuration of an [`MlsGroup`](crate::group::MlsGroup).
+pub const STATE_FORMAT_VERSION: u16 = 4;
+
+/// A migration of an encoded state from one format version to the next.
+pub(crate) type Migration = fn(Vec<u8>) -> Result<Vec<u8>, tls_codec::Error>;
+
+/// The migration of a state whose encoding didn't change.
+pub(crate) fn unchanged(state: Vec<u8>) -> Result<Vec<u8>, tls_codec::Error> {
+    Ok(state)
+}
+
+/// A value that is part of the persisted state of a group.
+///
+/// Implementations must never change the encoding of a released format
+/// version. See the module documentation for how the format is changed.
+pub(crate) trait Persist: Sized {
+    /// Append the encoding of this value to `writer`.
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error>;
+
+    /// Decode a value from the beginning of `bytes` and advance `bytes` to the
+    /// end of the value.
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error>;
+}
+
+/// The persisted state of a group, which is wrapped in the versioned envelope.
+pub(crate) trait PersistedState: Persist {
+    /// The migrations of this state. The migration at index `i` migrates an
+    /// encoded state from format version `i + 1` to version `i + 2`.
+    ///
+    /// A state type must have one migration per format version after the
+    /// first, even if its own encoding didn't change.
+    const MIGRATIONS: &'static [Migration];
+
+    /// Encode the state in the current format version.
+    fn to_persisted_state(&self) -> Result<Vec<u8>, LibraryError> {
+        let mut state = Vec::new();
+        self.persist(&mut state)
+            .map_err(LibraryError::missing_bound_check)?;
+
+        wrap_state(&state)
+    }
+
+    /// Decode a state that was persisted in the current or any previous
+    /// format version.
+    fn from_persisted_state(bytes: &[u8]) -> Result<Self, PersistenceError> {
+        let state = unwrap_state(bytes, Self::MIGRATIONS)?;
+        Ok(restore_exact(&state, Self::restore)?)
+    }
+}
+
+/// Wrap an encoded state of the current format version in the versioned
+/// envelope.
+pub(crate) fn wrap_state(state: &[u8]) -> Result<Vec<u8>, LibraryError> {
+    let mut bytes = Vec::with_capacity(state.len() + 8);
+    STATE_FORMAT_VERSION
+        .tls_serialize(&mut bytes)
+        .and_then(|_| VLByteSlice(state).tls_serialize(&mut bytes))
+        .map_err(LibraryError::missing_bound_check)?;
+
+    Ok(bytes)
+}
+
+/// Unwrap an encoded state from the versioned envelope and migrate it to the
+/// current format version with the given `migrations`.
+pub(crate) fn unwrap_state(
+    mut bytes: &[u8],
+    migrations: &[Migration],
+) -> Result<Vec<u8>, PersistenceError> {
+    let format_version = u16::tls_deserialize(&mut bytes)?;
+    if format_version == 0 || format_version > STATE_FORMAT_VERSION {
+        return Err(PersistenceError::UnsupportedFormatVersion(format_version));
+    }
+    let mut state = VLBytes::tls_deserialize(&mut bytes)?.as_slice().to_vec();
+    if !bytes.is_empty() {
+        return Err(trailing_data().into());
+    }
+
+    let migrations = migrations
+        .get(format_version as usize - 1..)
+        .ok_or_else(|| LibraryError::custom("Missing state migration"))?;
+    for migration in migrations {
+        state = migration(state)?;
+    }
+
+    Ok(state)
+}
+
+/// Decode a value with `restore` that must span all of `bytes`.
+pub(crate) fn restore_exact<T, E: From<tls_codec::Error>>(
+    mut bytes: &[u8],
+    restore: impl FnOnce(&mut &[u8]) -> Result<T, E>,
+) -> Result<T, E> {
+    let value = restore(&mut bytes)?;
+    if !bytes.is_empty() {
+        return Err(trailing_data().into());
+    }
+
+    Ok(value)
+}
+
+fn trailing_data() -> tls_codec::Error {
+    tls_codec::Error::DecodingError("Unexpected data after the persisted state".into())
+}
+
+/// Implement [`Persist`] for types with a TLS encoding.
+macro_rules! impl_persist_with_tls_codec {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl $crate::persistence::Persist for $ty {
+                fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+                    tls_codec::Serialize::tls_serialize(self, writer).map(|_| ())
+                }
+
+                fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+                    <Self as tls_codec::Deserialize>::tls_deserialize(bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_persist_with_tls_codec!(
+    u8,
+    u16,
+    u32,
+    u64,
+    tls_codec::VLBytes,
+    openmls_traits::types::Ciphersuite,
+    crate::versions::ProtocolVersion,
+    crate::binary_tree::array_representation::LeafNodeIndex,
+    crate::ciphersuite::hash_ref::HashReference,
+    crate::ciphersuite::signature::Signature,
+    crate::credentials::Credential,
+    crate::extensions::Extensions,
+    crate::extensions::ExternalSender,
+    crate::extensions::RequiredCapabilitiesExtension,
+    crate::framing::Sender,
+    crate::group::GroupContext,
+    crate::group::GroupEpoch,
+    crate::key_packages::Lifetime,
+    crate::messages::ConfirmationTag,
+    crate::messages::proposals::ProposalOrRefType,
+    crate::messages::proposals::ReInitProposal,
+    crate::treesync::node::encryption_keys::EncryptionKeyPair,
+    crate::treesync::node::leaf_node::Capabilities,
+    crate::treesync::node::parent_node::ParentNode,
+);
+
+impl Persist for bool {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        u8::from(*self).persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        match u8::restore(bytes)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(tls_codec::Error::DecodingError(format!(
+                "{value} is not a valid boolean"
+            ))),
+        }
+    }
+}
+
+/// `usize` values are encoded as `uint64` to be independent of the platform.
+impl Persist for usize {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        (*self as u64).persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        let value = u64::restore(bytes)?;
+        usize::try_from(value).map_err(|_| {
+            tls_codec::Error::DecodingError(format!("{value} exceeds the platform's usize"))
+        })
+    }
+}
+
+/// Optional values are encoded like `optional<T>` in the TLS presentation
+/// language.
+impl<T: Persist> Persist for Option<T> {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        match self {
+            Some(value) => {
+                true.persist(writer)?;
+                value.persist(writer)
+            }
+            None => false.persist(writer),
+        }
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        if bool::restore(bytes)? {
+            T::restore(bytes).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Sequences are encoded like `T values<V>` in the TLS presentation language.
+/// In particular, `Vec<u8>` is encoded like `opaque values<V>`.
+pub(crate) fn persist_sequence<'a, T: Persist + 'a>(
+    values: impl Iterator<Item = &'a T>,
+    writer: &mut Vec<u8>,
+) -> Result<(), tls_codec::Error> {
+    let mut content = Vec::new();
+    for value in values {
+        value.persist(&mut content)?;
+    }
+    VLByteSlice(&content).tls_serialize(writer).map(|_| ())
+}
+
+fn restore_sequence<T: Persist, C: FromIterator<T>>(
+    bytes: &mut &[u8],
+) -> Result<C, tls_codec::Error> {
+    let content = VLBytes::tls_deserialize(bytes)?;
+    let mut content = content.as_slice();
+    let mut values = Vec::new();
+    while !content.is_empty() {
+        values.push(T::restore(&mut content)?);
+    }
+    Ok(values.into_iter().collect())
+}
+
+impl<T: Persist> Persist for Vec<T> {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        persist_sequence(self.iter(), writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        restore_sequence(bytes)
+    }
+}
+
+impl<T: Persist> Persist for VecDeque<T> {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        persist_sequence(self.iter(), writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        restore_sequence(bytes)
+    }
+}
+
+/// Maps are encoded like a sequence of their entries, ordered by key.
+impl<K: Persist + Ord, V: Persist> Persist for BTreeMap<K, V> {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        let mut content = Vec::new();
+        for (key, value) in self {
+            key.persist(&mut content)?;
+            value.persist(&mut content)?;
+        }
+        VLByteSlice(&content).tls_serialize(writer).map(|_| ())
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        restore_sequence::<(K, V), _>(bytes)
+    }
+}
+
+impl<T: Persist> Persist for Box<T> {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.as_ref().persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        T::restore(bytes).map(Box::new)
+    }
+}
+
+impl<A: Persist, B: Persist> Persist for (A, B) {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.0.persist(writer)?;
+        self.1.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok((A::restore(bytes)?, B::restore(bytes)?))
+    }
+}
@@ -66,6 +66,11 @@ impl MessageSecrets {
         self.serialized_context.as_ref()
     }
 
+    /// Get a reference to the message secrets's secret tree.
+    pub(crate) fn secret_tree(&self) -> &SecretTree {
+        &self.secret_tree
+    }
+
     /// Get a mutable reference to the message secrets's secret tree.
     pub(crate) fn secret_tree_mut(&mut self) -> &mut SecretTree {
         &mut self.secret_tree
@@ -125,3 +130,23 @@ impl PartialEq for MessageSecrets {
             && self.confirmation_key == other.confirmation_key
     }
 }
+
+impl Persist for MessageSecrets {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.sender_data_secret.persist(writer)?;
+        self.membership_key.persist(writer)?;
+        self.confirmation_key.persist(writer)?;
+        self.serialized_context.persist(writer)?;
+        self.secret_tree.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            sender_data_secret: SenderDataSecret::restore(bytes)?,
+            membership_key: MembershipKey::restore(bytes)?,
+            confirmation_key: ConfirmationKey::restore(bytes)?,
+            serialized_context: Vec::restore(bytes)?,
+            secret_tree: SecretTree::restore(bytes)?,
+        })
+    }
+}
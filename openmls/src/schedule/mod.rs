@@ -132,6 +132,7 @@ use crate::{
     framing::{mls_content::AuthenticatedContentTbm, MembershipTag},
     group::GroupContext,
     messages::{ConfirmationTag, PathSecret},
+    persistence::Persist,
     tree::secret_tree::SecretTree,
     versions::ProtocolVersion,
 };
@@ -1221,3 +1222,51 @@ impl GroupEpochSecrets {
         &self.resumption_psk
     }
 }
+
+/// Implement [`Persist`] for secrets that consist of a single [`Secret`].
+macro_rules! impl_persist_for_secret {
+    ($($ty:ident),* $(,)?) => {
+        $(
+            impl Persist for $ty {
+                fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+                    self.secret.persist(writer)
+                }
+
+                fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+                    Secret::restore(bytes).map(|secret| Self { secret })
+                }
+            }
+        )*
+    };
+}
+
+impl_persist_for_secret!(
+    ResumptionPskSecret,
+    EpochAuthenticator,
+    InitSecret,
+    ExporterSecret,
+    ExternalSecret,
+    ConfirmationKey,
+    MembershipKey,
+    SenderDataSecret,
+);
+
+impl Persist for GroupEpochSecrets {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.init_secret.persist(writer)?;
+        self.exporter_secret.persist(writer)?;
+        self.epoch_authenticator.persist(writer)?;
+        self.external_secret.persist(writer)?;
+        self.resumption_psk.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            init_secret: InitSecret::restore(bytes)?,
+            exporter_secret: ExporterSecret::restore(bytes)?,
+            epoch_authenticator: EpochAuthenticator::restore(bytes)?,
+            external_secret: ExternalSecret::restore(bytes)?,
+            resumption_psk: ResumptionPskSecret::restore(bytes)?,
+        })
+    }
+}
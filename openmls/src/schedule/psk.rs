@@ -11,6 +11,7 @@ use tls_codec::{Serialize as TlsSerializeTrait, VLBytes};
 use super::*;
 use crate::{
     group::{GroupEpoch, GroupId},
+    messages::proposals::ReInitProposal,
     schedule::psk::store::ResumptionPskStore,
 };
 
@@ -93,6 +94,47 @@ impl ExternalPsk {
 #[derive(Serialize, Deserialize, TlsDeserialize, TlsSerialize, TlsSize)]
 pub(crate) struct PskBundle {
     secret: Secret,
+    // The ReInit proposal of the group the PSK stems from, if it is the
+    // resumption PSK of a reinitialized group.
+    #[serde(default)]
+    reinit_proposal: Option<ReInitProposal>,
+}
+
+impl PskBundle {
+    /// Create a [`PskBundle`] from the resumption PSK of a group, so that it
+    /// can be injected into the key schedule of another group with the given
+    /// `ciphersuite`.
+    pub(crate) fn from_resumption_psk(
+        resumption_psk: &ResumptionPskSecret,
+        ciphersuite: Ciphersuite,
+    ) -> Self {
+        let mut secret = resumption_psk.secret.clone();
+        secret.config(ciphersuite, ProtocolVersion::default());
+        Self {
+            secret,
+            reinit_proposal: None,
+        }
+    }
+
+    /// Create a [`PskBundle`] from the resumption PSK of a group that was
+    /// reinitialized with the given `reinit_proposal`, so that it can be
+    /// injected into the key schedule of the successor group.
+    pub(crate) fn from_reinit(
+        resumption_psk: &ResumptionPskSecret,
+        reinit_proposal: ReInitProposal,
+    ) -> Self {
+        let ciphersuite = reinit_proposal.ciphersuite();
+        Self {
+            reinit_proposal: Some(reinit_proposal),
+            ..Self::from_resumption_psk(resumption_psk, ciphersuite)
+        }
+    }
+
+    /// Returns the ReInit proposal of the group the PSK stems from, if it is
+    /// the resumption PSK of a reinitialized group.
+    pub(crate) fn reinit_proposal(&self) -> Option<&ReInitProposal> {
+        self.reinit_proposal.as_ref()
+    }
 }
 
 impl MlsEntity for PskBundle {
@@ -290,7 +332,10 @@ impl PreSharedKeyId {
         let psk_bundle = {
             let secret = Secret::from_slice(psk, ProtocolVersion::default(), ciphersuite);
 
-            PskBundle { secret }
+            PskBundle {
+                secret,
+                reinit_proposal: None,
+            }
         };
 
         provider
@@ -474,6 +519,17 @@ pub(crate) fn load_psks<'p>(
         log_crypto!(trace, "PSK store {:?}", resumption_psk_store);
 
         match &psk_id.psk {
+            // Resumption PSKs used for reinitialization and branching stem
+            // from a different group and are therefore kept in the key store.
+            Psk::Resumption(resumption)
+                if resumption.usage() != ResumptionPskUsage::Application =>
+            {
+                if let Some(psk_bundle) = key_store.read::<PskBundle>(&psk_id.keystore_id()?) {
+                    psk_bundles.push((psk_id, psk_bundle.secret));
+                } else {
+                    return Err(PskError::KeyNotFound);
+                }
+            }
             Psk::Resumption(resumption) => {
                 if let Some(psk_bundle) = resumption_psk_store.get(resumption.psk_epoch()) {
                     psk_bundles.push((psk_id, psk_bundle.secret.clone()));
@@ -498,7 +554,7 @@ pub(crate) fn load_psks<'p>(
 pub mod store {
     use serde::{Deserialize, Serialize};
 
-    use crate::{group::GroupEpoch, schedule::ResumptionPskSecret};
+    use crate::{group::GroupEpoch, persistence::Persist, schedule::ResumptionPskSecret};
 
     /// Resumption PSK store.
     ///
@@ -546,4 +602,20 @@ pub mod store {
                 .map(|(_e, s)| s)
         }
     }
+
+    impl Persist for ResumptionPskStore {
+        fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+            self.max_number_of_secrets.persist(writer)?;
+            self.resumption_psk.persist(writer)?;
+            self.cursor.persist(writer)
+        }
+
+        fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+            Ok(Self {
+                max_number_of_secrets: usize::restore(bytes)?,
+                resumption_psk: Vec::restore(bytes)?,
+                cursor: usize::restore(bytes)?,
+            })
+        }
+    }
 }
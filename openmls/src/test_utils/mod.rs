@@ -9,7 +9,11 @@ use std::{
 };
 
 use openmls_basic_credential::SignatureKeyPair;
-use openmls_traits::{key_store::OpenMlsKeyStore, types::HpkeKeyPair};
+use openmls_traits::{
+    key_store::OpenMlsKeyStore,
+    time::OpenMlsTime,
+    types::{HpkeKeyPair, SignatureScheme},
+};
 pub use openmls_traits::{types::Ciphersuite, OpenMlsProvider};
 pub use rstest::*;
 pub use rstest_reuse::{self, *};
@@ -22,6 +26,7 @@ use crate::{
     ciphersuite::{HpkePrivateKey, OpenMlsSignaturePublicKey},
     credentials::{Credential, CredentialType, CredentialWithKey},
     key_packages::KeyPackage,
+    prelude::Capabilities,
     prelude::{CryptoConfig, KeyPackageBuilder},
     treesync::node::encryption_keys::{EncryptionKeyPair, EncryptionPrivateKey},
 };
@@ -83,6 +88,18 @@ pub fn hex_to_bytes_option(hex: Option<String>) -> Vec<u8> {
     }
 }
 
+/// Returns whether the signatures in the test vectors of the given
+/// ciphersuite can be verified.
+///
+/// The test vectors sign with ECDSA on P-384 and P-521 using SHA-256 instead
+/// of the hash of the signature scheme, i.e., SHA-384 and SHA-512.
+pub(crate) fn vector_signatures_verifiable(ciphersuite: Ciphersuite) -> bool {
+    !matches!(
+        ciphersuite.signature_algorithm(),
+        SignatureScheme::ECDSA_SECP384R1_SHA384 | SignatureScheme::ECDSA_SECP521R1_SHA512
+    )
+}
+
 // === Convenience functions ===
 
 #[cfg(test)]
@@ -128,7 +145,8 @@ pub(crate) fn generate_group_candidate(
     };
 
     let (key_package, encryption_keypair, init_keypair) = {
-        let builder = KeyPackageBuilder::new();
+        let builder = KeyPackageBuilder::new()
+            .leaf_node_capabilities(Capabilities::with_ciphersuite(ciphersuite));
 
         if use_store {
             let key_package = builder
@@ -198,6 +216,62 @@ pub(crate) fn generate_group_candidate(
     }
 }
 
+/// A provider that wraps another provider, but whose time source is fixed at
+/// `now`, e.g. to test expired lifetimes.
+#[cfg(test)]
+pub(crate) struct FixedTimeProvider<'a, Provider: OpenMlsProvider> {
+    pub(crate) provider: &'a Provider,
+    pub(crate) now: u64,
+    pub(crate) clock_skew_tolerance: u64,
+}
+
+#[cfg(test)]
+impl<'a, Provider: OpenMlsProvider> FixedTimeProvider<'a, Provider> {
+    /// Wrap `provider` with a time source fixed at the current time of its
+    /// own time source, shifted by `offset` seconds.
+    pub(crate) fn shifted(provider: &'a Provider, offset: i64) -> Self {
+        Self {
+            provider,
+            now: provider.time().now().saturating_add_signed(offset),
+            clock_skew_tolerance: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+impl<'a, Provider: OpenMlsProvider> OpenMlsTime for FixedTimeProvider<'a, Provider> {
+    fn now(&self) -> u64 {
+        self.now
+    }
+
+    fn clock_skew_tolerance(&self) -> u64 {
+        self.clock_skew_tolerance
+    }
+}
+
+#[cfg(test)]
+impl<'a, Provider: OpenMlsProvider> OpenMlsProvider for FixedTimeProvider<'a, Provider> {
+    type CryptoProvider = Provider::CryptoProvider;
+    type RandProvider = Provider::RandProvider;
+    type KeyStoreProvider = Provider::KeyStoreProvider;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        self.provider.crypto()
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        self.provider.rand()
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        self.provider.key_store()
+    }
+
+    fn time(&self) -> &dyn OpenMlsTime {
+        self
+    }
+}
+
 // === Define provider per platform ===
 
 // This provider is currently used on all platforms
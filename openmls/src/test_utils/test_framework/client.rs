@@ -122,8 +122,13 @@ impl Client {
         welcome: Welcome,
         ratchet_tree: Option<RatchetTreeIn>,
     ) -> Result<(), ClientError> {
-        let new_group: MlsGroup =
-            MlsGroup::new_from_welcome(&self.crypto, &mls_group_config, welcome, ratchet_tree)?;
+        let new_group: MlsGroup = MlsGroup::new_from_welcome(
+            &self.crypto,
+            &mls_group_config,
+            welcome,
+            ratchet_tree,
+            &AcceptAllCredentials,
+        )?;
         self.groups
             .write()
             .expect("An unexpected error occurred.")
@@ -152,7 +157,11 @@ impl Client {
                 group_state.clear_pending_commit();
             }
             // Process the message.
-            let processed_message = group_state.process_message(&self.crypto, message.clone())?;
+            let processed_message = group_state.process_message(
+                &self.crypto,
+                message.clone(),
+                &AcceptAllCredentials,
+            )?;
 
             match processed_message.into_content() {
                 ProcessedMessageContent::ApplicationMessage(_) => {}
@@ -250,8 +259,12 @@ impl Client {
         .unwrap();
         let action_results = match action_type {
             ActionType::Commit => {
-                let (messages, welcome_message, group_info) =
-                    group.add_members(&self.crypto, &signer, key_packages)?;
+                let (messages, welcome_message, group_info) = group.add_members(
+                    &self.crypto,
+                    &signer,
+                    key_packages,
+                    &AcceptAllCredentials,
+                )?;
                 (
                     vec![messages],
                     Some(
@@ -12,6 +12,7 @@ use crate::{
         LeafNodeIndex,
     },
     framing::*,
+    persistence::Persist,
     schedule::*,
     tree::sender_ratchet::*,
 };
@@ -168,6 +169,48 @@ impl SecretTree {
         }
     }
 
+    /// Returns the generations of application messages received from the
+    /// other members in this epoch as inclusive ranges per sender, ordered by
+    /// leaf index and generation.
+    pub(crate) fn received_application_generations(
+        &self,
+    ) -> impl Iterator<Item = (LeafNodeIndex, Generation, Generation)> + '_ {
+        self.application_sender_ratchets
+            .iter()
+            .enumerate()
+            .filter_map(|(index, sender_ratchet)| match sender_ratchet {
+                Some(SenderRatchet::DecryptionRatchet(dec_ratchet)) => {
+                    Some((LeafNodeIndex::new(index as u32), dec_ratchet))
+                }
+                _ => None,
+            })
+            .flat_map(|(index, dec_ratchet)| {
+                dec_ratchet
+                    .received_generations()
+                    .iter()
+                    .map(move |&(first, last)| (index, first, last))
+            })
+    }
+
+    /// Record that a message of the given generation from the member at the
+    /// given index was decrypted. This must only be called once the AEAD
+    /// decryption with the key material from [`Self::secret_for_decryption()`]
+    /// succeeded, such that forged messages are not recorded.
+    pub(crate) fn mark_received(
+        &mut self,
+        index: LeafNodeIndex,
+        secret_type: SecretType,
+        generation: Generation,
+    ) -> Result<(), SecretTreeError> {
+        match self.ratchet_mut(index, secret_type)? {
+            SenderRatchet::EncryptionRatchet(_) => Err(SecretTreeError::RatchetTypeError),
+            SenderRatchet::DecryptionRatchet(dec_ratchet) => {
+                dec_ratchet.mark_received(generation);
+                Ok(())
+            }
+        }
+    }
+
     /// Initializes a specific SenderRatchet pair for a given index by
     /// calculating and deleting the appropriate values in the SecretTree
     fn initialize_sender_ratchets(
@@ -469,3 +512,35 @@ impl SecretTree {
         Ok(())
     }
 }
+
+impl Persist for SecretTreeNode {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.secret.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Secret::restore(bytes).map(|secret| Self { secret })
+    }
+}
+
+impl Persist for SecretTree {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.own_index.persist(writer)?;
+        self.leaf_nodes.persist(writer)?;
+        self.parent_nodes.persist(writer)?;
+        self.handshake_sender_ratchets.persist(writer)?;
+        self.application_sender_ratchets.persist(writer)?;
+        self.size.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            own_index: LeafNodeIndex::restore(bytes)?,
+            leaf_nodes: Vec::restore(bytes)?,
+            parent_nodes: Vec::restore(bytes)?,
+            handshake_sender_ratchets: Vec::restore(bytes)?,
+            application_sender_ratchets: Vec::restore(bytes)?,
+            size: TreeSize::restore(bytes)?,
+        })
+    }
+}
@@ -11,6 +11,7 @@ use std::collections::VecDeque;
 use openmls_traits::types::Ciphersuite;
 
 use crate::ciphersuite::{AeadNonce, *};
+use crate::persistence::Persist;
 use crate::tree::secret_tree::*;
 
 use super::*;
@@ -60,6 +61,20 @@ impl Default for SenderRatchetConfiguration {
     }
 }
 
+impl Persist for SenderRatchetConfiguration {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.out_of_order_tolerance.persist(writer)?;
+        self.maximum_forward_distance.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            out_of_order_tolerance: Generation::restore(bytes)?,
+            maximum_forward_distance: Generation::restore(bytes)?,
+        })
+    }
+}
+
 /// The key material derived from a [`RatchetSecret`] meant for use with a
 /// nonce-based symmetric encryption scheme.
 pub(crate) type RatchetKeyMaterial = (AeadKey, AeadNonce);
@@ -173,6 +188,8 @@ impl RatchetSecret {
 pub struct DecryptionRatchet {
     past_secrets: VecDeque<Option<RatchetKeyMaterial>>,
     ratchet_head: RatchetSecret,
+    #[serde(default)]
+    received_generations: Vec<(Generation, Generation)>,
 }
 
 impl DecryptionRatchet {
@@ -181,6 +198,40 @@ impl DecryptionRatchet {
         Self {
             past_secrets: VecDeque::new(),
             ratchet_head: RatchetSecret::initial_ratchet_secret(secret),
+            received_generations: Vec::new(),
+        }
+    }
+
+    /// Get the generations of the messages that were decrypted with this
+    /// ratchet as sorted, disjoint and inclusive ranges.
+    pub(crate) fn received_generations(&self) -> &[(Generation, Generation)] {
+        &self.received_generations
+    }
+
+    /// Record that a message of the given generation was decrypted, i.e.,
+    /// that its AEAD decryption succeeded. Adjacent ranges are merged.
+    pub(crate) fn mark_received(&mut self, generation: Generation) {
+        // The first range that ends right before, at or after the generation.
+        let index = self
+            .received_generations
+            .partition_point(|&(_, last)| last.saturating_add(1) < generation);
+        match self.received_generations.get_mut(index) {
+            Some((first, last)) if *first <= generation.saturating_add(1) => {
+                *first = (*first).min(generation);
+                *last = (*last).max(generation);
+            }
+            _ => {
+                self.received_generations
+                    .insert(index, (generation, generation));
+                return;
+            }
+        }
+        // Merge with the next range if the gap was closed.
+        if let Some(&(next_first, next_last)) = self.received_generations.get(index + 1) {
+            if next_first <= self.received_generations[index].1.saturating_add(1) {
+                self.received_generations[index].1 = next_last;
+                self.received_generations.remove(index + 1);
+            }
         }
     }
 
@@ -259,7 +310,8 @@ impl DecryptionRatchet {
                 return Err(SecretTreeError::TooDistantInThePast);
             };
             // Get the relevant secrets from the past secrets queue.
-            self.past_secrets
+            let ratchet_secrets = self
+                .past_secrets
                 .get_mut(index)
                 .ok_or(SecretTreeError::IndexOutOfBounds)?
                 // We use take here to replace the entry in the `past_secrets`
@@ -268,7 +320,63 @@ impl DecryptionRatchet {
                 .take()
                 // If the requested generation was used to decrypt a message
                 // earlier, throw an error.
-                .ok_or(SecretTreeError::SecretReuseError)
+                .ok_or(SecretTreeError::SecretReuseError)?;
+            Ok(ratchet_secrets)
         }
     }
 }
+
+impl Persist for SenderRatchet {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        match self {
+            SenderRatchet::EncryptionRatchet(ratchet) => {
+                1u8.persist(writer)?;
+                ratchet.persist(writer)
+            }
+            SenderRatchet::DecryptionRatchet(ratchet) => {
+                2u8.persist(writer)?;
+                ratchet.persist(writer)
+            }
+        }
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        match u8::restore(bytes)? {
+            1 => RatchetSecret::restore(bytes).map(SenderRatchet::EncryptionRatchet),
+            2 => DecryptionRatchet::restore(bytes).map(SenderRatchet::DecryptionRatchet),
+            value => Err(tls_codec::Error::DecodingError(format!(
+                "{value} is not a valid sender ratchet type"
+            ))),
+        }
+    }
+}
+
+impl Persist for RatchetSecret {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.secret.persist(writer)?;
+        self.generation.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            secret: Secret::restore(bytes)?,
+            generation: Generation::restore(bytes)?,
+        })
+    }
+}
+
+impl Persist for DecryptionRatchet {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.past_secrets.persist(writer)?;
+        self.ratchet_head.persist(writer)?;
+        self.received_generations.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            past_secrets: VecDeque::restore(bytes)?,
+            ratchet_head: RatchetSecret::restore(bytes)?,
+            received_generations: Vec::restore(bytes)?,
+        })
+    }
+}
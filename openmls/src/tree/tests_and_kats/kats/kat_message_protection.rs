@@ -378,7 +378,12 @@ pub fn run_test_vector(
                 .parse_message(decrypted_message, group.message_secrets_store())
                 .unwrap();
             let processed_message: AuthenticatedContent = processed_unverified_message
-                .verify(ciphersuite, provider.crypto(), ProtocolVersion::Mls10)
+                .verify(
+                    ciphersuite,
+                    provider.crypto(),
+                    provider.time(),
+                    ProtocolVersion::Mls10,
+                )
                 .unwrap()
                 .0;
             match processed_message.content().to_owned() {
@@ -514,7 +519,12 @@ pub fn run_test_vector(
                 .parse_message(decrypted_message, group.message_secrets_store())
                 .unwrap();
             let processed_message: AuthenticatedContent = processed_unverified_message
-                .verify(ciphersuite, provider.crypto(), ProtocolVersion::Mls10)
+                .verify(
+                    ciphersuite,
+                    provider.crypto(),
+                    provider.time(),
+                    ProtocolVersion::Mls10,
+                )
                 .unwrap()
                 .0;
             match processed_message.content().to_owned() {
@@ -557,7 +567,12 @@ pub fn run_test_vector(
                 .parse_message(decrypted_message, group.message_secrets_store())
                 .unwrap();
             let processed_message: AuthenticatedContent = processed_unverified_message
-                .verify(ciphersuite, provider.crypto(), ProtocolVersion::Mls10)
+                .verify(
+                    ciphersuite,
+                    provider.crypto(),
+                    provider.time(),
+                    ProtocolVersion::Mls10,
+                )
                 .unwrap()
                 .0;
             match processed_message.content().to_owned() {
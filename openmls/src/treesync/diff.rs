@@ -22,6 +22,7 @@ use std::collections::HashSet;
 use log::debug;
 use openmls_traits::crypto::OpenMlsCrypto;
 use openmls_traits::{signatures::Signer, types::Ciphersuite, OpenMlsProvider};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use super::{
@@ -46,6 +47,7 @@ use crate::{
     error::LibraryError,
     group::GroupId,
     messages::PathSecret,
+    persistence::Persist,
     schedule::CommitSecret,
     treesync::RatchetTree,
 };
@@ -73,6 +75,28 @@ impl StagedTreeSyncDiff {
     ) {
         (self.diff, self.new_tree_hash)
     }
+
+    /// Returns the leaves that are changed by this diff, except for the ones
+    /// that are blanked.
+    pub(crate) fn changed_leaves(&self) -> impl Iterator<Item = (LeafNodeIndex, &LeafNode)> {
+        self.diff
+            .leaves()
+            .filter_map(|(index, leaf)| leaf.node().as_ref().map(|leaf| (index, leaf)))
+    }
+}
+
+impl Persist for StagedTreeSyncDiff {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.diff.persist(writer)?;
+        self.new_tree_hash.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            diff: StagedMlsBinaryTreeDiff::restore(bytes)?,
+            new_tree_hash: Vec::restore(bytes)?,
+        })
+    }
 }
 
 /// A [`TreeSyncDiff`] serves as a way to perform changes on an otherwise
@@ -94,6 +118,14 @@ impl<'a> From<&'a TreeSync> for TreeSyncDiff<'a> {
 }
 
 impl<'a> TreeSyncDiff<'a> {
+    /// Create a [`TreeSyncDiff`] that contains the changes of the given
+    /// [`StagedTreeSyncDiff`] to the given [`TreeSync`].
+    pub(super) fn from_staged(tree_sync: &'a TreeSync, staged_diff: &StagedTreeSyncDiff) -> Self {
+        TreeSyncDiff {
+            diff: tree_sync.tree.staged_diff(&staged_diff.diff),
+        }
+    }
+
     /// Filtered direct path, skips the nodes whose copath resolution is empty.
     pub(crate) fn filtered_direct_path(&self, leaf_index: LeafNodeIndex) -> Vec<ParentNodeIndex> {
         // Full direct path
@@ -600,65 +632,87 @@ impl<'a> TreeSyncDiff<'a> {
         //
         // The parent hash for a given node is valid, when exactly one descendant
         // carries the parent hash it its parent hash field.
-        for (parent_index, tree_sync_parent_node) in self.diff.parents() {
-            if let Some(parent_node) = tree_sync_parent_node.node() {
-                // We consider both children of the parent node. One of them
-                // takes the role of the descendant, whose resolution carries
-                // the parent hash. The other one is the descendants sibling,
-                // whose original tree hash is used to compute the parent hash.
-                let left_child = self.diff.left_child(parent_index);
-                let right_child = self.diff.right_child(parent_index);
+        //
+        // The parent hashes of the parent nodes are verified in parallel.
+        let parent_nodes: Vec<(ParentNodeIndex, &ParentNode)> = self
+            .diff
+            .parents()
+            .filter_map(|(parent_index, tree_sync_parent_node)| {
+                tree_sync_parent_node
+                    .node()
+                    .as_ref()
+                    .map(|parent_node| (parent_index, parent_node))
+            })
+            .collect();
+        parent_nodes
+            .par_iter()
+            .try_for_each(|(parent_index, parent_node)| {
+                self.verify_parent_hash(crypto, ciphersuite, *parent_index, parent_node)
+            })
+    }
 
-                // We exclude the unmerged leaves from the parent node for the
-                // following computations. Those leaves were obviously addede
-                // after the parent node was populated during a commit and must
-                // therefore be removed to recreate the tree state at the time
-                // of the commit.
-                let exclusion_list = HashSet::from_iter(parent_node.unmerged_leaves().iter());
-
-                // Compute the original tree hash (oth) for the left and right child.
-                let oth_left =
-                    self.compute_tree_hash(crypto, ciphersuite, left_child, &exclusion_list)?;
-
-                let oth_right =
-                    self.compute_tree_hash(crypto, ciphersuite, right_child, &exclusion_list)?;
-
-                // Compute the parent hash for both child roles.
-                let parent_hash_left =
-                    parent_node.compute_parent_hash(crypto, ciphersuite, &oth_right)?;
-
-                let parent_hash_right =
-                    parent_node.compute_parent_hash(crypto, ciphersuite, &oth_left)?;
-
-                // Compute the resolution for both children.
-                let left_resolution = self.resolution(left_child, &exclusion_list);
-
-                let right_resolution = self.resolution(right_child, &exclusion_list);
-
-                // Find parent hash in the left resolution.
-                let left_descendant = left_resolution.iter().find(|(_, node)| match node {
-                    NodeReference::Leaf(leaf) => leaf
-                        .parent_hash()
-                        .map(|parent_hash| parent_hash == parent_hash_left)
-                        .unwrap_or(false),
-                    NodeReference::Parent(parent) => parent.parent_hash() == parent_hash_left,
-                });
-
-                // Find parent hash in the right resolution.
-                let right_descendant = right_resolution.iter().find(|(_, node)| match node {
-                    NodeReference::Leaf(leaf) => leaf
-                        .parent_hash()
-                        .map(|parent_hash| parent_hash == parent_hash_right)
-                        .unwrap_or(false),
-                    NodeReference::Parent(parent) => parent.parent_hash() == parent_hash_right,
-                });
-
-                // If one of the parent hashes is in the resolution of the
-                // other child, the parent hash is valid.
-                if left_descendant.is_none() ^ right_descendant.is_some() {
-                    return Err(TreeSyncParentHashError::InvalidParentHash);
-                }
-            }
+    /// Verify the parent hash of the given parent node.
+    ///
+    /// Returns an error if the parent hash is invalid.
+    fn verify_parent_hash(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        ciphersuite: Ciphersuite,
+        parent_index: ParentNodeIndex,
+        parent_node: &ParentNode,
+    ) -> Result<(), TreeSyncParentHashError> {
+        // We consider both children of the parent node. One of them
+        // takes the role of the descendant, whose resolution carries
+        // the parent hash. The other one is the descendants sibling,
+        // whose original tree hash is used to compute the parent hash.
+        let left_child = self.diff.left_child(parent_index);
+        let right_child = self.diff.right_child(parent_index);
+
+        // We exclude the unmerged leaves from the parent node for the
+        // following computations. Those leaves were obviously addede
+        // after the parent node was populated during a commit and must
+        // therefore be removed to recreate the tree state at the time
+        // of the commit.
+        let exclusion_list = HashSet::from_iter(parent_node.unmerged_leaves().iter());
+
+        // Compute the original tree hash (oth) for the left and right child.
+        let oth_left = self.compute_tree_hash(crypto, ciphersuite, left_child, &exclusion_list)?;
+
+        let oth_right =
+            self.compute_tree_hash(crypto, ciphersuite, right_child, &exclusion_list)?;
+
+        // Compute the parent hash for both child roles.
+        let parent_hash_left = parent_node.compute_parent_hash(crypto, ciphersuite, &oth_right)?;
+
+        let parent_hash_right = parent_node.compute_parent_hash(crypto, ciphersuite, &oth_left)?;
+
+        // Compute the resolution for both children.
+        let left_resolution = self.resolution(left_child, &exclusion_list);
+
+        let right_resolution = self.resolution(right_child, &exclusion_list);
+
+        // Find parent hash in the left resolution.
+        let left_descendant = left_resolution.iter().find(|(_, node)| match node {
+            NodeReference::Leaf(leaf) => leaf
+                .parent_hash()
+                .map(|parent_hash| parent_hash == parent_hash_left)
+                .unwrap_or(false),
+            NodeReference::Parent(parent) => parent.parent_hash() == parent_hash_left,
+        });
+
+        // Find parent hash in the right resolution.
+        let right_descendant = right_resolution.iter().find(|(_, node)| match node {
+            NodeReference::Leaf(leaf) => leaf
+                .parent_hash()
+                .map(|parent_hash| parent_hash == parent_hash_right)
+                .unwrap_or(false),
+            NodeReference::Parent(parent) => parent.parent_hash() == parent_hash_right,
+        });
+
+        // If one of the parent hashes is in the resolution of the
+        // other child, the parent hash is valid.
+        if left_descendant.is_none() ^ right_descendant.is_some() {
+            return Err(TreeSyncParentHashError::InvalidParentHash);
         }
         Ok(())
     }
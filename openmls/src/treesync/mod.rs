@@ -60,7 +60,7 @@ use crate::{
         array_representation::{is_node_in_tree, tree::TreeNode, LeafNodeIndex, TreeSize},
         MlsBinaryTree, MlsBinaryTreeError,
     },
-    ciphersuite::{signable::Verifiable, Secret},
+    ciphersuite::Secret,
     credentials::CredentialWithKey,
     error::LibraryError,
     extensions::Extensions,
@@ -68,6 +68,7 @@ use crate::{
     group::{config::CryptoConfig, GroupId, Member},
     key_packages::Lifetime,
     messages::{PathSecret, PathSecretError},
+    persistence::Persist,
     schedule::CommitSecret,
 };
 
@@ -160,8 +161,10 @@ impl RatchetTree {
             Some(Some(_)) => {
                 // The ratchet tree is not empty, i.e., has a last node, and the last node is not blank.
 
-                // Verify the nodes.
+                // Check the node types and collect the leaf nodes, whose
+                // signatures are verified in a batch.
                 let mut verified_nodes = Vec::new();
+                let mut leaf_nodes = Vec::new();
                 for (index, node) in nodes.into_iter().enumerate() {
                     let verified_node = match (index % 2, node) {
                         // Even indices must be leaf nodes.
@@ -170,30 +173,17 @@ impl RatchetTree {
                                 group_id.clone(),
                                 LeafNodeIndex::new((index / 2) as u32),
                             );
-                            let verifiable_leaf_node = leaf_node.into_verifiable_leaf_node();
+                            let mut verifiable_leaf_node = leaf_node.into_verifiable_leaf_node();
+                            verifiable_leaf_node.add_tree_position(tree_position);
                             let signature_key = verifiable_leaf_node
                                 .signature_key()
                                 .clone()
                                 .into_signature_public_key_enriched(
                                     ciphersuite.signature_algorithm(),
                                 );
-                            Some(Node::LeafNode(match verifiable_leaf_node {
-                                VerifiableLeafNode::KeyPackage(leaf_node) => leaf_node
-                                    .verify(crypto, &signature_key)
-                                    .map_err(|_| RatchetTreeError::InvalidNodeSignature)?,
-                                VerifiableLeafNode::Update(mut leaf_node) => {
-                                    leaf_node.add_tree_position(tree_position);
-                                    leaf_node
-                                        .verify(crypto, &signature_key)
-                                        .map_err(|_| RatchetTreeError::InvalidNodeSignature)?
-                                }
-                                VerifiableLeafNode::Commit(mut leaf_node) => {
-                                    leaf_node.add_tree_position(tree_position);
-                                    leaf_node
-                                        .verify(crypto, &signature_key)
-                                        .map_err(|_| RatchetTreeError::InvalidNodeSignature)?
-                                }
-                            }))
+                            leaf_nodes.push((index, (verifiable_leaf_node, signature_key)));
+                            // The leaf node is inserted after verification.
+                            None
                         }
                         // Odd indices must be parent nodes.
                         (1, Some(NodeIn::ParentNode(parent_node))) => {
@@ -208,6 +198,15 @@ impl RatchetTree {
                     };
                     verified_nodes.push(verified_node);
                 }
+
+                // Verify the leaf nodes.
+                let (indices, leaf_nodes): (Vec<usize>, Vec<_>) = leaf_nodes.into_iter().unzip();
+                let leaf_nodes = VerifiableLeafNode::verify_batch(crypto, leaf_nodes)
+                    .map_err(|_| RatchetTreeError::InvalidNodeSignature)?;
+                for (index, leaf_node) in indices.into_iter().zip(leaf_nodes) {
+                    verified_nodes[index] = Some(Node::LeafNode(leaf_node));
+                }
+
                 Ok(Self::trimmed(verified_nodes))
             }
         }
@@ -627,10 +626,15 @@ impl TreeSync {
         is_node_in_tree(leaf_index.into(), self.tree.tree_size())
     }
 
-    /// Return a vector containing all [`EncryptionKey`]s for which the owner of
-    /// the given `leaf_index` should have private key material.
-    pub(crate) fn owned_encryption_keys(&self, leaf_index: LeafNodeIndex) -> Vec<EncryptionKey> {
-        self.empty_diff()
+    /// Returns the [`EncryptionKey`]s for which the owner of the given
+    /// `leaf_index` should have private key material after merging the given
+    /// [`StagedTreeSyncDiff`], without merging it.
+    pub(crate) fn staged_owned_encryption_keys(
+        &self,
+        staged_diff: &StagedTreeSyncDiff,
+        leaf_index: LeafNodeIndex,
+    ) -> Vec<EncryptionKey> {
+        TreeSyncDiff::from_staged(self, staged_diff)
             .encryption_keys(leaf_index)
             .cloned()
             .collect::<Vec<EncryptionKey>>()
@@ -689,6 +693,22 @@ impl TreeSync {
     }
 }
 
+/// The tree hash is persisted together with the tree. A restored [`TreeSync`]
+/// is therefore not verified again.
+impl Persist for TreeSync {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.tree.persist(writer)?;
+        self.tree_hash.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            tree: MlsBinaryTree::restore(bytes)?,
+            tree_hash: Vec::restore(bytes)?,
+        })
+    }
+}
+
 #[cfg(test)]
 impl TreeSync {
     pub(crate) fn leaf_count(&self) -> u32 {
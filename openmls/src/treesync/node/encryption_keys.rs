@@ -40,7 +40,7 @@ impl EncryptionKey {
     /// the `ENCRYPTION_KEY_LABEL`.
     ///
     /// Returns the resulting bytes.
-    fn to_bytes_with_prefix(&self) -> Vec<u8> {
+    pub(crate) fn to_bytes_with_prefix(&self) -> Vec<u8> {
         let mut key_store_index = ENCRYPTION_KEY_LABEL.to_vec();
         key_store_index.extend_from_slice(self.as_slice());
         key_store_index
@@ -1,7 +1,12 @@
 //! This module contains the [`LeafNode`] struct and its implementation.
-use openmls_traits::{signatures::Signer, types::Ciphersuite, OpenMlsProvider};
+use openmls_traits::{
+    crypto::OpenMlsCrypto, signatures::Signer, types::Ciphersuite, OpenMlsProvider,
+};
 use serde::{Deserialize, Serialize};
-use tls_codec::{Serialize as TlsSerializeTrait, TlsDeserialize, TlsSerialize, TlsSize, VLBytes};
+use tls_codec::{
+    Deserialize as TlsDeserializeTrait, Serialize as TlsSerializeTrait, TlsDeserialize,
+    TlsSerialize, TlsSize, VLBytes,
+};
 
 #[cfg(test)]
 use openmls_traits::key_store::OpenMlsKeyStore;
@@ -12,14 +17,16 @@ use super::encryption_keys::{EncryptionKey, EncryptionKeyPair};
 use crate::{
     binary_tree::array_representation::LeafNodeIndex,
     ciphersuite::{
-        signable::{Signable, SignedStruct, Verifiable, VerifiedStruct},
-        Signature, SignaturePublicKey,
+        signable::{self, Signable, SignatureError, SignedStruct, Verifiable, VerifiedStruct},
+        OpenMlsSignaturePublicKey, Signature, SignaturePublicKey,
     },
     credentials::{Credential, CredentialWithKey},
     error::LibraryError,
     extensions::{ExtensionType, Extensions},
     group::{config::CryptoConfig, GroupId},
     key_packages::{KeyPackage, Lifetime},
+    messages::proposals::ProposalType,
+    persistence::Persist,
     treesync::errors::PublicTreeError,
     versions::ProtocolVersion,
 };
@@ -385,6 +392,47 @@ impl LeafNode {
             .contains(extension_type)
             || default_extensions().iter().any(|et| et == extension_type)
     }
+
+    /// Returns `true` if this leaf node supports the given group context
+    /// `extensions`, including the capabilities required by a
+    /// [`RequiredCapabilitiesExtension`](crate::extensions::RequiredCapabilitiesExtension).
+    /// Extension and proposal types defined in the MLS specification are
+    /// supported by default.
+    pub(crate) fn supports_group_context_extensions(&self, extensions: &Extensions) -> bool {
+        let capabilities = self.capabilities();
+        let supports_extension = |extension_type: &ExtensionType| {
+            !matches!(extension_type, ExtensionType::Unknown(_))
+                || capabilities.extensions().contains(extension_type)
+        };
+
+        if !extensions
+            .iter()
+            .all(|extension| supports_extension(&extension.extension_type()))
+        {
+            return false;
+        }
+
+        match extensions.required_capabilities() {
+            Some(required_capabilities) => {
+                required_capabilities
+                    .extension_types()
+                    .iter()
+                    .all(supports_extension)
+                    && required_capabilities
+                        .proposal_types()
+                        .iter()
+                        .all(|proposal_type| {
+                            !matches!(proposal_type, ProposalType::Unknown(_))
+                                || capabilities.proposals().contains(proposal_type)
+                        })
+                    && required_capabilities
+                        .credential_types()
+                        .iter()
+                        .all(|credential_type| capabilities.contains_credential(credential_type))
+            }
+            None => true,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -520,6 +568,17 @@ struct LeafNodePayload {
     extensions: Extensions,
 }
 
+impl LeafNodePayload {
+    /// Checks that the signature key is bound to the credential, which is the
+    /// case for X.509 credentials. A leaf node that fails this check is
+    /// treated like one with an invalid signature.
+    fn verify_credential_binding(&self) -> Result<(), SignatureError> {
+        self.credential
+            .validate_signature_key(self.signature_key.as_slice())
+            .map_err(|_| SignatureError::VerificationError)
+    }
+}
+
 #[derive(
     Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TlsSerialize, TlsDeserialize, TlsSize,
 )]
@@ -730,6 +789,19 @@ impl From<KeyPackage> for LeafNode {
     }
 }
 
+/// Leaf nodes in the group state have been verified before. They are therefore
+/// restored without verifying them again.
+impl Persist for LeafNode {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.tls_serialize(writer).map(|_| ())
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        let LeafNodeIn { payload, signature } = LeafNodeIn::tls_deserialize(bytes)?;
+        Ok(Self { payload, signature })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum VerifiableLeafNode {
     KeyPackage(VerifiableKeyPackageLeafNode),
@@ -745,6 +817,100 @@ impl VerifiableLeafNode {
             VerifiableLeafNode::Commit(v) => v.signature_key(),
         }
     }
+
+    /// Add the position in the tree to leaf nodes with an update or commit
+    /// source, which sign their position. Leaf nodes with a key package
+    /// source don't change.
+    pub(crate) fn add_tree_position(&mut self, tree_position: TreePosition) {
+        match self {
+            VerifiableLeafNode::KeyPackage(_) => (),
+            VerifiableLeafNode::Update(v) => v.add_tree_position(tree_position),
+            VerifiableLeafNode::Commit(v) => v.add_tree_position(tree_position),
+        }
+    }
+
+    fn payload(&self) -> &LeafNodePayload {
+        match self {
+            VerifiableLeafNode::KeyPackage(v) => &v.payload,
+            VerifiableLeafNode::Update(v) => &v.payload,
+            VerifiableLeafNode::Commit(v) => &v.payload,
+        }
+    }
+
+    /// Verify the credential bindings and the signatures of all `leaf_nodes`
+    /// in a batch, see [`signable::verify_batch()`].
+    ///
+    /// Returns the verified [`LeafNode`]s in the same order or a
+    /// [`SignatureError`] if any of the leaf nodes is invalid.
+    pub(crate) fn verify_batch(
+        crypto: &impl OpenMlsCrypto,
+        leaf_nodes: Vec<(VerifiableLeafNode, OpenMlsSignaturePublicKey)>,
+    ) -> Result<Vec<LeafNode>, SignatureError> {
+        for (leaf_node, _) in &leaf_nodes {
+            leaf_node.payload().verify_credential_binding()?;
+        }
+        signable::verify_batch(crypto, &leaf_nodes)?;
+
+        Ok(leaf_nodes
+            .into_iter()
+            .map(|(leaf_node, _)| match leaf_node {
+                VerifiableLeafNode::KeyPackage(v) => {
+                    LeafNode::from_verifiable(v, private_mod::Seal)
+                }
+                VerifiableLeafNode::Update(v) => LeafNode::from_verifiable(v, private_mod::Seal),
+                VerifiableLeafNode::Commit(v) => LeafNode::from_verifiable(v, private_mod::Seal),
+            })
+            .collect())
+    }
+}
+
+impl Verifiable for VerifiableLeafNode {
+    fn unsigned_payload(&self) -> Result<Vec<u8>, tls_codec::Error> {
+        match self {
+            VerifiableLeafNode::KeyPackage(v) => v.unsigned_payload(),
+            VerifiableLeafNode::Update(v) => v.unsigned_payload(),
+            VerifiableLeafNode::Commit(v) => v.unsigned_payload(),
+        }
+    }
+
+    fn signature(&self) -> &Signature {
+        match self {
+            VerifiableLeafNode::KeyPackage(v) => v.signature(),
+            VerifiableLeafNode::Update(v) => v.signature(),
+            VerifiableLeafNode::Commit(v) => v.signature(),
+        }
+    }
+
+    fn label(&self) -> &str {
+        LEAF_NODE_SIGNATURE_LABEL
+    }
+
+    fn verify<T>(
+        self,
+        crypto: &impl OpenMlsCrypto,
+        pk: &OpenMlsSignaturePublicKey,
+    ) -> Result<T, SignatureError>
+    where
+        T: VerifiedStruct<Self>,
+    {
+        self.verify_no_out(crypto, pk)?;
+        Ok(T::from_verifiable(self, T::SealingType::default()))
+    }
+
+    fn verify_no_out(
+        &self,
+        crypto: &impl OpenMlsCrypto,
+        pk: &OpenMlsSignaturePublicKey,
+    ) -> Result<(), SignatureError> {
+        // Like the variants, check the credential binding before the
+        // signature.
+        self.payload().verify_credential_binding()?;
+        match self {
+            VerifiableLeafNode::KeyPackage(v) => v.verify_no_out(crypto, pk),
+            VerifiableLeafNode::Update(v) => v.verify_no_out(crypto, pk),
+            VerifiableLeafNode::Commit(v) => v.verify_no_out(crypto, pk),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -771,6 +937,19 @@ impl Verifiable for VerifiableKeyPackageLeafNode {
     fn label(&self) -> &str {
         LEAF_NODE_SIGNATURE_LABEL
     }
+
+    fn verify<T>(
+        self,
+        crypto: &impl OpenMlsCrypto,
+        pk: &OpenMlsSignaturePublicKey,
+    ) -> Result<T, SignatureError>
+    where
+        T: VerifiedStruct<Self>,
+    {
+        self.payload.verify_credential_binding()?;
+        self.verify_no_out(crypto, pk)?;
+        Ok(T::from_verifiable(self, T::SealingType::default()))
+    }
 }
 
 impl VerifiedStruct<VerifiableKeyPackageLeafNode> for LeafNode {
@@ -821,6 +1000,19 @@ impl Verifiable for VerifiableUpdateLeafNode {
     fn label(&self) -> &str {
         LEAF_NODE_SIGNATURE_LABEL
     }
+
+    fn verify<T>(
+        self,
+        crypto: &impl OpenMlsCrypto,
+        pk: &OpenMlsSignaturePublicKey,
+    ) -> Result<T, SignatureError>
+    where
+        T: VerifiedStruct<Self>,
+    {
+        self.payload.verify_credential_binding()?;
+        self.verify_no_out(crypto, pk)?;
+        Ok(T::from_verifiable(self, T::SealingType::default()))
+    }
 }
 
 impl VerifiedStruct<VerifiableUpdateLeafNode> for LeafNode {
@@ -872,6 +1064,19 @@ impl Verifiable for VerifiableCommitLeafNode {
     fn label(&self) -> &str {
         LEAF_NODE_SIGNATURE_LABEL
     }
+
+    fn verify<T>(
+        self,
+        crypto: &impl OpenMlsCrypto,
+        pk: &OpenMlsSignaturePublicKey,
+    ) -> Result<T, SignatureError>
+    where
+        T: VerifiedStruct<Self>,
+    {
+        self.payload.verify_credential_binding()?;
+        self.verify_no_out(crypto, pk)?;
+        Ok(T::from_verifiable(self, T::SealingType::default()))
+    }
 }
 
 impl VerifiedStruct<VerifiableCommitLeafNode> for LeafNode {
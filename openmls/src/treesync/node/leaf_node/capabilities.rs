@@ -159,6 +159,14 @@ impl Capabilities {
             .all(|e| self.extensions().contains(&e))
     }
 
+    /// Add the `extension_type` to the supported extensions if it isn't
+    /// listed yet.
+    pub(crate) fn add_extension(&mut self, extension_type: ExtensionType) {
+        if !self.extensions.contains(&extension_type) {
+            self.extensions.push(extension_type);
+        }
+    }
+
     /// Check if these [`Capabilities`] contain all the credentials.
     pub(crate) fn contains_credential(&self, credential_type: &CredentialType) -> bool {
         self.credentials().contains(credential_type)
@@ -176,6 +184,17 @@ impl Capabilities {
     pub fn set_ciphersuites(&mut self, ciphersuites: Vec<VerifiableCiphersuite>) {
         self.ciphersuites = ciphersuites;
     }
+
+    /// Create the default [`Capabilities`], extended by `ciphersuite` if it
+    /// isn't one of the default ciphersuites.
+    pub(crate) fn with_ciphersuite(ciphersuite: Ciphersuite) -> Self {
+        let mut capabilities = Self::default();
+        let ciphersuite = VerifiableCiphersuite::from(ciphersuite);
+        if !capabilities.ciphersuites.contains(&ciphersuite) {
+            capabilities.ciphersuites.push(ciphersuite);
+        }
+        capabilities
+    }
 }
 
 impl Default for Capabilities {
@@ -197,6 +216,9 @@ pub(super) fn default_versions() -> Vec<ProtocolVersion> {
     vec![ProtocolVersion::Mls10]
 }
 
+/// The hybrid post-quantum
+/// [`Ciphersuite::MLS_256_XWING_CHACHA20POLY1305_SHA256_Ed25519`] is not a
+/// default ciphersuite and has to be listed explicitly to trial it.
 pub(super) fn default_ciphersuites() -> Vec<Ciphersuite> {
     vec![
         Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
@@ -249,6 +271,7 @@ mod tests {
             Ciphersuite::MLS_256_DHKEMP521_AES256GCM_SHA512_P521.into(),
             Ciphersuite::MLS_256_DHKEMX448_CHACHA20POLY1305_SHA512_Ed448.into(),
             Ciphersuite::MLS_256_DHKEMP384_AES256GCM_SHA384_P384.into(),
+            Ciphersuite::MLS_256_XWING_CHACHA20POLY1305_SHA256_Ed25519.into(),
             VerifiableCiphersuite::new(0x0000),
             VerifiableCiphersuite::new(0x0A0A),
             VerifiableCiphersuite::new(0x7A7A),
@@ -285,4 +308,19 @@ mod tests {
 
         assert_eq!(expected, got);
     }
+
+    #[test]
+    fn that_hybrid_ciphersuite_is_not_a_default() {
+        let hybrid = Ciphersuite::MLS_256_XWING_CHACHA20POLY1305_SHA256_Ed25519;
+
+        assert!(!Capabilities::default()
+            .ciphersuites()
+            .contains(&VerifiableCiphersuite::from(hybrid)));
+
+        let capabilities = Capabilities::new(None, Some(&[hybrid]), None, None, None);
+        assert_eq!(
+            capabilities.ciphersuites(),
+            &[VerifiableCiphersuite::from(hybrid)]
+        );
+    }
 }
@@ -108,6 +108,7 @@ fn that_commit_secret_is_derived_from_end_of_update_path_not_root(
             &alice.provider,
             &alice.credential_with_key_and_signer.signer,
             &[bob.key_package, charlie.key_package, dave.key_package],
+            &AcceptAllCredentials,
         )
         .expect("Adding members failed.");
 
@@ -123,6 +124,7 @@ fn that_commit_secret_is_derived_from_end_of_update_path_not_root(
             &mls_group_config,
             welcome.into_welcome().unwrap(),
             None,
+            &AcceptAllCredentials,
         )
         .expect("Joining the group failed.")
     };
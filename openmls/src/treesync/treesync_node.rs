@@ -10,6 +10,7 @@ use tls_codec::VLByteSlice;
 use crate::{
     binary_tree::array_representation::{tree::TreeNode, LeafNodeIndex},
     error::LibraryError,
+    persistence::Persist,
 };
 
 use super::{hashes::TreeHashInput, LeafNode, Node, ParentNode};
@@ -108,6 +109,18 @@ impl From<TreeSyncLeafNode> for Option<Node> {
     }
 }
 
+impl Persist for TreeSyncLeafNode {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.node.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            node: Option::restore(bytes)?,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[cfg_attr(test, derive(PartialEq))]
 /// This intermediate struct on top of `Option<Node>` allows us to cache tree
@@ -189,3 +202,15 @@ impl From<TreeSyncParentNode> for Option<Node> {
         tspn.node.map(Node::ParentNode)
     }
 }
+
+impl Persist for TreeSyncParentNode {
+    fn persist(&self, writer: &mut Vec<u8>) -> Result<(), tls_codec::Error> {
+        self.node.persist(writer)
+    }
+
+    fn restore(bytes: &mut &[u8]) -> Result<Self, tls_codec::Error> {
+        Ok(Self {
+            node: Option::restore(bytes)?,
+        })
+    }
+}
@@ -176,7 +176,12 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // === Alice adds Bob ===
     // ANCHOR: alice_adds_bob
     let (mls_message_out, welcome, group_info) = alice_group
-        .add_members(provider, &alice_signature_keys, &[bob_key_package])
+        .add_members(
+            provider,
+            &alice_signature_keys,
+            &[bob_key_package],
+            &AcceptAllCredentials,
+        )
         .expect("Could not add members.");
     // ANCHOR_END: alice_adds_bob
 
@@ -216,12 +221,41 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     assert_eq!(members[0].credential.identity(), b"Alice");
     assert_eq!(members[1].credential.identity(), b"Bob");
 
+    let welcome = welcome.into_welcome().expect("Unexpected message type.");
+
+    // ANCHOR: bob_inspects_welcome
+    let staged_welcome = StagedWelcome::new_from_welcome(
+        provider,
+        &mls_group_config,
+        welcome.clone(),
+        None, // We use the ratchet tree extension, so we don't provide a ratchet tree here
+        &AcceptAllCredentials,
+    )
+    .expect("Error staging group from Welcome");
+
+    // Check who invited Bob to which group
+    let inviter = staged_welcome
+        .welcome_sender()
+        .expect("The welcome sender is not in the group");
+    assert_eq!(inviter.credential().identity(), b"Alice");
+    assert_eq!(
+        staged_welcome.group_context().group_id(),
+        alice_group.group_id()
+    );
+    assert_eq!(staged_welcome.members().count(), 2);
+    // ANCHOR_END: bob_inspects_welcome
+
+    // Bob could join the group with `staged_welcome.into_group(provider)`.
+    // Instead, he discards the staged group and joins directly.
+    drop(staged_welcome);
+
     // ANCHOR: bob_joins_with_welcome
     let mut bob_group = MlsGroup::new_from_welcome(
         provider,
         &mls_group_config,
-        welcome.into_welcome().expect("Unexpected message type."),
+        welcome,
         None, // We use the ratchet tree extension, so we don't provide a ratchet tree here
+        &AcceptAllCredentials,
     )
     .expect("Error joining group from Welcome");
     // ANCHOR_END: bob_joins_with_welcome
@@ -243,6 +277,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         &mls_group_config,
         &[],
         dave_credential,
+        &AcceptAllCredentials,
     )
     .expect("Error joining from external commit");
     dave_group
@@ -281,7 +316,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // ANCHOR: process_message
     let protocol_message: ProtocolMessage = mls_message.into();
     let processed_message = bob_group
-        .process_message(provider, protocol_message)
+        .process_message(provider, protocol_message, &AcceptAllCredentials)
         .expect("Could not process message.");
     // ANCHOR_END: process_message
 
@@ -311,6 +346,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             mls_message_out
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
 
@@ -362,6 +398,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             mls_message_out
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
 
@@ -406,6 +443,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             mls_message_out
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
 
@@ -451,7 +489,12 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     );
 
     let (queued_message, welcome, _group_info) = bob_group
-        .add_members(provider, &bob_signature_keys, &[charlie_key_package])
+        .add_members(
+            provider,
+            &bob_signature_keys,
+            &[charlie_key_package],
+            &AcceptAllCredentials,
+        )
         .unwrap();
 
     let alice_processed_message = alice_group
@@ -460,6 +503,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             queued_message
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
     bob_group
@@ -482,6 +526,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         &mls_group_config,
         welcome.into_welcome().expect("Unexpected message type."),
         Some(bob_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("Error creating group from Welcome");
 
@@ -515,6 +560,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                 .clone()
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
     let _bob_processed_message = bob_group
@@ -523,6 +569,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             queued_message
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
 
@@ -538,6 +585,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                 .clone()
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
     let bob_processed_message = bob_group
@@ -546,6 +594,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             queued_message
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
     charlie_group
@@ -637,6 +686,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                 .clone()
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
 
@@ -660,6 +710,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             mls_message_out
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
     let charlies_leaf_index = charlie_group.own_leaf_index();
@@ -790,6 +841,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             mls_message_out
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
 
@@ -832,14 +884,22 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         .expect("Could not create proposal to add Bob");
     // ANCHOR_END: propose_add
 
+    // ANCHOR: credential_validator
+    // Only accept basic credentials of known clients
+    let credential_validator = |credential: &Credential, _signature_key: &SignaturePublicKey| {
+        credential.credential_type() == CredentialType::Basic
+            && [&b"Alice"[..], b"Bob", b"Charlie"].contains(&credential.identity())
+    };
     let charlie_processed_message = charlie_group
         .process_message(
             provider,
             mls_message_out
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &credential_validator,
         )
         .expect("Could not process message.");
+    // ANCHOR_END: credential_validator
 
     // Check that we received the correct proposals
     // ANCHOR: inspect_add_proposal
@@ -881,6 +941,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             queued_message
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
 
@@ -917,6 +978,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             .into_welcome()
             .expect("Unexpected message type."),
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("Error creating group from Welcome");
 
@@ -948,6 +1010,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             queued_message
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
 
@@ -998,6 +1061,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             queued_message
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
 
@@ -1057,6 +1121,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             queued_message
                 .into_protocol_message()
                 .expect("Unexpected message type"),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
 
@@ -1125,6 +1190,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             proposal
                 .into_protocol_message()
                 .expect("Unexpected message type."),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
     match alice_processed_message.into_content() {
@@ -1147,6 +1213,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
                     .into_welcome()
                     .expect("Unexpected message type."),
                 None,
+                &AcceptAllCredentials,
             )
             .expect("Bob could not join the group");
             assert_eq!(bob_group.members().count(), 2);
@@ -1185,6 +1252,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             proposal
                 .into_protocol_message()
                 .expect("Unexpected message type."),
+            &AcceptAllCredentials,
         )
         .expect("Could not process message.");
     match alice_processed_message.into_content() {
@@ -1216,7 +1284,12 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // Add Bob to the group
     let (_queued_message, welcome, _group_info) = alice_group
-        .add_members(provider, &alice_signature_keys, &[bob_key_package])
+        .add_members(
+            provider,
+            &alice_signature_keys,
+            &[bob_key_package],
+            &AcceptAllCredentials,
+        )
         .expect("Could not add Bob");
 
     // Merge Commit
@@ -1229,6 +1302,7 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         &mls_group_config,
         welcome.into_welcome().expect("Unexpected message type."),
         Some(alice_group.export_ratchet_tree().into()),
+        &AcceptAllCredentials,
     )
     .expect("Could not create group from Welcome");
 
@@ -1248,8 +1322,9 @@ fn book_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
     // Check that the state flag gets reset when saving
     assert_eq!(bob_group.state_changed(), InnerState::Persisted);
 
-    let bob_group =
-        MlsGroup::load(&group_id, provider.key_store()).expect("Could not load group from file");
+    let bob_group = MlsGroup::load(&group_id, provider.key_store())
+        .expect("Could not load group from file")
+        .expect("Group not found");
 
     // Make sure the state is still the same
     assert_eq!(
@@ -1285,7 +1360,7 @@ fn test_empty_input_errors(ciphersuite: Ciphersuite, provider: &impl OpenMlsProv
 
     assert_eq!(
         alice_group
-            .add_members(provider, &alice_signature_keys, &[])
+            .add_members(provider, &alice_signature_keys, &[], &AcceptAllCredentials)
             .expect_err("No EmptyInputError when trying to pass an empty slice to `add_members`."),
         AddMembersError::EmptyInput(EmptyInputError::AddMembers)
     );
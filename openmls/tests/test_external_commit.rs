@@ -93,6 +93,7 @@ fn test_external_commit(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                 .build(),
             b"",
             bob_credential,
+            &AcceptAllCredentials,
         )
         .unwrap();
     }
@@ -116,6 +117,7 @@ fn test_external_commit(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                 .build(),
             b"",
             bob_credential,
+            &AcceptAllCredentials,
         )
         .unwrap_err();
 
@@ -160,6 +162,7 @@ fn test_group_info(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             .build(),
         b"",
         bob_credential,
+        &AcceptAllCredentials,
     )
     .map(|(group, msg, group_info)| (group, MlsMessageIn::from(msg), group_info))
     .unwrap();
@@ -167,7 +170,7 @@ fn test_group_info(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
 
     // let alice process bob's new client
     let msg = alice_group
-        .process_message(provider, msg)
+        .process_message(provider, msg, &AcceptAllCredentials)
         .unwrap()
         .into_content();
     match msg {
@@ -183,7 +186,9 @@ fn test_group_info(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
         .unwrap()
         .into();
 
-    let msg = alice_group.process_message(provider, message).unwrap();
+    let msg = alice_group
+        .process_message(provider, message, &AcceptAllCredentials)
+        .unwrap();
     let decrypted = match msg.into_content() {
         ProcessedMessageContent::ApplicationMessage(msg) => msg.into_bytes(),
         _ => panic!("Not an ApplicationMessage"),
@@ -213,6 +218,7 @@ fn test_group_info(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             .build(),
         b"",
         bob_credential,
+        &AcceptAllCredentials,
     )
     .unwrap();
     bob_group.merge_pending_commit(provider).unwrap();
@@ -95,7 +95,12 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
         .expect("An unexpected error occurred.");
 
         // === Alice adds Bob ===
-        let welcome = match alice_group.add_members(provider, &alice_signer, &[bob_key_package]) {
+        let welcome = match alice_group.add_members(
+            provider,
+            &alice_signer,
+            &[bob_key_package],
+            &AcceptAllCredentials,
+        ) {
             Ok((_, welcome, _)) => welcome,
             Err(e) => panic!("Could not add member to group: {e:?}"),
         };
@@ -136,6 +141,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
             &mls_group_config,
             welcome.into_welcome().expect("Unexpected message type."),
             Some(alice_group.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
         )
         .expect("Error creating group from Welcome");
 
@@ -161,6 +167,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
         let sender = processed_message.credential().clone();
@@ -193,6 +200,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
 
@@ -239,6 +247,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
 
@@ -280,6 +289,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
 
@@ -320,7 +330,12 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
         );
 
         let (queued_message, welcome, _group_info) = bob_group
-            .add_members(provider, &bob_signer, &[charlie_key_package])
+            .add_members(
+                provider,
+                &bob_signer,
+                &[charlie_key_package],
+                &AcceptAllCredentials,
+            )
             .unwrap();
 
         let alice_processed_message = alice_group
@@ -330,6 +345,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
         bob_group
@@ -352,6 +368,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
             &mls_group_config,
             welcome.into_welcome().expect("Unexpected message type."),
             Some(bob_group.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
         )
         .expect("Error creating group from Welcome");
 
@@ -384,6 +401,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
         let _bob_processed_message = bob_group
@@ -393,6 +411,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
 
@@ -408,6 +427,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
         let bob_processed_message = bob_group
@@ -417,6 +437,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
         charlie_group
@@ -484,6 +505,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
         let bob_processed_message = bob_group
@@ -493,6 +515,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
         charlie_group
@@ -593,6 +616,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
 
@@ -630,6 +654,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
 
@@ -670,6 +695,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
 
@@ -706,6 +732,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                 .into_welcome()
                 .expect("Unexpected message type."),
             Some(alice_group.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
         )
         .expect("Error creating group from Welcome");
 
@@ -738,6 +765,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
         let sender = bob_processed_message.credential().clone();
@@ -770,6 +798,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
 
@@ -826,6 +855,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
                     .clone()
                     .into_protocol_message()
                     .expect("Unexpected message type"),
+                &AcceptAllCredentials,
             )
             .expect("Could not process message.");
 
@@ -874,7 +904,12 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
 
         // Add Bob to the group
         let (_queued_message, welcome, _group_info) = alice_group
-            .add_members(provider, &alice_signer, &[bob_key_package])
+            .add_members(
+                provider,
+                &alice_signer,
+                &[bob_key_package],
+                &AcceptAllCredentials,
+            )
             .expect("Could not add Bob");
 
         // Test saving & loading the group state when there is a pending commit
@@ -883,7 +918,8 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
             .expect("Could not save group state.");
 
         let _test_group = MlsGroup::load(&group_id, provider.key_store())
-            .expect("Could not load the group state.");
+            .expect("Could not load the group state.")
+            .expect("Group not found.");
 
         // Merge Commit
         alice_group
@@ -895,6 +931,7 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
             &mls_group_config,
             welcome.into_welcome().expect("Unexpected message type."),
             Some(alice_group.export_ratchet_tree().into()),
+            &AcceptAllCredentials,
         )
         .expect("Could not create group from Welcome");
 
@@ -914,7 +951,8 @@ fn mls_group_operations(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvide
         assert_eq!(bob_group.state_changed(), InnerState::Persisted);
 
         let bob_group = MlsGroup::load(&group_id, provider.key_store())
-            .expect("Could not load group from file");
+            .expect("Could not load group from file")
+            .expect("Group not found");
 
         // Make sure the state is still the same
         assert_eq!(
@@ -988,6 +1026,7 @@ fn addition_order(ciphersuite: Ciphersuite, provider: &impl OpenMlsProvider) {
             provider,
             &alice_signer,
             &[bob_key_package, charlie_key_package],
+            &AcceptAllCredentials,
         ) {
             Ok((_, welcome, _)) => welcome,
             Err(e) => panic!("Could not add member to group: {e:?}"),
@@ -1062,7 +1101,7 @@ fn test_empty_input_errors(ciphersuite: Ciphersuite, provider: &impl OpenMlsProv
 
     assert_eq!(
         alice_group
-            .add_members(provider, &alice_signer, &[])
+            .add_members(provider, &alice_signer, &[], &AcceptAllCredentials)
             .expect_err("No EmptyInputError when trying to pass an empty slice to `add_members`."),
         AddMembersError::EmptyInput(EmptyInputError::AddMembers)
     );
@@ -1126,7 +1165,12 @@ fn mls_group_ratchet_tree_extension(ciphersuite: Ciphersuite, provider: &impl Op
 
         // === Alice adds Bob ===
         let (_queued_message, welcome, _group_info) = alice_group
-            .add_members(provider, &alice_signer, &[bob_key_package.clone()])
+            .add_members(
+                provider,
+                &alice_signer,
+                &[bob_key_package.clone()],
+                &AcceptAllCredentials,
+            )
             .unwrap();
 
         // === Bob joins using the ratchet tree extension ===
@@ -1135,6 +1179,7 @@ fn mls_group_ratchet_tree_extension(ciphersuite: Ciphersuite, provider: &impl Op
             &mls_group_config,
             welcome.into_welcome().expect("Unexpected message type."),
             None,
+            &AcceptAllCredentials,
         )
         .expect("Error creating group from Welcome");
 
@@ -1178,7 +1223,12 @@ fn mls_group_ratchet_tree_extension(ciphersuite: Ciphersuite, provider: &impl Op
 
         // === Alice adds Bob ===
         let (_queued_message, welcome, _group_info) = alice_group
-            .add_members(provider, &alice_signer, &[bob_key_package])
+            .add_members(
+                provider,
+                &alice_signer,
+                &[bob_key_package],
+                &AcceptAllCredentials,
+            )
             .unwrap();
 
         // === Bob tries to join without the ratchet tree extension ===
@@ -1187,6 +1237,7 @@ fn mls_group_ratchet_tree_extension(ciphersuite: Ciphersuite, provider: &impl Op
             &mls_group_config,
             welcome.into_welcome().expect("Unexpected message type."),
             None,
+            &AcceptAllCredentials,
         )
         .expect_err("Could join a group without a ratchet tree");
 
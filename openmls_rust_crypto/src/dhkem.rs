@@ -0,0 +1,252 @@
+//! # DH-KEM
+//!
+//! The DH-KEMs of RFC 9180 on P-384, P-521 and x448, which are not implemented
+//! by `hpke-rs`.
+
+use openmls_traits::types::{CryptoError, HashType, HpkeKeyPair};
+use p384::elliptic_curve::sec1::ToEncodedPoint;
+use rand::{CryptoRng, RngCore};
+
+use crate::{
+    hpke_base::{labeled_expand, labeled_extract},
+    RustCrypto,
+};
+
+/// A DH-KEM of RFC 9180.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum DhKem {
+    P384,
+    P521,
+    X448,
+}
+
+impl DhKem {
+    /// The KEM id of RFC 9180.
+    fn kem_id(&self) -> u16 {
+        match self {
+            DhKem::P384 => 0x0011,
+            DhKem::P521 => 0x0012,
+            DhKem::X448 => 0x0021,
+        }
+    }
+
+    /// The hash function of the KDF of the KEM.
+    fn hash(&self) -> HashType {
+        match self {
+            DhKem::P384 => HashType::Sha2_384,
+            DhKem::P521 | DhKem::X448 => HashType::Sha2_512,
+        }
+    }
+
+    /// The length of an encoded private key `Nsk`.
+    fn private_key_len(&self) -> usize {
+        match self {
+            DhKem::P384 => 48,
+            DhKem::P521 => 66,
+            DhKem::X448 => 56,
+        }
+    }
+
+    /// The `suite_id` of the KEM for the labeled KDF functions.
+    fn suite_id(&self) -> Vec<u8> {
+        let mut suite_id = b"KEM".to_vec();
+        suite_id.extend_from_slice(&self.kem_id().to_be_bytes());
+        suite_id
+    }
+
+    /// Derive a key pair from `ikm` as specified in Section 7.1.3 of RFC 9180.
+    pub(crate) fn derive_key_pair(
+        &self,
+        crypto: &RustCrypto,
+        ikm: &[u8],
+    ) -> Result<HpkeKeyPair, CryptoError> {
+        let hash = self.hash();
+        let suite_id = self.suite_id();
+        let dkp_prk = labeled_extract(crypto, hash, &suite_id, &[], b"dkp_prk", ikm)?;
+
+        let private = match self {
+            DhKem::X448 => labeled_expand(
+                crypto,
+                hash,
+                &suite_id,
+                &dkp_prk,
+                b"sk",
+                &[],
+                self.private_key_len(),
+            )?,
+            DhKem::P384 | DhKem::P521 => {
+                // The bitmask that is applied to the first byte of a
+                // candidate.
+                let bitmask = match self {
+                    DhKem::P521 => 0x01,
+                    _ => 0xFF,
+                };
+                let mut private = None;
+                for counter in 0..=u8::MAX {
+                    let mut candidate = labeled_expand(
+                        crypto,
+                        hash,
+                        &suite_id,
+                        &dkp_prk,
+                        b"candidate",
+                        &[counter],
+                        self.private_key_len(),
+                    )?;
+                    candidate[0] &= bitmask;
+                    // Candidates that are zero or not smaller than the order
+                    // of the curve are rejected.
+                    if self.public_key(&candidate).is_ok() {
+                        private = Some(candidate);
+                        break;
+                    }
+                }
+                private.ok_or(CryptoError::CryptoLibraryError)?
+            }
+        };
+
+        let public = self.public_key(&private)?;
+        Ok(HpkeKeyPair {
+            private: private.into(),
+            public,
+        })
+    }
+
+    /// Returns the encoded public key of the private key `sk`.
+    fn public_key(&self, sk: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        match self {
+            DhKem::P384 => {
+                let sk =
+                    p384::SecretKey::from_slice(sk).map_err(|_| CryptoError::CryptoLibraryError)?;
+                Ok(sk.public_key().to_encoded_point(false).as_bytes().to_vec())
+            }
+            DhKem::P521 => {
+                let sk =
+                    p521::SecretKey::from_slice(sk).map_err(|_| CryptoError::CryptoLibraryError)?;
+                Ok(sk.public_key().to_encoded_point(false).as_bytes().to_vec())
+            }
+            DhKem::X448 => {
+                let sk = x448::Secret::from_bytes(sk).ok_or(CryptoError::CryptoLibraryError)?;
+                Ok(x448::PublicKey::from(&sk).as_bytes().to_vec())
+            }
+        }
+    }
+
+    /// Returns the Diffie-Hellman shared secret of `sk` and `pk`.
+    fn dh(&self, sk: &[u8], pk: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        match self {
+            DhKem::P384 => {
+                let sk =
+                    p384::SecretKey::from_slice(sk).map_err(|_| CryptoError::CryptoLibraryError)?;
+                let pk = p384::PublicKey::from_sec1_bytes(pk)
+                    .map_err(|_| CryptoError::CryptoLibraryError)?;
+                let dh = p384::ecdh::diffie_hellman(sk.to_nonzero_scalar(), pk.as_affine());
+                Ok(dh.raw_secret_bytes().to_vec())
+            }
+            DhKem::P521 => {
+                let sk =
+                    p521::SecretKey::from_slice(sk).map_err(|_| CryptoError::CryptoLibraryError)?;
+                let pk = p521::PublicKey::from_sec1_bytes(pk)
+                    .map_err(|_| CryptoError::CryptoLibraryError)?;
+                let dh = p521::ecdh::diffie_hellman(sk.to_nonzero_scalar(), pk.as_affine());
+                Ok(dh.raw_secret_bytes().to_vec())
+            }
+            DhKem::X448 => {
+                let sk = x448::Secret::from_bytes(sk).ok_or(CryptoError::CryptoLibraryError)?;
+                let pk = x448::PublicKey::from_bytes(pk).ok_or(CryptoError::CryptoLibraryError)?;
+                // Fails if the public key has a small order.
+                let dh = sk
+                    .as_diffie_hellman(&pk)
+                    .ok_or(CryptoError::CryptoLibraryError)?;
+                Ok(dh.as_bytes().to_vec())
+            }
+        }
+    }
+
+    /// `ExtractAndExpand` of RFC 9180.
+    fn extract_and_expand(
+        &self,
+        crypto: &RustCrypto,
+        dh: &[u8],
+        kem_context: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let hash = self.hash();
+        let suite_id = self.suite_id();
+        let eae_prk = labeled_extract(crypto, hash, &suite_id, &[], b"eae_prk", dh)?;
+        labeled_expand(
+            crypto,
+            hash,
+            &suite_id,
+            &eae_prk,
+            b"shared_secret",
+            kem_context,
+            hash.size(),
+        )
+    }
+
+    /// Returns a fresh shared secret and its encapsulation to `pk_r`.
+    pub(crate) fn encapsulate(
+        &self,
+        crypto: &RustCrypto,
+        rng: &mut (impl CryptoRng + RngCore),
+        pk_r: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+        let mut ikm = vec![0u8; self.private_key_len()];
+        rng.try_fill_bytes(&mut ikm)
+            .map_err(|_| CryptoError::InsufficientRandomness)?;
+        let ephemeral = self.derive_key_pair(crypto, &ikm)?;
+
+        let dh = self.dh(&ephemeral.private, pk_r)?;
+        let enc = ephemeral.public;
+        let kem_context = [enc.as_slice(), pk_r].concat();
+        let shared_secret = self.extract_and_expand(crypto, &dh, &kem_context)?;
+
+        Ok((shared_secret, enc))
+    }
+
+    /// Returns the shared secret encapsulated in `enc` for `sk_r`.
+    pub(crate) fn decapsulate(
+        &self,
+        crypto: &RustCrypto,
+        enc: &[u8],
+        sk_r: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        let dh = self.dh(sk_r, enc)?;
+        let pk_r = self.public_key(sk_r)?;
+        let kem_context = [enc, pk_r.as_slice()].concat();
+        self.extract_and_expand(crypto, &dh, &kem_context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_vectors::{FixedRng, HPKE_TEST_VECTORS};
+
+    #[test]
+    fn rfc9180_test_vectors() {
+        let crypto = RustCrypto::default();
+        for vector in HPKE_TEST_VECTORS {
+            let kem = vector.kem;
+            let hex = |value: &str| hex::decode(value).unwrap();
+
+            let key_pair = kem.derive_key_pair(&crypto, &hex(vector.ikm_r)).unwrap();
+            assert_eq!(key_pair.private.to_vec(), hex(vector.sk_rm));
+            assert_eq!(key_pair.public, hex(vector.pk_rm));
+
+            let (shared_secret, enc) = kem
+                .encapsulate(
+                    &crypto,
+                    &mut FixedRng(hex(vector.ikm_e)),
+                    &hex(vector.pk_rm),
+                )
+                .unwrap();
+            assert_eq!(enc, hex(vector.enc));
+            assert_eq!(shared_secret, hex(vector.shared_secret));
+
+            let shared_secret = kem
+                .decapsulate(&crypto, &hex(vector.enc), &hex(vector.sk_rm))
+                .unwrap();
+            assert_eq!(shared_secret, hex(vector.shared_secret));
+        }
+    }
+}
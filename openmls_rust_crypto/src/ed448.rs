@@ -0,0 +1,302 @@
+//! # Ed448
+//!
+//! Ed448 signatures without context as specified in RFC 8032. The curve
+//! arithmetic is done by [`ed448_goldilocks`], this module implements the
+//! encoding of points and scalars, the key derivation and the signature
+//! scheme on top of it.
+//!
+//! Private keys are the 57 byte seeds of RFC 8032 and public keys and
+//! signatures are encoded as in RFC 8032, i.e., public keys have 57 bytes and
+//! signatures 114 bytes.
+
+use ed448_goldilocks::{
+    curve::edwards::{CompressedEdwardsY, ExtendedPoint},
+    Scalar,
+};
+use openmls_traits::types::CryptoError;
+use rand::{CryptoRng, RngCore};
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+
+/// The length of private keys, i.e., seeds, and public keys.
+pub const KEY_LENGTH: usize = 57;
+
+/// The length of signatures.
+pub const SIGNATURE_LENGTH: usize = 2 * KEY_LENGTH;
+
+/// The prefix `dom4(0, "")` of the hashes of Ed448 without context.
+const DOM4: &[u8] = b"SigEd448\x00\x00";
+
+/// The number of bytes that are added per step when reducing a byte string
+/// modulo the group order. 28 bytes are less than the group order and their
+/// base fits into a [`Scalar`].
+const CHUNK_LENGTH: usize = 28;
+
+/// Generates a fresh key pair and returns the private and the public key.
+pub fn generate_key_pair(rng: &mut (impl RngCore + CryptoRng)) -> (Vec<u8>, Vec<u8>) {
+    let mut private = vec![0u8; KEY_LENGTH];
+    rng.fill_bytes(&mut private);
+    let (s, _) = expand(&private);
+    let public = encode_point(&ExtendedPoint::generator().scalar_mul(&s)).to_vec();
+    (private, public)
+}
+
+/// Derives the public key of the `private` key.
+pub fn public_key(private: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if private.len() != KEY_LENGTH {
+        return Err(CryptoError::CryptoLibraryError);
+    }
+    let (s, _) = expand(private);
+    Ok(encode_point(&ExtendedPoint::generator().scalar_mul(&s)).to_vec())
+}
+
+/// Signs the `message` with the `private` key.
+pub fn sign(private: &[u8], message: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if private.len() != KEY_LENGTH {
+        return Err(CryptoError::CryptoLibraryError);
+    }
+    let (s, prefix) = expand(private);
+    let public = encode_point(&ExtendedPoint::generator().scalar_mul(&s));
+
+    let r = hash_to_scalar(&[&prefix, message]);
+    let r_point = encode_point(&ExtendedPoint::generator().scalar_mul(&r));
+    let k = hash_to_scalar(&[&r_point, &public, message]);
+
+    let mut signature = Vec::with_capacity(SIGNATURE_LENGTH);
+    signature.extend_from_slice(&r_point);
+    signature.extend_from_slice(&(r + k * s).to_bytes());
+    signature.push(0);
+    Ok(signature)
+}
+
+/// Verifies the `signature` of the `message` with the `public` key.
+///
+/// Public keys and signatures with non-canonical encodings are rejected.
+pub fn verify(public: &[u8], message: &[u8], signature: &[u8]) -> Result<(), CryptoError> {
+    let public: &[u8; KEY_LENGTH] = public
+        .try_into()
+        .map_err(|_| CryptoError::CryptoLibraryError)?;
+    if signature.len() != SIGNATURE_LENGTH {
+        return Err(CryptoError::InvalidSignature);
+    }
+    let (r_bytes, s_bytes) = signature.split_at(KEY_LENGTH);
+    let r_bytes: &[u8; KEY_LENGTH] = r_bytes
+        .try_into()
+        .map_err(|_| CryptoError::InvalidSignature)?;
+    let a = decode_point(public).ok_or(CryptoError::CryptoLibraryError)?;
+    let r = decode_point(r_bytes).ok_or(CryptoError::InvalidSignature)?;
+    let s = decode_scalar(s_bytes).ok_or(CryptoError::InvalidSignature)?;
+    let k = hash_to_scalar(&[r_bytes, public, message]);
+
+    // The cofactored equation [4][S]B = [4]R + [4][k]A of RFC 8032.
+    let left = ExtendedPoint::generator().scalar_mul(&(s * Scalar::from(4)));
+    let right = r.add(&a.scalar_mul(&k)).scalar_mul(&Scalar::from(4));
+    if left == right {
+        Ok(())
+    } else {
+        Err(CryptoError::InvalidSignature)
+    }
+}
+
+/// Expands the `private` key into the secret scalar and the prefix that is
+/// used to derive the nonces.
+fn expand(private: &[u8]) -> (Scalar, [u8; KEY_LENGTH]) {
+    let mut h = [0u8; 2 * KEY_LENGTH];
+    let mut shake = Shake256::default();
+    shake.update(private);
+    shake.finalize_xof().read(&mut h);
+
+    let mut s = [0u8; KEY_LENGTH];
+    s.copy_from_slice(&h[..KEY_LENGTH]);
+    s[0] &= 0b1111_1100;
+    s[KEY_LENGTH - 2] |= 0b1000_0000;
+    s[KEY_LENGTH - 1] = 0;
+
+    let mut prefix = [0u8; KEY_LENGTH];
+    prefix.copy_from_slice(&h[KEY_LENGTH..]);
+    (reduce(&s), prefix)
+}
+
+/// Hashes the concatenation of `inputs`, prefixed with [`DOM4`], with
+/// SHAKE256 to a scalar.
+fn hash_to_scalar(inputs: &[&[u8]]) -> Scalar {
+    let mut shake = Shake256::default();
+    shake.update(DOM4);
+    for input in inputs {
+        shake.update(input);
+    }
+    let mut h = [0u8; 2 * KEY_LENGTH];
+    shake.finalize_xof().read(&mut h);
+    reduce(&h)
+}
+
+/// Reduces the little-endian integer `bytes` modulo the group order.
+fn reduce(bytes: &[u8]) -> Scalar {
+    let mut base = [0u8; 56];
+    base[CHUNK_LENGTH] = 1;
+    let base = Scalar::from_bytes(base);
+
+    bytes
+        .chunks(CHUNK_LENGTH)
+        .rev()
+        .fold(Scalar::zero(), |acc, chunk| {
+            let mut digit = [0u8; 56];
+            digit[..chunk.len()].copy_from_slice(chunk);
+            acc * base + Scalar::from_bytes(digit)
+        })
+}
+
+/// Decodes a canonically encoded scalar that is smaller than the group order.
+fn decode_scalar(bytes: &[u8]) -> Option<Scalar> {
+    let (last, bytes) = bytes.split_last()?;
+    let bytes: [u8; 56] = bytes.try_into().ok()?;
+    let s = Scalar::from_bytes(bytes);
+    // Adding zero subtracts the group order once from scalars that aren't
+    // reduced.
+    (*last == 0 && (s + Scalar::zero()).to_bytes() == bytes).then_some(s)
+}
+
+/// Encodes a point as in RFC 8032, i.e., the y-coordinate followed by the
+/// sign of the x-coordinate in the most significant bit.
+fn encode_point(point: &ExtendedPoint) -> [u8; KEY_LENGTH] {
+    let mut bytes = point.compress().0;
+    // `compress()` stores the sign in the least significant bit of the last
+    // byte.
+    bytes[KEY_LENGTH - 1] <<= 7;
+    bytes
+}
+
+/// Decodes a point and rejects non-canonical encodings.
+///
+/// The points with x = 0, i.e., the points of order one and two, are rejected
+/// as well, like small order points in the strict verification of Ed25519.
+fn decode_point(bytes: &[u8; KEY_LENGTH]) -> Option<ExtendedPoint> {
+    // `decompress()` doesn't choose the root with the right sign for the
+    // x-coordinate, so the sign is set on the encoding of the point.
+    let mut y = *bytes;
+    y[KEY_LENGTH - 1] = 0;
+    let mut point = CompressedEdwardsY(y).decompress()?;
+    if encode_point(&point)[KEY_LENGTH - 1] != bytes[KEY_LENGTH - 1] {
+        point = point.negate();
+    }
+    (encode_point(&point) == *bytes).then_some(point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A test vector of Ed448 without context. The values are hex encoded.
+    struct Ed448TestVector {
+        private: &'static str,
+        public: &'static str,
+        message: &'static str,
+        signature: &'static str,
+    }
+
+    /// The test vectors of section 7.4 of RFC 8032 without context.
+    const ED448_TEST_VECTORS: &[Ed448TestVector] = &[
+        Ed448TestVector {
+            private: "6c82a562cb808d10d632be89c8513ebf6c929f34ddfa8c9f63c9960ef6e348a3528c8a3fcc2f044e39a3fc5b94492f8f032e7549a20098f95b",
+            public: "5fd7449b59b461fd2ce787ec616ad46a1da1342485a70e1f8a0ea75d80e96778edf124769b46c7061bd6783df1e50f6cd1fa1abeafe8256180",
+            message: "",
+            signature: "533a37f6bbe457251f023c0d88f976ae2dfb504a843e34d2074fd823d41a591f2b233f034f628281f2fd7a22ddd47d7828c59bd0a21bfd3980ff0d2028d4b18a9df63e006c5d1c2d345b925d8dc00b4104852db99ac5c7cdda8530a113a0f4dbb61149f05a7363268c71d95808ff2e652600",
+        },
+        Ed448TestVector {
+            private: "c4eab05d357007c632f3dbb48489924d552b08fe0c353a0d4a1f00acda2c463afbea67c5e8d2877c5e3bc397a659949ef8021e954e0a12274e",
+            public: "43ba28f430cdff456ae531545f7ecd0ac834a55d9358c0372bfa0c6c6798c0866aea01eb00742802b8438ea4cb82169c235160627b4c3a9480",
+            message: "03",
+            signature: "26b8f91727bd62897af15e41eb43c377efb9c610d48f2335cb0bd0087810f4352541b143c4b981b7e18f62de8ccdf633fc1bf037ab7cd779805e0dbcc0aae1cbcee1afb2e027df36bc04dcecbf154336c19f0af7e0a6472905e799f1953d2a0ff3348ab21aa4adafd1d234441cf807c03a00",
+        },
+        Ed448TestVector {
+            private: "cd23d24f714274e744343237b93290f511f6425f98e64459ff203e8985083ffdf60500553abc0e05cd02184bdb89c4ccd67e187951267eb328",
+            public: "dcea9e78f35a1bf3499a831b10b86c90aac01cd84b67a0109b55a36e9328b1e365fce161d71ce7131a543ea4cb5f7e9f1d8b00696447001400",
+            message: "0c3e544074ec63b0265e0c",
+            signature: "1f0a8888ce25e8d458a21130879b840a9089d999aaba039eaf3e3afa090a09d389dba82c4ff2ae8ac5cdfb7c55e94d5d961a29fe0109941e00b8dbdeea6d3b051068df7254c0cdc129cbe62db2dc957dbb47b51fd3f213fb8698f064774250a5028961c9bf8ffd973fe5d5c206492b140e00",
+        },
+        Ed448TestVector {
+            private: "258cdd4ada32ed9c9ff54e63756ae582fb8fab2ac721f2c8e676a72768513d939f63dddb55609133f29adf86ec9929dccb52c1c5fd2ff7e21b",
+            public: "3ba16da0c6f2cc1f30187740756f5e798d6bc5fc015d7c63cc9510ee3fd44adc24d8e968b6e46e6f94d19b945361726bd75e149ef09817f580",
+            message: "64a65f3cdedcdd66811e2915",
+            signature: "7eeeab7c4e50fb799b418ee5e3197ff6bf15d43a14c34389b59dd1a7b1b85b4ae90438aca634bea45e3a2695f1270f07fdcdf7c62b8efeaf00b45c2c96ba457eb1a8bf075a3db28e5c24f6b923ed4ad747c3c9e03c7079efb87cb110d3a99861e72003cbae6d6b8b827e4e6c143064ff3c00",
+        },
+    ];
+
+    #[test]
+    fn rfc8032_test_vectors() {
+        let hex = |value: &str| hex::decode(value).unwrap();
+        for vector in ED448_TEST_VECTORS {
+            let private = hex(vector.private);
+            let public = hex(vector.public);
+            let message = hex(vector.message);
+            let signature = hex(vector.signature);
+
+            assert_eq!(public_key(&private).unwrap(), public);
+            assert_eq!(sign(&private, &message).unwrap(), signature);
+            verify(&public, &message, &signature).unwrap();
+
+            let mut other_message = message.clone();
+            other_message.push(0);
+            assert_eq!(
+                verify(&public, &other_message, &signature),
+                Err(CryptoError::InvalidSignature)
+            );
+        }
+    }
+
+    #[test]
+    fn non_canonical_signatures() {
+        let vector = &ED448_TEST_VECTORS[0];
+        let public = hex::decode(vector.public).unwrap();
+        let signature = hex::decode(vector.signature).unwrap();
+
+        // S + L encodes the same scalar as S, but isn't canonical.
+        let (r, s) = signature.split_at(KEY_LENGTH);
+        let s = decode_scalar(s).unwrap();
+        let order_minus_one = (Scalar::zero() - Scalar::one()).to_bytes();
+        let mut carry = 1u16;
+        let mut s_plus_order = Vec::with_capacity(KEY_LENGTH);
+        for (a, b) in s.to_bytes().iter().zip(order_minus_one.iter()) {
+            let sum = *a as u16 + *b as u16 + carry;
+            s_plus_order.push(sum as u8);
+            carry = sum >> 8;
+        }
+        s_plus_order.push(carry as u8);
+        let forged = [r, &s_plus_order].concat();
+        assert_eq!(
+            verify(&public, b"", &forged),
+            Err(CryptoError::InvalidSignature)
+        );
+
+        // The points with x = 0 are rejected.
+        let identity = encode_point(&ExtendedPoint::identity());
+        assert!(decode_point(&identity).is_none());
+        // The point (0, -1) of order two.
+        let mut order_two = [0xff; KEY_LENGTH];
+        order_two[0] = 0xfe;
+        order_two[28] = 0xfe;
+        order_two[KEY_LENGTH - 1] = 0;
+        assert!(decode_point(&order_two).is_none());
+        order_two[KEY_LENGTH - 1] |= 0x80;
+        assert!(decode_point(&order_two).is_none());
+
+        // The sign of the x-coordinate is decoded.
+        let public: [u8; KEY_LENGTH] = public.try_into().unwrap();
+        let mut negated = public;
+        negated[KEY_LENGTH - 1] ^= 0x80;
+        assert_eq!(
+            decode_point(&negated),
+            decode_point(&public).map(|point| point.negate())
+        );
+    }
+
+    #[test]
+    fn generated_keys() {
+        let (private, public) = generate_key_pair(&mut rand::thread_rng());
+        assert_eq!(public_key(&private).unwrap(), public);
+        let signature = sign(&private, b"message").unwrap();
+        verify(&public, b"message", &signature).unwrap();
+    }
+}
@@ -0,0 +1,387 @@
+//! # HPKE Base Mode
+//!
+//! `hpke-rs` only implements the DH-KEMs on x25519 and P-256. HPKE (RFC 9180)
+//! in base mode with the KEMs of the remaining ciphersuites is implemented
+//! here, using the KDFs and AEADs of the [`RustCrypto`] provider.
+//!
+//! Only single-shot encryption and the secret export are implemented, because
+//! that is all OpenMLS needs.
+
+use openmls_traits::{
+    crypto::OpenMlsCrypto,
+    types::{
+        AeadType, CryptoError, ExporterSecret, HashType, HpkeAeadType, HpkeCiphertext, HpkeConfig,
+        HpkeKdfType, HpkeKemType, HpkeKeyPair,
+    },
+};
+use rand::{CryptoRng, RngCore};
+
+use crate::{dhkem::DhKem, RustCrypto};
+
+/// The HPKE mode `mode_base`.
+const MODE_BASE: u8 = 0x00;
+
+/// A KEM that is implemented by this crate instead of `hpke-rs`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Kem {
+    Dh(DhKem),
+    #[cfg(feature = "xwing")]
+    XWing,
+}
+
+impl Kem {
+    /// Returns the [`Kem`] for `kem`, or `None` if it is implemented by
+    /// `hpke-rs`.
+    pub(crate) fn from_kem_type(kem: HpkeKemType) -> Option<Self> {
+        match kem {
+            HpkeKemType::DhKemP384 => Some(Kem::Dh(DhKem::P384)),
+            HpkeKemType::DhKemP521 => Some(Kem::Dh(DhKem::P521)),
+            HpkeKemType::DhKem448 => Some(Kem::Dh(DhKem::X448)),
+            #[cfg(feature = "xwing")]
+            HpkeKemType::XWingDraft06 => Some(Kem::XWing),
+            _ => None,
+        }
+    }
+
+    /// Derive a key pair from `ikm`.
+    pub(crate) fn derive_key_pair(
+        &self,
+        crypto: &RustCrypto,
+        ikm: &[u8],
+    ) -> Result<HpkeKeyPair, CryptoError> {
+        match self {
+            Kem::Dh(kem) => kem.derive_key_pair(crypto, ikm),
+            #[cfg(feature = "xwing")]
+            Kem::XWing => crate::xwing::derive_key_pair(ikm),
+        }
+    }
+
+    /// Returns a fresh shared secret and its encapsulation to `pk_r`.
+    fn encapsulate(
+        &self,
+        crypto: &RustCrypto,
+        rng: &mut (impl CryptoRng + RngCore),
+        pk_r: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+        match self {
+            Kem::Dh(kem) => kem.encapsulate(crypto, rng, pk_r),
+            #[cfg(feature = "xwing")]
+            Kem::XWing => crate::xwing::encapsulate(pk_r, rng),
+        }
+    }
+
+    /// Returns the shared secret encapsulated in `enc` for `sk_r`.
+    fn decapsulate(
+        &self,
+        crypto: &RustCrypto,
+        enc: &[u8],
+        sk_r: &[u8],
+    ) -> Result<Vec<u8>, CryptoError> {
+        match self {
+            Kem::Dh(kem) => kem.decapsulate(crypto, enc, sk_r),
+            #[cfg(feature = "xwing")]
+            Kem::XWing => crate::xwing::decapsulate(enc, sk_r),
+        }
+    }
+}
+
+/// `LabeledExtract` of RFC 9180 with the given `suite_id`.
+pub(crate) fn labeled_extract(
+    crypto: &RustCrypto,
+    hash: HashType,
+    suite_id: &[u8],
+    salt: &[u8],
+    label: &[u8],
+    ikm: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    let labeled_ikm = [b"HPKE-v1".as_slice(), suite_id, label, ikm].concat();
+    Ok(crypto
+        .hkdf_extract(hash, salt, &labeled_ikm)?
+        .as_slice()
+        .to_vec())
+}
+
+/// `LabeledExpand` of RFC 9180 with the given `suite_id`.
+pub(crate) fn labeled_expand(
+    crypto: &RustCrypto,
+    hash: HashType,
+    suite_id: &[u8],
+    prk: &[u8],
+    label: &[u8],
+    info: &[u8],
+    length: usize,
+) -> Result<Vec<u8>, CryptoError> {
+    let length_bytes = u16::try_from(length)
+        .map_err(|_| CryptoError::HkdfOutputLengthInvalid)?
+        .to_be_bytes();
+    let labeled_info = [
+        length_bytes.as_slice(),
+        b"HPKE-v1".as_slice(),
+        suite_id,
+        label,
+        info,
+    ]
+    .concat();
+    Ok(crypto
+        .hkdf_expand(hash, prk, &labeled_info, length)?
+        .as_slice()
+        .to_vec())
+}
+
+/// The HPKE context of a sender or receiver after the key schedule.
+struct Context {
+    aead: Option<AeadType>,
+    key: Vec<u8>,
+    base_nonce: Vec<u8>,
+    exporter_secret: Vec<u8>,
+    hash: HashType,
+    suite_id: Vec<u8>,
+}
+
+impl Context {
+    /// Run the HPKE key schedule in base mode for `shared_secret`.
+    fn key_schedule(
+        crypto: &RustCrypto,
+        config: &HpkeConfig,
+        shared_secret: &[u8],
+        info: &[u8],
+    ) -> Result<Self, CryptoError> {
+        let hash = match config.1 {
+            HpkeKdfType::HkdfSha256 => HashType::Sha2_256,
+            HpkeKdfType::HkdfSha384 => HashType::Sha2_384,
+            HpkeKdfType::HkdfSha512 => HashType::Sha2_512,
+        };
+        let aead = match config.2 {
+            HpkeAeadType::AesGcm128 => Some(AeadType::Aes128Gcm),
+            HpkeAeadType::AesGcm256 => Some(AeadType::Aes256Gcm),
+            HpkeAeadType::ChaCha20Poly1305 => Some(AeadType::ChaCha20Poly1305),
+            HpkeAeadType::Export => None,
+        };
+
+        let mut suite_id = b"HPKE".to_vec();
+        suite_id.extend_from_slice(&(config.0 as u16).to_be_bytes());
+        suite_id.extend_from_slice(&(config.1 as u16).to_be_bytes());
+        suite_id.extend_from_slice(&(config.2 as u16).to_be_bytes());
+
+        let psk_id_hash = labeled_extract(crypto, hash, &suite_id, &[], b"psk_id_hash", &[])?;
+        let info_hash = labeled_extract(crypto, hash, &suite_id, &[], b"info_hash", info)?;
+        let mut key_schedule_context = vec![MODE_BASE];
+        key_schedule_context.extend_from_slice(&psk_id_hash);
+        key_schedule_context.extend_from_slice(&info_hash);
+
+        let secret = labeled_extract(crypto, hash, &suite_id, shared_secret, b"secret", &[])?;
+        let expand = |label: &[u8], length: usize| {
+            labeled_expand(
+                crypto,
+                hash,
+                &suite_id,
+                &secret,
+                label,
+                &key_schedule_context,
+                length,
+            )
+        };
+        let (key, base_nonce) = match aead {
+            Some(aead) => (
+                expand(b"key", aead.key_size())?,
+                expand(b"base_nonce", aead.nonce_size())?,
+            ),
+            None => (vec![], vec![]),
+        };
+        let exporter_secret = expand(b"exp", hash.size())?;
+
+        Ok(Self {
+            aead,
+            key,
+            base_nonce,
+            exporter_secret,
+            hash,
+            suite_id,
+        })
+    }
+
+    /// Encrypt the first and only message of this context.
+    fn seal(&self, crypto: &RustCrypto, aad: &[u8], ptxt: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let aead = self.aead.ok_or(CryptoError::UnsupportedAeadAlgorithm)?;
+        crypto.aead_encrypt(aead, &self.key, ptxt, &self.base_nonce, aad)
+    }
+
+    /// Decrypt the first and only message of this context.
+    fn open(&self, crypto: &RustCrypto, aad: &[u8], ctxt: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let aead = self.aead.ok_or(CryptoError::UnsupportedAeadAlgorithm)?;
+        crypto
+            .aead_decrypt(aead, &self.key, ctxt, &self.base_nonce, aad)
+            .map_err(|_| CryptoError::HpkeDecryptionError)
+    }
+
+    /// Export a secret of `length` bytes for `exporter_context`.
+    fn export(
+        &self,
+        crypto: &RustCrypto,
+        exporter_context: &[u8],
+        length: usize,
+    ) -> Result<ExporterSecret, CryptoError> {
+        labeled_expand(
+            crypto,
+            self.hash,
+            &self.suite_id,
+            &self.exporter_secret,
+            b"sec",
+            exporter_context,
+            length,
+        )
+        .map(ExporterSecret::from)
+        .map_err(|_| CryptoError::ExporterError)
+    }
+}
+
+/// HPKE single-shot encryption to the public key `pk_r`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn seal(
+    crypto: &RustCrypto,
+    rng: &mut (impl CryptoRng + RngCore),
+    kem: Kem,
+    config: HpkeConfig,
+    pk_r: &[u8],
+    info: &[u8],
+    aad: &[u8],
+    ptxt: &[u8],
+) -> Result<HpkeCiphertext, CryptoError> {
+    let (shared_secret, kem_output) = kem.encapsulate(crypto, rng, pk_r)?;
+    let context = Context::key_schedule(crypto, &config, &shared_secret, info)?;
+    let ciphertext = context.seal(crypto, aad, ptxt)?;
+    Ok(HpkeCiphertext {
+        kem_output: kem_output.into(),
+        ciphertext: ciphertext.into(),
+    })
+}
+
+/// HPKE single-shot decryption with the private key `sk_r`.
+pub(crate) fn open(
+    crypto: &RustCrypto,
+    kem: Kem,
+    config: HpkeConfig,
+    input: &HpkeCiphertext,
+    sk_r: &[u8],
+    info: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    let shared_secret = kem
+        .decapsulate(crypto, input.kem_output.as_slice(), sk_r)
+        .map_err(|_| CryptoError::HpkeDecryptionError)?;
+    let context = Context::key_schedule(crypto, &config, &shared_secret, info)?;
+    context.open(crypto, aad, input.ciphertext.as_slice())
+}
+
+/// Set up an HPKE sender context for the public key `pk_r` and export a
+/// secret from it.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn setup_sender_and_export(
+    crypto: &RustCrypto,
+    rng: &mut (impl CryptoRng + RngCore),
+    kem: Kem,
+    config: HpkeConfig,
+    pk_r: &[u8],
+    info: &[u8],
+    exporter_context: &[u8],
+    exporter_length: usize,
+) -> Result<(Vec<u8>, ExporterSecret), CryptoError> {
+    let (shared_secret, kem_output) = kem
+        .encapsulate(crypto, rng, pk_r)
+        .map_err(|_| CryptoError::SenderSetupError)?;
+    let context = Context::key_schedule(crypto, &config, &shared_secret, info)
+        .map_err(|_| CryptoError::SenderSetupError)?;
+    let exported_secret = context.export(crypto, exporter_context, exporter_length)?;
+    Ok((kem_output, exported_secret))
+}
+
+/// Set up an HPKE receiver context with the private key `sk_r` and export a
+/// secret from it.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn setup_receiver_and_export(
+    crypto: &RustCrypto,
+    kem: Kem,
+    config: HpkeConfig,
+    enc: &[u8],
+    sk_r: &[u8],
+    info: &[u8],
+    exporter_context: &[u8],
+    exporter_length: usize,
+) -> Result<ExporterSecret, CryptoError> {
+    let shared_secret = kem
+        .decapsulate(crypto, enc, sk_r)
+        .map_err(|_| CryptoError::ReceiverSetupError)?;
+    let context = Context::key_schedule(crypto, &config, &shared_secret, info)
+        .map_err(|_| CryptoError::ReceiverSetupError)?;
+    context.export(crypto, exporter_context, exporter_length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_vectors::{FixedRng, HPKE_TEST_VECTORS};
+
+    #[test]
+    fn rfc9180_test_vectors() {
+        let crypto = RustCrypto::default();
+        for vector in HPKE_TEST_VECTORS {
+            let kem = Kem::Dh(vector.kem);
+            let hex = |value: &str| hex::decode(value).unwrap();
+
+            let ciphertext = seal(
+                &crypto,
+                &mut FixedRng(hex(vector.ikm_e)),
+                kem,
+                vector.config(),
+                &hex(vector.pk_rm),
+                &hex(vector.info),
+                &hex(vector.aad),
+                &hex(vector.pt),
+            )
+            .unwrap();
+            assert_eq!(ciphertext.kem_output.as_slice(), hex(vector.enc));
+            assert_eq!(ciphertext.ciphertext.as_slice(), hex(vector.ct));
+
+            let plaintext = open(
+                &crypto,
+                kem,
+                vector.config(),
+                &ciphertext,
+                &hex(vector.sk_rm),
+                &hex(vector.info),
+                &hex(vector.aad),
+            )
+            .unwrap();
+            assert_eq!(plaintext, hex(vector.pt));
+
+            for (exporter_context, length, exported_value) in vector.exports {
+                let (enc, exported_secret) = setup_sender_and_export(
+                    &crypto,
+                    &mut FixedRng(hex(vector.ikm_e)),
+                    kem,
+                    vector.config(),
+                    &hex(vector.pk_rm),
+                    &hex(vector.info),
+                    &hex(exporter_context),
+                    *length,
+                )
+                .unwrap();
+                assert_eq!(enc, hex(vector.enc));
+                assert_eq!(exported_secret.to_vec(), hex(exported_value));
+
+                let exported_secret = setup_receiver_and_export(
+                    &crypto,
+                    kem,
+                    vector.config(),
+                    &hex(vector.enc),
+                    &hex(vector.sk_rm),
+                    &hex(vector.info),
+                    &hex(exporter_context),
+                    *length,
+                )
+                .unwrap();
+                assert_eq!(exported_secret.to_vec(), hex(exported_value));
+            }
+        }
+    }
+}
@@ -2,12 +2,23 @@
 //!
 //! This is an implementation of the [`OpenMlsProvider`] trait to use with
 //! OpenMLS.
+//!
+//! The `xwing` feature adds support for the
+//! `MLS_256_XWING_CHACHA20POLY1305_SHA256_Ed25519` ciphersuite with the
+//! X-Wing hybrid KEM of x25519 and ML-KEM-768.
 
 pub use openmls_memory_keystore::{MemoryKeyStore, MemoryKeyStoreError};
 use openmls_traits::OpenMlsProvider;
 
+mod dhkem;
+pub mod ed448;
+mod hpke_base;
 mod provider;
 pub use provider::*;
+#[cfg(test)]
+mod test_vectors;
+#[cfg(feature = "xwing")]
+mod xwing;
 
 #[derive(Default, Debug)]
 pub struct OpenMlsRustCrypto {
@@ -5,6 +5,7 @@ use aes_gcm::{
     Aes128Gcm, Aes256Gcm, KeyInit,
 };
 use chacha20poly1305::ChaCha20Poly1305;
+use curve25519_dalek::edwards::CompressedEdwardsY;
 use ed25519_dalek::Signer;
 use hkdf::Hkdf;
 use hpke::Hpke;
@@ -16,6 +17,7 @@ use openmls_traits::{
     types::{
         self, AeadType, Ciphersuite, CryptoError, ExporterSecret, HashType, HpkeAeadType,
         HpkeCiphertext, HpkeConfig, HpkeKdfType, HpkeKemType, HpkeKeyPair, SignatureScheme,
+        SignatureVerification,
     },
 };
 use p256::{
@@ -26,6 +28,11 @@ use rand::{RngCore, SeedableRng};
 use sha2::{Digest, Sha256, Sha384, Sha512};
 use tls_codec::SecretVLBytes;
 
+use crate::{
+    ed448,
+    hpke_base::{self, Kem},
+};
+
 #[derive(Debug)]
 pub struct RustCrypto {
     rng: RwLock<rand_chacha::ChaCha20Rng>,
@@ -39,14 +46,18 @@ impl Default for RustCrypto {
     }
 }
 
+/// Returns the `hpke-rs` KEM for `kem`, or an error if `hpke-rs` doesn't
+/// implement it. The remaining KEMs are implemented in the [`crate::hpke_base`]
+/// module instead.
 #[inline(always)]
-fn kem_mode(kem: HpkeKemType) -> hpke_types::KemAlgorithm {
+fn kem_mode(kem: HpkeKemType) -> Result<hpke_types::KemAlgorithm, CryptoError> {
     match kem {
-        HpkeKemType::DhKemP256 => hpke_types::KemAlgorithm::DhKemP256,
-        HpkeKemType::DhKemP384 => hpke_types::KemAlgorithm::DhKemP384,
-        HpkeKemType::DhKemP521 => hpke_types::KemAlgorithm::DhKemP521,
-        HpkeKemType::DhKem25519 => hpke_types::KemAlgorithm::DhKem25519,
-        HpkeKemType::DhKem448 => hpke_types::KemAlgorithm::DhKem448,
+        HpkeKemType::DhKemP256 => Ok(hpke_types::KemAlgorithm::DhKemP256),
+        HpkeKemType::DhKem25519 => Ok(hpke_types::KemAlgorithm::DhKem25519),
+        HpkeKemType::DhKemP384
+        | HpkeKemType::DhKemP521
+        | HpkeKemType::DhKem448
+        | HpkeKemType::XWingDraft06 => Err(CryptoError::UnsupportedCiphersuite),
     }
 }
 
@@ -69,13 +80,46 @@ fn aead_mode(aead: HpkeAeadType) -> hpke_types::AeadAlgorithm {
     }
 }
 
+/// Decodes an Ed25519 signature for the batch verification of
+/// `ed25519_dalek`.
+///
+/// The batch verification only accepts exactly the signatures that
+/// `verify_strict()` accepts if the public key and `R` are encoded canonically
+/// and have no small order component. For all other signatures `None` is
+/// returned, and they have to be verified on their own.
+fn ed25519_batch_entry(
+    signature: &SignatureVerification<'_>,
+) -> Option<(ed25519_dalek::VerifyingKey, ed25519_dalek::Signature)> {
+    let k = ed25519_dalek::VerifyingKey::try_from(signature.pk).ok()?;
+    let sig = ed25519_dalek::Signature::from_slice(signature.signature).ok()?;
+    let a = k.to_edwards();
+    let r = CompressedEdwardsY(*sig.r_bytes()).decompress()?;
+    let canonical =
+        a.compress().as_bytes() == signature.pk && r.compress().as_bytes() == sig.r_bytes();
+    (canonical
+        && !a.is_small_order()
+        && a.is_torsion_free()
+        && !r.is_small_order()
+        && r.is_torsion_free())
+    .then_some((k, sig))
+}
+
 impl OpenMlsCrypto for RustCrypto {
     fn supports(&self, ciphersuite: Ciphersuite) -> Result<(), CryptoError> {
         match ciphersuite {
             Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519
             | Ciphersuite::MLS_128_DHKEMX25519_CHACHA20POLY1305_SHA256_Ed25519
-            | Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256 => Ok(()),
-            _ => Err(CryptoError::UnsupportedCiphersuite),
+            | Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256
+            | Ciphersuite::MLS_256_DHKEMX448_AES256GCM_SHA512_Ed448
+            | Ciphersuite::MLS_256_DHKEMP521_AES256GCM_SHA512_P521
+            | Ciphersuite::MLS_256_DHKEMX448_CHACHA20POLY1305_SHA512_Ed448
+            | Ciphersuite::MLS_256_DHKEMP384_AES256GCM_SHA384_P384 => Ok(()),
+            #[cfg(feature = "xwing")]
+            Ciphersuite::MLS_256_XWING_CHACHA20POLY1305_SHA256_Ed25519 => Ok(()),
+            #[cfg(not(feature = "xwing"))]
+            Ciphersuite::MLS_256_XWING_CHACHA20POLY1305_SHA256_Ed25519 => {
+                Err(CryptoError::UnsupportedCiphersuite)
+            }
         }
     }
 
@@ -84,6 +128,12 @@ impl OpenMlsCrypto for RustCrypto {
             Ciphersuite::MLS_128_DHKEMX25519_AES128GCM_SHA256_Ed25519,
             Ciphersuite::MLS_128_DHKEMX25519_CHACHA20POLY1305_SHA256_Ed25519,
             Ciphersuite::MLS_128_DHKEMP256_AES128GCM_SHA256_P256,
+            Ciphersuite::MLS_256_DHKEMX448_AES256GCM_SHA512_Ed448,
+            Ciphersuite::MLS_256_DHKEMP521_AES256GCM_SHA512_P521,
+            Ciphersuite::MLS_256_DHKEMX448_CHACHA20POLY1305_SHA512_Ed448,
+            Ciphersuite::MLS_256_DHKEMP384_AES256GCM_SHA384_P384,
+            #[cfg(feature = "xwing")]
+            Ciphersuite::MLS_256_XWING_CHACHA20POLY1305_SHA256_Ed25519,
         ]
     }
 
@@ -238,7 +288,34 @@ impl OpenMlsCrypto for RustCrypto {
                 let pk = sk.verifying_key().to_bytes().into();
                 Ok((sk.to_bytes().into(), pk))
             }
-            _ => Err(CryptoError::UnsupportedSignatureScheme),
+            SignatureScheme::ECDSA_SECP384R1_SHA384 => {
+                let mut rng = self
+                    .rng
+                    .write()
+                    .map_err(|_| CryptoError::InsufficientRandomness)?;
+                let k = p384::ecdsa::SigningKey::random(&mut *rng);
+                let pk = k.verifying_key().to_encoded_point(false).as_bytes().into();
+                Ok((k.to_bytes().as_slice().into(), pk))
+            }
+            SignatureScheme::ECDSA_SECP521R1_SHA512 => {
+                let mut rng = self
+                    .rng
+                    .write()
+                    .map_err(|_| CryptoError::InsufficientRandomness)?;
+                let k = p521::ecdsa::SigningKey::random(&mut *rng);
+                let pk = p521::ecdsa::VerifyingKey::from(&k)
+                    .to_encoded_point(false)
+                    .as_bytes()
+                    .into();
+                Ok((k.to_bytes().as_slice().into(), pk))
+            }
+            SignatureScheme::ED448 => {
+                let mut rng = self
+                    .rng
+                    .write()
+                    .map_err(|_| CryptoError::InsufficientRandomness)?;
+                Ok(ed448::generate_key_pair(&mut *rng))
+            }
         }
     }
 
@@ -272,10 +349,63 @@ impl OpenMlsCrypto for RustCrypto {
                 k.verify_strict(data, &ed25519_dalek::Signature::from(sig))
                     .map_err(|_| CryptoError::InvalidSignature)
             }
-            _ => Err(CryptoError::UnsupportedSignatureScheme),
+            SignatureScheme::ECDSA_SECP384R1_SHA384 => {
+                let k = p384::ecdsa::VerifyingKey::from_sec1_bytes(pk)
+                    .map_err(|_| CryptoError::CryptoLibraryError)?;
+                k.verify(
+                    data,
+                    &p384::ecdsa::Signature::from_der(signature)
+                        .map_err(|_| CryptoError::InvalidSignature)?,
+                )
+                .map_err(|_| CryptoError::InvalidSignature)
+            }
+            SignatureScheme::ECDSA_SECP521R1_SHA512 => {
+                let k = p521::ecdsa::VerifyingKey::from_sec1_bytes(pk)
+                    .map_err(|_| CryptoError::CryptoLibraryError)?;
+                k.verify(
+                    data,
+                    &p521::ecdsa::Signature::from_der(signature)
+                        .map_err(|_| CryptoError::InvalidSignature)?,
+                )
+                .map_err(|_| CryptoError::InvalidSignature)
+            }
+            SignatureScheme::ED448 => ed448::verify(pk, data, signature),
         }
     }
 
+    fn batch_verify_signatures(
+        &self,
+        alg: SignatureScheme,
+        signatures: &[SignatureVerification<'_>],
+    ) -> Result<(), CryptoError> {
+        if alg != SignatureScheme::ED25519 {
+            return signatures.iter().try_for_each(|signature| {
+                self.verify_signature(alg, signature.data, signature.pk, signature.signature)
+            });
+        }
+
+        let mut messages = Vec::with_capacity(signatures.len());
+        let mut ed25519_signatures = Vec::with_capacity(signatures.len());
+        let mut verifying_keys = Vec::with_capacity(signatures.len());
+        for signature in signatures {
+            match ed25519_batch_entry(signature) {
+                Some((k, sig)) => {
+                    messages.push(signature.data);
+                    ed25519_signatures.push(sig);
+                    verifying_keys.push(k);
+                }
+                None => {
+                    self.verify_signature(alg, signature.data, signature.pk, signature.signature)?
+                }
+            }
+        }
+        if messages.is_empty() {
+            return Ok(());
+        }
+        ed25519_dalek::verify_batch(&messages, &ed25519_signatures, &verifying_keys)
+            .map_err(|_| CryptoError::InvalidSignature)
+    }
+
     fn sign(
         &self,
         alg: openmls_traits::types::SignatureScheme,
@@ -295,7 +425,19 @@ impl OpenMlsCrypto for RustCrypto {
                 let signature = k.sign(data);
                 Ok(signature.to_bytes().into())
             }
-            _ => Err(CryptoError::UnsupportedSignatureScheme),
+            SignatureScheme::ECDSA_SECP384R1_SHA384 => {
+                let k = p384::ecdsa::SigningKey::from_slice(key)
+                    .map_err(|_| CryptoError::CryptoLibraryError)?;
+                let signature: p384::ecdsa::Signature = k.sign(data);
+                Ok(signature.to_der().to_bytes().into())
+            }
+            SignatureScheme::ECDSA_SECP521R1_SHA512 => {
+                let k = p521::ecdsa::SigningKey::from_slice(key)
+                    .map_err(|_| CryptoError::CryptoLibraryError)?;
+                let signature: p521::ecdsa::Signature = k.sign(data);
+                Ok(signature.to_der().to_bytes().into())
+            }
+            SignatureScheme::ED448 => ed448::sign(key, data),
         }
     }
 
@@ -307,7 +449,12 @@ impl OpenMlsCrypto for RustCrypto {
         aad: &[u8],
         ptxt: &[u8],
     ) -> types::HpkeCiphertext {
+        if let Some(kem) = Kem::from_kem_type(config.0) {
+            let mut rng = self.rng.write().unwrap();
+            return hpke_base::seal(self, &mut *rng, kem, config, pk_r, info, aad, ptxt).unwrap();
+        }
         let (kem_output, ciphertext) = hpke_from_config(config)
+            .unwrap()
             .seal(&pk_r.into(), info, aad, ptxt, None, None, None)
             .unwrap();
         HpkeCiphertext {
@@ -324,7 +471,10 @@ impl OpenMlsCrypto for RustCrypto {
         info: &[u8],
         aad: &[u8],
     ) -> Result<Vec<u8>, CryptoError> {
-        hpke_from_config(config)
+        if let Some(kem) = Kem::from_kem_type(config.0) {
+            return hpke_base::open(self, kem, config, input, sk_r, info, aad);
+        }
+        hpke_from_config(config)?
             .open(
                 input.kem_output.as_slice(),
                 &sk_r.into(),
@@ -346,7 +496,23 @@ impl OpenMlsCrypto for RustCrypto {
         exporter_context: &[u8],
         exporter_length: usize,
     ) -> Result<(Vec<u8>, ExporterSecret), CryptoError> {
-        let (kem_output, context) = hpke_from_config(config)
+        if let Some(kem) = Kem::from_kem_type(config.0) {
+            let mut rng = self
+                .rng
+                .write()
+                .map_err(|_| CryptoError::InsufficientRandomness)?;
+            return hpke_base::setup_sender_and_export(
+                self,
+                &mut *rng,
+                kem,
+                config,
+                pk_r,
+                info,
+                exporter_context,
+                exporter_length,
+            );
+        }
+        let (kem_output, context) = hpke_from_config(config)?
             .setup_sender(&pk_r.into(), info, None, None, None)
             .map_err(|_| CryptoError::SenderSetupError)?;
         let exported_secret = context
@@ -364,7 +530,19 @@ impl OpenMlsCrypto for RustCrypto {
         exporter_context: &[u8],
         exporter_length: usize,
     ) -> Result<ExporterSecret, CryptoError> {
-        let context = hpke_from_config(config)
+        if let Some(kem) = Kem::from_kem_type(config.0) {
+            return hpke_base::setup_receiver_and_export(
+                self,
+                kem,
+                config,
+                enc,
+                sk_r,
+                info,
+                exporter_context,
+                exporter_length,
+            );
+        }
+        let context = hpke_from_config(config)?
             .setup_receiver(enc, &sk_r.into(), info, None, None, None)
             .map_err(|_| CryptoError::ReceiverSetupError)?;
         let exported_secret = context
@@ -374,7 +552,11 @@ impl OpenMlsCrypto for RustCrypto {
     }
 
     fn derive_hpke_keypair(&self, config: HpkeConfig, ikm: &[u8]) -> types::HpkeKeyPair {
+        if let Some(kem) = Kem::from_kem_type(config.0) {
+            return kem.derive_key_pair(self, ikm).unwrap();
+        }
         let kp = hpke_from_config(config)
+            .unwrap()
             .derive_key_pair(ikm)
             .unwrap()
             .into_keys();
@@ -385,13 +567,13 @@ impl OpenMlsCrypto for RustCrypto {
     }
 }
 
-fn hpke_from_config(config: HpkeConfig) -> Hpke<HpkeRustCrypto> {
-    Hpke::<HpkeRustCrypto>::new(
+fn hpke_from_config(config: HpkeConfig) -> Result<Hpke<HpkeRustCrypto>, CryptoError> {
+    Ok(Hpke::<HpkeRustCrypto>::new(
         hpke::Mode::Base,
-        kem_mode(config.0),
+        kem_mode(config.0)?,
         kdf_mode(config.1),
         aead_mode(config.2),
-    )
+    ))
 }
 
 impl OpenMlsRand for RustCrypto {
@@ -421,3 +603,93 @@ pub enum RandError {
     #[error("Unable to collect enough randomness.")]
     NotEnoughRandomness,
 }
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::{
+        constants::{ED25519_BASEPOINT_POINT, EIGHT_TORSION},
+        EdwardsPoint, Scalar,
+    };
+
+    use super::*;
+
+    /// Signs `message` with the secret scalar `a` and the nonce `r`, and adds
+    /// `torsion` to `R` after the signature is computed.
+    fn ed25519_sign(a: Scalar, r: Scalar, torsion: EdwardsPoint, message: &[u8]) -> Vec<u8> {
+        let public = (a * ED25519_BASEPOINT_POINT).compress();
+        let r_point = (r * ED25519_BASEPOINT_POINT + torsion).compress();
+        let mut hash = Sha512::new();
+        hash.update(r_point.as_bytes());
+        hash.update(public.as_bytes());
+        hash.update(message);
+        let k = Scalar::from_hash(hash);
+        [r_point.as_bytes().as_slice(), (r + k * a).as_bytes()].concat()
+    }
+
+    #[test]
+    fn ed25519_batch_verification_is_strict() {
+        let crypto = RustCrypto::default();
+        let alg = SignatureScheme::ED25519;
+        let a = Scalar::from(7u8);
+        let public = (a * ED25519_BASEPOINT_POINT).compress().to_bytes();
+        let messages = (0u8..16).map(|i| vec![i; 32]).collect::<Vec<_>>();
+
+        let valid = messages
+            .iter()
+            .enumerate()
+            .map(|(i, message)| {
+                ed25519_sign(
+                    a,
+                    Scalar::from(i as u64 + 1),
+                    EdwardsPoint::default(),
+                    message,
+                )
+            })
+            .collect::<Vec<_>>();
+        let batch = messages
+            .iter()
+            .zip(valid.iter())
+            .map(|(data, signature)| SignatureVerification {
+                data,
+                pk: &public,
+                signature,
+            })
+            .collect::<Vec<_>>();
+        crypto.batch_verify_signatures(alg, &batch).unwrap();
+
+        // A torsion component in `R` is cancelled in the batch equation with
+        // a probability of 1/8 per signature, but `verify_strict()` rejects
+        // the signature.
+        for (i, message) in messages.iter().enumerate() {
+            let signature = ed25519_sign(a, Scalar::from(i as u64 + 1), EIGHT_TORSION[1], message);
+            assert_eq!(
+                crypto.verify_signature(alg, message, &public, &signature),
+                Err(CryptoError::InvalidSignature)
+            );
+            let mut forged_batch = batch.clone();
+            forged_batch[i].signature = &signature;
+            assert_eq!(
+                crypto.batch_verify_signatures(alg, &forged_batch),
+                Err(CryptoError::InvalidSignature)
+            );
+        }
+
+        // Signatures for public keys of small order are rejected.
+        let small_order = EIGHT_TORSION[1].compress().to_bytes();
+        let signature = ed25519_sign(Scalar::ZERO, Scalar::ONE, EdwardsPoint::default(), b"");
+        let mut forged_batch = batch.clone();
+        forged_batch[0] = SignatureVerification {
+            data: b"",
+            pk: &small_order,
+            signature: &signature,
+        };
+        assert!(crypto.batch_verify_signatures(alg, &forged_batch).is_err());
+
+        // Signatures with a non-canonical `S` are rejected.
+        let mut signature = valid[0].clone();
+        signature[32..].copy_from_slice(&[0xff; 32]);
+        let mut forged_batch = batch.clone();
+        forged_batch[0].signature = &signature;
+        assert!(crypto.batch_verify_signatures(alg, &forged_batch).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! Test vectors of RFC 9180 for the DH-KEMs and HPKE in base mode that are
+//! implemented by this crate.
+//!
+//! The vectors are taken from the [test vectors] of the CFRG, which include
+//! the DH-KEMs on P-521 and x448. There are no official test vectors for the
+//! DH-KEM on P-384.
+//!
+//! [test vectors]: https://github.com/cfrg/draft-irtf-cfrg-hpke/blob/master/test-vectors.json
+
+use openmls_traits::types::{HpkeAeadType, HpkeConfig, HpkeKdfType, HpkeKemType};
+use rand::{CryptoRng, RngCore};
+
+use crate::dhkem::DhKem;
+
+/// A test vector of HPKE in base mode. The values are hex encoded. Only the
+/// first encryption of each vector is included, because OpenMLS only uses
+/// single-shot encryption.
+pub(crate) struct HpkeTestVector {
+    pub(crate) kem: DhKem,
+    pub(crate) kem_type: HpkeKemType,
+    pub(crate) aead: HpkeAeadType,
+    pub(crate) info: &'static str,
+    pub(crate) ikm_r: &'static str,
+    pub(crate) ikm_e: &'static str,
+    pub(crate) sk_rm: &'static str,
+    pub(crate) pk_rm: &'static str,
+    pub(crate) enc: &'static str,
+    pub(crate) shared_secret: &'static str,
+    pub(crate) aad: &'static str,
+    pub(crate) pt: &'static str,
+    pub(crate) ct: &'static str,
+    /// The exporter context, the length and the exported value.
+    pub(crate) exports: &'static [(&'static str, usize, &'static str)],
+}
+
+impl HpkeTestVector {
+    /// Returns the [`HpkeConfig`] of the vector.
+    pub(crate) fn config(&self) -> HpkeConfig {
+        HpkeConfig(self.kem_type, HpkeKdfType::HkdfSha512, self.aead)
+    }
+}
+
+/// The test vectors for base mode with HKDF-SHA512 and the DH-KEMs on P-521
+/// and x448.
+pub(crate) const HPKE_TEST_VECTORS: &[HpkeTestVector] = &[
+    HpkeTestVector {
+        kem: DhKem::X448,
+        kem_type: HpkeKemType::DhKem448,
+        aead: HpkeAeadType::AesGcm256,
+        info: "4f6465206f6e2061204772656369616e2055726e",
+        ikm_r: "93e714430d3cb00e8e8a03dd820dcbcc7f0141f93c63a7dede2dfb152b5b23982a1a55f2d86dd9e0f5a0f53b9c21605257ec1349d7f89e53",
+        ikm_e: "39ed47496020ec7c2afc214425fc6a15fb6f1e16759c2b066265b6624c84ed50ee6c3129d9ed71318b19a96e5c5cc6b27aca5e1ae9cdc7e0",
+        sk_rm: "c4e72a57af1640806c01617b947ee6d1bbe5eb1a5b4616fb705a5d2ed30b7f4317365c504249750e090805d44a2ddc2970172414a90a09e5",
+        pk_rm: "d920db89afdb25df110a44cf0d7dc4e4d4b74f09ceaba5e76a12d3cafefcd962e244804a58bfd12303732be21d511f877ddc2ed694447b3d",
+        enc: "390f2971ca97d513915a2bc5aac0cb81b832d9424d2264eaa9e868d80862edd7918276883a8d0434309e049408fec2340ae5799702f948d7",
+        shared_secret: "081f8572019ac78daca420cf23c5183027e9bdaa7fe4b5f8e55b2ff24bc5cdc8bf4362965e6ccd2b832af12b0ed6f2f669b15b42cb6f4361d36d99b88b7dc5a6",
+        aad: "436f756e742d30",
+        pt: "4265617574792069732074727574682c20747275746820626561757479",
+        ct: "6a5ef0f8c88a17c6d26bee63b4468cd43360eb69804fb392d8c9b8eba2f9bd806726c7d99cb9073022000ce41a",
+        exports: &[
+            ("", 32, "596003579117f3edeeeeb84e602b1ff316fd6771ebeb9bd400fd5ae9155199ab"),
+            ("00", 32, "d0a4a36284288e3bffe9da9b84bc99da99d7912011bc26c462504e2596229246"),
+            ("54657374436f6e74657874", 32, "419d16ff65523a00452d37ba2fd5f2b1a9261aeb30f1b1736cc2f3febb16c884"),
+        ],
+    },
+    HpkeTestVector {
+        kem: DhKem::X448,
+        kem_type: HpkeKemType::DhKem448,
+        aead: HpkeAeadType::ChaCha20Poly1305,
+        info: "4f6465206f6e2061204772656369616e2055726e",
+        ikm_r: "1a91ec4a112661d663caad07437e07486dcc80b499c83c6bf17fb2faba77c180404d983bd32ed4284fa1aee3bb3887b61402036b058c3c8d",
+        ikm_e: "178e4db14a03ebf5b5205e11a3c3918431b4d4bb143b62a52bebdd61d107d23122868395cca3dbc46e98964d4c1dfdc4b0e05cbb2934d9e5",
+        sk_rm: "c2f51845154d6bb6917e44ef0fa0a1fbf1d80f61d199486e75295e8a7e50432d548a7f8040953826c4f1bce79e433dedb4469391c3cc98a1",
+        pk_rm: "f2fdb31a7829a6d2d78b9d8b670397457c92cb2417af37dbe0c1c12a9547e4eda9fde09fc3fe0f359bb7b4151e8a6fb592530af71d9dc0b5",
+        enc: "3d4f6aa08c635205bcd96a0791695d08638714474b4d2c0132b69e25cdb826e1a2a84bc0c40c4fc75f52051b034e0afa82b8457e28794f92",
+        shared_secret: "cc20a83a9af44bc5a03a53f06beb01af474d5a85dd3c4f2082197ccdfe32a275996e497433e58460726459a1b40e31e6141e1fb605fb8ae0580b90bd7398f318",
+        aad: "436f756e742d30",
+        pt: "4265617574792069732074727574682c20747275746820626561757479",
+        ct: "4df124bd68d45b84dd5b82146597cdab8b56ab618166f814c2fe98ce35f43b09917283a58810aac71e852bff0a",
+        exports: &[
+            ("", 32, "d13d9f30a9de3369f25b8de6a733d9c5b68a79b148a662a44cb84e9296419ed6"),
+            ("00", 32, "e584af331daaab516a39e2ba8a3421e428918e108c88dda9e921fc6ecb86fd5f"),
+            ("54657374436f6e74657874", 32, "f4b7df9f1f608657f97084d9847cab976e88083fd2d35f3636dcaa9a14ce62e5"),
+        ],
+    },
+    HpkeTestVector {
+        kem: DhKem::P521,
+        kem_type: HpkeKemType::DhKemP521,
+        aead: HpkeAeadType::AesGcm256,
+        info: "4f6465206f6e2061204772656369616e2055726e",
+        ikm_r: "2ad954bbe39b7122529f7dde780bff626cd97f850d0784a432784e69d86eccaade43b6c10a8ffdb94bf943c6da479db137914ec835a7e715e36e45e29b587bab3bf1",
+        ikm_e: "7f06ab8215105fc46aceeb2e3dc5028b44364f960426eb0d8e4026c2f8b5d7e7a986688f1591abf5ab753c357a5d6f0440414b4ed4ede71317772ac98d9239f70904",
+        sk_rm: "01462680369ae375e4b3791070a7458ed527842f6a98a79ff5e0d4cbde83c27196a3916956655523a6a2556a7af62c5cadabe2ef9da3760bb21e005202f7b2462847",
+        pk_rm: "0401b45498c1714e2dce167d3caf162e45e0642afc7ed435df7902ccae0e84ba0f7d373f646b7738bbbdca11ed91bdeae3cdcba3301f2457be452f271fa6837580e661012af49583a62e48d44bed350c7118c0d8dc861c238c72a2bda17f64704f464b57338e7f40b60959480c0e58e6559b190d81663ed816e523b6b6a418f66d2451ec64",
+        enc: "040138b385ca16bb0d5fa0c0665fbbd7e69e3ee29f63991d3e9b5fa740aab8900aaeed46ed73a49055758425a0ce36507c54b29cc5b85a5cee6bae0cf1c21f2731ece2013dc3fb7c8d21654bb161b463962ca19e8c654ff24c94dd2898de12051f1ed0692237fb02b2f8d1dc1c73e9b366b529eb436e98a996ee522aef863dd5739d2f29b0",
+        shared_secret: "776ab421302f6eff7d7cb5cb1adaea0cd50872c71c2d63c30c4f1d5e43653336fef33b103c67e7a98add2d3b66e2fda95b5b2a667aa9dac7e59cc1d46d30e818",
+        aad: "436f756e742d30",
+        pt: "4265617574792069732074727574682c20747275746820626561757479",
+        ct: "170f8beddfe949b75ef9c387e201baf4132fa7374593dfafa90768788b7b2b200aafcc6d80ea4c795a7c5b841a",
+        exports: &[
+            ("", 32, "05e2e5bd9f0c30832b80a279ff211cc65eceb0d97001524085d609ead60d0412"),
+            ("00", 32, "fca69744bb537f5b7a1596dbf34eaa8d84bf2e3ee7f1a155d41bd3624aa92b63"),
+            ("54657374436f6e74657874", 32, "f389beaac6fcf6c0d9376e20f97e364f0609a88f1bc76d7328e9104df8477013"),
+        ],
+    },
+];
+
+/// An RNG that returns the given bytes, e.g. the `ikmE` of a test vector, to
+/// derive a fixed ephemeral key pair.
+pub(crate) struct FixedRng(pub(crate) Vec<u8>);
+
+impl RngCore for FixedRng {
+    fn next_u32(&mut self) -> u32 {
+        unimplemented!()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        unimplemented!()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        assert!(dest.len() <= self.0.len(), "Not enough fixed randomness.");
+        let rest = self.0.split_off(dest.len());
+        dest.copy_from_slice(&self.0);
+        self.0 = rest;
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for FixedRng {}
@@ -0,0 +1,219 @@
+//! # X-Wing
+//!
+//! X-Wing is a hybrid KEM that combines x25519 and ML-KEM-768 such that the
+//! shared secret is secure as long as one of them is. This module implements
+//! the KEM of draft-connolly-cfrg-xwing-kem-06. HPKE with it is implemented in
+//! the [`crate::hpke_base`] module.
+
+use ml_kem::{
+    kem::{Decapsulate, Encapsulate},
+    Ciphertext, EncodedSizeUser, KemCore, MlKem768, B32,
+};
+use openmls_traits::types::{CryptoError, HpkeKeyPair};
+use rand::{CryptoRng, RngCore};
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Digest, Sha3_256, Shake256,
+};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// The X-Wing label `\.//^\` that is appended to the input of the combiner.
+const XWING_LABEL: &[u8] = b"\\.//^\\";
+
+/// The length of an encoded ML-KEM-768 public key.
+const ML_KEM_PK_LEN: usize = 1184;
+
+/// The length of an ML-KEM-768 ciphertext.
+const ML_KEM_CT_LEN: usize = 1088;
+
+/// The length of an x25519 public key and ciphertext.
+const X25519_LEN: usize = 32;
+
+/// The length of an X-Wing private key, which is the seed of all keys.
+const SK_LEN: usize = 32;
+
+/// The expanded private key of X-Wing.
+struct DecapsulationKey {
+    sk_m: <MlKem768 as KemCore>::DecapsulationKey,
+    sk_x: StaticSecret,
+    pk_m: <MlKem768 as KemCore>::EncapsulationKey,
+    pk_x: PublicKey,
+}
+
+impl DecapsulationKey {
+    /// Expand the seed `sk` into the ML-KEM-768 and x25519 key pairs.
+    fn expand(sk: &[u8]) -> Result<Self, CryptoError> {
+        if sk.len() != SK_LEN {
+            return Err(CryptoError::InvalidLength);
+        }
+
+        let mut expanded = [0u8; 96];
+        let mut shake = Shake256::default();
+        shake.update(sk);
+        shake.finalize_xof().read(&mut expanded);
+
+        let d = B32::try_from(&expanded[0..32]).map_err(|_| CryptoError::CryptoLibraryError)?;
+        let z = B32::try_from(&expanded[32..64]).map_err(|_| CryptoError::CryptoLibraryError)?;
+        let (sk_m, pk_m) = MlKem768::generate_deterministic(&d, &z);
+
+        let mut sk_x = [0u8; X25519_LEN];
+        sk_x.copy_from_slice(&expanded[64..96]);
+        let sk_x = StaticSecret::from(sk_x);
+        let pk_x = PublicKey::from(&sk_x);
+
+        Ok(Self {
+            sk_m,
+            sk_x,
+            pk_m,
+            pk_x,
+        })
+    }
+
+    /// The encoded public key `pk_M || pk_X`.
+    fn public_key(&self) -> Vec<u8> {
+        let mut pk = self.pk_m.as_bytes().to_vec();
+        pk.extend_from_slice(self.pk_x.as_bytes());
+        pk
+    }
+}
+
+/// Combine the shared secrets of ML-KEM-768 and x25519.
+fn combiner(ss_m: &[u8], ss_x: &[u8], ct_x: &[u8], pk_x: &[u8]) -> Vec<u8> {
+    Sha3_256::new()
+        .chain_update(ss_m)
+        .chain_update(ss_x)
+        .chain_update(ct_x)
+        .chain_update(pk_x)
+        .chain_update(XWING_LABEL)
+        .finalize()
+        .to_vec()
+}
+
+/// Encapsulate a fresh shared secret to the public key `pk`.
+///
+/// Returns the shared secret and its encapsulation `ct_M || ct_X`.
+pub(crate) fn encapsulate(
+    pk: &[u8],
+    rng: &mut (impl CryptoRng + RngCore),
+) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+    if pk.len() != ML_KEM_PK_LEN + X25519_LEN {
+        return Err(CryptoError::InvalidLength);
+    }
+    let (pk_m, pk_x) = pk.split_at(ML_KEM_PK_LEN);
+
+    let pk_m = <MlKem768 as KemCore>::EncapsulationKey::from_bytes(
+        &pk_m
+            .try_into()
+            .map_err(|_| CryptoError::CryptoLibraryError)?,
+    );
+    let (ct_m, ss_m) = pk_m
+        .encapsulate(&mut *rng)
+        .map_err(|_| CryptoError::CryptoLibraryError)?;
+
+    let mut pk_x_bytes = [0u8; X25519_LEN];
+    pk_x_bytes.copy_from_slice(pk_x);
+    let mut ek_x = [0u8; X25519_LEN];
+    rng.try_fill_bytes(&mut ek_x)
+        .map_err(|_| CryptoError::InsufficientRandomness)?;
+    let ek_x = StaticSecret::from(ek_x);
+    let ct_x = PublicKey::from(&ek_x);
+    let ss_x = ek_x.diffie_hellman(&PublicKey::from(pk_x_bytes));
+
+    let ss = combiner(&ss_m, ss_x.as_bytes(), ct_x.as_bytes(), pk_x);
+    let mut ct = ct_m.to_vec();
+    ct.extend_from_slice(ct_x.as_bytes());
+
+    Ok((ss, ct))
+}
+
+/// Decapsulate the shared secret from `ct` with the private key `sk`.
+pub(crate) fn decapsulate(ct: &[u8], sk: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if ct.len() != ML_KEM_CT_LEN + X25519_LEN {
+        return Err(CryptoError::InvalidLength);
+    }
+    let (ct_m, ct_x) = ct.split_at(ML_KEM_CT_LEN);
+    let dk = DecapsulationKey::expand(sk)?;
+
+    let ct_m =
+        Ciphertext::<MlKem768>::try_from(ct_m).map_err(|_| CryptoError::CryptoLibraryError)?;
+    let ss_m = dk
+        .sk_m
+        .decapsulate(&ct_m)
+        .map_err(|_| CryptoError::HpkeDecryptionError)?;
+
+    let mut ct_x_bytes = [0u8; X25519_LEN];
+    ct_x_bytes.copy_from_slice(ct_x);
+    let ss_x = dk.sk_x.diffie_hellman(&PublicKey::from(ct_x_bytes));
+
+    Ok(combiner(&ss_m, ss_x.as_bytes(), ct_x, dk.pk_x.as_bytes()))
+}
+
+/// Derive an X-Wing key pair from `ikm`.
+pub(crate) fn derive_key_pair(ikm: &[u8]) -> Result<HpkeKeyPair, CryptoError> {
+    let mut sk = [0u8; SK_LEN];
+    let mut shake = Shake256::default();
+    shake.update(ikm);
+    shake.finalize_xof().read(&mut sk);
+
+    let public = DecapsulationKey::expand(&sk)?.public_key();
+    Ok(HpkeKeyPair {
+        private: sk.as_slice().into(),
+        public,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_vectors::FixedRng;
+
+    /// A known-answer test of X-Wing. The values are hex encoded.
+    struct XWingTestVector {
+        seed: &'static str,
+        pk: &'static str,
+        eseed: &'static str,
+        ct: &'static str,
+        ss: &'static str,
+    }
+
+    /// Known-answer tests in the format of the test vectors of
+    /// draft-connolly-cfrg-xwing-kem-06. The expected values were computed
+    /// from the specification with the ML-KEM-768 and x25519 implementations
+    /// of OpenSSL 3.5, independently of the implementation in this module.
+    const XWING_TEST_VECTORS: &[XWingTestVector] = &[
+    XWingTestVector {
+        seed: "cd1c1d715946cc828866e8bfb756674b05bc3f0ad9aff59574f6496b15431a4f",
+        pk: "1549b48007cac09cc40d1a1dad2a30eae92924d5ca1e442bb3083c09baa4ca116055186581f529ff100745a666b7e697589901384482b907183b11b2cbbbb8e083ca1cb97829ec6ac7994a6d8122379c8a3e02bf37bc212f474bad1c5c77e05309fb8bae9178a5887a855338594c08b12b2e6763b2556866a245c3e26cb0612754518081e755c3871367bf038584a79d704505a09b50e13627666876e2ebad83b39ef60354c9d8369e5b4856a8cf9f655499e3bdd9d55716ca3c2d41298227bf1bc82fe75b1345a285f3ab7515fc87158a1310e45a83205f5dea9ad2522813c495234980b003ac2ef252a102cc7b801499857094081427fc174c10ba590b45a00b008a1680344707baa51fe2342e611443da5266ff997408bccccb146b0be284a81941c05145581045590ab9072cc88f4924c6f08fc213b970d9a1621b879a7442833799b2582f8ca5202c477b33d3976ac437b9eb1fb3e36762656c7c663ad8986461b76dd4a342f42a26afd1375ad9c6fc3c3abfe178698a1dfa7963007587ab4108ecd2432bd96d3b8a71431634d6b35b8a32506d9b7da8b254e6777dafd97dd802077f154bd4587ccc4418eb61915098112e807b2129c0cc8742f8b28246d88183aa9ecaa7ca4a03cfd64082aa97a9ec3a70794c6c50c1b92bda7d9037c9cbc801b57153edca6add11526c7a2e526a170d2cb35f7c9795cbb300c34c4b2813a0d8c2d82a7de0ba1f635681a8d23dad668f64615f8028c7d51b6487ca9f9348b40b0b6ddacc8cbea7255e095385dbbff4e85594e1b82b1c177d7b38f8937f30a255db42139d65937e95249ef01cf4774526f98dad7083fae4552ca0a07fc87f5e303a65d7a8b4747a6af29ddce4b95f910fdb7384f3c249136422dcc052ade380b2f89f0ba50e08916348ca38caf565b58c5c03153c0dba06830a31f2eaa32d5c8b7c5881d335c1730799409b698fa54e1bf54f42aa7669d37aeba6a2422a7e67419090298618d1388ea463b27c8e80c17272f24539c44201e0886039704329919415af38917c6bab01228977f5a2b445db5962e21d586231a44a0f1f0b23946543bd484911f7b111e15eac16be72e1a0835ac9089b02fb1093355346429377777a4b2068b12fca757e7263d2279da5273e774cab08aa206550827b3b072a6823cffa19a824acbb1174e39c61c2c75c8fea498cd37af866831229a1a7d24748fa50dba24bca37651254a50f1a0a4eb3b9b321a2de5c726dd1938691c404e51ebb8b26a4b63575811a77cb8788b0ba14b28c3aea60179642e97ab9678928b535014a9ba93f2b782c718bba813c0e9b0de7c2930d505e877c5f499610a26cba19589b728396a76042f74c737ad44462c7c6098b2d5b7848a202ab9b989cf9a5643985a0922a1d81dc1608f634c5fc5130bc4fedfc31178a0008780f7a6b883935a2b10b1b4cd08d7c493b50d46f2f00a235ac1c2ce9977a2932b87b00a641176192a67dcc166500307fa1162404c994c935fbbb58f3119adcc410c626851d1c26c162c879b5bfb6ea0642d04a130bb830e413f7629513d2be10cb2be5b581f87106941703d4a10218070bd82331d7e0b564412147f132dbc8389ae40e514a6403f51ab8d40087f6aa592a62f473bb35a6228dfb5d2621dd7f1c3033edca7c2dfed3d7121636413bd0b0ca4ce3371d5fdf6a706c235b959704123ce6c1b79a3d",
+        eseed: "22dc170d682021a8312a2679899f22eeabd4f17de2bcbf6febcf5b6230297c2621a0a772ebde3ddf8ba26b2d3f53c368f47cbd9eff4abc8566d5caa975e4a14c",
+        ct: "68e41ef28e5743b245d9e549c3af761f2347614747cccc22acba67b8291d69a9a92ab3f1b45f2a7d8c7a9860bb4cd8cd9bf40d0982881d90e2f9efc34866059d88ad23384326c378faf528d5efdc67ea202fbe302b87514d1ca7e91bbbe0b3d2d4c37377554e1eb867ae4bbf30c4072d60991966c9f5466bda8752f48be1b890ed20d8f1b55dd5827c806b387582ce0115c71712fd7f9314048a5af6f72b1b6182dc5208eef36dbb6dedc4628a7fcce027356bcc9b104cdb8df753e0468c70c1a05275f114f9a5d156feb47af040a128f5326d29b31ce98dbda6723f341b471ad43adc472598305d4a80a8d41953ef3661329ad5e2a142cf191b5fdc45015bc88f3b6fa66c2d8cedeac4c0f1a04597f218d0157c9ba34760bba68fb980d0912a87c5200dd2d3e20be02f3e1f2288e5823e15d7f5ea734b1c23d6ec5c582e668e80455c4c2b578e4d3f0c5e4127d704c6dcc631ade78c9363186a2fd1f307eb8f279a32d1e25601dba716f7b34b6fb60868615e224ff0ab91e97cc792a100295c802cef62f7d1cfd2b875b4ced956a6510e9334a6ed5b0207ba4cc54ccd2d0ceec7e376c20c412645dcba89493f01a2d8e0a0fc0dd3762e82a08aa6a7702e6321d65e59ee92b72836b1f4f639d44a242002bebfab3a2ce586ec930b91d6a475b11b62e2bb9d52905bc8c9ce2186f67ce9cd6e2358b870bae6fce0352482a0c49c0e512dc95031d989ec99b5267e1ac4b65769037a09f00d1f5142ef5f84c49d8f33699f6e117fecf4a52d7160c0c0e801ad0dd89908529e9f99f487f7e09b4863cdc135482cd28c8d882a77f6cb582a4a02cceec4b2b832808df3dca09d8b862414a1fdd133fd79b102477c8b4fe4eae224a033180e2c61484e46a695d471e0f2e7b5e224c7e4fd8c712c281fc1f55cfe38f2ce42feead80d8fcced9553651f9f2a6e4ce8024f67d09790ff1a1a52de40a80c027c07a0ed8074b67412dc30266fc5b290eaca2942122e4f43b3a07f390d5b4ccc4ee986cd29424876dd00c144ffab09257e483c0e32db2d5627e2729df77fbadbff6df2d3ba97ec7793e40bcf289be185d4b65dea9b64e2f4a935a51a59477df346b65a6f9c1684ba132b39771e6ede356326684fd27caa5c8584906ead5ae00129c1e7e4cba12b1ccc275dfdf73cbcf758fd3f06dacc0e8060c842cb1c6c66ddb0284347f5fa44cf9ddbd6139dde85ec81f0353265d627b96512fe245724fe8e4ea7f4ec1cc875b920089eb9ae88d7d55b3da19585d8fa7421801c512e840e659fd03309b7176088eb2cc304c77723e7e8109c2e8e5fa4fa677657d31c79f578fdff4935ecf7fb38cdf62650cbeb13751c5c98f78a9bfd27b9263f824d9ed00fa4de35682127a11fc92f7383d98848fca4b6449a9a6fa4936568325dfdf65fb473420cd5b8d72ce31754bdf01f929438a000c226c66275b1031e9767f7e89e5f9c68d273b5282b6d813aa541eecbd9e75a2e3892464cf7f24ce94e80badb5858fc66c769dbd36cbc7c1c5a8fee81068fa9c43fc92115d0e9a4165478611561a45fd23e45124ccb56d6eae9f423",
+        ss: "35d7b598b7182cbfd7ba2b4b37a2e58c78dd9abee1756df27d39b5d8fb765bb8",
+    },
+    XWingTestVector {
+        seed: "9f4b845caf17dbb8f79d2fffe0a2bbbc1d4c3896c3272f1fc6afbad6e365d648",
+        pk: "d1c26622b57d34c21cf01c9b0701a7bbac747a500ea5cbc60ef6c8e32583adcc120e316146d920a07665df2681b8dba7fa54b59052aa8fa5a6d7a3205f6c68332123e1e35b033304e4c0aebb7975766252c8e82b43c22662e173751bacf2d74fbac844276550c8d952617028c9249795a514f3a810dae7b693b5a8b5311ada62bce3746cb6128bd2330b8eab863e9ca838ec0d818cb81a352f82a88d0773cd88883c48841771193104b83ebcda113a8367cb2b9b543c259ac1226e44262ad967aad779dcb69737155ce2cc8a7ae8a182dc75f5598475e1c66ca2c8b765c0c1098fe24237ce8c33b52228b3a5187e91842fe2bb9e4a9aeb32c9d56ac022910c7c4107e28b18f6ba799224731aab2a9694c0f3005d27db00591849218410a9865630b05ec9823e7a140ddbf1030f082a82765b9ca4208f7a6bdc1cbc1ac1a7b3d11d9e728e7ecc648647c2119a753df3a4e1285b4732341991b5c45c1cdc1273fa26b6cc408a22eb3dcde66d8f261311fb1f3b85ba488b97c8b7c9add1c0e46b15b380c1b958cf43c4207155c6bc559b426875a60b63eb66507363304e5a87fdec480696423f5b90fc57c665825b6c080c82f1057231a85c21c96a8a8d6a30aa2a111d7b3188098a1c94a01859bcba7dd28a442587b37ca4ae143aff241b10c26fee0028cc7a62caa08159d07a5987c2bbdc8486179800608f74735b697b8b416a8cfa72a60ac99435a0b6cc823c23b1145c603fe2e1150b61bd6856a228b7bd82303e6da54298d55f061c1d8a4817fa692751d4c0a8dc3a2bd22e44647a60605a3bc7b25dba011265070b164bdae53f99e256214ab6279c0460f56ccb851f5e2b48e9bb01e82815f2e18c93150daba78a06c1b8de9b868201751ae92d23c6076e3c05e600555f20916fe14c84715850939d19aa726fc48f6ac0a7e658c32f4612e3d886a458425694c91bfa18db9991e2741dcca91ffb4989c5483d430351fb126e9e698e69b0077e623e81d634f3e2518c240d86bac3079a4acffa64a5798bcb3cc84e118b96f8473ae429fd029d60798fee47832c25067a394e985562c48b7b08e98f44997cc4966bc571282cf15149400435c950f2886ef5f26229040784c05602377e892766563228cb25b04370b85341c59cd1ccb404393603786676419e9560aaa99b496a354131a0389b4d35e045f4ea8f3bb7843e11bcbe26878deabad7986a9058815741370b3c540a93c478b26a17320a1de616f95a47521b272f43725ee85eea7b5ec15049645854c7c790d40bc36d3bc23d2bc2b9928aead4b679827dd1cc487459b65a668a3cd7b3e42597277bc030095db01acb99faa19eb1aef62998c6aa1d05db4992aa882af9aef8b668087743e0a389c90aa695780d0ab33ee1ea4c115993713a864b168f49caa6a9b0cdf8d7a2dcd22650fc59ff8164ee565a917a584ed38888661e8ef875a1d758e94778e89a7c0e9872b1d9c1f11c3c8539443e347cb6080d262bc1812152717b5172bb7c92903bd4491238a369bcd898b4598ec7b387ce8a3ee31b8cd5f6b31ef19a3c29a4dc420ccab3416f426541e197b781a59c38009817173344586f1005e8ea7d5ba75e38c6ac0771703b430edb32f14544e7f5374d158d75ee0aea8df2e9724f80f448e5e3d837b0367feba93c790ef7b37ff657822235812911943d284bf11d57483649a0da723cf5f75658",
+        eseed: "85196df7b383893cb83f5b87a1e69919fce645b6c5a9fcd157d00aa374c8e4b6ab9b4d9cabcb71220391470e52bc895d8127fe91129ebbd4a2e09bf0a6c4da39",
+        ct: "a0e8097df5f2dd5a5aa7b95023c1fa8f30bff5dd70b6f69333d19d3109c59ac677048062756f86658affac85a7380021c8ac56db1a709a8b22df3c1afdf128efee969663c4c58a0c2ad040647a9868bcdb3e2baffb58eb552e8e92e702e4257c31d562872809e7fde2bd60f21e75f4662be8229a70048a5ccd22073f9eaf878dd884440a743eca1843806624353c28b1e1da103e85240d0009ddcc464710372dd0a07b280e73f1b21741e643c317cc7620e6591f4addd85aa34acee04804a9ee6594b1b198ccdfdbd61d58f16755e897bc1e91b4773b989ff22d7a2825874f9b5f3160ebe1aba933da081a1712f7574710e234e6a817d8c33c20073c26e51fcccd3b413d369ee4e7dbc3db6ba13ff69d69ae593d35a76ef184adf89651408c563c9797327915e5eb3c51775ab470f3c2838cb9106bc1ae05615fc6caa7922df8c5ce518d6686951440e37dbebb8a1bf49715c90b8f06791b3359a265129f168d07ebb5f71636219084338668c53a6e1efd2cf7b6851bb1c055cea8245b77baa1f9b2af5bf62f77387d32dfb135358a5f85e4ae62cd2f21a27732174d10f3a556f8f93d8684226239d198d629c9472995a82f4418ee9e8cca5959dd020ba3c5af8346c5089bbdc8ccc59c95d52c1e3c5069d1e565170c36b18ab4698e2c51cc00d1a1e202486736d35039b2afd0927b217c01e00d9c1e619c85882af191bb11a901c21df204363c67e9ac60c9afd701e6b8e196c75a72c9bfefc6a3727434d79579d5275546ca888a62e0915efe605f17c3a12ce69490139c057a62caffa58f0e6eb927c0d75a2ef58d5adaa1e1809178c3421074164f67785cf16bfd293003ffecd335c6d894cf4298f906edbfe06fafbab9b8cb6c755cbbc1921449149cd83f75f2a503e7fcbe62b75c406791d0aed50b6f03d99d48ce59f6873bcf70e23aea9fd56a79f9412bd0b5317f8e60ccc1d6ac3136e75426688a36c376a2af76407aec02542958211deaf286935284e37114a871aa4c0ae5484020cb1dacaed9128c7c056f74b796b7ba97b931c2693b3dd841e66745683f303c4fb4fa2efb131168b39cf624608cb384f596c5f85d60d3aaaba1b0127ed153c706c289c3edfa2a7aab899e7583dae23b8b19d4f3a6ec52fd149821cd918a3c5051ad81ca6c6c7e14577a63ea1289d935e3eb6e59932027a8701e442aceaef8bbb2d858995d47d656b4188e4de6f55910bccf239a016473e21189d476774ff8be32ef15e5b2d96a3311e9bbe98f2fdef6e2978cd896b748b511964157889d34c5cfe2b9c37b84a8c452a439fd4e84e1ec7ccd9bb06519adc7b6fe161a36c7021405a065dfbcc6f8fbafe2985d0fc597cb4a04298c1ecc352f4ed2fd1c3e66a210754827938828691547471dc6ac1c4fa4ad6237a07bd3d9f40a92f5e99235f955d4fc5c357de04158933aa5069fe0b0c8a953d355a198240e9016dac39a6ff419ef9fa8fe516a5b4d05fdafe8e38c3931be387ab691a386cdcc64bd43642e72d0ee69abb56fc2bf947b3f56eb7f7bdb1f891ffb831b8fe56a579c6b2f1cb90c8a6078ba03f3891024",
+        ss: "13be9d8ecae08c622499b6da0850159f245fbe803dc544dc146a03de552ad317",
+    },
+    ];
+
+    #[test]
+    fn known_answer_tests() {
+        for vector in XWING_TEST_VECTORS {
+            let hex = |value: &str| hex::decode(value).unwrap();
+
+            // Key generation from the seed
+            let dk = DecapsulationKey::expand(&hex(vector.seed)).unwrap();
+            assert_eq!(dk.public_key(), hex(vector.pk));
+
+            // Encapsulation with the fixed `eseed`
+            let (ss, ct) = encapsulate(&hex(vector.pk), &mut FixedRng(hex(vector.eseed))).unwrap();
+            assert_eq!(ct, hex(vector.ct));
+            assert_eq!(ss, hex(vector.ss));
+
+            // Decapsulation
+            let ss = decapsulate(&hex(vector.ct), &hex(vector.seed)).unwrap();
+            assert_eq!(ss, hex(vector.ss));
+        }
+    }
+}
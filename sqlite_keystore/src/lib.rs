@@ -0,0 +1,583 @@
+//! # OpenMLS SQLite Key Store
+//!
+//! A persistent [`OpenMlsKeyStore`] backed by an SQLite database.
+//!
+//! Values are serialized with `serde_json` and stored in one table per
+//! [`MlsEntityId`]. Every write is a single SQL statement, which SQLite
+//! executes atomically in autocommit mode, such that a crash of the process
+//! never leaves a partially written value behind. Transactions of the
+//! [`OpenMlsKeyStore`] are mapped to SQLite savepoints, such that several
+//! writes are committed atomically. While a thread has open transactions, all
+//! operations of other threads wait until its outermost transaction was
+//! committed or rolled back.
+//!
+//! The database schema is versioned with SQLite's `user_version` and migrated
+//! to [`SCHEMA_VERSION`] when a database is opened.
+
+use std::{
+    path::Path,
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    thread::{self, ThreadId},
+};
+
+use openmls_traits::key_store::{MlsEntity, MlsEntityId, OpenMlsKeyStore};
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+
+/// The schema migrations of the key store.
+///
+/// The migration at index `i` migrates the database from schema version `i` to
+/// version `i + 1`. Released migrations must never be changed. Changes to the
+/// schema are made by appending a new migration.
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE signature_key_pairs (key BLOB PRIMARY KEY, value BLOB NOT NULL);
+    CREATE TABLE hpke_private_keys (key BLOB PRIMARY KEY, value BLOB NOT NULL);
+    CREATE TABLE key_packages (key BLOB PRIMARY KEY, value BLOB NOT NULL);
+    CREATE TABLE psk_bundles (key BLOB PRIMARY KEY, value BLOB NOT NULL);
+    CREATE TABLE encryption_key_pairs (key BLOB PRIMARY KEY, value BLOB NOT NULL);
+    CREATE TABLE group_states (key BLOB PRIMARY KEY, value BLOB NOT NULL);
+"];
+
+/// The name of the savepoints that implement the transactions of the key
+/// store. Nested savepoints with the same name refer to the innermost one.
+const SAVEPOINT: &str = "openmls_transaction";
+
+/// The version of the database schema created by this key store.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Returns the name of the table that holds the entities with the given id.
+fn table(entity_id: MlsEntityId) -> &'static str {
+    match entity_id {
+        MlsEntityId::SignatureKeyPair => "signature_key_pairs",
+        MlsEntityId::HpkePrivateKey => "hpke_private_keys",
+        MlsEntityId::KeyPackage => "key_packages",
+        MlsEntityId::PskBundle => "psk_bundles",
+        MlsEntityId::EncryptionKeyPair => "encryption_key_pairs",
+        MlsEntityId::GroupState => "group_states",
+    }
+}
+
+/// Read the schema version of the database.
+fn schema_version(connection: &Connection) -> Result<u32, rusqlite::Error> {
+    connection.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Migrate the database to [`SCHEMA_VERSION`].
+///
+/// All pending migrations are applied in a single transaction, so the
+/// database is either fully migrated or left untouched.
+fn migrate(connection: &mut Connection) -> Result<(), SqliteKeyStoreError> {
+    let transaction = connection.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+
+    let version = schema_version(&transaction)?;
+    if version > SCHEMA_VERSION {
+        return Err(SqliteKeyStoreError::UnsupportedSchemaVersion(version));
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        transaction.execute_batch(migration)?;
+    }
+    transaction.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+    transaction.commit()?;
+    Ok(())
+}
+
+/// The connection to the database and the open transactions on it.
+#[derive(Debug)]
+struct Database {
+    connection: Connection,
+    // The thread that opened the transactions and the number of open
+    // transactions.
+    transactions: Option<(ThreadId, usize)>,
+}
+
+impl Database {
+    /// Returns `true` if another thread has open transactions.
+    fn of_other_thread(&self) -> bool {
+        matches!(self.transactions, Some((thread, _)) if thread != thread::current().id())
+    }
+
+    /// Returns the number of open transactions of the current thread.
+    ///
+    /// Returns an error if the current thread has no open transaction.
+    fn depth(&self) -> Result<usize, SqliteKeyStoreError> {
+        match self.transactions {
+            Some((thread, depth)) if thread == thread::current().id() => Ok(depth),
+            _ => Err(SqliteKeyStoreError::NoTransaction),
+        }
+    }
+
+    /// Roll back all open transactions.
+    fn rollback_all(&mut self) {
+        if self.transactions.take().is_some() {
+            // A `ROLLBACK` without savepoint rolls back the outermost
+            // savepoint and releases all of them. It only fails if there
+            // is no open transaction anymore.
+            let _ = self.connection.execute_batch("ROLLBACK");
+        }
+    }
+}
+
+/// A key store that persists all values in an SQLite database.
+///
+/// The key store uses a single connection. Transactions are not isolated from
+/// each other on one connection, so all operations of other threads wait
+/// while a thread has open transactions.
+#[derive(Debug)]
+pub struct SqliteKeyStore {
+    database: Mutex<Database>,
+    // Notified when the outermost transaction of a thread ends.
+    transactions_ended: Condvar,
+}
+
+impl SqliteKeyStore {
+    /// Open the key store in the database file at `path`. The database is
+    /// created if it doesn't exist yet.
+    ///
+    /// Returns an error if the database can't be opened or migrated.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SqliteKeyStoreError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Open a key store in a new in-memory database. The content of the key
+    /// store is lost when it is dropped.
+    pub fn open_in_memory() -> Result<Self, SqliteKeyStoreError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Create a key store from an open database connection and migrate the
+    /// database to [`SCHEMA_VERSION`].
+    ///
+    /// Returns an error if the database was created by a newer version of this
+    /// key store or if the migration fails.
+    pub fn from_connection(mut connection: Connection) -> Result<Self, SqliteKeyStoreError> {
+        migrate(&mut connection)?;
+        Ok(Self {
+            database: Mutex::new(Database {
+                connection,
+                transactions: None,
+            }),
+            transactions_ended: Condvar::new(),
+        })
+    }
+
+    /// Returns the schema version of the database.
+    pub fn schema_version(&self) -> Result<u32, SqliteKeyStoreError> {
+        Ok(schema_version(&self.database().connection)?)
+    }
+
+    /// Lock the database without waiting for the transactions of other
+    /// threads.
+    fn lock(&self) -> MutexGuard<'_, Database> {
+        self.database
+            .lock()
+            .unwrap_or_else(|error| self.recover(error))
+    }
+
+    /// Lock the database once no other thread has open transactions.
+    fn database(&self) -> MutexGuard<'_, Database> {
+        let database = self.lock();
+        self.transactions_ended
+            .wait_while(database, |database| database.of_other_thread())
+            .unwrap_or_else(|error| self.recover(error))
+    }
+
+    /// Recover the database after a thread panicked while holding the lock.
+    ///
+    /// The panic can't leave a partially written value behind because every
+    /// write is a single statement. But the transactions that were open when
+    /// the thread panicked are never ended by it, so they are rolled back.
+    fn recover<'a>(
+        &self,
+        error: PoisonError<MutexGuard<'a, Database>>,
+    ) -> MutexGuard<'a, Database> {
+        let mut database = error.into_inner();
+        database.rollback_all();
+        self.database.clear_poison();
+        self.transactions_ended.notify_all();
+        database
+    }
+
+    /// End the innermost transaction of the current thread with `statement`.
+    ///
+    /// Returns an error if the current thread has no open transaction.
+    fn end_transaction(&self, statement: &str) -> Result<(), SqliteKeyStoreError> {
+        let mut database = self.lock();
+        let depth = database.depth()?;
+        database.connection.execute_batch(statement)?;
+        if depth > 1 {
+            database.transactions = Some((thread::current().id(), depth - 1));
+        } else {
+            database.transactions = None;
+            self.transactions_ended.notify_all();
+        }
+        Ok(())
+    }
+}
+
+impl OpenMlsKeyStore for SqliteKeyStore {
+    /// The error type returned by the [`OpenMlsKeyStore`].
+    type Error = SqliteKeyStoreError;
+
+    /// Store a value `v` that implements the [`MlsEntity`] trait for
+    /// serialization for ID `k`. An existing value for `k` is replaced.
+    ///
+    /// Returns an error if storing fails.
+    fn store<V: MlsEntity>(&self, k: &[u8], v: &V) -> Result<(), Self::Error> {
+        let value = serde_json::to_vec(v).map_err(|_| SqliteKeyStoreError::SerializationError)?;
+        let database = self.database();
+        database
+            .connection
+            .prepare_cached(&format!(
+                "INSERT OR REPLACE INTO {} (key, value) VALUES (?1, ?2)",
+                table(V::ID)
+            ))?
+            .execute(params![k, value])?;
+        Ok(())
+    }
+
+    /// Read and return a value stored for ID `k` that implements the
+    /// [`MlsEntity`] trait for deserialization.
+    ///
+    /// Returns [`None`] if no value is stored for `k` or reading fails.
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> Option<V> {
+        let database = self.database();
+        let value: Vec<u8> = database
+            .connection
+            .prepare_cached(&format!(
+                "SELECT value FROM {} WHERE key = ?1",
+                table(V::ID)
+            ))
+            .ok()?
+            .query_row(params![k], |row| row.get(0))
+            .optional()
+            .ok()??;
+        serde_json::from_slice(&value).ok()
+    }
+
+    /// Delete a value stored for ID `k`.
+    ///
+    /// Returns an error if deleting fails.
+    fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error> {
+        let database = self.database();
+        database
+            .connection
+            .prepare_cached(&format!("DELETE FROM {} WHERE key = ?1", table(V::ID)))?
+            .execute(params![k])?;
+        Ok(())
+    }
+
+    /// Return the IDs of all values that are stored for the entity with the
+    /// given [`MlsEntityId`].
+    ///
+    /// Returns an error if reading the IDs fails.
+    fn list_keys(&self, entity_id: MlsEntityId) -> Result<Option<Vec<Vec<u8>>>, Self::Error> {
+        let database = self.database();
+        let mut statement = database
+            .connection
+            .prepare_cached(&format!("SELECT key FROM {}", table(entity_id)))?;
+        let keys = statement
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(Some(keys))
+    }
+
+    /// Begin a transaction by creating a savepoint. Transactions can be
+    /// nested.
+    ///
+    /// Waits until no other thread has open transactions. Returns an error if
+    /// the savepoint can't be created.
+    fn begin_transaction(&self) -> Result<(), Self::Error> {
+        let mut database = self.database();
+        let depth = database.depth().unwrap_or(0);
+        database
+            .connection
+            .execute_batch(&format!("SAVEPOINT {SAVEPOINT}"))?;
+        database.transactions = Some((thread::current().id(), depth + 1));
+        Ok(())
+    }
+
+    /// Commit the innermost transaction by releasing its savepoint.
+    ///
+    /// Returns an error if the current thread has no open transaction or
+    /// committing fails.
+    fn commit_transaction(&self) -> Result<(), Self::Error> {
+        self.end_transaction(&format!("RELEASE {SAVEPOINT}"))
+    }
+
+    /// Roll back the innermost transaction to its savepoint.
+    ///
+    /// Returns an error if the current thread has no open transaction or
+    /// rolling back fails.
+    fn rollback_transaction(&self) -> Result<(), Self::Error> {
+        self.end_transaction(&format!("ROLLBACK TO {SAVEPOINT}; RELEASE {SAVEPOINT}"))
+    }
+}
+
+/// Errors thrown by the key store.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum SqliteKeyStoreError {
+    /// A value couldn't be serialized.
+    #[error("Error serializing value.")]
+    SerializationError,
+    /// The database was created by a newer version of the key store.
+    #[error("The database schema version {0} is not supported by this key store.")]
+    UnsupportedSchemaVersion(u32),
+    /// The current thread has no transaction to commit or roll back.
+    #[error("There is no transaction to commit or roll back.")]
+    NoTransaction,
+    /// An SQLite error with its message.
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+impl From<rusqlite::Error> for SqliteKeyStoreError {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::DatabaseError(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{sync::mpsc, time::Duration};
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct KeyPackage(Vec<u8>);
+
+    impl MlsEntity for KeyPackage {
+        const ID: MlsEntityId = MlsEntityId::KeyPackage;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct GroupState(String);
+
+    impl MlsEntity for GroupState {
+        const ID: MlsEntityId = MlsEntityId::GroupState;
+    }
+
+    #[test]
+    fn store_read_delete() {
+        let key_store = SqliteKeyStore::open_in_memory().unwrap();
+        assert_eq!(key_store.read::<KeyPackage>(b"id"), None);
+
+        key_store.store(b"id", &KeyPackage(vec![1, 2, 3])).unwrap();
+        key_store
+            .store(b"id", &GroupState("group".to_string()))
+            .unwrap();
+        assert_eq!(
+            key_store.read::<KeyPackage>(b"id"),
+            Some(KeyPackage(vec![1, 2, 3]))
+        );
+
+        // Storing again replaces the value.
+        key_store.store(b"id", &KeyPackage(vec![4, 5])).unwrap();
+        assert_eq!(
+            key_store.read::<KeyPackage>(b"id"),
+            Some(KeyPackage(vec![4, 5]))
+        );
+
+        // Entities with different ids are stored separately.
+        key_store.delete::<KeyPackage>(b"id").unwrap();
+        assert_eq!(key_store.read::<KeyPackage>(b"id"), None);
+        assert_eq!(
+            key_store.read::<GroupState>(b"id"),
+            Some(GroupState("group".to_string()))
+        );
+
+        // Lists of entities are stored in the table of the entity.
+        let key_packages = vec![KeyPackage(vec![1]), KeyPackage(vec![2])];
+        key_store.store(b"list", &key_packages).unwrap();
+        assert_eq!(
+            key_store.read::<Vec<KeyPackage>>(b"list"),
+            Some(key_packages)
+        );
+    }
+
+    #[test]
+    fn list() {
+        let key_store = SqliteKeyStore::open_in_memory().unwrap();
+        key_store.store(b"a", &KeyPackage(vec![1])).unwrap();
+        key_store.store(b"b", &KeyPackage(vec![2])).unwrap();
+        key_store
+            .store(b"list", &vec![KeyPackage(vec![3])])
+            .unwrap();
+        key_store
+            .store(b"c", &GroupState("group".to_string()))
+            .unwrap();
+
+        let mut keys = key_store
+            .list_keys(MlsEntityId::KeyPackage)
+            .unwrap()
+            .unwrap();
+        keys.sort();
+        assert_eq!(keys, vec![b"a".to_vec(), b"b".to_vec(), b"list".to_vec()]);
+
+        // Values of a different type are skipped.
+        let mut key_packages = key_store.list::<KeyPackage>().unwrap().unwrap();
+        key_packages.sort_by(|(a, _), (b, _)| a.cmp(b));
+        assert_eq!(
+            key_packages,
+            vec![
+                (b"a".to_vec(), KeyPackage(vec![1])),
+                (b"b".to_vec(), KeyPackage(vec![2]))
+            ]
+        );
+        assert_eq!(
+            key_store.list::<GroupState>().unwrap().unwrap(),
+            vec![(b"c".to_vec(), GroupState("group".to_string()))]
+        );
+
+        key_store.delete::<GroupState>(b"c").unwrap();
+        assert_eq!(
+            key_store.list_keys(MlsEntityId::GroupState),
+            Ok(Some(vec![]))
+        );
+    }
+
+    #[test]
+    fn persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("openmls.db");
+
+        let key_store = SqliteKeyStore::open(&path).unwrap();
+        key_store
+            .store(b"id", &GroupState("group".to_string()))
+            .unwrap();
+        drop(key_store);
+
+        let key_store = SqliteKeyStore::open(&path).unwrap();
+        assert_eq!(key_store.schema_version(), Ok(SCHEMA_VERSION));
+        assert_eq!(
+            key_store.read::<GroupState>(b"id"),
+            Some(GroupState("group".to_string()))
+        );
+    }
+
+    #[test]
+    fn transactions() {
+        let key_store = SqliteKeyStore::open_in_memory().unwrap();
+        key_store.store(b"id", &KeyPackage(vec![1])).unwrap();
+
+        // Rolled back changes are discarded.
+        key_store.begin_transaction().unwrap();
+        key_store.store(b"id", &KeyPackage(vec![2])).unwrap();
+        key_store.store(b"new", &KeyPackage(vec![3])).unwrap();
+        key_store.rollback_transaction().unwrap();
+        assert_eq!(key_store.read(b"id"), Some(KeyPackage(vec![1])));
+        assert_eq!(key_store.read::<KeyPackage>(b"new"), None);
+
+        // A committed nested transaction is rolled back with the enclosing
+        // transaction.
+        key_store.begin_transaction().unwrap();
+        key_store.delete::<KeyPackage>(b"id").unwrap();
+        key_store.begin_transaction().unwrap();
+        key_store.store(b"new", &KeyPackage(vec![3])).unwrap();
+        key_store.commit_transaction().unwrap();
+        key_store.rollback_transaction().unwrap();
+        assert_eq!(key_store.read(b"id"), Some(KeyPackage(vec![1])));
+        assert_eq!(key_store.read::<KeyPackage>(b"new"), None);
+
+        // Committed changes are kept.
+        key_store.begin_transaction().unwrap();
+        key_store.store(b"new", &KeyPackage(vec![3])).unwrap();
+        key_store.commit_transaction().unwrap();
+        assert_eq!(key_store.read(b"new"), Some(KeyPackage(vec![3])));
+
+        // There is no transaction left.
+        assert_eq!(
+            key_store.commit_transaction(),
+            Err(SqliteKeyStoreError::NoTransaction)
+        );
+        assert_eq!(
+            key_store.rollback_transaction(),
+            Err(SqliteKeyStoreError::NoTransaction)
+        );
+    }
+
+    #[test]
+    fn transactions_of_other_threads() {
+        let key_store = SqliteKeyStore::open_in_memory().unwrap();
+        key_store.begin_transaction().unwrap();
+        key_store.store(b"id", &KeyPackage(vec![1])).unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            let other_thread = scope.spawn(|| {
+                // Another thread can't end the transaction.
+                assert_eq!(
+                    key_store.commit_transaction(),
+                    Err(SqliteKeyStoreError::NoTransaction)
+                );
+                // Its operations wait until the transaction ended.
+                key_store.store(b"other", &KeyPackage(vec![2])).unwrap();
+                sender.send(()).unwrap();
+
+                key_store.begin_transaction().unwrap();
+                key_store.store(b"id", &KeyPackage(vec![3])).unwrap();
+                key_store.commit_transaction().unwrap();
+            });
+
+            assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+
+            // The write of the other thread is not part of the transaction.
+            key_store.rollback_transaction().unwrap();
+            receiver.recv().unwrap();
+            other_thread.join().unwrap();
+        });
+
+        assert_eq!(key_store.read(b"id"), Some(KeyPackage(vec![3])));
+        assert_eq!(key_store.read(b"other"), Some(KeyPackage(vec![2])));
+    }
+
+    #[test]
+    fn poisoned_lock() {
+        let key_store = SqliteKeyStore::open_in_memory().unwrap();
+        key_store.store(b"id", &KeyPackage(vec![1])).unwrap();
+
+        // A thread panics while holding the lock in a transaction.
+        thread::scope(|scope| {
+            let result = scope
+                .spawn(|| {
+                    key_store.begin_transaction().unwrap();
+                    key_store.store(b"id", &KeyPackage(vec![2])).unwrap();
+                    let _database = key_store.database();
+                    panic!("Panic while holding the lock.");
+                })
+                .join();
+            assert!(result.is_err());
+        });
+
+        // The transaction is rolled back and the key store can still be used.
+        assert_eq!(key_store.read(b"id"), Some(KeyPackage(vec![1])));
+        key_store.begin_transaction().unwrap();
+        key_store.store(b"id", &KeyPackage(vec![3])).unwrap();
+        key_store.commit_transaction().unwrap();
+        assert_eq!(key_store.read(b"id"), Some(KeyPackage(vec![3])));
+    }
+
+    #[test]
+    fn migrations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("openmls.db");
+
+        // A new database is migrated to the current schema.
+        let connection = Connection::open(&path).unwrap();
+        assert_eq!(schema_version(&connection), Ok(0));
+        let key_store = SqliteKeyStore::from_connection(connection).unwrap();
+        assert_eq!(key_store.schema_version(), Ok(SCHEMA_VERSION));
+        drop(key_store);
+
+        // A database from a newer version of the key store is rejected.
+        let connection = Connection::open(&path).unwrap();
+        connection
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        assert_eq!(
+            SqliteKeyStore::from_connection(connection).unwrap_err(),
+            SqliteKeyStoreError::UnsupportedSchemaVersion(SCHEMA_VERSION + 1)
+        );
+    }
+}
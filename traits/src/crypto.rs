@@ -6,7 +6,7 @@ use tls_codec::SecretVLBytes;
 
 use crate::types::{
     AeadType, Ciphersuite, CryptoError, ExporterSecret, HashType, HpkeCiphertext, HpkeConfig,
-    HpkeKeyPair, KemOutput, SignatureScheme,
+    HpkeKeyPair, KemOutput, SignatureScheme, SignatureVerification,
 };
 
 pub trait OpenMlsCrypto: Send + Sync {
@@ -89,6 +89,27 @@ pub trait OpenMlsCrypto: Send + Sync {
         signature: &[u8],
     ) -> Result<(), CryptoError>;
 
+    /// Verify a batch of signatures with the same [`SignatureScheme`].
+    ///
+    /// Returns an error if the [`SignatureScheme`] is not supported or the
+    /// verification of any of the signatures fails. The default
+    /// implementation verifies one signature at a time with
+    /// [`Self::verify_signature()`].
+    ///
+    /// Implementations must accept exactly the batches in which
+    /// [`Self::verify_signature()`] accepts every signature. Otherwise, members
+    /// that verify a signature on its own and members that verify it in a
+    /// batch could disagree on it.
+    fn batch_verify_signatures(
+        &self,
+        alg: SignatureScheme,
+        signatures: &[SignatureVerification<'_>],
+    ) -> Result<(), CryptoError> {
+        signatures.iter().try_for_each(|signature| {
+            self.verify_signature(alg, signature.data, signature.pk, signature.signature)
+        })
+    }
+
     /// Sign with the given parameters.
     ///
     /// Returns an error if the [`SignatureScheme`] is not supported or an error
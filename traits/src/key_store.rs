@@ -1,6 +1,10 @@
 //! # OpenMLS Key Store Trait
 
+#[cfg(feature = "async")]
+use std::future::Future;
+
 /// Sealed list of struct openmls manages (create/read/delete) through [OpenMlsKeyStore]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum MlsEntityId {
     SignatureKeyPair,
     HpkePrivateKey,
@@ -11,7 +15,9 @@ pub enum MlsEntityId {
 }
 
 /// To implement by any struct owned by openmls aiming to be persisted in [OpenMlsKeyStore]
-pub trait MlsEntity: serde::Serialize + serde::de::DeserializeOwned {
+pub trait MlsEntity:
+    serde::Serialize + serde::de::DeserializeOwned + Send + Sync + 'static
+{
     /// Identifier used to downcast the actual entity within an [OpenMlsKeyStore] method.
     /// In case for example you need to select a SQL table depending on the entity type
     const ID: MlsEntityId;
@@ -50,4 +56,206 @@ pub trait OpenMlsKeyStore: Send + Sync {
     ///
     /// Returns an error if storing fails.
     fn delete<V: MlsEntity>(&self, k: &[u8]) -> Result<(), Self::Error>;
+
+    /// Return the IDs of all values that are stored for the entity with the
+    /// given [`MlsEntityId`], in no particular order.
+    ///
+    /// The default implementation returns [`None`], i.e., the key store
+    /// doesn't support listing values. OpenMLS then doesn't remove any values
+    /// in `MlsGroup::collect_garbage()`.
+    ///
+    /// Returns an error if listing the IDs fails.
+    fn list_keys(&self, _entity_id: MlsEntityId) -> Result<Option<Vec<Vec<u8>>>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Return all values of type `V` with their IDs, in no particular order,
+    /// or [`None`] if the key store doesn't support listing values.
+    ///
+    /// Values that are stored for [`MlsEntity::ID`] of `V` but can't be
+    /// deserialized as `V`, e.g. a list of entities when a single entity is
+    /// listed, are skipped.
+    ///
+    /// Returns an error if listing the IDs fails.
+    #[allow(clippy::type_complexity)]
+    fn list<V: MlsEntity>(&self) -> Result<Option<Vec<(Vec<u8>, V)>>, Self::Error>
+    where
+        Self: Sized,
+    {
+        Ok(self.list_keys(V::ID)?.map(|keys| {
+            keys.into_iter()
+                .filter_map(|k| {
+                    let value = self.read(&k)?;
+                    Some((k, value))
+                })
+                .collect()
+        }))
+    }
+
+    /// Begin a transaction.
+    ///
+    /// All values that are stored or deleted until the transaction is
+    /// committed with [`Self::commit_transaction()`] or rolled back with
+    /// [`Self::rollback_transaction()`] are part of the transaction.
+    /// Transactions can be nested. A nested transaction that is committed
+    /// becomes part of the enclosing transaction.
+    ///
+    /// OpenMLS runs every operation that writes several values in a
+    /// transaction (see [`with_transaction()`]), such that a failed or
+    /// interrupted operation doesn't leave the key store in an inconsistent
+    /// state. Applications can wrap several operations, e.g. merging a commit
+    /// and saving the group, in a transaction of their own.
+    ///
+    /// The default implementation does nothing, i.e., every value is written
+    /// immediately.
+    ///
+    /// Returns an error if the transaction can't be started.
+    fn begin_transaction(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Commit the innermost transaction.
+    ///
+    /// Returns an error if there is no transaction or committing fails.
+    fn commit_transaction(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Roll back the innermost transaction, i.e., restore all values that
+    /// were stored or deleted since it began.
+    ///
+    /// Returns an error if there is no transaction or rolling back fails.
+    fn rollback_transaction(&self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Run `f` in a transaction of the `key_store`. The transaction is committed
+/// if `f` returns `Ok` and rolled back otherwise.
+///
+/// Returns the result of `f`, or an error if the transaction can't be begun,
+/// committed or rolled back.
+pub fn with_transaction<KeyStore: OpenMlsKeyStore, T, E>(
+    key_store: &KeyStore,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<Result<T, E>, KeyStore::Error> {
+    key_store.begin_transaction()?;
+    let result = f();
+    match result {
+        Ok(_) => key_store.commit_transaction()?,
+        Err(_) => key_store.rollback_transaction()?,
+    }
+    Ok(result)
+}
+
+/// The asynchronous counterpart of the [`OpenMlsKeyStore`] trait, for key
+/// stores that are backed by asynchronous I/O.
+///
+/// Every [`OpenMlsKeyStore`] is an [`AsyncOpenMlsKeyStore`] whose futures are
+/// ready immediately.
+#[cfg(feature = "async")]
+pub trait AsyncOpenMlsKeyStore: Send + Sync {
+    /// The error type returned by the [`AsyncOpenMlsKeyStore`].
+    type Error: std::error::Error + std::fmt::Debug + PartialEq + Send;
+
+    /// Store a value `v` that implements the [`MlsEntity`] trait for
+    /// serialization for ID `k`.
+    ///
+    /// Returns an error if storing fails.
+    fn store<V: MlsEntity>(
+        &self,
+        k: &[u8],
+        v: &V,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Read and return a value stored for ID `k` that implements the
+    /// [`MlsEntity`] trait for deserialization.
+    ///
+    /// Returns [`None`] if no value is stored for `k` or reading fails.
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> impl Future<Output = Option<V>> + Send;
+
+    /// Delete a value stored for ID `k`.
+    ///
+    /// Returns an error if storing fails.
+    fn delete<V: MlsEntity>(
+        &self,
+        k: &[u8],
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Return the IDs of all values that are stored for the entity with the
+    /// given [`MlsEntityId`], in no particular order. See
+    /// [`OpenMlsKeyStore::list_keys()`] for more details.
+    fn list_keys(
+        &self,
+        _entity_id: MlsEntityId,
+    ) -> impl Future<Output = Result<Option<Vec<Vec<u8>>>, Self::Error>> + Send {
+        std::future::ready(Ok(None))
+    }
+
+    /// Begin a transaction. See [`OpenMlsKeyStore::begin_transaction()`] for
+    /// more details.
+    fn begin_transaction(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        std::future::ready(Ok(()))
+    }
+
+    /// Commit the innermost transaction.
+    ///
+    /// Returns an error if there is no transaction or committing fails.
+    fn commit_transaction(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        std::future::ready(Ok(()))
+    }
+
+    /// Roll back the innermost transaction, i.e., restore all values that
+    /// were stored or deleted since it began.
+    ///
+    /// Returns an error if there is no transaction or rolling back fails.
+    fn rollback_transaction(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        std::future::ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<KeyStore: OpenMlsKeyStore> AsyncOpenMlsKeyStore for KeyStore
+where
+    KeyStore::Error: Send,
+{
+    type Error = KeyStore::Error;
+
+    fn store<V: MlsEntity>(
+        &self,
+        k: &[u8],
+        v: &V,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        std::future::ready(OpenMlsKeyStore::store(self, k, v))
+    }
+
+    fn read<V: MlsEntity>(&self, k: &[u8]) -> impl Future<Output = Option<V>> + Send {
+        std::future::ready(OpenMlsKeyStore::read(self, k))
+    }
+
+    fn delete<V: MlsEntity>(
+        &self,
+        k: &[u8],
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        std::future::ready(OpenMlsKeyStore::delete::<V>(self, k))
+    }
+
+    fn list_keys(
+        &self,
+        entity_id: MlsEntityId,
+    ) -> impl Future<Output = Result<Option<Vec<Vec<u8>>>, Self::Error>> + Send {
+        std::future::ready(OpenMlsKeyStore::list_keys(self, entity_id))
+    }
+
+    fn begin_transaction(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        std::future::ready(OpenMlsKeyStore::begin_transaction(self))
+    }
+
+    fn commit_transaction(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        std::future::ready(OpenMlsKeyStore::commit_transaction(self))
+    }
+
+    fn rollback_transaction(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        std::future::ready(OpenMlsKeyStore::rollback_transaction(self))
+    }
 }
@@ -10,3 +10,68 @@ pub trait Signer {
     /// The [`SignatureScheme`] of this signer.
     fn signature_scheme(&self) -> SignatureScheme;
 }
+
+/// Signers behind references and smart pointers, including trait objects, are
+/// signers as well, e.g., to pick a hardware-backed or in-memory signer at
+/// runtime.
+impl<S: Signer + ?Sized> Signer for &S {
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        (**self).sign(payload)
+    }
+
+    fn signature_scheme(&self) -> SignatureScheme {
+        (**self).signature_scheme()
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for Box<S> {
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        (**self).sign(payload)
+    }
+
+    fn signature_scheme(&self) -> SignatureScheme {
+        (**self).signature_scheme()
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for std::sync::Arc<S> {
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        (**self).sign(payload)
+    }
+
+    fn signature_scheme(&self) -> SignatureScheme {
+        (**self).signature_scheme()
+    }
+}
+
+/// The asynchronous counterpart of the [`Signer`] trait, e.g., for signature
+/// keys that are held by a remote HSM.
+///
+/// Every [`Signer`] is an [`AsyncSigner`] whose futures are ready immediately.
+#[cfg(feature = "async")]
+pub trait AsyncSigner: Send + Sync {
+    /// Sign the provided payload.
+    ///
+    /// Returns a signature on success or an Error.
+    fn sign(
+        &self,
+        payload: &[u8],
+    ) -> impl std::future::Future<Output = Result<Vec<u8>, Error>> + Send;
+
+    /// The [`SignatureScheme`] of this signer.
+    fn signature_scheme(&self) -> SignatureScheme;
+}
+
+#[cfg(feature = "async")]
+impl<S: Signer + Send + Sync> AsyncSigner for S {
+    fn sign(
+        &self,
+        payload: &[u8],
+    ) -> impl std::future::Future<Output = Result<Vec<u8>, Error>> + Send {
+        std::future::ready(Signer::sign(self, payload))
+    }
+
+    fn signature_scheme(&self) -> SignatureScheme {
+        Signer::signature_scheme(self)
+    }
+}
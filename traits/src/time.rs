@@ -0,0 +1,58 @@
+//! # Time Source for OpenMLS
+//!
+//! The [`OpenMlsTime`] trait defines the functionality required by OpenMLS to
+//! read the current time, e.g. when creating or validating lifetimes.
+//!
+//! [`SystemClock`] is the default implementation and reads the system time.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub trait OpenMlsTime: Send + Sync {
+    /// The current time, measured in seconds since the Unix epoch
+    /// (1970-01-01T00:00:00Z).
+    fn now(&self) -> u64;
+
+    /// The amount of time (in seconds) by which the local clock may deviate
+    /// from the clocks of other clients.
+    ///
+    /// Lifetimes are accepted this many seconds before their `not_before` and
+    /// after their `not_after` timestamp.
+    fn clock_skew_tolerance(&self) -> u64 {
+        0
+    }
+}
+
+/// A time source that reads the system clock.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SystemClock {
+    clock_skew_tolerance: u64,
+}
+
+impl SystemClock {
+    /// Create a new system clock without clock skew tolerance.
+    pub const fn new() -> Self {
+        Self {
+            clock_skew_tolerance: 0,
+        }
+    }
+
+    /// Set the clock skew tolerance (in seconds).
+    pub const fn with_clock_skew_tolerance(mut self, clock_skew_tolerance: u64) -> Self {
+        self.clock_skew_tolerance = clock_skew_tolerance;
+        self
+    }
+}
+
+impl OpenMlsTime for SystemClock {
+    fn now(&self) -> u64 {
+        // A system time before the Unix epoch is treated as the epoch itself.
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0)
+    }
+
+    fn clock_skew_tolerance(&self) -> u64 {
+        self.clock_skew_tolerance
+    }
+}
@@ -7,12 +7,18 @@ pub mod crypto;
 pub mod key_store;
 pub mod random;
 pub mod signatures;
+pub mod time;
 pub mod types;
 
+/// The time source used by providers that don't override
+/// [`OpenMlsProvider::time`].
+static DEFAULT_CLOCK: time::SystemClock = time::SystemClock::new();
+
 /// The OpenMLS Crypto Provider Trait
 ///
 /// An implementation of this trait must be passed in to the public OpenMLS API
-/// to perform randomness generation, cryptographic operations, and key storage.
+/// to perform randomness generation, cryptographic operations, key storage,
+/// and to read the current time.
 pub trait OpenMlsProvider: Send + Sync {
     type CryptoProvider: crypto::OpenMlsCrypto;
     type RandProvider: random::OpenMlsRand;
@@ -26,4 +32,66 @@ pub trait OpenMlsProvider: Send + Sync {
 
     /// Get the key store provider.
     fn key_store(&self) -> &Self::KeyStoreProvider;
+
+    /// Get the time source.
+    ///
+    /// Defaults to the [`SystemClock`](time::SystemClock) without clock skew
+    /// tolerance.
+    fn time(&self) -> &dyn time::OpenMlsTime {
+        &DEFAULT_CLOCK
+    }
+}
+
+/// The asynchronous counterpart of the [`OpenMlsProvider`] trait, whose key
+/// store is an [`AsyncOpenMlsKeyStore`](key_store::AsyncOpenMlsKeyStore).
+///
+/// Every [`OpenMlsProvider`] is an [`AsyncOpenMlsProvider`].
+#[cfg(feature = "async")]
+pub trait AsyncOpenMlsProvider: Send + Sync {
+    type CryptoProvider: crypto::OpenMlsCrypto;
+    type RandProvider: random::OpenMlsRand;
+    type KeyStoreProvider: key_store::AsyncOpenMlsKeyStore;
+
+    /// Get the crypto provider.
+    fn crypto(&self) -> &Self::CryptoProvider;
+
+    /// Get the randomness provider.
+    fn rand(&self) -> &Self::RandProvider;
+
+    /// Get the key store provider.
+    fn key_store(&self) -> &Self::KeyStoreProvider;
+
+    /// Get the time source.
+    ///
+    /// Defaults to the [`SystemClock`](time::SystemClock) without clock skew
+    /// tolerance.
+    fn time(&self) -> &dyn time::OpenMlsTime {
+        &DEFAULT_CLOCK
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Provider: OpenMlsProvider> AsyncOpenMlsProvider for Provider
+where
+    <Provider::KeyStoreProvider as key_store::OpenMlsKeyStore>::Error: Send,
+{
+    type CryptoProvider = Provider::CryptoProvider;
+    type RandProvider = Provider::RandProvider;
+    type KeyStoreProvider = Provider::KeyStoreProvider;
+
+    fn crypto(&self) -> &Self::CryptoProvider {
+        OpenMlsProvider::crypto(self)
+    }
+
+    fn rand(&self) -> &Self::RandProvider {
+        OpenMlsProvider::rand(self)
+    }
+
+    fn key_store(&self) -> &Self::KeyStoreProvider {
+        OpenMlsProvider::key_store(self)
+    }
+
+    fn time(&self) -> &dyn time::OpenMlsTime {
+        OpenMlsProvider::time(self)
+    }
 }